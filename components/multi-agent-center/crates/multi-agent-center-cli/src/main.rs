@@ -1,23 +1,30 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::File;
 use std::io::{BufWriter, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use clap::{Args, Parser, Subcommand};
-use memory_kernel_outcome_core::RetrievalMode;
 use multi_agent_center_domain::{
-    ContextPackageEnvelope, NormalizedWorkflow, NormalizedWorkflowEnvelope, RunId,
+    diff_golden_runs, now_utc, parse_prompt_ref, verify_run_manifest, ApprovalDelegation,
+    DeciderAuthMethod, DecisionWindow, DeferredGateResolution, EventQuery, GoldenRun,
+    NormalizedWorkflowEnvelope, PayloadFilter, PromptTemplateRecord, RunId, StepRerun,
+    TraceArtifactClass, TraceEventType, GATE_SIGNATURE_HEADER, GOLDEN_RUN_VERSION,
 };
 use multi_agent_center_orchestrator::{
-    AllowAllTrustGateSource, ApiMemoryKernelContextSource, DefaultHumanGateDecider,
-    HumanGateDecider, HumanGateRequest, HumanGateResponse, NoopProposedWriteApplier, Orchestrator,
-    OutcomeMemoryTrustGateSource, RunConfig, StaticContextPackageSource,
+    benchmark_trust_gate, load_workflow_snapshot, record_golden_run, workflow_upgrade_impact,
+    AllowAllTrustGateSource, ApiMemoryKernelContextSource, CachingContextPackageSource,
+    CancellationToken, ContextPackageSource, DefaultHumanGateDecider, GateNotifier,
+    GoldenHumanGateDecider, GoldenResponseSource, HumanGateDecider, HumanGateRequest,
+    HumanGateResponse, NoopProposedWriteApplier, Orchestrator, OutcomeMemoryEventSink,
+    RecordedContextPackageSource, RuleResponseScorer, RunConfig, RunExecutionSummary,
+    StaticContextPackageSource, TrustGateBenchmarkConfig, TrustGateSelection, TrustGateSource,
 };
+use multi_agent_center_provider::ProviderRegistry;
 use multi_agent_center_trace_core::TraceStore;
-use multi_agent_center_trace_sqlite::SqliteTraceStore;
-use multi_agent_center_workflow::load_workflow_from_path;
+use multi_agent_center_trace_sqlite::{BufferedSqliteTraceStore, SqliteTraceStore};
+use multi_agent_center_workflow::{diff_workflows, load_workflow_from_path};
 use serde_json::json;
 use time::OffsetDateTime;
 use ulid::Ulid;
@@ -36,14 +43,24 @@ enum Commands {
     Trace(TraceArgs),
     Replay(ReplayArgs),
     Export(ExportArgs),
+    Prompts(PromptsArgs),
+    /// Human gates non-interactive runs deferred, and their resolution.
+    Gates(GatesArgs),
+    UpgradeImpact(UpgradeImpactArgs),
+    /// Measure per-step trust gating overhead against a synthetic `OutcomeMemory` database.
+    BenchTrustGate(BenchTrustGateArgs),
 }
 
 #[derive(Debug, Args)]
+#[allow(clippy::struct_excessive_bools)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct RunArgs {
-    #[arg(long)]
-    workflow: PathBuf,
-    #[arg(long)]
-    trace_db: PathBuf,
+    #[command(subcommand)]
+    command: Option<RunSubcommand>,
+    #[arg(long, required = true)]
+    workflow: Option<PathBuf>,
+    #[arg(long, required = true)]
+    trace_db: Option<PathBuf>,
     #[arg(long)]
     memory_db: Option<PathBuf>,
     #[arg(long)]
@@ -54,12 +71,96 @@ struct RunArgs {
     external_correlation_id: Option<String>,
     #[arg(long, default_value_t = false)]
     non_interactive: bool,
+    /// Trust gate: `allow-all` or `outcome-memory`; defaults to `outcome-memory` exactly when
+    /// `--trust-db` is given.
+    #[arg(long)]
+    trust_source: Option<String>,
     #[arg(long)]
     trust_db: Option<PathBuf>,
     #[arg(long, default_value = "safe")]
     trust_mode: String,
     #[arg(long, default_value_t = false)]
     apply_proposed_writes: bool,
+    #[arg(long, default_value_t = false)]
+    context_cache: bool,
+    #[arg(long)]
+    context_cache_db: Option<PathBuf>,
+    #[arg(long, default_value_t = false)]
+    speculative: bool,
+    #[arg(long, default_value_t = false, requires = "external_correlation_id")]
+    reuse_correlated_run: bool,
+    /// Refuse to run unless a trust gate source is configured.
+    #[arg(long, default_value_t = false)]
+    require_trust_gating: bool,
+    /// Fail steps whose agent sets `fail_on_permission_prune` when permissions prune context.
+    #[arg(long, default_value_t = false)]
+    enforce_permission_prune: bool,
+    /// Reuse outputs of succeeded steps from earlier runs of the same workflow hash whose
+    /// run-independent request key matches, skipping their provider calls.
+    #[arg(long, default_value_t = false)]
+    memoize: bool,
+    /// Queue trace writes in memory and commit them in batches from a background thread; the
+    /// run is flushed to disk before it is reported as finished.
+    #[arg(long, default_value_t = false)]
+    buffer_trace_writes: bool,
+    /// Trace artifact class (`events`, `context_packages`, `provider_calls`, `step_checkpoints`)
+    /// whose rows may stay queued until the run finishes instead of being committed per write;
+    /// repeatable. Gate decisions and manifests are always committed synchronously.
+    #[arg(
+        long = "relaxed-trace-artifact",
+        value_parser = parse_trace_artifact_class,
+        requires = "buffer_trace_writes"
+    )]
+    relaxed_trace_artifacts: Vec<TraceArtifactClass>,
+    /// Serialize trace writes with other processes writing runs to the same `--trace-db`
+    /// through an advisory lock on `<trace-db>.writer-lock`.
+    #[arg(long, default_value_t = false, conflicts_with = "buffer_trace_writes")]
+    coordinate_trace_writes: bool,
+    /// Run label as `key=value`; repeatable.
+    #[arg(long = "label", value_parser = parse_label)]
+    labels: Vec<(String, String)>,
+}
+
+#[derive(Debug, Subcommand)]
+enum RunSubcommand {
+    /// Re-execute one step of a recorded run and everything downstream of it in a new run,
+    /// reusing the recorded outputs of the other steps.
+    RerunStep(Box<RerunStepArgs>),
+    /// Continue a crashed, failed, or partially completed run: failed and interrupted steps,
+    /// steps whose deferred gates are resolved, and the steps behind them run again.
+    Resume(Box<ResumeArgs>),
+}
+
+#[derive(Debug, Args)]
+struct ResumeArgs {
+    run_id: String,
+    #[arg(long)]
+    trace_db: PathBuf,
+    #[arg(long)]
+    memory_db: Option<PathBuf>,
+    /// Trust gate: `allow-all` or `outcome-memory`; defaults to `outcome-memory` exactly when
+    /// `--trust-db` is given.
+    #[arg(long)]
+    trust_source: Option<String>,
+    #[arg(long)]
+    trust_db: Option<PathBuf>,
+    #[arg(long, default_value = "safe")]
+    trust_mode: String,
+    #[arg(long, default_value_t = false)]
+    non_interactive: bool,
+}
+
+#[derive(Debug, Args)]
+struct RerunStepArgs {
+    run_id: String,
+    step_key: String,
+    #[arg(long)]
+    trace_db: PathBuf,
+    /// Workflow to run instead of the source run's snapshot, e.g. with the step's prompt fixed.
+    #[arg(long)]
+    workflow: Option<PathBuf>,
+    #[arg(long, default_value_t = false)]
+    non_interactive: bool,
 }
 
 #[derive(Debug, Args)]
@@ -70,6 +171,34 @@ struct ReplayArgs {
     run_id: String,
     #[arg(long, default_value_t = false)]
     rerun_provider: bool,
+    /// Re-execute the run with every provider call answered from its recorded calls, and fail
+    /// when the replay diverges from the run.
+    #[arg(long, default_value_t = false, conflicts_with = "rerun_provider")]
+    execute: bool,
+}
+
+#[derive(Debug, Args)]
+struct UpgradeImpactArgs {
+    #[arg(long)]
+    old_workflow: PathBuf,
+    #[arg(long)]
+    new_workflow: PathBuf,
+    #[arg(long)]
+    trace_db: PathBuf,
+    #[arg(long, default_value_t = 20)]
+    recent_runs: usize,
+}
+
+#[derive(Debug, Args)]
+struct BenchTrustGateArgs {
+    #[arg(long, default_value_t = 10_000)]
+    trust_rows: usize,
+    #[arg(long, default_value_t = 200)]
+    candidates: usize,
+    #[arg(long, default_value_t = 20)]
+    iterations: usize,
+    #[arg(long, default_value = "safe")]
+    trust_mode: String,
 }
 
 #[derive(Debug, Args)]
@@ -93,12 +222,251 @@ enum TraceSubcommand {
     Runs {
         #[arg(long)]
         trace_db: PathBuf,
+        /// Only list runs carrying this `key=value` label; repeatable, all must match.
+        #[arg(long = "label", value_parser = parse_label)]
+        labels: Vec<(String, String)>,
+    },
+    Events(TraceEventsArgs),
+    Progress {
+        #[arg(long)]
+        trace_db: PathBuf,
+        #[arg(long)]
+        run_id: String,
+    },
+    /// Print a run's stored manifest after checking it against the recorded manifest hash.
+    Manifest {
+        #[arg(long)]
+        trace_db: PathBuf,
+        #[arg(long)]
+        run_id: String,
+    },
+    Usage {
+        #[arg(long)]
+        trace_db: PathBuf,
+        #[arg(long, conflicts_with = "workflow")]
+        run_id: Option<String>,
+        #[arg(long)]
+        workflow: Option<String>,
+    },
+    /// Reason codes behind rejected and pruned gate decisions, by workflow and gate kind.
+    ReasonCodes {
+        #[arg(long)]
+        trace_db: PathBuf,
+        /// Inclusive lower bound on decision time (RFC3339).
+        #[arg(long)]
+        since: Option<String>,
+        /// Exclusive upper bound on decision time (RFC3339).
+        #[arg(long)]
+        until: Option<String>,
+    },
+    Workflows {
+        #[command(subcommand)]
+        command: TraceWorkflowsSubcommand,
+    },
+    Warnings {
+        #[command(subcommand)]
+        command: TraceWarningsSubcommand,
+    },
+    RecordGolden {
+        #[arg(long)]
+        trace_db: PathBuf,
+        #[arg(long)]
+        run_id: String,
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    CheckGolden(CheckGoldenArgs),
+}
+
+#[derive(Debug, Args)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct TraceEventsArgs {
+    #[command(subcommand)]
+    command: Option<TraceEventsSubcommand>,
+    #[arg(long, required = true)]
+    trace_db: Option<PathBuf>,
+    #[arg(long, required = true)]
+    run_id: Option<String>,
+}
+
+#[derive(Debug, Subcommand)]
+enum TraceEventsSubcommand {
+    /// Search events across runs by type, actor, time, and payload values.
+    Query(Box<TraceEventsQueryArgs>),
+}
+
+#[derive(Debug, Args)]
+struct TraceEventsQueryArgs {
+    #[arg(long)]
+    trace_db: PathBuf,
+    #[arg(long)]
+    run_id: Option<String>,
+    /// Event type such as `warning` or `gate_evaluated`; repeatable, any may match.
+    #[arg(long = "event-type", value_parser = parse_event_type)]
+    event_types: Vec<TraceEventType>,
+    #[arg(long)]
+    actor_type: Option<String>,
+    #[arg(long)]
+    actor_id: Option<String>,
+    /// Inclusive lower bound on `occurred_at` (RFC3339).
+    #[arg(long)]
+    since: Option<String>,
+    /// Exclusive upper bound on `occurred_at` (RFC3339).
+    #[arg(long)]
+    until: Option<String>,
+    /// Payload filter such as `payload.warning_code == "context_pruned"` (`==` or `!=`, JSON
+    /// literal on the right); repeatable, all must match.
+    #[arg(long = "where", value_parser = parse_payload_filter)]
+    payload: Vec<PayloadFilter>,
+    #[arg(long)]
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Args)]
+struct CheckGoldenArgs {
+    #[arg(long)]
+    workflow: PathBuf,
+    #[arg(long)]
+    golden: PathBuf,
+    #[arg(long)]
+    trace_db: PathBuf,
+    #[arg(long)]
+    memory_db: Option<PathBuf>,
+    /// Trust gate: `allow-all` or `outcome-memory`; defaults to `outcome-memory` exactly when
+    /// `--trust-db` is given.
+    #[arg(long)]
+    trust_source: Option<String>,
+    #[arg(long)]
+    trust_db: Option<PathBuf>,
+    #[arg(long, default_value = "safe")]
+    trust_mode: String,
+}
+
+#[derive(Debug, Args)]
+struct PromptsArgs {
+    #[command(subcommand)]
+    command: PromptsSubcommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum PromptsSubcommand {
+    Add {
+        #[arg(long)]
+        trace_db: PathBuf,
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        version: String,
+        #[arg(long)]
+        file: PathBuf,
     },
-    Events {
+    List {
+        #[arg(long)]
+        trace_db: PathBuf,
+    },
+    Show {
+        #[arg(long)]
+        trace_db: PathBuf,
+        #[arg(long)]
+        prompt_ref: String,
+    },
+}
+
+#[derive(Debug, Args)]
+struct GatesArgs {
+    #[command(subcommand)]
+    command: GatesSubcommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum GatesSubcommand {
+    List {
+        #[arg(long)]
+        trace_db: PathBuf,
+        #[arg(long)]
+        run_id: Option<String>,
+        /// Only list gates nobody has resolved yet.
+        #[arg(long, default_value_t = false)]
+        pending: bool,
+    },
+    /// Decide a deferred gate; `run resume` of its run, or `run rerun-step` of its step, then
+    /// applies the decision.
+    Resolve {
         #[arg(long)]
         trace_db: PathBuf,
         #[arg(long)]
         run_id: String,
+        #[arg(long)]
+        step_key: String,
+        #[arg(long)]
+        gate: String,
+        #[arg(long, required_unless_present = "reject", conflicts_with = "reject")]
+        approve: bool,
+        #[arg(long)]
+        reject: bool,
+        #[arg(long)]
+        notes: Option<String>,
+        /// Recorded decider; defaults to `$USER`. The name is not authenticated, so gates with
+        /// `approvers` must be resolved through the service instead.
+        #[arg(long)]
+        decided_by: Option<String>,
+        /// Approver the decider acts for; needs a delegation from them (`gates delegate`).
+        #[arg(long)]
+        on_behalf_of: Option<String>,
+    },
+    /// Let `--delegate` resolve deferred gates on behalf of the approver `--delegator`.
+    Delegate {
+        #[arg(long)]
+        trace_db: PathBuf,
+        #[arg(long)]
+        delegator: String,
+        #[arg(long)]
+        delegate: String,
+        /// Limit the delegation to one gate name.
+        #[arg(long)]
+        gate: Option<String>,
+        /// End of the delegation (RFC3339); open-ended when omitted.
+        #[arg(long)]
+        expires_at: Option<String>,
+    },
+    /// Print approval delegations as JSON lines.
+    Delegations {
+        #[arg(long)]
+        trace_db: PathBuf,
+    },
+    RevokeDelegation {
+        #[arg(long)]
+        trace_db: PathBuf,
+        delegation_id: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum TraceWarningsSubcommand {
+    /// Most frequent warning codes across runs, by workflow.
+    Top {
+        #[arg(long)]
+        trace_db: PathBuf,
+        #[arg(long)]
+        workflow: Option<String>,
+        /// Inclusive lower bound on event time (RFC3339).
+        #[arg(long)]
+        since: Option<String>,
+        /// Exclusive upper bound on event time (RFC3339).
+        #[arg(long)]
+        until: Option<String>,
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum TraceWorkflowsSubcommand {
+    Timings {
+        #[arg(long)]
+        trace_db: PathBuf,
+        #[arg(long)]
+        workflow_hash: Option<String>,
     },
 }
 
@@ -106,21 +474,70 @@ fn main() -> Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
+        Commands::Run(RunArgs {
+            command: Some(RunSubcommand::RerunStep(args)),
+            ..
+        }) => rerun_step_command(&args),
+        Commands::Run(RunArgs {
+            command: Some(RunSubcommand::Resume(args)),
+            ..
+        }) => resume_command(&args),
         Commands::Run(args) => run_command(args),
         Commands::Trace(args) => trace_command(args),
         Commands::Replay(args) => replay_command(&args),
         Commands::Export(args) => export_command(&args),
+        Commands::Prompts(args) => prompts_command(args),
+        Commands::Gates(args) => gates_command(args),
+        Commands::UpgradeImpact(args) => upgrade_impact_command(&args),
+        Commands::BenchTrustGate(args) => bench_trust_gate_command(&args),
     }
 }
 
+fn execute_with_trust_source(
+    trace_store: &dyn TraceStore,
+    context_source: &dyn ContextPackageSource,
+    trust_source: &dyn TrustGateSource,
+    outcome_db: Option<&Path>,
+    workflow: &NormalizedWorkflowEnvelope,
+    config: RunConfig,
+) -> Result<RunExecutionSummary> {
+    let human_gate = CliHumanGateDecider;
+    let write_applier = NoopProposedWriteApplier;
+    let scorer = RuleResponseScorer;
+    let outcome_sink = outcome_db.map(OutcomeMemoryEventSink::new);
+    let gate_notifier = WebhookGateNotifier::new(GATE_NOTIFY_TIMEOUT);
+    let mut orchestrator = Orchestrator::new(
+        trace_store,
+        context_source,
+        trust_source,
+        &human_gate,
+        &write_applier,
+        ProviderRegistry::default(),
+    )
+    .with_response_scorer(&scorer)
+    .with_gate_notifier(&gate_notifier);
+    if let Some(outcome_sink) = &outcome_sink {
+        orchestrator = orchestrator.with_outcome_sink(outcome_sink);
+    }
+    orchestrator.execute_workflow(workflow, config)
+}
+
 fn run_command(args: RunArgs) -> Result<()> {
-    let workflow = load_workflow_from_path(&args.workflow)?;
-    let trace_store = SqliteTraceStore::open(&args.trace_db)?;
+    let (Some(workflow_path), Some(trace_db)) = (args.workflow.as_ref(), args.trace_db.as_ref())
+    else {
+        return Err(anyhow!("run requires --workflow and --trace-db"));
+    };
+    let workflow = load_workflow_from_path(workflow_path)?;
+    let trace_store: Box<dyn TraceStore> = if args.buffer_trace_writes {
+        Box::new(BufferedSqliteTraceStore::open(trace_db)?)
+    } else if args.coordinate_trace_writes {
+        Box::new(SqliteTraceStore::open_coordinated(trace_db)?)
+    } else {
+        Box::new(SqliteTraceStore::open(trace_db)?)
+    };
     trace_store.migrate()?;
 
     let context_source = StaticContextPackageSource::default();
-    let human_gate = CliHumanGateDecider;
-    let write_applier = NoopProposedWriteApplier;
     let memory_db_opt = args.memory_db.clone();
     let trust_db_opt = args.trust_db.clone();
 
@@ -128,7 +545,12 @@ fn run_command(args: RunArgs) -> Result<()> {
 
     let as_of = args.as_of.as_deref().map(parse_rfc3339).transpose()?;
 
-    let trust_mode = parse_retrieval_mode(&args.trust_mode)?;
+    let trust_gate = TrustGateSelection::from_flags(
+        args.trust_source.as_deref(),
+        trust_db_opt.as_deref(),
+        &args.trust_mode,
+    )?;
+    let trust_source = trust_gate.open(Some(&workflow.normalized_workflow.workflow_name))?;
 
     let config = RunConfig {
         run_id,
@@ -141,88 +563,484 @@ fn run_command(args: RunArgs) -> Result<()> {
             "trace_db": args.trace_db,
             "memory_db": memory_db_opt,
             "non_interactive": args.non_interactive,
+            "trust_source": trust_gate.as_str(),
             "trust_mode": args.trust_mode,
             "trust_db": trust_db_opt,
             "apply_proposed_writes": args.apply_proposed_writes,
+            "context_cache": args.context_cache,
+            "context_cache_db": args.context_cache_db,
+            "speculative": args.speculative,
+            "reuse_correlated_run": args.reuse_correlated_run,
+            "require_trust_gating": args.require_trust_gating,
+            "enforce_permission_prune": args.enforce_permission_prune,
+            "memoize": args.memoize,
+            "buffer_trace_writes": args.buffer_trace_writes,
+            "coordinate_trace_writes": args.coordinate_trace_writes,
+            "relaxed_trace_artifacts": args
+                .relaxed_trace_artifacts
+                .iter()
+                .map(|class| class.as_str())
+                .collect::<Vec<_>>(),
         }),
+        labels: args.labels.into_iter().collect(),
         engine_version: "multi-agent-center.v0".to_string(),
         apply_proposed_writes: args.apply_proposed_writes,
+        speculative_execution: args.speculative,
+        reuse_correlated_run: args.reuse_correlated_run,
+        require_trust_gating: args.require_trust_gating,
+        memoize_steps: args.memoize,
+        relaxed_trace_artifacts: args.relaxed_trace_artifacts.iter().copied().collect(),
+        enforce_permission_prune: args.enforce_permission_prune,
+        ..RunConfig::default()
     };
 
     let summary = if let Some(memory_db) = memory_db_opt.as_ref() {
-        let context_source = ApiMemoryKernelContextSource::new(memory_db);
-        if let Some(trust_db) = trust_db_opt.as_ref() {
-            let trust_source = OutcomeMemoryTrustGateSource::new(trust_db, trust_mode);
-            Orchestrator::new(
-                &trace_store,
-                &context_source,
-                &trust_source,
-                &human_gate,
-                &write_applier,
-            )
-            .execute_workflow(&workflow, config)?
-        } else {
-            let trust_source = AllowAllTrustGateSource;
-            Orchestrator::new(
-                &trace_store,
-                &context_source,
-                &trust_source,
-                &human_gate,
-                &write_applier,
-            )
-            .execute_workflow(&workflow, config)?
-        }
-    } else if let Some(trust_db) = trust_db_opt.as_ref() {
-        let trust_source = OutcomeMemoryTrustGateSource::new(trust_db, trust_mode);
-        Orchestrator::new(
-            &trace_store,
-            &context_source,
-            &trust_source,
-            &human_gate,
-            &write_applier,
-        )
-        .execute_workflow(&workflow, config)?
+        let api_source = ApiMemoryKernelContextSource::new(memory_db);
+        let cached_source;
+        let context_source: &dyn ContextPackageSource =
+            if args.context_cache || args.context_cache_db.is_some() {
+                let mut cached = CachingContextPackageSource::new(api_source.clone());
+                if let Some(cache_db) = args.context_cache_db.as_ref() {
+                    cached = cached.with_sqlite(cache_db)?;
+                }
+                cached_source = cached;
+                &cached_source
+            } else {
+                &api_source
+            };
+        execute_with_trust_source(
+            trace_store.as_ref(),
+            context_source,
+            trust_source.as_ref(),
+            trust_gate.outcome_db(),
+            &workflow,
+            config,
+        )?
     } else {
-        let trust_source = AllowAllTrustGateSource;
-        Orchestrator::new(
-            &trace_store,
+        execute_with_trust_source(
+            trace_store.as_ref(),
             &context_source,
-            &trust_source,
-            &human_gate,
-            &write_applier,
-        )
-        .execute_workflow(&workflow, config)?
+            trust_source.as_ref(),
+            trust_gate.outcome_db(),
+            &workflow,
+            config,
+        )?
     };
 
+    print_run_summary(&summary);
+    Ok(())
+}
+
+fn print_run_summary(summary: &RunExecutionSummary) {
     println!(
-        "run_id={} status={} steps_total={} steps_succeeded={} steps_failed_or_rejected={}",
+        "run_id={} status={} steps_total={} steps_succeeded={} steps_failed_or_rejected={} \
+         steps_deferred={} trust_excluded={} human_approvals={} human_rejections={} human_deferrals={} \
+         policy_prunes={} proposed_writes={} provider_calls={} input_tokens={} output_tokens={} wall_time_ms={} \
+         warnings={} reused_existing_run={}",
         summary.run_id,
         format_run_status(&summary.status),
         summary.steps_total,
         summary.steps_succeeded,
-        summary.steps_failed_or_rejected
+        summary.steps_failed_or_rejected,
+        summary.steps_deferred,
+        summary.trust_excluded,
+        summary.human_approvals,
+        summary.human_rejections,
+        summary.human_deferrals,
+        summary.policy_prunes,
+        summary.proposed_writes,
+        summary.provider_calls,
+        summary.usage.input_tokens,
+        summary.usage.output_tokens,
+        summary.usage.wall_time_ms,
+        format_warning_counts(&summary.warnings),
+        summary.reused_existing_run
     );
+}
 
-    Ok(())
+/// `code:count` pairs joined by commas, or `none`.
+fn format_warning_counts(warnings: &BTreeMap<String, usize>) -> String {
+    if warnings.is_empty() {
+        return "none".to_string();
+    }
+    warnings
+        .iter()
+        .map(|(code, count)| format!("{code}:{count}"))
+        .collect::<Vec<_>>()
+        .join(",")
 }
 
 fn trace_command(args: TraceArgs) -> Result<()> {
     match args.command {
-        TraceSubcommand::Runs { trace_db } => {
+        TraceSubcommand::Runs { trace_db, labels } => {
             let trace_store = SqliteTraceStore::open(&trace_db)?;
-            let runs = trace_store.list_runs()?;
+            trace_store.migrate()?;
+            let runs = trace_store.list_runs_with_labels(&labels.into_iter().collect())?;
             for run in runs {
                 println!("{}", serde_json::to_string(&run)?);
             }
         }
-        TraceSubcommand::Events { trace_db, run_id } => {
+        TraceSubcommand::Events(args) => events_command(args)?,
+        TraceSubcommand::Progress { trace_db, run_id } => {
             let trace_store = SqliteTraceStore::open(&trace_db)?;
             let run_id = parse_run_id(&run_id)?;
-            let events = trace_store.list_events_for_run(run_id)?;
-            for event in events {
-                println!("{}", serde_json::to_string(&event)?);
+            let progress = trace_store
+                .get_run_progress(run_id)?
+                .ok_or_else(|| anyhow!("run not found: {}", run_id.0))?;
+            println!("{}", serde_json::to_string_pretty(&progress)?);
+        }
+        TraceSubcommand::Manifest { trace_db, run_id } => manifest_command(&trace_db, &run_id)?,
+        TraceSubcommand::Usage {
+            trace_db,
+            run_id,
+            workflow,
+        } => {
+            let trace_store = SqliteTraceStore::open(&trace_db)?;
+            trace_store.migrate()?;
+            if let Some(run_id) = run_id {
+                let run_id = parse_run_id(&run_id)?;
+                let usage = trace_store
+                    .get_run_usage(run_id)?
+                    .ok_or_else(|| anyhow!("no usage recorded for run: {}", run_id.0))?;
+                println!("{}", serde_json::to_string_pretty(&usage)?);
+            } else {
+                for usage in trace_store.list_run_usage(workflow.as_deref())? {
+                    println!("{}", serde_json::to_string(&usage)?);
+                }
+            }
+        }
+        TraceSubcommand::ReasonCodes {
+            trace_db,
+            since,
+            until,
+        } => {
+            let trace_store = SqliteTraceStore::open(&trace_db)?;
+            trace_store.migrate()?;
+            let window = DecisionWindow {
+                since: since.as_deref().map(parse_rfc3339).transpose()?,
+                until: until.as_deref().map(parse_rfc3339).transpose()?,
+            };
+            let stats = trace_store.reason_code_stats(window)?;
+            println!("{}", serde_json::to_string_pretty(&stats)?);
+        }
+        TraceSubcommand::Warnings { command } => warnings_command(command)?,
+        TraceSubcommand::Workflows {
+            command:
+                TraceWorkflowsSubcommand::Timings {
+                    trace_db,
+                    workflow_hash,
+                },
+        } => {
+            let trace_store = SqliteTraceStore::open(&trace_db)?;
+            trace_store.migrate()?;
+            let stats = trace_store.list_step_duration_stats(workflow_hash.as_deref())?;
+            for entry in stats {
+                println!("{}", serde_json::to_string(&entry)?);
+            }
+        }
+        TraceSubcommand::RecordGolden {
+            trace_db,
+            run_id,
+            out,
+        } => {
+            let trace_store = SqliteTraceStore::open(&trace_db)?;
+            trace_store.migrate()?;
+            let golden = record_golden_run(&trace_store, parse_run_id(&run_id)?)?;
+            let rendered = serde_json::to_string_pretty(&golden)?;
+            if let Some(out) = out {
+                std::fs::write(&out, format!("{rendered}\n"))
+                    .with_context(|| format!("failed to write golden file {}", out.display()))?;
+                println!(
+                    "recorded golden for run {} ({} steps) to {}",
+                    golden.source_run_id,
+                    golden.steps.len(),
+                    out.display()
+                );
+            } else {
+                println!("{rendered}");
             }
         }
+        TraceSubcommand::CheckGolden(args) => check_golden_command(&args)?,
+    }
+    Ok(())
+}
+
+/// Whether a recorded run failed steps on permission prunes (manifest `permission_prune`), so
+/// replays and reruns of it enforce the same policy.
+fn recorded_permission_prune(trace_store: &SqliteTraceStore, run_id: RunId) -> Result<bool> {
+    Ok(trace_store
+        .get_run_manifest(run_id)?
+        .is_some_and(|manifest| manifest["permission_prune"] == "fail"))
+}
+
+fn manifest_command(trace_db: &Path, run_id: &str) -> Result<()> {
+    let trace_store = SqliteTraceStore::open(trace_db)?;
+    trace_store.migrate()?;
+    let run_id = parse_run_id(run_id)?;
+    let run = trace_store
+        .get_run(run_id)?
+        .ok_or_else(|| anyhow!("run not found: {}", run_id.0))?;
+    let manifest = trace_store
+        .get_run_manifest(run_id)?
+        .ok_or_else(|| anyhow!("no manifest stored for run: {}", run_id.0))?;
+    let manifest_hash = run
+        .manifest_hash
+        .ok_or_else(|| anyhow!("no manifest hash recorded for run: {}", run_id.0))?;
+    let injected_memory = verify_run_manifest(&manifest, &manifest_hash)?;
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&json!({
+            "run_id": run_id.0.to_string(),
+            "manifest_hash": manifest_hash,
+            "verified": true,
+            "injected_memory_root": injected_memory.map(|memory| memory.root),
+            "manifest": manifest,
+        }))?
+    );
+    Ok(())
+}
+
+fn check_golden_command(args: &CheckGoldenArgs) -> Result<()> {
+    let raw = std::fs::read_to_string(&args.golden)
+        .with_context(|| format!("failed to read golden file {}", args.golden.display()))?;
+    let golden: GoldenRun = serde_json::from_str(&raw)
+        .with_context(|| format!("invalid golden file {}", args.golden.display()))?;
+    if golden.golden_version != GOLDEN_RUN_VERSION {
+        return Err(anyhow!(
+            "unsupported golden_version '{}'; expected '{GOLDEN_RUN_VERSION}'",
+            golden.golden_version
+        ));
+    }
+
+    let workflow = load_workflow_from_path(&args.workflow)?;
+    let trace_store = SqliteTraceStore::open(&args.trace_db)?;
+    trace_store.migrate()?;
+    let trust_gate = TrustGateSelection::from_flags(
+        args.trust_source.as_deref(),
+        args.trust_db.as_deref(),
+        &args.trust_mode,
+    )?;
+    let trust_source = trust_gate.open(Some(&workflow.normalized_workflow.workflow_name))?;
+
+    let config = RunConfig {
+        run_id: None,
+        as_of: Some(golden.as_of),
+        replay_of_run_id: None,
+        external_correlation_id: None,
+        labels: BTreeMap::new(),
+        non_interactive: true,
+        cli_args_json: json!({
+            "workflow": args.workflow,
+            "golden": args.golden,
+            "trace_db": args.trace_db,
+            "memory_db": args.memory_db,
+            "trust_source": trust_gate.as_str(),
+            "trust_db": args.trust_db,
+            "trust_mode": args.trust_mode,
+            "golden_source_run_id": golden.source_run_id.to_string(),
+        }),
+        engine_version: "multi-agent-center.v0".to_string(),
+        apply_proposed_writes: false,
+        speculative_execution: false,
+        reuse_correlated_run: false,
+        require_trust_gating: false,
+        memoize_steps: false,
+        rerun_of: None,
+        relaxed_trace_artifacts: BTreeSet::new(),
+        enforce_permission_prune: false,
+        resume_run: None,
+        parent_step: None,
+        cancellation: CancellationToken::default(),
+    };
+
+    let api_source;
+    let static_source = StaticContextPackageSource::default();
+    let context_source: &dyn ContextPackageSource = if let Some(memory_db) = &args.memory_db {
+        api_source = ApiMemoryKernelContextSource::new(memory_db);
+        &api_source
+    } else {
+        &static_source
+    };
+    let human_gate = GoldenHumanGateDecider::new(&golden);
+    let responses = GoldenResponseSource::new(&golden);
+    let write_applier = NoopProposedWriteApplier;
+    let scorer = RuleResponseScorer;
+
+    let summary = Orchestrator::new(
+        &trace_store,
+        context_source,
+        trust_source.as_ref(),
+        &human_gate,
+        &write_applier,
+        ProviderRegistry::default(),
+    )
+    .with_response_scorer(&scorer)
+    .with_recorded_responses(&responses)
+    .execute_workflow(&workflow, config)?;
+
+    let actual = record_golden_run(&trace_store, summary.run_id)?;
+    let differences = diff_golden_runs(&golden, &actual);
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&json!({
+            "golden_source_run_id": golden.source_run_id.to_string(),
+            "check_run_id": summary.run_id.to_string(),
+            "golden_workflow_hash": golden.workflow_hash,
+            "workflow_hash": workflow.normalized_hash,
+            "matched": differences.is_empty(),
+            "differences": differences,
+        }))?
+    );
+
+    if differences.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "golden check failed: {} difference(s) against run {}",
+            differences.len(),
+            golden.source_run_id
+        ))
+    }
+}
+
+fn gates_command(args: GatesArgs) -> Result<()> {
+    match args.command {
+        GatesSubcommand::List {
+            trace_db,
+            run_id,
+            pending,
+        } => {
+            let trace_store = SqliteTraceStore::open(&trace_db)?;
+            trace_store.migrate()?;
+            let run_id = run_id.as_deref().map(parse_run_id).transpose()?;
+            for gate in trace_store.list_deferred_gates(run_id)? {
+                if pending && gate.resolution.is_some() {
+                    continue;
+                }
+                println!("{}", serde_json::to_string(&gate)?);
+            }
+        }
+        GatesSubcommand::Resolve {
+            trace_db,
+            run_id,
+            step_key,
+            gate,
+            approve,
+            reject: _,
+            notes,
+            decided_by,
+            on_behalf_of,
+        } => {
+            let trace_store = SqliteTraceStore::open(&trace_db)?;
+            trace_store.migrate()?;
+            let decided_by = decided_by.unwrap_or_else(|| {
+                std::env::var("USER").unwrap_or_else(|_| "human.cli".to_string())
+            });
+            let resolved = trace_store.resolve_deferred_gate(
+                parse_run_id(&run_id)?,
+                &step_key,
+                &gate,
+                &DeferredGateResolution {
+                    approved: approve,
+                    decided_by,
+                    auth_method: DeciderAuthMethod::Unverified,
+                    on_behalf_of,
+                    notes,
+                    decided_at: now_utc(),
+                },
+            )?;
+            println!("{}", serde_json::to_string(&resolved)?);
+        }
+        GatesSubcommand::Delegate {
+            trace_db,
+            delegator,
+            delegate,
+            gate,
+            expires_at,
+        } => {
+            let trace_store = SqliteTraceStore::open(&trace_db)?;
+            trace_store.migrate()?;
+            let delegation = ApprovalDelegation {
+                delegation_id: Ulid::new().to_string(),
+                delegator,
+                delegate,
+                gate_name: gate,
+                created_at: now_utc(),
+                expires_at: expires_at.as_deref().map(parse_rfc3339).transpose()?,
+                revoked_at: None,
+            };
+            trace_store.add_approval_delegation(&delegation)?;
+            println!("{}", serde_json::to_string(&delegation)?);
+        }
+        GatesSubcommand::Delegations { trace_db } => {
+            let trace_store = SqliteTraceStore::open(&trace_db)?;
+            trace_store.migrate()?;
+            for delegation in trace_store.list_approval_delegations()? {
+                println!("{}", serde_json::to_string(&delegation)?);
+            }
+        }
+        GatesSubcommand::RevokeDelegation {
+            trace_db,
+            delegation_id,
+        } => {
+            let trace_store = SqliteTraceStore::open(&trace_db)?;
+            trace_store.migrate()?;
+            if !trace_store.revoke_approval_delegation(&delegation_id, now_utc())? {
+                return Err(anyhow!("no active approval delegation {delegation_id}"));
+            }
+            println!("revoked={delegation_id}");
+        }
+    }
+    Ok(())
+}
+
+fn prompts_command(args: PromptsArgs) -> Result<()> {
+    match args.command {
+        PromptsSubcommand::Add {
+            trace_db,
+            name,
+            version,
+            file,
+        } => {
+            let trace_store = SqliteTraceStore::open(&trace_db)?;
+            trace_store.migrate()?;
+            let body = std::fs::read_to_string(&file)
+                .with_context(|| format!("failed to read prompt template {}", file.display()))?;
+            let template = PromptTemplateRecord::new(&name, &version, &body)?;
+            trace_store.put_prompt_template(&template)?;
+            println!(
+                "{}",
+                serde_json::to_string(&json!({
+                    "prompt_ref": template.prompt_ref(),
+                    "template_hash": template.template_hash,
+                }))?
+            );
+        }
+        PromptsSubcommand::List { trace_db } => {
+            let trace_store = SqliteTraceStore::open(&trace_db)?;
+            trace_store.migrate()?;
+            for template in trace_store.list_prompt_templates()? {
+                println!(
+                    "{}",
+                    serde_json::to_string(&json!({
+                        "prompt_ref": template.prompt_ref(),
+                        "template_hash": template.template_hash,
+                    }))?
+                );
+            }
+        }
+        PromptsSubcommand::Show {
+            trace_db,
+            prompt_ref,
+        } => {
+            let trace_store = SqliteTraceStore::open(&trace_db)?;
+            trace_store.migrate()?;
+            let (name, version) = parse_prompt_ref(&prompt_ref)?;
+            let template = trace_store
+                .get_prompt_template(name, version)?
+                .ok_or_else(|| anyhow!("unknown prompt template {prompt_ref}"))?;
+            println!("{}", serde_json::to_string_pretty(&template)?);
+        }
     }
     Ok(())
 }
@@ -231,31 +1049,31 @@ fn trace_command(args: TraceArgs) -> Result<()> {
 fn replay_command(args: &ReplayArgs) -> Result<()> {
     let trace_store = SqliteTraceStore::open(&args.trace_db)?;
     let run_id = parse_run_id(&args.run_id)?;
-    if args.rerun_provider {
+    if args.execute {
+        let context_source = StaticContextPackageSource::default();
+        let report = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        )
+        .with_response_scorer(&RuleResponseScorer)
+        .replay_execute(run_id)?;
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        if !report.reproduced {
+            return Err(anyhow!(
+                "replay of run {run_id} diverged: {} difference(s)",
+                report.divergences.len()
+            ));
+        }
+    } else if args.rerun_provider {
         let source_run = trace_store
             .get_run(run_id)?
             .ok_or_else(|| anyhow!("run_id {run_id} not found"))?;
-        let snapshot = trace_store
-            .get_workflow_snapshot(&source_run.workflow_hash)?
-            .ok_or_else(|| anyhow!("workflow snapshot {} not found", source_run.workflow_hash))?;
-        let normalized_workflow: NormalizedWorkflow =
-            serde_json::from_value(snapshot.normalized_json.clone())
-                .map_err(|err| anyhow!("invalid normalized workflow snapshot JSON: {err}"))?;
-        let workflow = NormalizedWorkflowEnvelope {
-            source_format: snapshot.source_format,
-            source_yaml_hash: snapshot.source_yaml_hash,
-            normalized_hash: snapshot.workflow_hash,
-            normalized_workflow,
-            normalized_json: snapshot.normalized_json,
-        };
-
-        let context_rows = trace_store.get_step_context_packages(run_id)?;
-        let mut by_step: BTreeMap<String, Vec<ContextPackageEnvelope>> = BTreeMap::new();
-        for row in context_rows {
-            by_step.entry(row.step_key).or_default().push(row.envelope);
-        }
-
-        let context_source = StaticContextPackageSource::with_step_packages(by_step);
+        let workflow = load_workflow_snapshot(&trace_store, &source_run.workflow_hash)?;
+        let context_source = RecordedContextPackageSource::from_trace(&trace_store, run_id)?;
         let trust_source = AllowAllTrustGateSource;
         let human_gate = DefaultHumanGateDecider;
         let write_applier = NoopProposedWriteApplier;
@@ -266,6 +1084,7 @@ fn replay_command(args: &ReplayArgs) -> Result<()> {
             external_correlation_id: source_run
                 .external_correlation_id
                 .map(|id| format!("{id}:rerun")),
+            labels: source_run.labels.clone(),
             non_interactive: false,
             cli_args_json: json!({
                 "trace_db": args.trace_db,
@@ -274,6 +1093,16 @@ fn replay_command(args: &ReplayArgs) -> Result<()> {
             }),
             engine_version: "multi-agent-center.v0".to_string(),
             apply_proposed_writes: false,
+            speculative_execution: false,
+            reuse_correlated_run: false,
+            require_trust_gating: false,
+            memoize_steps: false,
+            rerun_of: None,
+            relaxed_trace_artifacts: BTreeSet::new(),
+            enforce_permission_prune: recorded_permission_prune(&trace_store, run_id)?,
+            resume_run: None,
+            parent_step: None,
+            cancellation: CancellationToken::default(),
         };
 
         let summary = Orchestrator::new(
@@ -282,6 +1111,7 @@ fn replay_command(args: &ReplayArgs) -> Result<()> {
             &trust_source,
             &human_gate,
             &write_applier,
+            ProviderRegistry::default(),
         )
         .execute_workflow(&workflow, replay_config)?;
 
@@ -305,6 +1135,7 @@ fn replay_command(args: &ReplayArgs) -> Result<()> {
             &trust_source,
             &human_gate,
             &write_applier,
+            ProviderRegistry::default(),
         )
         .replay_audit(run_id)?;
 
@@ -317,6 +1148,145 @@ fn replay_command(args: &ReplayArgs) -> Result<()> {
     Ok(())
 }
 
+fn rerun_step_command(args: &RerunStepArgs) -> Result<()> {
+    let trace_store = SqliteTraceStore::open(&args.trace_db)?;
+    trace_store.migrate()?;
+    let source_run_id = parse_run_id(&args.run_id)?;
+    let source_run = trace_store
+        .get_run(source_run_id)?
+        .ok_or_else(|| anyhow!("run_id {source_run_id} not found"))?;
+    let workflow = match &args.workflow {
+        Some(path) => load_workflow_from_path(path)?,
+        None => load_workflow_snapshot(&trace_store, &source_run.workflow_hash)?,
+    };
+    let context_source = RecordedContextPackageSource::from_trace(&trace_store, source_run_id)?;
+
+    let config = RunConfig {
+        run_id: None,
+        as_of: Some(source_run.as_of),
+        replay_of_run_id: None,
+        external_correlation_id: None,
+        labels: source_run.labels.clone(),
+        non_interactive: args.non_interactive,
+        cli_args_json: json!({
+            "trace_db": args.trace_db,
+            "workflow": args.workflow,
+            "source_run_id": source_run_id.to_string(),
+            "step_key": args.step_key,
+            "non_interactive": args.non_interactive,
+        }),
+        engine_version: "multi-agent-center.v0".to_string(),
+        apply_proposed_writes: false,
+        speculative_execution: false,
+        reuse_correlated_run: false,
+        require_trust_gating: false,
+        memoize_steps: false,
+        rerun_of: Some(StepRerun {
+            source_run_id,
+            step_key: args.step_key.clone(),
+        }),
+        relaxed_trace_artifacts: BTreeSet::new(),
+        enforce_permission_prune: recorded_permission_prune(&trace_store, source_run_id)?,
+        resume_run: None,
+        parent_step: None,
+        cancellation: CancellationToken::default(),
+    };
+    let summary = execute_with_trust_source(
+        &trace_store,
+        &context_source,
+        &AllowAllTrustGateSource,
+        None,
+        &workflow,
+        config,
+    )?;
+
+    println!(
+        "source_run_id={} rerun_run_id={} step_key={} status={} steps_total={} \
+         steps_succeeded={} steps_failed_or_rejected={} provider_calls={}",
+        source_run_id,
+        summary.run_id,
+        args.step_key,
+        format_run_status(&summary.status),
+        summary.steps_total,
+        summary.steps_succeeded,
+        summary.steps_failed_or_rejected,
+        summary.provider_calls
+    );
+    Ok(())
+}
+
+fn resume_command(args: &ResumeArgs) -> Result<()> {
+    let trace_store = SqliteTraceStore::open(&args.trace_db)?;
+    trace_store.migrate()?;
+    let run_id = parse_run_id(&args.run_id)?;
+    let run = trace_store
+        .get_run(run_id)?
+        .ok_or_else(|| anyhow!("run_id {run_id} not found"))?;
+    let workflow = load_workflow_snapshot(&trace_store, &run.workflow_hash)?;
+
+    let trust_gate = TrustGateSelection::from_flags(
+        args.trust_source.as_deref(),
+        args.trust_db.as_deref(),
+        &args.trust_mode,
+    )?;
+    let trust_source = trust_gate.open(Some(&workflow.normalized_workflow.workflow_name))?;
+
+    let config = RunConfig {
+        non_interactive: args.non_interactive,
+        apply_proposed_writes: run
+            .cli_args_json
+            .get("apply_proposed_writes")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false),
+        enforce_permission_prune: recorded_permission_prune(&trace_store, run_id)?,
+        resume_run: Some(run_id),
+        ..RunConfig::default()
+    };
+    let summary = match args.memory_db.as_ref() {
+        Some(memory_db) => execute_with_trust_source(
+            &trace_store,
+            &ApiMemoryKernelContextSource::new(memory_db),
+            trust_source.as_ref(),
+            trust_gate.outcome_db(),
+            &workflow,
+            config,
+        )?,
+        None => execute_with_trust_source(
+            &trace_store,
+            &StaticContextPackageSource::default(),
+            trust_source.as_ref(),
+            trust_gate.outcome_db(),
+            &workflow,
+            config,
+        )?,
+    };
+
+    print_run_summary(&summary);
+    Ok(())
+}
+
+fn upgrade_impact_command(args: &UpgradeImpactArgs) -> Result<()> {
+    let old = load_workflow_from_path(&args.old_workflow)?;
+    let new = load_workflow_from_path(&args.new_workflow)?;
+    let trace_store = SqliteTraceStore::open(&args.trace_db)?;
+    trace_store.migrate()?;
+    let impact =
+        workflow_upgrade_impact(&trace_store, diff_workflows(&old, &new), args.recent_runs)?;
+    println!("{}", serde_json::to_string_pretty(&impact)?);
+    Ok(())
+}
+
+fn bench_trust_gate_command(args: &BenchTrustGateArgs) -> Result<()> {
+    let report = benchmark_trust_gate(&TrustGateBenchmarkConfig::from_flags(
+        args.trust_rows,
+        args.candidates,
+        args.iterations,
+        &args.trust_mode,
+    )?)?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
 #[allow(clippy::needless_pass_by_value)]
 fn export_command(args: &ExportArgs) -> Result<()> {
     let trace_store = SqliteTraceStore::open(&args.trace_db)?;
@@ -387,6 +1357,131 @@ impl HumanGateDecider for CliHumanGateDecider {
     }
 }
 
+const GATE_NOTIFY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// [`GateNotifier`] sending signed gate notifications as HTTP `POST`s.
+struct WebhookGateNotifier {
+    agent: ureq::Agent,
+}
+
+impl WebhookGateNotifier {
+    fn new(timeout: std::time::Duration) -> Self {
+        Self {
+            agent: ureq::AgentBuilder::new().timeout(timeout).build(),
+        }
+    }
+}
+
+impl GateNotifier for WebhookGateNotifier {
+    fn notify(&self, url: &str, body: &[u8], signature: &str) -> Result<()> {
+        match self
+            .agent
+            .post(url)
+            .set("Content-Type", "application/json")
+            .set(GATE_SIGNATURE_HEADER, signature)
+            .send_bytes(body)
+        {
+            Ok(_) => Ok(()),
+            Err(ureq::Error::Status(code, _)) => Err(anyhow!("gate webhook returned HTTP {code}")),
+            Err(err) => Err(anyhow!("gate webhook delivery failed: {err}")),
+        }
+    }
+}
+
+fn parse_label(input: &str) -> std::result::Result<(String, String), String> {
+    match input.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected key=value, got '{input}'")),
+    }
+}
+
+fn parse_trace_artifact_class(input: &str) -> std::result::Result<TraceArtifactClass, String> {
+    TraceArtifactClass::ALL
+        .into_iter()
+        .find(|class| class.as_str() == input)
+        .ok_or_else(|| {
+            let known: Vec<&str> = TraceArtifactClass::ALL
+                .iter()
+                .map(|class| class.as_str())
+                .collect();
+            format!("unknown trace artifact class `{input}`; expected one of {known:?}")
+        })
+}
+
+fn parse_event_type(input: &str) -> std::result::Result<TraceEventType, String> {
+    serde_json::from_value(serde_json::Value::String(input.to_string()))
+        .map_err(|_| format!("unknown trace event type `{input}`"))
+}
+
+fn parse_payload_filter(input: &str) -> std::result::Result<PayloadFilter, String> {
+    PayloadFilter::parse(input).map_err(|err| err.to_string())
+}
+
+fn warnings_command(command: TraceWarningsSubcommand) -> Result<()> {
+    let TraceWarningsSubcommand::Top {
+        trace_db,
+        workflow,
+        since,
+        until,
+        limit,
+    } = command;
+    let trace_store = SqliteTraceStore::open(&trace_db)?;
+    trace_store.migrate()?;
+    let window = DecisionWindow {
+        since: since.as_deref().map(parse_rfc3339).transpose()?,
+        until: until.as_deref().map(parse_rfc3339).transpose()?,
+    };
+    let mut stats = trace_store.warning_stats(window, workflow.as_deref())?;
+    stats.stats.truncate(limit);
+    println!("{}", serde_json::to_string_pretty(&stats)?);
+    Ok(())
+}
+
+fn events_command(args: TraceEventsArgs) -> Result<()> {
+    let (trace_db, run_id) = match args {
+        TraceEventsArgs {
+            command: Some(TraceEventsSubcommand::Query(query)),
+            ..
+        } => return events_query_command(&query),
+        TraceEventsArgs {
+            trace_db: Some(trace_db),
+            run_id: Some(run_id),
+            ..
+        } => (trace_db, run_id),
+        TraceEventsArgs { .. } => {
+            return Err(anyhow!("trace events requires --trace-db and --run-id"));
+        }
+    };
+    let trace_store = SqliteTraceStore::open(&trace_db)?;
+    let run_id = parse_run_id(&run_id)?;
+    let events = trace_store.list_events_for_run(run_id)?;
+    for event in events {
+        println!("{}", serde_json::to_string(&event)?);
+    }
+    Ok(())
+}
+
+fn events_query_command(args: &TraceEventsQueryArgs) -> Result<()> {
+    let trace_store = SqliteTraceStore::open(&args.trace_db)?;
+    trace_store.migrate()?;
+    let query = EventQuery {
+        run_id: args.run_id.as_deref().map(parse_run_id).transpose()?,
+        event_types: args.event_types.clone(),
+        actor_type: args.actor_type.clone(),
+        actor_id: args.actor_id.clone(),
+        since: args.since.as_deref().map(parse_rfc3339).transpose()?,
+        until: args.until.as_deref().map(parse_rfc3339).transpose()?,
+        payload: args.payload.clone(),
+        limit: args.limit,
+    };
+    for event in trace_store.query_events(&query)? {
+        println!("{}", serde_json::to_string(&event)?);
+    }
+    Ok(())
+}
+
 fn parse_run_id(input: &str) -> Result<RunId> {
     let value = Ulid::from_str(input).map_err(|err| anyhow!("invalid run_id ULID: {err}"))?;
     Ok(RunId(value))
@@ -397,11 +1492,6 @@ fn parse_rfc3339(input: &str) -> Result<OffsetDateTime> {
         .map_err(|err| anyhow!("invalid RFC3339 timestamp: {err}"))
 }
 
-fn parse_retrieval_mode(input: &str) -> Result<RetrievalMode> {
-    RetrievalMode::parse(input)
-        .ok_or_else(|| anyhow!("invalid trust_mode '{input}'; use 'safe' or 'exploration'"))
-}
-
 fn format_run_status(status: &multi_agent_center_domain::RunStatus) -> &'static str {
     match status {
         multi_agent_center_domain::RunStatus::Pending => "pending",
@@ -409,5 +1499,7 @@ fn format_run_status(status: &multi_agent_center_domain::RunStatus) -> &'static
         multi_agent_center_domain::RunStatus::Succeeded => "succeeded",
         multi_agent_center_domain::RunStatus::Failed => "failed",
         multi_agent_center_domain::RunStatus::Rejected => "rejected",
+        multi_agent_center_domain::RunStatus::PartiallyCompleted => "partially_completed",
+        multi_agent_center_domain::RunStatus::Cancelled => "cancelled",
     }
 }
@@ -85,6 +85,7 @@ fn seed_memory_kernel_db(memory_db: &Path) {
             effective_at: None,
             supersedes: Vec::new(),
             contradicts: Vec::new(),
+            tags: Vec::new(),
         })
         .is_ok());
     assert!(api
@@ -105,6 +106,7 @@ fn seed_memory_kernel_db(memory_db: &Path) {
             effective_at: None,
             supersedes: Vec::new(),
             contradicts: Vec::new(),
+            tags: Vec::new(),
         })
         .is_ok());
 }
@@ -316,4 +318,26 @@ fn replay_rerun_keeps_trust_memory_ref_identity_fields() {
             "run {run_id} has trust memory_ref rows without full identity",
         );
     }
+
+    let execute_output = Command::new(env!("CARGO_BIN_EXE_multi-agent-center-cli"))
+        .arg("replay")
+        .arg("--trace-db")
+        .arg(&trace_db)
+        .arg("--run-id")
+        .arg(source_run_id.to_string())
+        .arg("--execute")
+        .output()
+        .unwrap_or_else(|err| panic!("failed to run replay --execute: {err}"));
+    let execute_stdout = String::from_utf8_lossy(&execute_output.stdout).to_string();
+    assert!(
+        execute_output.status.success(),
+        "replay --execute diverged: {execute_stdout}"
+    );
+    let report: serde_json::Value = serde_json::from_str(&execute_stdout)
+        .unwrap_or_else(|err| panic!("invalid replay report {execute_stdout}: {err}"));
+    assert_eq!(report["reproduced"], serde_json::json!(true));
+    assert_eq!(
+        report["source_run_id"],
+        serde_json::json!(source_run_id.to_string())
+    );
 }
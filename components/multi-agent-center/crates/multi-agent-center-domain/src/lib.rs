@@ -1,7 +1,11 @@
 #![forbid(unsafe_code)]
 
+use std::collections::BTreeMap;
+
 use anyhow::{anyhow, Result};
-use memory_kernel_core::{ContextPackage, MemoryId, MemoryVersionId, RecordType};
+use memory_kernel_core::{
+    validate_memory_tag, ContextItem, ContextPackage, MemoryId, MemoryVersionId, RecordType,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sha2::{Digest, Sha256};
@@ -62,6 +66,10 @@ pub enum RunStatus {
     Succeeded,
     Failed,
     Rejected,
+    /// Finished with steps held by deferred gates; resuming the run picks them up.
+    PartiallyCompleted,
+    /// Stopped through its cancellation token before every step finished.
+    Cancelled,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
@@ -73,6 +81,12 @@ pub enum StepStatus {
     Failed,
     Rejected,
     Skipped,
+    /// Held by a deferred human gate until a person resolves it and the run is resumed.
+    Deferred,
+    /// Its provider calls outran `constraints.timeout_seconds`.
+    TimedOut,
+    /// Interrupted, or never started, because the run was cancelled.
+    Cancelled,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
@@ -96,7 +110,30 @@ pub enum GateDecision {
 pub enum TraceEventType {
     RunStarted,
     RunFinished,
+    /// A resumed run continues its event chain; the payload lists the steps it runs again.
+    RunResumed,
     WorkflowNormalized,
+    /// A step's `when` condition was evaluated; the payload records the observed value and
+    /// whether the step runs.
+    ConditionEvaluated,
+    /// A `foreach` step's items were resolved from its source step's output; the payload lists
+    /// the items and the child steps added for them.
+    ForeachExpanded,
+    /// An `iterate` step added its next iteration step; the payload records the iteration's
+    /// task, which carries the previous iteration's output.
+    IterationStarted,
+    /// An iteration of an `iterate` step succeeded and its output was checked against the
+    /// step's `until` expression.
+    IterationEvaluated,
+    /// A `workflow_call` step started its nested run; the payload carries the child `run_id`.
+    WorkflowCallStarted,
+    /// A `workflow_call` step's nested run finished; the payload records the child run status.
+    WorkflowCallFinished,
+    /// A `vote` step added its candidate steps, or its judge step with the candidates' outputs.
+    VoteExpanded,
+    /// A `vote` step's aggregator selected its output; the payload lists every candidate and
+    /// records the tally or the deciding judge or person.
+    VoteDecided,
     StepReady,
     StepStarted,
     StepInputPrepared,
@@ -107,10 +144,43 @@ pub enum TraceEventType {
     ProposedMemoryWrite,
     ReplayStarted,
     ReplayFinished,
+    StepScored,
     Warning,
     Error,
 }
 
+/// Trace artifact classes a run may write with relaxed durability: a buffering trace store may
+/// keep their rows queued until its next flush instead of committing them before the write
+/// returns. Run and step rows, gate decisions, proposed writes, and manifests are always
+/// written synchronously.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceArtifactClass {
+    Events,
+    ContextPackages,
+    ProviderCalls,
+    StepCheckpoints,
+}
+
+impl TraceArtifactClass {
+    pub const ALL: [Self; 4] = [
+        Self::Events,
+        Self::ContextPackages,
+        Self::ProviderCalls,
+        Self::StepCheckpoints,
+    ];
+
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Events => "events",
+            Self::ContextPackages => "context_packages",
+            Self::ProviderCalls => "provider_calls",
+            Self::StepCheckpoints => "step_checkpoints",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct ProviderBinding {
@@ -120,6 +190,118 @@ pub struct ProviderBinding {
     pub params: Value,
 }
 
+/// Equivalent provider endpoints an agent's calls are spread across. The agent's primary
+/// `provider` is endpoint 0, weighted `provider_weight`; `endpoints` follow from position 1.
+/// Each step is assigned one endpoint from its turn, so retries and conversation turns stay on
+/// it: a run claims one turn per step of the agent from a per-agent counter kept by the trace
+/// store, so consecutive runs continue the rotation, and resumed, replayed, and rerun runs keep
+/// the turns of the run they continue.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ProviderLoadBalancing {
+    #[serde(default)]
+    pub strategy: LoadBalancingStrategy,
+    #[serde(default = "default_endpoint_weight")]
+    pub provider_weight: u32,
+    pub endpoints: Vec<ProviderEndpoint>,
+}
+
+/// How a [`ProviderLoadBalancing`] pool assigns endpoints to steps.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LoadBalancingStrategy {
+    /// Cycle through the endpoints in order, ignoring weights.
+    #[default]
+    RoundRobin,
+    /// Smooth weighted round-robin: each endpoint serves `weight` of every `total weight` steps,
+    /// interleaved.
+    Weighted,
+}
+
+impl LoadBalancingStrategy {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::RoundRobin => "round_robin",
+            Self::Weighted => "weighted",
+        }
+    }
+}
+
+/// A provider binding in a [`ProviderLoadBalancing`] pool.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ProviderEndpoint {
+    pub provider_name: String,
+    pub model_id: String,
+    #[serde(default)]
+    pub params: Value,
+    #[serde(default = "default_endpoint_weight")]
+    pub weight: u32,
+}
+
+fn default_endpoint_weight() -> u32 {
+    1
+}
+
+/// Endpoint a [`ProviderLoadBalancing`] pool assigned to a step.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct SelectedEndpoint {
+    /// 0 for the agent's primary `provider`, `n` for `endpoints[n - 1]`.
+    pub position: usize,
+    pub weight: u32,
+    pub binding: ProviderBinding,
+}
+
+impl ProviderLoadBalancing {
+    /// Endpoint serving the agent's `ordinal`-th step (from 0), with `primary` as endpoint 0.
+    #[must_use]
+    pub fn select(&self, primary: &ProviderBinding, ordinal: usize) -> SelectedEndpoint {
+        let weights: Vec<u32> = std::iter::once(self.provider_weight)
+            .chain(self.endpoints.iter().map(|endpoint| endpoint.weight))
+            .collect();
+        let position = match self.strategy {
+            LoadBalancingStrategy::RoundRobin => ordinal % weights.len(),
+            LoadBalancingStrategy::Weighted => smooth_weighted_position(&weights, ordinal),
+        };
+        let binding = match position.checked_sub(1) {
+            None => primary.clone(),
+            Some(index) => {
+                let endpoint = &self.endpoints[index];
+                ProviderBinding {
+                    provider_name: endpoint.provider_name.clone(),
+                    model_id: endpoint.model_id.clone(),
+                    params: endpoint.params.clone(),
+                }
+            }
+        };
+        SelectedEndpoint {
+            position,
+            weight: weights[position],
+            binding,
+        }
+    }
+}
+
+/// Position picked at turn `ordinal` of smooth weighted round-robin over `weights`; the sequence
+/// repeats every `sum(weights)` turns. Ties go to the lowest position.
+fn smooth_weighted_position(weights: &[u32], ordinal: usize) -> usize {
+    let total: i64 = weights.iter().map(|weight| i64::from(*weight)).sum();
+    let turns = u64::try_from(ordinal).unwrap_or(u64::MAX) % total.unsigned_abs().max(1);
+    let mut current = vec![0_i64; weights.len()];
+    let mut picked = 0;
+    for _ in 0..=turns {
+        for (slot, weight) in current.iter_mut().zip(weights) {
+            *slot += i64::from(*weight);
+        }
+        picked = (0..current.len())
+            .max_by(|left, right| current[*left].cmp(&current[*right]).then(right.cmp(left)))
+            .unwrap_or(0);
+        current[picked] -= total;
+    }
+    picked
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize, Eq, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct AgentPermissions {
@@ -132,6 +314,29 @@ pub struct AgentPermissions {
     pub can_propose_memory_writes: bool,
     #[serde(default)]
     pub fail_on_permission_prune: bool,
+    /// When non-empty, only memories carrying at least one of these tags reach the agent.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_record_tags: Vec<String>,
+    /// Memories carrying any of these tags never reach the agent.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub denied_record_tags: Vec<String>,
+}
+
+impl AgentPermissions {
+    /// Reject malformed entries in `allowed_record_tags` and `denied_record_tags`.
+    ///
+    /// # Errors
+    /// Returns an error naming the first malformed tag.
+    pub fn validate(&self) -> Result<()> {
+        for tag in self
+            .allowed_record_tags
+            .iter()
+            .chain(&self.denied_record_tags)
+        {
+            validate_memory_tag(tag).map_err(|err| anyhow!("permissions: {err}"))?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
@@ -146,6 +351,60 @@ pub struct AgentDefinition {
     pub default_instructions: Vec<String>,
     #[serde(default)]
     pub metadata: std::collections::BTreeMap<String, String>,
+    /// Name of the workflow persona applied to this agent's provider calls.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persona: Option<String>,
+    /// Ordered provider/model bindings tried in turn when the primary binding fails with a
+    /// retryable error.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallbacks: Vec<ProviderBinding>,
+    /// Equivalent endpoints the agent's steps are balanced across, the primary binding included.
+    /// Fallbacks follow whichever endpoint a step was assigned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub load_balancing: Option<ProviderLoadBalancing>,
+}
+
+/// Versioned system prompt and style constraints, declared once per workflow and applied to
+/// provider calls instead of being embedded in task payloads.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PersonaDefinition {
+    pub persona_name: String,
+    pub persona_version: String,
+    pub system_prompt: String,
+    #[serde(default)]
+    pub style_constraints: Vec<String>,
+}
+
+/// Persona resolved for a step together with the hash of its normalized definition.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct AppliedPersona {
+    pub persona: PersonaDefinition,
+    pub persona_hash: String,
+}
+
+impl AppliedPersona {
+    /// Resolve `persona` for application, hashing its normalized definition.
+    ///
+    /// # Errors
+    /// Returns an error if the persona cannot be serialized.
+    pub fn new(persona: &PersonaDefinition) -> Result<Self> {
+        let persona_hash = hash_json(&serde_json::to_value(persona)?)?;
+        Ok(Self {
+            persona: persona.clone(),
+            persona_hash,
+        })
+    }
+
+    /// Identity recorded per provider call: name, version, and definition hash.
+    #[must_use]
+    pub fn trace_json(&self) -> Value {
+        serde_json::json!({
+            "persona_name": self.persona.persona_name,
+            "persona_version": self.persona.persona_version,
+            "persona_hash": self.persona_hash,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, Eq, PartialEq)]
@@ -155,6 +414,41 @@ pub struct StepConstraints {
     pub max_output_tokens: Option<u32>,
     #[serde(default)]
     pub timeout_ms: Option<u64>,
+    /// Wall-clock limit on the step's provider calls, retries and fallbacks included; a step
+    /// that outruns it is abandoned as `timed_out`. Time spent on gates does not count.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>,
+    /// Total provider attempts for the step, including the first; transient provider failures
+    /// are retried until this is reached. Absent means a single attempt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_attempts: Option<u32>,
+    /// Whether retries rebuild context at the retry time or reuse the first attempt's context.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_context: Option<RetryContextMode>,
+    /// Embed a deterministic summary of trust gating in the step request so the agent knows
+    /// its context was filtered.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explain_trust_gating: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryContextMode {
+    /// Reuse the context packages of the first attempt verbatim (deterministic retries).
+    #[default]
+    Reuse,
+    /// Rebuild context packages with `as_of` set to the retry time (fresh retries).
+    Refresh,
+}
+
+impl RetryContextMode {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Reuse => "reuse",
+            Self::Refresh => "refresh",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
@@ -164,12 +458,112 @@ pub struct GatePointDefinition {
     pub gate_kind: GateKind,
     #[serde(default)]
     pub required: bool,
+    /// Rules under which a human gate is approved without asking a person; the first matching
+    /// rule wins.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub auto_approve: Vec<DelegatedApprovalRule>,
+    /// What a human gate decides in non-interactive runs; `reject` when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_non_interactive: Option<NonInteractiveGateAction>,
+    /// Identities (API token labels or SSO emails) allowed to resolve the gate once it is
+    /// deferred; empty allows anyone. A non-empty list only accepts verified identities.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub approvers: Vec<String>,
+    /// Endpoint notified when the gate is deferred; it may answer through the signed gate
+    /// callback.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<GateWebhook>,
+}
+
+/// Webhook of a human gate. Notifications and callbacks are signed with HMAC-SHA256 under the
+/// secret held in the `secret_env` environment variable, so the secret never enters the
+/// workflow hash or the trace.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct GateWebhook {
+    pub url: String,
+    pub secret_env: String,
+}
+
+impl GateWebhook {
+    /// # Errors
+    /// Returns an error when `secret_env` is unset or empty.
+    pub fn secret(&self) -> Result<String> {
+        std::env::var(&self.secret_env)
+            .ok()
+            .filter(|secret| !secret.is_empty())
+            .ok_or_else(|| anyhow!("gate webhook secret {} is not set", self.secret_env))
+    }
+}
+
+/// Header carrying `sha256=<hex>` HMAC-SHA256 signatures of gate notifications and callbacks.
+pub const GATE_SIGNATURE_HEADER: &str = "X-Gate-Signature";
+
+/// Hex HMAC-SHA256 of `body` under a gate webhook's `secret`.
+#[must_use]
+pub fn sign_gate_payload(secret: &str, body: &[u8]) -> String {
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret.as_bytes());
+    hex::encode(ring::hmac::sign(&key, body).as_ref())
+}
+
+/// Check a `sha256=<hex>` [`GATE_SIGNATURE_HEADER`] value against `body` in constant time.
+#[must_use]
+pub fn verify_gate_signature(secret: &str, body: &[u8], signature: &str) -> bool {
+    let Some(tag) = signature
+        .trim()
+        .strip_prefix("sha256=")
+        .and_then(|hex_tag| hex::decode(hex_tag).ok())
+    else {
+        return false;
+    };
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret.as_bytes());
+    ring::hmac::verify(&key, body, &tag).is_ok()
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum NonInteractiveGateAction {
+    #[default]
+    Reject,
+    Approve,
+    /// Queue the gate for a person to resolve later; a required deferred gate holds its step as
+    /// `deferred`.
+    Defer,
+}
+
+impl NonInteractiveGateAction {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Reject => "reject",
+            Self::Approve => "approve",
+            Self::Defer => "defer",
+        }
+    }
+}
+
+/// Conditions for approving a human gate on a person's behalf. Every condition that is set must
+/// hold; a rule with no conditions always matches.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DelegatedApprovalRule {
+    pub rule_name: String,
+    /// Trust statuses every included injected memory must have (e.g. `validated`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trust_statuses: Vec<String>,
+    /// Upper bound on the step's estimated token cost: prompt size plus `max_output_tokens`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_estimated_cost_tokens: Option<u64>,
+    /// Upper bound on the number of context items injected into the step.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_context_items: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct WorkflowStepDefinition {
     pub step_key: String,
+    /// Agent the step invokes; empty for a `workflow_call` step.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub agent_name: String,
     #[serde(default)]
     pub task: Value,
@@ -177,10 +571,166 @@ pub struct WorkflowStepDefinition {
     pub depends_on: Vec<String>,
     #[serde(default)]
     pub condition: Option<String>,
+    /// [`StepCondition`] on a dependency's output; the step is skipped when it does not hold.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<String>,
+    /// [`StepForeach`] naming an array in a dependency's output; the step runs once per item.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub foreach: Option<String>,
+    /// Re-invoke the step with its previous output until its output converges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iterate: Option<StepIteration>,
+    /// Run another workflow as a nested run instead of invoking an agent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workflow_call: Option<StepWorkflowCall>,
+    /// Run the task once per agent and select one output instead of invoking a single agent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vote: Option<StepVote>,
     #[serde(default)]
     pub gate_points: Vec<String>,
     #[serde(default)]
     pub constraints: StepConstraints,
+    /// Persona override for this step; defaults to the agent's persona.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persona: Option<String>,
+    /// Prompt template reference in `<name>.<version>` form, resolved from the trace store's
+    /// template library during step preparation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_ref: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scoring: Option<ResponseScoringSpec>,
+    /// Memories the step expects in its injected context, checked after trust and permission
+    /// gating.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub memory_dependencies: Vec<MemoryDependency>,
+}
+
+/// A memory a step declares it depends on, named either by `memory_id` (an exact memory id or
+/// a `prefix*` pattern) or by `tag`; the dependency holds when at least one injected item matches.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct MemoryDependency {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub memory_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub on_missing: MissingDependencyAction,
+}
+
+impl MemoryDependency {
+    /// Require exactly one of `memory_id` or `tag`, and reject malformed tags, empty patterns,
+    /// a `*` anywhere but the end, and exact ids that are not ULIDs.
+    ///
+    /// # Errors
+    /// Returns an error describing the malformed pattern.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(tag) = &self.tag {
+            if !self.memory_id.is_empty() {
+                return Err(anyhow!(
+                    "memory dependency may name a memory_id or a tag, not both (tag {tag})"
+                ));
+            }
+            return validate_memory_tag(tag)
+                .map_err(|err| anyhow!("memory dependency tag is invalid: {err}"));
+        }
+        ensure_non_empty("memory_dependencies.memory_id", &self.memory_id)?;
+        match self.memory_id.find('*') {
+            Some(index) if index + 1 != self.memory_id.len() || index == 0 => Err(anyhow!(
+                "memory dependency pattern {} may only end in a single '*' after a prefix",
+                self.memory_id
+            )),
+            Some(_) => Ok(()),
+            None => Ulid::from_string(&self.memory_id)
+                .map(|_| ())
+                .map_err(|err| {
+                    anyhow!(
+                        "memory dependency {} is not a memory id: {err}",
+                        self.memory_id
+                    )
+                }),
+        }
+    }
+
+    /// Whether `memory_id` satisfies an id-based dependency; tag dependencies never match here.
+    #[must_use]
+    pub fn matches(&self, memory_id: MemoryId) -> bool {
+        if self.tag.is_some() {
+            return false;
+        }
+        let memory_id = memory_id.to_string();
+        match self.memory_id.strip_suffix('*') {
+            Some(prefix) => memory_id.starts_with(prefix),
+            None => memory_id == self.memory_id,
+        }
+    }
+
+    /// Whether an injected context item satisfies this dependency by id or by tag.
+    #[must_use]
+    pub fn matches_item(&self, item: &ContextItem) -> bool {
+        match &self.tag {
+            Some(tag) => item.tags.contains(tag),
+            None => self.matches(item.memory_id),
+        }
+    }
+}
+
+/// What happens to a step whose declared memory dependency was not injected.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MissingDependencyAction {
+    /// Fail the step before any provider call.
+    #[default]
+    Fail,
+    /// Record a `memory_dependency_missing` warning and run the step anyway.
+    Warn,
+}
+
+impl MissingDependencyAction {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Fail => "fail",
+            Self::Warn => "warn",
+        }
+    }
+}
+
+/// Checks applied to a step's output by the configured response scorer. Every configured check
+/// must pass for the verdict to pass.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ResponseScoringSpec {
+    /// Regular expression the output message must match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regex: Option<String>,
+    /// Value the output payload must equal exactly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<Value>,
+    /// Secondary provider asked to judge the output; its response payload must carry
+    /// `passed: true`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub judge: Option<ProviderBinding>,
+    /// Log a success or failure outcome event for each injected memory based on the verdict.
+    #[serde(default)]
+    pub log_outcomes: bool,
+}
+
+/// Result of one scoring check.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct ScoreCheck {
+    pub check: String,
+    pub passed: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Verdict of a response scorer on a step output.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct ScoreVerdict {
+    pub scorer: String,
+    pub passed: bool,
+    pub checks: Vec<ScoreCheck>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, Eq, PartialEq)]
@@ -188,6 +738,95 @@ pub struct WorkflowStepDefinition {
 pub struct WorkflowDefaults {
     #[serde(default)]
     pub non_interactive: bool,
+    /// Refuse to start runs of this workflow when no trust gate source is configured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub require_trust_gating: Option<bool>,
+    /// Fail steps whose agent sets `fail_on_permission_prune` when permission rules prune their
+    /// context, instead of continuing with a `fail_on_permission_prune_ignored` warning.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enforce_permission_prune: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub taint: Option<TaintPolicy>,
+}
+
+/// Which injected context taints a step, and the human gate a tainted step must pass before its
+/// outputs reach an external provider or are proposed as memory writes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TaintPolicy {
+    /// Context package sources (`ContextPackageEnvelope::source`) whose items are sensitive.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sensitive_sources: Vec<String>,
+    /// Memory tags that mark an injected item as sensitive.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sensitive_tags: Vec<String>,
+    /// Human gate decided, as if required, for tainted steps.
+    pub elevated_gate: String,
+}
+
+impl TaintPolicy {
+    /// Require at least one sensitive source or tag, well-formed tags, and an elevated gate name.
+    ///
+    /// # Errors
+    /// Returns an error describing the first problem found.
+    pub fn validate(&self) -> Result<()> {
+        ensure_non_empty("defaults.taint.elevated_gate", &self.elevated_gate)?;
+        if self.sensitive_sources.is_empty() && self.sensitive_tags.is_empty() {
+            return Err(anyhow!(
+                "defaults.taint must list sensitive_sources or sensitive_tags"
+            ));
+        }
+        for source in &self.sensitive_sources {
+            ensure_non_empty("defaults.taint.sensitive_sources", source)?;
+        }
+        for tag in &self.sensitive_tags {
+            validate_memory_tag(tag).map_err(|err| anyhow!("defaults.taint: {err}"))?;
+        }
+        Ok(())
+    }
+
+    /// Why `source`/`item` is sensitive under this policy: `source:<name>` and `tag:<tag>`
+    /// entries, empty when it is not.
+    #[must_use]
+    pub fn sensitive_reasons(&self, source: &str, item: &ContextItem) -> Vec<String> {
+        let mut reasons = Vec::new();
+        if self
+            .sensitive_sources
+            .iter()
+            .any(|sensitive| sensitive == source)
+        {
+            reasons.push(format!("source:{source}"));
+        }
+        for tag in &item.tags {
+            if self.sensitive_tags.contains(tag) {
+                reasons.push(format!("tag:{tag}"));
+            }
+        }
+        reasons
+    }
+}
+
+/// Taint recorded on a step: the sensitive items it was given and the tainted upstream steps it
+/// consumed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Eq, PartialEq)]
+pub struct StepTaint {
+    /// Injected memory versions the policy marks sensitive.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub memory_version_ids: Vec<MemoryVersionId>,
+    /// Distinct `source:<name>`/`tag:<tag>` reasons behind `memory_version_ids`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reasons: Vec<String>,
+    /// Step keys of tainted dependencies whose outputs this step consumes.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inherited_from: Vec<String>,
+}
+
+impl StepTaint {
+    /// Whether the step saw sensitive context directly or through a dependency.
+    #[must_use]
+    pub fn is_tainted(&self) -> bool {
+        !self.memory_version_ids.is_empty() || !self.inherited_from.is_empty()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
@@ -202,6 +841,8 @@ pub struct NormalizedWorkflow {
     pub gates: Vec<GatePointDefinition>,
     #[serde(default)]
     pub defaults: WorkflowDefaults,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub personas: Vec<PersonaDefinition>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
@@ -213,6 +854,78 @@ pub struct NormalizedWorkflowEnvelope {
     pub normalized_json: Value,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkflowChangeCategory {
+    StepAdded,
+    StepRemoved,
+    DependenciesChanged,
+    StepChanged,
+    AgentAdded,
+    AgentRemoved,
+    ProviderChanged,
+    PermissionsChanged,
+    AgentChanged,
+    GateAdded,
+    GateRemoved,
+    GateChanged,
+    PersonaChanged,
+}
+
+/// One structural change between two normalized workflows. `subject` is the step, agent, gate, or
+/// persona name; `field` names the changed attribute where the category covers several.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorkflowChange {
+    pub category: WorkflowChangeCategory,
+    pub subject: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    pub old: Value,
+    pub new: Value,
+}
+
+/// Step whose behaviour may differ after an upgrade. Reasons are `added`, `removed`,
+/// `step.<field>`, `dependencies`, `agent.<field>`, `gate.<gate_name>`, `persona.<name>`, or
+/// `upstream.<step_key>` when a dependency is affected.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct AffectedStep {
+    pub step_key: String,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorkflowDiff {
+    pub old_workflow_hash: String,
+    pub new_workflow_hash: String,
+    pub changes: Vec<WorkflowChange>,
+    /// Steps of the new workflow in declaration order, followed by removed steps.
+    pub affected_steps: Vec<AffectedStep>,
+}
+
+/// Affected step cross-referenced with recent runs of the old workflow. `estimate` is
+/// `new_step`, `removed_step`, `no_history`, `not_exercised` (only ever skipped), or
+/// `behavior_change_expected`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StepUpgradeImpact {
+    pub step_key: String,
+    pub reasons: Vec<String>,
+    pub estimate: String,
+    pub runs_observed: usize,
+    /// Final step status counts over the considered runs.
+    pub statuses: std::collections::BTreeMap<String, usize>,
+    /// Decision counts per gate name over the considered runs.
+    pub gate_decisions:
+        std::collections::BTreeMap<String, std::collections::BTreeMap<String, usize>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorkflowUpgradeImpact {
+    pub diff: WorkflowDiff,
+    /// Most recent runs of the old workflow hash, newest first.
+    pub runs_considered: Vec<RunId>,
+    pub steps: Vec<StepUpgradeImpact>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ContextPackageEnvelope {
     pub package_slot: usize,
@@ -236,6 +949,38 @@ pub struct TrustGateAttachment {
     pub source: String,
 }
 
+/// One step and the agent it runs as, defined outside a workflow (for context previews).
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct StandaloneStepDefinition {
+    pub agent: AgentDefinition,
+    pub step: WorkflowStepDefinition,
+}
+
+/// What context gating did with one retrieved item: `injected`, `pruned_by_permissions`,
+/// `excluded_by_trust`, or `not_selected` (excluded by retrieval itself).
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct ContextItemDecision {
+    pub package_slot: usize,
+    pub memory_id: MemoryId,
+    pub version: u32,
+    pub memory_version_id: MemoryVersionId,
+    pub record_type: RecordType,
+    pub decision: String,
+    pub reasons: Vec<String>,
+}
+
+/// The context a step would receive at `as_of`, with a decision for every retrieved item.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StepContextPreview {
+    pub step_key: String,
+    pub agent_name: String,
+    pub as_of: DateTimeUtc,
+    pub injected_context_packages: Vec<ContextPackageEnvelope>,
+    pub item_decisions: Vec<ContextItemDecision>,
+    pub trust_gate_attachments: Vec<TrustGateAttachment>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub struct EffectivePermissions {
     pub allowed_record_types: Vec<RecordType>,
@@ -243,6 +988,25 @@ pub struct EffectivePermissions {
     pub max_context_items: Option<u32>,
     pub can_propose_memory_writes: bool,
     pub fail_on_permission_prune: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_record_tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub denied_record_tags: Vec<String>,
+}
+
+impl EffectivePermissions {
+    /// Whether an item with `tags` passes the tag rules: no denied tag, and at least one allowed
+    /// tag when `allowed_record_tags` is set.
+    #[must_use]
+    pub fn permits_tags(&self, tags: &[String]) -> bool {
+        if tags.iter().any(|tag| self.denied_record_tags.contains(tag)) {
+            return false;
+        }
+        self.allowed_record_tags.is_empty()
+            || tags
+                .iter()
+                .any(|tag| self.allowed_record_tags.contains(tag))
+    }
 }
 
 impl From<&AgentPermissions> for EffectivePermissions {
@@ -253,6 +1017,8 @@ impl From<&AgentPermissions> for EffectivePermissions {
             max_context_items: value.max_context_items,
             can_propose_memory_writes: value.can_propose_memory_writes,
             fail_on_permission_prune: value.fail_on_permission_prune,
+            allowed_record_tags: value.allowed_record_tags.clone(),
+            denied_record_tags: value.denied_record_tags.clone(),
         }
     }
 }
@@ -269,6 +1035,14 @@ pub struct StepRequest {
     pub trust_gate_attachments: Vec<TrustGateAttachment>,
     pub effective_permissions: EffectivePermissions,
     pub constraints: StepConstraints,
+    /// Trust gating summary embedded when `constraints.explain_trust_gating` is set; covered by
+    /// `input_hash`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trust_gate_summary: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persona: Option<AppliedPersona>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<ResolvedPrompt>,
     pub input_hash: String,
 }
 
@@ -326,6 +1100,9 @@ pub struct ProviderCallRecord {
     pub ended_at: DateTimeUtc,
     pub status: String,
     pub error_text: Option<String>,
+    /// Persona applied to the call (`AppliedPersona::trace_json`), if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persona_json: Option<Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -339,6 +1116,12 @@ pub struct StepResult {
     pub gate_decisions: Vec<GateDecisionRecord>,
     pub output_hash: String,
     pub error: Option<ErrorEnvelope>,
+    /// State hashes of the step's checkpoints, in order, so the output hash covers them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checkpoint_hashes: Vec<String>,
+    /// Response scorer verdict, when the step declares `scoring`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<ScoreVerdict>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
@@ -357,6 +1140,24 @@ pub struct TraceEvent {
     pub event_hash: String,
 }
 
+/// The `workflow_call` step of a parent run that started a nested run.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ParentStep {
+    pub run_id: RunId,
+    pub step_id: StepId,
+    pub step_key: String,
+}
+
+/// Run and step a step rerun re-executes from: the step and everything downstream of it run
+/// again, while upstream steps reuse the source run's outputs.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct StepRerun {
+    pub source_run_id: RunId,
+    pub step_key: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub struct RunRecord {
     pub run_id: RunId,
@@ -375,6 +1176,109 @@ pub struct RunRecord {
     pub manifest_hash: Option<String>,
     pub manifest_signature: Option<String>,
     pub manifest_signature_status: String,
+    /// Free-form key/value labels (team, environment, ...) for slicing shared trace stores.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rerun_of: Option<StepRerun>,
+    /// The `workflow_call` step that started this run as a nested run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_step: Option<ParentStep>,
+}
+
+/// Digest of the memory versions injected into one step; see [`memory_version_set_digest`].
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct StepMemoryDigest {
+    pub step_index: usize,
+    pub step_key: String,
+    pub memory_versions: usize,
+    pub digest: String,
+}
+
+/// Per-step injected-memory digests of a run, rolled up into `root`. The run manifest carries
+/// this object, so its hash commits to every step's memory version set.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RunMemoryManifest {
+    pub root: String,
+    pub steps: Vec<StepMemoryDigest>,
+}
+
+impl RunMemoryManifest {
+    /// Order `steps` by step index and compute their root.
+    #[must_use]
+    pub fn from_steps(mut steps: Vec<StepMemoryDigest>) -> Self {
+        steps.sort_by_key(|step| step.step_index);
+        Self {
+            root: memory_manifest_root(&steps),
+            steps,
+        }
+    }
+
+    /// Whether `root` matches the listed step digests.
+    #[must_use]
+    pub fn root_is_consistent(&self) -> bool {
+        memory_manifest_root(&self.steps) == self.root
+    }
+
+    /// Whether `memory_version_ids` is exactly the set recorded for `step_key`.
+    #[must_use]
+    pub fn step_matches(
+        &self,
+        step_key: &str,
+        memory_version_ids: impl IntoIterator<Item = MemoryVersionId>,
+    ) -> bool {
+        let digest = memory_version_set_digest(memory_version_ids);
+        self.steps
+            .iter()
+            .any(|step| step.step_key == step_key && step.digest == digest)
+    }
+}
+
+/// What produced a run: engine version and build profile, component crate versions, the linked
+/// sqlite library, OS and architecture, and the run features that were enabled. The run manifest
+/// carries it under `environment`; `hash` covers every other field, so two runs' environments
+/// compare by hash alone.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct EnvironmentFingerprint {
+    pub engine_version: String,
+    pub build_profile: String,
+    pub crate_versions: BTreeMap<String, String>,
+    pub sqlite_version: String,
+    pub os: String,
+    pub arch: String,
+    /// Run features that were switched on, sorted.
+    pub features: Vec<String>,
+    pub hash: String,
+}
+
+impl EnvironmentFingerprint {
+    /// Sort `features` and compute `hash`.
+    ///
+    /// # Errors
+    /// Returns an error if the fingerprint cannot be serialized.
+    pub fn sealed(mut self) -> Result<Self> {
+        self.features.sort();
+        self.features.dedup();
+        self.hash = self.content_hash()?;
+        Ok(self)
+    }
+
+    /// Whether `hash` matches the other fields.
+    #[must_use]
+    pub fn hash_is_consistent(&self) -> bool {
+        self.content_hash().is_ok_and(|hash| hash == self.hash)
+    }
+
+    fn content_hash(&self) -> Result<String> {
+        let mut value = serde_json::to_value(self)?;
+        if let Some(fields) = value.as_object_mut() {
+            fields.remove("hash");
+        }
+        hash_json(&value)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -393,6 +1297,29 @@ pub struct StepRecord {
     pub input_hash: String,
     pub output_hash: Option<String>,
     pub error_json: Option<Value>,
+    /// Retry policy applied to the step and one entry per retried attempt; `None` when the
+    /// step did not retry.
+    #[serde(default)]
+    pub retry_json: Option<Value>,
+    /// Hash of the prompt template resolved from the step's `prompt_ref`, if any.
+    #[serde(default)]
+    pub prompt_template_hash: Option<String>,
+    /// Provider bindings attempted for the step and the one finally used, and under
+    /// `load_balancing` the endpoint the step was assigned; `None` when the agent declares
+    /// neither fallbacks nor load balancing.
+    #[serde(default)]
+    pub provider_selection_json: Option<Value>,
+    /// Sensitive-source taint of the step's inputs, set when the workflow declares a taint policy
+    /// and the step injected sensitive context or depends on a tainted step.
+    #[serde(default)]
+    pub taint_json: Option<Value>,
+    /// Run whose succeeded step with the same workflow hash and memo key supplied this step's
+    /// output instead of a provider call.
+    #[serde(default)]
+    pub memoized_from_run: Option<RunId>,
+    /// Run-independent request key (`compute_step_memo_key`) matched by memoized runs.
+    #[serde(default)]
+    pub memo_key: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
@@ -413,10 +1340,1063 @@ pub struct WorkflowSnapshotRecord {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct StepContextPackageRecord {
     pub step_key: String,
+    /// Attempt of the step the package was injected into, from 1.
+    pub attempt: u32,
     pub envelope: ContextPackageEnvelope,
 }
 
-#[must_use]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StepProviderCallRecord {
+    pub step_key: String,
+    pub provider_call: ProviderCallRecord,
+}
+
+/// Succeeded step of an earlier run that a memoized step reuses, with the provider call that
+/// produced its output.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MemoizedStepRecord {
+    pub run_id: RunId,
+    pub step_id: StepId,
+    pub provider_call: ProviderCallRecord,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StepGateDecisionRecord {
+    pub step_key: String,
+    pub decision: GateDecisionRecord,
+}
+
+/// A recorded proposed memory write and what became of it (`applied`, `not_applied`,
+/// `discarded`, ...).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StepProposedWriteRecord {
+    pub step_key: String,
+    pub proposal_index: usize,
+    pub payload: Value,
+    pub disposition: String,
+    pub disposition_reason: Option<String>,
+}
+
+/// Provider response captured from a recorded run, replayed in place of a live provider call.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RecordedProviderResponse {
+    pub provider_name: String,
+    pub adapter_version: String,
+    pub model_id: String,
+    pub status: String,
+    pub response_json: Value,
+    pub error_text: Option<String>,
+}
+
+impl From<&ProviderCallRecord> for RecordedProviderResponse {
+    fn from(call: &ProviderCallRecord) -> Self {
+        Self {
+            provider_name: call.provider_name.clone(),
+            adapter_version: call.adapter_version.clone(),
+            model_id: call.model_id.clone(),
+            status: call.status.clone(),
+            response_json: call.response_json.clone(),
+            error_text: call.error_text.clone(),
+        }
+    }
+}
+
+pub const GOLDEN_RUN_VERSION: &str = "golden_run.v1";
+
+/// Expected outcome of a workflow run, recorded from its trace. Re-executing the workflow with the
+/// recorded provider responses must reproduce every step's status, output hash, and gate decisions.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GoldenRun {
+    pub golden_version: String,
+    pub source_run_id: RunId,
+    pub workflow_name: String,
+    pub workflow_hash: String,
+    pub as_of: DateTimeUtc,
+    pub steps: Vec<GoldenStep>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GoldenStep {
+    pub step_key: String,
+    pub status: StepStatus,
+    /// Run-independent output hash (`compute_step_content_hash`); `None` for steps that never ran.
+    pub output_hash: Option<String>,
+    pub gate_decisions: Vec<GoldenGateDecision>,
+    /// Provider responses of the step, in call order.
+    pub provider_responses: Vec<RecordedProviderResponse>,
+}
+
+/// Gate decision fields that must be reproduced; who decided and when are not compared.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GoldenGateDecision {
+    pub gate_kind: GateKind,
+    pub gate_name: String,
+    pub subject_type: String,
+    pub memory_id: Option<MemoryId>,
+    pub version: Option<u32>,
+    pub decision: GateDecision,
+    pub reason_codes: Vec<String>,
+}
+
+impl From<&GateDecisionRecord> for GoldenGateDecision {
+    fn from(record: &GateDecisionRecord) -> Self {
+        Self {
+            gate_kind: record.gate_kind.clone(),
+            gate_name: record.gate_name.clone(),
+            subject_type: record.subject_type.clone(),
+            memory_id: record.memory_id,
+            version: record.version,
+            decision: record.decision.clone(),
+            reason_codes: record.reason_codes.clone(),
+        }
+    }
+}
+
+/// One mismatch between a golden run and a re-execution. `field` is `step` when the step is
+/// missing on one side, otherwise `status`, `output_hash`, or `gate_decisions`; replay execution
+/// also reports `provider_calls` (the call count), `provider_calls[<n>].request_hash`, and
+/// `child_run` when a `workflow_call` step's nested run is missing on one side.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GoldenDifference {
+    pub step_key: String,
+    pub field: String,
+    pub expected: Value,
+    pub actual: Value,
+}
+
+/// Result of re-executing a recorded run with every provider call answered from the run's own
+/// `provider_calls` rows. The replay is a new run with `replay_of_run_id` set; it reproduced the
+/// source run when `divergences` is empty.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReplayExecutionReport {
+    pub source_run_id: RunId,
+    pub replay_run_id: RunId,
+    pub workflow_hash: String,
+    pub status: RunStatus,
+    pub provider_calls_replayed: usize,
+    pub reproduced: bool,
+    pub divergences: Vec<GoldenDifference>,
+}
+
+/// Compare a re-execution against a golden run, step by step in golden order. Steps only present
+/// in `actual` are reported after the golden steps.
+#[must_use]
+pub fn diff_golden_runs(expected: &GoldenRun, actual: &GoldenRun) -> Vec<GoldenDifference> {
+    let mut differences = Vec::new();
+    for golden in &expected.steps {
+        let Some(step) = actual
+            .steps
+            .iter()
+            .find(|step| step.step_key == golden.step_key)
+        else {
+            differences.push(GoldenDifference {
+                step_key: golden.step_key.clone(),
+                field: "step".to_string(),
+                expected: serde_json::json!("present"),
+                actual: serde_json::json!("missing"),
+            });
+            continue;
+        };
+        if step.status != golden.status {
+            differences.push(GoldenDifference {
+                step_key: golden.step_key.clone(),
+                field: "status".to_string(),
+                expected: serde_json::json!(golden.status),
+                actual: serde_json::json!(step.status),
+            });
+        }
+        if step.output_hash != golden.output_hash {
+            differences.push(GoldenDifference {
+                step_key: golden.step_key.clone(),
+                field: "output_hash".to_string(),
+                expected: serde_json::json!(golden.output_hash),
+                actual: serde_json::json!(step.output_hash),
+            });
+        }
+        if step.gate_decisions != golden.gate_decisions {
+            differences.push(GoldenDifference {
+                step_key: golden.step_key.clone(),
+                field: "gate_decisions".to_string(),
+                expected: serde_json::json!(golden.gate_decisions),
+                actual: serde_json::json!(step.gate_decisions),
+            });
+        }
+    }
+    for step in &actual.steps {
+        if !expected
+            .steps
+            .iter()
+            .any(|golden| golden.step_key == step.step_key)
+        {
+            differences.push(GoldenDifference {
+                step_key: step.step_key.clone(),
+                field: "step".to_string(),
+                expected: serde_json::json!("missing"),
+                actual: serde_json::json!("present"),
+            });
+        }
+    }
+    differences
+}
+
+/// Intermediate state of a multi-call step, recorded after each provider call so the step can
+/// resume from its last completed call instead of restarting.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StepCheckpoint {
+    pub run_id: RunId,
+    pub step_id: StepId,
+    pub checkpoint_seq: u32,
+    pub provider_call_id: Option<Ulid>,
+    pub state_json: Value,
+    pub prev_checkpoint_hash: Option<String>,
+    pub state_hash: String,
+    pub created_at: DateTimeUtc,
+}
+
+/// Named, versioned prompt template stored in the trace database. Versions are immutable once
+/// registered.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PromptTemplateRecord {
+    pub template_name: String,
+    pub template_version: String,
+    pub body: String,
+    pub template_hash: String,
+    pub created_at: DateTimeUtc,
+}
+
+impl PromptTemplateRecord {
+    /// Build a template record, hashing its name, version, and body.
+    ///
+    /// # Errors
+    /// Returns an error if the name or version is empty, or contains `.` in the name.
+    pub fn new(template_name: &str, template_version: &str, body: &str) -> Result<Self> {
+        ensure_non_empty("template_name", template_name)?;
+        ensure_non_empty("template_version", template_version)?;
+        if template_name.contains('.') || template_version.contains('.') {
+            return Err(anyhow!(
+                "template name and version must not contain `.`: {template_name}.{template_version}"
+            ));
+        }
+        let template_hash = hash_json(&serde_json::json!({
+            "template_name": template_name,
+            "template_version": template_version,
+            "body": body,
+        }))?;
+        Ok(Self {
+            template_name: template_name.to_string(),
+            template_version: template_version.to_string(),
+            body: body.to_string(),
+            template_hash,
+            created_at: now_utc(),
+        })
+    }
+
+    #[must_use]
+    pub fn prompt_ref(&self) -> String {
+        format!("{}.{}", self.template_name, self.template_version)
+    }
+}
+
+/// Prompt template resolved for a step request.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ResolvedPrompt {
+    pub prompt_ref: String,
+    pub template_hash: String,
+    pub body: String,
+}
+
+/// Split a `prompt_ref` such as `summarize.v3` into template name and version.
+///
+/// # Errors
+/// Returns an error if the reference is not of the form `<name>.<version>`.
+pub fn parse_prompt_ref(prompt_ref: &str) -> Result<(&str, &str)> {
+    match prompt_ref.rsplit_once('.') {
+        Some((name, version)) if !name.is_empty() && !version.is_empty() => Ok((name, version)),
+        _ => Err(anyhow!(
+            "invalid prompt_ref `{prompt_ref}`; expected <name>.<version>"
+        )),
+    }
+}
+
+/// Duration statistics for one `(workflow_hash, step_key)` across succeeded runs.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StepDurationStats {
+    pub workflow_hash: String,
+    pub step_key: String,
+    pub sample_count: u64,
+    pub mean_ms: u64,
+    pub min_ms: u64,
+    pub max_ms: u64,
+    pub stddev_ms: u64,
+    /// Duration observed in the most recently finished succeeded run.
+    pub last_ms: u64,
+    pub updated_at: DateTimeUtc,
+}
+
+/// Resource usage of one finished run, aggregated from its trace rows so cost can be attributed
+/// per workflow (or per caller via `external_correlation_id`) without joining the trace tables.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RunUsage {
+    pub run_id: RunId,
+    pub workflow_name: String,
+    pub workflow_version: String,
+    pub workflow_hash: String,
+    pub external_correlation_id: Option<String>,
+    pub status: RunStatus,
+    pub provider_calls: u64,
+    /// Tokens reported by providers; calls that report no counts contribute zero.
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub wall_time_ms: u64,
+    pub provider_latency_ms: u64,
+    /// Rows the run wrote across the per-run trace tables, including its own `runs` row.
+    pub trace_rows_written: u64,
+    /// Serialized size of the context packages injected into step requests.
+    pub context_bytes_injected: u64,
+    pub recorded_at: DateTimeUtc,
+}
+
+/// Window over gate decision or event times: `since` is inclusive, `until` exclusive, and `None`
+/// leaves that side open.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DecisionWindow {
+    pub since: Option<DateTimeUtc>,
+    pub until: Option<DateTimeUtc>,
+}
+
+impl DecisionWindow {
+    #[must_use]
+    pub fn contains(&self, at: DateTimeUtc) -> bool {
+        self.since.map_or(true, |since| at >= since) && self.until.map_or(true, |until| at < until)
+    }
+}
+
+/// How often one reason code drove a blocking gate decision (`rejected` or `pruned`) for one
+/// workflow and gate kind within a window.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReasonCodeStat {
+    pub workflow_name: String,
+    pub gate_kind: GateKind,
+    pub decision: GateDecision,
+    pub reason_code: String,
+    pub count: u64,
+    pub first_decided_at: DateTimeUtc,
+    pub last_decided_at: DateTimeUtc,
+}
+
+/// Reason-code breakdown of gate decisions in a window, for reviewing whether gate thresholds
+/// are too strict or too lax.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReasonCodeStats {
+    pub window: DecisionWindow,
+    /// Gate decisions of every outcome in the window.
+    pub decisions_evaluated: u64,
+    /// Decisions in the window that rejected or pruned their subject.
+    pub decisions_blocking: u64,
+    /// Most frequent first; a decision carrying several codes counts once under each.
+    pub stats: Vec<ReasonCodeStat>,
+}
+
+/// How often one warning code was emitted by runs of one workflow within a window.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WarningStat {
+    pub workflow_name: String,
+    /// The event's `payload.warning_code`, or `unspecified` when it has none.
+    pub warning_code: String,
+    pub count: u64,
+    /// Distinct runs that emitted the warning.
+    pub runs: u64,
+    pub first_occurred_at: DateTimeUtc,
+    pub last_occurred_at: DateTimeUtc,
+}
+
+/// Cross-run breakdown of `warning` trace events, so warnings that do not fail a run still get
+/// noticed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WarningStats {
+    pub window: DecisionWindow,
+    pub warnings_total: u64,
+    /// Most frequent first.
+    pub stats: Vec<WarningStat>,
+}
+
+/// Warning code recorded for `warning` events whose payload carries no `warning_code`.
+pub const UNSPECIFIED_WARNING_CODE: &str = "unspecified";
+
+/// A human gate a non-interactive run queued instead of rejecting, with its resolution once a
+/// person decided it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DeferredGateRecord {
+    pub run_id: RunId,
+    pub step_id: StepId,
+    pub step_key: String,
+    pub gate_name: String,
+    pub required: bool,
+    pub deferred_at: DateTimeUtc,
+    /// The gate's `approvers` when it was deferred.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub approvers: Vec<String>,
+    /// The gate's `webhook` when it was deferred; signed callbacks are verified against it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<GateWebhook>,
+    pub resolution: Option<DeferredGateResolution>,
+}
+
+impl DeferredGateRecord {
+    /// Check that `resolution` may decide this gate: a decision made on behalf of someone needs
+    /// an active delegation from them among `delegations`, and a gate with approvers needs a
+    /// verified decider who is, or acts for, one of them.
+    ///
+    /// # Errors
+    /// Returns an error naming the identity that is not allowed to decide.
+    pub fn authorize(
+        &self,
+        resolution: &DeferredGateResolution,
+        delegations: &[ApprovalDelegation],
+    ) -> Result<()> {
+        if let Some(principal) = resolution.on_behalf_of.as_deref() {
+            if !delegations.iter().any(|delegation| {
+                delegation.covers(
+                    principal,
+                    &resolution.decided_by,
+                    &self.gate_name,
+                    resolution.decided_at,
+                )
+            }) {
+                return Err(anyhow!(
+                    "{} holds no active delegation from {principal} for gate {}",
+                    resolution.decided_by,
+                    self.gate_name
+                ));
+            }
+        }
+        if self.approvers.is_empty() {
+            return Ok(());
+        }
+        if resolution.auth_method == DeciderAuthMethod::Unverified {
+            return Err(anyhow!(
+                "gate {} of step {} only accepts verified approvers; {} was not authenticated",
+                self.gate_name,
+                self.step_key,
+                resolution.decided_by
+            ));
+        }
+        let principal = resolution
+            .on_behalf_of
+            .as_deref()
+            .unwrap_or(&resolution.decided_by);
+        if !self
+            .approvers
+            .iter()
+            .any(|approver| approver.eq_ignore_ascii_case(principal))
+        {
+            return Err(anyhow!(
+                "{principal} is not an approver of gate {} of step {} (approvers: {})",
+                self.gate_name,
+                self.step_key,
+                self.approvers.join(", ")
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DeferredGateResolution {
+    pub approved: bool,
+    /// Identity of the decider, established as `auth_method` says.
+    pub decided_by: String,
+    #[serde(default)]
+    pub auth_method: DeciderAuthMethod,
+    /// Approver the decider acted for under an [`ApprovalDelegation`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_behalf_of: Option<String>,
+    pub notes: Option<String>,
+    pub decided_at: DateTimeUtc,
+}
+
+/// How the identity that resolved a deferred gate was established.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeciderAuthMethod {
+    /// A name the decider supplied, e.g. `gates resolve --decided-by`.
+    #[default]
+    Unverified,
+    /// The label of the bearer API token the decision was submitted with.
+    Token,
+    /// The email the SSO proxy in front of the service asserted.
+    Sso,
+    /// The name in a gate callback signed with the gate's webhook secret.
+    Webhook,
+}
+
+impl DeciderAuthMethod {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Unverified => "unverified",
+            Self::Token => "token",
+            Self::Sso => "sso",
+            Self::Webhook => "webhook",
+        }
+    }
+
+    /// # Errors
+    /// Returns an error for an unknown method name.
+    pub fn parse(value: &str) -> Result<Self> {
+        match value {
+            "unverified" => Ok(Self::Unverified),
+            "token" => Ok(Self::Token),
+            "sso" => Ok(Self::Sso),
+            "webhook" => Ok(Self::Webhook),
+            other => Err(anyhow!("unknown decider auth method: {other}")),
+        }
+    }
+}
+
+/// Lets `delegate` resolve deferred gates on behalf of the approver `delegator`, limited to
+/// `gate_name` when set, until it expires or is revoked.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ApprovalDelegation {
+    pub delegation_id: String,
+    pub delegator: String,
+    pub delegate: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gate_name: Option<String>,
+    pub created_at: DateTimeUtc,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTimeUtc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<DateTimeUtc>,
+}
+
+impl ApprovalDelegation {
+    /// Whether this delegation lets `delegate` act for `delegator` on `gate_name` at `at`.
+    #[must_use]
+    pub fn covers(
+        &self,
+        delegator: &str,
+        delegate: &str,
+        gate_name: &str,
+        at: DateTimeUtc,
+    ) -> bool {
+        self.delegator.eq_ignore_ascii_case(delegator)
+            && self.delegate.eq_ignore_ascii_case(delegate)
+            && self
+                .gate_name
+                .as_deref()
+                .map_or(true, |gate| gate == gate_name)
+            && self.created_at <= at
+            && self.expires_at.map_or(true, |expires_at| at < expires_at)
+            && self.revoked_at.map_or(true, |revoked_at| at < revoked_at)
+    }
+}
+
+/// One step into an event payload: an object key or an array index.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadPathSegment {
+    Key(String),
+    Index(usize),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadComparison {
+    Eq,
+    Ne,
+}
+
+/// Filter on one value inside a trace event payload, written `payload.<path> == <literal>` or
+/// `payload.<path> != <literal>`, e.g. `payload.items[0].code == "context_pruned"`. Both
+/// comparisons only match payloads that contain the path.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PayloadFilter {
+    pub path: Vec<PayloadPathSegment>,
+    pub comparison: PayloadComparison,
+    /// JSON string, number, boolean, or null.
+    pub value: Value,
+}
+
+impl PayloadFilter {
+    /// Parse a filter expression. Keys are ASCII letters, digits, `_`, or `-`.
+    ///
+    /// # Errors
+    /// Returns an error when the expression has no path, an unknown operator, or a literal that
+    /// is not a JSON scalar.
+    pub fn parse(expression: &str) -> Result<Self> {
+        let invalid = |reason: &str| anyhow!("invalid payload filter `{expression}`: {reason}");
+        let rest = expression
+            .trim_start()
+            .strip_prefix("payload")
+            .ok_or_else(|| invalid("expected payload.<path> == <value>"))?;
+        let (path, rest) = parse_value_path(rest, &invalid)?;
+        if path.is_empty() {
+            return Err(invalid("expected a path after `payload`"));
+        }
+        let (comparison, value) = parse_scalar_comparison(rest, &invalid)?;
+        Ok(Self {
+            path,
+            comparison,
+            value,
+        })
+    }
+
+    /// Numbers compare by value, so `1` matches `1.0`.
+    #[must_use]
+    pub fn matches(&self, payload: &Value) -> bool {
+        value_at_path(payload, &self.path)
+            .is_some_and(|observed| self.comparison.holds(observed, &self.value))
+    }
+}
+
+impl PayloadComparison {
+    /// Whether `observed` compares to `literal` this way; numbers compare by value.
+    #[must_use]
+    pub fn holds(self, observed: &Value, literal: &Value) -> bool {
+        let equal = match (observed.as_f64(), literal.as_f64()) {
+            (Some(left), Some(right)) => left.total_cmp(&right).is_eq(),
+            _ => observed == literal,
+        };
+        match self {
+            Self::Eq => equal,
+            Self::Ne => !equal,
+        }
+    }
+}
+
+/// Parse `.key` and `[index]` segments off the front of `rest`. Keys are ASCII letters, digits,
+/// `_`, or `-`.
+fn parse_value_path<'e>(
+    mut rest: &'e str,
+    invalid: &dyn Fn(&str) -> anyhow::Error,
+) -> Result<(Vec<PayloadPathSegment>, &'e str)> {
+    let mut path = Vec::new();
+    loop {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+                .unwrap_or(after.len());
+            if end == 0 {
+                return Err(invalid("expected a key after `.`"));
+            }
+            path.push(PayloadPathSegment::Key(after[..end].to_string()));
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let (index, tail) = after
+                .split_once(']')
+                .ok_or_else(|| invalid("unclosed `[`"))?;
+            let index = index
+                .parse::<usize>()
+                .map_err(|_| invalid("array index must be a non-negative integer"))?;
+            path.push(PayloadPathSegment::Index(index));
+            rest = tail;
+        } else {
+            return Ok((path, rest));
+        }
+    }
+}
+
+/// Parse `== <literal>` or `!= <literal>`, where the literal is a JSON scalar.
+fn parse_scalar_comparison(
+    rest: &str,
+    invalid: &dyn Fn(&str) -> anyhow::Error,
+) -> Result<(PayloadComparison, Value)> {
+    let rest = rest.trim_start();
+    let (comparison, literal) = if let Some(literal) = rest.strip_prefix("==") {
+        (PayloadComparison::Eq, literal)
+    } else if let Some(literal) = rest.strip_prefix("!=") {
+        (PayloadComparison::Ne, literal)
+    } else {
+        return Err(invalid("expected `==` or `!=`"));
+    };
+    let value: Value = serde_json::from_str(literal.trim())
+        .map_err(|err| invalid(&format!("value is not a JSON literal: {err}")))?;
+    if value.is_object() || value.is_array() {
+        return Err(invalid("value must be a string, number, boolean, or null"));
+    }
+    Ok((comparison, value))
+}
+
+fn value_at_path<'v>(value: &'v Value, path: &[PayloadPathSegment]) -> Option<&'v Value> {
+    path.iter()
+        .try_fold(value, |current, segment| match segment {
+            PayloadPathSegment::Key(key) => current.get(key.as_str()),
+            PayloadPathSegment::Index(index) => current.get(*index),
+        })
+}
+
+/// A step's `when` condition on the output of one of its dependencies, written
+/// `steps.<step_key>.<path> == <literal>` or `!=`, e.g. `steps.triage.payload.route == "billing"`.
+/// The path starts at the dependency's output envelope (`message`, `payload`); a path the
+/// output does not contain fails the condition.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StepCondition {
+    pub step_key: String,
+    pub path: Vec<PayloadPathSegment>,
+    pub comparison: PayloadComparison,
+    /// JSON string, number, boolean, or null.
+    pub value: Value,
+}
+
+impl StepCondition {
+    /// Parse a `when` expression.
+    ///
+    /// # Errors
+    /// Returns an error when the expression does not name a step and an output path, has an
+    /// unknown operator, or compares against a literal that is not a JSON scalar.
+    pub fn parse(expression: &str) -> Result<Self> {
+        let invalid = |reason: &str| anyhow!("invalid when condition `{expression}`: {reason}");
+        let rest = expression
+            .trim_start()
+            .strip_prefix("steps")
+            .ok_or_else(|| invalid("expected steps.<step_key>.<path> == <value>"))?;
+        let (mut path, rest) = parse_value_path(rest, &invalid)?;
+        if path.len() < 2 {
+            return Err(invalid(
+                "expected a step key and an output path after `steps`",
+            ));
+        }
+        let PayloadPathSegment::Key(step_key) = path.remove(0) else {
+            return Err(invalid("expected a step key after `steps`"));
+        };
+        let (comparison, value) = parse_scalar_comparison(rest, &invalid)?;
+        Ok(Self {
+            step_key,
+            path,
+            comparison,
+            value,
+        })
+    }
+
+    /// The value at the condition's path in `output`, a serialized [`StepOutputEnvelope`].
+    #[must_use]
+    pub fn observe<'v>(&self, output: &'v Value) -> Option<&'v Value> {
+        value_at_path(output, &self.path)
+    }
+
+    /// Whether the condition holds for the value [`Self::observe`] found.
+    #[must_use]
+    pub fn holds(&self, observed: Option<&Value>) -> bool {
+        observed.is_some_and(|observed| self.comparison.holds(observed, &self.value))
+    }
+}
+
+/// A step's `foreach` source, written `steps.<step_key>.<path>`, e.g.
+/// `steps.plan.payload.tickets`. The path starts at the dependency's output envelope and must
+/// lead to an array; the step then runs as one child step per item, keyed `<step_key>[<index>]`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StepForeach {
+    pub step_key: String,
+    pub path: Vec<PayloadPathSegment>,
+}
+
+impl StepForeach {
+    /// Parse a `foreach` expression.
+    ///
+    /// # Errors
+    /// Returns an error when the expression does not name a step and an output path.
+    pub fn parse(expression: &str) -> Result<Self> {
+        let invalid = |reason: &str| anyhow!("invalid foreach source `{expression}`: {reason}");
+        let rest = expression
+            .trim_start()
+            .strip_prefix("steps")
+            .ok_or_else(|| invalid("expected steps.<step_key>.<path>"))?;
+        let (mut path, rest) = parse_value_path(rest, &invalid)?;
+        if !rest.trim().is_empty() {
+            return Err(invalid("unexpected input after the output path"));
+        }
+        if path.len() < 2 {
+            return Err(invalid(
+                "expected a step key and an output path after `steps`",
+            ));
+        }
+        let PayloadPathSegment::Key(step_key) = path.remove(0) else {
+            return Err(invalid("expected a step key after `steps`"));
+        };
+        Ok(Self { step_key, path })
+    }
+
+    /// The items at the source path in `output`, a serialized [`StepOutputEnvelope`], or `None`
+    /// when the output has no array there.
+    #[must_use]
+    pub fn items<'v>(&self, output: &'v Value) -> Option<&'v Vec<Value>> {
+        value_at_path(output, &self.path).and_then(Value::as_array)
+    }
+
+    /// Task of the child step for `item`: the `foreach` step's task with `item` and
+    /// `item_index` added.
+    #[must_use]
+    pub fn child_task(task: &Value, item: &Value, index: usize) -> Value {
+        let mut task = match task {
+            Value::Object(fields) => fields.clone(),
+            Value::Null => serde_json::Map::new(),
+            other => [("task".to_string(), other.clone())].into_iter().collect(),
+        };
+        task.insert("item".to_string(), item.clone());
+        task.insert("item_index".to_string(), Value::from(index));
+        Value::Object(task)
+    }
+
+    /// Step key of the child step for the item at `index`.
+    #[must_use]
+    pub fn child_key(step_key: &str, index: usize) -> String {
+        format!("{step_key}[{index}]")
+    }
+}
+
+/// A step's `workflow_call` block. `path` names the called workflow's YAML relative to the
+/// calling file; loading the caller resolves it into `workflow`, so the caller's normalized hash
+/// covers the callee. Workflows built in code set `workflow` directly.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct StepWorkflowCall {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workflow: Option<Box<NormalizedWorkflow>>,
+}
+
+/// A step's `vote` block. The step runs its task once per agent in `agents`, as candidate steps
+/// keyed `<step_key>@<agent>`, and `aggregator` selects its output from the candidates that
+/// succeeded.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct StepVote {
+    pub agents: Vec<String>,
+    #[serde(default)]
+    pub aggregator: VoteAggregator,
+    /// Agent that picks the winner for the `judge` aggregator.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub judge_agent: Option<String>,
+    /// Output path, written `output.<path>`, whose value the `majority` aggregator compares;
+    /// the whole output payload when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ballot: Option<String>,
+}
+
+/// How a `vote` step selects its output.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VoteAggregator {
+    /// The output payload more candidates returned than any other; a tie fails the step.
+    #[default]
+    Majority,
+    /// A judge step, given every candidate's output, names the winning agent in
+    /// `payload.selected` (or `payload.payload.selected` from adapters that nest the answer).
+    Judge,
+    /// A person approves candidates in agent order; the first approved one wins.
+    HumanGate,
+}
+
+impl StepVote {
+    /// Step key of the candidate step of `agent`.
+    #[must_use]
+    pub fn candidate_key(step_key: &str, agent: &str) -> String {
+        format!("{step_key}@{agent}")
+    }
+
+    /// The value a candidate's output, a serialized [`StepOutputEnvelope`], votes for; `None`
+    /// when the output does not contain the ballot path.
+    ///
+    /// # Errors
+    /// Returns an error when `ballot` is not an output path.
+    pub fn ballot<'v>(&self, output: &'v Value) -> Result<Option<&'v Value>> {
+        let Some(ballot) = self.ballot.as_deref() else {
+            return Ok(output.get("payload"));
+        };
+        let invalid = |reason: &str| anyhow!("invalid vote ballot `{ballot}`: {reason}");
+        let rest = ballot
+            .trim_start()
+            .strip_prefix("output")
+            .ok_or_else(|| invalid("expected output.<path>"))?;
+        let (path, rest) = parse_value_path(rest, &invalid)?;
+        if path.is_empty() || !rest.trim().is_empty() {
+            return Err(invalid("expected an output path after `output`"));
+        }
+        Ok(value_at_path(output, &path))
+    }
+
+    /// Step key of the judge step.
+    #[must_use]
+    pub fn judge_key(step_key: &str, judge_agent: &str) -> String {
+        format!("{step_key}@judge:{judge_agent}")
+    }
+
+    /// Task of the judge step: the `vote` step's task with `candidates`, each candidate's agent
+    /// and output envelope.
+    #[must_use]
+    pub fn judge_task(task: &Value, candidates: &[(String, Value)]) -> Value {
+        let mut task = match task {
+            Value::Object(fields) => fields.clone(),
+            Value::Null => serde_json::Map::new(),
+            other => [("task".to_string(), other.clone())].into_iter().collect(),
+        };
+        let candidates = candidates
+            .iter()
+            .map(|(agent, output)| serde_json::json!({"agent": agent, "output": output}))
+            .collect();
+        task.insert("candidates".to_string(), Value::Array(candidates));
+        Value::Object(task)
+    }
+}
+
+/// A step's `iterate` block. The step runs as iteration steps keyed `<step_key>#<n>`, from 1,
+/// each given the previous iteration's output, until one's output satisfies `until` (a
+/// [`StepConvergence`]) or `max_iterations` have run.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct StepIteration {
+    pub max_iterations: u32,
+    pub until: String,
+}
+
+impl StepIteration {
+    /// Task of iteration `iteration`: the `iterate` step's task with `iteration` and
+    /// `previous_output`, the previous iteration's output envelope (null for the first).
+    #[must_use]
+    pub fn iteration_task(task: &Value, iteration: u32, previous_output: &Value) -> Value {
+        let mut task = match task {
+            Value::Object(fields) => fields.clone(),
+            Value::Null => serde_json::Map::new(),
+            other => [("task".to_string(), other.clone())].into_iter().collect(),
+        };
+        task.insert("iteration".to_string(), Value::from(iteration));
+        task.insert("previous_output".to_string(), previous_output.clone());
+        Value::Object(task)
+    }
+
+    /// Step key of iteration `iteration`.
+    #[must_use]
+    pub fn child_key(step_key: &str, iteration: u32) -> String {
+        format!("{step_key}#{iteration}")
+    }
+}
+
+/// An `iterate` step's `until` expression on one iteration's output, written
+/// `output.<path> == <literal>` or `!=`, e.g. `output.payload.approved == true`. The path starts
+/// at the iteration's output envelope (`message`, `payload`); a path the output does not contain
+/// does not converge.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StepConvergence {
+    pub path: Vec<PayloadPathSegment>,
+    pub comparison: PayloadComparison,
+    /// JSON string, number, boolean, or null.
+    pub value: Value,
+}
+
+impl StepConvergence {
+    /// Parse an `until` expression.
+    ///
+    /// # Errors
+    /// Returns an error when the expression does not start with an output path, has an unknown
+    /// operator, or compares against a literal that is not a JSON scalar.
+    pub fn parse(expression: &str) -> Result<Self> {
+        let invalid = |reason: &str| anyhow!("invalid until expression `{expression}`: {reason}");
+        let rest = expression
+            .trim_start()
+            .strip_prefix("output")
+            .ok_or_else(|| invalid("expected output.<path> == <value>"))?;
+        let (path, rest) = parse_value_path(rest, &invalid)?;
+        if path.is_empty() {
+            return Err(invalid("expected an output path after `output`"));
+        }
+        let (comparison, value) = parse_scalar_comparison(rest, &invalid)?;
+        Ok(Self {
+            path,
+            comparison,
+            value,
+        })
+    }
+
+    /// The value at the expression's path in `output`, a serialized [`StepOutputEnvelope`].
+    #[must_use]
+    pub fn observe<'v>(&self, output: &'v Value) -> Option<&'v Value> {
+        value_at_path(output, &self.path)
+    }
+
+    /// Whether the output [`Self::observe`] read from has converged.
+    #[must_use]
+    pub fn holds(&self, observed: Option<&Value>) -> bool {
+        observed.is_some_and(|observed| self.comparison.holds(observed, &self.value))
+    }
+}
+
+/// Trace event search across runs. Empty `event_types` matches every type; `since` is
+/// inclusive and `until` exclusive on `occurred_at`; every payload filter must match. Results
+/// are in `event_seq` order, truncated to `limit`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct EventQuery {
+    pub run_id: Option<RunId>,
+    pub event_types: Vec<TraceEventType>,
+    pub actor_type: Option<String>,
+    pub actor_id: Option<String>,
+    pub since: Option<DateTimeUtc>,
+    pub until: Option<DateTimeUtc>,
+    pub payload: Vec<PayloadFilter>,
+    pub limit: Option<usize>,
+}
+
+/// Per-step trust gating overhead measured against a synthetic context package: the outcome
+/// trust gate evaluation and the package filter that drops excluded items.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TrustGateBenchmarkReport {
+    pub trust_rows: usize,
+    pub candidates: usize,
+    pub iterations: usize,
+    pub mode: String,
+    pub evaluate_p50_ms: f64,
+    pub evaluate_p95_ms: f64,
+    pub evaluate_p99_ms: f64,
+    pub evaluate_max_ms: f64,
+    pub filter_p50_ms: f64,
+    pub filter_p95_ms: f64,
+    /// Evaluation plus filtering, i.e. what gating adds to each step.
+    pub step_p95_ms: f64,
+    pub per_candidate_p95_us: f64,
+    pub included: usize,
+    pub excluded: usize,
+    pub package_bytes_before: usize,
+    pub package_bytes_after: usize,
+    /// Serialized size of the trust attachments recorded on the step.
+    pub attachment_bytes: usize,
+}
+
+/// Progress of one planned workflow step within a run.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StepProgress {
+    pub step_key: String,
+    pub status: StepStatus,
+    pub started_at: Option<DateTimeUtc>,
+    pub ended_at: Option<DateTimeUtc>,
+    /// Wall time spent so far (running) or in total (finished).
+    pub elapsed_ms: Option<u64>,
+    /// Mean duration of this step across prior succeeded runs of the same workflow hash.
+    pub historical_mean_ms: Option<u64>,
+}
+
+/// Live progress snapshot for a run, including an ETA derived from historical step durations.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RunProgress {
+    pub run_id: RunId,
+    pub workflow_hash: String,
+    pub status: RunStatus,
+    pub started_at: DateTimeUtc,
+    pub elapsed_ms: u64,
+    pub total_steps: usize,
+    pub finished_steps: usize,
+    pub running_steps: usize,
+    pub pending_steps: usize,
+    /// Number of prior runs of the same workflow hash that contributed duration samples.
+    pub historical_runs: usize,
+    /// Remaining time assuming sequential execution; `None` when a remaining step has no
+    /// historical samples.
+    pub eta_ms: Option<u64>,
+    pub steps: Vec<StepProgress>,
+    pub generated_at: DateTimeUtc,
+}
+
+#[must_use]
 pub fn now_utc() -> DateTimeUtc {
     OffsetDateTime::now_utc()
 }
@@ -437,6 +2417,77 @@ pub fn hash_json(value: &Value) -> Result<String> {
     Ok(hash_bytes(&bytes))
 }
 
+/// SHA-256 over the sorted, de-duplicated ids, one per line. Order and repeats of injection do
+/// not change the digest.
+#[must_use]
+pub fn memory_version_set_digest(
+    memory_version_ids: impl IntoIterator<Item = MemoryVersionId>,
+) -> String {
+    let ids: std::collections::BTreeSet<String> = memory_version_ids
+        .into_iter()
+        .map(|id| id.to_string())
+        .collect();
+    let mut bytes = Vec::new();
+    for id in ids {
+        bytes.extend_from_slice(id.as_bytes());
+        bytes.push(b'\n');
+    }
+    hash_bytes(&bytes)
+}
+
+/// SHA-256 over one `step_index\tstep_key\tdigest` line per step, in the given order.
+#[must_use]
+pub fn memory_manifest_root(steps: &[StepMemoryDigest]) -> String {
+    let mut bytes = Vec::new();
+    for step in steps {
+        bytes.extend_from_slice(
+            format!("{}\t{}\t{}\n", step.step_index, step.step_key, step.digest).as_bytes(),
+        );
+    }
+    hash_bytes(&bytes)
+}
+
+/// Check a stored run manifest against its recorded hash and, when it carries an
+/// `injected_memory` rollup, check that rollup's root against its step digests. An
+/// `environment` fingerprint is checked against its own hash.
+///
+/// # Errors
+/// Returns an error when the hash, the environment hash, or the rollup root does not match, or
+/// `environment` or `injected_memory` is malformed.
+pub fn verify_run_manifest(
+    manifest: &Value,
+    manifest_hash: &str,
+) -> Result<Option<RunMemoryManifest>> {
+    let actual = hash_json(manifest)?;
+    if actual != manifest_hash {
+        return Err(anyhow!(
+            "run manifest hash mismatch: recorded {manifest_hash}, computed {actual}"
+        ));
+    }
+    if let Some(raw) = manifest.get("environment").filter(|raw| !raw.is_null()) {
+        let environment: EnvironmentFingerprint = serde_json::from_value(raw.clone())
+            .map_err(|err| anyhow!("invalid environment in run manifest: {err}"))?;
+        if !environment.hash_is_consistent() {
+            return Err(anyhow!(
+                "environment hash {} does not match its fields",
+                environment.hash
+            ));
+        }
+    }
+    let Some(raw) = manifest.get("injected_memory").filter(|raw| !raw.is_null()) else {
+        return Ok(None);
+    };
+    let memory: RunMemoryManifest = serde_json::from_value(raw.clone())
+        .map_err(|err| anyhow!("invalid injected_memory in run manifest: {err}"))?;
+    if !memory.root_is_consistent() {
+        return Err(anyhow!(
+            "injected_memory root {} does not match its step digests",
+            memory.root
+        ));
+    }
+    Ok(Some(memory))
+}
+
 /// Ensure a string field is non-empty after trimming.
 ///
 /// # Errors
@@ -457,6 +2508,46 @@ pub fn compute_step_request_hash(request: &StepRequest) -> Result<String> {
     hash_json(&value)
 }
 
+/// Compute a run-independent key of a step request for memoization: the request without its
+/// run and step ids, `as_of`, and `input_hash`. Injected context packages and trust attachments
+/// keep their own `as_of`, so steps given context only match runs at the same `as_of`.
+///
+/// # Errors
+/// Returns an error if the request cannot be serialized.
+pub fn compute_step_memo_key(request: &StepRequest) -> Result<String> {
+    let mut value = serde_json::to_value(request)?;
+    if let Some(fields) = value.as_object_mut() {
+        for field in ["run_id", "step_id", "as_of", "input_hash"] {
+            fields.remove(field);
+        }
+    }
+    hash_json(&value)
+}
+
+/// Compute a run-independent hash of a recorded provider request (`ProviderCallRecord`
+/// `request_json`): the request without its run and step ids and the `input_hash` derived from
+/// them, also inside the `request` an adapter wraps in its outbound body (`http_json`), so a
+/// replayed call can be checked against the call it replays.
+///
+/// # Errors
+/// Returns an error if the request cannot be hashed.
+pub fn compute_provider_request_replay_hash(request_json: &Value) -> Result<String> {
+    fn strip_run_fields(value: &mut Value) {
+        if let Some(fields) = value.as_object_mut() {
+            for field in ["run_id", "step_id", "input_hash"] {
+                fields.remove(field);
+            }
+        }
+    }
+
+    let mut value = request_json.clone();
+    strip_run_fields(&mut value);
+    if let Some(request) = value.get_mut("request") {
+        strip_run_fields(request);
+    }
+    hash_json(&value)
+}
+
 /// Compute a deterministic hash for a fully formed step result envelope.
 ///
 /// # Errors
@@ -466,6 +2557,34 @@ pub fn compute_step_result_hash(result: &StepResult) -> Result<String> {
     hash_json(&value)
 }
 
+/// Compute a run-independent hash of a step result: its status, outputs, error, and score, but
+/// none of the run, step, or provider call identifiers and timestamps.
+///
+/// # Errors
+/// Returns an error if the result cannot be serialized.
+pub fn compute_step_content_hash(result: &StepResult) -> Result<String> {
+    hash_json(&serde_json::json!({
+        "status": result.status,
+        "outputs": result.outputs,
+        "error": result.error,
+        "score": result.score,
+    }))
+}
+
+/// Compute the chained hash of a step checkpoint's state.
+///
+/// # Errors
+/// Returns an error if the state cannot be serialized.
+pub fn compute_checkpoint_hash(
+    prev_checkpoint_hash: Option<&str>,
+    state: &Value,
+) -> Result<String> {
+    hash_json(&serde_json::json!({
+        "prev_checkpoint_hash": prev_checkpoint_hash,
+        "state": state,
+    }))
+}
+
 /// Compute a deterministic hash for a trace event envelope.
 ///
 /// # Errors
@@ -1,36 +1,441 @@
 #![forbid(unsafe_code)]
 
-use std::collections::{BTreeMap, BTreeSet};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Context, Result};
 use memory_kernel_api::{AskRequest, MemoryKernelApi, RecallRequest};
 use memory_kernel_core::{
-    build_context_package, build_recall_context_package, default_recall_record_types, MemoryRecord,
-    QueryRequest, RecordType,
+    build_context_package, build_recall_context_package_with_tags, default_recall_record_types,
+    normalize_memory_tags, validate_memory_tag, MemoryRecord, MemoryVersionId, QueryRequest,
+    ReasonCodeRegistry, RecordType,
 };
 use memory_kernel_outcome_core::{
-    apply_as_of_decay, gate_memory, parse_rfc3339_utc, GateDecision as OutcomeGateDecision,
-    MemoryKey, MemoryTrust, OutcomeRuleset, RetrievalMode, TrustStatus,
+    apply_as_of_decay, exclude_superseded_versions, gate_memory, is_trusted_for_supersession,
+    parse_rfc3339_utc, GateDecision as OutcomeGateDecision, MemoryKey, MemoryTrust,
+    OutcomeEventInput, OutcomeEventSource, OutcomeEventType, OutcomeRuleset, RetrievalMode,
+    TrustStatus,
+};
+use memory_kernel_outcome_store_sqlite::{
+    resolve_ruleset_pin, seed_benchmark_trust_rows, SqliteOutcomeStore,
 };
 use memory_kernel_store_sqlite::SqliteStore as MemoryKernelSqliteStore;
 use multi_agent_center_domain::{
-    compute_step_request_hash, compute_step_result_hash, hash_json, now_utc, AgentDefinition,
-    ContextPackageEnvelope, EffectivePermissions, EventRow, GateDecision, GateDecisionRecord,
-    GateKind, NormalizedWorkflowEnvelope, ProposedMemoryWrite, RunId, RunRecord, RunStatus, StepId,
-    StepRecord, StepRequest, StepResult, StepStatus, TraceEvent, TraceEventType,
-    TrustGateAttachment,
+    compute_checkpoint_hash, compute_provider_request_replay_hash, compute_step_content_hash,
+    compute_step_memo_key, compute_step_request_hash, compute_step_result_hash, diff_golden_runs,
+    hash_json, memory_version_set_digest, now_utc, parse_prompt_ref, sign_gate_payload,
+    AgentDefinition, AppliedPersona, ContextItemDecision, ContextPackageEnvelope,
+    DeferredGateRecord, DeferredGateResolution, DelegatedApprovalRule, EffectivePermissions,
+    EnvironmentFingerprint, EventQuery, EventRow, GateDecision, GateDecisionRecord, GateKind,
+    GatePointDefinition, GateWebhook, GoldenDifference, GoldenGateDecision, GoldenRun, GoldenStep,
+    MemoizedStepRecord, MissingDependencyAction, NonInteractiveGateAction, NormalizedWorkflow,
+    NormalizedWorkflowEnvelope, ParentStep, ProposedMemoryWrite, ProviderBinding,
+    ProviderCallRecord, RecordedProviderResponse, ReplayExecutionReport, ResolvedPrompt,
+    ResponseScoringSpec, RetryContextMode, RunId, RunMemoryManifest, RunRecord, RunStatus,
+    RunUsage, ScoreCheck, ScoreVerdict, StandaloneStepDefinition, StepCheckpoint, StepCondition,
+    StepConstraints, StepContextPreview, StepConvergence, StepForeach, StepId, StepIteration,
+    StepMemoryDigest, StepOutputEnvelope, StepRecord, StepRequest, StepRerun, StepResult,
+    StepStatus, StepTaint, StepUpgradeImpact, StepVote, StepWorkflowCall, TaintPolicy,
+    TraceArtifactClass, TraceEvent, TraceEventType, TrustGateAttachment, TrustGateBenchmarkReport,
+    VoteAggregator, WorkflowDefaults, WorkflowDiff, WorkflowStepDefinition, WorkflowUpgradeImpact,
+    UNSPECIFIED_WARNING_CODE,
+};
+use multi_agent_center_policy::{
+    apply_context_permissions, PermissionPruneResult, PrunedReference,
 };
-use multi_agent_center_policy::{apply_context_permissions, PermissionPruneResult};
 use multi_agent_center_provider::{
-    HttpJsonProvider, MockProvider, ProviderAdapter, ProviderInvocation,
+    replay_recorded_response, ProviderInvocation, ProviderRegistry, ProviderTransportError,
 };
 use multi_agent_center_trace_core::TraceStore;
+use multi_agent_center_workflow::normalize_workflow_definition;
 use rusqlite::OptionalExtension;
 use serde_json::{json, Map, Value};
 use ulid::Ulid;
 
+/// Steps a step rerun executes again (by index) and the source outputs reused by the rest,
+/// keyed by step key.
+struct PreparedStepRerun {
+    invalidated: BTreeSet<usize>,
+    reused: BTreeMap<String, MemoizedStepRecord>,
+}
+
+/// Hashes of a step's recorded context packages, by attempt and package slot.
+type RecordedStepContext = BTreeMap<(u32, usize), String>;
+
+/// Recorded state a resumed run continues from.
+struct ResumedRun {
+    run: RunRecord,
+    steps: Vec<StepRecord>,
+    manifest: Value,
+    prev_event_hash: Option<String>,
+    /// Recorded steps that run again: failed or interrupted steps, deferred steps whose gates
+    /// have all been resolved, and the steps skipped behind them.
+    resumed_steps: BTreeSet<String>,
+    /// Hashes of the context packages recorded so far, by step key, then attempt and package
+    /// slot.
+    recorded_context: BTreeMap<String, RecordedStepContext>,
+    /// Latest recorded `when` condition result, by step key.
+    recorded_conditions: BTreeMap<String, bool>,
+    /// Recorded `foreach` expansions, iterations, and vote steps in event order, by step key.
+    recorded_expansions: Vec<(String, StepExpansion)>,
+}
+
+/// Child steps the scheduler appends to a `foreach`, `iterate`, or `vote` step at the top of its
+/// next pass.
+#[derive(Debug, Clone)]
+enum StepExpansion {
+    /// One child per item; `None` when the source output had no array to expand.
+    Foreach(Option<Vec<Value>>),
+    /// The next iteration, with its task.
+    Iteration { iteration: u32, task: Value },
+    /// One candidate per voting agent, or with `judge_task` the judge step.
+    Vote { judge_task: Option<Value> },
+}
+
+impl PreparedStepRerun {
+    fn rerun_step_keys<'w>(&self, workflow: &'w NormalizedWorkflow) -> Vec<&'w str> {
+        self.invalidated
+            .iter()
+            .map(|idx| workflow.steps[*idx].step_key.as_str())
+            .collect()
+    }
+}
+
+/// Step bookkeeping of one [`Orchestrator::execute_workflow`] call, indexed by step index.
+/// `foreach`, `iterate`, and `vote` steps append their child steps, so the step list and every
+/// per-step vector grow as the run goes.
+struct SchedulerState {
+    steps: Vec<WorkflowStepDefinition>,
+    step_by_key: BTreeMap<String, usize>,
+    statuses: Vec<StepStatus>,
+    step_ids: Vec<StepId>,
+    inserted_steps: BTreeSet<usize>,
+    /// Deferred gate steps each step relies on under speculative execution.
+    speculative_deps: Vec<BTreeSet<usize>>,
+    taints: Vec<StepTaint>,
+    /// `when` conditions, evaluated as soon as the step they observe succeeds.
+    conditions: Vec<Option<StepCondition>>,
+    condition_met: BTreeMap<usize, bool>,
+    /// `foreach` items are resolved as soon as the source step succeeds, an `iterate` step's
+    /// next iteration is queued as soon as the previous one fails to converge, and a `vote`
+    /// step's candidates and judge are queued once it is ready; the child steps are appended at
+    /// the top of the next scheduling pass.
+    expansions: Vec<(usize, StepExpansion)>,
+    /// Children of each expanded `foreach` step; `None` when its source output had no array.
+    foreach_children: BTreeMap<usize, Option<Vec<usize>>>,
+    foreach_parent: BTreeMap<usize, usize>,
+    iterations: BTreeMap<usize, Vec<usize>>,
+    iteration_parent: BTreeMap<usize, (usize, u32)>,
+    /// Candidate steps of each `vote` step, followed by its judge step once added.
+    vote_children: BTreeMap<usize, Vec<usize>>,
+    vote_parent: BTreeMap<usize, usize>,
+    /// Whether the latest evaluated iteration of each `iterate` step converged.
+    converged: BTreeMap<usize, bool>,
+    child_outputs: BTreeMap<usize, StepOutputEnvelope>,
+    /// The last iteration a resumed run recorded for each `iterate` step.
+    last_recorded_iteration: BTreeMap<usize, u32>,
+}
+
+impl SchedulerState {
+    fn new(workflow: &NormalizedWorkflow) -> Result<Self> {
+        let steps = workflow.steps.clone();
+        let total_steps = steps.len();
+        let conditions = steps
+            .iter()
+            .map(|step| step.when.as_deref().map(StepCondition::parse).transpose())
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            step_by_key: steps
+                .iter()
+                .enumerate()
+                .map(|(index, step)| (step.step_key.clone(), index))
+                .collect(),
+            statuses: vec![StepStatus::Pending; total_steps],
+            step_ids: (0..total_steps).map(|_| StepId::new()).collect(),
+            inserted_steps: BTreeSet::new(),
+            speculative_deps: vec![BTreeSet::new(); total_steps],
+            taints: vec![StepTaint::default(); total_steps],
+            conditions,
+            condition_met: BTreeMap::new(),
+            expansions: Vec::new(),
+            foreach_children: BTreeMap::new(),
+            foreach_parent: BTreeMap::new(),
+            iterations: BTreeMap::new(),
+            iteration_parent: BTreeMap::new(),
+            vote_children: BTreeMap::new(),
+            vote_parent: BTreeMap::new(),
+            converged: BTreeMap::new(),
+            child_outputs: BTreeMap::new(),
+            last_recorded_iteration: BTreeMap::new(),
+            steps,
+        })
+    }
+
+    /// Continue from a resumed run: its finished steps keep their status, resolved deferred
+    /// steps run again under their recorded step ids, and recorded expansions are queued in
+    /// their original order, so every child step gets back its recorded step index.
+    fn restore(&mut self, resumed: &ResumedRun) -> Result<()> {
+        for record in &resumed.steps {
+            let Some(&idx) = self.step_by_key.get(record.step_key.as_str()) else {
+                continue;
+            };
+            self.step_ids[idx] = record.step_id;
+            self.inserted_steps.insert(idx);
+            self.statuses[idx] = if resumed.resumed_steps.contains(&record.step_key) {
+                StepStatus::Pending
+            } else {
+                record.status.clone()
+            };
+            if let Some(taint_json) = record.taint_json.as_ref() {
+                self.taints[idx] = serde_json::from_value(taint_json.clone())
+                    .context("invalid recorded taint_json")?;
+            }
+        }
+        for (step_key, met) in &resumed.recorded_conditions {
+            if let Some(&idx) = self.step_by_key.get(step_key.as_str()) {
+                self.condition_met.insert(idx, *met);
+            }
+        }
+        for (step_key, expansion) in &resumed.recorded_expansions {
+            let idx = *self
+                .step_by_key
+                .get(step_key.as_str())
+                .ok_or_else(|| anyhow!("recorded foreach step {step_key} is unknown"))?;
+            if let StepExpansion::Iteration { iteration, .. } = expansion {
+                self.last_recorded_iteration.insert(idx, *iteration);
+            }
+            self.expansions.push((idx, expansion.clone()));
+        }
+        Ok(())
+    }
+
+    /// Append the child steps of every queued expansion.
+    fn apply_expansions(&mut self, resumed: Option<&ResumedRun>) -> Result<()> {
+        for (parent, expansion) in std::mem::take(&mut self.expansions) {
+            match expansion {
+                StepExpansion::Foreach(items) => self.expand_foreach(parent, items, resumed)?,
+                StepExpansion::Iteration { iteration, task } => {
+                    self.expand_iteration(parent, iteration, task, resumed)?;
+                }
+                StepExpansion::Vote { judge_task } => {
+                    self.expand_vote(parent, judge_task, resumed)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// One child per item of the `foreach` step at `parent`.
+    fn expand_foreach(
+        &mut self,
+        parent: usize,
+        items: Option<Vec<Value>>,
+        resumed: Option<&ResumedRun>,
+    ) -> Result<()> {
+        let Some(items) = items else {
+            self.foreach_children.insert(parent, None);
+            return Ok(());
+        };
+        let step = &self.steps[parent];
+        let added = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                (
+                    StepForeach::child_key(&step.step_key, index),
+                    StepForeach::child_task(&step.task, item, index),
+                    step.agent_name.clone(),
+                )
+            })
+            .collect();
+        let children = self.add_child_steps(parent, added, None, resumed)?;
+        self.foreach_parent
+            .extend(children.iter().map(|child| (*child, parent)));
+        self.foreach_children.insert(parent, Some(children));
+        Ok(())
+    }
+
+    /// Iteration `iteration` of the `iterate` step at `parent`.
+    fn expand_iteration(
+        &mut self,
+        parent: usize,
+        iteration: u32,
+        task: Value,
+        resumed: Option<&ResumedRun>,
+    ) -> Result<()> {
+        let step = &self.steps[parent];
+        let added = vec![(
+            StepIteration::child_key(&step.step_key, iteration),
+            task,
+            step.agent_name.clone(),
+        )];
+        let children = self.add_child_steps(parent, added, Some(iteration), resumed)?;
+        self.iteration_parent
+            .insert(children[0], (parent, iteration));
+        self.iterations.entry(parent).or_default().extend(children);
+        Ok(())
+    }
+
+    /// The candidate steps of the `vote` step at `parent`, or with `judge_task` its judge step.
+    fn expand_vote(
+        &mut self,
+        parent: usize,
+        judge_task: Option<Value>,
+        resumed: Option<&ResumedRun>,
+    ) -> Result<()> {
+        let step = &self.steps[parent];
+        let vote = step
+            .vote
+            .as_ref()
+            .ok_or_else(|| anyhow!("step {} does not vote", step.step_key))?;
+        let added = match (judge_task, vote.judge_agent.as_ref()) {
+            (Some(task), Some(judge)) => vec![(
+                StepVote::judge_key(&step.step_key, judge),
+                task,
+                judge.clone(),
+            )],
+            (Some(_), None) => {
+                return Err(anyhow!("vote step {} has no judge_agent", step.step_key));
+            }
+            (None, _) => vote
+                .agents
+                .iter()
+                .map(|agent| {
+                    (
+                        StepVote::candidate_key(&step.step_key, agent),
+                        step.task.clone(),
+                        agent.clone(),
+                    )
+                })
+                .collect(),
+        };
+        let children = self.add_child_steps(parent, added, None, resumed)?;
+        self.vote_parent
+            .extend(children.iter().map(|child| (*child, parent)));
+        self.vote_children
+            .entry(parent)
+            .or_default()
+            .extend(children);
+        Ok(())
+    }
+
+    /// Append `added` (step key, task, agent) as child steps of `parent`, which inherit its
+    /// definition and `when` condition, and return their indexes. A child the earlier attempt of
+    /// a resumed run recorded keeps its step id; succeeded children of a step that aggregates
+    /// again run again, since their outputs are gone. Of an unfinished `iterate` step only the
+    /// last recorded iteration can have succeeded, as each later one was started from its
+    /// predecessor's output.
+    fn add_child_steps(
+        &mut self,
+        parent: usize,
+        added: Vec<(String, Value, String)>,
+        iteration: Option<u32>,
+        resumed: Option<&ResumedRun>,
+    ) -> Result<Vec<usize>> {
+        let mut children = Vec::with_capacity(added.len());
+        for (step_key, task, agent_name) in added {
+            let idx = self.steps.len();
+            let child = WorkflowStepDefinition {
+                step_key,
+                agent_name,
+                task,
+                foreach: None,
+                iterate: None,
+                vote: None,
+                ..self.steps[parent].clone()
+            };
+            self.conditions.push(self.conditions[parent].clone());
+            if let Some(met) = self.condition_met.get(&parent).copied() {
+                self.condition_met.insert(idx, met);
+            }
+            self.statuses.push(StepStatus::Pending);
+            self.step_ids.push(StepId::new());
+            self.speculative_deps.push(BTreeSet::new());
+            self.taints.push(StepTaint::default());
+            let outputs_needed = iteration.map_or(true, |iteration| {
+                self.last_recorded_iteration.get(&parent) == Some(&iteration)
+            });
+            let recorded = resumed.and_then(|resumed| {
+                resumed
+                    .steps
+                    .iter()
+                    .find(|record| record.step_key == child.step_key)
+                    .map(|record| (resumed, record))
+            });
+            if let Some((resumed, record)) = recorded {
+                self.step_ids[idx] = record.step_id;
+                self.inserted_steps.insert(idx);
+                let rerun = resumed.resumed_steps.contains(&record.step_key)
+                    || (record.status == StepStatus::Succeeded
+                        && self.statuses[parent] == StepStatus::Pending
+                        && outputs_needed);
+                if !rerun {
+                    self.statuses[idx] = record.status.clone();
+                }
+                if let Some(taint_json) = record.taint_json.as_ref() {
+                    self.taints[idx] = serde_json::from_value(taint_json.clone())
+                        .context("invalid recorded taint_json")?;
+                }
+            }
+            self.step_by_key.insert(child.step_key.clone(), idx);
+            self.steps.push(child);
+            children.push(idx);
+        }
+        Ok(children)
+    }
+
+    /// Whether every step in `children` finished, so their parent can aggregate them.
+    fn children_finished(&self, children: &[usize]) -> bool {
+        children.iter().all(|child| {
+            !matches!(
+                self.statuses[*child],
+                StepStatus::Pending | StepStatus::Running | StepStatus::Deferred
+            )
+        })
+    }
+}
+
+/// Step key of the single step run by [`Orchestrator::execute_single_step`].
+pub const ADHOC_STEP_KEY: &str = "adhoc";
+
+/// How often an in-flight provider call checks its step's timeout and the run's cancellation.
+const PROVIDER_POLL_INTERVAL: Duration = Duration::from_millis(25);
+
+/// Cooperative cancellation for a run. Clones share one flag, so another thread can keep a
+/// clone and cancel the run while [`Orchestrator::execute_workflow`] blocks.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the run to stop: the provider call in flight is abandoned and steps that have not
+    /// started are marked `cancelled`.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Whether another handle shares this token's flag. A token nothing else holds cannot be
+    /// cancelled while the run that owns it is executing, since steps run on the calling thread.
+    fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.0) > 1
+    }
+}
+
 #[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct RunConfig {
     pub run_id: Option<RunId>,
     pub as_of: Option<time::OffsetDateTime>,
@@ -40,6 +445,44 @@ pub struct RunConfig {
     pub cli_args_json: Value,
     pub engine_version: String,
     pub apply_proposed_writes: bool,
+    /// Defer optional human gates until the workflow finishes, running the gated steps and their
+    /// dependents speculatively; their writes are held back and committed only if every deferred
+    /// gate they rely on is approved, otherwise the steps are marked skipped.
+    pub speculative_execution: bool,
+    /// When `external_correlation_id` already has a run that neither failed nor was cancelled,
+    /// return that run's summary instead of executing again, so external schedulers can trigger at least once.
+    pub reuse_correlated_run: bool,
+    /// Key/value labels stored with the run for filtering run listings.
+    pub labels: BTreeMap<String, String>,
+    /// Fail before the run starts when the trust gate source is not configured (every memory
+    /// would pass with `included.no_trust_gating_configured`). Workflows can require the same
+    /// through `defaults.require_trust_gating`. Replays are exempt.
+    pub require_trust_gating: bool,
+    /// Reuse the output of the most recent succeeded step with the same workflow hash and memo
+    /// key (`compute_step_memo_key`) instead of calling its provider; the step records
+    /// `memoized_from_run`. Conversation steps and runs replaying recorded responses always call
+    /// their provider.
+    pub memoize_steps: bool,
+    /// Re-execute one step of an earlier run and everything downstream of it. Other steps reuse
+    /// the source run's recorded outputs as memoized steps; the run records `rerun_of`.
+    pub rerun_of: Option<StepRerun>,
+    /// Artifact classes the trace store may keep queued until the run's durability barrier
+    /// instead of committing each row before the write returns (see `TraceStore::flush`). Rows
+    /// lost to a crash before the barrier reduce replay fidelity; gate decisions and manifests
+    /// are always synchronous.
+    pub relaxed_trace_artifacts: BTreeSet<TraceArtifactClass>,
+    /// Fail steps whose agent sets `fail_on_permission_prune` when permission rules prune their
+    /// context. Workflows can enable the same through `defaults.enforce_permission_prune`; the
+    /// resolved policy is recorded in the run manifest as `permission_prune`.
+    pub enforce_permission_prune: bool,
+    /// Continue this run instead of starting a new one (see [`Orchestrator::resume_run`]).
+    pub resume_run: Option<RunId>,
+    /// The `workflow_call` step this run is nested under; set by the orchestrator for the runs
+    /// it starts for such steps and recorded as the run's `parent_step`.
+    pub parent_step: Option<ParentStep>,
+    /// Checked before each step and while provider calls are in flight. A cancelled run
+    /// finishes as `cancelled` with its event chain closed by `RunFinished`.
+    pub cancellation: CancellationToken,
 }
 
 impl Default for RunConfig {
@@ -53,6 +496,17 @@ impl Default for RunConfig {
             cli_args_json: Value::Object(Map::default()),
             engine_version: "multi-agent-center.v0".to_string(),
             apply_proposed_writes: false,
+            speculative_execution: false,
+            reuse_correlated_run: false,
+            labels: BTreeMap::new(),
+            require_trust_gating: false,
+            memoize_steps: false,
+            rerun_of: None,
+            relaxed_trace_artifacts: BTreeSet::new(),
+            enforce_permission_prune: false,
+            resume_run: None,
+            parent_step: None,
+            cancellation: CancellationToken::default(),
         }
     }
 }
@@ -64,6 +518,28 @@ pub struct RunExecutionSummary {
     pub steps_total: usize,
     pub steps_succeeded: usize,
     pub steps_failed_or_rejected: usize,
+    /// Steps held by deferred gates; they and the steps behind them stay pending until the run
+    /// is resumed.
+    pub steps_deferred: usize,
+    /// Memory refs the trust gate kept out of step context.
+    pub trust_excluded: usize,
+    /// Human gate decisions, including delegated auto-approvals.
+    pub human_approvals: usize,
+    pub human_rejections: usize,
+    /// Human gates a non-interactive run queued for a person instead of deciding them.
+    pub human_deferrals: usize,
+    /// Steps whose context was pruned by agent permissions.
+    pub policy_prunes: usize,
+    pub proposed_writes: usize,
+    /// Proposed writes per disposition (`applied`, `not_applied`, `discarded`, ...).
+    pub proposed_write_dispositions: BTreeMap<String, usize>,
+    pub provider_calls: usize,
+    /// `warning` events per `warning_code`, so warnings that do not fail the run stay visible.
+    pub warnings: BTreeMap<String, usize>,
+    /// Usage aggregated from the run's trace and persisted once the run finished.
+    pub usage: RunUsage,
+    /// The summary describes an earlier run found by `external_correlation_id`; nothing executed.
+    pub reused_existing_run: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -88,6 +564,13 @@ pub trait ContextPackageSource {
         step: &multi_agent_center_domain::WorkflowStepDefinition,
         as_of: time::OffsetDateTime,
     ) -> Result<Vec<ContextPackageEnvelope>>;
+
+    /// Identifies the memory state packages are built from at `as_of`, so identical builds can
+    /// be reused by [`CachingContextPackageSource`]. Sources returning `None` are never cached.
+    #[allow(clippy::missing_errors_doc)]
+    fn cache_snapshot(&self, _as_of: time::OffsetDateTime) -> Result<Option<String>> {
+        Ok(None)
+    }
 }
 
 pub trait TrustGateSource {
@@ -100,6 +583,12 @@ pub trait TrustGateSource {
         as_of: time::OffsetDateTime,
         refs: &[ContextRef],
     ) -> Result<Vec<TrustGateAttachment>>;
+
+    /// Whether this source actually gates memories; `false` for the allow-all fallback used
+    /// when no trust database is configured.
+    fn is_configured(&self) -> bool {
+        true
+    }
 }
 
 pub trait HumanGateDecider {
@@ -117,6 +606,61 @@ pub trait ProposedWriteApplier {
     ) -> Result<WriteApplyResult>;
 }
 
+/// Scores a step's output against the step's `scoring` spec.
+pub trait ResponseScorer {
+    #[allow(clippy::missing_errors_doc)]
+    fn score(
+        &self,
+        spec: &ResponseScoringSpec,
+        request: &StepRequest,
+        output: &StepOutputEnvelope,
+    ) -> Result<ScoreVerdict>;
+}
+
+/// Receives score verdicts for the memories injected into a step, closing the trust feedback
+/// loop. Returns the number of outcome events recorded.
+pub trait OutcomeEventSink {
+    #[allow(clippy::missing_errors_doc)]
+    fn log_scored_outcome(
+        &self,
+        origin: &ScoredStepOrigin<'_>,
+        verdict: &ScoreVerdict,
+        memories: &[ScoredMemory],
+    ) -> Result<usize>;
+}
+
+/// Delivers deferred-gate notifications to gate webhooks. The orchestrator builds and signs
+/// the `gate_notification.v1` body; implementations only transport it.
+pub trait GateNotifier {
+    /// POST `body` to `url` with `signature` (`sha256=<hex>`) in
+    /// [`multi_agent_center_domain::GATE_SIGNATURE_HEADER`].
+    #[allow(clippy::missing_errors_doc)]
+    fn notify(&self, url: &str, body: &[u8], signature: &str) -> Result<()>;
+}
+
+/// The run and step a scored outcome came from; sinks record it as the events' source.
+#[derive(Debug, Clone, Copy)]
+pub struct ScoredStepOrigin<'a> {
+    pub run_id: RunId,
+    pub step_id: StepId,
+    pub step_key: &'a str,
+    pub engine_version: &'a str,
+}
+
+/// Supplies recorded provider responses that replace live provider calls, one per call in
+/// order. Returning `None` means no recorded response is left for the step.
+pub trait RecordedResponseSource {
+    fn next_response(&self, step_key: &str) -> Option<RecordedProviderResponse>;
+}
+
+/// Memory version injected into a scored step, with the ruleset its trust was gated under.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoredMemory {
+    pub memory_id: memory_kernel_core::MemoryId,
+    pub version: u32,
+    pub ruleset_version: Option<u32>,
+}
+
 #[derive(Debug, Clone)]
 pub struct HumanGateRequest {
     pub run_id: RunId,
@@ -168,6 +712,61 @@ impl ContextPackageSource for StaticContextPackageSource {
     }
 }
 
+/// Serves the context packages a recorded run injected into each step, attempt by attempt: a
+/// step's first request gets its first attempt's packages and each later one (a retry
+/// rebuilding its context) the next attempt's, staying on the last attempt recorded.
+#[derive(Debug, Clone, Default)]
+pub struct RecordedContextPackageSource {
+    attempts: BTreeMap<String, Vec<Vec<ContextPackageEnvelope>>>,
+    requests: RefCell<BTreeMap<String, usize>>,
+}
+
+impl RecordedContextPackageSource {
+    /// Collect the context packages recorded for `run_id`.
+    ///
+    /// # Errors
+    /// Returns an error when the run's context packages cannot be read.
+    pub fn from_trace(trace_store: &dyn TraceStore, run_id: RunId) -> Result<Self> {
+        let mut by_attempt: BTreeMap<String, BTreeMap<u32, Vec<ContextPackageEnvelope>>> =
+            BTreeMap::new();
+        for row in trace_store.get_step_context_packages(run_id)? {
+            by_attempt
+                .entry(row.step_key)
+                .or_default()
+                .entry(row.attempt)
+                .or_default()
+                .push(row.envelope);
+        }
+        Ok(Self {
+            attempts: by_attempt
+                .into_iter()
+                .map(|(step_key, attempts)| (step_key, attempts.into_values().collect()))
+                .collect(),
+            requests: RefCell::default(),
+        })
+    }
+}
+
+impl ContextPackageSource for RecordedContextPackageSource {
+    fn packages_for_step(
+        &self,
+        _run_id: RunId,
+        step: &multi_agent_center_domain::WorkflowStepDefinition,
+        _as_of: time::OffsetDateTime,
+    ) -> Result<Vec<ContextPackageEnvelope>> {
+        let mut requests = self.requests.borrow_mut();
+        let served = requests.entry(step.step_key.clone()).or_default();
+        let packages = self
+            .attempts
+            .get(&step.step_key)
+            .and_then(|attempts| attempts.get(*served).or(attempts.last()))
+            .cloned()
+            .unwrap_or_default();
+        *served += 1;
+        Ok(packages)
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct InMemoryMemoryKernelContextSource {
     pub records_by_step: BTreeMap<String, Vec<MemoryRecord>>,
@@ -193,6 +792,11 @@ impl ContextPackageSource for InMemoryMemoryKernelContextSource {
             "memory_kernel.in_memory",
         )
     }
+
+    fn cache_snapshot(&self, _as_of: time::OffsetDateTime) -> Result<Option<String>> {
+        let records = serde_json::to_value(&self.records_by_step)?;
+        Ok(Some(hash_json(&records)?))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -222,11 +826,16 @@ impl ContextPackageSource for SqliteMemoryKernelContextSource {
         records.retain(|record| record.effective_at <= as_of);
         build_context_packages_from_records(&records, run_id, step, as_of, "memory_kernel.sqlite")
     }
+
+    fn cache_snapshot(&self, as_of: time::OffsetDateTime) -> Result<Option<String>> {
+        memory_kernel_snapshot(&self.db_path, as_of).map(Some)
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct ApiMemoryKernelContextSource {
     api: MemoryKernelApi,
+    db_path: PathBuf,
 }
 
 impl ApiMemoryKernelContextSource {
@@ -234,6 +843,7 @@ impl ApiMemoryKernelContextSource {
     pub fn new(db_path: &Path) -> Self {
         Self {
             api: MemoryKernelApi::new(db_path.to_path_buf()),
+            db_path: db_path.to_path_buf(),
         }
     }
 }
@@ -257,13 +867,16 @@ impl ContextPackageSource for ApiMemoryKernelContextSource {
                     resource: request.resource,
                     as_of: Some(request.as_of),
                 })?,
-                StepContextQuery::Recall { text, record_types } => {
-                    self.api.query_recall(RecallRequest {
-                        text,
-                        record_types,
-                        as_of: Some(as_of),
-                    })?
-                }
+                StepContextQuery::Recall {
+                    text,
+                    record_types,
+                    record_tags,
+                } => self.api.query_recall(RecallRequest {
+                    text,
+                    record_types,
+                    record_tags,
+                    as_of: Some(as_of),
+                })?,
             };
 
             let package_json = serde_json::to_value(&package)?;
@@ -278,6 +891,10 @@ impl ContextPackageSource for ApiMemoryKernelContextSource {
 
         Self::rewrite_snapshot_ids(run_id, step, envelopes)
     }
+
+    fn cache_snapshot(&self, as_of: time::OffsetDateTime) -> Result<Option<String>> {
+        memory_kernel_snapshot(&self.db_path, as_of).map(Some)
+    }
 }
 
 impl ApiMemoryKernelContextSource {
@@ -297,6 +914,211 @@ impl ApiMemoryKernelContextSource {
     }
 }
 
+const CONTEXT_PACKAGE_CACHE_SQL: &str = r"
+CREATE TABLE IF NOT EXISTS context_package_cache (
+  cache_key TEXT PRIMARY KEY,
+  snapshot TEXT NOT NULL,
+  as_of TEXT NOT NULL,
+  queries_json TEXT NOT NULL,
+  envelopes_json TEXT NOT NULL,
+  created_at TEXT NOT NULL
+);
+";
+
+/// Reuses built context packages for steps whose resolved queries, `as_of`, and memory snapshot
+/// match an earlier build. Hits are served from memory first, then from the optional sqlite
+/// table, and their envelope `source` gains a `+cache:<tier>:<key>` suffix.
+#[derive(Debug)]
+pub struct CachingContextPackageSource<S> {
+    inner: S,
+    entries: std::sync::Mutex<BTreeMap<String, Vec<ContextPackageEnvelope>>>,
+    db_path: Option<PathBuf>,
+}
+
+impl<S: ContextPackageSource> CachingContextPackageSource<S> {
+    #[must_use]
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            entries: std::sync::Mutex::new(BTreeMap::new()),
+            db_path: None,
+        }
+    }
+
+    /// Persist cache entries in `db_path` so they survive across runs.
+    ///
+    /// # Errors
+    /// Returns an error when the sqlite database cannot be opened or migrated.
+    pub fn with_sqlite(mut self, db_path: &Path) -> Result<Self> {
+        let conn = open_context_package_cache(db_path)?;
+        drop(conn);
+        self.db_path = Some(db_path.to_path_buf());
+        Ok(self)
+    }
+
+    fn cache_key(
+        step: &multi_agent_center_domain::WorkflowStepDefinition,
+        as_of: time::OffsetDateTime,
+        snapshot: &str,
+    ) -> Result<(String, Value)> {
+        let queries: Vec<Value> = resolve_step_context_queries(step, as_of)?
+            .iter()
+            .map(StepContextQuery::cache_key_json)
+            .collect();
+        let queries = Value::Array(queries);
+        let key = hash_json(&json!({
+            "queries": queries,
+            "as_of": format_rfc3339(as_of)?,
+            "snapshot": snapshot,
+        }))?;
+        Ok((key, queries))
+    }
+
+    fn load_persisted(&self, key: &str) -> Result<Option<Vec<ContextPackageEnvelope>>> {
+        let Some(db_path) = self.db_path.as_ref() else {
+            return Ok(None);
+        };
+        let conn = open_context_package_cache(db_path)?;
+        let raw: Option<String> = conn
+            .query_row(
+                "SELECT envelopes_json FROM context_package_cache WHERE cache_key = ?1",
+                [key],
+                |row| row.get(0),
+            )
+            .optional()?;
+        raw.map(|raw| serde_json::from_str(&raw).context("invalid cached context packages"))
+            .transpose()
+    }
+
+    fn persist(
+        &self,
+        key: &str,
+        snapshot: &str,
+        as_of: time::OffsetDateTime,
+        queries: &Value,
+        envelopes: &[ContextPackageEnvelope],
+    ) -> Result<()> {
+        let Some(db_path) = self.db_path.as_ref() else {
+            return Ok(());
+        };
+        let conn = open_context_package_cache(db_path)?;
+        conn.execute(
+            "INSERT OR IGNORE INTO context_package_cache(
+               cache_key, snapshot, as_of, queries_json, envelopes_json, created_at
+             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![
+                key,
+                snapshot,
+                format_rfc3339(as_of)?,
+                serde_json::to_string(queries)?,
+                serde_json::to_string(envelopes)?,
+                format_rfc3339(now_utc())?,
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+impl<S: ContextPackageSource> ContextPackageSource for CachingContextPackageSource<S> {
+    fn packages_for_step(
+        &self,
+        run_id: RunId,
+        step: &multi_agent_center_domain::WorkflowStepDefinition,
+        as_of: time::OffsetDateTime,
+    ) -> Result<Vec<ContextPackageEnvelope>> {
+        let Some(snapshot) = self.inner.cache_snapshot(as_of)? else {
+            return self.inner.packages_for_step(run_id, step, as_of);
+        };
+        let (key, queries) = Self::cache_key(step, as_of, &snapshot)?;
+
+        let cached = self
+            .entries
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(&key)
+            .cloned();
+        if let Some(envelopes) = cached {
+            return Ok(with_cache_provenance(envelopes, "memory", &key));
+        }
+        if let Some(envelopes) = self.load_persisted(&key)? {
+            self.entries
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .insert(key.clone(), envelopes.clone());
+            return Ok(with_cache_provenance(envelopes, "sqlite", &key));
+        }
+
+        let envelopes = self.inner.packages_for_step(run_id, step, as_of)?;
+        self.persist(&key, &snapshot, as_of, &queries, &envelopes)?;
+        self.entries
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(key, envelopes.clone());
+        Ok(envelopes)
+    }
+
+    fn cache_snapshot(&self, as_of: time::OffsetDateTime) -> Result<Option<String>> {
+        self.inner.cache_snapshot(as_of)
+    }
+}
+
+fn open_context_package_cache(db_path: &Path) -> Result<rusqlite::Connection> {
+    let conn = rusqlite::Connection::open(db_path).with_context(|| {
+        format!(
+            "failed to open context package cache at {}",
+            db_path.display()
+        )
+    })?;
+    conn.execute_batch(CONTEXT_PACKAGE_CACHE_SQL)?;
+    Ok(conn)
+}
+
+fn with_cache_provenance(
+    mut envelopes: Vec<ContextPackageEnvelope>,
+    tier: &str,
+    key: &str,
+) -> Vec<ContextPackageEnvelope> {
+    let short_key = key.get(..12).unwrap_or(key);
+    for envelope in &mut envelopes {
+        envelope.source = format!("{}+cache:{tier}:{short_key}", envelope.source);
+    }
+    envelopes
+}
+
+/// Marker of the memory versions visible at `as_of` in a Memory Kernel sqlite database: their
+/// count and highest rowid, which change whenever a version visible at `as_of` is added since
+/// `memory_records` is insert-only. The database is opened read-only and never migrated; a
+/// missing database or `memory_records` table reads as empty.
+fn memory_kernel_snapshot(db_path: &Path, as_of: time::OffsetDateTime) -> Result<String> {
+    let (records, max_rowid) = if db_path.exists() {
+        let conn = rusqlite::Connection::open_with_flags(
+            db_path,
+            rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY,
+        )
+        .with_context(|| format!("failed to open memory database {}", db_path.display()))?;
+        let has_records = conn.query_row(
+            "SELECT EXISTS(
+                SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'memory_records'
+             )",
+            [],
+            |row| row.get::<_, bool>(0),
+        )?;
+        if has_records {
+            conn.query_row(
+                "SELECT COUNT(*), COALESCE(MAX(rowid), 0) FROM memory_records
+                 WHERE julianday(effective_at) <= julianday(?1)",
+                [format_rfc3339(as_of)?],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)),
+            )?
+        } else {
+            (0, 0)
+        }
+    } else {
+        (0, 0)
+    };
+    hash_json(&json!({"records": records, "max_rowid": max_rowid}))
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct AllowAllTrustGateSource;
 
@@ -319,46 +1141,169 @@ impl TrustGateSource for AllowAllTrustGateSource {
                 trust_status: "active".to_string(),
                 confidence_effective: 1.0,
                 capped: false,
-                reason_codes: vec!["included.no_trust_gating_configured".to_string()],
+                reason_codes: vec![
+                    memory_kernel_core::REASON_INCLUDED_NO_TRUST_GATING_CONFIGURED.to_string(),
+                ],
                 ruleset_version: None,
                 evaluated_at: as_of,
                 source: "trust.none".to_string(),
             })
             .collect())
     }
+
+    fn is_configured(&self) -> bool {
+        false
+    }
 }
 
-#[derive(Debug, Clone)]
-pub struct OutcomeMemoryTrustGateSource {
-    db_path: PathBuf,
-    mode: RetrievalMode,
+/// Repeats the trust gate decisions a recorded run made, by step key, attempt, and memory
+/// version, so a replay gates context exactly as the run did: a step's first evaluation gets
+/// its first attempt's decisions and each later one (a retry rebuilding its context) the next
+/// attempt's. Refs the run never gated are let through like [`AllowAllTrustGateSource`].
+#[derive(Debug, Clone, Default)]
+pub struct RecordedTrustGateSource {
+    attachments: BTreeMap<(String, u32, String), TrustGateAttachment>,
+    evaluations: RefCell<BTreeMap<String, u32>>,
 }
 
-impl OutcomeMemoryTrustGateSource {
-    #[must_use]
-    pub fn new(db_path: &Path, mode: RetrievalMode) -> Self {
-        Self {
-            db_path: db_path.to_path_buf(),
-            mode,
+impl RecordedTrustGateSource {
+    /// Collect the trust gate decisions recorded for `run_id`.
+    ///
+    /// # Errors
+    /// Returns an error when the run's gate decisions cannot be read.
+    pub fn from_trace(trace_store: &dyn TraceStore, run_id: RunId) -> Result<Self> {
+        let mut attachments = BTreeMap::new();
+        for row in trace_store.get_step_gate_decisions(run_id)? {
+            let decision = row.decision;
+            let (GateKind::Trust, Some(memory_id), Some(version), Some(memory_version_id)) = (
+                &decision.gate_kind,
+                decision.memory_id,
+                decision.version,
+                decision.memory_version_id,
+            ) else {
+                continue;
+            };
+            let evidence = decision.evidence_json.unwrap_or(Value::Null);
+            let attempt = evidence["attempt"]
+                .as_u64()
+                .and_then(|attempt| u32::try_from(attempt).ok())
+                .unwrap_or(1);
+            let attachment = TrustGateAttachment {
+                memory_id,
+                version,
+                memory_version_id,
+                include: decision.decision == GateDecision::Approved,
+                trust_status: evidence["trust_status"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                #[allow(clippy::cast_possible_truncation)]
+                confidence_effective: evidence["confidence_effective"].as_f64().unwrap_or(0.0)
+                    as f32,
+                capped: evidence["capped"].as_bool().unwrap_or(false),
+                reason_codes: decision.reason_codes,
+                ruleset_version: decision.source_ruleset_version,
+                evaluated_at: decision.decided_at,
+                source: decision.decided_by,
+            };
+            attachments.insert(
+                (row.step_key, attempt, memory_version_id.to_string()),
+                attachment,
+            );
         }
+        Ok(Self {
+            attachments,
+            evaluations: RefCell::default(),
+        })
     }
 }
 
-impl TrustGateSource for OutcomeMemoryTrustGateSource {
+impl TrustGateSource for RecordedTrustGateSource {
     fn evaluate(
         &self,
         run_id: RunId,
-        _step_id: StepId,
+        step_id: StepId,
         step_key: &str,
         as_of: time::OffsetDateTime,
         refs: &[ContextRef],
     ) -> Result<Vec<TrustGateAttachment>> {
-        let conn = rusqlite::Connection::open(&self.db_path).with_context(|| {
-            format!(
-                "failed to open OutcomeMemory sqlite at {}",
-                self.db_path.display()
-            )
-        })?;
+        let attempt = {
+            let mut evaluations = self.evaluations.borrow_mut();
+            let attempt = evaluations.entry(step_key.to_string()).or_default();
+            *attempt += 1;
+            *attempt
+        };
+        refs.iter()
+            .map(|item| {
+                let key = (
+                    step_key.to_string(),
+                    attempt,
+                    item.memory_version_id.to_string(),
+                );
+                match self.attachments.get(&key) {
+                    Some(recorded) => Ok(recorded.clone()),
+                    None => AllowAllTrustGateSource
+                        .evaluate(run_id, step_id, step_key, as_of, std::slice::from_ref(item))
+                        .map(|mut allowed| allowed.remove(0)),
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OutcomeMemoryTrustGateSource {
+    db_path: PathBuf,
+    mode: RetrievalMode,
+    pin_context: Option<String>,
+    exclude_superseded: bool,
+}
+
+impl OutcomeMemoryTrustGateSource {
+    #[must_use]
+    pub fn new(db_path: &Path, mode: RetrievalMode) -> Self {
+        Self {
+            db_path: db_path.to_path_buf(),
+            mode,
+            pin_context: None,
+            exclude_superseded: false,
+        }
+    }
+
+    /// Exclude a ref when a newer version of the same memory passes the gate and is not
+    /// retired (`excluded.superseded_by_newer_version`), so explicit refs to stale versions do
+    /// not reach the context.
+    #[must_use]
+    pub fn with_supersession_gating(mut self) -> Self {
+        self.exclude_superseded = true;
+        self
+    }
+
+    /// Look up ruleset pins under `context` (for example the workflow name) instead of the
+    /// per-step gate context `<run_id>:<step_key>`.
+    #[must_use]
+    pub fn with_pin_context(mut self, context: impl Into<String>) -> Self {
+        self.pin_context = Some(context.into());
+        self
+    }
+}
+
+impl TrustGateSource for OutcomeMemoryTrustGateSource {
+    #[allow(clippy::too_many_lines)]
+    fn evaluate(
+        &self,
+        run_id: RunId,
+        _step_id: StepId,
+        step_key: &str,
+        as_of: time::OffsetDateTime,
+        refs: &[ContextRef],
+    ) -> Result<Vec<TrustGateAttachment>> {
+        let conn = rusqlite::Connection::open(&self.db_path).with_context(|| {
+            format!(
+                "failed to open OutcomeMemory sqlite at {}",
+                self.db_path.display()
+            )
+        })?;
 
         let candidates: Vec<MemoryKey> = refs
             .iter()
@@ -370,13 +1315,16 @@ impl TrustGateSource for OutcomeMemoryTrustGateSource {
 
         let rulesets = load_outcome_rulesets(&conn)?;
         let context_id = format!("{run_id}:{step_key}");
-        let mut decisions: Vec<(OutcomeGateDecision, Option<u32>)> =
-            Vec::with_capacity(candidates.len());
-        for candidate in &candidates {
-            let Some((trust, last_ruleset_version)) =
+        let pin = resolve_ruleset_pin(&conn, self.pin_context.as_deref().unwrap_or(&context_id))?;
+        let authoritative =
+            memory_kernel_outcome_store_sqlite::authoritative_memory_keys(&conn, &candidates)?;
+        let gate_candidate = |candidate: &MemoryKey,
+                              authoritative: bool|
+         -> Result<(OutcomeGateDecision, Option<u32>)> {
+            let Some((mut trust, last_ruleset_version)) =
                 get_memory_trust_and_ruleset(&conn, candidate.memory_id, candidate.version)?
             else {
-                decisions.push((
+                return Ok((
                     OutcomeGateDecision {
                         memory_id: candidate.memory_id,
                         version: candidate.version,
@@ -384,22 +1332,59 @@ impl TrustGateSource for OutcomeMemoryTrustGateSource {
                         confidence_effective: 0.0,
                         trust_status: TrustStatus::Active,
                         capped: false,
-                        reason_codes: vec!["excluded.no_trust_snapshot".to_string()],
+                        reason_codes: vec![
+                            memory_kernel_core::REASON_EXCLUDED_NO_TRUST_SNAPSHOT.to_string()
+                        ],
                     },
                     None,
                 ));
-                continue;
             };
 
+            let ruleset_version = pin
+                .as_ref()
+                .map_or(last_ruleset_version, |pin| pin.ruleset_version);
             let ruleset = rulesets
-                .get(&last_ruleset_version)
-                .ok_or_else(|| anyhow!("missing outcome ruleset {last_ruleset_version}"))?;
+                .get(&ruleset_version)
+                .ok_or_else(|| anyhow!("missing outcome ruleset {ruleset_version}"))?;
+            trust.authoritative = authoritative;
             let trust_with_decay = apply_as_of_decay(&trust, ruleset, as_of);
-            decisions.push((
-                gate_memory(&trust_with_decay, self.mode, Some(&context_id), ruleset),
-                Some(last_ruleset_version),
-            ));
+            let mut decision =
+                gate_memory(&trust_with_decay, self.mode, Some(&context_id), ruleset);
+            if let Some(pin) = &pin {
+                decision.reason_codes.push(pin.reason_code());
+            }
+            Ok((decision, Some(ruleset_version)))
+        };
+        let (mut gated, ruleset_versions): (Vec<OutcomeGateDecision>, Vec<Option<u32>>) =
+            candidates
+                .iter()
+                .map(|candidate| gate_candidate(candidate, authoritative.contains(candidate)))
+                .collect::<Result<Vec<_>>>()?
+                .into_iter()
+                .unzip();
+
+        if self.exclude_superseded {
+            let newer =
+                memory_kernel_outcome_store_sqlite::supersession_candidate_keys(&conn, &gated)?;
+            let newer_authoritative =
+                memory_kernel_outcome_store_sqlite::authoritative_memory_keys(&conn, &newer)?;
+            let mut latest_trusted = BTreeMap::new();
+            for key in &newer {
+                let (decision, ruleset_version) =
+                    gate_candidate(key, newer_authoritative.contains(key))?;
+                let trusted = ruleset_version
+                    .and_then(|version| rulesets.get(&version))
+                    .is_some_and(|ruleset| is_trusted_for_supersession(&decision, ruleset));
+                if trusted {
+                    latest_trusted
+                        .entry(decision.memory_id)
+                        .and_modify(|version: &mut u32| *version = (*version).max(decision.version))
+                        .or_insert(decision.version);
+                }
+            }
+            exclude_superseded_versions(&mut gated, &latest_trusted);
         }
+        let decisions = gated.into_iter().zip(ruleset_versions);
 
         let memory_version_by_key: BTreeMap<(String, u32), memory_kernel_core::MemoryVersionId> =
             refs.iter()
@@ -412,7 +1397,6 @@ impl TrustGateSource for OutcomeMemoryTrustGateSource {
                 .collect();
 
         decisions
-            .into_iter()
             .map(|(decision, ruleset_version)| {
                 let memory_version_id = *memory_version_by_key
                     .get(&(decision.memory_id.to_string(), decision.version))
@@ -435,13 +1419,106 @@ impl TrustGateSource for OutcomeMemoryTrustGateSource {
                     reason_codes: decision.reason_codes,
                     ruleset_version,
                     evaluated_at: as_of,
-                    source: "outcome_memory.live".to_string(),
+                    source: pin.as_ref().map_or_else(
+                        || "outcome_memory.live".to_string(),
+                        |pin| format!("outcome_memory.live+pin:{}", pin.context_pattern),
+                    ),
                 })
             })
             .collect()
     }
 }
 
+/// Which trust gate a run uses: `allow-all` includes every item, `outcome-memory` gates items
+/// against an `OutcomeMemory` database in the given retrieval mode.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum TrustGateSelection {
+    #[default]
+    AllowAll,
+    OutcomeMemory {
+        db_path: PathBuf,
+        mode: RetrievalMode,
+    },
+}
+
+impl TrustGateSelection {
+    /// Resolve a selection from host flags. `source` is `allow-all` or `outcome-memory`; when it
+    /// is omitted, `OutcomeMemory` is selected exactly when `trust_db` is given.
+    ///
+    /// # Errors
+    /// Returns an error for an unknown source or mode, when `outcome-memory` is selected without
+    /// a database, or when a database is given with `allow-all`.
+    pub fn from_flags(source: Option<&str>, trust_db: Option<&Path>, mode: &str) -> Result<Self> {
+        let mode = RetrievalMode::parse(mode)
+            .ok_or_else(|| anyhow!("invalid trust_mode '{mode}'; use 'safe' or 'exploration'"))?;
+        match (source, trust_db) {
+            (None | Some("allow-all"), None) => Ok(Self::AllowAll),
+            (None | Some("outcome-memory"), Some(db_path)) => Ok(Self::OutcomeMemory {
+                db_path: db_path.to_path_buf(),
+                mode,
+            }),
+            (Some("outcome-memory"), None) => {
+                Err(anyhow!("trust source outcome-memory requires a trust database"))
+            }
+            (Some("allow-all"), Some(_)) => Err(anyhow!(
+                "trust source allow-all does not use a trust database; drop it or select outcome-memory"
+            )),
+            (Some(other), _) => Err(anyhow!(
+                "invalid trust source '{other}'; use 'allow-all' or 'outcome-memory'"
+            )),
+        }
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AllowAll => "allow-all",
+            Self::OutcomeMemory { .. } => "outcome-memory",
+        }
+    }
+
+    #[must_use]
+    pub fn outcome_db(&self) -> Option<&Path> {
+        match self {
+            Self::AllowAll => None,
+            Self::OutcomeMemory { db_path, .. } => Some(db_path),
+        }
+    }
+
+    /// Build the selected trust gate. An `OutcomeMemory` database must already exist and carry
+    /// the outcome schema; it is never created or migrated here. Ruleset pins are looked up
+    /// under `pin_context` when given (see [`OutcomeMemoryTrustGateSource::with_pin_context`]).
+    ///
+    /// # Errors
+    /// Returns an error when the `OutcomeMemory` database is missing, unreadable, or unmigrated.
+    pub fn open(&self, pin_context: Option<&str>) -> Result<Box<dyn TrustGateSource>> {
+        match self {
+            Self::AllowAll => Ok(Box::new(AllowAllTrustGateSource)),
+            Self::OutcomeMemory { db_path, mode } => {
+                if !db_path.is_file() {
+                    return Err(anyhow!(
+                        "OutcomeMemory trust database not found at {}",
+                        db_path.display()
+                    ));
+                }
+                let store = SqliteOutcomeStore::open(db_path)?;
+                if !store.is_migrated()? {
+                    return Err(anyhow!(
+                        "OutcomeMemory trust database at {} is not migrated; run \
+                         `mk --db <path> outcome projector status` first",
+                        db_path.display()
+                    ));
+                }
+                let source = OutcomeMemoryTrustGateSource::new(db_path, *mode);
+                Ok(Box::new(match pin_context {
+                    Some(context) => source.with_pin_context(context),
+                    None => source,
+                }))
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct DefaultHumanGateDecider;
 
@@ -482,12 +1559,522 @@ impl ProposedWriteApplier for NoopProposedWriteApplier {
     }
 }
 
+/// Replays the provider responses of a [`GoldenRun`], step by step in call order.
+#[derive(Debug, Default)]
+pub struct GoldenResponseSource {
+    by_step: RefCell<BTreeMap<String, VecDeque<RecordedProviderResponse>>>,
+}
+
+impl GoldenResponseSource {
+    #[must_use]
+    pub fn new(golden: &GoldenRun) -> Self {
+        let by_step = golden
+            .steps
+            .iter()
+            .map(|step| {
+                (
+                    step.step_key.clone(),
+                    step.provider_responses.iter().cloned().collect(),
+                )
+            })
+            .collect();
+        Self {
+            by_step: RefCell::new(by_step),
+        }
+    }
+}
+
+impl RecordedResponseSource for GoldenResponseSource {
+    fn next_response(&self, step_key: &str) -> Option<RecordedProviderResponse> {
+        self.by_step
+            .borrow_mut()
+            .get_mut(step_key)
+            .and_then(VecDeque::pop_front)
+    }
+}
+
+/// Repeats the human gate decisions of a [`GoldenRun`], including their reason codes; gates the
+/// golden did not decide are rejected.
+#[derive(Debug, Clone, Default)]
+pub struct GoldenHumanGateDecider {
+    decisions: BTreeMap<(String, String), GoldenGateDecision>,
+}
+
+impl GoldenHumanGateDecider {
+    #[must_use]
+    pub fn new(golden: &GoldenRun) -> Self {
+        let decisions = golden
+            .steps
+            .iter()
+            .flat_map(|step| {
+                step.gate_decisions
+                    .iter()
+                    .filter(|decision| matches!(decision.gate_kind, GateKind::Human))
+                    .map(|decision| {
+                        (
+                            (step.step_key.clone(), decision.gate_name.clone()),
+                            decision.clone(),
+                        )
+                    })
+            })
+            .collect();
+        Self { decisions }
+    }
+}
+
+impl HumanGateDecider for GoldenHumanGateDecider {
+    fn decide(&self, request: &HumanGateRequest) -> Result<HumanGateResponse> {
+        let recorded = self
+            .decisions
+            .get(&(request.step_key.clone(), request.gate_name.clone()));
+        Ok(HumanGateResponse {
+            approved: recorded
+                .is_some_and(|decision| matches!(decision.decision, GateDecision::Approved)),
+            notes: Some("replayed from golden run".to_string()),
+            decided_by: "system.golden".to_string(),
+            reason_codes: recorded.map_or_else(
+                || vec!["rejected.golden_missing".to_string()],
+                |decision| decision.reason_codes.clone(),
+            ),
+        })
+    }
+}
+
+/// Everything a replay answers one recorded run's steps from: its context packages, trust and
+/// human gate decisions, and provider responses.
+struct ReplaySources {
+    context: RecordedContextPackageSource,
+    trust: RecordedTrustGateSource,
+    human_gate: GoldenHumanGateDecider,
+    responses: GoldenResponseSource,
+}
+
+impl ReplaySources {
+    fn from_trace(trace_store: &dyn TraceStore, run_id: RunId) -> Result<Self> {
+        let golden = record_golden_run(trace_store, run_id)?;
+        Ok(Self {
+            context: RecordedContextPackageSource::from_trace(trace_store, run_id)?,
+            trust: RecordedTrustGateSource::from_trace(trace_store, run_id)?,
+            human_gate: GoldenHumanGateDecider::new(&golden),
+            responses: GoldenResponseSource::new(&golden),
+        })
+    }
+}
+
+/// The normalized workflow the trace store snapshotted under `workflow_hash`.
+///
+/// # Errors
+/// Returns an error when the snapshot is missing or does not parse.
+pub fn load_workflow_snapshot(
+    trace_store: &dyn TraceStore,
+    workflow_hash: &str,
+) -> Result<NormalizedWorkflowEnvelope> {
+    let snapshot = trace_store
+        .get_workflow_snapshot(workflow_hash)?
+        .ok_or_else(|| anyhow!("workflow snapshot {workflow_hash} not found"))?;
+    let normalized_workflow: NormalizedWorkflow =
+        serde_json::from_value(snapshot.normalized_json.clone())
+            .map_err(|err| anyhow!("invalid normalized workflow snapshot JSON: {err}"))?;
+    Ok(NormalizedWorkflowEnvelope {
+        source_format: snapshot.source_format,
+        source_yaml_hash: snapshot.source_yaml_hash,
+        normalized_hash: snapshot.workflow_hash,
+        normalized_workflow,
+        normalized_json: snapshot.normalized_json,
+    })
+}
+
+/// Build the golden record of a run from its trace: final step statuses, run-independent output
+/// hashes (the `content_hash` of each step's last `step_finished` event), gate decisions, and
+/// provider responses.
+///
+/// # Errors
+/// Returns an error when the run is unknown or its trace rows cannot be read.
+pub fn record_golden_run(trace_store: &dyn TraceStore, run_id: RunId) -> Result<GoldenRun> {
+    let run = trace_store
+        .get_run(run_id)?
+        .ok_or_else(|| anyhow!("run_id {run_id} not found"))?;
+
+    let mut content_hashes: BTreeMap<String, String> = BTreeMap::new();
+    for row in trace_store.list_events_for_run(run_id)? {
+        if row.event.event_type != TraceEventType::StepFinished {
+            continue;
+        }
+        let payload = &row.event.payload_json;
+        if let (Some(step_key), Some(hash)) = (
+            payload.get("step_key").and_then(Value::as_str),
+            payload.get("content_hash").and_then(Value::as_str),
+        ) {
+            content_hashes.insert(step_key.to_string(), hash.to_string());
+        }
+    }
+
+    let mut gate_decisions: BTreeMap<String, Vec<GoldenGateDecision>> = BTreeMap::new();
+    for row in trace_store.get_step_gate_decisions(run_id)? {
+        gate_decisions
+            .entry(row.step_key)
+            .or_default()
+            .push(GoldenGateDecision::from(&row.decision));
+    }
+
+    let mut provider_responses: BTreeMap<String, Vec<RecordedProviderResponse>> = BTreeMap::new();
+    for row in trace_store.get_step_provider_calls(run_id)? {
+        provider_responses
+            .entry(row.step_key)
+            .or_default()
+            .push(RecordedProviderResponse::from(&row.provider_call));
+    }
+
+    let steps = trace_store
+        .get_step_records(run_id)?
+        .into_iter()
+        .map(|step| GoldenStep {
+            output_hash: content_hashes.remove(&step.step_key),
+            gate_decisions: gate_decisions.remove(&step.step_key).unwrap_or_default(),
+            provider_responses: provider_responses
+                .remove(&step.step_key)
+                .unwrap_or_default(),
+            status: step.status,
+            step_key: step.step_key,
+        })
+        .collect();
+
+    Ok(GoldenRun {
+        golden_version: multi_agent_center_domain::GOLDEN_RUN_VERSION.to_string(),
+        source_run_id: run_id,
+        workflow_name: run.workflow_name,
+        workflow_hash: run.workflow_hash,
+        as_of: run.as_of,
+        steps,
+    })
+}
+
+/// Resolve a standalone step's context queries at `as_of`, apply the agent's permissions and
+/// trust gating, and report the packages the step would be given plus a decision for every
+/// retrieved item. Nothing is executed or traced.
+///
+/// # Errors
+/// Returns an error when context packages cannot be built or trust gating fails.
+pub fn preview_step_context(
+    context_source: &dyn ContextPackageSource,
+    trust_source: &dyn TrustGateSource,
+    definition: &StandaloneStepDefinition,
+    as_of: time::OffsetDateTime,
+) -> Result<StepContextPreview> {
+    let step = &definition.step;
+    let gated = gate_step_context(
+        context_source,
+        trust_source,
+        RunId::new(),
+        StepId::new(),
+        step,
+        &EffectivePermissions::from(&definition.agent.permissions),
+        as_of,
+    )?;
+
+    let trust_by_key: BTreeMap<(String, u32), &TrustGateAttachment> = gated
+        .trust_attachments
+        .iter()
+        .map(|item| ((item.memory_id.to_string(), item.version), item))
+        .collect();
+    let mut item_decisions = Vec::new();
+    for package in &gated.packages {
+        for item in &package.context_package.selected_items {
+            let pruned = gated.pruned_references.iter().find(|pruned| {
+                pruned.package_slot == package.package_slot
+                    && pruned.memory_version_id == item.memory_version_id.to_string()
+            });
+            let trust = trust_by_key.get(&(item.memory_id.to_string(), item.version));
+            let (decision, reasons) = match (pruned, trust) {
+                (Some(pruned), _) => ("pruned_by_permissions", vec![pruned.reason.clone()]),
+                (None, Some(trust)) if !trust.include => {
+                    ("excluded_by_trust", trust.reason_codes.clone())
+                }
+                (None, Some(trust)) => ("injected", trust.reason_codes.clone()),
+                (None, None) => ("injected", item.why.reasons.clone()),
+            };
+            item_decisions.push(ContextItemDecision {
+                package_slot: package.package_slot,
+                memory_id: item.memory_id,
+                version: item.version,
+                memory_version_id: item.memory_version_id,
+                record_type: item.record_type,
+                decision: decision.to_string(),
+                reasons,
+            });
+        }
+        for item in &package.context_package.excluded_items {
+            item_decisions.push(ContextItemDecision {
+                package_slot: package.package_slot,
+                memory_id: item.memory_id,
+                version: item.version,
+                memory_version_id: item.memory_version_id,
+                record_type: item.record_type,
+                decision: "not_selected".to_string(),
+                reasons: item.why.reasons.clone(),
+            });
+        }
+    }
+
+    Ok(StepContextPreview {
+        step_key: step.step_key.clone(),
+        agent_name: definition.agent.agent_name.clone(),
+        as_of,
+        injected_context_packages: gated.gated_packages,
+        item_decisions,
+        trust_gate_attachments: gated.trust_attachments,
+    })
+}
+
+/// Cross-reference the affected steps of `diff` with the `recent_runs` most recent runs of the
+/// old workflow hash: how often each step ran, its final statuses, and its gate decisions.
+///
+/// # Errors
+/// Returns an error when trace rows cannot be read.
+pub fn workflow_upgrade_impact(
+    trace_store: &dyn TraceStore,
+    diff: WorkflowDiff,
+    recent_runs: usize,
+) -> Result<WorkflowUpgradeImpact> {
+    let runs_considered: Vec<RunId> = trace_store
+        .list_runs()?
+        .into_iter()
+        .filter(|run| run.workflow_hash == diff.old_workflow_hash)
+        .take(recent_runs)
+        .map(|run| run.run_id)
+        .collect();
+
+    let mut steps: Vec<StepUpgradeImpact> = diff
+        .affected_steps
+        .iter()
+        .map(|affected| StepUpgradeImpact {
+            step_key: affected.step_key.clone(),
+            reasons: affected.reasons.clone(),
+            estimate: String::new(),
+            runs_observed: 0,
+            statuses: BTreeMap::new(),
+            gate_decisions: BTreeMap::new(),
+        })
+        .collect();
+    for run_id in &runs_considered {
+        let records = trace_store.get_step_records(*run_id)?;
+        let decisions = trace_store.get_step_gate_decisions(*run_id)?;
+        for impact in &mut steps {
+            let Some(record) = records
+                .iter()
+                .find(|record| record.step_key == impact.step_key)
+            else {
+                continue;
+            };
+            impact.runs_observed += 1;
+            *impact
+                .statuses
+                .entry(step_status_to_text(&record.status).to_string())
+                .or_default() += 1;
+            for row in decisions
+                .iter()
+                .filter(|row| row.step_key == impact.step_key)
+            {
+                if matches!(row.decision.gate_kind, GateKind::Trust) {
+                    continue;
+                }
+                *impact
+                    .gate_decisions
+                    .entry(row.decision.gate_name.clone())
+                    .or_default()
+                    .entry(gate_decision_to_text(&row.decision.decision).to_string())
+                    .or_default() += 1;
+            }
+        }
+    }
+
+    for impact in &mut steps {
+        let has = |reason: &str| impact.reasons.iter().any(|item| item == reason);
+        impact.estimate = if has("added") {
+            "new_step"
+        } else if has("removed") {
+            "removed_step"
+        } else if impact.runs_observed == 0 {
+            "no_history"
+        } else if impact.statuses.keys().all(|status| status == "skipped") {
+            "not_exercised"
+        } else {
+            "behavior_change_expected"
+        }
+        .to_string();
+    }
+
+    Ok(WorkflowUpgradeImpact {
+        diff,
+        runs_considered,
+        steps,
+    })
+}
+
+/// Built-in [`ResponseScorer`] running the regex, exact-match, and judge checks of a step's
+/// `scoring` spec.
+#[derive(Debug, Clone, Default)]
+pub struct RuleResponseScorer;
+
+impl ResponseScorer for RuleResponseScorer {
+    fn score(
+        &self,
+        spec: &ResponseScoringSpec,
+        request: &StepRequest,
+        output: &StepOutputEnvelope,
+    ) -> Result<ScoreVerdict> {
+        let mut checks = Vec::new();
+
+        if let Some(pattern) = &spec.regex {
+            let regex = regex::Regex::new(pattern)
+                .with_context(|| format!("invalid scoring.regex `{pattern}`"))?;
+            checks.push(ScoreCheck {
+                check: "regex".to_string(),
+                passed: regex.is_match(&output.message),
+                detail: None,
+            });
+        }
+
+        if let Some(expected) = &spec.expected {
+            checks.push(ScoreCheck {
+                check: "expected".to_string(),
+                passed: &output.payload == expected,
+                detail: None,
+            });
+        }
+
+        if let Some(judge) = &spec.judge {
+            let mut judge_request = request.clone();
+            judge_request.agent.provider = judge.clone();
+            judge_request.task_payload = json!({
+                "judge": {
+                    "task": request.task_payload,
+                    "output": output,
+                },
+            });
+            judge_request.input_hash = compute_step_request_hash(&judge_request)?;
+            let check = match ProviderRegistry::default().invoke(&judge_request) {
+                Ok(invocation) => {
+                    let verdict = invocation
+                        .output
+                        .payload
+                        .get("passed")
+                        .and_then(Value::as_bool);
+                    ScoreCheck {
+                        check: "judge".to_string(),
+                        passed: verdict.unwrap_or(false),
+                        detail: Some(match verdict {
+                            Some(_) => {
+                                format!("judge response {}", invocation.provider_call.response_hash)
+                            }
+                            None => "judge returned no `passed` verdict".to_string(),
+                        }),
+                    }
+                }
+                Err(err) => ScoreCheck {
+                    check: "judge".to_string(),
+                    passed: false,
+                    detail: Some(format!("judge invocation failed: {err}")),
+                },
+            };
+            checks.push(check);
+        }
+
+        Ok(ScoreVerdict {
+            scorer: "rule".to_string(),
+            passed: checks.iter().all(|check| check.passed),
+            checks,
+        })
+    }
+}
+
+/// [`OutcomeEventSink`] appending `success`/`failure` events to an `OutcomeMemory` database.
+#[derive(Debug, Clone)]
+pub struct OutcomeMemoryEventSink {
+    db_path: PathBuf,
+}
+
+impl OutcomeMemoryEventSink {
+    pub const WRITER: &'static str = "multi-agent-center.scorer";
+
+    #[must_use]
+    pub fn new(db_path: &Path) -> Self {
+        Self {
+            db_path: db_path.to_path_buf(),
+        }
+    }
+}
+
+impl OutcomeEventSink for OutcomeMemoryEventSink {
+    fn log_scored_outcome(
+        &self,
+        origin: &ScoredStepOrigin<'_>,
+        verdict: &ScoreVerdict,
+        memories: &[ScoredMemory],
+    ) -> Result<usize> {
+        if memories.is_empty() {
+            return Ok(0);
+        }
+        let mut store = SqliteOutcomeStore::open(&self.db_path)?;
+        let latest_ruleset = store
+            .get_rulesets()?
+            .keys()
+            .next_back()
+            .copied()
+            .ok_or_else(|| anyhow!("OutcomeMemory database has no rulesets"))?;
+        let event_type = if verdict.passed {
+            OutcomeEventType::Success
+        } else {
+            OutcomeEventType::Failure
+        };
+        let occurred_at = now_utc();
+        let gate_context = format!("{}:{}", origin.run_id, origin.step_key);
+        let source = OutcomeEventSource {
+            run_id: origin.run_id.to_string(),
+            step_id: Some(origin.step_id.to_string()),
+            gate_context: Some(gate_context.clone()),
+            engine_version: Some(origin.engine_version.to_string()),
+        };
+        let inputs: Vec<OutcomeEventInput> = memories
+            .iter()
+            .map(|memory| OutcomeEventInput {
+                event_id: None,
+                ruleset_version: memory.ruleset_version.unwrap_or(latest_ruleset),
+                memory_id: memory.memory_id,
+                version: memory.version,
+                event_type,
+                occurred_at,
+                writer: Self::WRITER.to_string(),
+                justification: format!("step output scored by {} scorer", verdict.scorer),
+                context_id: Some(gate_context.clone()),
+                edited: false,
+                escalated: false,
+                severity: None,
+                manual_confidence: None,
+                override_cap: false,
+                payload_json: json!({ "verdict": verdict }),
+                cosigner: None,
+                signature: None,
+                source: Some(source.clone()),
+                effective_at: None,
+            })
+            .collect();
+        Ok(store.append_events(&inputs)?.len())
+    }
+}
+
 pub struct Orchestrator<'a> {
     trace_store: &'a dyn TraceStore,
     context_source: &'a dyn ContextPackageSource,
     trust_source: &'a dyn TrustGateSource,
     human_gate: &'a dyn HumanGateDecider,
     write_applier: &'a dyn ProposedWriteApplier,
+    response_scorer: Option<&'a dyn ResponseScorer>,
+    outcome_sink: Option<&'a dyn OutcomeEventSink>,
+    gate_notifier: Option<&'a dyn GateNotifier>,
+    recorded_responses: Option<&'a dyn RecordedResponseSource>,
+    reason_codes: Option<&'a ReasonCodeRegistry>,
+    providers: ProviderRegistry,
 }
 
 impl<'a> Orchestrator<'a> {
@@ -498,6 +2085,7 @@ impl<'a> Orchestrator<'a> {
         trust_source: &'a dyn TrustGateSource,
         human_gate: &'a dyn HumanGateDecider,
         write_applier: &'a dyn ProposedWriteApplier,
+        providers: ProviderRegistry,
     ) -> Self {
         Self {
             trace_store,
@@ -505,9 +2093,51 @@ impl<'a> Orchestrator<'a> {
             trust_source,
             human_gate,
             write_applier,
+            response_scorer: None,
+            outcome_sink: None,
+            gate_notifier: None,
+            recorded_responses: None,
+            reason_codes: None,
+            providers,
         }
     }
 
+    /// Score outputs of steps that declare `scoring` with `scorer`.
+    #[must_use]
+    pub fn with_response_scorer(mut self, scorer: &'a dyn ResponseScorer) -> Self {
+        self.response_scorer = Some(scorer);
+        self
+    }
+
+    /// Record score verdicts of steps with `scoring.log_outcomes` as outcome events in `sink`.
+    #[must_use]
+    pub fn with_outcome_sink(mut self, sink: &'a dyn OutcomeEventSink) -> Self {
+        self.outcome_sink = Some(sink);
+        self
+    }
+
+    /// Notify the webhooks of deferred human gates through `notifier`.
+    #[must_use]
+    pub fn with_gate_notifier(mut self, notifier: &'a dyn GateNotifier) -> Self {
+        self.gate_notifier = Some(notifier);
+        self
+    }
+
+    /// Replay provider responses from `source` instead of calling providers.
+    #[must_use]
+    pub fn with_recorded_responses(mut self, source: &'a dyn RecordedResponseSource) -> Self {
+        self.recorded_responses = Some(source);
+        self
+    }
+
+    /// Fail a step when its trust gate or permission pruning emits a reason code that is not in
+    /// `registry`. Custom trust gates register their codes with [`ReasonCodeRegistry::register`].
+    #[must_use]
+    pub fn with_reason_code_registry(mut self, registry: &'a ReasonCodeRegistry) -> Self {
+        self.reason_codes = Some(registry);
+        self
+    }
+
     /// Execute a normalized workflow and persist full trace artifacts.
     ///
     /// # Errors
@@ -520,84 +2150,232 @@ impl<'a> Orchestrator<'a> {
         config: RunConfig,
     ) -> Result<RunExecutionSummary> {
         self.trace_store.migrate()?;
+        self.trace_store
+            .set_relaxed_artifacts(&config.relaxed_trace_artifacts)?;
+
+        let require_trust_gating = config.require_trust_gating
+            || workflow
+                .normalized_workflow
+                .defaults
+                .require_trust_gating
+                .unwrap_or(false);
+        // Replays and step reruns inject the recorded, already gated packages, so they are exempt.
+        if require_trust_gating
+            && config.replay_of_run_id.is_none()
+            && config.rerun_of.is_none()
+            && !self.trust_source.is_configured()
+        {
+            return Err(anyhow!(
+                "workflow {} requires trust gating but no trust gate source is configured",
+                workflow.normalized_workflow.workflow_name
+            ));
+        }
 
-        let run_id = config.run_id.unwrap_or_default();
-        let as_of = config.as_of.unwrap_or_else(now_utc);
-        let as_of_was_default = config.as_of.is_none();
+        let enforce_permission_prune = config.enforce_permission_prune
+            || workflow
+                .normalized_workflow
+                .defaults
+                .enforce_permission_prune
+                .unwrap_or(false);
+
+        if config.reuse_correlated_run {
+            if let Some(correlation_id) = config.external_correlation_id.as_deref() {
+                let existing = self
+                    .trace_store
+                    .find_runs_by_external_correlation_id(correlation_id)?
+                    .into_iter()
+                    .find(|run| !matches!(run.status, RunStatus::Failed | RunStatus::Cancelled));
+                if let Some(existing) = existing {
+                    return self.existing_run_summary(&existing);
+                }
+            }
+        }
 
-        self.trace_store.upsert_workflow_snapshot(
-            &workflow.normalized_hash,
-            workflow.normalized_workflow.normalization_version,
-            &workflow.source_format,
-            &workflow.source_yaml_hash,
-            &workflow.normalized_json,
-        )?;
+        let rerun = config
+            .rerun_of
+            .as_ref()
+            .map(|rerun| self.prepare_step_rerun(&workflow.normalized_workflow, rerun))
+            .transpose()?;
 
-        let run = RunRecord {
-            run_id,
-            workflow_name: workflow.normalized_workflow.workflow_name.clone(),
-            workflow_version: workflow.normalized_workflow.workflow_version.clone(),
-            workflow_hash: workflow.normalized_hash.clone(),
-            as_of,
-            as_of_was_default,
-            started_at: now_utc(),
-            ended_at: None,
-            status: RunStatus::Running,
-            replay_of_run_id: config.replay_of_run_id,
-            external_correlation_id: config.external_correlation_id.clone(),
-            engine_version: config.engine_version.clone(),
-            cli_args_json: config.cli_args_json.clone(),
-            manifest_hash: None,
-            manifest_signature: None,
-            manifest_signature_status: "unsigned".to_string(),
-        };
-        self.trace_store.insert_run(&run)?;
-
-        let run_manifest_payload = json!({
-            "schema": "run_manifest.v1",
-            "run_id": run_id.to_string(),
-            "workflow_hash": workflow.normalized_hash,
-            "source_yaml_hash": workflow.source_yaml_hash,
-            "normalization_version": workflow.normalized_workflow.normalization_version,
-            "workflow_name": workflow.normalized_workflow.workflow_name,
-            "workflow_version": workflow.normalized_workflow.workflow_version,
-            "as_of": format_rfc3339(as_of)?,
-            "as_of_was_default": as_of_was_default,
-            "replay_of_run_id": config.replay_of_run_id.map(|id| id.to_string()),
-            "external_correlation_id": config.external_correlation_id,
-            "engine_version": config.engine_version,
-            "cli_args_json": config.cli_args_json,
-        });
-        let run_manifest_hash = hash_json(&run_manifest_payload)?;
-        self.trace_store
-            .update_run_manifest(run_id, &run_manifest_hash, None, "unsigned")?;
+        if config.resume_run.is_some()
+            && (config.rerun_of.is_some() || config.replay_of_run_id.is_some())
+        {
+            return Err(anyhow!("a resumed run cannot also be a rerun or replay"));
+        }
+        let resumed = config
+            .resume_run
+            .map(|run_id| self.load_resumed_run(workflow, run_id))
+            .transpose()?;
+
+        let run_id = match resumed.as_ref() {
+            Some(resumed) => resumed.run.run_id,
+            None => config.run_id.unwrap_or_default(),
+        };
+        let as_of = match resumed.as_ref() {
+            Some(resumed) => resumed.run.as_of,
+            None => config.as_of.unwrap_or_else(now_utc),
+        };
+        let as_of_was_default = match resumed.as_ref() {
+            Some(resumed) => resumed.run.as_of_was_default,
+            None => config.as_of.is_none(),
+        };
 
-        let mut chain = EventChain::default();
-        self.emit_event(
-            run_id,
-            None,
-            TraceEventType::WorkflowNormalized,
-            "system",
-            "orchestrator",
-            json!({
+        let (mut run_manifest_payload, mut chain) = if let Some(resumed) = resumed.as_ref() {
+            let mut chain = EventChain {
+                prev_event_hash: resumed.prev_event_hash.clone(),
+            };
+            self.emit_event(
+                run_id,
+                None,
+                TraceEventType::RunResumed,
+                "system",
+                "orchestrator",
+                json!({
+                    "previous_status": run_status_to_text(&resumed.run.status),
+                    "resumed_steps": resumed.resumed_steps,
+                }),
+                &mut chain,
+            )?;
+            (resumed.manifest.clone(), chain)
+        } else {
+            self.trace_store.upsert_workflow_snapshot(
+                &workflow.normalized_hash,
+                workflow.normalized_workflow.normalization_version,
+                &workflow.source_format,
+                &workflow.source_yaml_hash,
+                &workflow.normalized_json,
+            )?;
+
+            let run = RunRecord {
+                run_id,
+                workflow_name: workflow.normalized_workflow.workflow_name.clone(),
+                workflow_version: workflow.normalized_workflow.workflow_version.clone(),
+                workflow_hash: workflow.normalized_hash.clone(),
+                as_of,
+                as_of_was_default,
+                started_at: now_utc(),
+                ended_at: None,
+                status: RunStatus::Running,
+                replay_of_run_id: config.replay_of_run_id,
+                external_correlation_id: config.external_correlation_id.clone(),
+                engine_version: config.engine_version.clone(),
+                cli_args_json: config.cli_args_json.clone(),
+                manifest_hash: None,
+                manifest_signature: None,
+                manifest_signature_status: "unsigned".to_string(),
+                labels: config.labels.clone(),
+                rerun_of: config.rerun_of.clone(),
+                parent_step: config.parent_step.clone(),
+            };
+            if config.reuse_correlated_run {
+                // Another submitter may have claimed the correlation id since the check above.
+                if let Some(existing) = self.trace_store.insert_run_claiming_correlation(&run)? {
+                    return self.existing_run_summary(&existing);
+                }
+            } else {
+                self.trace_store.insert_run(&run)?;
+            }
+
+            let mut run_manifest_payload = json!({
+                "schema": "run_manifest.v2",
+                "run_id": run_id.to_string(),
                 "workflow_hash": workflow.normalized_hash,
                 "source_yaml_hash": workflow.source_yaml_hash,
                 "normalization_version": workflow.normalized_workflow.normalization_version,
-            }),
-            &mut chain,
-        )?;
-        self.emit_event(
-            run_id,
-            None,
-            TraceEventType::RunStarted,
-            "system",
-            "orchestrator",
-            json!({
+                "workflow_name": workflow.normalized_workflow.workflow_name,
+                "workflow_version": workflow.normalized_workflow.workflow_version,
                 "as_of": format_rfc3339(as_of)?,
                 "as_of_was_default": as_of_was_default,
-            }),
-            &mut chain,
-        )?;
+                "replay_of_run_id": config.replay_of_run_id.map(|id| id.to_string()),
+                "external_correlation_id": config.external_correlation_id,
+                "engine_version": config.engine_version,
+                "cli_args_json": config.cli_args_json,
+                "environment": environment_fingerprint(&config)?,
+                "permission_prune": if enforce_permission_prune { "fail" } else { "continue" },
+            });
+            if let Some(rerun_of) = config.rerun_of.as_ref() {
+                run_manifest_payload["rerun_of"] = serde_json::to_value(rerun_of)?;
+            }
+            if let Some(parent_step) = config.parent_step.as_ref() {
+                run_manifest_payload["parent_step"] = serde_json::to_value(parent_step)?;
+            }
+            let load_balancing_turns =
+                self.claim_load_balancing_turns(&workflow.normalized_workflow, &config)?;
+            if !load_balancing_turns.is_empty() {
+                run_manifest_payload["load_balancing_turns"] = json!(load_balancing_turns);
+            }
+            let run_manifest_hash = hash_json(&run_manifest_payload)?;
+            self.trace_store.update_run_manifest(
+                run_id,
+                &run_manifest_payload,
+                &run_manifest_hash,
+                None,
+                "unsigned",
+            )?;
+
+            let mut chain = EventChain::default();
+            self.emit_event(
+                run_id,
+                None,
+                TraceEventType::WorkflowNormalized,
+                "system",
+                "orchestrator",
+                json!({
+                    "workflow_hash": workflow.normalized_hash,
+                    "source_yaml_hash": workflow.source_yaml_hash,
+                    "normalization_version": workflow.normalized_workflow.normalization_version,
+                }),
+                &mut chain,
+            )?;
+            self.emit_event(
+                run_id,
+                None,
+                TraceEventType::RunStarted,
+                "system",
+                "orchestrator",
+                json!({
+                    "as_of": format_rfc3339(as_of)?,
+                    "as_of_was_default": as_of_was_default,
+                }),
+                &mut chain,
+            )?;
+            if let (Some(rerun_of), Some(rerun)) = (config.rerun_of.as_ref(), rerun.as_ref()) {
+                self.emit_event(
+                    run_id,
+                    None,
+                    TraceEventType::Warning,
+                    "system",
+                    "orchestrator",
+                    json!({
+                        "warning_code": "step_rerun",
+                        "rerun_of_run_id": rerun_of.source_run_id,
+                        "step_key": rerun_of.step_key,
+                        "rerun_steps": rerun.rerun_step_keys(&workflow.normalized_workflow),
+                    }),
+                    &mut chain,
+                )?;
+            }
+            (run_manifest_payload, chain)
+        };
+
+        // Reruns and resumed runs decide gates deferred earlier the way a person resolved them.
+        let resolved_gates: BTreeMap<(String, String), DeferredGateResolution> = match config
+            .rerun_of
+            .as_ref()
+            .map(|rerun_of| rerun_of.source_run_id)
+            .or(config.resume_run)
+        {
+            Some(deferring_run) => self
+                .trace_store
+                .list_deferred_gates(Some(deferring_run))?
+                .into_iter()
+                .filter_map(|gate| {
+                    gate.resolution
+                        .map(|resolution| ((gate.step_key, gate.gate_name), resolution))
+                })
+                .collect(),
+            None => BTreeMap::new(),
+        };
 
         let agents: BTreeMap<&str, &AgentDefinition> = workflow
             .normalized_workflow
@@ -605,20 +2383,41 @@ impl<'a> Orchestrator<'a> {
             .iter()
             .map(|agent| (agent.agent_name.as_str(), agent))
             .collect();
-
-        let steps = &workflow.normalized_workflow.steps;
-        let total_steps = steps.len();
-        let mut step_by_key: BTreeMap<&str, usize> = BTreeMap::new();
-        for (index, step) in steps.iter().enumerate() {
-            step_by_key.insert(step.step_key.as_str(), index);
+        let load_balancing_turns = recorded_load_balancing_turns(&run_manifest_payload);
+        let personas: BTreeMap<&str, AppliedPersona> = workflow
+            .normalized_workflow
+            .personas
+            .iter()
+            .map(|persona| Ok((persona.persona_name.as_str(), AppliedPersona::new(persona)?)))
+            .collect::<Result<_>>()?;
+
+        let mut state = SchedulerState::new(&workflow.normalized_workflow)?;
+        let mut step_memory: BTreeMap<usize, StepMemoryDigest> = BTreeMap::new();
+        // Speculative execution: optional human gates deferred until the DAG finishes, and the
+        // held-back writes of speculative steps.
+        let mut deferred: BTreeMap<usize, Vec<&GatePointDefinition>> = BTreeMap::new();
+        let mut quarantined: BTreeMap<usize, (String, Vec<ProposedMemoryWrite>)> = BTreeMap::new();
+        let taint_policy = workflow.normalized_workflow.defaults.taint.as_ref();
+
+        if let Some(resumed) = resumed.as_ref() {
+            state.restore(resumed)?;
+            if let Some(manifest) = run_manifest_payload.get("injected_memory") {
+                let recorded: RunMemoryManifest = serde_json::from_value(manifest.clone())
+                    .context("invalid recorded injected_memory manifest")?;
+                step_memory.extend(
+                    recorded
+                        .steps
+                        .into_iter()
+                        .map(|digest| (digest.step_index, digest)),
+                );
+            }
         }
 
-        let mut statuses: Vec<StepStatus> = vec![StepStatus::Pending; total_steps];
-        let step_ids: Vec<StepId> = (0..total_steps).map(|_| StepId::new()).collect();
-        let mut inserted_steps = BTreeSet::new();
-
         loop {
-            if statuses
+            state.apply_expansions(resumed.as_ref())?;
+
+            if state
+                .statuses
                 .iter()
                 .all(|status| !matches!(status, StepStatus::Pending | StepStatus::Running))
             {
@@ -627,24 +2426,31 @@ impl<'a> Orchestrator<'a> {
 
             let mut ready: Vec<usize> = Vec::new();
             let mut blocked: Vec<usize> = Vec::new();
+            let mut unmet: BTreeSet<usize> = BTreeSet::new();
 
-            for (idx, step) in steps.iter().enumerate() {
-                if statuses[idx] != StepStatus::Pending {
+            for (idx, step) in state.steps.iter().enumerate() {
+                if state.statuses[idx] != StepStatus::Pending {
                     continue;
                 }
 
                 let mut has_non_success_dependency = false;
                 let mut all_done = true;
                 for dep in &step.depends_on {
-                    let dep_idx = step_by_key
+                    let dep_idx = state
+                        .step_by_key
                         .get(dep.as_str())
                         .ok_or_else(|| anyhow!("unknown dependency {dep}"))?;
-                    match statuses[*dep_idx] {
+                    match state.statuses[*dep_idx] {
                         StepStatus::Succeeded => {}
-                        StepStatus::Pending | StepStatus::Running => {
+                        // Steps behind a deferred step stay pending until the run is resumed.
+                        StepStatus::Pending | StepStatus::Running | StepStatus::Deferred => {
                             all_done = false;
                         }
-                        StepStatus::Failed | StepStatus::Rejected | StepStatus::Skipped => {
+                        StepStatus::Failed
+                        | StepStatus::Rejected
+                        | StepStatus::Skipped
+                        | StepStatus::TimedOut
+                        | StepStatus::Cancelled => {
                             has_non_success_dependency = true;
                         }
                     }
@@ -653,36 +2459,105 @@ impl<'a> Orchestrator<'a> {
                 if has_non_success_dependency {
                     blocked.push(idx);
                 } else if all_done {
-                    ready.push(idx);
+                    match (&state.conditions[idx], state.condition_met.get(&idx)) {
+                        // A `foreach` step is ready to aggregate once all its children finished.
+                        // An `iterate` step starts its first iteration once ready, and finishes
+                        // once its latest iteration finished without queuing another. A `vote`
+                        // step adds its candidates once ready, and its judge step or its
+                        // decision once they finished.
+                        (None, _) | (Some(_), Some(true))
+                            if step.iterate.is_some() || step.vote.is_some() =>
+                        {
+                            let children = if step.vote.is_some() {
+                                state.vote_children.get(&idx)
+                            } else {
+                                state.iterations.get(&idx)
+                            };
+                            if children.map_or(true, |children| state.children_finished(children)) {
+                                ready.push(idx);
+                            }
+                        }
+                        (None, _) | (Some(_), Some(true)) if step.foreach.is_some() => {
+                            let children = state.foreach_children.get(&idx).ok_or_else(|| {
+                                anyhow!("step {} foreach items were never resolved", step.step_key)
+                            })?;
+                            if state.children_finished(children.as_deref().unwrap_or_default()) {
+                                ready.push(idx);
+                            }
+                        }
+                        (None, _) | (Some(_), Some(true)) => ready.push(idx),
+                        (Some(_), Some(false)) => {
+                            blocked.push(idx);
+                            unmet.insert(idx);
+                        }
+                        (Some(condition), None) => {
+                            return Err(anyhow!(
+                                "step {} when condition on step {} was never evaluated",
+                                step.step_key,
+                                condition.step_key
+                            ));
+                        }
+                    }
                 }
             }
 
             ready.sort_unstable();
             blocked.sort_unstable();
 
+            // A cancelled run starts no further state.steps; every pending step is recorded as
+            // cancelled instead.
+            let cancelled = config.cancellation.is_cancelled();
+            let (unstarted_status, unstarted_reason) = if cancelled {
+                ready.clear();
+                unmet.clear();
+                blocked = (0..state.steps.len())
+                    .filter(|idx| state.statuses[*idx] == StepStatus::Pending)
+                    .collect();
+                (StepStatus::Cancelled, "run_cancelled")
+            } else {
+                (StepStatus::Skipped, "dependency_not_satisfied")
+            };
+
             for idx in blocked {
-                statuses[idx] = StepStatus::Skipped;
-                let step = &steps[idx];
-                let step_id = step_ids[idx];
+                let unstarted_reason = if unmet.contains(&idx) {
+                    "condition_not_met"
+                } else {
+                    unstarted_reason
+                };
+                state.statuses[idx] = unstarted_status.clone();
+                let step = &state.steps[idx];
+                let step_id = state.step_ids[idx];
                 let step_record = StepRecord {
                     step_id,
                     run_id,
                     step_index: idx,
                     step_key: step.step_key.clone(),
                     agent_name: step.agent_name.clone(),
-                    status: StepStatus::Skipped,
+                    status: unstarted_status.clone(),
                     started_at: Some(now_utc()),
                     ended_at: Some(now_utc()),
                     task_payload_json: step.task.clone(),
                     constraints_json: serde_json::to_value(&step.constraints)?,
                     permissions_json: Value::Object(Map::default()),
-                    input_hash: "skipped".to_string(),
-                    output_hash: Some("skipped".to_string()),
-                    error_json: Some(json!({"reason": "dependency_not_satisfied"})),
+                    input_hash: step_status_to_text(&unstarted_status).to_string(),
+                    output_hash: Some(step_status_to_text(&unstarted_status).to_string()),
+                    error_json: Some(json!({"reason": unstarted_reason})),
+                    retry_json: None,
+                    prompt_template_hash: None,
+                    provider_selection_json: None,
+                    taint_json: None,
+                    memoized_from_run: None,
+                    memo_key: None,
                 };
-                if !inserted_steps.contains(&idx) {
+                if state.inserted_steps.insert(idx) {
                     self.trace_store.insert_step(&step_record)?;
-                    inserted_steps.insert(idx);
+                } else {
+                    self.trace_store.update_step_status(
+                        step_id,
+                        unstarted_status.clone(),
+                        step_record.output_hash.as_deref(),
+                        step_record.error_json.as_ref(),
+                    )?;
                 }
                 self.emit_event(
                     run_id,
@@ -690,90 +2565,250 @@ impl<'a> Orchestrator<'a> {
                     TraceEventType::StepFinished,
                     "system",
                     "scheduler",
-                    json!({"step_key": step.step_key, "status": "skipped"}),
+                    json!({
+                        "step_key": step.step_key,
+                        "status": step_status_to_text(&unstarted_status),
+                    }),
                     &mut chain,
                 )?;
             }
 
+            if cancelled {
+                break;
+            }
             if ready.is_empty() {
-                if statuses
+                if state
+                    .statuses
                     .iter()
                     .all(|status| !matches!(status, StepStatus::Pending | StepStatus::Running))
+                    || state.statuses.contains(&StepStatus::Deferred)
                 {
                     break;
                 }
                 return Err(anyhow!(
-                    "no ready steps found while pending steps remain; check workflow dependencies"
+                    "no ready state.steps found while pending state.steps remain; check workflow dependencies"
                 ));
             }
 
             for idx in ready {
-                let step = &steps[idx];
-                let step_id = step_ids[idx];
-                statuses[idx] = StepStatus::Running;
+                if config.cancellation.is_cancelled() {
+                    break;
+                }
+                // `foreach`, `iterate`, and `vote` state.steps are never invoked themselves; they
+                // aggregate their children. `workflow_call` state.steps roll up the nested run they start.
+                let aggregated = if let Some(children) = state.foreach_children.get(&idx) {
+                    let children = children.as_deref();
+                    let result = foreach_result(
+                        run_id,
+                        state.step_ids[idx],
+                        &state.steps[idx],
+                        children,
+                        &state.statuses,
+                        &state.child_outputs,
+                    );
+                    Some((result, children))
+                } else if let Some(iterate) = state.steps[idx].iterate.as_ref() {
+                    let Some(children) = state.iterations.get(&idx) else {
+                        state.expansions.push((
+                            idx,
+                            self.start_iteration(
+                                run_id,
+                                &state.steps,
+                                idx,
+                                1,
+                                &Value::Null,
+                                &mut chain,
+                            )?,
+                        ));
+                        continue;
+                    };
+                    let result = iterate_result(
+                        run_id,
+                        state.step_ids[idx],
+                        iterate,
+                        children,
+                        &state.statuses,
+                        &state.child_outputs,
+                        state.converged.get(&idx).copied().unwrap_or(false),
+                    );
+                    Some((result, Some(children.as_slice())))
+                } else if let Some(vote) = state.steps[idx].vote.as_ref() {
+                    let children = state.vote_children.get(&idx);
+                    let judged = children.map(|children| children.len() > vote.agents.len());
+                    let judge_next = vote.aggregator == VoteAggregator::Judge
+                        && judged == Some(false)
+                        && children
+                            .into_iter()
+                            .flatten()
+                            .any(|child| state.statuses[*child] == StepStatus::Succeeded);
+                    if children.is_none() || judge_next {
+                        state.expansions.push((
+                            idx,
+                            self.start_vote_stage(
+                                run_id,
+                                &state.steps,
+                                idx,
+                                children.map(Vec::as_slice),
+                                &state.child_outputs,
+                                &mut chain,
+                            )?,
+                        ));
+                        continue;
+                    }
+                    let children = children.map(Vec::as_slice).unwrap_or_default();
+                    // `vote_decided` is traced on the vote step, so its row is written first.
+                    if state.inserted_steps.insert(idx) {
+                        self.trace_store.insert_step(&aggregate_step_record(
+                            run_id,
+                            state.step_ids[idx],
+                            idx,
+                            &state.steps,
+                            Some(children),
+                        )?)?;
+                    }
+                    let result = self.vote_result(
+                        run_id,
+                        &state.steps,
+                        idx,
+                        children,
+                        &state.statuses,
+                        &state.step_ids,
+                        &state.child_outputs,
+                        config.non_interactive,
+                        &mut chain,
+                    )?;
+                    Some((result, Some(children)))
+                } else if let Some(call) = state.steps[idx].workflow_call.as_ref() {
+                    let result = self.execute_workflow_call(
+                        run_id,
+                        state.step_ids[idx],
+                        &state.steps[idx],
+                        call,
+                        &config,
+                        as_of,
+                        &mut chain,
+                    )?;
+                    Some((result, None))
+                } else {
+                    None
+                };
+                if let Some((result, children)) = aggregated {
+                    let result = self.finish_aggregate_step(
+                        run_id,
+                        state.step_ids[idx],
+                        idx,
+                        &state.steps,
+                        children,
+                        result,
+                        !state.inserted_steps.insert(idx),
+                        &mut chain,
+                    )?;
+                    for child in children.unwrap_or_default() {
+                        let relies_on = state.speculative_deps[*child].clone();
+                        state.speculative_deps[idx].extend(relies_on);
+                    }
+                    state.taints[idx].inherited_from = children
+                        .unwrap_or_default()
+                        .iter()
+                        .filter(|child| state.taints[**child].is_tainted())
+                        .map(|child| state.steps[*child].step_key.clone())
+                        .collect();
+                    if result.status == StepStatus::Succeeded {
+                        self.evaluate_step_conditions(
+                            run_id,
+                            state.step_ids[idx],
+                            &state.steps,
+                            &state.conditions,
+                            idx,
+                            &result.outputs,
+                            &mut state.condition_met,
+                            &mut chain,
+                        )?;
+                        state.expansions.extend(self.resolve_foreach_items(
+                            run_id,
+                            state.step_ids[idx],
+                            &state.steps,
+                            idx,
+                            &result.outputs,
+                            &state.foreach_children,
+                            &mut chain,
+                        )?);
+                    }
+                    state.statuses[idx] = result.status;
+                    continue;
+                }
+                let step = &state.steps[idx];
+                let step_id = state.step_ids[idx];
+                state.statuses[idx] = StepStatus::Running;
 
                 let agent = agents
                     .get(step.agent_name.as_str())
                     .ok_or_else(|| anyhow!("unknown agent {}", step.agent_name))?;
+                let first_turn = load_balancing_turns
+                    .get(agent.agent_name.as_str())
+                    .copied()
+                    .unwrap_or(0);
+                let balanced = balanced_agent(agent, &state.steps, idx, first_turn);
+                let (agent, endpoint_selection) = match balanced.as_ref() {
+                    Some((agent, selection)) => (agent, Some(selection)),
+                    None => (*agent, None),
+                };
                 let effective_permissions = EffectivePermissions::from(&agent.permissions);
-
-                let packages = self
-                    .context_source
-                    .packages_for_step(run_id, step, as_of)
-                    .with_context(|| {
-                        format!(
-                            "failed to obtain context packages for step {}",
-                            step.step_key
-                        )
-                    })?;
-
-                let PermissionPruneResult {
-                    packages: permission_packages,
-                    pruned_references,
-                } = apply_context_permissions(&packages, &effective_permissions)?;
-
-                let refs: Vec<ContextRef> = permission_packages
-                    .iter()
-                    .flat_map(|package| {
-                        package
-                            .context_package
-                            .selected_items
-                            .iter()
-                            .map(|item| ContextRef {
-                                memory_id: item.memory_id,
-                                version: item.version,
-                                memory_version_id: item.memory_version_id,
-                            })
+                let persona = step
+                    .persona
+                    .as_deref()
+                    .or(agent.persona.as_deref())
+                    .map(|name| {
+                        personas
+                            .get(name)
+                            .ok_or_else(|| anyhow!("unknown persona {name}"))
                     })
-                    .collect();
-
-                let trust_attachments =
-                    self.trust_source
-                        .evaluate(run_id, step_id, &step.step_key, as_of, &refs)?;
-
-                let trust_map: BTreeMap<(String, u32), bool> = trust_attachments
-                    .iter()
-                    .map(|item| ((item.memory_id.to_string(), item.version), item.include))
-                    .collect();
-                let trust_included = trust_attachments.iter().filter(|item| item.include).count();
-                let trust_excluded = trust_attachments.len().saturating_sub(trust_included);
+                    .transpose()?;
 
-                let gated_packages = apply_trust_filter(&permission_packages, &trust_map)?;
-
-                let mut step_request = StepRequest {
+                let prepared = self.prepare_step_input(
                     run_id,
                     step_id,
-                    step_key: step.step_key.clone(),
+                    step,
+                    agent,
+                    persona,
+                    &effective_permissions,
                     as_of,
-                    agent: (*agent).clone(),
-                    task_payload: step.task.clone(),
-                    injected_context_packages: gated_packages,
-                    trust_gate_attachments: trust_attachments,
-                    effective_permissions: effective_permissions.clone(),
-                    constraints: step.constraints.clone(),
-                    input_hash: String::new(),
+                )?;
+                let mut step_request = prepared.request.clone();
+
+                let mut taint = match taint_policy {
+                    Some(policy) => {
+                        let mut taint = injected_taint(policy, &step_request);
+                        taint.inherited_from = step
+                            .depends_on
+                            .iter()
+                            .filter(|dep| {
+                                state
+                                    .step_by_key
+                                    .get(dep.as_str())
+                                    .is_some_and(|dep_idx| state.taints[*dep_idx].is_tainted())
+                            })
+                            .cloned()
+                            .collect();
+                        taint
+                    }
+                    None => StepTaint::default(),
+                };
+                let memo_key = compute_step_memo_key(&step_request)?;
+                let memoized = match rerun.as_ref() {
+                    Some(rerun) if rerun.invalidated.contains(&idx) => None,
+                    Some(rerun) if conversation_turns(step)?.is_none() => {
+                        rerun.reused.get(step.step_key.as_str()).cloned()
+                    }
+                    None if config.memoize_steps
+                        && self.recorded_responses.is_none()
+                        && conversation_turns(step)?.is_none() =>
+                    {
+                        self.trace_store
+                            .find_memoized_step(&workflow.normalized_hash, &memo_key)?
+                    }
+                    _ => None,
                 };
-                step_request.input_hash = compute_step_request_hash(&step_request)?;
 
                 let step_record = StepRecord {
                     step_id,
@@ -790,48 +2825,24 @@ impl<'a> Orchestrator<'a> {
                     input_hash: step_request.input_hash.clone(),
                     output_hash: None,
                     error_json: None,
+                    retry_json: None,
+                    prompt_template_hash: step_request
+                        .prompt
+                        .as_ref()
+                        .map(|prompt| prompt.template_hash.clone()),
+                    provider_selection_json: None,
+                    taint_json: taint
+                        .is_tainted()
+                        .then(|| serde_json::to_value(&taint))
+                        .transpose()?,
+                    memoized_from_run: memoized.as_ref().map(|memo| memo.run_id),
+                    memo_key: Some(memo_key),
                 };
-                if !inserted_steps.contains(&idx) {
+                if !state.inserted_steps.contains(&idx) {
                     self.trace_store.insert_step(&step_record)?;
-                    inserted_steps.insert(idx);
-                }
-
-                for attachment in &step_request.trust_gate_attachments {
-                    let trust_decision = if attachment.include {
-                        GateDecision::Approved
-                    } else {
-                        GateDecision::Rejected
-                    };
-                    self.trace_store.append_gate_decision(
-                        run_id,
-                        step_id,
-                        &GateDecisionRecord {
-                            gate_kind: GateKind::Trust,
-                            gate_name: "trust_gate".to_string(),
-                            subject_type: "memory_ref".to_string(),
-                            memory_id: Some(attachment.memory_id),
-                            version: Some(attachment.version),
-                            memory_version_id: Some(attachment.memory_version_id),
-                            decision: trust_decision,
-                            reason_codes: attachment.reason_codes.clone(),
-                            notes: Some(format!(
-                                "status={} confidence_effective={:.6} capped={}",
-                                attachment.trust_status,
-                                attachment.confidence_effective,
-                                attachment.capped
-                            )),
-                            decided_by: attachment.source.clone(),
-                            decided_at: attachment.evaluated_at,
-                            source_ruleset_version: attachment.ruleset_version,
-                            evidence_json: Some(json!({
-                                "trust_status": attachment.trust_status,
-                                "confidence_effective": attachment.confidence_effective,
-                                "capped": attachment.capped,
-                                "source": attachment.source,
-                            })),
-                        },
-                    )?;
+                    state.inserted_steps.insert(idx);
                 }
+                step_memory.insert(idx, injected_memory_digest(idx, step, &step_request));
 
                 self.emit_event(
                     run_id,
@@ -843,108 +2854,28 @@ impl<'a> Orchestrator<'a> {
                     &mut chain,
                 )?;
 
-                for package in &step_request.injected_context_packages {
-                    self.trace_store
-                        .append_context_package(run_id, step_id, package)?;
-                }
+                let recorded_context = resumed
+                    .as_ref()
+                    .and_then(|resumed| resumed.recorded_context.get(&step.step_key));
+                let fail_on_prune = self.record_step_context(
+                    step,
+                    &prepared,
+                    1,
+                    recorded_context,
+                    enforce_permission_prune,
+                    &mut chain,
+                )?;
+                let pruned_references = prepared.pruned_references;
 
-                self.emit_event(
+                let missing_dependencies = self.check_memory_dependencies(
                     run_id,
-                    Some(step_id),
-                    TraceEventType::StepInputPrepared,
-                    "system",
-                    "orchestrator",
-                    json!({
-                        "step_key": step.step_key,
-                        "context_packages": step_request.injected_context_packages.len(),
-                        "context_refs": refs.len(),
-                        "trust_attachments": step_request.trust_gate_attachments.len(),
-                    }),
+                    step_id,
+                    step,
+                    &step_request,
+                    &pruned_references,
                     &mut chain,
                 )?;
 
-                if trust_included + trust_excluded > 0 {
-                    self.emit_event(
-                        run_id,
-                        Some(step_id),
-                        TraceEventType::GateEvaluated,
-                        "system",
-                        "trust_gate",
-                        json!({
-                            "gate_kind": "trust",
-                            "gate_name": "trust_gate",
-                            "step_key": step.step_key,
-                            "included": trust_included,
-                            "excluded": trust_excluded,
-                        }),
-                        &mut chain,
-                    )?;
-                }
-
-                if !pruned_references.is_empty() {
-                    self.emit_event(
-                        run_id,
-                        Some(step_id),
-                        TraceEventType::StepPermissionPruned,
-                        "system",
-                        "policy",
-                        json!({
-                            "count": pruned_references.len(),
-                            "items": pruned_references,
-                        }),
-                        &mut chain,
-                    )?;
-
-                    self.emit_event(
-                        run_id,
-                        Some(step_id),
-                        TraceEventType::Warning,
-                        "system",
-                        "policy",
-                        json!({
-                            "warning_code": "context_pruned",
-                            "count": pruned_references.len(),
-                            "continue_execution": true,
-                        }),
-                        &mut chain,
-                    )?;
-
-                    if effective_permissions.fail_on_permission_prune {
-                        self.emit_event(
-                            run_id,
-                            Some(step_id),
-                            TraceEventType::Warning,
-                            "system",
-                            "policy",
-                            json!({
-                                "warning_code": "fail_on_permission_prune_ignored",
-                                "reason": "locked_decision_continue_on_prune",
-                            }),
-                            &mut chain,
-                        )?;
-                    }
-
-                    self.trace_store.append_gate_decision(
-                        run_id,
-                        step_id,
-                        &GateDecisionRecord {
-                            gate_kind: GateKind::Policy,
-                            gate_name: "context_permission".to_string(),
-                            subject_type: "context_items".to_string(),
-                            memory_id: None,
-                            version: None,
-                            memory_version_id: None,
-                            decision: GateDecision::Pruned,
-                            reason_codes: vec!["context_items_pruned".to_string()],
-                            notes: Some(format!("{} item(s) pruned", pruned_references.len())),
-                            decided_by: "policy.engine".to_string(),
-                            decided_at: now_utc(),
-                            source_ruleset_version: None,
-                            evidence_json: None,
-                        },
-                    )?;
-                }
-
                 self.emit_event(
                     run_id,
                     Some(step_id),
@@ -956,7 +2887,17 @@ impl<'a> Orchestrator<'a> {
                 )?;
 
                 let mut rejected_by_human_gate = false;
-                for gate_name in &step.gate_points {
+                let mut elevated_approved: Option<bool> = None;
+                let mut deferred_gates = Vec::new();
+                let mut held_by_gates = Vec::new();
+                // A step failing its declared dependencies or a prune it may not continue past
+                // never reaches its human gates.
+                let gate_points: &[String] = if missing_dependencies.is_some() || fail_on_prune {
+                    &[]
+                } else {
+                    &step.gate_points
+                };
+                for gate_name in gate_points {
                     let gate = workflow
                         .normalized_workflow
                         .gates
@@ -968,147 +2909,252 @@ impl<'a> Orchestrator<'a> {
                         continue;
                     }
 
-                    let decision = self.human_gate.decide(&HumanGateRequest {
-                        run_id,
-                        step_id,
-                        step_key: step.step_key.clone(),
-                        gate_name: gate_name.clone(),
-                        required: gate.required,
-                        non_interactive: config.non_interactive,
-                    })?;
+                    if let Some((rule, evidence)) = match_delegated_approval(gate, &step_request)? {
+                        self.record_delegated_approval(
+                            run_id, step_id, gate, rule, &evidence, &mut chain,
+                        )?;
+                        continue;
+                    }
 
-                    let gate_decision = if decision.approved {
-                        GateDecision::Approved
-                    } else {
-                        GateDecision::Rejected
-                    };
+                    let resolution =
+                        resolved_gates.get(&(step.step_key.clone(), gate_name.clone()));
+                    if resolution.is_none()
+                        && config.non_interactive
+                        && gate.on_non_interactive == Some(NonInteractiveGateAction::Defer)
+                    {
+                        self.defer_human_gate(run_id, step_id, &step.step_key, gate, &mut chain)?;
+                        if gate.required {
+                            held_by_gates.push(gate.gate_name.clone());
+                        }
+                        continue;
+                    }
 
-                    self.trace_store.append_gate_decision(
-                        run_id,
-                        step_id,
-                        &GateDecisionRecord {
-                            gate_kind: GateKind::Human,
-                            gate_name: gate_name.clone(),
-                            subject_type: "step".to_string(),
-                            memory_id: None,
-                            version: None,
-                            memory_version_id: None,
-                            decision: gate_decision.clone(),
-                            reason_codes: decision.reason_codes.clone(),
-                            notes: decision.notes.clone(),
-                            decided_by: decision.decided_by.clone(),
-                            decided_at: now_utc(),
-                            source_ruleset_version: None,
-                            evidence_json: None,
-                        },
-                    )?;
+                    if resolution.is_none() && config.speculative_execution && !gate.required {
+                        self.emit_event(
+                            run_id,
+                            Some(step_id),
+                            TraceEventType::GateEvaluated,
+                            "system",
+                            "orchestrator",
+                            json!({
+                                "gate_kind": "human",
+                                "gate_name": gate_name,
+                                "decision": "pending",
+                                "required": false,
+                                "speculative": true,
+                            }),
+                            &mut chain,
+                        )?;
+                        deferred_gates.push(gate);
+                        continue;
+                    }
 
-                    self.emit_event(
-                        run_id,
-                        Some(step_id),
-                        TraceEventType::GateEvaluated,
-                        "human",
-                        &decision.decided_by,
-                        json!({
-                            "gate_kind": "human",
-                            "gate_name": gate_name,
-                            "decision": match gate_decision {
-                                GateDecision::Approved => "approved",
-                                GateDecision::Rejected => "rejected",
-                                GateDecision::Pruned => "pruned",
-                            },
-                            "required": gate.required,
-                            "reason_codes": decision.reason_codes,
-                            "notes": decision.notes,
-                        }),
-                        &mut chain,
-                    )?;
+                    let decision = match resolution {
+                        Some(resolution) => self.record_gate_resolution(
+                            run_id, step_id, gate, resolution, &mut chain,
+                        )?,
+                        None => self.decide_human_gate(
+                            run_id,
+                            step_id,
+                            &step.step_key,
+                            gate,
+                            config.non_interactive,
+                            &mut chain,
+                        )?,
+                    };
 
                     if gate.required && !decision.approved {
                         rejected_by_human_gate = true;
                     }
+                    if taint_policy.is_some_and(|policy| policy.elevated_gate == gate.gate_name) {
+                        elevated_approved = Some(decision.approved);
+                    }
+                }
+
+                // Tainted context only reaches an external provider once the elevated gate
+                // approves it.
+                if let Some(policy) = taint_policy {
+                    if taint.is_tainted()
+                        && missing_dependencies.is_none()
+                        && !fail_on_prune
+                        && !rejected_by_human_gate
+                        && held_by_gates.is_empty()
+                        && elevated_approved.is_none()
+                        && memoized.is_none()
+                        && sends_to_external_provider(agent)
+                    {
+                        let approved = self.decide_elevated_gate(
+                            run_id,
+                            step_id,
+                            &step.step_key,
+                            workflow,
+                            policy,
+                            "external_provider",
+                            config.non_interactive,
+                            &mut chain,
+                        )?;
+                        elevated_approved = Some(approved);
+                        rejected_by_human_gate = !approved;
+                    }
                 }
 
                 let result = if rejected_by_human_gate {
-                    StepResult {
-                        run_id,
-                        step_id,
-                        status: StepStatus::Rejected,
-                        outputs: multi_agent_center_domain::StepOutputEnvelope {
-                            message: "step rejected by human gate".to_string(),
-                            payload: json!({"rejected": true}),
-                        },
-                        proposed_memory_writes: Vec::new(),
-                        provider_calls: Vec::new(),
-                        gate_decisions: Vec::new(),
-                        output_hash: String::new(),
-                        error: None,
+                    rejected_step_result(run_id, step_id)
+                } else if !held_by_gates.is_empty() {
+                    deferred_gate_result(run_id, step_id, &held_by_gates)
+                } else if let Some(missing) = missing_dependencies {
+                    missing_dependency_result(run_id, step_id, &missing)
+                } else if fail_on_prune {
+                    permission_prune_result(run_id, step_id, &pruned_references)
+                } else if let Some(memo) = memoized.as_ref() {
+                    self.memoized_step_result(run_id, step_id, &step_request, memo, &mut chain)?
+                } else if let Some(turns) = conversation_turns(step)? {
+                    if let Some(selection) = endpoint_selection {
+                        self.trace_store.update_step_provider_selection(
+                            step_id,
+                            &json!({"endpoint": selection}),
+                        )?;
                     }
+                    let budget = StepBudget::new(&step.constraints, &config.cancellation);
+                    self.run_conversation_turns(step, &step_request, turns, &budget, &mut chain)?
                 } else {
-                    match route_provider_call(&step_request) {
-                        Ok(invocation) => {
-                            self.persist_provider_call(run_id, step_id, &invocation, &mut chain)?;
-                            build_step_result_from_provider(run_id, step_id, invocation)
+                    let budget = StepBudget::new(&step.constraints, &config.cancellation);
+                    let ProviderStepOutcome {
+                        invocation,
+                        retry_json,
+                        provider_selection_json,
+                        halted,
+                    } = self.invoke_provider_with_retries(
+                        step,
+                        agent,
+                        &effective_permissions,
+                        &mut step_request,
+                        &budget,
+                        RetryGating {
+                            workflow,
+                            recorded_context,
+                            enforce_permission_prune,
+                            non_interactive: config.non_interactive,
+                            taint: &mut taint,
+                            elevated_approved: &mut elevated_approved,
+                        },
+                        &mut chain,
+                    )?;
+                    if let Some(retry_json) = retry_json.as_ref() {
+                        self.trace_store.update_step_retry(step_id, retry_json)?;
+                        step_memory.insert(idx, injected_memory_digest(idx, step, &step_request));
+                    }
+                    let provider_selection_json = match endpoint_selection {
+                        Some(endpoint) => {
+                            let mut selection =
+                                provider_selection_json.unwrap_or_else(|| json!({}));
+                            selection["endpoint"] = endpoint.clone();
+                            Some(selection)
                         }
-                        Err(err) => {
-                            self.emit_event(
+                        None => provider_selection_json,
+                    };
+                    if let Some(selection) = provider_selection_json.as_ref() {
+                        self.trace_store
+                            .update_step_provider_selection(step_id, selection)?;
+                    }
+                    match (halted, invocation) {
+                        (Some(result), _) => result,
+                        (None, Ok(invocation)) => {
+                            self.persist_provider_call(
                                 run_id,
-                                Some(step_id),
-                                TraceEventType::Error,
-                                "provider",
-                                "router",
-                                json!({
-                                    "step_key": step.step_key,
-                                    "error": err.to_string(),
-                                }),
+                                step_id,
+                                &invocation.provider_call,
                                 &mut chain,
                             )?;
-                            StepResult {
-                                run_id,
-                                step_id,
-                                status: StepStatus::Failed,
-                                outputs: multi_agent_center_domain::StepOutputEnvelope {
-                                    message: "provider invocation failed".to_string(),
-                                    payload: json!({"failed": true}),
-                                },
-                                proposed_memory_writes: Vec::new(),
-                                provider_calls: Vec::new(),
-                                gate_decisions: Vec::new(),
-                                output_hash: String::new(),
-                                error: Some(multi_agent_center_domain::ErrorEnvelope {
-                                    code: "provider_invocation_failed".to_string(),
-                                    message: err.to_string(),
-                                }),
-                            }
+                            build_step_result_from_provider(run_id, step_id, invocation)
                         }
+                        (None, Err(err)) => self.provider_failure_result(
+                            run_id,
+                            step_id,
+                            &step.step_key,
+                            &err,
+                            &mut chain,
+                        )?,
                     }
                 };
 
                 let mut result = result;
+                if result.status == StepStatus::Succeeded {
+                    if let Some(spec) = step.scoring.as_ref() {
+                        result.score = self.score_step_output(
+                            &step.step_key,
+                            &config.engine_version,
+                            spec,
+                            &step_request,
+                            &result.outputs,
+                            &mut chain,
+                        )?;
+                    }
+                }
                 result.output_hash = compute_step_result_hash(&result)?;
 
-                if config.apply_proposed_writes && !result.proposed_memory_writes.is_empty() {
-                    for proposal in &result.proposed_memory_writes {
-                        let apply = self.write_applier.apply(run_id, step_id, proposal)?;
-                        self.trace_store.append_proposed_memory_write(
-                            run_id,
-                            step_id,
-                            proposal,
-                            &apply.disposition,
-                            apply.disposition_reason.as_deref(),
-                        )?;
+                // Tainted outputs are proposed as writes only once the elevated gate approves.
+                let mut taint_rejected_writes = Vec::new();
+                if let Some(policy) = taint_policy {
+                    if taint.is_tainted() && !result.proposed_memory_writes.is_empty() {
+                        let approved = match elevated_approved {
+                            Some(approved) => approved,
+                            None => self.decide_elevated_gate(
+                                run_id,
+                                step_id,
+                                &step.step_key,
+                                workflow,
+                                policy,
+                                "proposed_writes",
+                                config.non_interactive,
+                                &mut chain,
+                            )?,
+                        };
+                        if !approved {
+                            taint_rejected_writes =
+                                std::mem::take(&mut result.proposed_memory_writes);
+                        }
                     }
+                }
+                for proposal in &taint_rejected_writes {
+                    self.trace_store.append_proposed_memory_write(
+                        run_id,
+                        step_id,
+                        proposal,
+                        "discarded",
+                        Some("taint_gate_rejected"),
+                    )?;
+                }
+                state.taints[idx] = taint;
+
+                let mut relies_on: BTreeSet<usize> = step
+                    .depends_on
+                    .iter()
+                    .filter_map(|dep| state.step_by_key.get(dep.as_str()))
+                    .flat_map(|dep_idx| state.speculative_deps[*dep_idx].iter().copied())
+                    .collect();
+                if !deferred_gates.is_empty() {
+                    relies_on.insert(idx);
+                    deferred.insert(idx, deferred_gates);
+                }
+                let speculative = !relies_on.is_empty() && result.status == StepStatus::Succeeded;
+                if speculative {
+                    quarantined.insert(
+                        idx,
+                        (
+                            result.output_hash.clone(),
+                            result.proposed_memory_writes.clone(),
+                        ),
+                    );
                 } else {
-                    for proposal in &result.proposed_memory_writes {
-                        self.trace_store.append_proposed_memory_write(
-                            run_id,
-                            step_id,
-                            proposal,
-                            "not_applied",
-                            Some("apply_proposed_writes_disabled"),
-                        )?;
-                    }
+                    self.record_proposed_writes(
+                        run_id,
+                        step_id,
+                        &result.proposed_memory_writes,
+                        config.apply_proposed_writes,
+                    )?;
                 }
+                state.speculative_deps[idx] = relies_on;
 
                 self.trace_store.update_step_status(
                     step_id,
@@ -1132,38 +3178,187 @@ impl<'a> Orchestrator<'a> {
                         "step_key": step.step_key,
                         "status": step_status_to_text(&result.status),
                         "output_hash": result.output_hash,
+                        "content_hash": compute_step_content_hash(&result)?,
+                        "speculative": speculative,
+                    }),
+                    &mut chain,
+                )?;
+
+                if result.status == StepStatus::Succeeded {
+                    self.evaluate_step_conditions(
+                        run_id,
+                        step_id,
+                        &state.steps,
+                        &state.conditions,
+                        idx,
+                        &result.outputs,
+                        &mut state.condition_met,
+                        &mut chain,
+                    )?;
+                    state.expansions.extend(self.resolve_foreach_items(
+                        run_id,
+                        step_id,
+                        &state.steps,
+                        idx,
+                        &result.outputs,
+                        &state.foreach_children,
+                        &mut chain,
+                    )?);
+                    if let Some(&(parent, iteration)) = state.iteration_parent.get(&idx) {
+                        let (met, next) = self.evaluate_iteration(
+                            run_id,
+                            step_id,
+                            &state.steps,
+                            parent,
+                            iteration,
+                            &result.outputs,
+                            &mut chain,
+                        )?;
+                        state.converged.insert(parent, met);
+                        state.expansions.extend(next.map(|next| (parent, next)));
+                    }
+                    if state.foreach_parent.contains_key(&idx)
+                        || state.iteration_parent.contains_key(&idx)
+                        || state.vote_parent.contains_key(&idx)
+                    {
+                        state.child_outputs.insert(idx, result.outputs);
+                    }
+                }
+                state.statuses[idx] = result.status;
+            }
+        }
+
+        let mut gate_approved: BTreeMap<usize, bool> = BTreeMap::new();
+        for (gate_idx, gates) in &deferred {
+            let mut approved = true;
+            for gate in gates {
+                let decision = self.decide_human_gate(
+                    run_id,
+                    state.step_ids[*gate_idx],
+                    &state.steps[*gate_idx].step_key,
+                    gate,
+                    config.non_interactive,
+                    &mut chain,
+                )?;
+                approved &= decision.approved;
+            }
+            gate_approved.insert(*gate_idx, approved);
+        }
+
+        for (idx, (output_hash, proposals)) in quarantined {
+            let step_id = state.step_ids[idx];
+            let step_key = &state.steps[idx].step_key;
+            let rejected_gate_steps: Vec<&str> = state.speculative_deps[idx]
+                .iter()
+                .filter(|gate_idx| !gate_approved.get(gate_idx).copied().unwrap_or(false))
+                .map(|gate_idx| state.steps[*gate_idx].step_key.as_str())
+                .collect();
+
+            if rejected_gate_steps.is_empty() {
+                self.record_proposed_writes(
+                    run_id,
+                    step_id,
+                    &proposals,
+                    config.apply_proposed_writes,
+                )?;
+                self.emit_event(
+                    run_id,
+                    Some(step_id),
+                    TraceEventType::Warning,
+                    "system",
+                    "orchestrator",
+                    json!({
+                        "warning_code": "speculative_step_committed",
+                        "step_key": step_key,
                     }),
                     &mut chain,
                 )?;
+                continue;
+            }
 
-                statuses[idx] = result.status;
+            for proposal in &proposals {
+                self.trace_store.append_proposed_memory_write(
+                    run_id,
+                    step_id,
+                    proposal,
+                    "discarded",
+                    Some("speculative_gate_rejected"),
+                )?;
             }
+            self.trace_store.update_step_status(
+                step_id,
+                StepStatus::Skipped,
+                Some(&output_hash),
+                Some(&json!({
+                    "reason": "speculation_discarded",
+                    "rejected_gate_steps": rejected_gate_steps,
+                })),
+            )?;
+            state.statuses[idx] = StepStatus::Skipped;
+            self.emit_event(
+                run_id,
+                Some(step_id),
+                TraceEventType::Warning,
+                "system",
+                "orchestrator",
+                json!({
+                    "warning_code": "speculative_step_discarded",
+                    "step_key": step_key,
+                    "rejected_gate_steps": rejected_gate_steps,
+                }),
+                &mut chain,
+            )?;
         }
 
         let mut succeeded = 0_usize;
         let mut failed_or_rejected = 0_usize;
-        for status in &statuses {
+        let mut deferred_steps = 0_usize;
+        let mut cancelled_steps = 0_usize;
+        for status in &state.statuses {
             match status {
                 StepStatus::Succeeded => succeeded += 1,
-                StepStatus::Failed | StepStatus::Rejected => failed_or_rejected += 1,
+                StepStatus::Failed | StepStatus::Rejected | StepStatus::TimedOut => {
+                    failed_or_rejected += 1;
+                }
+                StepStatus::Deferred => deferred_steps += 1,
+                StepStatus::Cancelled => cancelled_steps += 1,
                 StepStatus::Pending | StepStatus::Running | StepStatus::Skipped => {}
             }
         }
 
-        let run_status = if statuses
+        // A run with deferred state.steps stays resumable whatever else happened in it, unless it was
+        // cancelled.
+        let run_status = if cancelled_steps > 0 {
+            RunStatus::Cancelled
+        } else if deferred_steps > 0 {
+            RunStatus::PartiallyCompleted
+        } else if state
+            .statuses
             .iter()
             .any(|status| matches!(status, StepStatus::Rejected))
         {
             RunStatus::Rejected
-        } else if statuses
+        } else if state
+            .statuses
             .iter()
-            .any(|status| matches!(status, StepStatus::Failed))
+            .any(|status| matches!(status, StepStatus::Failed | StepStatus::TimedOut))
         {
             RunStatus::Failed
         } else {
             RunStatus::Succeeded
         };
 
+        // Commit the manifest to the memory each step actually saw before the run is final.
+        run_manifest_payload["injected_memory"] = serde_json::to_value(
+            RunMemoryManifest::from_steps(step_memory.into_values().collect()),
+        )?;
+        self.trace_store.update_run_manifest(
+            run_id,
+            &run_manifest_payload,
+            &hash_json(&run_manifest_payload)?,
+            None,
+            "unsigned",
+        )?;
         self.trace_store
             .update_run_finished(run_id, run_status.clone())?;
 
@@ -1175,20 +3370,202 @@ impl<'a> Orchestrator<'a> {
             "orchestrator",
             json!({
                 "status": run_status_to_text(&run_status),
-                "steps_total": total_steps,
+                "steps_total": state.steps.len(),
                 "steps_succeeded": succeeded,
                 "steps_failed_or_rejected": failed_or_rejected,
+                "steps_deferred": deferred_steps,
+                "steps_cancelled": cancelled_steps,
             }),
             &mut chain,
         )?;
+        // Durability barrier: a buffered trace store must have committed the whole run before
+        // it is reported as finished.
+        self.trace_store.flush()?;
+        let usage = self.trace_store.record_run_usage(run_id)?;
+        self.build_run_summary(
+            run_id,
+            run_status,
+            state.steps.len(),
+            succeeded,
+            failed_or_rejected,
+            usage,
+        )
+    }
+
+    /// Run one agent invocation outside any workflow, with the same context retrieval, trust and
+    /// permission gating, tracing, and write handling as a workflow step. The invocation is
+    /// traced as a run of the one-step workflow `adhoc.<agent_name>` whose step key is `adhoc`.
+    ///
+    /// # Errors
+    /// Returns an error when the agent definition is invalid or execution fails.
+    pub fn execute_single_step(
+        &self,
+        agent: &AgentDefinition,
+        task: Value,
+        constraints: StepConstraints,
+        config: RunConfig,
+    ) -> Result<RunExecutionSummary> {
+        let workflow = normalize_workflow_definition(NormalizedWorkflow {
+            workflow_name: format!("adhoc.{}", agent.agent_name),
+            workflow_version: "adhoc".to_string(),
+            normalization_version: 0,
+            agents: vec![agent.clone()],
+            steps: vec![WorkflowStepDefinition {
+                step_key: ADHOC_STEP_KEY.to_string(),
+                agent_name: agent.agent_name.clone(),
+                task,
+                depends_on: Vec::new(),
+                condition: None,
+                when: None,
+                foreach: None,
+                iterate: None,
+                workflow_call: None,
+                vote: None,
+                gate_points: Vec::new(),
+                constraints,
+                persona: None,
+                prompt_ref: None,
+                scoring: None,
+                memory_dependencies: Vec::new(),
+            }],
+            gates: Vec::new(),
+            defaults: WorkflowDefaults {
+                non_interactive: config.non_interactive,
+                require_trust_gating: None,
+                enforce_permission_prune: None,
+                taint: None,
+            },
+            personas: Vec::new(),
+        })?;
+        self.execute_workflow(&workflow, config)
+    }
+
+    /// Continue run `run_id` from its trace under the workflow snapshot it recorded, e.g. after
+    /// the process executing it crashed. The run keeps its id, `as_of`, and manifest, and its
+    /// event chain continues with a `RunResumed` event. Succeeded and rejected steps keep their
+    /// records; failed, timed-out, and interrupted (`running`) steps, deferred steps whose gates
+    /// were all resolved, and the steps skipped or still pending behind them execute again.
+    /// Only `running`, `failed`, and `partially_completed` runs can be resumed, and a `running`
+    /// run must no longer be executing anywhere else.
+    ///
+    /// # Errors
+    /// Returns an error when the run or its workflow snapshot is missing, the run cannot be
+    /// resumed or has no step to resume, or execution fails.
+    pub fn resume_run(&self, run_id: RunId, config: RunConfig) -> Result<RunExecutionSummary> {
+        let run = self
+            .trace_store
+            .get_run(run_id)?
+            .ok_or_else(|| anyhow!("run_id {run_id} not found"))?;
+        let workflow = load_workflow_snapshot(self.trace_store, &run.workflow_hash)?;
+        self.execute_workflow(
+            &workflow,
+            RunConfig {
+                resume_run: Some(run_id),
+                ..config
+            },
+        )
+    }
+
+    /// Summary of an earlier run submitted under the same correlation id. Usage is recorded
+    /// now if the run has none yet; a still-running run's record is replaced when it finishes.
+    fn existing_run_summary(&self, run: &RunRecord) -> Result<RunExecutionSummary> {
+        let steps = self.trace_store.get_step_records(run.run_id)?;
+        let planned_steps = self
+            .trace_store
+            .get_workflow_snapshot(&run.workflow_hash)?
+            .and_then(|snapshot| snapshot.normalized_json["steps"].as_array().map(Vec::len))
+            .unwrap_or(0);
+        let usage = match self.trace_store.get_run_usage(run.run_id)? {
+            Some(usage) => usage,
+            None => self.trace_store.record_run_usage(run.run_id)?,
+        };
+        let mut summary = self.build_run_summary(
+            run.run_id,
+            run.status.clone(),
+            planned_steps.max(steps.len()),
+            steps
+                .iter()
+                .filter(|step| step.status == StepStatus::Succeeded)
+                .count(),
+            steps
+                .iter()
+                .filter(|step| {
+                    matches!(
+                        step.status,
+                        StepStatus::Failed | StepStatus::Rejected | StepStatus::TimedOut
+                    )
+                })
+                .count(),
+            usage,
+        )?;
+        summary.reused_existing_run = true;
+        Ok(summary)
+    }
 
-        Ok(RunExecutionSummary {
+    fn build_run_summary(
+        &self,
+        run_id: RunId,
+        status: RunStatus,
+        steps_total: usize,
+        steps_succeeded: usize,
+        steps_failed_or_rejected: usize,
+        usage: RunUsage,
+    ) -> Result<RunExecutionSummary> {
+        let mut summary = RunExecutionSummary {
             run_id,
-            status: run_status,
-            steps_total: total_steps,
-            steps_succeeded: succeeded,
-            steps_failed_or_rejected: failed_or_rejected,
-        })
+            status,
+            steps_total,
+            steps_succeeded,
+            steps_failed_or_rejected,
+            steps_deferred: self
+                .trace_store
+                .get_step_records(run_id)?
+                .iter()
+                .filter(|step| step.status == StepStatus::Deferred)
+                .count(),
+            trust_excluded: 0,
+            human_approvals: 0,
+            human_rejections: 0,
+            human_deferrals: self.trace_store.list_deferred_gates(Some(run_id))?.len(),
+            policy_prunes: 0,
+            proposed_writes: 0,
+            proposed_write_dispositions: BTreeMap::new(),
+            provider_calls: usize::try_from(usage.provider_calls).unwrap_or(usize::MAX),
+            warnings: BTreeMap::new(),
+            usage,
+            reused_existing_run: false,
+        };
+        for record in self.trace_store.get_step_gate_decisions(run_id)? {
+            match (&record.decision.gate_kind, &record.decision.decision) {
+                (GateKind::Trust, GateDecision::Rejected) => summary.trust_excluded += 1,
+                (GateKind::Human, GateDecision::Approved) => summary.human_approvals += 1,
+                (GateKind::Human, GateDecision::Rejected) => summary.human_rejections += 1,
+                (GateKind::Policy, GateDecision::Pruned) => summary.policy_prunes += 1,
+                _ => {}
+            }
+        }
+        for write in self.trace_store.get_step_proposed_writes(run_id)? {
+            summary.proposed_writes += 1;
+            *summary
+                .proposed_write_dispositions
+                .entry(write.disposition)
+                .or_default() += 1;
+        }
+        let warnings = self.trace_store.query_events(&EventQuery {
+            run_id: Some(run_id),
+            event_types: vec![TraceEventType::Warning],
+            ..EventQuery::default()
+        })?;
+        for row in warnings {
+            let code = row
+                .event
+                .payload_json
+                .get("warning_code")
+                .and_then(Value::as_str)
+                .unwrap_or(UNSPECIFIED_WARNING_CODE);
+            *summary.warnings.entry(code.to_string()).or_default() += 1;
+        }
+        Ok(summary)
     }
 
     /// Reconstruct and verify the event hash chain for a recorded run.
@@ -1216,705 +3593,8371 @@ impl<'a> Orchestrator<'a> {
         })
     }
 
-    fn persist_provider_call(
-        &self,
-        run_id: RunId,
-        step_id: StepId,
-        invocation: &ProviderInvocation,
-        chain: &mut EventChain,
-    ) -> Result<()> {
-        self.trace_store
-            .append_provider_call(run_id, step_id, &invocation.provider_call)?;
-        self.emit_event(
+    /// Re-execute a recorded run as a new run (`replay_of_run_id` set) from its workflow
+    /// snapshot, context packages, trust and human gate decisions, with every provider call
+    /// answered from the run's `provider_calls` rows instead of a provider. Each replayed call's
+    /// run-independent request hash (`compute_provider_request_replay_hash`) is checked against
+    /// the call it replays, and every step against the source run's status, output, and gate
+    /// decisions; each mismatch is reported as a divergence. `workflow_call` steps replay the
+    /// nested run recorded for them from that run's own trace, and the nested runs are compared
+    /// the same way, their step keys prefixed with `<workflow_call step key>/`.
+    ///
+    /// # Errors
+    /// Returns an error when the run or its workflow snapshot is missing, or the replay cannot
+    /// be executed.
+    pub fn replay_execute(&self, run_id: RunId) -> Result<ReplayExecutionReport> {
+        let source_run = self
+            .trace_store
+            .get_run(run_id)?
+            .ok_or_else(|| anyhow!("run_id {run_id} not found"))?;
+        let workflow = load_workflow_snapshot(self.trace_store, &source_run.workflow_hash)?;
+        let sources = ReplaySources::from_trace(self.trace_store, run_id)?;
+        let summary = self.replaying(&sources).execute_workflow(
+            &workflow,
+            RunConfig {
+                as_of: Some(source_run.as_of),
+                replay_of_run_id: Some(run_id),
+                labels: source_run.labels.clone(),
+                cli_args_json: json!({
+                    "source_run_id": run_id.to_string(),
+                    "replay_execute": true,
+                }),
+                enforce_permission_prune: self
+                    .trace_store
+                    .get_run_manifest(run_id)?
+                    .is_some_and(|manifest| manifest["permission_prune"] == "fail"),
+                ..RunConfig::default()
+            },
+        )?;
+
+        let mut divergences = Vec::new();
+        let provider_calls_replayed = replay_divergences(
+            self.trace_store,
             run_id,
-            Some(step_id),
-            TraceEventType::ProviderCalled,
-            "provider",
-            &invocation.provider_call.provider_name,
-            json!({
-                "provider_call_id": invocation.provider_call.provider_call_id,
-                "provider_name": invocation.provider_call.provider_name,
-                "model_id": invocation.provider_call.model_id,
-                "request_hash": invocation.provider_call.request_hash,
-                "response_hash": invocation.provider_call.response_hash,
-                "latency_ms": invocation.provider_call.latency_ms,
-            }),
-            chain,
+            summary.run_id,
+            "",
+            &mut divergences,
         )?;
-        Ok(())
+
+        Ok(ReplayExecutionReport {
+            source_run_id: run_id,
+            replay_run_id: summary.run_id,
+            workflow_hash: workflow.normalized_hash,
+            status: summary.status,
+            provider_calls_replayed,
+            reproduced: divergences.is_empty(),
+            divergences,
+        })
     }
 
-    #[allow(clippy::too_many_arguments)]
-    fn emit_event(
+    /// First load balancing turn of each agent with `load_balancing` in a new run. A replay or
+    /// rerun keeps the turns its source run recorded so every step is served by the same
+    /// endpoint; any other run claims one turn per step of the agent from the trace store's
+    /// per-agent counter, so consecutive runs continue the rotation.
+    fn claim_load_balancing_turns(
         &self,
-        run_id: RunId,
-        step_id: Option<StepId>,
-        event_type: TraceEventType,
-        actor_type: &str,
-        actor_id: &str,
-        payload_json: Value,
-        chain: &mut EventChain,
-    ) -> Result<EventRow> {
-        let occurred_at = now_utc();
-        let recorded_at = now_utc();
-        let payload_hash = hash_json(&payload_json)?;
-        let event_id = Ulid::new();
+        workflow: &NormalizedWorkflow,
+        config: &RunConfig,
+    ) -> Result<BTreeMap<String, u64>> {
+        let source = config.replay_of_run_id.or(config
+            .rerun_of
+            .as_ref()
+            .map(|rerun_of| rerun_of.source_run_id));
+        if let Some(source) = source {
+            return Ok(self
+                .trace_store
+                .get_run_manifest(source)?
+                .map(|manifest| recorded_load_balancing_turns(&manifest))
+                .unwrap_or_default());
+        }
+        let mut turns = BTreeMap::new();
+        for agent in &workflow.agents {
+            if agent.load_balancing.is_none() {
+                continue;
+            }
+            let steps = workflow
+                .steps
+                .iter()
+                .filter(|step| calls_provider_as(step, agent))
+                .count();
+            let first_turn = self.trace_store.claim_load_balancing_turns(
+                &agent.agent_name,
+                u64::try_from(steps.max(1)).unwrap_or(u64::MAX),
+            )?;
+            turns.insert(agent.agent_name.clone(), first_turn);
+        }
+        Ok(turns)
+    }
 
-        let material = json!({
-            "event_id": event_id,
-            "run_id": run_id,
-            "step_id": step_id,
-            "event_type": event_type,
-            "occurred_at": format_rfc3339(occurred_at)?,
-            "recorded_at": format_rfc3339(recorded_at)?,
-            "actor_type": actor_type,
-            "actor_id": actor_id,
-            "payload_hash": payload_hash,
-            "prev_event_hash": chain.prev_event_hash,
-        });
-        let event_hash = hash_json(&material)?;
+    /// An orchestrator on the same trace store and providers that answers every step from
+    /// `sources` and applies no proposed writes.
+    fn replaying<'b>(&'b self, sources: &'b ReplaySources) -> Orchestrator<'b> {
+        Orchestrator {
+            trace_store: self.trace_store,
+            context_source: &sources.context,
+            trust_source: &sources.trust,
+            human_gate: &sources.human_gate,
+            write_applier: &NoopProposedWriteApplier,
+            response_scorer: self.response_scorer,
+            outcome_sink: None,
+            gate_notifier: None,
+            recorded_responses: Some(&sources.responses),
+            reason_codes: self.reason_codes,
+            providers: self.providers.clone(),
+        }
+    }
 
-        let event = TraceEvent {
-            event_id,
+    /// Build the step request for `as_of`: fetch context packages, prune them by permission, and
+    /// apply trust gating.
+    #[allow(clippy::too_many_arguments)]
+    fn prepare_step_input(
+        &self,
+        run_id: RunId,
+        step_id: StepId,
+        step: &multi_agent_center_domain::WorkflowStepDefinition,
+        agent: &AgentDefinition,
+        persona: Option<&AppliedPersona>,
+        effective_permissions: &EffectivePermissions,
+        as_of: time::OffsetDateTime,
+    ) -> Result<PreparedStepInput> {
+        let GatedStepContext {
+            packages: _,
+            pruned_references,
+            refs,
+            trust_attachments,
+            gated_packages,
+        } = gate_step_context(
+            self.context_source,
+            self.trust_source,
             run_id,
             step_id,
-            event_type,
-            occurred_at,
-            recorded_at,
-            actor_type: actor_type.to_string(),
-            actor_id: actor_id.to_string(),
-            payload_json,
-            payload_hash,
-            prev_event_hash: chain.prev_event_hash.clone(),
-            event_hash: event_hash.clone(),
+            step,
+            effective_permissions,
+            as_of,
+        )?;
+        if let Some(registry) = self.reason_codes {
+            registry
+                .validate(
+                    trust_attachments
+                        .iter()
+                        .flat_map(|item| item.reason_codes.iter().map(String::as_str))
+                        .chain(pruned_references.iter().map(|item| item.reason.as_str())),
+                )
+                .map_err(|err| anyhow!("step {}: {err}", step.step_key))?;
+        }
+        let trust_included = trust_attachments.iter().filter(|item| item.include).count();
+        let trust_excluded = trust_attachments.len().saturating_sub(trust_included);
+
+        let prompt = step
+            .prompt_ref
+            .as_deref()
+            .map(|prompt_ref| self.resolve_prompt(prompt_ref))
+            .transpose()?;
+        let trust_gate_summary = step
+            .constraints
+            .explain_trust_gating
+            .unwrap_or(false)
+            .then(|| trust_gate_summary(&trust_attachments));
+
+        let mut request = StepRequest {
+            run_id,
+            step_id,
+            step_key: step.step_key.clone(),
+            as_of,
+            agent: agent.clone(),
+            task_payload: step.task.clone(),
+            injected_context_packages: gated_packages,
+            trust_gate_attachments: trust_attachments,
+            effective_permissions: effective_permissions.clone(),
+            constraints: step.constraints.clone(),
+            trust_gate_summary,
+            persona: persona.cloned(),
+            prompt,
+            input_hash: String::new(),
         };
-
-        let event_seq = self.trace_store.append_event(&event)?;
-        chain.prev_event_hash = Some(event_hash);
-
-        Ok(EventRow { event_seq, event })
+        request.input_hash = compute_step_request_hash(&request)?;
+
+        Ok(PreparedStepInput {
+            request,
+            refs,
+            pruned_references,
+            trust_included,
+            trust_excluded,
+        })
     }
-}
 
-fn route_provider_call(request: &StepRequest) -> Result<ProviderInvocation> {
-    match request.agent.provider.provider_name.as_str() {
-        "mock" => MockProvider::new().invoke(request),
-        "http_json" => HttpJsonProvider::new().invoke(request),
-        other => Err(anyhow!(
-            "unsupported provider adapter '{other}'; supported providers are 'mock' and 'http_json'"
-        )),
-    }
-}
+    /// Record the context prepared for `attempt` (from 1) of a step: a trust gate decision per
+    /// attachment, the context packages, the `step_input_prepared` and trust `gate_evaluated`
+    /// events, and the permission prune when items were pruned. Context packages are
+    /// append-only: a resumed step keeps the packages an earlier execution recorded for the
+    /// attempt and flags slots whose package has changed since. Records of a retry carry its
+    /// `attempt`. Returns whether the prune fails the step.
+    #[allow(clippy::too_many_lines)]
+    fn record_step_context(
+        &self,
+        step: &WorkflowStepDefinition,
+        prepared: &PreparedStepInput,
+        attempt: u32,
+        recorded_context: Option<&RecordedStepContext>,
+        enforce_permission_prune: bool,
+        chain: &mut EventChain,
+    ) -> Result<bool> {
+        let request = &prepared.request;
+        let (run_id, step_id) = (request.run_id, request.step_id);
+        let retry_attempt = (attempt > 1).then_some(attempt);
+        let with_attempt = |mut value: Value| {
+            if let Some(attempt) = retry_attempt {
+                value["attempt"] = json!(attempt);
+            }
+            value
+        };
 
-fn build_step_result_from_provider(
-    run_id: RunId,
-    step_id: StepId,
-    invocation: ProviderInvocation,
-) -> StepResult {
-    StepResult {
-        run_id,
-        step_id,
-        status: StepStatus::Succeeded,
-        outputs: invocation.output,
-        proposed_memory_writes: invocation.proposed_memory_writes,
-        provider_calls: vec![invocation.provider_call],
-        gate_decisions: Vec::new(),
-        output_hash: String::new(),
-        error: None,
-    }
-}
+        for attachment in &request.trust_gate_attachments {
+            let trust_decision = if attachment.include {
+                GateDecision::Approved
+            } else {
+                GateDecision::Rejected
+            };
+            self.trace_store.append_gate_decision(
+                run_id,
+                step_id,
+                &GateDecisionRecord {
+                    gate_kind: GateKind::Trust,
+                    gate_name: "trust_gate".to_string(),
+                    subject_type: "memory_ref".to_string(),
+                    memory_id: Some(attachment.memory_id),
+                    version: Some(attachment.version),
+                    memory_version_id: Some(attachment.memory_version_id),
+                    decision: trust_decision,
+                    reason_codes: attachment.reason_codes.clone(),
+                    notes: Some(format!(
+                        "status={} confidence_effective={:.6} capped={}",
+                        attachment.trust_status, attachment.confidence_effective, attachment.capped
+                    )),
+                    decided_by: attachment.source.clone(),
+                    decided_at: attachment.evaluated_at,
+                    source_ruleset_version: attachment.ruleset_version,
+                    evidence_json: Some(with_attempt(json!({
+                        "trust_status": attachment.trust_status,
+                        "confidence_effective": attachment.confidence_effective,
+                        "capped": attachment.capped,
+                        "source": attachment.source,
+                    }))),
+                },
+            )?;
+        }
 
-fn apply_trust_filter(
-    packages: &[ContextPackageEnvelope],
-    trust_map: &BTreeMap<(String, u32), bool>,
-) -> Result<Vec<ContextPackageEnvelope>> {
-    let mut out = Vec::with_capacity(packages.len());
+        for package in &request.injected_context_packages {
+            match recorded_context.and_then(|slots| slots.get(&(attempt, package.package_slot))) {
+                None => self
+                    .trace_store
+                    .append_context_package(run_id, step_id, attempt, package)?,
+                Some(recorded_hash) if *recorded_hash == package.package_hash => {}
+                Some(recorded_hash) => {
+                    self.emit_event(
+                        run_id,
+                        Some(step_id),
+                        TraceEventType::Warning,
+                        "system",
+                        "orchestrator",
+                        with_attempt(json!({
+                            "warning_code": "resumed_context_changed",
+                            "step_key": step.step_key,
+                            "package_slot": package.package_slot,
+                            "recorded_package_hash": recorded_hash,
+                            "package_hash": package.package_hash,
+                        })),
+                        chain,
+                    )?;
+                }
+            }
+        }
 
-    for package in packages {
-        let mut package_copy = package.clone();
-        package_copy.context_package.selected_items.retain(|item| {
-            trust_map
-                .get(&(item.memory_id.to_string(), item.version))
-                .copied()
-                .unwrap_or(true)
-        });
-        package_copy.package_hash =
-            hash_json(&serde_json::to_value(&package_copy.context_package)?)?;
-        out.push(package_copy);
-    }
+        self.emit_event(
+            run_id,
+            Some(step_id),
+            TraceEventType::StepInputPrepared,
+            "system",
+            "orchestrator",
+            with_attempt(json!({
+                "step_key": step.step_key,
+                "context_packages": request.injected_context_packages.len(),
+                "context_refs": prepared.refs.len(),
+                "trust_attachments": request.trust_gate_attachments.len(),
+                "prompt_ref": step.prompt_ref,
+            })),
+            chain,
+        )?;
 
-    Ok(out)
-}
+        if prepared.trust_included + prepared.trust_excluded > 0 {
+            self.emit_event(
+                run_id,
+                Some(step_id),
+                TraceEventType::GateEvaluated,
+                "system",
+                "trust_gate",
+                with_attempt(json!({
+                    "gate_kind": "trust",
+                    "gate_name": "trust_gate",
+                    "step_key": step.step_key,
+                    "included": prepared.trust_included,
+                    "excluded": prepared.trust_excluded,
+                })),
+                chain,
+            )?;
+        }
 
-#[derive(Debug, Default)]
-struct EventChain {
-    prev_event_hash: Option<String>,
-}
+        let pruned_references = &prepared.pruned_references;
+        let fail_on_permission_prune = request.effective_permissions.fail_on_permission_prune;
+        let fail_on_prune =
+            !pruned_references.is_empty() && fail_on_permission_prune && enforce_permission_prune;
+        if pruned_references.is_empty() {
+            return Ok(false);
+        }
+        self.emit_event(
+            run_id,
+            Some(step_id),
+            TraceEventType::StepPermissionPruned,
+            "system",
+            "policy",
+            with_attempt(json!({
+                "count": pruned_references.len(),
+                "items": pruned_references,
+            })),
+            chain,
+        )?;
 
-fn format_rfc3339(value: time::OffsetDateTime) -> Result<String> {
-    value
-        .format(&time::format_description::well_known::Rfc3339)
-        .map_err(|err| anyhow!("invalid RFC3339 value: {err}"))
-}
+        self.emit_event(
+            run_id,
+            Some(step_id),
+            TraceEventType::Warning,
+            "system",
+            "policy",
+            with_attempt(json!({
+                "warning_code": "context_pruned",
+                "count": pruned_references.len(),
+                "continue_execution": !fail_on_prune,
+            })),
+            chain,
+        )?;
 
-fn step_status_to_text(status: &StepStatus) -> &'static str {
-    match status {
-        StepStatus::Pending => "pending",
-        StepStatus::Running => "running",
-        StepStatus::Succeeded => "succeeded",
-        StepStatus::Failed => "failed",
-        StepStatus::Rejected => "rejected",
-        StepStatus::Skipped => "skipped",
-    }
-}
+        if fail_on_permission_prune && !enforce_permission_prune {
+            self.emit_event(
+                run_id,
+                Some(step_id),
+                TraceEventType::Warning,
+                "system",
+                "policy",
+                json!({
+                    "warning_code": "fail_on_permission_prune_ignored",
+                    "reason": "locked_decision_continue_on_prune",
+                }),
+                chain,
+            )?;
+        }
 
-fn run_status_to_text(status: &RunStatus) -> &'static str {
-    match status {
-        RunStatus::Pending => "pending",
-        RunStatus::Running => "running",
-        RunStatus::Succeeded => "succeeded",
-        RunStatus::Failed => "failed",
-        RunStatus::Rejected => "rejected",
+        self.trace_store.append_gate_decision(
+            run_id,
+            step_id,
+            &GateDecisionRecord {
+                gate_kind: GateKind::Policy,
+                gate_name: "context_permission".to_string(),
+                subject_type: "context_items".to_string(),
+                memory_id: None,
+                version: None,
+                memory_version_id: None,
+                decision: if fail_on_prune {
+                    GateDecision::Rejected
+                } else {
+                    GateDecision::Pruned
+                },
+                reason_codes: if fail_on_prune {
+                    vec![
+                        "context_items_pruned".to_string(),
+                        "fail_on_permission_prune".to_string(),
+                    ]
+                } else {
+                    vec!["context_items_pruned".to_string()]
+                },
+                notes: Some(format!("{} item(s) pruned", pruned_references.len())),
+                decided_by: "policy.engine".to_string(),
+                decided_at: now_utc(),
+                source_ruleset_version: None,
+                evidence_json: retry_attempt.map(|attempt| json!({ "attempt": attempt })),
+            },
+        )?;
+        Ok(fail_on_prune)
     }
-}
-
-enum StepContextQuery {
-    Policy(QueryRequest),
-    Recall {
-        text: String,
-        record_types: Vec<RecordType>,
-    },
-}
 
-fn resolve_step_context_queries(
-    step: &multi_agent_center_domain::WorkflowStepDefinition,
-    as_of: time::OffsetDateTime,
-) -> Result<Vec<StepContextQuery>> {
-    let mut queries: Vec<StepContextQuery> = Vec::new();
+    /// Record and gate the context a retry rebuilt the way the step's first context was, for
+    /// `attempt`. Returns the step's result when the rebuilt context stops the step: a prune
+    /// it may not continue past, an unmet `fail` memory dependency, or sensitive context the
+    /// elevated gate rejects for an external provider.
+    fn gate_retry_context(
+        &self,
+        step: &WorkflowStepDefinition,
+        agent: &AgentDefinition,
+        prepared: &PreparedStepInput,
+        attempt: u32,
+        gating: &mut RetryGating<'_>,
+        chain: &mut EventChain,
+    ) -> Result<Option<StepResult>> {
+        let (run_id, step_id) = (prepared.request.run_id, prepared.request.step_id);
+        if self.record_step_context(
+            step,
+            prepared,
+            attempt,
+            gating.recorded_context,
+            gating.enforce_permission_prune,
+            chain,
+        )? {
+            return Ok(Some(permission_prune_result(
+                run_id,
+                step_id,
+                &prepared.pruned_references,
+            )));
+        }
+        if let Some(missing) = self.check_memory_dependencies(
+            run_id,
+            step_id,
+            step,
+            &prepared.request,
+            &prepared.pruned_references,
+            chain,
+        )? {
+            return Ok(Some(missing_dependency_result(run_id, step_id, &missing)));
+        }
 
-    if let Some(raw) = step.task.get("context_queries") {
-        let array = raw
-            .as_array()
-            .ok_or_else(|| anyhow!("task.context_queries must be an array"))?;
-        for entry in array {
-            let object = entry
-                .as_object()
-                .ok_or_else(|| anyhow!("task.context_queries entries must be objects"))?;
-            let text = object
-                .get("text")
-                .and_then(Value::as_str)
-                .unwrap_or(step.step_key.as_str())
-                .to_string();
-            let actor = object
-                .get("actor")
-                .and_then(Value::as_str)
-                .unwrap_or("*")
-                .to_string();
-            let action = object
-                .get("action")
-                .and_then(Value::as_str)
-                .unwrap_or("*")
-                .to_string();
-            let resource = object
-                .get("resource")
-                .and_then(Value::as_str)
-                .unwrap_or("*")
-                .to_string();
-            let mode = object
-                .get("mode")
-                .and_then(Value::as_str)
-                .map(str::trim)
-                .map_or_else(|| "policy".to_string(), str::to_ascii_lowercase);
+        let workflow = gating.workflow;
+        let Some(policy) = workflow.normalized_workflow.defaults.taint.as_ref() else {
+            return Ok(None);
+        };
+        let inherited_from = std::mem::take(&mut gating.taint.inherited_from);
+        *gating.taint = StepTaint {
+            inherited_from,
+            ..injected_taint(policy, &prepared.request)
+        };
+        if gating.taint.is_tainted()
+            && gating.elevated_approved.is_none()
+            && sends_to_external_provider(agent)
+        {
+            let approved = self.decide_elevated_gate(
+                run_id,
+                step_id,
+                &step.step_key,
+                workflow,
+                policy,
+                "external_provider",
+                gating.non_interactive,
+                chain,
+            )?;
+            *gating.elevated_approved = Some(approved);
+            if !approved {
+                return Ok(Some(rejected_step_result(run_id, step_id)));
+            }
+        }
+        Ok(None)
+    }
 
-            match mode.as_str() {
-                "policy" => queries.push(StepContextQuery::Policy(QueryRequest {
-                    text,
-                    actor,
-                    action,
-                    resource,
-                    as_of,
-                })),
-                "recall" => {
-                    let record_types = parse_recall_record_types(object.get("record_types"))?;
-                    queries.push(StepContextQuery::Recall { text, record_types });
+    /// Score a step output with the configured [`ResponseScorer`] and, when the spec asks for it,
+    /// log the verdict as outcome events for the injected memories. Returns `None` without a
+    /// scorer.
+    fn score_step_output(
+        &self,
+        step_key: &str,
+        engine_version: &str,
+        spec: &ResponseScoringSpec,
+        request: &StepRequest,
+        output: &StepOutputEnvelope,
+        chain: &mut EventChain,
+    ) -> Result<Option<ScoreVerdict>> {
+        let Some(scorer) = self.response_scorer else {
+            return Ok(None);
+        };
+        let verdict = scorer.score(spec, request, output)?;
+
+        let mut outcome_events = 0;
+        if spec.log_outcomes {
+            match self.outcome_sink {
+                Some(sink) => {
+                    outcome_events = sink.log_scored_outcome(
+                        &ScoredStepOrigin {
+                            run_id: request.run_id,
+                            step_id: request.step_id,
+                            step_key,
+                            engine_version,
+                        },
+                        &verdict,
+                        &scored_memories(request),
+                    )?;
                 }
-                _ => {
-                    return Err(anyhow!(
-                        "task.context_queries mode must be one of: policy, recall"
-                    ));
+                None => {
+                    self.emit_event(
+                        request.run_id,
+                        Some(request.step_id),
+                        TraceEventType::Warning,
+                        "system",
+                        "orchestrator",
+                        json!({
+                            "warning_code": "scoring_outcomes_not_logged",
+                            "step_key": step_key,
+                            "reason": "no outcome event sink configured",
+                        }),
+                        chain,
+                    )?;
                 }
             }
         }
-    } else {
-        let text = step
-            .task
-            .get("text")
-            .and_then(Value::as_str)
-            .unwrap_or(step.step_key.as_str())
-            .to_string();
-        let actor = step
-            .task
-            .get("actor")
-            .and_then(Value::as_str)
-            .unwrap_or("*")
-            .to_string();
-        let action = step
-            .task
-            .get("action")
-            .and_then(Value::as_str)
-            .unwrap_or("*")
-            .to_string();
-        let resource = step
-            .task
-            .get("resource")
-            .and_then(Value::as_str)
-            .unwrap_or("*")
-            .to_string();
-        queries.push(StepContextQuery::Policy(QueryRequest {
-            text,
-            actor,
-            action,
-            resource,
-            as_of,
-        }));
-    }
-
-    Ok(queries)
-}
 
-fn build_context_packages_from_records(
-    records: &[MemoryRecord],
-    run_id: RunId,
-    step: &multi_agent_center_domain::WorkflowStepDefinition,
-    as_of: time::OffsetDateTime,
-    source: &str,
-) -> Result<Vec<ContextPackageEnvelope>> {
-    if records.is_empty() {
-        return Ok(Vec::new());
+        self.emit_event(
+            request.run_id,
+            Some(request.step_id),
+            TraceEventType::StepScored,
+            "system",
+            "scorer",
+            json!({
+                "step_key": step_key,
+                "verdict": verdict,
+                "outcome_events": outcome_events,
+            }),
+            chain,
+        )?;
+        Ok(Some(verdict))
     }
-    let queries = resolve_step_context_queries(step, as_of)?;
 
-    let mut envelopes = Vec::with_capacity(queries.len());
-    for (package_slot, query) in queries.into_iter().enumerate() {
-        let package = match query {
-            StepContextQuery::Policy(request) => build_context_package(
-                records,
-                request,
-                &format!("{}:{}:{}", run_id, step.step_key, package_slot),
-            ),
-            StepContextQuery::Recall { text, record_types } => build_recall_context_package(
-                records,
-                QueryRequest {
-                    text,
-                    actor: "*".to_string(),
-                    action: "*".to_string(),
-                    resource: "*".to_string(),
-                    as_of,
-                },
-                &format!("{}:{}:{}", run_id, step.step_key, package_slot),
-                &record_types,
-            ),
-        }
-        .map_err(|err| anyhow!("memory kernel context package build failed: {err}"))?;
-        let package_json = serde_json::to_value(&package)?;
-        let package_hash = hash_json(&package_json)?;
-        envelopes.push(ContextPackageEnvelope {
-            package_slot,
-            source: source.to_string(),
-            context_package: package,
-            package_hash,
-        });
+    /// Resolve a step's `prompt_ref` against the trace store's prompt template library.
+    fn resolve_prompt(&self, prompt_ref: &str) -> Result<ResolvedPrompt> {
+        let (name, version) = parse_prompt_ref(prompt_ref)?;
+        let template = self
+            .trace_store
+            .get_prompt_template(name, version)?
+            .ok_or_else(|| anyhow!("unknown prompt template {prompt_ref}"))?;
+        Ok(ResolvedPrompt {
+            prompt_ref: prompt_ref.to_string(),
+            template_hash: template.template_hash,
+            body: template.body,
+        })
     }
 
-    Ok(envelopes)
-}
+    /// Invoke the step's provider, retrying invocations that fail with a retryable error (see
+    /// [`classify_provider_failure`]) until `max_attempts` is reached; fatal failures and
+    /// interruptions end the step at once. Each failed attempt that is retried is recorded as a
+    /// provider call. With [`RetryContextMode::Refresh`] each retry rebuilds `request` at the
+    /// retry time and its context is recorded and gated as the first attempt's was (see
+    /// [`Self::gate_retry_context`]); otherwise the first attempt's context is reused verbatim.
+    /// Returns the final invocation outcome with the records stored in the step's `retry_json`
+    /// (when a retry happened) and `provider_selection_json` (when the agent declares
+    /// fallbacks).
+    #[allow(clippy::too_many_arguments)]
+    fn invoke_provider_with_retries(
+        &self,
+        step: &multi_agent_center_domain::WorkflowStepDefinition,
+        agent: &AgentDefinition,
+        effective_permissions: &EffectivePermissions,
+        request: &mut StepRequest,
+        budget: &StepBudget<'_>,
+        mut gating: RetryGating<'_>,
+        chain: &mut EventChain,
+    ) -> Result<ProviderStepOutcome> {
+        let max_attempts = step.constraints.max_attempts.unwrap_or(1).max(1);
+        let mode = step.constraints.retry_context.unwrap_or_default();
+        let mut retries = Vec::new();
+        let mut attempt = 1_u32;
+        let mut provider_selection_json;
+        let mut halted = None;
+
+        let outcome = loop {
+            let (outcome, selection) = self.invoke_provider_chain(request, budget, chain)?;
+            provider_selection_json = selection;
+            if attempt >= max_attempts
+                || classify_provider_failure(&outcome) != Some(ProviderFailureClass::Retryable)
+            {
+                break outcome;
+            }
+            let failed_call = match &outcome {
+                Ok(invocation) => invocation.provider_call.clone(),
+                Err(err) => failed_provider_call(request, err)?,
+            };
+            // A fallback chain has already recorded each of its failed attempts.
+            if provider_selection_json.is_none() {
+                self.persist_provider_call(request.run_id, request.step_id, &failed_call, chain)?;
+            }
 
-fn parse_recall_record_types(raw: Option<&Value>) -> Result<Vec<RecordType>> {
-    let Some(value) = raw else {
-        return Ok(default_recall_record_types());
-    };
+            attempt += 1;
+            let refreshed = if mode == RetryContextMode::Refresh {
+                let persona = request.persona.clone();
+                let prepared = self.prepare_step_input(
+                    request.run_id,
+                    request.step_id,
+                    step,
+                    agent,
+                    persona.as_ref(),
+                    effective_permissions,
+                    now_utc(),
+                )?;
+                *request = prepared.request.clone();
+                Some(prepared)
+            } else {
+                None
+            };
+            let retry = json!({
+                "attempt": attempt,
+                "previous_error": failed_call.error_text,
+                "previous_provider_call_id": failed_call.provider_call_id,
+                "context": mode.as_str(),
+                "as_of": format_rfc3339(request.as_of)?,
+                "input_hash": request.input_hash,
+                "context_package_hashes": request
+                    .injected_context_packages
+                    .iter()
+                    .map(|package| package.package_hash.clone())
+                    .collect::<Vec<_>>(),
+            });
+            self.emit_event(
+                request.run_id,
+                Some(request.step_id),
+                TraceEventType::Warning,
+                "system",
+                "orchestrator",
+                json!({
+                    "warning_code": "step_retry",
+                    "step_key": step.step_key,
+                    "retry": retry,
+                }),
+                chain,
+            )?;
+            retries.push(retry);
+
+            if let Some(prepared) = refreshed.as_ref() {
+                halted =
+                    self.gate_retry_context(step, agent, prepared, attempt, &mut gating, chain)?;
+                if halted.is_some() {
+                    break outcome;
+                }
+            }
+        };
 
-    let array = value
-        .as_array()
-        .ok_or_else(|| anyhow!("task.context_queries[].record_types must be an array"))?;
-    if array.is_empty() {
-        return Ok(default_recall_record_types());
+        let retry_json = (!retries.is_empty()).then(|| {
+            json!({
+                "max_attempts": max_attempts,
+                "retry_context": mode.as_str(),
+                "attempts_made": if halted.is_some() { attempt - 1 } else { attempt },
+                "retries": retries,
+            })
+        });
+        Ok(ProviderStepOutcome {
+            invocation: outcome,
+            retry_json,
+            provider_selection_json,
+            halted,
+        })
     }
 
-    let mut record_types = Vec::with_capacity(array.len());
-    for item in array {
-        let raw_record_type = item
-            .as_str()
-            .ok_or_else(|| anyhow!("task.context_queries[].record_types entries must be strings"))?
-            .trim()
-            .to_ascii_lowercase();
-        let record_type = RecordType::parse(&raw_record_type).ok_or_else(|| {
+    /// Call the request's provider, or replay the next recorded response when a recorded
+    /// response source is configured. Fails with a `StepInterruption` once `budget` runs out.
+    fn call_provider(
+        &self,
+        request: &StepRequest,
+        budget: &StepBudget<'_>,
+    ) -> Result<ProviderInvocation> {
+        if let Some(interruption) = budget.interruption() {
+            return Err(interruption.into());
+        }
+        let Some(source) = self.recorded_responses else {
+            return call_live_provider(request, &self.providers, budget);
+        };
+        let recorded = source.next_response(&request.step_key).ok_or_else(|| {
             anyhow!(
-                "unsupported recall record type `{raw_record_type}`; expected one of constraint|decision|preference|event|outcome"
+                "no recorded provider response left for step '{}'",
+                request.step_key
             )
         })?;
-        record_types.push(record_type);
+        replay_recorded_response(request, &recorded)
     }
 
-    Ok(record_types)
-}
-
-fn load_outcome_rulesets(conn: &rusqlite::Connection) -> Result<BTreeMap<u32, OutcomeRuleset>> {
-    let mut stmt = conn.prepare(
-        "SELECT ruleset_version, ruleset_json
-         FROM outcome_rulesets
-         ORDER BY ruleset_version ASC",
-    )?;
-    let mut rows = stmt.query([])?;
+    /// Invoke the agent's primary provider binding, moving down its declared fallbacks while
+    /// attempts fail with a retryable error. Every attempt except a final success is recorded as
+    /// a provider call here (the caller persists a successful invocation). Returns the final
+    /// outcome and, when the agent declares fallbacks, the provider selection record.
+    fn invoke_provider_chain(
+        &self,
+        request: &StepRequest,
+        budget: &StepBudget<'_>,
+        chain: &mut EventChain,
+    ) -> Result<(Result<ProviderInvocation>, Option<Value>)> {
+        if request.agent.fallbacks.is_empty() {
+            return Ok((self.call_provider(request, budget), None));
+        }
 
-    let mut out = BTreeMap::new();
-    while let Some(row) = rows.next()? {
-        let version_i64: i64 = row.get(0)?;
-        let version = u32::try_from(version_i64)
-            .map_err(|_| anyhow!("invalid outcome ruleset_version: {version_i64}"))?;
-        let ruleset_json: String = row.get(1)?;
-        let value: Value =
-            serde_json::from_str(&ruleset_json).context("invalid outcome_rulesets.ruleset_json")?;
-        let ruleset = OutcomeRuleset::from_json(&value)
-            .map_err(|err| anyhow!("invalid outcome ruleset {version}: {err}"))?;
-        out.insert(version, ruleset);
-    }
+        let bindings: Vec<&ProviderBinding> = std::iter::once(&request.agent.provider)
+            .chain(request.agent.fallbacks.iter())
+            .collect();
+        let mut attempts = Vec::new();
+        for (position, binding) in bindings.iter().enumerate() {
+            let mut attempt_request = request.clone();
+            attempt_request.agent.provider = (*binding).clone();
+            let outcome = self.call_provider(&attempt_request, budget);
+            let failure = classify_provider_failure(&outcome);
+            let mut entry = json!({
+                "position": position,
+                "provider_name": binding.provider_name,
+                "model_id": binding.model_id,
+                "outcome": failure.map_or("succeeded", ProviderFailureClass::as_str),
+            });
 
-    if out.is_empty() {
-        out.insert(1, OutcomeRuleset::v1());
-    }
+            let final_attempt =
+                failure != Some(ProviderFailureClass::Retryable) || position + 1 == bindings.len();
+            let provider_call = match &outcome {
+                Ok(invocation) => invocation.provider_call.clone(),
+                Err(err) => failed_provider_call(&attempt_request, err)?,
+            };
+            entry["provider_call_id"] = json!(provider_call.provider_call_id);
+            if failure.is_some() {
+                entry["error"] = json!(provider_call.error_text);
+                self.persist_provider_call(request.run_id, request.step_id, &provider_call, chain)?;
+            }
+            attempts.push(entry);
+
+            if final_attempt {
+                let selection = json!({
+                    "selected": {
+                        "position": position,
+                        "provider_name": binding.provider_name,
+                        "model_id": binding.model_id,
+                    },
+                    "attempts": attempts,
+                });
+                return Ok((outcome, Some(selection)));
+            }
 
-    Ok(out)
-}
+            let next = bindings[position + 1];
+            self.emit_event(
+                request.run_id,
+                Some(request.step_id),
+                TraceEventType::Warning,
+                "system",
+                "orchestrator",
+                json!({
+                    "warning_code": "provider_fallback",
+                    "step_key": request.step_key,
+                    "failed": {
+                        "provider_name": binding.provider_name,
+                        "model_id": binding.model_id,
+                        "error": provider_call.error_text,
+                    },
+                    "next": {
+                        "provider_name": next.provider_name,
+                        "model_id": next.model_id,
+                    },
+                }),
+                chain,
+            )?;
+        }
 
-fn get_memory_trust_and_ruleset(
-    conn: &rusqlite::Connection,
-    memory_id: memory_kernel_core::MemoryId,
-    version: u32,
-) -> Result<Option<(MemoryTrust, u32)>> {
-    let mut stmt = conn.prepare(
-        "SELECT
-            confidence_raw, confidence_effective, baseline_confidence,
-            trust_status, contradiction_cap_active, cap_value, manual_override_active,
-            wins_last5, failures_last5, last_event_seq, last_ruleset_version,
-            last_scored_at, updated_at
-         FROM memory_trust
-         WHERE memory_id = ?1 AND version = ?2",
-    )?;
+        Err(anyhow!(
+            "provider chain for step {} is empty",
+            request.step_key
+        ))
+    }
 
-    stmt.query_row(
-        rusqlite::params![memory_id.to_string(), i64::from(version)],
-        |row| {
-            let trust_status_raw: String = row.get(3)?;
-            let trust_status = TrustStatus::parse(&trust_status_raw).ok_or_else(|| {
-                rusqlite::Error::FromSqlConversionFailure(
-                    3,
-                    rusqlite::types::Type::Text,
-                    Box::new(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("invalid trust_status: {trust_status_raw}"),
-                    )),
-                )
-            })?;
+    /// Run a conversation step: one provider call per entry in `task.turns`, each seeing the
+    /// transcript so far. A checkpoint holding the transcript is appended after every call, and
+    /// turns already covered by checkpoints for the step are not invoked again.
+    fn run_conversation_turns(
+        &self,
+        step: &multi_agent_center_domain::WorkflowStepDefinition,
+        request: &StepRequest,
+        turns: &[Value],
+        budget: &StepBudget<'_>,
+        chain: &mut EventChain,
+    ) -> Result<StepResult> {
+        let run_id = request.run_id;
+        let step_id = request.step_id;
+        let checkpoints = self.trace_store.get_step_checkpoints(step_id)?;
+        let mut transcript: Vec<Value> = checkpoints
+            .last()
+            .and_then(|checkpoint| checkpoint.state_json.get("transcript"))
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        let mut checkpoint_hashes: Vec<String> = checkpoints
+            .iter()
+            .map(|checkpoint| checkpoint.state_hash.clone())
+            .collect();
+        if let Some(last) = checkpoints.last() {
+            self.emit_event(
+                run_id,
+                Some(step_id),
+                TraceEventType::Warning,
+                "system",
+                "orchestrator",
+                json!({
+                    "warning_code": "step_resumed_from_checkpoint",
+                    "step_key": step.step_key,
+                    "checkpoint_seq": last.checkpoint_seq,
+                    "state_hash": last.state_hash,
+                }),
+                chain,
+            )?;
+        }
 
-            let last_ruleset_version_i64: i64 = row.get(10)?;
-            let last_ruleset_version = u32::try_from(last_ruleset_version_i64).map_err(|_| {
-                rusqlite::Error::FromSqlConversionFailure(
-                    10,
-                    rusqlite::types::Type::Integer,
-                    Box::new(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("invalid ruleset version: {last_ruleset_version_i64}"),
-                    )),
-                )
+        let mut provider_calls = Vec::new();
+        let mut proposed_memory_writes = Vec::new();
+        for (turn_index, turn) in turns.iter().enumerate().skip(transcript.len()) {
+            let mut turn_request = request.clone();
+            turn_request.task_payload = json!({
+                "task": step.task,
+                "turn_index": turn_index,
+                "turn": turn,
+                "transcript": transcript,
+            });
+            turn_request.input_hash = compute_step_request_hash(&turn_request)?;
+
+            let invocation = match self.call_provider(&turn_request, budget) {
+                Ok(invocation) => invocation,
+                Err(err) => {
+                    let mut result =
+                        self.provider_failure_result(run_id, step_id, &step.step_key, &err, chain)?;
+                    result.provider_calls = provider_calls;
+                    result.checkpoint_hashes = checkpoint_hashes;
+                    return Ok(result);
+                }
+            };
+            self.persist_provider_call(run_id, step_id, &invocation.provider_call, chain)?;
+
+            transcript.push(json!({
+                "turn_index": turn_index,
+                "message": invocation.output.message,
+                "payload": invocation.output.payload,
+            }));
+            let state_json = json!({
+                "turn_index": turn_index,
+                "transcript": transcript,
+            });
+            let prev_checkpoint_hash = checkpoint_hashes.last().cloned();
+            let state_hash = compute_checkpoint_hash(prev_checkpoint_hash.as_deref(), &state_json)?;
+            self.trace_store.append_step_checkpoint(&StepCheckpoint {
+                run_id,
+                step_id,
+                checkpoint_seq: u32::try_from(turn_index)
+                    .map_err(|_| anyhow!("too many conversation turns"))?,
+                provider_call_id: Some(invocation.provider_call.provider_call_id),
+                state_json,
+                prev_checkpoint_hash,
+                state_hash: state_hash.clone(),
+                created_at: now_utc(),
             })?;
+            checkpoint_hashes.push(state_hash);
+            provider_calls.push(invocation.provider_call);
+            proposed_memory_writes.extend(invocation.proposed_memory_writes);
+        }
 
-            let last_scored_at = row
-                .get::<_, Option<String>>(11)?
-                .as_deref()
-                .map(|value| parse_rfc3339_utc(value).map_err(|err| to_sql_error(&err)))
-                .transpose()?;
-            let updated_at_raw: String = row.get(12)?;
-            let updated_at =
-                parse_rfc3339_utc(&updated_at_raw).map_err(|err| to_sql_error(&err))?;
+        let message = transcript
+            .last()
+            .and_then(|entry| entry.get("message"))
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        Ok(StepResult {
+            run_id,
+            step_id,
+            status: StepStatus::Succeeded,
+            outputs: multi_agent_center_domain::StepOutputEnvelope {
+                message,
+                payload: json!({ "turns": transcript }),
+            },
+            proposed_memory_writes,
+            provider_calls,
+            gate_decisions: Vec::new(),
+            output_hash: String::new(),
+            error: None,
+            checkpoint_hashes,
+            score: None,
+        })
+    }
 
-            let wins_last5_i64: i64 = row.get(7)?;
-            let failures_last5_i64: i64 = row.get(8)?;
-            let wins_last5 = u8::try_from(wins_last5_i64).map_err(|_| {
-                rusqlite::Error::FromSqlConversionFailure(
-                    7,
-                    rusqlite::types::Type::Integer,
-                    Box::new(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("invalid wins_last5: {wins_last5_i64}"),
-                    )),
-                )
-            })?;
-            let failures_last5 = u8::try_from(failures_last5_i64).map_err(|_| {
-                rusqlite::Error::FromSqlConversionFailure(
-                    8,
-                    rusqlite::types::Type::Integer,
-                    Box::new(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("invalid failures_last5: {failures_last5_i64}"),
-                    )),
-                )
-            })?;
+    /// Check the step's declared memory dependencies against its injected context. Every
+    /// unmet dependency gets a `memory_dependency_missing` warning naming why it is absent:
+    /// `excluded_by_trust`, `pruned_by_permissions`, or `not_retrieved` (for example, the
+    /// memory was retired). Returns the unmet `fail` dependencies, if any.
+    fn check_memory_dependencies(
+        &self,
+        run_id: RunId,
+        step_id: StepId,
+        step: &WorkflowStepDefinition,
+        request: &StepRequest,
+        pruned_references: &[PrunedReference],
+        chain: &mut EventChain,
+    ) -> Result<Option<Vec<Value>>> {
+        let mut failing = Vec::new();
+        for dependency in &step.memory_dependencies {
+            let injected = request
+                .injected_context_packages
+                .iter()
+                .flat_map(|package| package.context_package.selected_items.iter())
+                .any(|item| dependency.matches_item(item));
+            if injected {
+                continue;
+            }
+            let reason =
+                if request.trust_gate_attachments.iter().any(|attachment| {
+                    !attachment.include && dependency.matches(attachment.memory_id)
+                }) {
+                    "excluded_by_trust"
+                } else if pruned_references
+                    .iter()
+                    .any(|pruned| match &dependency.tag {
+                        Some(tag) => pruned.tags.contains(tag),
+                        None => Ulid::from_string(&pruned.memory_id)
+                            .is_ok_and(|id| dependency.matches(memory_kernel_core::MemoryId(id))),
+                    })
+                {
+                    "pruned_by_permissions"
+                } else {
+                    "not_retrieved"
+                };
+            let mut detail = json!({
+                "on_missing": dependency.on_missing.as_str(),
+                "reason": reason,
+            });
+            match &dependency.tag {
+                Some(tag) => detail["tag"] = json!(tag),
+                None => detail["memory_id"] = json!(dependency.memory_id),
+            }
+            self.emit_event(
+                run_id,
+                Some(step_id),
+                TraceEventType::Warning,
+                "system",
+                "orchestrator",
+                json!({
+                    "warning_code": "memory_dependency_missing",
+                    "step_key": step.step_key,
+                    "dependency": detail,
+                    "continue_execution": dependency.on_missing == MissingDependencyAction::Warn,
+                }),
+                chain,
+            )?;
+            if dependency.on_missing == MissingDependencyAction::Fail {
+                failing.push(detail);
+            }
+        }
+        Ok((!failing.is_empty()).then_some(failing))
+    }
 
-            let trust = MemoryTrust {
-                memory_id,
-                version,
-                confidence_raw: row.get(0)?,
-                confidence_effective: row.get(1)?,
-                baseline_confidence: row.get(2)?,
-                trust_status,
-                contradiction_cap_active: row.get::<_, i64>(4)? == 1,
-                cap_value: row.get(5)?,
-                manual_override_active: row.get::<_, i64>(6)? == 1,
-                wins_last5,
-                failures_last5,
-                last_event_seq: row.get(9)?,
-                last_scored_at,
-                updated_at,
-            };
-            Ok((trust, last_ruleset_version))
-        },
-    )
-    .optional()
-    .map_err(anyhow::Error::from)
-}
+    /// Result of a step whose provider invocation failed, timed out, or was cancelled.
+    fn provider_failure_result(
+        &self,
+        run_id: RunId,
+        step_id: StepId,
+        step_key: &str,
+        err: &anyhow::Error,
+        chain: &mut EventChain,
+    ) -> Result<StepResult> {
+        let interruption = err.downcast_ref::<StepInterruption>().copied();
+        let (status, message, code) = match interruption {
+            Some(interruption) => (
+                interruption.status(),
+                "provider invocation abandoned",
+                interruption.error_code(),
+            ),
+            None => (
+                StepStatus::Failed,
+                "provider invocation failed",
+                "provider_invocation_failed",
+            ),
+        };
+        self.emit_event(
+            run_id,
+            Some(step_id),
+            TraceEventType::Error,
+            "provider",
+            "router",
+            json!({
+                "step_key": step_key,
+                "error": err.to_string(),
+                "error_code": code,
+            }),
+            chain,
+        )?;
+        Ok(StepResult {
+            run_id,
+            step_id,
+            status,
+            outputs: multi_agent_center_domain::StepOutputEnvelope {
+                message: message.to_string(),
+                payload: json!({"failed": true}),
+            },
+            proposed_memory_writes: Vec::new(),
+            provider_calls: Vec::new(),
+            gate_decisions: Vec::new(),
+            output_hash: String::new(),
+            error: Some(multi_agent_center_domain::ErrorEnvelope {
+                code: code.to_string(),
+                message: err.to_string(),
+            }),
+            checkpoint_hashes: Vec::new(),
+            score: None,
+        })
+    }
 
-fn to_sql_error(err: &memory_kernel_outcome_core::OutcomeError) -> rusqlite::Error {
-    rusqlite::Error::FromSqlConversionFailure(
-        0,
-        rusqlite::types::Type::Text,
-        Box::new(std::io::Error::new(
-            std::io::ErrorKind::InvalidData,
-            err.to_string(),
-        )),
-    )
-}
+    fn record_proposed_writes(
+        &self,
+        run_id: RunId,
+        step_id: StepId,
+        proposals: &[ProposedMemoryWrite],
+        apply_proposed_writes: bool,
+    ) -> Result<()> {
+        for proposal in proposals {
+            if apply_proposed_writes {
+                let apply = self.write_applier.apply(run_id, step_id, proposal)?;
+                self.trace_store.append_proposed_memory_write(
+                    run_id,
+                    step_id,
+                    proposal,
+                    &apply.disposition,
+                    apply.disposition_reason.as_deref(),
+                )?;
+            } else {
+                self.trace_store.append_proposed_memory_write(
+                    run_id,
+                    step_id,
+                    proposal,
+                    "not_applied",
+                    Some("apply_proposed_writes_disabled"),
+                )?;
+            }
+        }
+        Ok(())
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::{
-        AllowAllTrustGateSource, ApiMemoryKernelContextSource, ContextRef, DefaultHumanGateDecider,
-        HumanGateDecider, HumanGateRequest, HumanGateResponse, InMemoryMemoryKernelContextSource,
-        NoopProposedWriteApplier, Orchestrator, RunConfig, TrustGateAttachment, TrustGateSource,
-    };
-    use memory_kernel_api::{AddConstraintRequest, AddSummaryRequest, MemoryKernelApi};
-    use memory_kernel_core::{
-        default_recall_record_types, Answer, AnswerResult, Authority, ConstraintEffect,
-        ConstraintPayload, ConstraintScope, ContextItem, ContextPackage, DecisionPayload,
-        DeterminismMetadata, MemoryId, MemoryPayload, MemoryRecord, MemoryVersionId, QueryRequest,
-        RecordType, TruthStatus, Why,
-    };
-    use multi_agent_center_domain::{ContextPackageEnvelope, StepId, StepStatus};
-    use multi_agent_center_trace_core::TraceStore;
-    use multi_agent_center_trace_sqlite::SqliteTraceStore;
-    use multi_agent_center_workflow::normalize_workflow_yaml;
-    use serde_json::json;
-    use std::collections::BTreeMap;
-
-    fn temp_db_path(name: &str) -> std::path::PathBuf {
-        std::env::temp_dir().join(format!(
-            "mac-orchestrator-test-{}-{}.sqlite",
-            name,
-            ulid::Ulid::new()
-        ))
+    fn record_delegated_approval(
+        &self,
+        run_id: RunId,
+        step_id: StepId,
+        gate: &GatePointDefinition,
+        rule: &DelegatedApprovalRule,
+        evidence: &Value,
+        chain: &mut EventChain,
+    ) -> Result<()> {
+        let reason_codes = vec!["approved.delegated_rule".to_string()];
+        self.trace_store.append_gate_decision(
+            run_id,
+            step_id,
+            &GateDecisionRecord {
+                gate_kind: GateKind::Human,
+                gate_name: gate.gate_name.clone(),
+                subject_type: "step".to_string(),
+                memory_id: None,
+                version: None,
+                memory_version_id: None,
+                decision: GateDecision::Approved,
+                reason_codes: reason_codes.clone(),
+                notes: Some(format!("auto-approved by rule {}", rule.rule_name)),
+                decided_by: DELEGATED_DECIDER.to_string(),
+                decided_at: now_utc(),
+                source_ruleset_version: None,
+                evidence_json: Some(evidence.clone()),
+            },
+        )?;
+        self.emit_event(
+            run_id,
+            Some(step_id),
+            TraceEventType::GateEvaluated,
+            "system",
+            DELEGATED_DECIDER,
+            json!({
+                "gate_kind": "human",
+                "gate_name": gate.gate_name,
+                "decision": "approved",
+                "required": gate.required,
+                "reason_codes": reason_codes,
+                "evidence": evidence,
+            }),
+            chain,
+        )?;
+        Ok(())
     }
 
-    fn fixture_memory_record() -> MemoryRecord {
-        let now = time::OffsetDateTime::now_utc();
-        MemoryRecord {
-            memory_version_id: MemoryVersionId::new(),
-            memory_id: MemoryId::new(),
-            version: 1,
-            created_at: now,
-            effective_at: now,
-            truth_status: TruthStatus::Asserted,
-            authority: Authority::Authoritative,
-            confidence: Some(0.9),
-            writer: "test".to_string(),
-            justification: "fixture".to_string(),
-            provenance: memory_kernel_core::Provenance {
-                source_uri: "test://fixture".to_string(),
-                source_hash: None,
-                evidence: Vec::new(),
+    fn decide_human_gate(
+        &self,
+        run_id: RunId,
+        step_id: StepId,
+        step_key: &str,
+        gate: &GatePointDefinition,
+        non_interactive: bool,
+        chain: &mut EventChain,
+    ) -> Result<HumanGateResponse> {
+        let decision = if non_interactive
+            && gate.on_non_interactive == Some(NonInteractiveGateAction::Approve)
+        {
+            HumanGateResponse {
+                approved: true,
+                notes: Some("non-interactive policy approve".to_string()),
+                decided_by: NON_INTERACTIVE_DECIDER.to_string(),
+                reason_codes: vec!["approved.non_interactive_policy".to_string()],
+            }
+        } else {
+            self.human_gate.decide(&HumanGateRequest {
+                run_id,
+                step_id,
+                step_key: step_key.to_string(),
+                gate_name: gate.gate_name.clone(),
+                required: gate.required,
+                non_interactive,
+            })?
+        };
+
+        let gate_decision = if decision.approved {
+            GateDecision::Approved
+        } else {
+            GateDecision::Rejected
+        };
+
+        self.trace_store.append_gate_decision(
+            run_id,
+            step_id,
+            &GateDecisionRecord {
+                gate_kind: GateKind::Human,
+                gate_name: gate.gate_name.clone(),
+                subject_type: "step".to_string(),
+                memory_id: None,
+                version: None,
+                memory_version_id: None,
+                decision: gate_decision.clone(),
+                reason_codes: decision.reason_codes.clone(),
+                notes: decision.notes.clone(),
+                decided_by: decision.decided_by.clone(),
+                decided_at: now_utc(),
+                source_ruleset_version: None,
+                evidence_json: None,
             },
-            supersedes: Vec::new(),
-            contradicts: Vec::new(),
-            payload: MemoryPayload::Constraint(ConstraintPayload {
-                scope: ConstraintScope {
-                    actor: "*".to_string(),
-                    action: "*".to_string(),
-                    resource: "*".to_string(),
-                },
-                effect: ConstraintEffect::Allow,
-                note: None,
+        )?;
+
+        self.emit_event(
+            run_id,
+            Some(step_id),
+            TraceEventType::GateEvaluated,
+            "human",
+            &decision.decided_by,
+            json!({
+                "gate_kind": "human",
+                "gate_name": gate.gate_name,
+                "decision": gate_decision_to_text(&gate_decision),
+                "required": gate.required,
+                "reason_codes": decision.reason_codes,
+                "notes": decision.notes,
+            }),
+            chain,
+        )?;
+
+        Ok(decision)
+    }
+
+    /// Queue `gate` for a person to resolve instead of deciding it in this non-interactive run.
+    fn defer_human_gate(
+        &self,
+        run_id: RunId,
+        step_id: StepId,
+        step_key: &str,
+        gate: &GatePointDefinition,
+        chain: &mut EventChain,
+    ) -> Result<()> {
+        let record = DeferredGateRecord {
+            run_id,
+            step_id,
+            step_key: step_key.to_string(),
+            gate_name: gate.gate_name.clone(),
+            required: gate.required,
+            deferred_at: now_utc(),
+            approvers: gate.approvers.clone(),
+            webhook: gate.webhook.clone(),
+            resolution: None,
+        };
+        self.trace_store.enqueue_deferred_gate(&record)?;
+        self.emit_event(
+            run_id,
+            Some(step_id),
+            TraceEventType::GateEvaluated,
+            "system",
+            NON_INTERACTIVE_DECIDER,
+            json!({
+                "gate_kind": "human",
+                "gate_name": gate.gate_name,
+                "decision": "deferred",
+                "required": gate.required,
+                "reason_codes": ["deferred.non_interactive"],
             }),
+            chain,
+        )?;
+        if let Some(webhook) = gate.webhook.as_ref() {
+            if let Err(err) = self.notify_deferred_gate(&record, webhook) {
+                self.emit_event(
+                    run_id,
+                    Some(step_id),
+                    TraceEventType::Warning,
+                    "system",
+                    "orchestrator",
+                    json!({
+                        "warning_code": "gate_notification_failed",
+                        "step_key": step_key,
+                        "gate_name": gate.gate_name,
+                        "url": webhook.url,
+                        "error": format!("{err:#}"),
+                    }),
+                    chain,
+                )?;
+            }
         }
+        Ok(())
     }
 
-    fn fixture_summary_record(record_type: RecordType, summary: &str) -> MemoryRecord {
-        let now = time::OffsetDateTime::now_utc();
-        let payload = match record_type {
-            RecordType::Decision => MemoryPayload::Decision(DecisionPayload {
-                summary: summary.to_string(),
+    /// Send the signed `gate_notification.v1` document for `record` to `webhook`.
+    fn notify_deferred_gate(
+        &self,
+        record: &DeferredGateRecord,
+        webhook: &GateWebhook,
+    ) -> Result<()> {
+        let notifier = self
+            .gate_notifier
+            .ok_or_else(|| anyhow!("no gate notifier configured"))?;
+        let body = serde_json::to_vec(&json!({
+            "contract_version": "gate_notification.v1",
+            "run_id": record.run_id,
+            "step_id": record.step_id,
+            "step_key": record.step_key,
+            "gate_name": record.gate_name,
+            "required": record.required,
+            "approvers": record.approvers,
+            "deferred_at": format_rfc3339(record.deferred_at)?,
+            "callback_path": format!("/v1/trace/runs/{}/gates/callback", record.run_id),
+        }))?;
+        let signature = sign_gate_payload(&webhook.secret()?, &body);
+        notifier.notify(&webhook.url, &body, &format!("sha256={signature}"))
+    }
+
+    /// Record the decision a person made on `gate` after an earlier run deferred it.
+    fn record_gate_resolution(
+        &self,
+        run_id: RunId,
+        step_id: StepId,
+        gate: &GatePointDefinition,
+        resolution: &DeferredGateResolution,
+        chain: &mut EventChain,
+    ) -> Result<HumanGateResponse> {
+        let (decision, reason_code) = if resolution.approved {
+            (GateDecision::Approved, "approved.deferred_resolution")
+        } else {
+            (GateDecision::Rejected, "rejected.deferred_resolution")
+        };
+        let reason_codes = vec![reason_code.to_string()];
+        let identity = json!({
+            "auth_method": resolution.auth_method.as_str(),
+            "on_behalf_of": resolution.on_behalf_of,
+        });
+        self.trace_store.append_gate_decision(
+            run_id,
+            step_id,
+            &GateDecisionRecord {
+                gate_kind: GateKind::Human,
+                gate_name: gate.gate_name.clone(),
+                subject_type: "step".to_string(),
+                memory_id: None,
+                version: None,
+                memory_version_id: None,
+                decision: decision.clone(),
+                reason_codes: reason_codes.clone(),
+                notes: resolution.notes.clone(),
+                decided_by: resolution.decided_by.clone(),
+                decided_at: resolution.decided_at,
+                source_ruleset_version: None,
+                evidence_json: Some(identity.clone()),
+            },
+        )?;
+        self.emit_event(
+            run_id,
+            Some(step_id),
+            TraceEventType::GateEvaluated,
+            "human",
+            &resolution.decided_by,
+            json!({
+                "gate_kind": "human",
+                "gate_name": gate.gate_name,
+                "decision": gate_decision_to_text(&decision),
+                "required": gate.required,
+                "reason_codes": reason_codes,
+                "notes": resolution.notes,
+                "decider": identity,
             }),
-            RecordType::Preference => {
-                MemoryPayload::Preference(memory_kernel_core::PreferencePayload {
-                    summary: summary.to_string(),
+            chain,
+        )?;
+        Ok(HumanGateResponse {
+            approved: resolution.approved,
+            notes: resolution.notes.clone(),
+            decided_by: resolution.decided_by.clone(),
+            reason_codes,
+        })
+    }
+
+    /// Recorded state of the run `run_id`, which `workflow` must have produced. Fails when the
+    /// run has no step to run again.
+    fn load_resumed_run(
+        &self,
+        workflow: &NormalizedWorkflowEnvelope,
+        run_id: RunId,
+    ) -> Result<ResumedRun> {
+        let run = self
+            .trace_store
+            .get_run(run_id)?
+            .ok_or_else(|| anyhow!("run_id {run_id} not found"))?;
+        if !matches!(
+            run.status,
+            RunStatus::Running | RunStatus::Failed | RunStatus::PartiallyCompleted
+        ) {
+            return Err(anyhow!(
+                "run {run_id} is {}; only running, failed, or partially completed runs can be \
+                 resumed",
+                run_status_to_text(&run.status)
+            ));
+        }
+        if run.workflow_hash != workflow.normalized_hash {
+            return Err(anyhow!(
+                "run {run_id} was recorded for workflow hash {}, not {}",
+                run.workflow_hash,
+                workflow.normalized_hash
+            ));
+        }
+        let manifest = self
+            .trace_store
+            .get_run_manifest(run_id)?
+            .ok_or_else(|| anyhow!("run {run_id} has no manifest"))?;
+        let events = self.trace_store.list_events_for_run(run_id)?;
+        let prev_event_hash = events.last().map(|row| row.event.event_hash.clone());
+        let recorded_conditions: BTreeMap<String, bool> = recorded_step_events(
+            &events,
+            &[TraceEventType::ConditionEvaluated],
+            |_, payload| payload.get("result")?.as_bool(),
+        );
+        let recorded_expansions = recorded_expansions(&events);
+        let unresolved: BTreeSet<String> = self
+            .trace_store
+            .list_deferred_gates(Some(run_id))?
+            .into_iter()
+            .filter(|gate| gate.resolution.is_none())
+            .map(|gate| gate.step_key)
+            .collect();
+        let steps = self.trace_store.get_step_records(run_id)?;
+        let mut resumed_steps: BTreeSet<String> = steps
+            .iter()
+            .filter(|step| match step.status {
+                StepStatus::Deferred => !unresolved.contains(&step.step_key),
+                StepStatus::Running | StepStatus::Failed | StepStatus::TimedOut => true,
+                _ => false,
+            })
+            .map(|step| step.step_key.clone())
+            .collect();
+        if resumed_steps.is_empty() {
+            return Err(anyhow!(
+                "run {run_id} has no failed or interrupted step and no deferred step whose gates \
+                 are all resolved"
+            ));
+        }
+        // Steps skipped because a resumed step did not succeed get another chance too.
+        let dependencies: BTreeMap<&str, &[String]> = workflow
+            .normalized_workflow
+            .steps
+            .iter()
+            .map(|step| (step.step_key.as_str(), step.depends_on.as_slice()))
+            .collect();
+        loop {
+            let behind: Vec<String> = steps
+                .iter()
+                .filter(|step| {
+                    step.status == StepStatus::Skipped
+                        && !resumed_steps.contains(&step.step_key)
+                        && dependencies
+                            .get(step.step_key.as_str())
+                            .is_some_and(|deps| deps.iter().any(|dep| resumed_steps.contains(dep)))
                 })
+                .map(|step| step.step_key.clone())
+                .collect();
+            if behind.is_empty() {
+                break;
             }
-            RecordType::Event => MemoryPayload::Event(memory_kernel_core::EventPayload {
-                summary: summary.to_string(),
-            }),
-            RecordType::Outcome => MemoryPayload::Outcome(memory_kernel_core::OutcomePayload {
-                summary: summary.to_string(),
-            }),
-            RecordType::Constraint => panic!("fixture_summary_record does not support constraint"),
-        };
+            resumed_steps.extend(behind);
+        }
+        let mut recorded_context: BTreeMap<String, RecordedStepContext> = BTreeMap::new();
+        for row in self.trace_store.get_step_context_packages(run_id)? {
+            recorded_context.entry(row.step_key).or_default().insert(
+                (row.attempt, row.envelope.package_slot),
+                row.envelope.package_hash,
+            );
+        }
+        Ok(ResumedRun {
+            run,
+            steps,
+            manifest,
+            prev_event_hash,
+            resumed_steps,
+            recorded_context,
+            recorded_conditions,
+            recorded_expansions,
+        })
+    }
 
-        MemoryRecord {
-            memory_version_id: MemoryVersionId::new(),
-            memory_id: MemoryId::new(),
-            version: 1,
-            created_at: now,
-            effective_at: now,
-            truth_status: TruthStatus::Observed,
-            authority: Authority::Derived,
-            confidence: Some(0.8),
-            writer: "test".to_string(),
-            justification: "fixture".to_string(),
-            provenance: memory_kernel_core::Provenance {
-                source_uri: "test://fixture".to_string(),
-                source_hash: None,
-                evidence: Vec::new(),
+    /// Evaluate the `when` conditions that observe the output of the step at `source`, which
+    /// just succeeded, and record each result in a `condition_evaluated` event.
+    #[allow(clippy::too_many_arguments)]
+    fn evaluate_step_conditions(
+        &self,
+        run_id: RunId,
+        source_step_id: StepId,
+        steps: &[WorkflowStepDefinition],
+        conditions: &[Option<StepCondition>],
+        source: usize,
+        outputs: &StepOutputEnvelope,
+        condition_met: &mut BTreeMap<usize, bool>,
+        chain: &mut EventChain,
+    ) -> Result<()> {
+        let source_key = &steps[source].step_key;
+        let output = serde_json::to_value(outputs)?;
+        for (idx, condition) in conditions.iter().enumerate() {
+            let Some(condition) = condition.as_ref().filter(|c| c.step_key == *source_key) else {
+                continue;
+            };
+            let observed = condition.observe(&output);
+            let met = condition.holds(observed);
+            condition_met.insert(idx, met);
+            self.emit_event(
+                run_id,
+                Some(source_step_id),
+                TraceEventType::ConditionEvaluated,
+                "system",
+                "orchestrator",
+                json!({
+                    "step_key": steps[idx].step_key,
+                    "when": steps[idx].when,
+                    "observed_step": source_key,
+                    "observed": observed,
+                    "result": met,
+                }),
+                chain,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Resolve the items of the `foreach` steps whose source is the step at `source`, which just
+    /// succeeded, and record each resolution in a `foreach_expanded` event. Returns the steps to
+    /// expand with their items, `None` where the output has no array at the source path.
+    #[allow(clippy::too_many_arguments)]
+    fn resolve_foreach_items(
+        &self,
+        run_id: RunId,
+        source_step_id: StepId,
+        steps: &[WorkflowStepDefinition],
+        source: usize,
+        outputs: &StepOutputEnvelope,
+        expanded: &BTreeMap<usize, Option<Vec<usize>>>,
+        chain: &mut EventChain,
+    ) -> Result<Vec<(usize, StepExpansion)>> {
+        let source_key = &steps[source].step_key;
+        let output = serde_json::to_value(outputs)?;
+        let mut resolved = Vec::new();
+        for (idx, step) in steps.iter().enumerate() {
+            let Some(foreach) = step.foreach.as_deref() else {
+                continue;
+            };
+            let foreach_source = StepForeach::parse(foreach)?;
+            if foreach_source.step_key != *source_key || expanded.contains_key(&idx) {
+                continue;
+            }
+            let items = foreach_source.items(&output).cloned();
+            let child_keys: Vec<String> = (0..items.as_ref().map_or(0, Vec::len))
+                .map(|index| StepForeach::child_key(&step.step_key, index))
+                .collect();
+            self.emit_event(
+                run_id,
+                Some(source_step_id),
+                TraceEventType::ForeachExpanded,
+                "system",
+                "orchestrator",
+                json!({
+                    "step_key": step.step_key,
+                    "foreach": foreach,
+                    "source_step": source_key,
+                    "items": items,
+                    "child_keys": child_keys,
+                }),
+                chain,
+            )?;
+            resolved.push((idx, StepExpansion::Foreach(items)));
+        }
+        Ok(resolved)
+    }
+
+    /// Record the `foreach`, `iterate`, `vote`, or `workflow_call` step at `idx` as finished with
+    /// `result`, aggregated from its `children` (`None` for a `workflow_call` step and for a
+    /// `foreach` step whose source had no array to expand).
+    #[allow(clippy::too_many_arguments)]
+    fn finish_aggregate_step(
+        &self,
+        run_id: RunId,
+        step_id: StepId,
+        idx: usize,
+        steps: &[WorkflowStepDefinition],
+        children: Option<&[usize]>,
+        mut result: StepResult,
+        recorded: bool,
+        chain: &mut EventChain,
+    ) -> Result<StepResult> {
+        let step = &steps[idx];
+        result.output_hash = compute_step_result_hash(&result)?;
+        let error_json = result
+            .error
+            .as_ref()
+            .map(serde_json::to_value)
+            .transpose()?;
+        if recorded {
+            self.trace_store.update_step_status(
+                step_id,
+                result.status.clone(),
+                Some(&result.output_hash),
+                error_json.as_ref(),
+            )?;
+        } else {
+            self.trace_store.insert_step(&StepRecord {
+                status: result.status.clone(),
+                ended_at: Some(now_utc()),
+                output_hash: Some(result.output_hash.clone()),
+                error_json,
+                ..aggregate_step_record(run_id, step_id, idx, steps, children)?
+            })?;
+        }
+        let count_field = match (&step.iterate, &step.workflow_call, &step.vote) {
+            (Some(_), _, _) => Some("iterations"),
+            (None, Some(_), _) => None,
+            (None, None, Some(_)) => Some("vote_children"),
+            (None, None, None) => Some("foreach_children"),
+        };
+        let mut finished = json!({
+            "step_key": step.step_key,
+            "status": step_status_to_text(&result.status),
+            "output_hash": result.output_hash,
+            "content_hash": match &step.workflow_call {
+                Some(_) => workflow_call_content_hash(&result)?,
+                None => compute_step_content_hash(&result)?,
             },
-            supersedes: Vec::new(),
-            contradicts: Vec::new(),
-            payload,
+        });
+        match count_field {
+            Some(count_field) => finished[count_field] = json!(children.map(<[usize]>::len)),
+            None => finished["child_run_id"] = result.outputs.payload["child_run_id"].clone(),
         }
+        self.emit_event(
+            run_id,
+            Some(step_id),
+            TraceEventType::StepFinished,
+            "system",
+            "orchestrator",
+            finished,
+            chain,
+        )?;
+        Ok(result)
     }
 
-    fn fixture_context_package(step_key: &str) -> ContextPackageEnvelope {
-        let now = time::OffsetDateTime::now_utc();
-        let selected_items = vec![
-            ContextItem {
+    /// Run the workflow called by the `workflow_call` step `step` as a nested run linked to it,
+    /// sharing this run's `as_of`, labels, and execution policy, and roll the nested run's
+    /// status up into the step's result. A replay replays the nested run its source run recorded
+    /// for the step, answered from that run's own trace.
+    #[allow(clippy::too_many_arguments)]
+    fn execute_workflow_call(
+        &self,
+        run_id: RunId,
+        step_id: StepId,
+        step: &WorkflowStepDefinition,
+        call: &StepWorkflowCall,
+        config: &RunConfig,
+        as_of: time::OffsetDateTime,
+        chain: &mut EventChain,
+    ) -> Result<StepResult> {
+        let callee = call.workflow.as_deref().ok_or_else(|| {
+            anyhow!(
+                "step {} calls workflow {}, which was not resolved",
+                step.step_key,
+                call.path
+            )
+        })?;
+        let callee = normalize_workflow_definition(callee.clone())?;
+        let replayed_child = match (config.replay_of_run_id, self.recorded_responses) {
+            (Some(source_run_id), Some(_)) => Some(
+                self.trace_store
+                    .list_child_runs(source_run_id)?
+                    .into_iter()
+                    .find(|run| {
+                        run.parent_step
+                            .as_ref()
+                            .is_some_and(|parent| parent.step_key == step.step_key)
+                    })
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "run {source_run_id} recorded no nested run for step {}",
+                            step.step_key
+                        )
+                    })?
+                    .run_id,
+            ),
+            _ => None,
+        };
+        let child_run_id = RunId::new();
+        self.emit_event(
+            run_id,
+            None,
+            TraceEventType::WorkflowCallStarted,
+            "system",
+            "orchestrator",
+            json!({
+                "step_key": step.step_key,
+                "child_run_id": child_run_id,
+                "workflow_name": callee.normalized_workflow.workflow_name,
+                "workflow_version": callee.normalized_workflow.workflow_version,
+                "workflow_hash": callee.normalized_hash,
+            }),
+            chain,
+        )?;
+        let child_config = RunConfig {
+            run_id: Some(child_run_id),
+            as_of: Some(as_of),
+            replay_of_run_id: replayed_child,
+            non_interactive: config.non_interactive,
+            cli_args_json: config.cli_args_json.clone(),
+            engine_version: config.engine_version.clone(),
+            apply_proposed_writes: config.apply_proposed_writes,
+            speculative_execution: config.speculative_execution,
+            labels: config.labels.clone(),
+            require_trust_gating: config.require_trust_gating,
+            memoize_steps: config.memoize_steps,
+            relaxed_trace_artifacts: config.relaxed_trace_artifacts.clone(),
+            enforce_permission_prune: config.enforce_permission_prune,
+            parent_step: Some(ParentStep {
+                run_id,
+                step_id,
+                step_key: step.step_key.clone(),
+            }),
+            cancellation: config.cancellation.clone(),
+            ..RunConfig::default()
+        };
+        let summary = match replayed_child {
+            Some(source_child) => {
+                let sources = ReplaySources::from_trace(self.trace_store, source_child)?;
+                self.replaying(&sources)
+                    .execute_workflow(&callee, child_config)?
+            }
+            None => self.execute_workflow(&callee, child_config)?,
+        };
+        self.emit_event(
+            run_id,
+            None,
+            TraceEventType::WorkflowCallFinished,
+            "system",
+            "orchestrator",
+            json!({
+                "step_key": step.step_key,
+                "child_run_id": child_run_id,
+                "status": run_status_to_text(&summary.status),
+            }),
+            chain,
+        )?;
+        Ok(workflow_call_result(run_id, step_id, &summary))
+    }
+
+    /// Queue iteration `iteration` of the `iterate` step at `parent`, given the previous
+    /// iteration's output, and record its task in an `iteration_started` event.
+    fn start_iteration(
+        &self,
+        run_id: RunId,
+        steps: &[WorkflowStepDefinition],
+        parent: usize,
+        iteration: u32,
+        previous_output: &Value,
+        chain: &mut EventChain,
+    ) -> Result<StepExpansion> {
+        let step = &steps[parent];
+        let task = StepIteration::iteration_task(&step.task, iteration, previous_output);
+        self.emit_event(
+            run_id,
+            None,
+            TraceEventType::IterationStarted,
+            "system",
+            "orchestrator",
+            json!({
+                "step_key": step.step_key,
+                "iteration": iteration,
+                "child_key": StepIteration::child_key(&step.step_key, iteration),
+                "max_iterations": step.iterate.as_ref().map(|iterate| iterate.max_iterations),
+                "task": task,
+            }),
+            chain,
+        )?;
+        Ok(StepExpansion::Iteration { iteration, task })
+    }
+
+    /// Queue the candidate steps of the `vote` step at `parent`, or, once its candidates
+    /// (`children`) finished, its judge step given the outputs of those that succeeded, and
+    /// record them in a `vote_expanded` event.
+    fn start_vote_stage(
+        &self,
+        run_id: RunId,
+        steps: &[WorkflowStepDefinition],
+        parent: usize,
+        children: Option<&[usize]>,
+        child_outputs: &BTreeMap<usize, StepOutputEnvelope>,
+        chain: &mut EventChain,
+    ) -> Result<StepExpansion> {
+        let step = &steps[parent];
+        let vote = step
+            .vote
+            .as_ref()
+            .ok_or_else(|| anyhow!("step {} does not vote", step.step_key))?;
+        let Some(children) = children else {
+            let child_keys: Vec<String> = vote
+                .agents
+                .iter()
+                .map(|agent| StepVote::candidate_key(&step.step_key, agent))
+                .collect();
+            self.emit_event(
+                run_id,
+                None,
+                TraceEventType::VoteExpanded,
+                "system",
+                "orchestrator",
+                json!({
+                    "step_key": step.step_key,
+                    "aggregator": vote.aggregator,
+                    "agents": vote.agents,
+                    "child_keys": child_keys,
+                }),
+                chain,
+            )?;
+            return Ok(StepExpansion::Vote { judge_task: None });
+        };
+        let judge = vote
+            .judge_agent
+            .as_deref()
+            .ok_or_else(|| anyhow!("vote step {} has no judge_agent", step.step_key))?;
+        let candidates = children
+            .iter()
+            .filter_map(|child| child_outputs.get(child).map(|outputs| (child, outputs)))
+            .map(|(child, outputs)| {
+                Ok((
+                    steps[*child].agent_name.clone(),
+                    serde_json::to_value(outputs)?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let task = StepVote::judge_task(&step.task, &candidates);
+        self.emit_event(
+            run_id,
+            None,
+            TraceEventType::VoteExpanded,
+            "system",
+            "orchestrator",
+            json!({
+                "step_key": step.step_key,
+                "aggregator": vote.aggregator,
+                "judge_agent": judge,
+                "child_keys": [StepVote::judge_key(&step.step_key, judge)],
+                "judge_task": task,
+            }),
+            chain,
+        )?;
+        Ok(StepExpansion::Vote {
+            judge_task: Some(task),
+        })
+    }
+
+    /// Result of the `vote` step at `parent` from its candidate steps and judge step
+    /// (`children`): the output of the candidate its aggregator selected, recorded with every
+    /// candidate in a `vote_decided` event. The `human_gate` aggregator asks for approval of each
+    /// succeeded candidate in agent order, recording each decision on the candidate step.
+    #[allow(clippy::too_many_arguments, clippy::too_many_lines)]
+    fn vote_result(
+        &self,
+        run_id: RunId,
+        steps: &[WorkflowStepDefinition],
+        parent: usize,
+        children: &[usize],
+        statuses: &[StepStatus],
+        step_ids: &[StepId],
+        child_outputs: &BTreeMap<usize, StepOutputEnvelope>,
+        non_interactive: bool,
+        chain: &mut EventChain,
+    ) -> Result<StepResult> {
+        let step = &steps[parent];
+        let vote = step
+            .vote
+            .as_ref()
+            .ok_or_else(|| anyhow!("step {} does not vote", step.step_key))?;
+        let (candidates, judge) = children.split_at(children.len().min(vote.agents.len()));
+        let succeeded: Vec<usize> = candidates
+            .iter()
+            .copied()
+            .filter(|child| {
+                statuses[*child] == StepStatus::Succeeded && child_outputs.contains_key(child)
+            })
+            .collect();
+        let mut decision = json!({
+            "step_key": step.step_key,
+            "aggregator": vote.aggregator,
+            "candidates": candidates
+                .iter()
+                .map(|child| json!({
+                    "agent": steps[*child].agent_name,
+                    "step_key": steps[*child].step_key,
+                    "status": step_status_to_text(&statuses[*child]),
+                    "output": child_outputs.get(child),
+                }))
+                .collect::<Vec<_>>(),
+        });
+
+        let selected = if succeeded.is_empty() {
+            Err((
+                StepStatus::Failed,
+                "vote_no_candidates",
+                format!("none of the {} candidate(s) succeeded", candidates.len()),
+            ))
+        } else {
+            match vote.aggregator {
+                VoteAggregator::Majority => {
+                    // Distinct ballots in first-seen order with their voters; the stable sort
+                    // keeps that order among equal tallies. Outputs without a ballot abstain.
+                    let mut tally: Vec<(Value, Vec<usize>)> = Vec::new();
+                    for child in &succeeded {
+                        let output = serde_json::to_value(&child_outputs[child])?;
+                        let Some(ballot) = vote.ballot(&output)? else {
+                            continue;
+                        };
+                        match tally.iter_mut().find(|(seen, _)| seen == ballot) {
+                            Some((_, voters)) => voters.push(*child),
+                            None => tally.push((ballot.clone(), vec![*child])),
+                        }
+                    }
+                    tally.sort_by_key(|(_, voters)| std::cmp::Reverse(voters.len()));
+                    decision["tally"] = json!(tally
+                        .iter()
+                        .map(|(ballot, voters)| json!({
+                            "ballot": ballot,
+                            "agents": voters
+                                .iter()
+                                .map(|child| steps[*child].agent_name.as_str())
+                                .collect::<Vec<_>>(),
+                        }))
+                        .collect::<Vec<_>>());
+                    match tally.as_slice() {
+                        [(_, top), (_, runner_up), ..] if top.len() == runner_up.len() => Err((
+                            StepStatus::Failed,
+                            "vote_tied",
+                            format!(
+                                "no output has a majority; the top outputs tied with {} vote(s)",
+                                top.len()
+                            ),
+                        )),
+                        [(_, top), ..] => Ok(top[0]),
+                        [] => Err((
+                            StepStatus::Failed,
+                            "vote_no_candidates",
+                            "no candidate output holds a ballot".to_string(),
+                        )),
+                    }
+                }
+                VoteAggregator::Judge => {
+                    let verdict = judge
+                        .first()
+                        .map(|child| (*child, &statuses[*child], child_outputs.get(child)));
+                    match verdict {
+                        Some((judge_step, StepStatus::Succeeded, Some(outputs))) => {
+                            // Adapters that wrap the agent's answer nest it under `payload`.
+                            let choice = outputs
+                                .payload
+                                .get("selected")
+                                .or_else(|| outputs.payload.pointer("/payload/selected"))
+                                .and_then(Value::as_str);
+                            decision["judge_step"] = json!(steps[judge_step].step_key);
+                            decision["judge_selected"] = json!(choice);
+                            succeeded
+                                .iter()
+                                .copied()
+                                .find(|child| Some(steps[*child].agent_name.as_str()) == choice)
+                                .ok_or_else(|| {
+                                    (
+                                        StepStatus::Failed,
+                                        "vote_judge_invalid_selection",
+                                        format!(
+                                            "judge step {} did not select a succeeded candidate \
+                                             in payload.selected",
+                                            steps[judge_step].step_key
+                                        ),
+                                    )
+                                })
+                        }
+                        verdict => Err((
+                            match verdict.map(|(_, status, _)| status) {
+                                Some(status @ (StepStatus::Cancelled | StepStatus::Rejected)) => {
+                                    status.clone()
+                                }
+                                _ => StepStatus::Failed,
+                            },
+                            "vote_judge_not_succeeded",
+                            "the judge step did not succeed".to_string(),
+                        )),
+                    }
+                }
+                VoteAggregator::HumanGate => {
+                    let mut approved = None;
+                    for child in &succeeded {
+                        let gate = GatePointDefinition {
+                            gate_name: format!("vote.{}", steps[*child].agent_name),
+                            gate_kind: GateKind::Human,
+                            required: true,
+                            auto_approve: Vec::new(),
+                            on_non_interactive: None,
+                            approvers: Vec::new(),
+                            webhook: None,
+                        };
+                        let response = self.decide_human_gate(
+                            run_id,
+                            step_ids[*child],
+                            &steps[*child].step_key,
+                            &gate,
+                            non_interactive,
+                            chain,
+                        )?;
+                        if response.approved {
+                            decision["decided_by"] = json!(response.decided_by);
+                            approved = Some(*child);
+                            break;
+                        }
+                    }
+                    approved.ok_or_else(|| {
+                        (
+                            StepStatus::Rejected,
+                            "vote_rejected",
+                            format!(
+                                "none of the {} succeeded candidate(s) was approved",
+                                succeeded.len()
+                            ),
+                        )
+                    })
+                }
+            }
+        };
+
+        let (status, outputs, error) = match selected {
+            Ok(child) => {
+                decision["selected"] = json!(steps[child].agent_name);
+                (StepStatus::Succeeded, child_outputs[&child].clone(), None)
+            }
+            Err((status, code, message)) => {
+                decision["selected"] = Value::Null;
+                decision["reason_code"] = json!(code);
+                (
+                    status,
+                    multi_agent_center_domain::StepOutputEnvelope {
+                        message: "vote did not select an output".to_string(),
+                        payload: json!({"failed": true, "candidates": candidates.len()}),
+                    },
+                    Some(multi_agent_center_domain::ErrorEnvelope {
+                        code: code.to_string(),
+                        message,
+                    }),
+                )
+            }
+        };
+        decision["status"] = json!(step_status_to_text(&status));
+        self.emit_event(
+            run_id,
+            Some(step_ids[parent]),
+            TraceEventType::VoteDecided,
+            "system",
+            "orchestrator",
+            decision,
+            chain,
+        )?;
+        Ok(StepResult {
+            run_id,
+            step_id: step_ids[parent],
+            status,
+            outputs,
+            proposed_memory_writes: Vec::new(),
+            provider_calls: Vec::new(),
+            gate_decisions: Vec::new(),
+            output_hash: String::new(),
+            error,
+            checkpoint_hashes: Vec::new(),
+            score: None,
+        })
+    }
+
+    /// Check the output of iteration `iteration` of the `iterate` step at `parent`, which just
+    /// succeeded, against the step's `until` expression and record the result in an
+    /// `iteration_evaluated` event. Returns whether it converged, and the next iteration when it
+    /// did not and iterations remain.
+    #[allow(clippy::too_many_arguments)]
+    fn evaluate_iteration(
+        &self,
+        run_id: RunId,
+        step_id: StepId,
+        steps: &[WorkflowStepDefinition],
+        parent: usize,
+        iteration: u32,
+        outputs: &StepOutputEnvelope,
+        chain: &mut EventChain,
+    ) -> Result<(bool, Option<StepExpansion>)> {
+        let step = &steps[parent];
+        let iterate = step
+            .iterate
+            .as_ref()
+            .ok_or_else(|| anyhow!("step {} does not iterate", step.step_key))?;
+        let output = serde_json::to_value(outputs)?;
+        let until = StepConvergence::parse(&iterate.until)?;
+        let observed = until.observe(&output);
+        let met = until.holds(observed);
+        self.emit_event(
+            run_id,
+            Some(step_id),
+            TraceEventType::IterationEvaluated,
+            "system",
+            "orchestrator",
+            json!({
+                "step_key": step.step_key,
+                "iteration": iteration,
+                "child_key": StepIteration::child_key(&step.step_key, iteration),
+                "until": iterate.until,
+                "observed": observed,
+                "converged": met,
+            }),
+            chain,
+        )?;
+        let next = if met || iteration >= iterate.max_iterations {
+            None
+        } else {
+            Some(self.start_iteration(run_id, steps, parent, iteration + 1, &output, chain)?)
+        };
+        Ok((met, next))
+    }
+
+    /// Steps a rerun of `rerun.step_key` executes again, and the recorded outputs of `rerun`'s
+    /// source run the remaining steps reuse. A source step that was itself memoized reuses the
+    /// run it was memoized from.
+    fn prepare_step_rerun(
+        &self,
+        workflow: &NormalizedWorkflow,
+        rerun: &StepRerun,
+    ) -> Result<PreparedStepRerun> {
+        if self.trace_store.get_run(rerun.source_run_id)?.is_none() {
+            return Err(anyhow!("run_id {} not found", rerun.source_run_id));
+        }
+        let steps = &workflow.steps;
+        let target = steps
+            .iter()
+            .position(|step| step.step_key == rerun.step_key)
+            .ok_or_else(|| {
+                anyhow!(
+                    "step {} is not part of workflow {}",
+                    rerun.step_key,
+                    workflow.workflow_name
+                )
+            })?;
+        let mut invalidated = BTreeSet::from([target]);
+        loop {
+            let downstream: Vec<usize> = steps
+                .iter()
+                .enumerate()
+                .filter(|(idx, step)| {
+                    !invalidated.contains(idx)
+                        && step.depends_on.iter().any(|dep| {
+                            invalidated
+                                .iter()
+                                .any(|upstream| steps[*upstream].step_key == *dep)
+                        })
+                })
+                .map(|(idx, _)| idx)
+                .collect();
+            if downstream.is_empty() {
+                break;
+            }
+            invalidated.extend(downstream);
+        }
+
+        let mut reused = BTreeMap::new();
+        for step in self.trace_store.get_step_records(rerun.source_run_id)? {
+            let rerun_step = steps
+                .iter()
+                .position(|candidate| candidate.step_key == step.step_key)
+                .map_or(true, |idx| invalidated.contains(&idx));
+            if step.status != StepStatus::Succeeded || rerun_step {
+                continue;
+            }
+            let origin_run = step.memoized_from_run.unwrap_or(step.run_id);
+            let Some(origin_step) = self
+                .trace_store
+                .get_step_records(origin_run)?
+                .into_iter()
+                .find(|candidate| candidate.step_key == step.step_key)
+            else {
+                continue;
+            };
+            let calls: Vec<ProviderCallRecord> = self
+                .trace_store
+                .get_step_provider_calls(origin_run)?
+                .into_iter()
+                .filter(|call| call.step_key == step.step_key)
+                .map(|call| call.provider_call)
+                .collect();
+            if let [call] = calls.as_slice() {
+                if call.status == "succeeded" {
+                    reused.insert(
+                        step.step_key.clone(),
+                        MemoizedStepRecord {
+                            run_id: origin_run,
+                            step_id: origin_step.step_id,
+                            provider_call: call.clone(),
+                        },
+                    );
+                }
+            }
+        }
+        Ok(PreparedStepRerun {
+            invalidated,
+            reused,
+        })
+    }
+
+    /// Rebuild the output of the earlier step `memo` from its recorded provider response without
+    /// calling the provider, emitting a `step_memoized` warning naming the source run and step.
+    fn memoized_step_result(
+        &self,
+        run_id: RunId,
+        step_id: StepId,
+        request: &StepRequest,
+        memo: &MemoizedStepRecord,
+        chain: &mut EventChain,
+    ) -> Result<StepResult> {
+        let invocation = replay_recorded_response(
+            request,
+            &RecordedProviderResponse::from(&memo.provider_call),
+        )?;
+        self.emit_event(
+            run_id,
+            Some(step_id),
+            TraceEventType::Warning,
+            "system",
+            "orchestrator",
+            json!({
+                "warning_code": "step_memoized",
+                "step_key": request.step_key,
+                "memoized_from_run": memo.run_id,
+                "memoized_from_step": memo.step_id,
+            }),
+            chain,
+        )?;
+        Ok(StepResult {
+            provider_calls: Vec::new(),
+            ..build_step_result_from_provider(run_id, step_id, invocation)
+        })
+    }
+
+    /// Decide the taint policy's elevated gate for a tainted step, as a required gate, with
+    /// `subject` (`external_provider` or `proposed_writes`) recorded as the reason it was asked.
+    #[allow(clippy::too_many_arguments)]
+    fn decide_elevated_gate(
+        &self,
+        run_id: RunId,
+        step_id: StepId,
+        step_key: &str,
+        workflow: &NormalizedWorkflowEnvelope,
+        policy: &TaintPolicy,
+        subject: &str,
+        non_interactive: bool,
+        chain: &mut EventChain,
+    ) -> Result<bool> {
+        let gate = workflow
+            .normalized_workflow
+            .gates
+            .iter()
+            .find(|candidate| candidate.gate_name == policy.elevated_gate)
+            .ok_or_else(|| anyhow!("missing gate {}", policy.elevated_gate))?;
+        let elevated = GatePointDefinition {
+            required: true,
+            ..gate.clone()
+        };
+        self.emit_event(
+            run_id,
+            Some(step_id),
+            TraceEventType::Warning,
+            "system",
+            "orchestrator",
+            json!({
+                "warning_code": "tainted_step_elevated_gate",
+                "step_key": step_key,
+                "gate_name": elevated.gate_name,
+                "subject": subject,
+            }),
+            chain,
+        )?;
+        let decision =
+            self.decide_human_gate(run_id, step_id, step_key, &elevated, non_interactive, chain)?;
+        Ok(decision.approved)
+    }
+
+    fn persist_provider_call(
+        &self,
+        run_id: RunId,
+        step_id: StepId,
+        provider_call: &ProviderCallRecord,
+        chain: &mut EventChain,
+    ) -> Result<()> {
+        self.trace_store
+            .append_provider_call(run_id, step_id, provider_call)?;
+        self.emit_event(
+            run_id,
+            Some(step_id),
+            TraceEventType::ProviderCalled,
+            "provider",
+            &provider_call.provider_name,
+            json!({
+                "provider_call_id": provider_call.provider_call_id,
+                "provider_name": provider_call.provider_name,
+                "model_id": provider_call.model_id,
+                "request_hash": provider_call.request_hash,
+                "response_hash": provider_call.response_hash,
+                "latency_ms": provider_call.latency_ms,
+            }),
+            chain,
+        )?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn emit_event(
+        &self,
+        run_id: RunId,
+        step_id: Option<StepId>,
+        event_type: TraceEventType,
+        actor_type: &str,
+        actor_id: &str,
+        payload_json: Value,
+        chain: &mut EventChain,
+    ) -> Result<EventRow> {
+        let occurred_at = now_utc();
+        let recorded_at = now_utc();
+        let payload_hash = hash_json(&payload_json)?;
+        let event_id = Ulid::new();
+
+        let material = json!({
+            "event_id": event_id,
+            "run_id": run_id,
+            "step_id": step_id,
+            "event_type": event_type,
+            "occurred_at": format_rfc3339(occurred_at)?,
+            "recorded_at": format_rfc3339(recorded_at)?,
+            "actor_type": actor_type,
+            "actor_id": actor_id,
+            "payload_hash": payload_hash,
+            "prev_event_hash": chain.prev_event_hash,
+        });
+        let event_hash = hash_json(&material)?;
+
+        let event = TraceEvent {
+            event_id,
+            run_id,
+            step_id,
+            event_type,
+            occurred_at,
+            recorded_at,
+            actor_type: actor_type.to_string(),
+            actor_id: actor_id.to_string(),
+            payload_json,
+            payload_hash,
+            prev_event_hash: chain.prev_event_hash.clone(),
+            event_hash: event_hash.clone(),
+        };
+
+        let event_seq = self.trace_store.append_event(&event)?;
+        chain.prev_event_hash = Some(event_hash);
+
+        Ok(EventRow { event_seq, event })
+    }
+}
+
+/// Call the provider for one step. When `budget` can never interrupt the call (no deadline and
+/// an unshared cancellation token) it runs inline on the calling thread.
+///
+/// Otherwise it runs on a worker thread so the step can be abandoned when `budget` runs out.
+/// Adapters cannot be interrupted, so an abandoned worker keeps running until `invoke` returns
+/// and its result is dropped; each timed-out or cancelled step leaves at most one such worker,
+/// and HTTP adapters bound its lifetime by their `timeout_ms`.
+fn call_live_provider(
+    request: &StepRequest,
+    providers: &ProviderRegistry,
+    budget: &StepBudget<'_>,
+) -> Result<ProviderInvocation> {
+    let adapter = providers.adapter(&request.agent.provider.provider_name)?;
+    if !budget.can_interrupt() {
+        return adapter.invoke(request);
+    }
+    if let Some(interruption) = budget.interruption() {
+        return Err(interruption.into());
+    }
+    let (sender, receiver) = mpsc::channel();
+    let worker_request = request.clone();
+    std::thread::Builder::new()
+        .name(format!("provider-call-{}", request.step_key))
+        .spawn(move || {
+            let _ = sender.send(adapter.invoke(&worker_request));
+        })?;
+    loop {
+        match receiver.recv_timeout(PROVIDER_POLL_INTERVAL) {
+            Ok(outcome) => return outcome,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                if let Some(interruption) = budget.interruption() {
+                    return Err(interruption.into());
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                return Err(anyhow!(
+                    "provider call for step {} ended without a result",
+                    request.step_key
+                ));
+            }
+        }
+    }
+}
+
+/// `agent` with its primary binding replaced by the `load_balancing` endpoint assigned to step
+/// `idx`, and the assignment record stored under `endpoint` in the step's provider selection.
+/// Steps are numbered per agent in step order from the run's `first_turn`; `foreach`,
+/// `iterate`, and `vote` steps never call a provider, so only their children are counted.
+fn balanced_agent(
+    agent: &AgentDefinition,
+    steps: &[multi_agent_center_domain::WorkflowStepDefinition],
+    idx: usize,
+    first_turn: u64,
+) -> Option<(AgentDefinition, Value)> {
+    let balancing = agent.load_balancing.as_ref()?;
+    let turn = steps[..idx]
+        .iter()
+        .filter(|step| calls_provider_as(step, agent))
+        .count();
+    let ordinal = usize::try_from(first_turn)
+        .unwrap_or(usize::MAX)
+        .wrapping_add(turn);
+    let selected = balancing.select(&agent.provider, ordinal);
+    let record = json!({
+        "strategy": balancing.strategy.as_str(),
+        "ordinal": ordinal,
+        "position": selected.position,
+        "weight": selected.weight,
+        "provider_name": selected.binding.provider_name,
+        "model_id": selected.binding.model_id,
+    });
+    let mut agent = agent.clone();
+    agent.provider = selected.binding;
+    Some((agent, record))
+}
+
+/// Whether `step` calls `agent`'s provider itself rather than through child steps.
+fn calls_provider_as(step: &WorkflowStepDefinition, agent: &AgentDefinition) -> bool {
+    step.agent_name == agent.agent_name
+        && step.foreach.is_none()
+        && step.iterate.is_none()
+        && step.vote.is_none()
+}
+
+/// First load balancing turn of each balanced agent, as recorded in a run manifest; runs
+/// recorded without one start every agent at turn 0.
+fn recorded_load_balancing_turns(manifest: &Value) -> BTreeMap<String, u64> {
+    manifest
+        .get("load_balancing_turns")
+        .cloned()
+        .and_then(|turns| serde_json::from_value(turns).ok())
+        .unwrap_or_default()
+}
+
+/// Whether any of `agent`'s provider bindings leaves the process; only the `mock` adapter is local.
+fn sends_to_external_provider(agent: &AgentDefinition) -> bool {
+    std::iter::once(&agent.provider)
+        .chain(&agent.fallbacks)
+        .any(|binding| binding.provider_name != "mock")
+}
+
+/// Injected items of `request` that `policy` marks sensitive, by memory version, with the
+/// distinct reasons they were marked.
+fn injected_taint(policy: &TaintPolicy, request: &StepRequest) -> StepTaint {
+    let mut memory_version_ids = BTreeSet::new();
+    let mut reasons = BTreeSet::new();
+    for package in &request.injected_context_packages {
+        for item in &package.context_package.selected_items {
+            let item_reasons = policy.sensitive_reasons(&package.source, item);
+            if !item_reasons.is_empty() {
+                memory_version_ids.insert(item.memory_version_id);
+                reasons.extend(item_reasons);
+            }
+        }
+    }
+    StepTaint {
+        memory_version_ids: memory_version_ids.into_iter().collect(),
+        reasons: reasons.into_iter().collect(),
+        inherited_from: Vec::new(),
+    }
+}
+
+/// Memories injected into `request` (after trust gating), each with the ruleset version of its
+/// trust attachment.
+fn scored_memories(request: &StepRequest) -> Vec<ScoredMemory> {
+    let rulesets: BTreeMap<(String, u32), Option<u32>> = request
+        .trust_gate_attachments
+        .iter()
+        .map(|item| {
+            (
+                (item.memory_id.to_string(), item.version),
+                item.ruleset_version,
+            )
+        })
+        .collect();
+    let mut seen = BTreeSet::new();
+    request
+        .injected_context_packages
+        .iter()
+        .flat_map(|package| package.context_package.selected_items.iter())
+        .filter(|item| seen.insert((item.memory_id.to_string(), item.version)))
+        .map(|item| ScoredMemory {
+            memory_id: item.memory_id,
+            version: item.version,
+            ruleset_version: rulesets
+                .get(&(item.memory_id.to_string(), item.version))
+                .copied()
+                .flatten(),
+        })
+        .collect()
+}
+
+/// Outcome of invoking a step's provider, including retries and fallbacks.
+struct ProviderStepOutcome {
+    invocation: Result<ProviderInvocation>,
+    retry_json: Option<Value>,
+    provider_selection_json: Option<Value>,
+    /// Result of a step whose retry rebuilt context it may not run with; `invocation` is then
+    /// the failed attempt before it.
+    halted: Option<StepResult>,
+}
+
+/// What a retry that rebuilds its context goes through again before the provider is called:
+/// the recording and gating of the step's first context.
+struct RetryGating<'a> {
+    workflow: &'a NormalizedWorkflowEnvelope,
+    recorded_context: Option<&'a RecordedStepContext>,
+    enforce_permission_prune: bool,
+    non_interactive: bool,
+    taint: &'a mut StepTaint,
+    elevated_approved: &'a mut Option<bool>,
+}
+
+/// Why a step's provider calls were abandoned before they returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StepInterruption {
+    TimedOut,
+    Cancelled,
+}
+
+impl StepInterruption {
+    fn status(self) -> StepStatus {
+        match self {
+            Self::TimedOut => StepStatus::TimedOut,
+            Self::Cancelled => StepStatus::Cancelled,
+        }
+    }
+
+    fn error_code(self) -> &'static str {
+        match self {
+            Self::TimedOut => "step_timed_out",
+            Self::Cancelled => "run_cancelled",
+        }
+    }
+}
+
+impl std::fmt::Display for StepInterruption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TimedOut => write!(f, "step exceeded constraints.timeout_seconds"),
+            Self::Cancelled => write!(f, "run was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for StepInterruption {}
+
+/// Limits on one step's provider calls: the deadline from `constraints.timeout_seconds`,
+/// counted from the first call, and the run's cancellation token.
+struct StepBudget<'c> {
+    deadline: Option<Instant>,
+    cancellation: &'c CancellationToken,
+}
+
+impl<'c> StepBudget<'c> {
+    fn new(constraints: &StepConstraints, cancellation: &'c CancellationToken) -> Self {
+        Self {
+            deadline: constraints
+                .timeout_seconds
+                .map(|seconds| Instant::now() + Duration::from_secs(seconds)),
+            cancellation,
+        }
+    }
+
+    /// Whether a provider call could be interrupted: the step has a deadline, or the run's
+    /// token is already cancelled or held elsewhere.
+    fn can_interrupt(&self) -> bool {
+        self.deadline.is_some() || self.cancellation.is_cancelled() || self.cancellation.is_shared()
+    }
+
+    fn interruption(&self) -> Option<StepInterruption> {
+        if self.cancellation.is_cancelled() {
+            Some(StepInterruption::Cancelled)
+        } else if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            Some(StepInterruption::TimedOut)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProviderFailureClass {
+    /// Transport failures and HTTP 408/429/5xx responses; the next fallback binding is tried.
+    Retryable,
+    /// Configuration, adapter, and other client errors; fallbacks are not tried.
+    Fatal,
+}
+
+impl ProviderFailureClass {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Retryable => "retryable",
+            Self::Fatal => "fatal",
+        }
+    }
+}
+
+/// Classify a provider outcome; `None` means the call succeeded.
+fn classify_provider_failure(outcome: &Result<ProviderInvocation>) -> Option<ProviderFailureClass> {
+    match outcome {
+        Ok(invocation) if invocation.provider_call.status == "succeeded" => None,
+        Ok(invocation) => {
+            let status_code = invocation
+                .provider_call
+                .response_json
+                .get("status_code")
+                .and_then(Value::as_u64);
+            if status_code.is_some_and(|code| code == 408 || code == 429 || code >= 500) {
+                Some(ProviderFailureClass::Retryable)
+            } else {
+                Some(ProviderFailureClass::Fatal)
+            }
+        }
+        Err(err) if err.downcast_ref::<ProviderTransportError>().is_some() => {
+            Some(ProviderFailureClass::Retryable)
+        }
+        Err(_) => Some(ProviderFailureClass::Fatal),
+    }
+}
+
+/// Provider call record for an attempt that failed before the adapter produced one.
+fn failed_provider_call(request: &StepRequest, err: &anyhow::Error) -> Result<ProviderCallRecord> {
+    let request_json = json!({
+        "provider_name": request.agent.provider.provider_name,
+        "model_id": request.agent.provider.model_id,
+        "step_key": request.step_key,
+        "input_hash": request.input_hash,
+    });
+    let response_json = json!({ "error": err.to_string() });
+    let now = now_utc();
+    Ok(ProviderCallRecord {
+        provider_call_id: Ulid::new(),
+        provider_name: request.agent.provider.provider_name.clone(),
+        adapter_version: "unavailable".to_string(),
+        model_id: request.agent.provider.model_id.clone(),
+        request_hash: hash_json(&request_json)?,
+        request_json,
+        response_hash: hash_json(&response_json)?,
+        response_json,
+        latency_ms: None,
+        input_tokens: None,
+        output_tokens: None,
+        started_at: now,
+        ended_at: now,
+        status: "failed".to_string(),
+        error_text: Some(err.to_string()),
+        persona_json: request.persona.as_ref().map(AppliedPersona::trace_json),
+    })
+}
+
+/// Result of a step a human or elevated gate rejected.
+fn rejected_step_result(run_id: RunId, step_id: StepId) -> StepResult {
+    StepResult {
+        run_id,
+        step_id,
+        status: StepStatus::Rejected,
+        outputs: multi_agent_center_domain::StepOutputEnvelope {
+            message: "step rejected by human gate".to_string(),
+            payload: json!({"rejected": true}),
+        },
+        proposed_memory_writes: Vec::new(),
+        provider_calls: Vec::new(),
+        gate_decisions: Vec::new(),
+        output_hash: String::new(),
+        error: None,
+        checkpoint_hashes: Vec::new(),
+        score: None,
+    }
+}
+
+fn build_step_result_from_provider(
+    run_id: RunId,
+    step_id: StepId,
+    invocation: ProviderInvocation,
+) -> StepResult {
+    StepResult {
+        run_id,
+        step_id,
+        status: StepStatus::Succeeded,
+        outputs: invocation.output,
+        proposed_memory_writes: invocation.proposed_memory_writes,
+        provider_calls: vec![invocation.provider_call],
+        gate_decisions: Vec::new(),
+        output_hash: String::new(),
+        error: None,
+        checkpoint_hashes: Vec::new(),
+        score: None,
+    }
+}
+
+const DELEGATED_DECIDER: &str = "policy.delegated";
+const NON_INTERACTIVE_DECIDER: &str = "system.non_interactive";
+
+/// The first `auto_approve` rule of `gate` that `request` satisfies, with the evidence recorded
+/// alongside the delegated decision.
+fn match_delegated_approval<'g>(
+    gate: &'g GatePointDefinition,
+    request: &StepRequest,
+) -> Result<Option<(&'g DelegatedApprovalRule, Value)>> {
+    if gate.gate_kind != GateKind::Human || gate.auto_approve.is_empty() {
+        return Ok(None);
+    }
+
+    let trust_statuses: BTreeSet<&str> = request
+        .trust_gate_attachments
+        .iter()
+        .filter(|attachment| attachment.include)
+        .map(|attachment| attachment.trust_status.as_str())
+        .collect();
+    let estimated_cost_tokens = estimate_step_cost_tokens(request)?;
+    let context_items: usize = request
+        .injected_context_packages
+        .iter()
+        .map(|package| package.context_package.selected_items.len())
+        .sum();
+
+    let matched = gate.auto_approve.iter().find(|rule| {
+        let statuses_ok = rule.trust_statuses.is_empty()
+            || trust_statuses
+                .iter()
+                .all(|status| rule.trust_statuses.iter().any(|allowed| allowed == status));
+        statuses_ok
+            && rule
+                .max_estimated_cost_tokens
+                .map_or(true, |max| estimated_cost_tokens <= max)
+            && rule
+                .max_context_items
+                .map_or(true, |max| context_items <= max)
+    });
+    let Some(rule) = matched else {
+        return Ok(None);
+    };
+
+    let evidence = json!({
+        "rule": rule,
+        "observed": {
+            "trust_statuses": trust_statuses,
+            "estimated_cost_tokens": estimated_cost_tokens,
+            "context_items": context_items,
+        },
+    });
+    Ok(Some((rule, evidence)))
+}
+
+/// Rough token cost of a step: serialized task and context at four bytes per token, plus the
+/// step's `max_output_tokens` budget.
+fn estimate_step_cost_tokens(request: &StepRequest) -> Result<u64> {
+    let prompt_bytes = serde_json::to_vec(&request.task_payload)?.len()
+        + serde_json::to_vec(&request.injected_context_packages)?.len();
+    let prompt_tokens = u64::try_from(prompt_bytes.div_ceil(4)).unwrap_or(u64::MAX);
+    Ok(prompt_tokens.saturating_add(u64::from(
+        request.constraints.max_output_tokens.unwrap_or(0),
+    )))
+}
+
+/// The `task.turns` of a conversation step, or `None` for a single-call step.
+fn conversation_turns(
+    step: &multi_agent_center_domain::WorkflowStepDefinition,
+) -> Result<Option<&[Value]>> {
+    let Some(raw) = step.task.get("turns") else {
+        return Ok(None);
+    };
+    let turns = raw
+        .as_array()
+        .ok_or_else(|| anyhow!("task.turns must be an array"))?;
+    if turns.is_empty() {
+        return Err(anyhow!("task.turns must not be empty"));
+    }
+    Ok(Some(turns.as_slice()))
+}
+
+fn missing_dependency_result(run_id: RunId, step_id: StepId, missing: &[Value]) -> StepResult {
+    StepResult {
+        run_id,
+        step_id,
+        status: StepStatus::Failed,
+        outputs: multi_agent_center_domain::StepOutputEnvelope {
+            message: "declared memory dependencies missing from context".to_string(),
+            payload: json!({"failed": true, "missing_dependencies": missing}),
+        },
+        proposed_memory_writes: Vec::new(),
+        provider_calls: Vec::new(),
+        gate_decisions: Vec::new(),
+        output_hash: String::new(),
+        error: Some(multi_agent_center_domain::ErrorEnvelope {
+            code: "memory_dependency_missing".to_string(),
+            message: format!(
+                "{} declared memory dependency(ies) not injected",
+                missing.len()
+            ),
+        }),
+        checkpoint_hashes: Vec::new(),
+        score: None,
+    }
+}
+
+/// `(step_key, value)` of the recorded events of `event_types`, in event order, for the events
+/// whose payload `value_of` reads.
+fn recorded_step_events<T, C: FromIterator<(String, T)>>(
+    events: &[EventRow],
+    event_types: &[TraceEventType],
+    value_of: impl Fn(&TraceEventType, &Value) -> Option<T>,
+) -> C {
+    events
+        .iter()
+        .filter(|row| event_types.contains(&row.event.event_type))
+        .filter_map(|row| {
+            let payload = &row.event.payload_json;
+            Some((
+                payload.get("step_key")?.as_str()?.to_string(),
+                value_of(&row.event.event_type, payload)?,
+            ))
+        })
+        .collect()
+}
+
+/// Compare the replay `replay_run_id` with its source run `source_run_id` and, recursively, each
+/// nested run with the nested run replaying it, pushing every mismatch with its step key
+/// prefixed by `prefix`. Returns the number of provider calls the replay (nested runs included)
+/// made.
+fn replay_divergences(
+    trace_store: &dyn TraceStore,
+    source_run_id: RunId,
+    replay_run_id: RunId,
+    prefix: &str,
+    divergences: &mut Vec<GoldenDifference>,
+) -> Result<usize> {
+    let prefixed = |step_key: &str| format!("{prefix}{step_key}");
+    divergences.extend(
+        diff_golden_runs(
+            &record_golden_run(trace_store, source_run_id)?,
+            &record_golden_run(trace_store, replay_run_id)?,
+        )
+        .into_iter()
+        .map(|difference| GoldenDifference {
+            step_key: prefixed(&difference.step_key),
+            ..difference
+        }),
+    );
+    let recorded = provider_request_hashes(trace_store, source_run_id)?;
+    let replayed = provider_request_hashes(trace_store, replay_run_id)?;
+    let step_keys: BTreeSet<&String> = recorded.keys().chain(replayed.keys()).collect();
+    for step_key in step_keys {
+        let expected = recorded.get(step_key).map_or(&[][..], Vec::as_slice);
+        let actual = replayed.get(step_key).map_or(&[][..], Vec::as_slice);
+        if expected.len() != actual.len() {
+            divergences.push(GoldenDifference {
+                step_key: prefixed(step_key),
+                field: "provider_calls".to_string(),
+                expected: json!(expected.len()),
+                actual: json!(actual.len()),
+            });
+        }
+        for (index, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+            if expected != actual {
+                divergences.push(GoldenDifference {
+                    step_key: prefixed(step_key),
+                    field: format!("provider_calls[{index}].request_hash"),
+                    expected: json!(expected),
+                    actual: json!(actual),
+                });
+            }
+        }
+    }
+    let mut provider_calls = replayed.values().map(Vec::len).sum();
+
+    let child_runs = |run_id: RunId| -> Result<BTreeMap<String, RunId>> {
+        Ok(trace_store
+            .list_child_runs(run_id)?
+            .into_iter()
+            .filter_map(|run| Some((run.parent_step?.step_key, run.run_id)))
+            .collect())
+    };
+    let recorded = child_runs(source_run_id)?;
+    let mut replayed = child_runs(replay_run_id)?;
+    for (step_key, source_child) in recorded {
+        let Some(replay_child) = replayed.remove(&step_key) else {
+            divergences.push(GoldenDifference {
+                step_key: prefixed(&step_key),
+                field: "child_run".to_string(),
+                expected: json!("present"),
+                actual: json!("missing"),
+            });
+            continue;
+        };
+        provider_calls += replay_divergences(
+            trace_store,
+            source_child,
+            replay_child,
+            &format!("{}/", prefixed(&step_key)),
+            divergences,
+        )?;
+    }
+    for step_key in replayed.into_keys() {
+        divergences.push(GoldenDifference {
+            step_key: prefixed(&step_key),
+            field: "child_run".to_string(),
+            expected: json!("missing"),
+            actual: json!("present"),
+        });
+    }
+    Ok(provider_calls)
+}
+
+/// Run-independent request hashes of a run's provider calls, by step key in call order.
+fn provider_request_hashes(
+    trace_store: &dyn TraceStore,
+    run_id: RunId,
+) -> Result<BTreeMap<String, Vec<String>>> {
+    let mut hashes: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for row in trace_store.get_step_provider_calls(run_id)? {
+        hashes
+            .entry(row.step_key)
+            .or_default()
+            .push(compute_provider_request_replay_hash(
+                &row.provider_call.request_json,
+            )?);
+    }
+    Ok(hashes)
+}
+
+/// Recorded `foreach` expansions, iterations, and vote steps, in event order.
+fn recorded_expansions(events: &[EventRow]) -> Vec<(String, StepExpansion)> {
+    recorded_step_events(
+        events,
+        &[
+            TraceEventType::ForeachExpanded,
+            TraceEventType::IterationStarted,
+            TraceEventType::VoteExpanded,
+        ],
+        |event_type, payload| match event_type {
+            TraceEventType::ForeachExpanded => Some(StepExpansion::Foreach(
+                payload.get("items")?.as_array().cloned(),
+            )),
+            TraceEventType::VoteExpanded => Some(StepExpansion::Vote {
+                judge_task: payload.get("judge_task").cloned(),
+            }),
+            _ => Some(StepExpansion::Iteration {
+                iteration: u32::try_from(payload.get("iteration")?.as_u64()?).ok()?,
+                task: payload.get("task")?.clone(),
+            }),
+        },
+    )
+}
+
+/// Step row of the `foreach`, `iterate`, `vote`, or `workflow_call` step at `idx`, running and
+/// keyed by its definition and the step keys of its `children`.
+fn aggregate_step_record(
+    run_id: RunId,
+    step_id: StepId,
+    idx: usize,
+    steps: &[WorkflowStepDefinition],
+    children: Option<&[usize]>,
+) -> Result<StepRecord> {
+    let step = &steps[idx];
+    let mut definition = match (&step.iterate, &step.workflow_call, &step.vote) {
+        (Some(iterate), _, _) => json!({"iterate": iterate}),
+        (None, Some(call), _) => json!({"workflow_call": call}),
+        (None, None, Some(vote)) => json!({"vote": vote}),
+        (None, None, None) => json!({"foreach": step.foreach}),
+    };
+    let child_keys: Vec<&str> = children
+        .unwrap_or_default()
+        .iter()
+        .map(|child| steps[*child].step_key.as_str())
+        .collect();
+    definition["children"] = json!(child_keys);
+    Ok(StepRecord {
+        step_id,
+        run_id,
+        step_index: idx,
+        step_key: step.step_key.clone(),
+        agent_name: step.agent_name.clone(),
+        status: StepStatus::Running,
+        started_at: Some(now_utc()),
+        ended_at: None,
+        task_payload_json: step.task.clone(),
+        constraints_json: serde_json::to_value(&step.constraints)?,
+        permissions_json: Value::Object(Map::default()),
+        input_hash: hash_json(&definition)?,
+        output_hash: None,
+        error_json: None,
+        retry_json: None,
+        prompt_template_hash: None,
+        provider_selection_json: None,
+        taint_json: None,
+        memoized_from_run: None,
+        memo_key: None,
+    })
+}
+
+fn foreach_result(
+    run_id: RunId,
+    step_id: StepId,
+    step: &WorkflowStepDefinition,
+    children: Option<&[usize]>,
+    statuses: &[StepStatus],
+    child_outputs: &BTreeMap<usize, StepOutputEnvelope>,
+) -> StepResult {
+    let (status, outputs, error) = match children {
+        None => (
+            StepStatus::Failed,
+            multi_agent_center_domain::StepOutputEnvelope {
+                message: "foreach source has no items".to_string(),
+                payload: json!({"failed": true, "foreach": step.foreach}),
+            },
+            Some(multi_agent_center_domain::ErrorEnvelope {
+                code: "foreach_items_not_array".to_string(),
+                message: format!(
+                    "foreach source {} did not resolve to an array",
+                    step.foreach.as_deref().unwrap_or_default()
+                ),
+            }),
+        ),
+        Some(children) => {
+            // Cancelled outranks rejected, which outranks failed and timed out, then skipped.
+            let rank = |status: &StepStatus| match status {
+                StepStatus::Cancelled => 4,
+                StepStatus::Rejected => 3,
+                StepStatus::Failed | StepStatus::TimedOut => 2,
+                StepStatus::Skipped => 1,
+                _ => 0,
+            };
+            let status = match children
+                .iter()
+                .map(|child| &statuses[*child])
+                .max_by_key(|status| rank(status))
+            {
+                None | Some(StepStatus::Succeeded) => StepStatus::Succeeded,
+                Some(StepStatus::TimedOut) => StepStatus::Failed,
+                Some(status) => status.clone(),
+            };
+            let unsucceeded = children
+                .iter()
+                .filter(|child| statuses[**child] != StepStatus::Succeeded)
+                .count();
+            let items: Vec<Value> = children
+                .iter()
+                .map(|child| {
+                    child_outputs
+                        .get(child)
+                        .map_or(Value::Null, |outputs| outputs.payload.clone())
+                })
+                .collect();
+            let error = (unsucceeded > 0).then(|| multi_agent_center_domain::ErrorEnvelope {
+                code: "foreach_item_not_succeeded".to_string(),
+                message: format!(
+                    "{unsucceeded} of {} foreach item(s) did not succeed",
+                    children.len()
+                ),
+            });
+            (
+                status,
+                multi_agent_center_domain::StepOutputEnvelope {
+                    message: format!("aggregated {} foreach item(s)", children.len()),
+                    payload: json!({"items": items}),
+                },
+                error,
+            )
+        }
+    };
+    StepResult {
+        run_id,
+        step_id,
+        status,
+        outputs,
+        proposed_memory_writes: Vec::new(),
+        provider_calls: Vec::new(),
+        gate_decisions: Vec::new(),
+        output_hash: String::new(),
+        error,
+        checkpoint_hashes: Vec::new(),
+        score: None,
+    }
+}
+
+/// Result of an `iterate` step from its iterations: the output of the last one when it
+/// converged, `iteration_not_converged` when it succeeded without converging, and otherwise the
+/// last iteration's status with `iteration_not_succeeded`.
+fn iterate_result(
+    run_id: RunId,
+    step_id: StepId,
+    iterate: &StepIteration,
+    children: &[usize],
+    statuses: &[StepStatus],
+    child_outputs: &BTreeMap<usize, StepOutputEnvelope>,
+    converged: bool,
+) -> StepResult {
+    let ran = children.len();
+    let last = children
+        .last()
+        .map(|child| (&statuses[*child], child_outputs.get(child)));
+    let (status, outputs, error) = match last {
+        Some((StepStatus::Succeeded, Some(outputs))) if converged => {
+            (StepStatus::Succeeded, outputs.clone(), None)
+        }
+        Some((StepStatus::Succeeded, _)) => (
+            StepStatus::Failed,
+            multi_agent_center_domain::StepOutputEnvelope {
+                message: "iteration did not converge".to_string(),
+                payload: json!({"failed": true, "iterations": ran}),
+            },
+            Some(multi_agent_center_domain::ErrorEnvelope {
+                code: "iteration_not_converged".to_string(),
+                message: format!(
+                    "until expression {} did not hold after {ran} of {} iteration(s)",
+                    iterate.until, iterate.max_iterations
+                ),
+            }),
+        ),
+        status => (
+            match status {
+                None | Some((StepStatus::TimedOut, _)) => StepStatus::Failed,
+                Some((status, _)) => status.clone(),
+            },
+            multi_agent_center_domain::StepOutputEnvelope {
+                message: "iteration did not succeed".to_string(),
+                payload: json!({"failed": true, "iterations": ran}),
+            },
+            Some(multi_agent_center_domain::ErrorEnvelope {
+                code: "iteration_not_succeeded".to_string(),
+                message: format!("iteration {ran} did not succeed"),
+            }),
+        ),
+    };
+    StepResult {
+        run_id,
+        step_id,
+        status,
+        outputs,
+        proposed_memory_writes: Vec::new(),
+        provider_calls: Vec::new(),
+        gate_decisions: Vec::new(),
+        output_hash: String::new(),
+        error,
+        checkpoint_hashes: Vec::new(),
+        score: None,
+    }
+}
+
+/// Result of a `workflow_call` step from its nested run: succeeded, rejected, and cancelled
+/// runs carry over, and any other status fails the step with `workflow_call_not_succeeded`.
+/// Content hash of a `workflow_call` step's result without the nested run's id, which differs
+/// between a run and its replay.
+fn workflow_call_content_hash(result: &StepResult) -> Result<String> {
+    let mut content = result.clone();
+    if let Some(child_run_id) = result.outputs.payload["child_run_id"].as_str() {
+        content.outputs.payload["child_run_id"] = Value::Null;
+        content.outputs.message = content.outputs.message.replace(child_run_id, "");
+        if let Some(error) = content.error.as_mut() {
+            error.message = error.message.replace(child_run_id, "");
+        }
+    }
+    compute_step_content_hash(&content)
+}
+
+fn workflow_call_result(
+    run_id: RunId,
+    step_id: StepId,
+    summary: &RunExecutionSummary,
+) -> StepResult {
+    let child_status = run_status_to_text(&summary.status);
+    let status = match summary.status {
+        RunStatus::Succeeded => StepStatus::Succeeded,
+        RunStatus::Rejected => StepStatus::Rejected,
+        RunStatus::Cancelled => StepStatus::Cancelled,
+        _ => StepStatus::Failed,
+    };
+    let mut payload = json!({
+        "child_run_id": summary.run_id,
+        "status": child_status,
+        "steps_total": summary.steps_total,
+        "steps_succeeded": summary.steps_succeeded,
+        "steps_failed_or_rejected": summary.steps_failed_or_rejected,
+    });
+    let error = (status != StepStatus::Succeeded).then(|| {
+        payload["failed"] = json!(true);
+        multi_agent_center_domain::ErrorEnvelope {
+            code: "workflow_call_not_succeeded".to_string(),
+            message: format!("nested run {} finished {child_status}", summary.run_id),
+        }
+    });
+    StepResult {
+        run_id,
+        step_id,
+        status,
+        outputs: multi_agent_center_domain::StepOutputEnvelope {
+            message: format!("nested run {} finished {child_status}", summary.run_id),
+            payload,
+        },
+        proposed_memory_writes: Vec::new(),
+        provider_calls: Vec::new(),
+        gate_decisions: Vec::new(),
+        output_hash: String::new(),
+        error,
+        checkpoint_hashes: Vec::new(),
+        score: None,
+    }
+}
+
+fn deferred_gate_result(run_id: RunId, step_id: StepId, gates: &[String]) -> StepResult {
+    StepResult {
+        run_id,
+        step_id,
+        status: StepStatus::Deferred,
+        outputs: multi_agent_center_domain::StepOutputEnvelope {
+            message: "step held by deferred human gate".to_string(),
+            payload: json!({"deferred": true, "deferred_gates": gates}),
+        },
+        proposed_memory_writes: Vec::new(),
+        provider_calls: Vec::new(),
+        gate_decisions: Vec::new(),
+        output_hash: String::new(),
+        error: Some(multi_agent_center_domain::ErrorEnvelope {
+            code: "gate_deferred".to_string(),
+            message: format!("{} required human gate(s) deferred", gates.len()),
+        }),
+        checkpoint_hashes: Vec::new(),
+        score: None,
+    }
+}
+
+fn permission_prune_result(
+    run_id: RunId,
+    step_id: StepId,
+    pruned_references: &[PrunedReference],
+) -> StepResult {
+    StepResult {
+        run_id,
+        step_id,
+        status: StepStatus::Failed,
+        outputs: multi_agent_center_domain::StepOutputEnvelope {
+            message: "context pruned by permissions with fail_on_permission_prune".to_string(),
+            payload: json!({"failed": true, "pruned_items": pruned_references}),
+        },
+        proposed_memory_writes: Vec::new(),
+        provider_calls: Vec::new(),
+        gate_decisions: Vec::new(),
+        output_hash: String::new(),
+        error: Some(multi_agent_center_domain::ErrorEnvelope {
+            code: "permission_pruned".to_string(),
+            message: format!(
+                "{} context item(s) pruned by agent permissions",
+                pruned_references.len()
+            ),
+        }),
+        checkpoint_hashes: Vec::new(),
+        score: None,
+    }
+}
+
+/// Engine build, store crate versions, sqlite, OS, and the run features enabled by `config`.
+fn environment_fingerprint(config: &RunConfig) -> Result<EnvironmentFingerprint> {
+    let features = [
+        ("apply_proposed_writes", config.apply_proposed_writes),
+        ("memoize_steps", config.memoize_steps),
+        ("non_interactive", config.non_interactive),
+        (
+            "relaxed_trace_durability",
+            !config.relaxed_trace_artifacts.is_empty(),
+        ),
+        ("require_trust_gating", config.require_trust_gating),
+        ("reuse_correlated_run", config.reuse_correlated_run),
+        ("speculative_execution", config.speculative_execution),
+    ];
+    EnvironmentFingerprint {
+        engine_version: config.engine_version.clone(),
+        build_profile: if cfg!(debug_assertions) {
+            "debug"
+        } else {
+            "release"
+        }
+        .to_string(),
+        crate_versions: BTreeMap::from([
+            (
+                "memory-kernel-outcome-store-sqlite".to_string(),
+                memory_kernel_outcome_store_sqlite::CRATE_VERSION.to_string(),
+            ),
+            (
+                "memory-kernel-store-sqlite".to_string(),
+                memory_kernel_store_sqlite::CRATE_VERSION.to_string(),
+            ),
+            (
+                "multi-agent-center-orchestrator".to_string(),
+                env!("CARGO_PKG_VERSION").to_string(),
+            ),
+        ]),
+        sqlite_version: rusqlite::version().to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        features: features
+            .into_iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(name, _)| name.to_string())
+            .collect(),
+        hash: String::new(),
+    }
+    .sealed()
+}
+
+fn injected_memory_digest(
+    step_index: usize,
+    step: &WorkflowStepDefinition,
+    request: &StepRequest,
+) -> StepMemoryDigest {
+    let ids: BTreeSet<MemoryVersionId> = request
+        .injected_context_packages
+        .iter()
+        .flat_map(|package| package.context_package.selected_items.iter())
+        .map(|item| item.memory_version_id)
+        .collect();
+    StepMemoryDigest {
+        step_index,
+        step_key: step.step_key.clone(),
+        memory_versions: ids.len(),
+        digest: memory_version_set_digest(ids),
+    }
+}
+
+fn apply_trust_filter(
+    packages: &[ContextPackageEnvelope],
+    trust_map: &BTreeMap<(String, u32), bool>,
+) -> Result<Vec<ContextPackageEnvelope>> {
+    let mut out = Vec::with_capacity(packages.len());
+
+    for package in packages {
+        let mut package_copy = package.clone();
+        package_copy.context_package.selected_items.retain(|item| {
+            trust_map
+                .get(&(item.memory_id.to_string(), item.version))
+                .copied()
+                .unwrap_or(true)
+        });
+        package_copy.package_hash =
+            hash_json(&serde_json::to_value(&package_copy.context_package)?)?;
+        out.push(package_copy);
+    }
+
+    Ok(out)
+}
+
+/// Sizing of a [`benchmark_trust_gate`] run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrustGateBenchmarkConfig {
+    pub trust_rows: usize,
+    pub candidates: usize,
+    pub iterations: usize,
+    pub mode: RetrievalMode,
+}
+
+impl TrustGateBenchmarkConfig {
+    /// Build a config from host flags; `mode` is `safe` or `exploration`.
+    ///
+    /// # Errors
+    /// Returns an error for an unknown mode.
+    pub fn from_flags(
+        trust_rows: usize,
+        candidates: usize,
+        iterations: usize,
+        mode: &str,
+    ) -> Result<Self> {
+        let mode = RetrievalMode::parse(mode)
+            .ok_or_else(|| anyhow!("invalid trust_mode '{mode}'; use 'safe' or 'exploration'"))?;
+        Ok(Self {
+            trust_rows,
+            candidates,
+            iterations,
+            mode,
+        })
+    }
+}
+
+/// Measure the per-step trust gating path: seed a temporary `OutcomeMemory` database with
+/// `trust_rows` projected trust rows, then gate a context package of `candidates` items through
+/// [`OutcomeMemoryTrustGateSource`] and the package filter `iterations` times.
+///
+/// # Errors
+/// Returns an error for zero-sized configs or when seeding or gating fails.
+pub fn benchmark_trust_gate(config: &TrustGateBenchmarkConfig) -> Result<TrustGateBenchmarkReport> {
+    if config.trust_rows == 0 || config.candidates == 0 || config.iterations == 0 {
+        return Err(anyhow!(
+            "trust gate benchmark trust_rows, candidates, and iterations must be >= 1"
+        ));
+    }
+
+    let db_path =
+        std::env::temp_dir().join(format!("mac-trust-gate-bench-{}.sqlite3", Ulid::new()));
+    let result = benchmark_trust_gate_at(&db_path, config);
+    let _ = std::fs::remove_file(&db_path);
+    result
+}
+
+fn benchmark_trust_gate_at(
+    db_path: &Path,
+    config: &TrustGateBenchmarkConfig,
+) -> Result<TrustGateBenchmarkReport> {
+    let keys = seed_benchmark_trust_rows(db_path, config.trust_rows)?;
+    let package = benchmark_context_package(keys.iter().copied().cycle().take(config.candidates))?;
+    let packages = vec![package];
+    let refs: Vec<ContextRef> = packages[0]
+        .context_package
+        .selected_items
+        .iter()
+        .map(|item| ContextRef {
+            memory_id: item.memory_id,
+            version: item.version,
+            memory_version_id: item.memory_version_id,
+        })
+        .collect();
+
+    let source = OutcomeMemoryTrustGateSource::new(db_path, config.mode);
+    let run_id = RunId::new();
+    let step_id = StepId::new();
+    let as_of = now_utc();
+    let mut evaluate_ms = Vec::with_capacity(config.iterations);
+    let mut filter_ms = Vec::with_capacity(config.iterations);
+    let mut step_ms = Vec::with_capacity(config.iterations);
+    let mut attachments = Vec::new();
+    let mut gated = Vec::new();
+    for _ in 0..config.iterations {
+        let start = std::time::Instant::now();
+        attachments = source.evaluate(run_id, step_id, "bench", as_of, &refs)?;
+        let evaluated = start.elapsed();
+        let trust_map: BTreeMap<(String, u32), bool> = attachments
+            .iter()
+            .map(|item| ((item.memory_id.to_string(), item.version), item.include))
+            .collect();
+        gated = apply_trust_filter(&packages, &trust_map)?;
+        let total = start.elapsed();
+        evaluate_ms.push(evaluated.as_secs_f64() * 1_000.0);
+        filter_ms.push(total.saturating_sub(evaluated).as_secs_f64() * 1_000.0);
+        step_ms.push(total.as_secs_f64() * 1_000.0);
+    }
+
+    let included = attachments.iter().filter(|item| item.include).count();
+    let evaluate_p95_ms = benchmark_percentile(&evaluate_ms, 0.95);
+    #[allow(clippy::cast_precision_loss)]
+    let per_candidate_p95_us = evaluate_p95_ms * 1_000.0 / refs.len() as f64;
+    Ok(TrustGateBenchmarkReport {
+        trust_rows: config.trust_rows,
+        candidates: refs.len(),
+        iterations: config.iterations,
+        mode: match config.mode {
+            RetrievalMode::Safe => "safe",
+            RetrievalMode::Exploration => "exploration",
+        }
+        .to_string(),
+        evaluate_p50_ms: benchmark_percentile(&evaluate_ms, 0.50),
+        evaluate_p95_ms,
+        evaluate_p99_ms: benchmark_percentile(&evaluate_ms, 0.99),
+        evaluate_max_ms: benchmark_percentile(&evaluate_ms, 1.0),
+        filter_p50_ms: benchmark_percentile(&filter_ms, 0.50),
+        filter_p95_ms: benchmark_percentile(&filter_ms, 0.95),
+        step_p95_ms: benchmark_percentile(&step_ms, 0.95),
+        per_candidate_p95_us,
+        included,
+        excluded: attachments.len() - included,
+        package_bytes_before: serde_json::to_vec(&packages)?.len(),
+        package_bytes_after: serde_json::to_vec(&gated)?.len(),
+        attachment_bytes: serde_json::to_vec(&attachments)?.len(),
+    })
+}
+
+fn benchmark_context_package(
+    keys: impl Iterator<Item = MemoryKey>,
+) -> Result<ContextPackageEnvelope> {
+    let now = now_utc();
+    let selected_items = keys
+        .enumerate()
+        .map(|(index, key)| memory_kernel_core::ContextItem {
+            rank: index + 1,
+            memory_version_id: memory_kernel_core::MemoryVersionId::new(),
+            memory_id: key.memory_id,
+            record_type: RecordType::Constraint,
+            version: key.version,
+            truth_status: memory_kernel_core::TruthStatus::Asserted,
+            confidence: Some(0.9),
+            authority: memory_kernel_core::Authority::Authoritative,
+            tags: Vec::new(),
+            why: memory_kernel_core::Why {
+                included: true,
+                reasons: vec!["benchmark".to_string()],
+                rule_scores: None,
+            },
+        })
+        .collect();
+    let context_package = memory_kernel_core::ContextPackage {
+        context_package_id: "trust-gate-benchmark".to_string(),
+        generated_at: now,
+        query: QueryRequest {
+            text: "benchmark".to_string(),
+            actor: "benchmark".to_string(),
+            action: "benchmark".to_string(),
+            resource: "benchmark".to_string(),
+            as_of: now,
+        },
+        determinism: memory_kernel_core::DeterminismMetadata {
+            ruleset_version: "benchmark".to_string(),
+            snapshot_id: "benchmark".to_string(),
+            tie_breakers: Vec::new(),
+        },
+        answer: memory_kernel_core::Answer {
+            result: memory_kernel_core::AnswerResult::Allow,
+            why: "benchmark".to_string(),
+        },
+        selected_items,
+        excluded_items: Vec::new(),
+        ordering_trace: Vec::new(),
+    };
+    Ok(ContextPackageEnvelope {
+        package_slot: 0,
+        source: "benchmark".to_string(),
+        package_hash: hash_json(&serde_json::to_value(&context_package)?)?,
+        context_package,
+    })
+}
+
+/// Nearest-rank percentile of `values`; `rank` is in `[0, 1]`.
+fn benchmark_percentile(values: &[f64], rank: f64) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    let index = ((sorted.len() as f64 * rank).ceil() as usize).clamp(1, sorted.len()) - 1;
+    sorted[index]
+}
+
+/// Build a step's context packages, prune them by permissions, and trust-gate what remains.
+fn gate_step_context(
+    context_source: &dyn ContextPackageSource,
+    trust_source: &dyn TrustGateSource,
+    run_id: RunId,
+    step_id: StepId,
+    step: &WorkflowStepDefinition,
+    effective_permissions: &EffectivePermissions,
+    as_of: time::OffsetDateTime,
+) -> Result<GatedStepContext> {
+    let packages = context_source
+        .packages_for_step(run_id, step, as_of)
+        .with_context(|| {
+            format!(
+                "failed to obtain context packages for step {}",
+                step.step_key
+            )
+        })?;
+
+    let PermissionPruneResult {
+        packages: permission_packages,
+        pruned_references,
+    } = apply_context_permissions(&packages, effective_permissions)?;
+
+    let refs: Vec<ContextRef> = permission_packages
+        .iter()
+        .flat_map(|package| {
+            package
+                .context_package
+                .selected_items
+                .iter()
+                .map(|item| ContextRef {
+                    memory_id: item.memory_id,
+                    version: item.version,
+                    memory_version_id: item.memory_version_id,
+                })
+        })
+        .collect();
+
+    let trust_attachments = trust_source.evaluate(run_id, step_id, &step.step_key, as_of, &refs)?;
+
+    let trust_map: BTreeMap<(String, u32), bool> = trust_attachments
+        .iter()
+        .map(|item| ((item.memory_id.to_string(), item.version), item.include))
+        .collect();
+    let gated_packages = apply_trust_filter(&permission_packages, &trust_map)?;
+
+    Ok(GatedStepContext {
+        packages,
+        pruned_references,
+        refs,
+        trust_attachments,
+        gated_packages,
+    })
+}
+
+/// Deterministic summary of trust gating for embedding in a step request: counts, the excluded
+/// memory versions in id order, and exclusion reason categories (the first two segments of each
+/// reason code) with counts.
+fn trust_gate_summary(attachments: &[TrustGateAttachment]) -> Value {
+    let mut excluded: Vec<&TrustGateAttachment> =
+        attachments.iter().filter(|item| !item.include).collect();
+    excluded.sort_by(|a, b| {
+        (a.memory_id.to_string(), a.version).cmp(&(b.memory_id.to_string(), b.version))
+    });
+
+    let mut categories: BTreeMap<String, usize> = BTreeMap::new();
+    let excluded_memories: Vec<Value> = excluded
+        .iter()
+        .map(|item| {
+            let mut reasons: BTreeSet<String> = BTreeSet::new();
+            for code in &item.reason_codes {
+                let category = code.splitn(3, '.').take(2).collect::<Vec<_>>().join(".");
+                reasons.insert(category);
+            }
+            for category in &reasons {
+                *categories.entry(category.clone()).or_default() += 1;
+            }
+            json!({
+                "memory_id": item.memory_id.to_string(),
+                "version": item.version,
+                "trust_status": item.trust_status,
+                "reason_categories": reasons,
+            })
+        })
+        .collect();
+
+    json!({
+        "evaluated": attachments.len(),
+        "included": attachments.len() - excluded.len(),
+        "excluded": excluded.len(),
+        "excluded_memories": excluded_memories,
+        "exclusion_categories": categories,
+    })
+}
+
+#[derive(Debug, Default)]
+struct EventChain {
+    prev_event_hash: Option<String>,
+}
+
+struct GatedStepContext {
+    packages: Vec<ContextPackageEnvelope>,
+    pruned_references: Vec<PrunedReference>,
+    refs: Vec<ContextRef>,
+    trust_attachments: Vec<TrustGateAttachment>,
+    gated_packages: Vec<ContextPackageEnvelope>,
+}
+
+struct PreparedStepInput {
+    request: StepRequest,
+    refs: Vec<ContextRef>,
+    pruned_references: Vec<PrunedReference>,
+    trust_included: usize,
+    trust_excluded: usize,
+}
+
+fn format_rfc3339(value: time::OffsetDateTime) -> Result<String> {
+    value
+        .format(&time::format_description::well_known::Rfc3339)
+        .map_err(|err| anyhow!("invalid RFC3339 value: {err}"))
+}
+
+fn step_status_to_text(status: &StepStatus) -> &'static str {
+    match status {
+        StepStatus::Pending => "pending",
+        StepStatus::Running => "running",
+        StepStatus::Succeeded => "succeeded",
+        StepStatus::Failed => "failed",
+        StepStatus::Rejected => "rejected",
+        StepStatus::Skipped => "skipped",
+        StepStatus::Deferred => "deferred",
+        StepStatus::TimedOut => "timed_out",
+        StepStatus::Cancelled => "cancelled",
+    }
+}
+
+fn gate_decision_to_text(decision: &GateDecision) -> &'static str {
+    match decision {
+        GateDecision::Approved => "approved",
+        GateDecision::Rejected => "rejected",
+        GateDecision::Pruned => "pruned",
+    }
+}
+
+fn run_status_to_text(status: &RunStatus) -> &'static str {
+    match status {
+        RunStatus::Pending => "pending",
+        RunStatus::Running => "running",
+        RunStatus::Succeeded => "succeeded",
+        RunStatus::Failed => "failed",
+        RunStatus::Rejected => "rejected",
+        RunStatus::PartiallyCompleted => "partially_completed",
+        RunStatus::Cancelled => "cancelled",
+    }
+}
+
+enum StepContextQuery {
+    Policy(QueryRequest),
+    Recall {
+        text: String,
+        record_types: Vec<RecordType>,
+        record_tags: Vec<String>,
+    },
+}
+
+impl StepContextQuery {
+    fn cache_key_json(&self) -> Value {
+        match self {
+            Self::Policy(request) => json!({ "mode": "policy", "request": request }),
+            Self::Recall {
+                text,
+                record_types,
+                record_tags,
+            } => {
+                let mut key = json!({
+                    "mode": "recall",
+                    "text": text,
+                    "record_types": record_types,
+                });
+                if !record_tags.is_empty() {
+                    key["record_tags"] = json!(record_tags);
+                }
+                key
+            }
+        }
+    }
+}
+
+fn resolve_step_context_queries(
+    step: &multi_agent_center_domain::WorkflowStepDefinition,
+    as_of: time::OffsetDateTime,
+) -> Result<Vec<StepContextQuery>> {
+    let mut queries: Vec<StepContextQuery> = Vec::new();
+
+    if let Some(raw) = step.task.get("context_queries") {
+        let array = raw
+            .as_array()
+            .ok_or_else(|| anyhow!("task.context_queries must be an array"))?;
+        for entry in array {
+            let object = entry
+                .as_object()
+                .ok_or_else(|| anyhow!("task.context_queries entries must be objects"))?;
+            let text = object
+                .get("text")
+                .and_then(Value::as_str)
+                .unwrap_or(step.step_key.as_str())
+                .to_string();
+            let actor = object
+                .get("actor")
+                .and_then(Value::as_str)
+                .unwrap_or("*")
+                .to_string();
+            let action = object
+                .get("action")
+                .and_then(Value::as_str)
+                .unwrap_or("*")
+                .to_string();
+            let resource = object
+                .get("resource")
+                .and_then(Value::as_str)
+                .unwrap_or("*")
+                .to_string();
+            let mode = object
+                .get("mode")
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .map_or_else(|| "policy".to_string(), str::to_ascii_lowercase);
+
+            match mode.as_str() {
+                "policy" => queries.push(StepContextQuery::Policy(QueryRequest {
+                    text,
+                    actor,
+                    action,
+                    resource,
+                    as_of,
+                })),
+                "recall" => {
+                    let record_types = parse_recall_record_types(object.get("record_types"))?;
+                    let record_tags = parse_recall_record_tags(object.get("record_tags"))?;
+                    queries.push(StepContextQuery::Recall {
+                        text,
+                        record_types,
+                        record_tags,
+                    });
+                }
+                _ => {
+                    return Err(anyhow!(
+                        "task.context_queries mode must be one of: policy, recall"
+                    ));
+                }
+            }
+        }
+    } else {
+        let text = step
+            .task
+            .get("text")
+            .and_then(Value::as_str)
+            .unwrap_or(step.step_key.as_str())
+            .to_string();
+        let actor = step
+            .task
+            .get("actor")
+            .and_then(Value::as_str)
+            .unwrap_or("*")
+            .to_string();
+        let action = step
+            .task
+            .get("action")
+            .and_then(Value::as_str)
+            .unwrap_or("*")
+            .to_string();
+        let resource = step
+            .task
+            .get("resource")
+            .and_then(Value::as_str)
+            .unwrap_or("*")
+            .to_string();
+        queries.push(StepContextQuery::Policy(QueryRequest {
+            text,
+            actor,
+            action,
+            resource,
+            as_of,
+        }));
+    }
+
+    Ok(queries)
+}
+
+fn build_context_packages_from_records(
+    records: &[MemoryRecord],
+    run_id: RunId,
+    step: &multi_agent_center_domain::WorkflowStepDefinition,
+    as_of: time::OffsetDateTime,
+    source: &str,
+) -> Result<Vec<ContextPackageEnvelope>> {
+    if records.is_empty() {
+        return Ok(Vec::new());
+    }
+    let queries = resolve_step_context_queries(step, as_of)?;
+
+    let mut envelopes = Vec::with_capacity(queries.len());
+    for (package_slot, query) in queries.into_iter().enumerate() {
+        let package = match query {
+            StepContextQuery::Policy(request) => build_context_package(
+                records,
+                request,
+                &format!("{}:{}:{}", run_id, step.step_key, package_slot),
+            ),
+            StepContextQuery::Recall {
+                text,
+                record_types,
+                record_tags,
+            } => build_recall_context_package_with_tags(
+                records,
+                QueryRequest {
+                    text,
+                    actor: "*".to_string(),
+                    action: "*".to_string(),
+                    resource: "*".to_string(),
+                    as_of,
+                },
+                &format!("{}:{}:{}", run_id, step.step_key, package_slot),
+                &record_types,
+                &record_tags,
+            ),
+        }
+        .map_err(|err| anyhow!("memory kernel context package build failed: {err}"))?;
+        let package_json = serde_json::to_value(&package)?;
+        let package_hash = hash_json(&package_json)?;
+        envelopes.push(ContextPackageEnvelope {
+            package_slot,
+            source: source.to_string(),
+            context_package: package,
+            package_hash,
+        });
+    }
+
+    Ok(envelopes)
+}
+
+fn parse_recall_record_types(raw: Option<&Value>) -> Result<Vec<RecordType>> {
+    let Some(value) = raw else {
+        return Ok(default_recall_record_types());
+    };
+
+    let array = value
+        .as_array()
+        .ok_or_else(|| anyhow!("task.context_queries[].record_types must be an array"))?;
+    if array.is_empty() {
+        return Ok(default_recall_record_types());
+    }
+
+    let mut record_types = Vec::with_capacity(array.len());
+    for item in array {
+        let raw_record_type = item
+            .as_str()
+            .ok_or_else(|| anyhow!("task.context_queries[].record_types entries must be strings"))?
+            .trim()
+            .to_ascii_lowercase();
+        let record_type = RecordType::parse(&raw_record_type).ok_or_else(|| {
+            anyhow!(
+                "unsupported recall record type `{raw_record_type}`; expected one of constraint|decision|preference|event|outcome"
+            )
+        })?;
+        record_types.push(record_type);
+    }
+
+    Ok(record_types)
+}
+
+fn parse_recall_record_tags(raw: Option<&Value>) -> Result<Vec<String>> {
+    let Some(value) = raw else {
+        return Ok(Vec::new());
+    };
+
+    let array = value
+        .as_array()
+        .ok_or_else(|| anyhow!("task.context_queries[].record_tags must be an array"))?;
+    let mut record_tags = Vec::with_capacity(array.len());
+    for item in array {
+        let tag = item
+            .as_str()
+            .ok_or_else(|| anyhow!("task.context_queries[].record_tags entries must be strings"))?
+            .trim()
+            .to_string();
+        validate_memory_tag(&tag)
+            .map_err(|err| anyhow!("task.context_queries[].record_tags: {err}"))?;
+        record_tags.push(tag);
+    }
+
+    Ok(normalize_memory_tags(record_tags))
+}
+
+fn load_outcome_rulesets(conn: &rusqlite::Connection) -> Result<BTreeMap<u32, OutcomeRuleset>> {
+    let mut stmt = conn.prepare(
+        "SELECT ruleset_version, ruleset_json
+         FROM outcome_rulesets
+         ORDER BY ruleset_version ASC",
+    )?;
+    let mut rows = stmt.query([])?;
+
+    let mut out = BTreeMap::new();
+    while let Some(row) = rows.next()? {
+        let version_i64: i64 = row.get(0)?;
+        let version = u32::try_from(version_i64)
+            .map_err(|_| anyhow!("invalid outcome ruleset_version: {version_i64}"))?;
+        let ruleset_json: String = row.get(1)?;
+        let value: Value =
+            serde_json::from_str(&ruleset_json).context("invalid outcome_rulesets.ruleset_json")?;
+        let ruleset = OutcomeRuleset::from_json(&value)
+            .map_err(|err| anyhow!("invalid outcome ruleset {version}: {err}"))?;
+        out.insert(version, ruleset);
+    }
+
+    if out.is_empty() {
+        out.insert(1, OutcomeRuleset::v1());
+    }
+
+    Ok(out)
+}
+
+fn get_memory_trust_and_ruleset(
+    conn: &rusqlite::Connection,
+    memory_id: memory_kernel_core::MemoryId,
+    version: u32,
+) -> Result<Option<(MemoryTrust, u32)>> {
+    let mut stmt = conn.prepare(
+        "SELECT
+            confidence_raw, confidence_effective, baseline_confidence,
+            trust_status, contradiction_cap_active, cap_value, manual_override_active,
+            wins_last5, failures_last5, last_event_seq, last_ruleset_version,
+            last_scored_at, updated_at, retire_grace_until
+         FROM memory_trust
+         WHERE memory_id = ?1 AND version = ?2",
+    )?;
+
+    stmt.query_row(
+        rusqlite::params![memory_id.to_string(), i64::from(version)],
+        |row| {
+            let trust_status_raw: String = row.get(3)?;
+            let trust_status = TrustStatus::parse(&trust_status_raw).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    3,
+                    rusqlite::types::Type::Text,
+                    Box::new(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("invalid trust_status: {trust_status_raw}"),
+                    )),
+                )
+            })?;
+
+            let last_ruleset_version_i64: i64 = row.get(10)?;
+            let last_ruleset_version = u32::try_from(last_ruleset_version_i64).map_err(|_| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    10,
+                    rusqlite::types::Type::Integer,
+                    Box::new(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("invalid ruleset version: {last_ruleset_version_i64}"),
+                    )),
+                )
+            })?;
+
+            let last_scored_at = row
+                .get::<_, Option<String>>(11)?
+                .as_deref()
+                .map(|value| parse_rfc3339_utc(value).map_err(|err| to_sql_error(&err)))
+                .transpose()?;
+            let updated_at_raw: String = row.get(12)?;
+            let updated_at =
+                parse_rfc3339_utc(&updated_at_raw).map_err(|err| to_sql_error(&err))?;
+            let retire_grace_until = row
+                .get::<_, Option<String>>(13)?
+                .as_deref()
+                .map(|value| parse_rfc3339_utc(value).map_err(|err| to_sql_error(&err)))
+                .transpose()?;
+
+            let wins_last5_i64: i64 = row.get(7)?;
+            let failures_last5_i64: i64 = row.get(8)?;
+            let wins_last5 = u8::try_from(wins_last5_i64).map_err(|_| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    7,
+                    rusqlite::types::Type::Integer,
+                    Box::new(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("invalid wins_last5: {wins_last5_i64}"),
+                    )),
+                )
+            })?;
+            let failures_last5 = u8::try_from(failures_last5_i64).map_err(|_| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    8,
+                    rusqlite::types::Type::Integer,
+                    Box::new(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("invalid failures_last5: {failures_last5_i64}"),
+                    )),
+                )
+            })?;
+
+            let trust = MemoryTrust {
+                memory_id,
+                version,
+                confidence_raw: row.get(0)?,
+                confidence_effective: row.get(1)?,
+                baseline_confidence: row.get(2)?,
+                trust_status,
+                contradiction_cap_active: row.get::<_, i64>(4)? == 1,
+                cap_value: row.get(5)?,
+                manual_override_active: row.get::<_, i64>(6)? == 1,
+                wins_last5,
+                failures_last5,
+                last_event_seq: row.get(9)?,
+                last_scored_at,
+                updated_at,
+                retire_grace_until,
+                authoritative: false,
+            };
+            Ok((trust, last_ruleset_version))
+        },
+    )
+    .optional()
+    .map_err(anyhow::Error::from)
+}
+
+fn to_sql_error(err: &memory_kernel_outcome_core::OutcomeError) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(
+        0,
+        rusqlite::types::Type::Text,
+        Box::new(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            err.to_string(),
+        )),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        memory_kernel_snapshot, AllowAllTrustGateSource, ApiMemoryKernelContextSource,
+        CachingContextPackageSource, ContextPackageSource, ContextRef, DefaultHumanGateDecider,
+        HumanGateDecider, HumanGateRequest, HumanGateResponse, InMemoryMemoryKernelContextSource,
+        NoopProposedWriteApplier, Orchestrator, RunConfig, TrustGateAttachment, TrustGateSource,
+    };
+    use memory_kernel_api::{AddConstraintRequest, AddSummaryRequest, MemoryKernelApi};
+    use memory_kernel_core::{
+        default_recall_record_types, Answer, AnswerResult, Authority, ConstraintEffect,
+        ConstraintPayload, ConstraintScope, ContextItem, ContextPackage, DecisionPayload,
+        DeterminismMetadata, MemoryId, MemoryPayload, MemoryRecord, MemoryVersionId, QueryRequest,
+        RecordType, TruthStatus, Why,
+    };
+    use multi_agent_center_domain::{
+        AgentDefinition, ContextPackageEnvelope, RunId, StepConstraints, StepId, StepRecord,
+        StepRerun, StepStatus, TraceArtifactClass, TraceEventType,
+    };
+    use multi_agent_center_provider::ProviderRegistry;
+    use multi_agent_center_trace_core::TraceStore;
+    use multi_agent_center_trace_sqlite::SqliteTraceStore;
+    use multi_agent_center_workflow::normalize_workflow_yaml;
+    use serde_json::json;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    fn temp_db_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "mac-orchestrator-test-{}-{}.sqlite",
+            name,
+            ulid::Ulid::new()
+        ))
+    }
+
+    fn fixture_memory_record() -> MemoryRecord {
+        let now = time::OffsetDateTime::now_utc();
+        MemoryRecord {
+            memory_version_id: MemoryVersionId::new(),
+            memory_id: MemoryId::new(),
+            version: 1,
+            created_at: now,
+            effective_at: now,
+            truth_status: TruthStatus::Asserted,
+            authority: Authority::Authoritative,
+            confidence: Some(0.9),
+            writer: "test".to_string(),
+            justification: "fixture".to_string(),
+            provenance: memory_kernel_core::Provenance {
+                source_uri: "test://fixture".to_string(),
+                source_hash: None,
+                evidence: Vec::new(),
+            },
+            supersedes: Vec::new(),
+            contradicts: Vec::new(),
+            tags: Vec::new(),
+            payload: MemoryPayload::Constraint(ConstraintPayload {
+                scope: ConstraintScope {
+                    actor: "*".to_string(),
+                    action: "*".to_string(),
+                    resource: "*".to_string(),
+                },
+                effect: ConstraintEffect::Allow,
+                note: None,
+            }),
+        }
+    }
+
+    fn fixture_summary_record(record_type: RecordType, summary: &str) -> MemoryRecord {
+        let now = time::OffsetDateTime::now_utc();
+        let payload = match record_type {
+            RecordType::Decision => MemoryPayload::Decision(DecisionPayload {
+                summary: summary.to_string(),
+            }),
+            RecordType::Preference => {
+                MemoryPayload::Preference(memory_kernel_core::PreferencePayload {
+                    summary: summary.to_string(),
+                })
+            }
+            RecordType::Event => MemoryPayload::Event(memory_kernel_core::EventPayload {
+                summary: summary.to_string(),
+            }),
+            RecordType::Outcome => MemoryPayload::Outcome(memory_kernel_core::OutcomePayload {
+                summary: summary.to_string(),
+            }),
+            RecordType::Constraint => panic!("fixture_summary_record does not support constraint"),
+        };
+
+        MemoryRecord {
+            memory_version_id: MemoryVersionId::new(),
+            memory_id: MemoryId::new(),
+            version: 1,
+            created_at: now,
+            effective_at: now,
+            truth_status: TruthStatus::Observed,
+            authority: Authority::Derived,
+            confidence: Some(0.8),
+            writer: "test".to_string(),
+            justification: "fixture".to_string(),
+            provenance: memory_kernel_core::Provenance {
+                source_uri: "test://fixture".to_string(),
+                source_hash: None,
+                evidence: Vec::new(),
+            },
+            supersedes: Vec::new(),
+            contradicts: Vec::new(),
+            tags: Vec::new(),
+            payload,
+        }
+    }
+
+    fn fixture_context_package(step_key: &str) -> ContextPackageEnvelope {
+        let now = time::OffsetDateTime::now_utc();
+        let selected_items = vec![
+            ContextItem {
                 rank: 1,
                 memory_version_id: MemoryVersionId::new(),
                 memory_id: MemoryId::new(),
                 record_type: RecordType::Constraint,
                 version: 1,
-                truth_status: TruthStatus::Asserted,
+                truth_status: TruthStatus::Asserted,
+                confidence: Some(0.9),
+                authority: Authority::Authoritative,
+                tags: vec!["deploy".to_string()],
+                why: Why {
+                    included: true,
+                    reasons: vec!["fixture".to_string()],
+                    rule_scores: None,
+                },
+            },
+            ContextItem {
+                rank: 2,
+                memory_version_id: MemoryVersionId::new(),
+                memory_id: MemoryId::new(),
+                record_type: RecordType::Constraint,
+                version: 2,
+                truth_status: TruthStatus::Asserted,
+                confidence: Some(0.7),
+                authority: Authority::Authoritative,
+                tags: Vec::new(),
+                why: Why {
+                    included: true,
+                    reasons: vec!["fixture".to_string()],
+                    rule_scores: None,
+                },
+            },
+        ];
+        let package = ContextPackage {
+            context_package_id: format!("pkg-{step_key}"),
+            generated_at: now,
+            query: QueryRequest {
+                text: "t".to_string(),
+                actor: "a".to_string(),
+                action: "act".to_string(),
+                resource: "res".to_string(),
+                as_of: now,
+            },
+            determinism: DeterminismMetadata {
+                ruleset_version: "mk.v1".to_string(),
+                snapshot_id: "snapshot".to_string(),
+                tie_breakers: vec!["fixture".to_string()],
+            },
+            answer: Answer {
+                result: AnswerResult::Allow,
+                why: "fixture".to_string(),
+            },
+            selected_items,
+            excluded_items: Vec::new(),
+            ordering_trace: vec!["fixture".to_string()],
+        };
+        ContextPackageEnvelope {
+            package_slot: 0,
+            source: "test".to_string(),
+            package_hash: "fixture-hash".to_string(),
+            context_package: package,
+        }
+    }
+
+    struct ApproveHumanGate;
+
+    impl HumanGateDecider for ApproveHumanGate {
+        fn decide(&self, _request: &HumanGateRequest) -> anyhow::Result<HumanGateResponse> {
+            Ok(HumanGateResponse {
+                approved: true,
+                notes: None,
+                decided_by: "test".to_string(),
+                reason_codes: vec!["approved.test".to_string()],
+            })
+        }
+    }
+
+    struct SelectiveTrustGate;
+
+    impl TrustGateSource for SelectiveTrustGate {
+        fn evaluate(
+            &self,
+            _run_id: multi_agent_center_domain::RunId,
+            _step_id: StepId,
+            _step_key: &str,
+            as_of: time::OffsetDateTime,
+            refs: &[ContextRef],
+        ) -> anyhow::Result<Vec<TrustGateAttachment>> {
+            Ok(refs
+                .iter()
+                .map(|item| TrustGateAttachment {
+                    memory_id: item.memory_id,
+                    version: item.version,
+                    memory_version_id: item.memory_version_id,
+                    include: item.version % 2 == 1,
+                    trust_status: "active".to_string(),
+                    confidence_effective: 0.8,
+                    capped: false,
+                    reason_codes: vec!["fixture".to_string()],
+                    ruleset_version: Some(1),
+                    evaluated_at: as_of,
+                    source: "test.trust".to_string(),
+                })
+                .collect())
+        }
+    }
+
+    #[test]
+    fn provider_failure_marks_step_failed_instead_of_aborting_run() {
+        let trace_db = temp_db_path("provider-failure");
+        let trace_store = SqliteTraceStore::open(&trace_db);
+        assert!(trace_store.is_ok());
+        let trace_store = trace_store.unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let workflow_yaml = r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider:
+      provider_name: unsupported_provider
+      model_id: x
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task: { text: "a" }
+    depends_on: []
+    gate_points: []
+  - step_key: step_b
+    agent_name: planner
+    task: { text: "b" }
+    depends_on: [step_a]
+    gate_points: []
+gates: []
+defaults:
+  non_interactive: true
+"#;
+        let workflow = normalize_workflow_yaml(workflow_yaml);
+        assert!(workflow.is_ok());
+        let workflow = workflow.unwrap_or_else(|_| unreachable!());
+
+        let context_source = super::StaticContextPackageSource::default();
+        let trust_source = AllowAllTrustGateSource;
+        let human_gate = DefaultHumanGateDecider;
+        let write_applier = NoopProposedWriteApplier;
+
+        let summary = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &trust_source,
+            &human_gate,
+            &write_applier,
+            ProviderRegistry::default(),
+        )
+        .execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
+            },
+        );
+        let summary = match summary {
+            Ok(value) => value,
+            Err(err) => panic!("workflow execution failed: {err:#}"),
+        };
+        assert_eq!(summary.status, multi_agent_center_domain::RunStatus::Failed);
+        let run_record = trace_store.get_run(summary.run_id);
+        assert!(run_record.is_ok());
+        let run_record = run_record
+            .unwrap_or_else(|_| unreachable!())
+            .unwrap_or_else(|| unreachable!());
+        assert!(run_record.manifest_hash.is_some());
+        assert_eq!(run_record.manifest_signature_status, "unsigned");
+
+        let steps = trace_store.get_step_records(summary.run_id);
+        assert!(steps.is_ok());
+        let steps = steps.unwrap_or_else(|_| unreachable!());
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].status, StepStatus::Failed);
+        assert_eq!(steps[1].status, StepStatus::Skipped);
+    }
+
+    fn run_retrying_step(name: &str, retry_context: &str) -> StepRecord {
+        let trace_db = temp_db_path(name);
+        let trace_store = SqliteTraceStore::open(&trace_db);
+        assert!(trace_store.is_ok());
+        let trace_store = trace_store.unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let closed_port = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .map_or_else(|_| unreachable!(), |addr| addr.port());
+        let workflow_yaml = format!(
+            r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider:
+      provider_name: http_json
+      model_id: x
+      params: {{ url: "http://127.0.0.1:{closed_port}/", timeout_ms: 500 }}
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task: {{ text: "a" }}
+    depends_on: []
+    gate_points: []
+    constraints: {{ max_attempts: 3, retry_context: {retry_context} }}
+gates: []
+defaults:
+  non_interactive: true
+"#
+        );
+        let workflow = normalize_workflow_yaml(&workflow_yaml);
+        assert!(workflow.is_ok());
+        let workflow = workflow.unwrap_or_else(|_| unreachable!());
+
+        let mut records_by_step = BTreeMap::new();
+        records_by_step.insert("step_a".to_string(), vec![fixture_memory_record()]);
+        let context_source = InMemoryMemoryKernelContextSource { records_by_step };
+
+        let summary = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        )
+        .execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
+            },
+        );
+        let summary = match summary {
+            Ok(value) => value,
+            Err(err) => panic!("workflow execution failed: {err:#}"),
+        };
+        assert_eq!(summary.status, multi_agent_center_domain::RunStatus::Failed);
+
+        let steps = trace_store.get_step_records(summary.run_id);
+        assert!(steps.is_ok());
+        let mut steps = steps.unwrap_or_else(|_| unreachable!());
+        assert_eq!(steps.len(), 1);
+        steps.remove(0)
+    }
+
+    #[test]
+    fn retried_step_reuses_context_by_default_and_records_retries() {
+        let step = run_retrying_step("retry-reuse", "reuse");
+        assert_eq!(step.status, StepStatus::Failed);
+        let retry = step.retry_json.unwrap_or_else(|| unreachable!());
+        assert_eq!(retry["retry_context"], "reuse");
+        assert_eq!(retry["attempts_made"], 3);
+        let retries = retry["retries"].as_array().cloned().unwrap_or_default();
+        assert_eq!(retries.len(), 2);
+        for entry in &retries {
+            assert_eq!(entry["context"], "reuse");
+            assert_eq!(entry["input_hash"], step.input_hash.as_str());
+        }
+    }
+
+    #[test]
+    fn retried_step_refreshes_context_at_retry_time_when_configured() {
+        let step = run_retrying_step("retry-refresh", "refresh");
+        let retry = step.retry_json.unwrap_or_else(|| unreachable!());
+        assert_eq!(retry["retry_context"], "refresh");
+        let retries = retry["retries"].as_array().cloned().unwrap_or_default();
+        assert_eq!(retries.len(), 2);
+        for entry in &retries {
+            assert_eq!(entry["context"], "refresh");
+            assert_ne!(entry["input_hash"], step.input_hash.as_str());
+            assert!(entry["previous_error"]
+                .as_str()
+                .is_some_and(|error| error.contains("http transport failure")));
+        }
+    }
+
+    /// Answer one HTTP request per entry of `responses` on a local port, in order, with the
+    /// entry's status and JSON body. Returns the port.
+    fn serve_http_responses(responses: Vec<(u16, serde_json::Value)>) -> u16 {
+        let listener =
+            std::net::TcpListener::bind("127.0.0.1:0").unwrap_or_else(|_| unreachable!());
+        let port = listener
+            .local_addr()
+            .unwrap_or_else(|_| unreachable!())
+            .port();
+        std::thread::spawn(move || {
+            for (status, body) in responses {
+                let Ok((mut stream, _)) = listener.accept() else {
+                    return;
+                };
+                let mut reader =
+                    std::io::BufReader::new(stream.try_clone().unwrap_or_else(|_| unreachable!()));
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    if std::io::BufRead::read_line(&mut reader, &mut line).is_err()
+                        || line.trim().is_empty()
+                    {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap_or_default();
+                        }
+                    }
+                }
+                let mut request_body = vec![0; content_length];
+                let _ = std::io::Read::read_exact(&mut reader, &mut request_body);
+                let body = body.to_string();
+                let _ = std::io::Write::write_all(
+                    &mut stream,
+                    format!(
+                        "HTTP/1.1 {status} Status\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                        body.len()
+                    )
+                    .as_bytes(),
+                );
+            }
+        });
+        port
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn http_503_is_retried_with_refreshed_context_recorded_per_attempt() {
+        let trace_db = temp_db_path("retry-503");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+        let port = serve_http_responses(vec![
+            (503, json!({"error": "unavailable"})),
+            (200, json!({"answer": "ok"})),
+        ]);
+        let workflow = normalize_workflow_yaml(&format!(
+            r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider: {{ provider_name: http_json, model_id: x, params: {{ url: "http://127.0.0.1:{port}/", timeout_ms: 5000 }} }}
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task: {{ text: "a" }}
+    constraints: {{ max_attempts: 3, retry_context: refresh }}
+gates: []
+defaults:
+  non_interactive: true
+"#
+        ))
+        .unwrap_or_else(|_| unreachable!());
+        let mut records_by_step = BTreeMap::new();
+        records_by_step.insert("step_a".to_string(), vec![fixture_memory_record()]);
+        let context_source = InMemoryMemoryKernelContextSource { records_by_step };
+        let orchestrator = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        );
+        let summary = orchestrator
+            .execute_workflow(
+                &workflow,
+                RunConfig {
+                    non_interactive: true,
+                    ..RunConfig::default()
+                },
+            )
+            .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+        assert_eq!(
+            summary.status,
+            multi_agent_center_domain::RunStatus::Succeeded
+        );
+
+        let steps = trace_store
+            .get_step_records(summary.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(steps[0].status, StepStatus::Succeeded);
+        let retry = steps[0]
+            .retry_json
+            .clone()
+            .unwrap_or_else(|| unreachable!());
+        assert_eq!(retry["attempts_made"], 2);
+        assert_eq!(retry["retries"][0]["previous_error"], "http status 503");
+        assert_eq!(
+            provider_call_statuses(&trace_db, summary.run_id),
+            vec![
+                ("x".to_string(), "failed".to_string()),
+                ("x".to_string(), "succeeded".to_string()),
+            ]
+        );
+
+        let packages = trace_store
+            .get_step_context_packages(summary.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(
+            packages.iter().map(|row| row.attempt).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(
+            retry["retries"][0]["context_package_hashes"][0],
+            packages[1].envelope.package_hash.as_str()
+        );
+        let trust_attempts: Vec<serde_json::Value> = trace_store
+            .get_step_gate_decisions(summary.run_id)
+            .unwrap_or_else(|_| unreachable!())
+            .into_iter()
+            .filter(|row| row.decision.gate_kind == multi_agent_center_domain::GateKind::Trust)
+            .map(|row| {
+                row.decision
+                    .evidence_json
+                    .unwrap_or(serde_json::Value::Null)["attempt"]
+                    .clone()
+            })
+            .collect();
+        assert_eq!(trust_attempts, vec![serde_json::Value::Null, json!(2)]);
+
+        let report = orchestrator
+            .replay_execute(summary.run_id)
+            .unwrap_or_else(|err| panic!("replay failed: {err:#}"));
+        assert_eq!(
+            report.status,
+            multi_agent_center_domain::RunStatus::Succeeded
+        );
+        assert_eq!(report.provider_calls_replayed, 2);
+        assert!(report.reproduced, "{:?}", report.divergences);
+        let replayed = trace_store
+            .get_step_context_packages(report.replay_run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(
+            replayed
+                .iter()
+                .map(|row| (row.attempt, row.envelope.package_hash.clone()))
+                .collect::<Vec<_>>(),
+            packages
+                .iter()
+                .map(|row| (row.attempt, row.envelope.package_hash.clone()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn fatal_provider_failure_is_not_retried() {
+        let trace_store =
+            SqliteTraceStore::open(&temp_db_path("retry-fatal")).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+        let workflow = normalize_workflow_yaml(
+            r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider: { provider_name: unsupported_provider, model_id: x }
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task: { text: "a" }
+    constraints: { max_attempts: 3 }
+gates: []
+defaults:
+  non_interactive: true
+"#,
+        )
+        .unwrap_or_else(|_| unreachable!());
+        let context_source = super::StaticContextPackageSource::default();
+        let summary = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        )
+        .execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
+            },
+        )
+        .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+        let steps = trace_store
+            .get_step_records(summary.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(steps[0].status, StepStatus::Failed);
+        assert!(steps[0].retry_json.is_none());
+        assert!(!warning_codes(&trace_store, summary.run_id).contains(&"step_retry".to_string()));
+    }
+
+    /// Serves the fixture record to a step's first context request only.
+    struct FirstRequestContextSource {
+        record: MemoryRecord,
+        requests: std::cell::Cell<usize>,
+    }
+
+    impl ContextPackageSource for FirstRequestContextSource {
+        fn packages_for_step(
+            &self,
+            run_id: multi_agent_center_domain::RunId,
+            step: &multi_agent_center_domain::WorkflowStepDefinition,
+            as_of: time::OffsetDateTime,
+        ) -> anyhow::Result<Vec<multi_agent_center_domain::ContextPackageEnvelope>> {
+            let requests = self.requests.get();
+            self.requests.set(requests + 1);
+            let records = if requests == 0 {
+                vec![self.record.clone()]
+            } else {
+                Vec::new()
+            };
+            super::build_context_packages_from_records(
+                &records,
+                run_id,
+                step,
+                as_of,
+                "memory_kernel.in_memory",
+            )
+        }
+    }
+
+    #[test]
+    fn refreshed_retry_context_is_gated_like_the_first_attempt() {
+        let trace_db = temp_db_path("retry-refresh-gated");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+        let closed_port = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .map_or_else(|_| unreachable!(), |addr| addr.port());
+        let record = fixture_memory_record();
+        let workflow = normalize_workflow_yaml(&format!(
+            r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider: {{ provider_name: http_json, model_id: x, params: {{ url: "http://127.0.0.1:{closed_port}/", timeout_ms: 500 }} }}
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task: {{ text: "a" }}
+    constraints: {{ max_attempts: 3, retry_context: refresh }}
+    memory_dependencies:
+      - memory_id: "{memory_id}"
+gates: []
+defaults:
+  non_interactive: true
+"#,
+            memory_id = record.memory_id
+        ))
+        .unwrap_or_else(|_| unreachable!());
+        let context_source = FirstRequestContextSource {
+            record,
+            requests: std::cell::Cell::new(0),
+        };
+        let summary = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        )
+        .execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
+            },
+        )
+        .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+
+        let steps = trace_store
+            .get_step_records(summary.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(steps[0].status, StepStatus::Failed);
+        assert_eq!(
+            steps[0]
+                .error_json
+                .as_ref()
+                .map(|error| error["code"].clone()),
+            Some(json!("memory_dependency_missing"))
+        );
+        let retry = steps[0]
+            .retry_json
+            .clone()
+            .unwrap_or_else(|| unreachable!());
+        assert_eq!(retry["attempts_made"], 1);
+        assert_eq!(retry["retries"].as_array().map(Vec::len), Some(1));
+        assert_eq!(
+            provider_call_statuses(&trace_db, summary.run_id),
+            vec![("x".to_string(), "failed".to_string())]
+        );
+        assert!(warning_codes(&trace_store, summary.run_id)
+            .contains(&"memory_dependency_missing".to_string()));
+        assert_eq!(retry["retries"][0]["context_package_hashes"], json!([]));
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn conversation_step_checkpoints_each_turn_and_resumes_from_last_checkpoint() {
+        let trace_db = temp_db_path("conversation-checkpoints");
+        let trace_store = SqliteTraceStore::open(&trace_db);
+        assert!(trace_store.is_ok());
+        let trace_store = trace_store.unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let workflow_yaml = r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider:
+      provider_name: mock
+      model_id: x
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task:
+      turns: [{ text: "first" }, { text: "second" }, { text: "third" }]
+    depends_on: []
+    gate_points: []
+gates: []
+defaults:
+  non_interactive: true
+"#;
+        let workflow = normalize_workflow_yaml(workflow_yaml);
+        assert!(workflow.is_ok());
+        let workflow = workflow.unwrap_or_else(|_| unreachable!());
+
+        let context_source = super::StaticContextPackageSource::default();
+        let orchestrator = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        );
+        let summary = orchestrator.execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
+            },
+        );
+        let summary = match summary {
+            Ok(value) => value,
+            Err(err) => panic!("workflow execution failed: {err:#}"),
+        };
+        assert_eq!(
+            summary.status,
+            multi_agent_center_domain::RunStatus::Succeeded
+        );
+
+        let steps = trace_store
+            .get_step_records(summary.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        let step_id = steps[0].step_id;
+        let checkpoints = trace_store
+            .get_step_checkpoints(step_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(checkpoints.len(), 3);
+        assert_eq!(checkpoints[0].prev_checkpoint_hash, None);
+        for pair in checkpoints.windows(2) {
+            assert_eq!(
+                pair[1].prev_checkpoint_hash.as_deref(),
+                Some(pair[0].state_hash.as_str())
+            );
+        }
+        assert!(checkpoints
+            .iter()
+            .all(|checkpoint| checkpoint.provider_call_id.is_some()));
+
+        let mut extended = workflow.normalized_workflow.steps[0].clone();
+        extended.task = json!({
+            "turns": [
+                { "text": "first" }, { "text": "second" }, { "text": "third" }, { "text": "fourth" }
+            ]
+        });
+        let agent = &workflow.normalized_workflow.agents[0];
+        let prepared = orchestrator.prepare_step_input(
+            summary.run_id,
+            step_id,
+            &extended,
+            agent,
+            None,
+            &multi_agent_center_domain::EffectivePermissions::from(&agent.permissions),
+            time::OffsetDateTime::now_utc(),
+        );
+        let prepared = prepared.unwrap_or_else(|_| unreachable!());
+        let turns = super::conversation_turns(&extended)
+            .unwrap_or_else(|_| unreachable!())
+            .unwrap_or_else(|| unreachable!());
+        let mut chain = super::EventChain::default();
+        let cancellation = super::CancellationToken::new();
+        let budget = super::StepBudget::new(&extended.constraints, &cancellation);
+        let resumed = orchestrator.run_conversation_turns(
+            &extended,
+            &prepared.request,
+            turns,
+            &budget,
+            &mut chain,
+        );
+        let resumed = match resumed {
+            Ok(value) => value,
+            Err(err) => panic!("resume failed: {err:#}"),
+        };
+        assert_eq!(resumed.status, StepStatus::Succeeded);
+        assert_eq!(resumed.provider_calls.len(), 1);
+        assert_eq!(resumed.checkpoint_hashes.len(), 4);
+        assert_eq!(
+            resumed.checkpoint_hashes[..3],
+            checkpoints
+                .iter()
+                .map(|checkpoint| checkpoint.state_hash.clone())
+                .collect::<Vec<_>>()[..]
+        );
+        assert_eq!(
+            resumed.outputs.payload["turns"].as_array().map(Vec::len),
+            Some(4)
+        );
+    }
+
+    fn run_speculative_workflow(
+        name: &str,
+        human_gate: &dyn HumanGateDecider,
+    ) -> (SqliteTraceStore, super::RunExecutionSummary) {
+        let trace_db = temp_db_path(name);
+        let trace_store = SqliteTraceStore::open(&trace_db);
+        assert!(trace_store.is_ok());
+        let trace_store = trace_store.unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let workflow_yaml = r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider:
+      provider_name: mock
+      model_id: x
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task: { text: "a" }
+    depends_on: []
+    gate_points: [review]
+  - step_key: step_b
+    agent_name: planner
+    task: { text: "b" }
+    depends_on: [step_a]
+    gate_points: []
+  - step_key: step_c
+    agent_name: planner
+    task: { text: "c" }
+    depends_on: []
+    gate_points: []
+gates:
+  - gate_name: review
+    gate_kind: human
+    required: false
+defaults:
+  non_interactive: true
+"#;
+        let workflow = normalize_workflow_yaml(workflow_yaml);
+        assert!(workflow.is_ok());
+        let workflow = workflow.unwrap_or_else(|_| unreachable!());
+
+        let context_source = super::StaticContextPackageSource::default();
+        let summary = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &AllowAllTrustGateSource,
+            human_gate,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        )
+        .execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                speculative_execution: true,
+                ..RunConfig::default()
+            },
+        );
+        let summary = match summary {
+            Ok(value) => value,
+            Err(err) => panic!("workflow execution failed: {err:#}"),
+        };
+        (trace_store, summary)
+    }
+
+    fn warning_codes(
+        trace_store: &SqliteTraceStore,
+        run_id: multi_agent_center_domain::RunId,
+    ) -> Vec<String> {
+        trace_store
+            .list_events_for_run(run_id)
+            .unwrap_or_else(|_| unreachable!())
+            .into_iter()
+            .filter_map(|row| {
+                row.event.payload_json["warning_code"]
+                    .as_str()
+                    .map(str::to_string)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn speculative_steps_behind_optional_gate_commit_after_approval() {
+        let (trace_store, summary) =
+            run_speculative_workflow("speculative-commit", &ApproveHumanGate);
+        let run_id = summary.run_id;
+        assert_eq!((summary.human_approvals, summary.human_rejections), (1, 0));
+        assert_eq!(summary.provider_calls, 3);
+        let steps = trace_store
+            .get_step_records(run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert!(steps
+            .iter()
+            .all(|step| step.status == StepStatus::Succeeded));
+        assert_eq!(
+            warning_codes(&trace_store, run_id),
+            vec!["speculative_step_committed", "speculative_step_committed"]
+        );
+
+        let events = trace_store
+            .list_events_for_run(run_id)
+            .unwrap_or_else(|_| unreachable!());
+        let position = |decision: &str| {
+            events.iter().position(|row| {
+                row.event.payload_json["gate_name"] == "review"
+                    && row.event.payload_json["decision"] == decision
+            })
+        };
+        let step_b_finished = events.iter().position(|row| {
+            row.event.payload_json["step_key"] == "step_b"
+                && row.event.payload_json["speculative"] == true
+        });
+        assert!(position("pending") < step_b_finished);
+        assert!(step_b_finished < position("approved"));
+    }
+
+    #[test]
+    fn speculative_steps_are_discarded_when_optional_gate_is_rejected() {
+        let (trace_store, summary) =
+            run_speculative_workflow("speculative-discard", &DefaultHumanGateDecider);
+        let run_id = summary.run_id;
+        assert_eq!((summary.human_approvals, summary.human_rejections), (0, 1));
+        assert_eq!((summary.trust_excluded, summary.policy_prunes), (0, 0));
+        assert_eq!(summary.proposed_writes, 0);
+        assert!(summary.proposed_write_dispositions.is_empty());
+        let run = trace_store
+            .get_run(run_id)
+            .unwrap_or_else(|_| unreachable!())
+            .unwrap_or_else(|| unreachable!());
+        assert_eq!(run.status, multi_agent_center_domain::RunStatus::Succeeded);
+
+        let steps = trace_store
+            .get_step_records(run_id)
+            .unwrap_or_else(|_| unreachable!());
+        let status_of = |key: &str| {
+            steps.iter().find(|step| step.step_key == key).map_or_else(
+                || unreachable!(),
+                |step| (step.status.clone(), step.error_json.clone()),
+            )
+        };
+        for key in ["step_a", "step_b"] {
+            let (status, error_json) = status_of(key);
+            assert_eq!(status, StepStatus::Skipped);
+            let error_json = error_json.unwrap_or_else(|| unreachable!());
+            assert_eq!(error_json["reason"], "speculation_discarded");
+            assert_eq!(error_json["rejected_gate_steps"], json!(["step_a"]));
+        }
+        assert_eq!(status_of("step_c").0, StepStatus::Succeeded);
+        assert_eq!(
+            warning_codes(&trace_store, run_id),
+            vec!["speculative_step_discarded", "speculative_step_discarded"]
+        );
+    }
+
+    #[test]
+    fn step_supports_multiple_context_packages() {
+        let trace_db = temp_db_path("multi-packages");
+        let trace_store = SqliteTraceStore::open(&trace_db);
+        assert!(trace_store.is_ok());
+        let trace_store = trace_store.unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let workflow_yaml = r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider:
+      provider_name: mock
+      model_id: x
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task:
+      context_queries:
+        - { text: "q1", actor: "a", action: "x", resource: "r1" }
+        - { text: "q2", actor: "a", action: "x", resource: "r2" }
+    depends_on: []
+    gate_points: []
+gates: []
+defaults:
+  non_interactive: true
+"#;
+        let workflow = normalize_workflow_yaml(workflow_yaml);
+        assert!(workflow.is_ok());
+        let workflow = workflow.unwrap_or_else(|_| unreachable!());
+
+        let mut records_by_step = BTreeMap::new();
+        records_by_step.insert("step_a".to_string(), vec![fixture_memory_record()]);
+        let context_source = InMemoryMemoryKernelContextSource { records_by_step };
+
+        let trust_source = AllowAllTrustGateSource;
+        let human_gate = DefaultHumanGateDecider;
+        let write_applier = NoopProposedWriteApplier;
+        let summary = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &trust_source,
+            &human_gate,
+            &write_applier,
+            ProviderRegistry::default(),
+        )
+        .execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
+            },
+        );
+        let summary = match summary {
+            Ok(value) => value,
+            Err(err) => panic!("workflow execution failed: {err:#}"),
+        };
+        assert_eq!(
+            summary.status,
+            multi_agent_center_domain::RunStatus::Succeeded
+        );
+
+        let packages = trace_store.get_step_context_packages(summary.run_id);
+        assert!(packages.is_ok());
+        let packages = packages.unwrap_or_else(|_| unreachable!());
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].envelope.package_slot, 0);
+        assert_eq!(packages[1].envelope.package_slot, 1);
+
+        let usage = &summary.usage;
+        assert_eq!(usage.provider_calls, 1);
+        assert!(usage.provider_latency_ms > 0);
+        assert_eq!((usage.input_tokens, usage.output_tokens), (0, 0));
+        let package_bytes: usize = packages
+            .iter()
+            .map(|record| {
+                serde_json::to_string(&record.envelope.context_package).map_or(0, |json| json.len())
+            })
+            .sum();
+        assert_eq!(usage.context_bytes_injected, package_bytes as u64);
+        let events = trace_store
+            .list_events_for_run(summary.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert!(usage.trace_rows_written > events.len() as u64 + 1);
+        let stored = trace_store.get_run_usage(summary.run_id);
+        assert!(matches!(stored, Ok(Some(ref stored)) if stored == usage));
+    }
+
+    #[test]
+    fn step_context_query_recall_mode_uses_recall_resolver_rules() {
+        let trace_db = temp_db_path("recall-mode");
+        let trace_store = SqliteTraceStore::open(&trace_db);
+        assert!(trace_store.is_ok());
+        let trace_store = trace_store.unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let workflow_yaml = r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider:
+      provider_name: mock
+      model_id: x
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task:
+      context_queries:
+        - { mode: "recall", text: "usb project notes", record_types: ["decision", "outcome"] }
+    depends_on: []
+    gate_points: []
+gates: []
+defaults:
+  non_interactive: true
+"#;
+        let workflow = normalize_workflow_yaml(workflow_yaml);
+        assert!(workflow.is_ok());
+        let workflow = workflow.unwrap_or_else(|_| unreachable!());
+
+        let mut records_by_step = BTreeMap::new();
+        records_by_step.insert(
+            "step_a".to_string(),
+            vec![
+                fixture_memory_record(),
+                fixture_summary_record(RecordType::Decision, "usb project notes decision"),
+                fixture_summary_record(RecordType::Outcome, "usb project notes outcome"),
+            ],
+        );
+        let context_source = InMemoryMemoryKernelContextSource { records_by_step };
+
+        let trust_source = AllowAllTrustGateSource;
+        let human_gate = DefaultHumanGateDecider;
+        let write_applier = NoopProposedWriteApplier;
+        let summary = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &trust_source,
+            &human_gate,
+            &write_applier,
+            ProviderRegistry::default(),
+        )
+        .execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
+            },
+        );
+        let summary = match summary {
+            Ok(value) => value,
+            Err(err) => panic!("workflow execution failed: {err:#}"),
+        };
+        assert_eq!(
+            summary.status,
+            multi_agent_center_domain::RunStatus::Succeeded
+        );
+
+        let packages = trace_store.get_step_context_packages(summary.run_id);
+        assert!(packages.is_ok());
+        let packages = packages.unwrap_or_else(|_| unreachable!());
+        assert_eq!(packages.len(), 1);
+        let selected = &packages[0].envelope.context_package.selected_items;
+        assert!(selected
+            .iter()
+            .all(|item| item.record_type == RecordType::Decision
+                || item.record_type == RecordType::Outcome));
+    }
+
+    #[test]
+    fn step_context_query_recall_missing_record_types_defaults_scope() {
+        let trace_db = temp_db_path("recall-default-missing");
+        let trace_store = SqliteTraceStore::open(&trace_db);
+        assert!(trace_store.is_ok());
+        let trace_store = trace_store.unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let workflow_yaml = r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider:
+      provider_name: mock
+      model_id: x
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task:
+      context_queries:
+        - { mode: "recall", text: "repo policy decision" }
+    depends_on: []
+    gate_points: []
+gates: []
+defaults:
+  non_interactive: true
+"#;
+        let workflow = normalize_workflow_yaml(workflow_yaml);
+        assert!(workflow.is_ok());
+        let workflow = workflow.unwrap_or_else(|_| unreachable!());
+
+        let mut records_by_step = BTreeMap::new();
+        records_by_step.insert(
+            "step_a".to_string(),
+            vec![
+                fixture_memory_record(),
+                fixture_summary_record(RecordType::Decision, "repo policy decision"),
+                fixture_summary_record(RecordType::Outcome, "repo policy outcome"),
+            ],
+        );
+        let context_source = InMemoryMemoryKernelContextSource { records_by_step };
+
+        let summary = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        )
+        .execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
+            },
+        );
+        let summary = match summary {
+            Ok(value) => value,
+            Err(err) => panic!("workflow execution failed: {err:#}"),
+        };
+        assert_eq!(
+            summary.status,
+            multi_agent_center_domain::RunStatus::Succeeded
+        );
+
+        let packages = trace_store.get_step_context_packages(summary.run_id);
+        assert!(packages.is_ok());
+        let packages = packages.unwrap_or_else(|_| unreachable!());
+        assert_eq!(packages.len(), 1);
+        let selected = &packages[0].envelope.context_package.selected_items;
+        assert!(!selected.is_empty());
+        let default_types = default_recall_record_types();
+        assert!(selected
+            .iter()
+            .all(|item| default_types.contains(&item.record_type)));
+    }
+
+    #[test]
+    fn step_context_query_recall_tags_scope_context_and_permissions() {
+        let trace_db = temp_db_path("recall-tags");
+        let trace_store = SqliteTraceStore::open(&trace_db);
+        assert!(trace_store.is_ok());
+        let trace_store = trace_store.unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let workflow_yaml = r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider:
+      provider_name: mock
+      model_id: x
+    permissions:
+      denied_record_tags: [billing]
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task:
+      context_queries:
+        - { mode: "recall", text: "repo policy", record_tags: ["deploy"] }
+    memory_dependencies:
+      - { tag: billing, on_missing: warn }
+    depends_on: []
+    gate_points: []
+gates: []
+defaults:
+  non_interactive: true
+"#;
+        let workflow = normalize_workflow_yaml(workflow_yaml);
+        assert!(workflow.is_ok());
+        let workflow = workflow.unwrap_or_else(|_| unreachable!());
+
+        let mut deploy = fixture_summary_record(RecordType::Decision, "repo policy decision");
+        deploy.tags = vec!["deploy".to_string()];
+        let mut billing = fixture_summary_record(RecordType::Outcome, "repo policy outcome");
+        billing.tags = vec!["billing".to_string(), "deploy".to_string()];
+        let untagged = fixture_summary_record(RecordType::Decision, "repo policy untagged");
+        let mut records_by_step = BTreeMap::new();
+        records_by_step.insert(
+            "step_a".to_string(),
+            vec![deploy.clone(), billing, untagged],
+        );
+        let context_source = InMemoryMemoryKernelContextSource { records_by_step };
+
+        let summary = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        )
+        .execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
+            },
+        );
+        let summary = match summary {
+            Ok(value) => value,
+            Err(err) => panic!("workflow execution failed: {err:#}"),
+        };
+        assert_eq!(
+            summary.status,
+            multi_agent_center_domain::RunStatus::Succeeded
+        );
+
+        let packages = trace_store.get_step_context_packages(summary.run_id);
+        assert!(packages.is_ok());
+        let packages = packages.unwrap_or_else(|_| unreachable!());
+        let selected = &packages[0].envelope.context_package.selected_items;
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].memory_id, deploy.memory_id);
+        assert_eq!(selected[0].tags, vec!["deploy".to_string()]);
+        assert!(warning_codes(&trace_store, summary.run_id)
+            .contains(&"memory_dependency_missing".to_string()));
+    }
+
+    #[test]
+    fn step_context_query_recall_empty_record_types_defaults_scope() {
+        let trace_db = temp_db_path("recall-default-empty");
+        let trace_store = SqliteTraceStore::open(&trace_db);
+        assert!(trace_store.is_ok());
+        let trace_store = trace_store.unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let workflow_yaml = r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider:
+      provider_name: mock
+      model_id: x
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task:
+      context_queries:
+        - { mode: "recall", text: "repo policy outcome", record_types: [] }
+    depends_on: []
+    gate_points: []
+gates: []
+defaults:
+  non_interactive: true
+"#;
+        let workflow = normalize_workflow_yaml(workflow_yaml);
+        assert!(workflow.is_ok());
+        let workflow = workflow.unwrap_or_else(|_| unreachable!());
+
+        let mut records_by_step = BTreeMap::new();
+        records_by_step.insert(
+            "step_a".to_string(),
+            vec![
+                fixture_memory_record(),
+                fixture_summary_record(RecordType::Decision, "repo policy decision"),
+                fixture_summary_record(RecordType::Outcome, "repo policy outcome"),
+            ],
+        );
+        let context_source = InMemoryMemoryKernelContextSource { records_by_step };
+
+        let summary = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        )
+        .execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
+            },
+        );
+        let summary = match summary {
+            Ok(value) => value,
+            Err(err) => panic!("workflow execution failed: {err:#}"),
+        };
+        assert_eq!(
+            summary.status,
+            multi_agent_center_domain::RunStatus::Succeeded
+        );
+
+        let packages = trace_store.get_step_context_packages(summary.run_id);
+        assert!(packages.is_ok());
+        let packages = packages.unwrap_or_else(|_| unreachable!());
+        assert_eq!(packages.len(), 1);
+        let selected = &packages[0].envelope.context_package.selected_items;
+        assert!(!selected.is_empty());
+        let default_types = default_recall_record_types();
+        assert!(selected
+            .iter()
+            .all(|item| default_types.contains(&item.record_type)));
+    }
+
+    #[test]
+    fn step_context_query_recall_invalid_record_type_fails() {
+        let trace_db = temp_db_path("recall-invalid-type");
+        let trace_store = SqliteTraceStore::open(&trace_db);
+        assert!(trace_store.is_ok());
+        let trace_store = trace_store.unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let workflow_yaml = r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider:
+      provider_name: mock
+      model_id: x
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task:
+      context_queries:
+        - { mode: "recall", text: "repo policy decision", record_types: ["constraint", "invalid_type"] }
+    depends_on: []
+    gate_points: []
+gates: []
+defaults:
+  non_interactive: true
+"#;
+        let workflow = normalize_workflow_yaml(workflow_yaml);
+        assert!(workflow.is_ok());
+        let workflow = workflow.unwrap_or_else(|_| unreachable!());
+
+        let mut records_by_step = BTreeMap::new();
+        records_by_step.insert("step_a".to_string(), vec![fixture_memory_record()]);
+        let context_source = InMemoryMemoryKernelContextSource { records_by_step };
+
+        let run = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        )
+        .execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
+            },
+        );
+        assert!(run.is_err());
+    }
+
+    #[test]
+    fn parse_recall_record_types_invalid_value_errors() {
+        let raw = json!(["decision", "invalid_type"]);
+        let parsed = super::parse_recall_record_types(Some(&raw));
+        assert!(parsed.is_err());
+        let err = parsed.err().unwrap_or_else(|| unreachable!());
+        let message = err.to_string();
+        assert!(message.contains("unsupported recall record type"));
+        assert!(message.contains("invalid_type"));
+    }
+
+    #[test]
+    fn step_context_query_default_mode_is_policy() {
+        let trace_db = temp_db_path("default-policy-mode");
+        let trace_store = SqliteTraceStore::open(&trace_db);
+        assert!(trace_store.is_ok());
+        let trace_store = trace_store.unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let workflow_yaml = r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider:
+      provider_name: mock
+      model_id: x
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task:
+      context_queries:
+        - { text: "Can dev read repo?", actor: "dev", action: "read", resource: "repo" }
+    depends_on: []
+    gate_points: []
+gates: []
+defaults:
+  non_interactive: true
+"#;
+        let workflow = normalize_workflow_yaml(workflow_yaml);
+        assert!(workflow.is_ok());
+        let workflow = workflow.unwrap_or_else(|_| unreachable!());
+
+        let mut records_by_step = BTreeMap::new();
+        records_by_step.insert("step_a".to_string(), vec![fixture_memory_record()]);
+        let context_source = InMemoryMemoryKernelContextSource { records_by_step };
+
+        let summary = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        )
+        .execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
+            },
+        );
+        let summary = match summary {
+            Ok(value) => value,
+            Err(err) => panic!("workflow execution failed: {err:#}"),
+        };
+        assert_eq!(
+            summary.status,
+            multi_agent_center_domain::RunStatus::Succeeded
+        );
+
+        let packages = trace_store.get_step_context_packages(summary.run_id);
+        assert!(packages.is_ok());
+        let packages = packages.unwrap_or_else(|_| unreachable!());
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].envelope.context_package.query.actor, "dev");
+        assert_eq!(packages[0].envelope.context_package.query.action, "read");
+        assert_eq!(packages[0].envelope.context_package.query.resource, "repo");
+    }
+
+    #[test]
+    fn caching_context_source_reuses_packages_for_identical_queries_and_as_of() {
+        let workflow_yaml = r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider:
+      provider_name: mock
+      model_id: x
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task:
+      context_queries:
+        - { text: "Can dev read repo?", actor: "dev", action: "read", resource: "repo" }
+    depends_on: []
+    gate_points: []
+gates: []
+defaults:
+  non_interactive: true
+"#;
+        let workflow = normalize_workflow_yaml(workflow_yaml);
+        assert!(workflow.is_ok());
+        let workflow = workflow.unwrap_or_else(|_| unreachable!());
+        let step = &workflow.normalized_workflow.steps[0];
+
+        let mut records_by_step = BTreeMap::new();
+        records_by_step.insert("step_a".to_string(), vec![fixture_memory_record()]);
+        let cache_db = temp_db_path("context-cache");
+        let source = CachingContextPackageSource::new(InMemoryMemoryKernelContextSource {
+            records_by_step: records_by_step.clone(),
+        })
+        .with_sqlite(&cache_db);
+        assert!(source.is_ok());
+        let source = source.unwrap_or_else(|_| unreachable!());
+
+        let as_of = time::OffsetDateTime::now_utc();
+        let run_id = multi_agent_center_domain::RunId::new();
+        let fresh = source.packages_for_step(run_id, step, as_of);
+        assert!(fresh.is_ok());
+        let fresh = fresh.unwrap_or_else(|_| unreachable!());
+        assert_eq!(fresh.len(), 1);
+        assert_eq!(fresh[0].source, "memory_kernel.in_memory");
+
+        let retry = source.packages_for_step(run_id, step, as_of);
+        assert!(retry.is_ok());
+        let retry = retry.unwrap_or_else(|_| unreachable!());
+        assert!(retry[0]
+            .source
+            .starts_with("memory_kernel.in_memory+cache:memory:"));
+        assert_eq!(retry[0].package_hash, fresh[0].package_hash);
+
+        let reopened =
+            CachingContextPackageSource::new(InMemoryMemoryKernelContextSource { records_by_step })
+                .with_sqlite(&cache_db);
+        assert!(reopened.is_ok());
+        let reopened = reopened.unwrap_or_else(|_| unreachable!());
+        let persisted =
+            reopened.packages_for_step(multi_agent_center_domain::RunId::new(), step, as_of);
+        assert!(persisted.is_ok());
+        let persisted = persisted.unwrap_or_else(|_| unreachable!());
+        assert!(persisted[0]
+            .source
+            .starts_with("memory_kernel.in_memory+cache:sqlite:"));
+        assert_eq!(persisted[0].package_hash, fresh[0].package_hash);
+
+        let later = reopened.packages_for_step(run_id, step, as_of + time::Duration::seconds(1));
+        assert!(later.is_ok());
+        let later = later.unwrap_or_else(|_| unreachable!());
+        assert_eq!(later[0].source, "memory_kernel.in_memory");
+    }
+
+    #[test]
+    fn memory_kernel_snapshot_reads_without_migrating_and_tracks_visible_versions() {
+        let now = time::OffsetDateTime::now_utc();
+        let missing = temp_db_path("snapshot-missing");
+        let empty = memory_kernel_snapshot(&missing, now);
+        assert!(empty.is_ok());
+        let empty = empty.unwrap_or_else(|_| unreachable!());
+        assert!(!missing.exists());
+
+        let unmigrated = temp_db_path("snapshot-unmigrated");
+        assert!(rusqlite::Connection::open(&unmigrated).is_ok());
+        let snapshot = memory_kernel_snapshot(&unmigrated, now);
+        assert!(matches!(snapshot, Ok(ref hash) if *hash == empty));
+        let conn = rusqlite::Connection::open(&unmigrated);
+        assert!(conn.is_ok());
+        let conn = conn.unwrap_or_else(|_| unreachable!());
+        let tables: rusqlite::Result<i64> =
+            conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get(0));
+        assert!(matches!(tables, Ok(0)));
+
+        let memory_db = temp_db_path("snapshot-memory");
+        let api = MemoryKernelApi::new(memory_db.clone());
+        assert!(api.migrate(false).is_ok());
+        let add = |resource: &str| {
+            api.add_constraint(AddConstraintRequest {
+                actor: "dev".to_string(),
+                action: "read".to_string(),
+                resource: resource.to_string(),
+                effect: ConstraintEffect::Allow,
+                note: None,
+                memory_id: None,
+                version: 1,
+                writer: "test".to_string(),
+                justification: "seed constraint".to_string(),
+                source_uri: "file:///constraint.md".to_string(),
+                source_hash: None,
+                evidence: Vec::new(),
                 confidence: Some(0.9),
+                truth_status: TruthStatus::Observed,
                 authority: Authority::Authoritative,
-                why: Why {
-                    included: true,
-                    reasons: vec!["fixture".to_string()],
-                    rule_scores: None,
-                },
+                created_at: None,
+                effective_at: None,
+                supersedes: Vec::new(),
+                contradicts: Vec::new(),
+                tags: Vec::new(),
+            })
+        };
+        assert!(add("repo").is_ok());
+        let as_of = time::OffsetDateTime::now_utc();
+        let first = memory_kernel_snapshot(&memory_db, as_of);
+        assert!(first.is_ok());
+        let first = first.unwrap_or_else(|_| unreachable!());
+        assert_ne!(first, empty);
+        let past = memory_kernel_snapshot(&memory_db, as_of - time::Duration::days(1));
+        assert!(matches!(past, Ok(ref hash) if *hash == empty));
+
+        assert!(add("wiki").is_ok());
+        let unchanged = memory_kernel_snapshot(&memory_db, as_of);
+        assert!(matches!(unchanged, Ok(ref hash) if *hash == first));
+        let later = memory_kernel_snapshot(&memory_db, time::OffsetDateTime::now_utc());
+        assert!(later.is_ok());
+        assert_ne!(later.unwrap_or_else(|_| unreachable!()), first);
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn api_memory_kernel_context_source_builds_step_packages() {
+        let trace_db = temp_db_path("api-context-source");
+        let memory_db = temp_db_path("api-memory-db");
+
+        let api = MemoryKernelApi::new(memory_db.clone());
+        assert!(api.migrate(false).is_ok());
+        assert!(api
+            .add_constraint(AddConstraintRequest {
+                actor: "dev".to_string(),
+                action: "read".to_string(),
+                resource: "repo".to_string(),
+                effect: ConstraintEffect::Allow,
+                note: None,
+                memory_id: None,
+                version: 1,
+                writer: "test".to_string(),
+                justification: "seed constraint".to_string(),
+                source_uri: "file:///constraint.md".to_string(),
+                source_hash: None,
+                evidence: Vec::new(),
+                confidence: Some(0.9),
+                truth_status: TruthStatus::Observed,
+                authority: Authority::Authoritative,
+                created_at: None,
+                effective_at: None,
+                supersedes: Vec::new(),
+                contradicts: Vec::new(),
+                tags: Vec::new(),
+            })
+            .is_ok());
+        assert!(api
+            .add_summary(AddSummaryRequest {
+                record_type: RecordType::Decision,
+                summary: "repo policy decision".to_string(),
+                memory_id: None,
+                version: 1,
+                writer: "test".to_string(),
+                justification: "seed decision".to_string(),
+                source_uri: "file:///decision.md".to_string(),
+                source_hash: None,
+                evidence: Vec::new(),
+                confidence: Some(0.8),
+                truth_status: TruthStatus::Observed,
+                authority: Authority::Derived,
+                created_at: None,
+                effective_at: None,
+                supersedes: Vec::new(),
+                contradicts: Vec::new(),
+                tags: Vec::new(),
+            })
+            .is_ok());
+
+        let trace_store = SqliteTraceStore::open(&trace_db);
+        assert!(trace_store.is_ok());
+        let trace_store = trace_store.unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let workflow_yaml = r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider:
+      provider_name: mock
+      model_id: x
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task:
+      context_queries:
+        - { mode: "policy", text: "Can dev read repo?", actor: "dev", action: "read", resource: "repo" }
+        - { mode: "recall", text: "repo policy decision", record_types: ["decision"] }
+    depends_on: []
+    gate_points: []
+gates: []
+defaults:
+  non_interactive: true
+"#;
+        let workflow = normalize_workflow_yaml(workflow_yaml);
+        assert!(workflow.is_ok());
+        let workflow = workflow.unwrap_or_else(|_| unreachable!());
+
+        let context_source = ApiMemoryKernelContextSource::new(&memory_db);
+        let trust_source = AllowAllTrustGateSource;
+        let human_gate = DefaultHumanGateDecider;
+        let write_applier = NoopProposedWriteApplier;
+        let summary = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &trust_source,
+            &human_gate,
+            &write_applier,
+            ProviderRegistry::default(),
+        )
+        .execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
+            },
+        );
+        let summary = match summary {
+            Ok(value) => value,
+            Err(err) => panic!("workflow execution failed: {err:#}"),
+        };
+        assert_eq!(
+            summary.status,
+            multi_agent_center_domain::RunStatus::Succeeded
+        );
+
+        let packages = trace_store.get_step_context_packages(summary.run_id);
+        assert!(packages.is_ok());
+        let packages = packages.unwrap_or_else(|_| unreachable!());
+        assert_eq!(packages.len(), 2);
+        assert_eq!(
+            packages[0].envelope.context_package.query.actor, "dev",
+            "first query should run policy mode ask() semantics"
+        );
+        assert_eq!(packages[0].envelope.context_package.query.action, "read");
+        assert_eq!(packages[0].envelope.context_package.query.resource, "repo");
+        assert!(packages[0]
+            .envelope
+            .context_package
+            .selected_items
+            .iter()
+            .any(|item| item.record_type == RecordType::Constraint));
+        assert!(packages[1]
+            .envelope
+            .context_package
+            .selected_items
+            .iter()
+            .all(|item| item.record_type == RecordType::Decision));
+    }
+
+    #[test]
+    fn trust_gate_decisions_are_persisted_per_memory_ref() {
+        let trace_db = temp_db_path("trust-gates");
+        let trace_store = SqliteTraceStore::open(&trace_db);
+        assert!(trace_store.is_ok());
+        let trace_store = trace_store.unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let workflow_yaml = r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider:
+      provider_name: mock
+      model_id: x
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task: { text: "a" }
+    depends_on: []
+    gate_points: []
+gates: []
+defaults:
+  non_interactive: true
+"#;
+        let workflow = normalize_workflow_yaml(workflow_yaml);
+        assert!(workflow.is_ok());
+        let workflow = workflow.unwrap_or_else(|_| unreachable!());
+
+        let mut by_step = BTreeMap::new();
+        by_step.insert(
+            "step_a".to_string(),
+            vec![fixture_context_package("step_a")],
+        );
+        let context_source = super::StaticContextPackageSource::with_step_packages(by_step);
+        let trust_source = SelectiveTrustGate;
+        let human_gate = ApproveHumanGate;
+        let write_applier = NoopProposedWriteApplier;
+        let summary = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &trust_source,
+            &human_gate,
+            &write_applier,
+            ProviderRegistry::default(),
+        )
+        .execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
+            },
+        );
+        let summary = match summary {
+            Ok(value) => value,
+            Err(err) => panic!("workflow execution failed: {err:#}"),
+        };
+
+        let conn = rusqlite::Connection::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        let trust_rows: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM step_gate_decisions WHERE run_id = ?1 AND gate_kind = ?2",
+                rusqlite::params![summary.run_id.to_string(), "trust"],
+                |row| row.get(0),
+            )
+            .unwrap_or_else(|_| unreachable!());
+        let rejected_rows: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM step_gate_decisions
+                 WHERE run_id = ?1 AND gate_kind = ?2 AND decision = ?3",
+                rusqlite::params![summary.run_id.to_string(), "trust", "rejected"],
+                |row| row.get(0),
+            )
+            .unwrap_or_else(|_| unreachable!());
+
+        assert_eq!(trust_rows, 2);
+        assert_eq!(rejected_rows, 1);
+        assert_eq!(summary.trust_excluded, 1);
+    }
+
+    #[test]
+    fn run_manifest_commits_to_the_memory_versions_injected_per_step() {
+        let trace_db = temp_db_path("manifest-injected-memory");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let workflow_yaml = r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider:
+      provider_name: mock
+      model_id: x
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task: { text: "a" }
+    depends_on: []
+    gate_points: []
+  - step_key: step_b
+    agent_name: planner
+    task: { text: "b" }
+    depends_on: [step_a]
+    gate_points: []
+gates: []
+defaults:
+  non_interactive: true
+"#;
+        let workflow = normalize_workflow_yaml(workflow_yaml).unwrap_or_else(|_| unreachable!());
+        let package = fixture_context_package("step_a");
+        let items = &package.context_package.selected_items;
+        // `SelectiveTrustGate` keeps odd versions only, so step_a sees just its first item.
+        let injected = vec![items[0].memory_version_id];
+        let trust_excluded = items[1].memory_version_id;
+        let mut by_step = BTreeMap::new();
+        by_step.insert("step_a".to_string(), vec![package.clone()]);
+        let context_source = super::StaticContextPackageSource::with_step_packages(by_step);
+        let summary = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &SelectiveTrustGate,
+            &ApproveHumanGate,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        )
+        .execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
+            },
+        )
+        .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+
+        let run = trace_store
+            .get_run(summary.run_id)
+            .unwrap_or_else(|_| unreachable!())
+            .unwrap_or_else(|| unreachable!());
+        let manifest = trace_store
+            .get_run_manifest(summary.run_id)
+            .unwrap_or_else(|_| unreachable!())
+            .unwrap_or_else(|| unreachable!());
+        assert_eq!(manifest["schema"], "run_manifest.v2");
+        let manifest_hash = run.manifest_hash.unwrap_or_else(|| unreachable!());
+        let memory = multi_agent_center_domain::verify_run_manifest(&manifest, &manifest_hash)
+            .unwrap_or_else(|err| panic!("manifest did not verify: {err:#}"))
+            .unwrap_or_else(|| unreachable!());
+
+        assert_eq!(
+            memory
+                .steps
+                .iter()
+                .map(|step| (step.step_key.as_str(), step.memory_versions))
+                .collect::<Vec<_>>(),
+            vec![("step_a", 1), ("step_b", 0)]
+        );
+        assert!(memory.step_matches("step_a", injected.clone()));
+        assert!(!memory.step_matches("step_a", vec![injected[0], trust_excluded]));
+        assert!(memory.step_matches("step_b", Vec::new()));
+
+        let mut tampered = manifest.clone();
+        tampered["injected_memory"]["steps"][0]["digest"] =
+            json!(multi_agent_center_domain::memory_version_set_digest(vec![
+                trust_excluded
+            ]));
+        assert!(multi_agent_center_domain::verify_run_manifest(&tampered, &manifest_hash).is_err());
+        let rehashed = super::hash_json(&tampered).unwrap_or_else(|_| unreachable!());
+        let err = multi_agent_center_domain::verify_run_manifest(&tampered, &rehashed)
+            .err()
+            .unwrap_or_else(|| unreachable!());
+        assert!(err.to_string().contains("does not match its step digests"));
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn declared_memory_dependencies_warn_or_fail_when_not_injected() {
+        let trace_db = temp_db_path("memory-dependencies");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let package = fixture_context_package("step_a");
+        let items = &package.context_package.selected_items;
+        // `SelectiveTrustGate` keeps the version 1 item and excludes the version 2 one.
+        let included = items[0].memory_id.to_string();
+        let excluded = items[1].memory_id.to_string();
+        let retired = MemoryId::new().to_string();
+        let workflow_yaml = format!(
+            r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider:
+      provider_name: mock
+      model_id: x
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task: {{ text: "a" }}
+    depends_on: []
+    gate_points: []
+    memory_dependencies:
+      - memory_id: "{included}"
+      - memory_id: "{excluded_prefix}*"
+        on_missing: warn
+  - step_key: step_b
+    agent_name: planner
+    task: {{ text: "b" }}
+    depends_on: []
+    gate_points: [review]
+    memory_dependencies:
+      - memory_id: "{retired}"
+gates:
+  - gate_name: review
+    gate_kind: human
+    required: true
+defaults:
+  non_interactive: true
+"#,
+            excluded_prefix = &excluded[..20],
+        );
+        let workflow = normalize_workflow_yaml(&workflow_yaml)
+            .unwrap_or_else(|err| panic!("workflow did not normalize: {err:#}"));
+        let mut by_step = BTreeMap::new();
+        by_step.insert("step_a".to_string(), vec![package.clone()]);
+        let context_source = super::StaticContextPackageSource::with_step_packages(by_step);
+        let summary = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &SelectiveTrustGate,
+            &ApproveHumanGate,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        )
+        .execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
+            },
+        )
+        .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+
+        assert_eq!(summary.status, multi_agent_center_domain::RunStatus::Failed);
+        assert_eq!(summary.provider_calls, 1);
+        assert_eq!(summary.human_approvals, 0);
+        let steps = trace_store
+            .get_step_records(summary.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(steps[0].status, StepStatus::Succeeded);
+        assert_eq!(steps[1].status, StepStatus::Failed);
+        assert_eq!(
+            steps[1].error_json.as_ref().map(|error| &error["code"]),
+            Some(&json!("memory_dependency_missing"))
+        );
+
+        let missing: Vec<(String, serde_json::Value)> = trace_store
+            .list_events_for_run(summary.run_id)
+            .unwrap_or_else(|_| unreachable!())
+            .into_iter()
+            .filter(|row| row.event.payload_json["warning_code"] == "memory_dependency_missing")
+            .map(|row| {
+                (
+                    row.event.payload_json["step_key"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    row.event.payload_json["dependency"].clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            missing,
+            vec![
+                (
+                    "step_a".to_string(),
+                    json!({
+                        "memory_id": format!("{}*", &excluded[..20]),
+                        "on_missing": "warn",
+                        "reason": "excluded_by_trust",
+                    })
+                ),
+                (
+                    "step_b".to_string(),
+                    json!({"memory_id": retired, "on_missing": "fail", "reason": "not_retrieved"})
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn sensitive_context_taints_downstream_steps_and_gates_external_providers() {
+        let trace_db = temp_db_path("taint-propagation");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let workflow_yaml = r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider:
+      provider_name: mock
+      model_id: x
+  - agent_name: publisher
+    role: publishing
+    provider:
+      provider_name: http_json
+      model_id: remote
+      params: { url: "http://127.0.0.1:9/", timeout_ms: 100 }
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task: { text: "a" }
+    depends_on: []
+    gate_points: []
+  - step_key: step_b
+    agent_name: planner
+    task: { text: "b" }
+    depends_on: [step_a]
+    gate_points: []
+  - step_key: step_c
+    agent_name: publisher
+    task: { text: "c" }
+    depends_on: [step_b]
+    gate_points: []
+gates:
+  - gate_name: security_review
+    gate_kind: human
+defaults:
+  non_interactive: true
+  taint:
+    sensitive_tags: [deploy]
+    elevated_gate: security_review
+"#;
+        let workflow = normalize_workflow_yaml(workflow_yaml)
+            .unwrap_or_else(|err| panic!("workflow did not normalize: {err:#}"));
+        let package = fixture_context_package("step_a");
+        let sensitive = package.context_package.selected_items[0].memory_version_id;
+        let mut by_step = BTreeMap::new();
+        by_step.insert("step_a".to_string(), vec![package]);
+        let context_source = super::StaticContextPackageSource::with_step_packages(by_step);
+        let summary = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        )
+        .execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
+            },
+        )
+        .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+
+        assert_eq!(summary.provider_calls, 2);
+        assert_eq!(summary.human_rejections, 1);
+        let steps = trace_store
+            .get_step_records(summary.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        let taint_of = |idx: usize| {
+            steps[idx]
+                .taint_json
+                .clone()
+                .map(serde_json::from_value::<super::StepTaint>)
+                .transpose()
+                .unwrap_or_else(|_| unreachable!())
+        };
+        assert_eq!(
+            taint_of(0),
+            Some(super::StepTaint {
+                memory_version_ids: vec![sensitive],
+                reasons: vec!["tag:deploy".to_string()],
+                inherited_from: Vec::new(),
+            })
+        );
+        assert_eq!(
+            taint_of(1).map(|taint| taint.inherited_from),
+            Some(vec!["step_a".to_string()])
+        );
+        assert_eq!(
+            taint_of(2).map(|taint| taint.inherited_from),
+            Some(vec!["step_b".to_string()])
+        );
+        assert_eq!(
+            steps
+                .iter()
+                .map(|step| step.status.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                StepStatus::Succeeded,
+                StepStatus::Succeeded,
+                StepStatus::Rejected
+            ]
+        );
+
+        let elevated: Vec<serde_json::Value> = trace_store
+            .list_events_for_run(summary.run_id)
+            .unwrap_or_else(|_| unreachable!())
+            .into_iter()
+            .filter(|row| row.event.payload_json["warning_code"] == "tainted_step_elevated_gate")
+            .map(|row| row.event.payload_json)
+            .collect();
+        assert_eq!(elevated.len(), 1);
+        assert_eq!(elevated[0]["step_key"], "step_c");
+        assert_eq!(elevated[0]["subject"], "external_provider");
+    }
+
+    #[test]
+    fn explain_trust_gating_embeds_summary_in_provider_request() {
+        let trace_db = temp_db_path("explain-trust-gating");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let workflow_yaml = r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider:
+      provider_name: mock
+      model_id: x
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task: { text: "a" }
+    depends_on: []
+    gate_points: []
+    constraints:
+      explain_trust_gating: true
+gates: []
+defaults:
+  non_interactive: true
+"#;
+        let workflow = normalize_workflow_yaml(workflow_yaml).unwrap_or_else(|_| unreachable!());
+
+        let mut by_step = BTreeMap::new();
+        by_step.insert(
+            "step_a".to_string(),
+            vec![fixture_context_package("step_a")],
+        );
+        let context_source = super::StaticContextPackageSource::with_step_packages(by_step);
+        let summary = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &SelectiveTrustGate,
+            &ApproveHumanGate,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        )
+        .execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
+            },
+        );
+        let summary = match summary {
+            Ok(value) => value,
+            Err(err) => panic!("workflow execution failed: {err:#}"),
+        };
+
+        let conn = rusqlite::Connection::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        let request_json: String = conn
+            .query_row(
+                "SELECT request_json FROM provider_calls WHERE run_id = ?1",
+                rusqlite::params![summary.run_id.to_string()],
+                |row| row.get(0),
+            )
+            .unwrap_or_else(|_| unreachable!());
+        let request: serde_json::Value =
+            serde_json::from_str(&request_json).unwrap_or_else(|_| unreachable!());
+        let gate_summary = &request["trust_gate_summary"];
+        assert_eq!(gate_summary["evaluated"], 2);
+        assert_eq!(gate_summary["included"], 1);
+        assert_eq!(gate_summary["excluded"], 1);
+        assert_eq!(gate_summary["excluded_memories"][0]["version"], 2);
+        assert_eq!(gate_summary["exclusion_categories"]["fixture"], 1);
+    }
+
+    #[test]
+    fn applied_persona_is_recorded_per_provider_call() {
+        let trace_db = temp_db_path("persona");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let workflow_yaml = r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+personas:
+  - persona_name: reviewer
+    persona_version: v2
+    system_prompt: "Review critically."
+  - persona_name: terse
+    persona_version: v1
+    system_prompt: "Answer briefly."
+    style_constraints: [no_preamble]
+agents:
+  - agent_name: planner
+    role: planning
+    provider:
+      provider_name: mock
+      model_id: x
+    persona: terse
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task: { text: "a" }
+    depends_on: []
+    gate_points: []
+  - step_key: step_b
+    agent_name: planner
+    task: { text: "b" }
+    depends_on: [step_a]
+    gate_points: []
+    persona: reviewer
+gates: []
+defaults:
+  non_interactive: true
+"#;
+        let workflow = normalize_workflow_yaml(workflow_yaml).unwrap_or_else(|_| unreachable!());
+        let context_source = super::StaticContextPackageSource::default();
+        let summary = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &super::AllowAllTrustGateSource,
+            &ApproveHumanGate,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        )
+        .execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
             },
-            ContextItem {
-                rank: 2,
-                memory_version_id: MemoryVersionId::new(),
-                memory_id: MemoryId::new(),
-                record_type: RecordType::Constraint,
-                version: 2,
-                truth_status: TruthStatus::Asserted,
-                confidence: Some(0.7),
-                authority: Authority::Authoritative,
-                why: Why {
-                    included: true,
-                    reasons: vec!["fixture".to_string()],
-                    rule_scores: None,
+        );
+        let summary = match summary {
+            Ok(value) => value,
+            Err(err) => panic!("workflow execution failed: {err:#}"),
+        };
+
+        let conn = rusqlite::Connection::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        let mut stmt = conn
+            .prepare(
+                "SELECT s.step_key, p.persona_json, p.request_json
+                 FROM provider_calls p JOIN steps s ON s.step_id = p.step_id
+                 WHERE p.run_id = ?1 ORDER BY s.step_index",
+            )
+            .unwrap_or_else(|_| unreachable!());
+        let rows: Vec<(String, String, String)> = stmt
+            .query_map(rusqlite::params![summary.run_id.to_string()], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .and_then(Iterator::collect)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(rows.len(), 2);
+
+        let persona = |raw: &str| -> serde_json::Value {
+            serde_json::from_str(raw).unwrap_or_else(|_| unreachable!())
+        };
+        let (step_a, step_b) = (persona(&rows[0].1), persona(&rows[1].1));
+        assert_eq!(rows[0].0, "step_a");
+        assert_eq!(step_a["persona_name"], "terse");
+        assert_eq!(step_a["persona_version"], "v1");
+        assert_eq!(step_b["persona_name"], "reviewer");
+        assert_ne!(step_a["persona_hash"], step_b["persona_hash"]);
+        assert_eq!(persona(&rows[0].2)["system_prompt"], "Answer briefly.");
+    }
+
+    #[test]
+    fn prompt_ref_resolves_template_and_records_hash_on_step() {
+        let trace_db = temp_db_path("prompt-ref");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+        let template = multi_agent_center_domain::PromptTemplateRecord::new(
+            "summarize",
+            "v3",
+            "Summarize the context.",
+        )
+        .unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.put_prompt_template(&template).is_ok());
+
+        let workflow_yaml = |prompt_ref: &str| {
+            format!(
+                r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider:
+      provider_name: mock
+      model_id: x
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task: {{ text: "a" }}
+    depends_on: []
+    gate_points: []
+    prompt_ref: {prompt_ref}
+gates: []
+defaults:
+  non_interactive: true
+"#
+            )
+        };
+        let context_source = super::StaticContextPackageSource::default();
+        let orchestrator = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &super::AllowAllTrustGateSource,
+            &ApproveHumanGate,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        );
+        let run = |prompt_ref: &str| {
+            let workflow = normalize_workflow_yaml(&workflow_yaml(prompt_ref))
+                .unwrap_or_else(|_| unreachable!());
+            orchestrator.execute_workflow(
+                &workflow,
+                RunConfig {
+                    non_interactive: true,
+                    ..RunConfig::default()
                 },
+            )
+        };
+
+        let summary = match run("summarize.v3") {
+            Ok(value) => value,
+            Err(err) => panic!("workflow execution failed: {err:#}"),
+        };
+        let steps = trace_store
+            .get_step_records(summary.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(
+            steps[0].prompt_template_hash.as_deref(),
+            Some(template.template_hash.as_str())
+        );
+
+        let conn = rusqlite::Connection::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        let request_json: String = conn
+            .query_row(
+                "SELECT request_json FROM provider_calls WHERE run_id = ?1",
+                rusqlite::params![summary.run_id.to_string()],
+                |row| row.get(0),
+            )
+            .unwrap_or_else(|_| unreachable!());
+        let request: serde_json::Value =
+            serde_json::from_str(&request_json).unwrap_or_else(|_| unreachable!());
+        assert_eq!(request["prompt"], "Summarize the context.");
+        assert_eq!(request["prompt_ref"], "summarize.v3");
+
+        let missing = run("summarize.v4");
+        assert!(missing.is_err_and(|err| format!("{err:#}").contains("unknown prompt template")));
+    }
+
+    /// Run `step_a` against an `http_json` endpoint that accepts connections but never answers,
+    /// followed by `step_b` that depends on it.
+    fn run_hanging_provider_workflow(
+        name: &str,
+        step_a_constraints: &str,
+        config: RunConfig,
+    ) -> (
+        SqliteTraceStore,
+        super::RunExecutionSummary,
+        std::time::Duration,
+    ) {
+        let trace_store =
+            SqliteTraceStore::open(&temp_db_path(name)).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+        let listener =
+            std::net::TcpListener::bind("127.0.0.1:0").unwrap_or_else(|_| unreachable!());
+        let port = listener
+            .local_addr()
+            .unwrap_or_else(|_| unreachable!())
+            .port();
+
+        let workflow_yaml = format!(
+            r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider: {{ provider_name: http_json, model_id: slow-model, params: {{ url: "http://127.0.0.1:{port}/", timeout_ms: 30000 }} }}
+  - agent_name: writer
+    role: writing
+    provider: {{ provider_name: mock, model_id: mock-model }}
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task: {{ text: "a" }}
+    constraints: {step_a_constraints}
+  - step_key: step_b
+    agent_name: writer
+    task: {{ text: "b" }}
+    depends_on: [step_a]
+gates: []
+defaults:
+  non_interactive: true
+"#
+        );
+        let workflow = normalize_workflow_yaml(&workflow_yaml).unwrap_or_else(|_| unreachable!());
+        let context_source = super::StaticContextPackageSource::default();
+        let started = std::time::Instant::now();
+        let summary = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &super::AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        )
+        .execute_workflow(&workflow, config)
+        .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+        let elapsed = started.elapsed();
+        drop(listener);
+        (trace_store, summary, elapsed)
+    }
+
+    fn step_statuses(
+        trace_store: &SqliteTraceStore,
+        run_id: multi_agent_center_domain::RunId,
+    ) -> BTreeMap<String, (StepStatus, Option<String>)> {
+        trace_store
+            .get_step_records(run_id)
+            .unwrap_or_else(|_| unreachable!())
+            .into_iter()
+            .map(|step| {
+                let code = step
+                    .error_json
+                    .as_ref()
+                    .and_then(|error| error.get("code").or_else(|| error.get("reason")))
+                    .and_then(serde_json::Value::as_str)
+                    .map(str::to_string);
+                (step.step_key, (step.status, code))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn step_timeout_abandons_provider_call_and_fails_run() {
+        let (trace_store, summary, elapsed) = run_hanging_provider_workflow(
+            "step-timeout",
+            "{ timeout_seconds: 1 }",
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
+            },
+        );
+        assert!(elapsed < std::time::Duration::from_secs(10), "{elapsed:?}");
+        assert_eq!(summary.status, multi_agent_center_domain::RunStatus::Failed);
+        assert_eq!(summary.steps_failed_or_rejected, 1);
+
+        let statuses = step_statuses(&trace_store, summary.run_id);
+        assert_eq!(
+            statuses["step_a"],
+            (StepStatus::TimedOut, Some("step_timed_out".to_string()))
+        );
+        assert_eq!(
+            statuses["step_b"],
+            (
+                StepStatus::Skipped,
+                Some("dependency_not_satisfied".to_string())
+            )
+        );
+        assert!(trace_store
+            .get_run(summary.run_id)
+            .is_ok_and(|run| run.is_some_and(|run| run.ended_at.is_some())));
+        let context_source = super::StaticContextPackageSource::default();
+        let orchestrator = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &super::AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        );
+        assert!(orchestrator
+            .replay_audit(summary.run_id)
+            .is_ok_and(|report| report.chain_valid));
+    }
+
+    #[test]
+    fn cancellation_abandons_running_step_and_cancels_pending_steps() {
+        let cancellation = super::CancellationToken::new();
+        let canceller = {
+            let cancellation = cancellation.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(std::time::Duration::from_millis(300));
+                cancellation.cancel();
+            })
+        };
+        let (trace_store, summary, elapsed) = run_hanging_provider_workflow(
+            "run-cancel",
+            "{}",
+            RunConfig {
+                non_interactive: true,
+                cancellation: cancellation.clone(),
+                ..RunConfig::default()
+            },
+        );
+        assert!(canceller.join().is_ok());
+        assert!(elapsed < std::time::Duration::from_secs(10), "{elapsed:?}");
+        assert_eq!(
+            summary.status,
+            multi_agent_center_domain::RunStatus::Cancelled
+        );
+
+        let statuses = step_statuses(&trace_store, summary.run_id);
+        assert_eq!(
+            statuses["step_a"],
+            (StepStatus::Cancelled, Some("run_cancelled".to_string()))
+        );
+        assert_eq!(
+            statuses["step_b"],
+            (StepStatus::Cancelled, Some("run_cancelled".to_string()))
+        );
+        let context_source = super::StaticContextPackageSource::default();
+        let orchestrator = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &super::AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        );
+        assert!(orchestrator
+            .replay_audit(summary.run_id)
+            .is_ok_and(|report| report.chain_valid));
+
+        // A token cancelled up front starts no step at all.
+        let (trace_store, summary, _) = run_hanging_provider_workflow(
+            "run-cancel-upfront",
+            "{}",
+            RunConfig {
+                cancellation,
+                ..RunConfig::default()
+            },
+        );
+        assert_eq!(
+            summary.status,
+            multi_agent_center_domain::RunStatus::Cancelled
+        );
+        let statuses = step_statuses(&trace_store, summary.run_id);
+        assert_eq!(statuses.len(), 2);
+        assert!(statuses
+            .values()
+            .all(|(status, _)| *status == StepStatus::Cancelled));
+    }
+
+    /// Mock adapter that records the thread each call runs on, by step key.
+    struct ThreadRecordingProvider {
+        inner: multi_agent_center_provider::MockProvider,
+        threads: std::sync::Arc<std::sync::Mutex<BTreeMap<String, std::thread::ThreadId>>>,
+    }
+
+    impl multi_agent_center_provider::ProviderAdapter for ThreadRecordingProvider {
+        fn provider_name(&self) -> &'static str {
+            "mock"
+        }
+
+        fn invoke(
+            &self,
+            request: &multi_agent_center_domain::StepRequest,
+        ) -> anyhow::Result<multi_agent_center_provider::ProviderInvocation> {
+            if let Ok(mut threads) = self.threads.lock() {
+                threads.insert(request.step_key.clone(), std::thread::current().id());
+            }
+            self.inner.invoke(request)
+        }
+    }
+
+    #[test]
+    fn provider_calls_run_inline_unless_step_can_be_interrupted() {
+        let trace_store = SqliteTraceStore::open(&temp_db_path("inline-provider-calls"))
+            .unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+        let workflow = normalize_workflow_yaml(
+            r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: writer
+    role: writing
+    provider: { provider_name: mock, model_id: mock-model }
+steps:
+  - step_key: unbounded
+    agent_name: writer
+    task: { text: "a" }
+  - step_key: bounded
+    agent_name: writer
+    task: { text: "b" }
+    constraints: { timeout_seconds: 30 }
+gates: []
+defaults:
+  non_interactive: true
+"#,
+        )
+        .unwrap_or_else(|_| unreachable!());
+        let threads = std::sync::Arc::default();
+        let providers = ProviderRegistry::default().with_adapter(ThreadRecordingProvider {
+            inner: multi_agent_center_provider::MockProvider::new(),
+            threads: std::sync::Arc::clone(&threads),
+        });
+        let context_source = super::StaticContextPackageSource::default();
+        let orchestrator = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &super::AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            providers,
+        );
+        let run = |cancellation| {
+            orchestrator
+                .execute_workflow(
+                    &workflow,
+                    RunConfig {
+                        non_interactive: true,
+                        cancellation,
+                        ..RunConfig::default()
+                    },
+                )
+                .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"))
+        };
+        let calling_thread = std::thread::current().id();
+        let recorded = |threads: &std::sync::Mutex<BTreeMap<String, std::thread::ThreadId>>| {
+            threads
+                .lock()
+                .map_or_else(|_| unreachable!(), |threads| threads.clone())
+        };
+
+        // Without a deadline or a shared token nothing can interrupt the call.
+        let summary = run(super::CancellationToken::new());
+        assert_eq!(
+            summary.status,
+            multi_agent_center_domain::RunStatus::Succeeded
+        );
+        let calls = recorded(&threads);
+        assert_eq!(calls["unbounded"], calling_thread);
+        assert_ne!(calls["bounded"], calling_thread);
+
+        // A token the caller keeps a clone of can cancel any call, so every call is offloaded.
+        let cancellation = super::CancellationToken::new();
+        let summary = run(cancellation.clone());
+        assert_eq!(
+            summary.status,
+            multi_agent_center_domain::RunStatus::Succeeded
+        );
+        assert!(!cancellation.is_cancelled());
+        let calls = recorded(&threads);
+        assert_ne!(calls["unbounded"], calling_thread);
+        assert_ne!(calls["bounded"], calling_thread);
+    }
+
+    fn run_fallback_workflow(
+        name: &str,
+        primary: &str,
+    ) -> (
+        std::path::PathBuf,
+        SqliteTraceStore,
+        multi_agent_center_domain::RunId,
+    ) {
+        let trace_db = temp_db_path(name);
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let workflow_yaml = format!(
+            r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider: {primary}
+    fallbacks:
+      - {{ provider_name: mock, model_id: fallback-model }}
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task: {{ text: "a" }}
+    depends_on: []
+    gate_points: []
+gates: []
+defaults:
+  non_interactive: true
+"#
+        );
+        let workflow = normalize_workflow_yaml(&workflow_yaml).unwrap_or_else(|_| unreachable!());
+        let context_source = super::StaticContextPackageSource::default();
+        let summary = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &super::AllowAllTrustGateSource,
+            &ApproveHumanGate,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        )
+        .execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
             },
-        ];
-        let package = ContextPackage {
-            context_package_id: format!("pkg-{step_key}"),
-            generated_at: now,
-            query: QueryRequest {
-                text: "t".to_string(),
-                actor: "a".to_string(),
-                action: "act".to_string(),
-                resource: "res".to_string(),
-                as_of: now,
+        );
+        let summary = match summary {
+            Ok(value) => value,
+            Err(err) => panic!("workflow execution failed: {err:#}"),
+        };
+        (trace_db, trace_store, summary.run_id)
+    }
+
+    fn provider_call_statuses(
+        trace_db: &std::path::Path,
+        run_id: multi_agent_center_domain::RunId,
+    ) -> Vec<(String, String)> {
+        let conn = rusqlite::Connection::open(trace_db).unwrap_or_else(|_| unreachable!());
+        let mut stmt = conn
+            .prepare(
+                "SELECT model_id, status FROM provider_calls
+                 WHERE run_id = ?1 ORDER BY started_at, rowid",
+            )
+            .unwrap_or_else(|_| unreachable!());
+        stmt.query_map(rusqlite::params![run_id.to_string()], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .and_then(Iterator::collect)
+        .unwrap_or_else(|_| unreachable!())
+    }
+
+    #[test]
+    fn retryable_primary_failure_falls_back_to_next_binding() {
+        let closed_port = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .map_or_else(|_| unreachable!(), |addr| addr.port());
+        let primary = format!(
+            "{{ provider_name: http_json, model_id: primary-model, params: {{ url: \"http://127.0.0.1:{closed_port}/\", timeout_ms: 500 }} }}"
+        );
+        let (trace_db, trace_store, run_id) = run_fallback_workflow("fallback-retryable", &primary);
+
+        let steps = trace_store
+            .get_step_records(run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(steps[0].status, StepStatus::Succeeded);
+        let selection = steps[0]
+            .provider_selection_json
+            .clone()
+            .unwrap_or_else(|| unreachable!());
+        assert_eq!(selection["selected"]["position"], 1);
+        assert_eq!(selection["selected"]["model_id"], "fallback-model");
+        assert_eq!(selection["attempts"][0]["outcome"], "retryable");
+        assert_eq!(selection["attempts"][1]["outcome"], "succeeded");
+
+        assert_eq!(
+            provider_call_statuses(&trace_db, run_id),
+            vec![
+                ("primary-model".to_string(), "failed".to_string()),
+                ("fallback-model".to_string(), "succeeded".to_string()),
+            ]
+        );
+        assert!(warning_codes(&trace_store, run_id).contains(&"provider_fallback".to_string()));
+    }
+
+    #[test]
+    fn fatal_primary_failure_does_not_fall_back() {
+        let (trace_db, trace_store, run_id) = run_fallback_workflow(
+            "fallback-fatal",
+            "{ provider_name: unsupported_provider, model_id: primary-model }",
+        );
+
+        let steps = trace_store
+            .get_step_records(run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(steps[0].status, StepStatus::Failed);
+        let selection = steps[0]
+            .provider_selection_json
+            .clone()
+            .unwrap_or_else(|| unreachable!());
+        assert_eq!(selection["selected"]["position"], 0);
+        assert_eq!(selection["attempts"][0]["outcome"], "fatal");
+        assert_eq!(
+            provider_call_statuses(&trace_db, run_id),
+            vec![("primary-model".to_string(), "failed".to_string())]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn load_balanced_agents_spread_steps_across_weighted_endpoints() {
+        let trace_db = temp_db_path("load-balanced");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+        let workflow = normalize_workflow_yaml(
+            r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider: { provider_name: mock, model_id: model-a }
+    load_balancing:
+      strategy: weighted
+      provider_weight: 2
+      endpoints:
+        - { provider_name: mock, model_id: model-b }
+  - agent_name: reviewer
+    role: review
+    provider: { provider_name: mock, model_id: model-r }
+steps:
+  - { step_key: step_a, agent_name: planner, task: { text: "a" } }
+  - { step_key: step_r, agent_name: reviewer, task: { text: "r" } }
+  - { step_key: step_b, agent_name: planner, task: { text: "b" } }
+  - { step_key: step_c, agent_name: planner, task: { text: "c" } }
+gates: []
+defaults:
+  non_interactive: true
+"#,
+        )
+        .unwrap_or_else(|_| unreachable!());
+        let context_source = super::StaticContextPackageSource::default();
+        let summary = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &super::AllowAllTrustGateSource,
+            &ApproveHumanGate,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        )
+        .execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
             },
-            determinism: DeterminismMetadata {
-                ruleset_version: "mk.v1".to_string(),
-                snapshot_id: "snapshot".to_string(),
-                tie_breakers: vec!["fixture".to_string()],
+        )
+        .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+
+        let steps = trace_store
+            .get_step_records(summary.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        let endpoints: Vec<(String, serde_json::Value)> = steps
+            .iter()
+            .map(|step| {
+                let endpoint = step
+                    .provider_selection_json
+                    .as_ref()
+                    .map_or(serde_json::Value::Null, |selection| {
+                        selection["endpoint"].clone()
+                    });
+                (step.step_key.clone(), endpoint)
+            })
+            .collect();
+        assert_eq!(
+            endpoints[1],
+            ("step_r".to_string(), serde_json::Value::Null)
+        );
+        for (index, ordinal, position, model_id) in [
+            (0, 0, 0, "model-a"),
+            (2, 1, 1, "model-b"),
+            (3, 2, 0, "model-a"),
+        ] {
+            let endpoint = &endpoints[index].1;
+            assert_eq!(endpoint["strategy"], "weighted");
+            assert_eq!(endpoint["ordinal"], ordinal);
+            assert_eq!(endpoint["position"], position);
+            assert_eq!(endpoint["model_id"], model_id);
+        }
+
+        let conn = rusqlite::Connection::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        let mut stmt = conn
+            .prepare(
+                "SELECT s.step_key, p.model_id FROM provider_calls p
+                 JOIN steps s ON s.step_id = p.step_id
+                 WHERE p.run_id = ?1 ORDER BY s.step_index",
+            )
+            .unwrap_or_else(|_| unreachable!());
+        let served: Vec<(String, String)> = stmt
+            .query_map(rusqlite::params![summary.run_id.to_string()], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .and_then(Iterator::collect)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(
+            served,
+            [
+                ("step_a", "model-a"),
+                ("step_r", "model-r"),
+                ("step_b", "model-b"),
+                ("step_c", "model-a"),
+            ]
+            .map(|(step, model)| (step.to_string(), model.to_string()))
+        );
+    }
+
+    #[test]
+    fn load_balanced_runs_continue_the_rotation_and_replays_keep_their_endpoints() {
+        let trace_store = SqliteTraceStore::open(&temp_db_path("load-balanced-runs"))
+            .unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+        let workflow = normalize_workflow_yaml(
+            r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider: { provider_name: mock, model_id: model-a }
+    load_balancing:
+      endpoints:
+        - { provider_name: mock, model_id: model-b }
+        - { provider_name: mock, model_id: model-c }
+steps:
+  - { step_key: step_a, agent_name: planner, task: { text: "a" } }
+gates: []
+defaults:
+  non_interactive: true
+"#,
+        )
+        .unwrap_or_else(|_| unreachable!());
+        let context_source = super::StaticContextPackageSource::default();
+        let orchestrator = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &super::AllowAllTrustGateSource,
+            &ApproveHumanGate,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        );
+        let served = |run_id| {
+            trace_store
+                .get_step_records(run_id)
+                .unwrap_or_else(|_| unreachable!())[0]
+                .provider_selection_json
+                .as_ref()
+                .map_or(serde_json::Value::Null, |selection| {
+                    selection["endpoint"]["model_id"].clone()
+                })
+        };
+
+        let runs: Vec<super::RunExecutionSummary> = (0..4)
+            .map(|_| {
+                orchestrator
+                    .execute_workflow(
+                        &workflow,
+                        RunConfig {
+                            non_interactive: true,
+                            ..RunConfig::default()
+                        },
+                    )
+                    .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"))
+            })
+            .collect();
+        assert_eq!(
+            runs.iter()
+                .map(|run| served(run.run_id))
+                .collect::<Vec<_>>(),
+            ["model-a", "model-b", "model-c", "model-a"].map(|model| json!(model))
+        );
+
+        let report = orchestrator
+            .replay_execute(runs[1].run_id)
+            .unwrap_or_else(|err| panic!("replay failed: {err:#}"));
+        assert!(report.reproduced, "{:?}", report.divergences);
+        assert_eq!(served(report.replay_run_id), json!("model-b"));
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn resume_run_reexecutes_failed_and_skipped_steps_under_the_same_run() {
+        let trace_db = temp_db_path("resume-failed");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+        let workflow = normalize_workflow_yaml(
+            r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider: { provider_name: mock, model_id: mock-model }
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task: { text: "a" }
+  - step_key: step_b
+    agent_name: planner
+    task: { text: "b" }
+    depends_on: [step_a]
+  - step_key: step_c
+    agent_name: planner
+    task: { text: "c" }
+    depends_on: [step_b]
+gates: []
+defaults:
+  non_interactive: true
+"#,
+        )
+        .unwrap_or_else(|_| unreachable!());
+        let script = multi_agent_center_provider::MockScript::from_yaml_str(
+            r#"
+responses:
+  - step_key: step_b
+    failure: { kind: error, message: "model overloaded" }
+  - step_key: step_b
+    message: "b done"
+"#,
+        )
+        .unwrap_or_else(|_| unreachable!());
+        let mock = multi_agent_center_provider::MockProvider::with_script(script);
+        let context_source =
+            super::StaticContextPackageSource::with_step_packages(BTreeMap::from([(
+                "step_b".to_string(),
+                vec![fixture_context_package("step_b")],
+            )]));
+        let orchestrator = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &super::AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default().with_adapter(mock),
+        );
+        let config = RunConfig {
+            non_interactive: true,
+            ..RunConfig::default()
+        };
+
+        let failed = orchestrator
+            .execute_workflow(&workflow, config.clone())
+            .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+        assert_eq!(failed.status, multi_agent_center_domain::RunStatus::Failed);
+        let statuses: Vec<StepStatus> = step_statuses(&trace_store, failed.run_id)
+            .into_values()
+            .map(|(status, _)| status)
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                StepStatus::Succeeded,
+                StepStatus::Failed,
+                StepStatus::Skipped
+            ]
+        );
+
+        let resumed = orchestrator
+            .resume_run(failed.run_id, config.clone())
+            .unwrap_or_else(|err| panic!("resume failed: {err:#}"));
+        assert_eq!(resumed.run_id, failed.run_id);
+        assert_eq!(
+            resumed.status,
+            multi_agent_center_domain::RunStatus::Succeeded
+        );
+        assert_eq!(resumed.steps_succeeded, 3);
+        assert!(step_statuses(&trace_store, failed.run_id)
+            .values()
+            .all(|(status, _)| *status == StepStatus::Succeeded));
+        // The resumed step kept the context its failed attempt recorded.
+        let packages = trace_store
+            .get_step_context_packages(failed.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(packages.len(), 1);
+
+        let resumed_payload = trace_store
+            .list_events_for_run(failed.run_id)
+            .unwrap_or_else(|_| unreachable!())
+            .into_iter()
+            .find(|row| row.event.event_type == TraceEventType::RunResumed)
+            .map(|row| row.event.payload_json);
+        assert_eq!(
+            resumed_payload,
+            Some(json!({"previous_status": "failed", "resumed_steps": ["step_b", "step_c"]}))
+        );
+        assert!(orchestrator
+            .replay_audit(failed.run_id)
+            .is_ok_and(|report| report.chain_valid));
+
+        let err = orchestrator.resume_run(failed.run_id, config);
+        assert!(err.is_err_and(|err| err.to_string().contains("is succeeded")));
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn when_conditions_route_on_step_output_and_record_their_evaluation() {
+        let trace_db = temp_db_path("when-conditions");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+        let workflow = normalize_workflow_yaml(
+            r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider: { provider_name: mock, model_id: mock-model }
+steps:
+  - step_key: triage
+    agent_name: planner
+    task: { text: "route the ticket" }
+  - step_key: billing
+    agent_name: planner
+    task: { text: "billing" }
+    depends_on: [triage]
+    when: 'steps.triage.payload.payload.route == "billing"'
+  - step_key: support
+    agent_name: planner
+    task: { text: "support" }
+    depends_on: [triage]
+    when: 'steps.triage.payload.payload.route == "support"'
+  - step_key: support_followup
+    agent_name: planner
+    task: { text: "follow up" }
+    depends_on: [support]
+gates: []
+defaults:
+  non_interactive: true
+"#,
+        )
+        .unwrap_or_else(|_| unreachable!());
+        let script = multi_agent_center_provider::MockScript::from_yaml_str(
+            r#"
+responses:
+  - step_key: triage
+    message: "routed"
+    payload: { route: billing }
+"#,
+        )
+        .unwrap_or_else(|_| unreachable!());
+        let mock = multi_agent_center_provider::MockProvider::with_script(script);
+        let context_source = super::StaticContextPackageSource::default();
+        let orchestrator = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &super::AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default().with_adapter(mock),
+        );
+
+        let summary = orchestrator
+            .execute_workflow(
+                &workflow,
+                RunConfig {
+                    non_interactive: true,
+                    ..RunConfig::default()
+                },
+            )
+            .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+        assert_eq!(
+            summary.status,
+            multi_agent_center_domain::RunStatus::Succeeded
+        );
+        let statuses = step_statuses(&trace_store, summary.run_id);
+        assert_eq!(statuses["triage"], (StepStatus::Succeeded, None));
+        assert_eq!(statuses["billing"], (StepStatus::Succeeded, None));
+        assert_eq!(
+            statuses["support"],
+            (StepStatus::Skipped, Some("condition_not_met".to_string()))
+        );
+        assert_eq!(
+            statuses["support_followup"],
+            (
+                StepStatus::Skipped,
+                Some("dependency_not_satisfied".to_string())
+            )
+        );
+
+        let evaluations: Vec<serde_json::Value> = trace_store
+            .list_events_for_run(summary.run_id)
+            .unwrap_or_else(|_| unreachable!())
+            .into_iter()
+            .filter(|row| row.event.event_type == TraceEventType::ConditionEvaluated)
+            .map(|row| row.event.payload_json)
+            .collect();
+        assert_eq!(
+            evaluations,
+            vec![
+                json!({
+                    "step_key": "billing",
+                    "when": "steps.triage.payload.payload.route == \"billing\"",
+                    "observed_step": "triage",
+                    "observed": "billing",
+                    "result": true,
+                }),
+                json!({
+                    "step_key": "support",
+                    "when": "steps.triage.payload.payload.route == \"support\"",
+                    "observed_step": "triage",
+                    "observed": "billing",
+                    "result": false,
+                }),
+            ]
+        );
+        assert!(orchestrator
+            .replay_audit(summary.run_id)
+            .is_ok_and(|report| report.chain_valid));
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn foreach_steps_fan_out_over_step_output_and_aggregate_their_children() {
+        let trace_db = temp_db_path("foreach");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+        let workflow = normalize_workflow_yaml(
+            r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider: { provider_name: mock, model_id: mock-model }
+steps:
+  - step_key: plan
+    agent_name: planner
+    task: { text: "list the tickets" }
+  - step_key: review
+    agent_name: planner
+    task: { text: "review the ticket" }
+    depends_on: [plan]
+    foreach: 'steps.plan.payload.payload.tickets'
+  - step_key: summary
+    agent_name: planner
+    task: { text: "summarize" }
+    depends_on: [review]
+    when: 'steps.review.payload.items[1].payload.verdict == "fine"'
+gates: []
+defaults:
+  non_interactive: true
+"#,
+        )
+        .unwrap_or_else(|_| unreachable!());
+        let script = multi_agent_center_provider::MockScript::from_yaml_str(
+            r#"
+responses:
+  - step_key: plan
+    payload: { tickets: ["T-1", "T-2"] }
+  - step_key: "review[0]"
+    payload: { verdict: ok }
+  - step_key: "review[1]"
+    failure: { kind: error, message: "review crashed" }
+  - step_key: "review[1]"
+    payload: { verdict: fine }
+"#,
+        )
+        .unwrap_or_else(|_| unreachable!());
+        let mock = multi_agent_center_provider::MockProvider::with_script(script);
+        let context_source = super::StaticContextPackageSource::default();
+        let orchestrator = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &super::AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default().with_adapter(mock),
+        );
+        let config = RunConfig {
+            non_interactive: true,
+            ..RunConfig::default()
+        };
+
+        let failed = orchestrator
+            .execute_workflow(&workflow, config.clone())
+            .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+        assert_eq!(failed.status, multi_agent_center_domain::RunStatus::Failed);
+        let statuses = step_statuses(&trace_store, failed.run_id);
+        assert_eq!(statuses["review[0]"], (StepStatus::Succeeded, None));
+        assert_eq!(statuses["review[1]"].0, StepStatus::Failed);
+        assert_eq!(
+            statuses["review"],
+            (
+                StepStatus::Failed,
+                Some("foreach_item_not_succeeded".to_string())
+            )
+        );
+        assert_eq!(
+            statuses["summary"],
+            (
+                StepStatus::Skipped,
+                Some("dependency_not_satisfied".to_string())
+            )
+        );
+        let records = trace_store
+            .get_step_records(failed.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        let child = records
+            .iter()
+            .find(|record| record.step_key == "review[1]")
+            .unwrap_or_else(|| unreachable!());
+        assert_eq!(
+            child.task_payload_json,
+            json!({"text": "review the ticket", "item": "T-2", "item_index": 1})
+        );
+        let first_child_id = records
+            .iter()
+            .find(|record| record.step_key == "review[0]")
+            .map(|record| record.step_id);
+
+        // The resumed run reuses the recorded expansion and runs both children again, since the
+        // succeeded child's output is needed for the aggregate.
+        let resumed = orchestrator
+            .resume_run(failed.run_id, config)
+            .unwrap_or_else(|err| panic!("resume failed: {err:#}"));
+        assert_eq!(
+            resumed.status,
+            multi_agent_center_domain::RunStatus::Succeeded
+        );
+        let records = trace_store
+            .get_step_records(failed.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(records.len(), 5);
+        assert!(records
+            .iter()
+            .all(|record| record.status == StepStatus::Succeeded));
+        assert_eq!(
+            records
+                .iter()
+                .find(|record| record.step_key == "review[0]")
+                .map(|record| record.step_id),
+            first_child_id
+        );
+
+        let events = trace_store
+            .list_events_for_run(failed.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        let expansions: Vec<serde_json::Value> = events
+            .iter()
+            .filter(|row| row.event.event_type == TraceEventType::ForeachExpanded)
+            .map(|row| row.event.payload_json.clone())
+            .collect();
+        assert_eq!(
+            expansions,
+            vec![json!({
+                "step_key": "review",
+                "foreach": "steps.plan.payload.payload.tickets",
+                "source_step": "plan",
+                "items": ["T-1", "T-2"],
+                "child_keys": ["review[0]", "review[1]"],
+            })]
+        );
+        let evaluation = events
+            .iter()
+            .find(|row| row.event.event_type == TraceEventType::ConditionEvaluated)
+            .map(|row| row.event.payload_json.clone());
+        assert_eq!(
+            evaluation
+                .as_ref()
+                .and_then(|payload| payload.get("observed").cloned()),
+            Some(json!("fine"))
+        );
+        assert!(orchestrator
+            .replay_audit(failed.run_id)
+            .is_ok_and(|report| report.chain_valid));
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn iterate_steps_rerun_with_their_previous_output_until_converged() {
+        let trace_db = temp_db_path("iterate");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+        let workflow = |max_iterations: u32| {
+            normalize_workflow_yaml(&format!(
+                r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: writer
+    role: writing
+    provider: {{ provider_name: mock, model_id: mock-model }}
+steps:
+  - step_key: draft
+    agent_name: writer
+    task: {{ text: "draft the reply" }}
+    iterate:
+      max_iterations: {max_iterations}
+      until: 'output.payload.payload.approved == true'
+  - step_key: publish
+    agent_name: writer
+    task: {{ text: "publish" }}
+    depends_on: [draft]
+    when: 'steps.draft.payload.payload.revision == 2'
+gates: []
+defaults:
+  non_interactive: true
+"#
+            ))
+            .unwrap_or_else(|_| unreachable!())
+        };
+        let script = multi_agent_center_provider::MockScript::from_yaml_str(
+            r#"
+responses:
+  - step_key: "draft#1"
+    payload: { approved: false, revision: 1 }
+  - step_key: "draft#2"
+    failure: { kind: error, message: "writer crashed" }
+  - step_key: "draft#2"
+    payload: { approved: true, revision: 2 }
+"#,
+        )
+        .unwrap_or_else(|_| unreachable!());
+        let mock = multi_agent_center_provider::MockProvider::with_script(script);
+        let context_source = super::StaticContextPackageSource::default();
+        let orchestrator = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &super::AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default().with_adapter(mock),
+        );
+        let config = RunConfig {
+            non_interactive: true,
+            ..RunConfig::default()
+        };
+
+        let failed = orchestrator
+            .execute_workflow(&workflow(3), config.clone())
+            .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+        assert_eq!(failed.status, multi_agent_center_domain::RunStatus::Failed);
+        let statuses = step_statuses(&trace_store, failed.run_id);
+        assert_eq!(statuses["draft#1"], (StepStatus::Succeeded, None));
+        assert_eq!(statuses["draft#2"].0, StepStatus::Failed);
+        assert_eq!(
+            statuses["draft"],
+            (
+                StepStatus::Failed,
+                Some("iteration_not_succeeded".to_string())
+            )
+        );
+        let records = trace_store
+            .get_step_records(failed.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        let second = records
+            .iter()
+            .find(|record| record.step_key == "draft#2")
+            .unwrap_or_else(|| unreachable!());
+        assert_eq!(second.task_payload_json["iteration"], json!(2));
+        assert_eq!(
+            second.task_payload_json["previous_output"]["payload"]["payload"],
+            json!({"approved": false, "revision": 1})
+        );
+
+        // The resumed run replays the recorded iterations and runs only the failed one again.
+        let resumed = orchestrator
+            .resume_run(failed.run_id, config.clone())
+            .unwrap_or_else(|err| panic!("resume failed: {err:#}"));
+        assert_eq!(
+            resumed.status,
+            multi_agent_center_domain::RunStatus::Succeeded
+        );
+        let records = trace_store
+            .get_step_records(failed.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(records.len(), 4);
+        assert!(records
+            .iter()
+            .all(|record| record.status == StepStatus::Succeeded));
+        let events = trace_store
+            .list_events_for_run(failed.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        let evaluations: Vec<(serde_json::Value, serde_json::Value)> = events
+            .iter()
+            .filter(|row| row.event.event_type == TraceEventType::IterationEvaluated)
+            .map(|row| {
+                (
+                    row.event.payload_json["child_key"].clone(),
+                    row.event.payload_json["converged"].clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            evaluations,
+            vec![
+                (json!("draft#1"), json!(false)),
+                (json!("draft#2"), json!(true))
+            ]
+        );
+        assert_eq!(
+            events
+                .iter()
+                .filter(|row| row.event.event_type == TraceEventType::IterationStarted)
+                .count(),
+            2
+        );
+        assert!(orchestrator
+            .replay_audit(failed.run_id)
+            .is_ok_and(|report| report.chain_valid));
+
+        // A step that never converges fails once its iterations run out.
+        let exhausted = orchestrator
+            .execute_workflow(&workflow(1), config)
+            .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+        let statuses = step_statuses(&trace_store, exhausted.run_id);
+        assert_eq!(
+            statuses["draft"],
+            (
+                StepStatus::Failed,
+                Some("iteration_not_converged".to_string())
+            )
+        );
+        assert!(!statuses.contains_key("draft#2"));
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn vote_steps_run_every_agent_and_record_the_aggregated_decision() {
+        let trace_db = temp_db_path("vote");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+        let workflow = |vote: &str| {
+            normalize_workflow_yaml(&format!(
+                r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: a
+    role: triage
+    provider: {{ provider_name: mock, model_id: mock-model }}
+  - agent_name: b
+    role: triage
+    provider: {{ provider_name: mock, model_id: mock-model }}
+  - agent_name: c
+    role: triage
+    provider: {{ provider_name: mock, model_id: mock-model }}
+  - agent_name: referee
+    role: judging
+    provider: {{ provider_name: mock, model_id: mock-model }}
+steps:
+  - step_key: classify
+    task: {{ text: "classify the ticket" }}
+    vote: {vote}
+  - step_key: route
+    agent_name: a
+    task: {{ text: "route the ticket" }}
+    depends_on: [classify]
+    when: 'steps.classify.payload.payload.label == "billing"'
+gates: []
+defaults:
+  non_interactive: true
+"#
+            ))
+            .unwrap_or_else(|err| panic!("invalid workflow: {err:#}"))
+        };
+        let script = multi_agent_center_provider::MockScript::from_yaml_str(
+            r#"
+responses:
+  - step_key: "classify@a"
+    payload: { label: billing, confidence: 0.9 }
+  - step_key: "classify@b"
+    payload: { label: tech, confidence: 0.6 }
+  - step_key: "classify@c"
+    payload: { label: billing, confidence: 0.7 }
+  - step_key: "classify@judge:referee"
+    payload: { selected: b }
+  - step_key: "classify@a"
+    payload: { label: billing }
+  - step_key: "classify@b"
+    payload: { label: tech }
+  - step_key: "classify@c"
+    payload: { label: billing }
+"#,
+        )
+        .unwrap_or_else(|_| unreachable!());
+        let mock = multi_agent_center_provider::MockProvider::with_script(script);
+        let context_source = super::StaticContextPackageSource::default();
+        let orchestrator = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &super::AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default().with_adapter(mock),
+        );
+        let config = RunConfig {
+            non_interactive: true,
+            ..RunConfig::default()
+        };
+        let decided = |run_id| {
+            let events = trace_store
+                .list_events_for_run(run_id)
+                .unwrap_or_else(|_| unreachable!());
+            let decided: Vec<&multi_agent_center_domain::TraceEvent> = events
+                .iter()
+                .map(|row| &row.event)
+                .filter(|event| event.event_type == TraceEventType::VoteDecided)
+                .collect();
+            assert_eq!(decided.len(), 1);
+            // The decision is traced on the vote step itself.
+            let vote_step = trace_store
+                .get_step_records(run_id)
+                .unwrap_or_else(|_| unreachable!())
+                .into_iter()
+                .find(|step| step.step_key == "classify")
+                .map(|step| step.step_id);
+            assert_eq!(decided[0].step_id, vote_step);
+            decided[0].payload_json.clone()
+        };
+
+        // Majority voting compares the ballot path; the first agent with the winning ballot
+        // supplies the step's output.
+        let majority = orchestrator
+            .execute_workflow(
+                &workflow("{ agents: [a, b, c], ballot: output.payload.payload.label }"),
+                config.clone(),
+            )
+            .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+        assert_eq!(
+            majority.status,
+            multi_agent_center_domain::RunStatus::Succeeded
+        );
+        let statuses = step_statuses(&trace_store, majority.run_id);
+        for step_key in [
+            "classify",
+            "classify@a",
+            "classify@b",
+            "classify@c",
+            "route",
+        ] {
+            assert_eq!(
+                statuses[step_key],
+                (StepStatus::Succeeded, None),
+                "{step_key}"
+            );
+        }
+        let decision = decided(majority.run_id);
+        assert_eq!(decision["aggregator"], json!("majority"));
+        assert_eq!(decision["selected"], json!("a"));
+        assert_eq!(
+            decision["tally"],
+            json!([
+                {"ballot": "billing", "agents": ["a", "c"]},
+                {"ballot": "tech", "agents": ["b"]},
+            ])
+        );
+        assert_eq!(decision["candidates"].as_array().map(Vec::len), Some(3));
+        assert_eq!(
+            decision["candidates"][1]["output"]["payload"]["payload"]["label"],
+            json!("tech")
+        );
+        assert_eq!(
+            trace_store
+                .get_step_provider_calls(majority.run_id)
+                .map(|calls| calls.len())
+                .ok(),
+            Some(4)
+        );
+
+        // The judge step sees every candidate's output and picks the winner.
+        let judged = orchestrator
+            .execute_workflow(
+                &workflow("{ agents: [a, b, c], aggregator: judge, judge_agent: referee }"),
+                config.clone(),
+            )
+            .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+        let statuses = step_statuses(&trace_store, judged.run_id);
+        assert_eq!(statuses["classify"], (StepStatus::Succeeded, None));
+        assert_eq!(statuses["classify@judge:referee"].0, StepStatus::Succeeded);
+        assert_eq!(statuses["route"].1.as_deref(), Some("condition_not_met"));
+        let decision = decided(judged.run_id);
+        assert_eq!(decision["selected"], json!("b"));
+        assert_eq!(decision["judge_step"], json!("classify@judge:referee"));
+        let records = trace_store
+            .get_step_records(judged.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        let judge = records
+            .iter()
+            .find(|record| record.step_key == "classify@judge:referee")
+            .unwrap_or_else(|| unreachable!());
+        assert_eq!(judge.agent_name, "referee");
+        assert_eq!(
+            judge.task_payload_json["candidates"][2]["agent"],
+            json!("c")
+        );
+
+        // A non-interactive run rejects every candidate put to the human gate.
+        let human = orchestrator
+            .execute_workflow(
+                &workflow("{ agents: [a, b, c], aggregator: human_gate }"),
+                config,
+            )
+            .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+        let statuses = step_statuses(&trace_store, human.run_id);
+        assert_eq!(
+            statuses["classify"],
+            (StepStatus::Rejected, Some("vote_rejected".to_string()))
+        );
+        let gates = trace_store
+            .get_step_gate_decisions(human.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(
+            gates
+                .iter()
+                .filter(|gate| gate.decision.gate_name.starts_with("vote."))
+                .count(),
+            3
+        );
+        assert!(orchestrator
+            .replay_audit(human.run_id)
+            .is_ok_and(|report| report.chain_valid));
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn workflow_call_steps_run_the_called_workflow_as_a_linked_nested_run() {
+        let trace_db = temp_db_path("workflow-call");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+        let dir = std::env::temp_dir().join(format!("mac-workflow-call-{}", ulid::Ulid::new()));
+        assert!(std::fs::create_dir_all(&dir).is_ok());
+        assert!(std::fs::write(
+            dir.join("child.yaml"),
+            r#"
+workflow_name: child
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: summarizer
+    role: summarizing
+    provider: { provider_name: mock, model_id: mock-model }
+steps:
+  - step_key: summarize
+    agent_name: summarizer
+    task: { text: "summarize" }
+gates: []
+"#,
+        )
+        .is_ok());
+        assert!(std::fs::write(
+            dir.join("parent.yaml"),
+            r#"
+workflow_name: parent
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: writer
+    role: writing
+    provider: { provider_name: mock, model_id: mock-model }
+steps:
+  - step_key: prepare
+    agent_name: writer
+    task: { text: "prepare" }
+  - step_key: nested
+    depends_on: [prepare]
+    workflow_call: { path: child.yaml }
+gates: []
+defaults:
+  non_interactive: true
+"#,
+        )
+        .is_ok());
+        let workflow =
+            multi_agent_center_workflow::load_workflow_from_path(&dir.join("parent.yaml"))
+                .unwrap_or_else(|err| panic!("workflow load failed: {err:#}"));
+        let script = multi_agent_center_provider::MockScript::from_yaml_str(
+            r#"
+responses:
+  - step_key: summarize
+    payload: { summary: "ok" }
+  - step_key: summarize
+    failure: { kind: error, message: "summarizer crashed" }
+"#,
+        )
+        .unwrap_or_else(|_| unreachable!());
+        let mock = multi_agent_center_provider::MockProvider::with_script(script);
+        let context_source = super::StaticContextPackageSource::default();
+        let orchestrator = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &super::AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default().with_adapter(mock),
+        );
+        let config = RunConfig {
+            non_interactive: true,
+            labels: BTreeMap::from([("team".to_string(), "docs".to_string())]),
+            ..RunConfig::default()
+        };
+
+        let succeeded = orchestrator
+            .execute_workflow(&workflow, config.clone())
+            .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+        assert_eq!(
+            succeeded.status,
+            multi_agent_center_domain::RunStatus::Succeeded
+        );
+        let nested_step = trace_store
+            .get_step_records(succeeded.run_id)
+            .unwrap_or_else(|_| unreachable!())
+            .into_iter()
+            .find(|record| record.step_key == "nested")
+            .unwrap_or_else(|| unreachable!());
+        assert_eq!(nested_step.status, StepStatus::Succeeded);
+        let children = trace_store
+            .list_child_runs(succeeded.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(children.len(), 1);
+        let child = &children[0];
+        assert_eq!(child.workflow_name, "child");
+        assert_eq!(
+            child.status,
+            multi_agent_center_domain::RunStatus::Succeeded
+        );
+        assert_eq!(child.labels, config.labels);
+        let parent_step = child.parent_step.clone().unwrap_or_else(|| unreachable!());
+        assert_eq!(parent_step.run_id, succeeded.run_id);
+        assert_eq!(parent_step.step_id, nested_step.step_id);
+        assert_eq!(parent_step.step_key, "nested");
+        let finished: Vec<serde_json::Value> = trace_store
+            .list_events_for_run(succeeded.run_id)
+            .unwrap_or_else(|_| unreachable!())
+            .into_iter()
+            .filter(|row| row.event.event_type == TraceEventType::WorkflowCallFinished)
+            .map(|row| row.event.payload_json)
+            .collect();
+        assert_eq!(
+            finished,
+            vec![json!({
+                "step_key": "nested",
+                "child_run_id": child.run_id,
+                "status": "succeeded",
+            })]
+        );
+        assert!(orchestrator
+            .replay_audit(child.run_id)
+            .is_ok_and(|report| report.chain_valid));
+
+        // A failed nested run fails the calling step and the parent run.
+        let failed = orchestrator
+            .execute_workflow(&workflow, config)
+            .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+        assert_eq!(failed.status, multi_agent_center_domain::RunStatus::Failed);
+        assert_eq!(
+            step_statuses(&trace_store, failed.run_id)["nested"],
+            (
+                StepStatus::Failed,
+                Some("workflow_call_not_succeeded".to_string())
+            )
+        );
+        let children = trace_store
+            .list_child_runs(failed.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(children.len(), 1);
+        assert_eq!(
+            children[0].status,
+            multi_agent_center_domain::RunStatus::Failed
+        );
+
+        // Replays answer the nested run from its own trace and nest a replay of it.
+        let report = orchestrator
+            .replay_execute(succeeded.run_id)
+            .unwrap_or_else(|err| panic!("replay failed: {err:#}"));
+        assert!(report.reproduced, "{:?}", report.divergences);
+        assert_eq!(report.provider_calls_replayed, 2);
+        for (source, replay) in [
+            (succeeded.run_id, report.replay_run_id),
+            (
+                failed.run_id,
+                orchestrator
+                    .replay_execute(failed.run_id)
+                    .unwrap_or_else(|err| panic!("replay failed: {err:#}"))
+                    .replay_run_id,
+            ),
+        ] {
+            let source_child = &trace_store
+                .list_child_runs(source)
+                .unwrap_or_else(|_| unreachable!())[0];
+            let replayed = trace_store
+                .list_child_runs(replay)
+                .unwrap_or_else(|_| unreachable!());
+            assert_eq!(replayed.len(), 1);
+            assert_eq!(replayed[0].replay_of_run_id, Some(source_child.run_id));
+            assert_eq!(replayed[0].status, source_child.status);
+        }
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn scripted_mock_failures_drive_fallback_and_record_token_usage() {
+        let trace_db = temp_db_path("scripted-mock");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let workflow = normalize_workflow_yaml(
+            r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider: { provider_name: mock, model_id: primary-model }
+    fallbacks:
+      - { provider_name: mock, model_id: fallback-model }
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task: { text: "a" }
+    depends_on: []
+    gate_points: []
+gates: []
+defaults:
+  non_interactive: true
+"#,
+        )
+        .unwrap_or_else(|_| unreachable!());
+        let script = multi_agent_center_provider::MockScript::from_yaml_str(
+            r#"
+responses:
+  - step_key: step_a
+    failure: { kind: transport, message: "connection reset" }
+  - step_key: step_a
+    message: "plan ready"
+    latency_ms: 120
+    input_tokens: 40
+    output_tokens: 12
+"#,
+        )
+        .unwrap_or_else(|_| unreachable!());
+        let mock = multi_agent_center_provider::MockProvider::with_script(script);
+        let context_source = super::StaticContextPackageSource::default();
+        let summary = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &super::AllowAllTrustGateSource,
+            &ApproveHumanGate,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default().with_adapter(mock),
+        )
+        .execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
             },
-            answer: Answer {
-                result: AnswerResult::Allow,
-                why: "fixture".to_string(),
+        )
+        .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+
+        let steps = trace_store
+            .get_step_records(summary.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(steps[0].status, StepStatus::Succeeded);
+        assert_eq!(
+            provider_call_statuses(&trace_db, summary.run_id),
+            vec![
+                ("primary-model".to_string(), "failed".to_string()),
+                ("fallback-model".to_string(), "succeeded".to_string()),
+            ]
+        );
+        let calls = trace_store
+            .get_step_provider_calls(summary.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        let succeeded = calls
+            .iter()
+            .find(|row| row.provider_call.status == "succeeded")
+            .unwrap_or_else(|| unreachable!());
+        assert_eq!(
+            succeeded.provider_call.response_json["message"],
+            "plan ready"
+        );
+        assert_eq!(succeeded.provider_call.latency_ms, Some(120));
+        assert_eq!(succeeded.provider_call.input_tokens, Some(40));
+        assert_eq!(succeeded.provider_call.output_tokens, Some(12));
+    }
+
+    fn run_scored_workflow(
+        name: &str,
+        scoring: &str,
+    ) -> (
+        SqliteTraceStore,
+        multi_agent_center_domain::RunId,
+        Vec<String>,
+    ) {
+        let trace_db = temp_db_path(name);
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let package = fixture_context_package("step_a");
+        let outcome_db = temp_db_path(&format!("{name}-outcome"));
+        let conn = rusqlite::Connection::open(&outcome_db).unwrap_or_else(|_| unreachable!());
+        for item in &package.context_package.selected_items {
+            assert!(
+                memory_kernel_outcome_store_sqlite::seed_minimal_memory_record(
+                    &conn,
+                    item.memory_id,
+                    item.version
+                )
+                .is_ok()
+            );
+        }
+        let outcome_store =
+            super::SqliteOutcomeStore::open(&outcome_db).unwrap_or_else(|_| unreachable!());
+        assert!(outcome_store.migrate().is_ok());
+
+        let workflow_yaml = format!(
+            r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider:
+      provider_name: mock
+      model_id: x
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task: {{ text: "a" }}
+    depends_on: []
+    gate_points: []
+    scoring: {scoring}
+gates: []
+defaults:
+  non_interactive: true
+"#
+        );
+        let workflow = normalize_workflow_yaml(&workflow_yaml).unwrap_or_else(|_| unreachable!());
+        let mut by_step = BTreeMap::new();
+        by_step.insert("step_a".to_string(), vec![package]);
+        let context_source = super::StaticContextPackageSource::with_step_packages(by_step);
+        let scorer = super::RuleResponseScorer;
+        let sink = super::OutcomeMemoryEventSink::new(&outcome_db);
+        let summary = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &super::AllowAllTrustGateSource,
+            &ApproveHumanGate,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        )
+        .with_response_scorer(&scorer)
+        .with_outcome_sink(&sink)
+        .execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                ..RunConfig::default()
             },
-            selected_items,
-            excluded_items: Vec::new(),
-            ordering_trace: vec!["fixture".to_string()],
+        );
+        let summary = match summary {
+            Ok(value) => value,
+            Err(err) => panic!("workflow execution failed: {err:#}"),
         };
-        ContextPackageEnvelope {
-            package_slot: 0,
-            source: "test".to_string(),
-            package_hash: "fixture-hash".to_string(),
-            context_package: package,
-        }
+
+        let mut stmt = conn
+            .prepare("SELECT event_type FROM outcome_events WHERE writer = ?1 ORDER BY event_seq")
+            .unwrap_or_else(|_| unreachable!());
+        let outcome_events: Vec<String> = stmt
+            .query_map(
+                rusqlite::params![super::OutcomeMemoryEventSink::WRITER],
+                |row| row.get(0),
+            )
+            .and_then(Iterator::collect)
+            .unwrap_or_else(|_| unreachable!());
+
+        assert_outcomes_attributed(
+            &trace_store,
+            &outcome_db,
+            summary.run_id,
+            outcome_events.len(),
+        );
+        (trace_store, summary.run_id, outcome_events)
     }
 
-    struct ApproveHumanGate;
+    /// Every scored outcome is attributed to its run and step, and found by run id.
+    fn assert_outcomes_attributed(
+        trace_store: &SqliteTraceStore,
+        outcome_db: &std::path::Path,
+        run_id: multi_agent_center_domain::RunId,
+        expected: usize,
+    ) {
+        let outcome_store =
+            super::SqliteOutcomeStore::open(outcome_db).unwrap_or_else(|_| unreachable!());
+        let attributed = outcome_store
+            .list_events_for_run(&run_id.to_string(), None)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(attributed.len(), expected);
+        let step_id = trace_store
+            .get_step_records(run_id)
+            .unwrap_or_else(|_| unreachable!())[0]
+            .step_id;
+        for event in &attributed {
+            let source = event.source.clone().unwrap_or_else(|| unreachable!());
+            assert_eq!(source.step_id, Some(step_id.to_string()));
+            assert_eq!(source.gate_context, Some(format!("{run_id}:step_a")));
+            assert_eq!(
+                source.engine_version.as_deref(),
+                Some(RunConfig::default().engine_version.as_str())
+            );
+            assert!(!event.payload_json.to_string().contains("run_id"));
+        }
+    }
 
-    impl HumanGateDecider for ApproveHumanGate {
-        fn decide(&self, _request: &HumanGateRequest) -> anyhow::Result<HumanGateResponse> {
-            Ok(HumanGateResponse {
-                approved: true,
-                notes: None,
-                decided_by: "test".to_string(),
-                reason_codes: vec!["approved.test".to_string()],
+    fn step_scored_payload(
+        trace_store: &SqliteTraceStore,
+        run_id: multi_agent_center_domain::RunId,
+    ) -> serde_json::Value {
+        trace_store
+            .list_events_for_run(run_id)
+            .unwrap_or_else(|_| unreachable!())
+            .into_iter()
+            .find(|row| {
+                row.event.event_type == multi_agent_center_domain::TraceEventType::StepScored
             })
-        }
+            .map_or_else(|| unreachable!(), |row| row.event.payload_json)
     }
 
-    struct SelectiveTrustGate;
+    #[test]
+    fn passing_score_logs_success_outcomes_for_injected_memories() {
+        let (trace_store, run_id, outcome_events) = run_scored_workflow(
+            "scoring-pass",
+            "{ regex: \"^mock:step_a:\", log_outcomes: true }",
+        );
 
-    impl TrustGateSource for SelectiveTrustGate {
-        fn evaluate(
-            &self,
-            _run_id: multi_agent_center_domain::RunId,
-            _step_id: StepId,
-            _step_key: &str,
-            as_of: time::OffsetDateTime,
-            refs: &[ContextRef],
-        ) -> anyhow::Result<Vec<TrustGateAttachment>> {
-            Ok(refs
-                .iter()
-                .map(|item| TrustGateAttachment {
-                    memory_id: item.memory_id,
-                    version: item.version,
-                    memory_version_id: item.memory_version_id,
-                    include: item.version % 2 == 1,
-                    trust_status: "active".to_string(),
-                    confidence_effective: 0.8,
-                    capped: false,
-                    reason_codes: vec!["fixture".to_string()],
-                    ruleset_version: Some(1),
-                    evaluated_at: as_of,
-                    source: "test.trust".to_string(),
-                })
-                .collect())
-        }
+        let payload = step_scored_payload(&trace_store, run_id);
+        assert_eq!(payload["verdict"]["passed"], true);
+        assert_eq!(payload["verdict"]["checks"][0]["check"], "regex");
+        assert_eq!(payload["outcome_events"], 2);
+        assert_eq!(outcome_events, vec!["success", "success"]);
     }
 
     #[test]
-    fn provider_failure_marks_step_failed_instead_of_aborting_run() {
-        let trace_db = temp_db_path("provider-failure");
+    fn failing_score_logs_failure_outcomes_without_failing_step() {
+        let (trace_store, run_id, outcome_events) = run_scored_workflow(
+            "scoring-fail",
+            "{ regex: \"^mock:\", expected: { answer: 42 }, log_outcomes: true }",
+        );
+
+        let steps = trace_store
+            .get_step_records(run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(steps[0].status, StepStatus::Succeeded);
+        let payload = step_scored_payload(&trace_store, run_id);
+        assert_eq!(payload["verdict"]["passed"], false);
+        assert_eq!(payload["verdict"]["checks"][1]["passed"], false);
+        assert_eq!(outcome_events, vec!["failure", "failure"]);
+    }
+
+    fn run_delegated_gate_workflow(
+        name: &str,
+        rule: &str,
+    ) -> (
+        std::path::PathBuf,
+        multi_agent_center_domain::RunId,
+        multi_agent_center_domain::RunStatus,
+    ) {
+        let trace_db = temp_db_path(name);
         let trace_store = SqliteTraceStore::open(&trace_db);
         assert!(trace_store.is_ok());
         let trace_store = trace_store.unwrap_or_else(|_| unreachable!());
         assert!(trace_store.migrate().is_ok());
 
-        let workflow_yaml = r#"
+        let workflow_yaml = format!(
+            r#"
 workflow_name: wf
 workflow_version: v1
 normalization_version: 0
@@ -1922,38 +11965,38 @@ agents:
   - agent_name: planner
     role: planning
     provider:
-      provider_name: unsupported_provider
+      provider_name: mock
       model_id: x
 steps:
   - step_key: step_a
     agent_name: planner
-    task: { text: "a" }
+    task: {{ text: "a" }}
     depends_on: []
-    gate_points: []
-  - step_key: step_b
-    agent_name: planner
-    task: { text: "b" }
-    depends_on: [step_a]
-    gate_points: []
-gates: []
+    gate_points: [review]
+gates:
+  - gate_name: review
+    gate_kind: human
+    required: true
+    auto_approve:
+      - {rule}
 defaults:
   non_interactive: true
-"#;
-        let workflow = normalize_workflow_yaml(workflow_yaml);
+"#
+        );
+        let workflow = normalize_workflow_yaml(&workflow_yaml);
         assert!(workflow.is_ok());
         let workflow = workflow.unwrap_or_else(|_| unreachable!());
 
-        let context_source = super::StaticContextPackageSource::default();
-        let trust_source = AllowAllTrustGateSource;
-        let human_gate = DefaultHumanGateDecider;
-        let write_applier = NoopProposedWriteApplier;
-
+        let mut records_by_step = BTreeMap::new();
+        records_by_step.insert("step_a".to_string(), vec![fixture_memory_record()]);
+        let context_source = InMemoryMemoryKernelContextSource { records_by_step };
         let summary = Orchestrator::new(
             &trace_store,
             &context_source,
-            &trust_source,
-            &human_gate,
-            &write_applier,
+            &AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
         )
         .execute_workflow(
             &workflow,
@@ -1966,32 +12009,393 @@ defaults:
             Ok(value) => value,
             Err(err) => panic!("workflow execution failed: {err:#}"),
         };
-        assert_eq!(summary.status, multi_agent_center_domain::RunStatus::Failed);
-        let run_record = trace_store.get_run(summary.run_id);
-        assert!(run_record.is_ok());
-        let run_record = run_record
+        (trace_db, summary.run_id, summary.status)
+    }
+
+    fn human_gate_decision(
+        trace_db: &std::path::Path,
+        run_id: multi_agent_center_domain::RunId,
+    ) -> (String, String, Option<String>) {
+        let conn = rusqlite::Connection::open(trace_db).unwrap_or_else(|_| unreachable!());
+        conn.query_row(
+            "SELECT decision, decided_by, evidence_json FROM step_gate_decisions
+             WHERE run_id = ?1 AND gate_kind = 'human'",
+            rusqlite::params![run_id.to_string()],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap_or_else(|_| unreachable!())
+    }
+
+    #[test]
+    fn delegated_rule_auto_approves_human_gate_with_matched_rule_in_evidence() {
+        let (trace_db, run_id, status) = run_delegated_gate_workflow(
+            "delegated-approve",
+            "{ rule_name: low_risk, trust_statuses: [active], max_estimated_cost_tokens: 100000, max_context_items: 5 }",
+        );
+        assert_eq!(status, multi_agent_center_domain::RunStatus::Succeeded);
+
+        let (decision, decided_by, evidence) = human_gate_decision(&trace_db, run_id);
+        assert_eq!(decision, "approved");
+        assert_eq!(decided_by, "policy.delegated");
+        let evidence: serde_json::Value = evidence
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_else(|| unreachable!());
+        assert_eq!(evidence["rule"]["rule_name"], "low_risk");
+        assert_eq!(evidence["observed"]["trust_statuses"], json!(["active"]));
+        assert_eq!(evidence["observed"]["context_items"], 1);
+    }
+
+    #[test]
+    fn delegated_rule_falls_back_to_human_decider_when_conditions_fail() {
+        let (trace_db, run_id, status) = run_delegated_gate_workflow(
+            "delegated-fallback",
+            "{ rule_name: validated_only, trust_statuses: [validated] }",
+        );
+        assert_eq!(status, multi_agent_center_domain::RunStatus::Rejected);
+
+        let (decision, decided_by, evidence) = human_gate_decision(&trace_db, run_id);
+        assert_eq!(decision, "rejected");
+        assert_eq!(decided_by, "system.non_interactive");
+        assert_eq!(evidence, None);
+    }
+
+    #[derive(Default)]
+    struct RecordingGateNotifier {
+        sent: std::cell::RefCell<Vec<(String, Vec<u8>, String)>>,
+    }
+
+    impl super::GateNotifier for RecordingGateNotifier {
+        fn notify(&self, url: &str, body: &[u8], signature: &str) -> anyhow::Result<()> {
+            self.sent
+                .borrow_mut()
+                .push((url.to_string(), body.to_vec(), signature.to_string()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn deferred_gates_with_webhooks_send_signed_notifications() {
+        let trace_store = SqliteTraceStore::open(&temp_db_path("gate-webhooks"))
+            .unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+        let secret_env = format!("MAC_TEST_GATE_SECRET_{}", ulid::Ulid::new());
+        std::env::set_var(&secret_env, "review-secret");
+        let workflow = normalize_workflow_yaml(&format!(
+            r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider: {{ provider_name: mock, model_id: x }}
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task: {{ text: "a" }}
+    gate_points: [review]
+  - step_key: step_b
+    agent_name: planner
+    task: {{ text: "b" }}
+    gate_points: [audit]
+gates:
+  - gate_name: review
+    gate_kind: human
+    required: true
+    on_non_interactive: defer
+    webhook: {{ url: "https://hooks.example.com/review", secret_env: "{secret_env}" }}
+  - gate_name: audit
+    gate_kind: human
+    required: true
+    on_non_interactive: defer
+    webhook: {{ url: "https://hooks.example.com/audit", secret_env: "MAC_TEST_UNSET_SECRET" }}
+defaults:
+  non_interactive: true
+"#
+        ))
+        .unwrap_or_else(|err| panic!("workflow did not normalize: {err:#}"));
+        let context_source = super::StaticContextPackageSource::default();
+        let notifier = RecordingGateNotifier::default();
+        let orchestrator = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        )
+        .with_gate_notifier(&notifier);
+
+        let summary = orchestrator
+            .execute_workflow(
+                &workflow,
+                RunConfig {
+                    non_interactive: true,
+                    ..RunConfig::default()
+                },
+            )
+            .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+        assert_eq!(summary.human_deferrals, 2);
+
+        let sent = notifier.sent.borrow();
+        assert_eq!(sent.len(), 1);
+        let (url, body, signature) = &sent[0];
+        assert_eq!(url, "https://hooks.example.com/review");
+        assert!(multi_agent_center_domain::verify_gate_signature(
+            "review-secret",
+            body,
+            signature
+        ));
+        assert!(!multi_agent_center_domain::verify_gate_signature(
+            "other-secret",
+            body,
+            signature
+        ));
+        let payload: serde_json::Value =
+            serde_json::from_slice(body).unwrap_or_else(|_| unreachable!());
+        assert_eq!(payload["contract_version"], "gate_notification.v1");
+        assert_eq!(payload["run_id"], json!(summary.run_id));
+        assert_eq!(payload["step_key"], "step_a");
+        assert_eq!(payload["gate_name"], "review");
+        assert_eq!(
+            payload["callback_path"],
+            format!("/v1/trace/runs/{}/gates/callback", summary.run_id)
+        );
+
+        let failures: Vec<serde_json::Value> = trace_store
+            .list_events_for_run(summary.run_id)
+            .unwrap_or_else(|_| unreachable!())
+            .into_iter()
+            .map(|row| row.event.payload_json)
+            .filter(|payload| payload["warning_code"] == "gate_notification_failed")
+            .collect();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0]["gate_name"], "audit");
+        assert!(failures[0]["error"]
+            .as_str()
+            .is_some_and(|error| error.contains("MAC_TEST_UNSET_SECRET")));
+        let deferred = trace_store
+            .list_deferred_gates(Some(summary.run_id))
+            .unwrap_or_else(|_| unreachable!());
+        assert!(deferred.iter().all(|gate| gate.webhook.is_some()));
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn non_interactive_gate_policies_approve_or_defer_and_rerun_or_resume_applies_resolution() {
+        let trace_store = SqliteTraceStore::open(&temp_db_path("non-interactive-gate-policy"))
+            .unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let workflow = normalize_workflow_yaml(
+            r#"
+workflow_name: wf
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: planner
+    role: planning
+    provider:
+      provider_name: mock
+      model_id: x
+steps:
+  - step_key: step_a
+    agent_name: planner
+    task: { text: "a" }
+    depends_on: []
+    gate_points: [review]
+  - step_key: step_b
+    agent_name: planner
+    task: { text: "b" }
+    depends_on: [step_a]
+    gate_points: []
+  - step_key: step_c
+    agent_name: planner
+    task: { text: "c" }
+    depends_on: []
+    gate_points: [ack]
+gates:
+  - gate_name: review
+    gate_kind: human
+    required: true
+    on_non_interactive: defer
+  - gate_name: ack
+    gate_kind: human
+    required: true
+    on_non_interactive: approve
+defaults:
+  non_interactive: true
+"#,
+        )
+        .unwrap_or_else(|err| panic!("workflow did not normalize: {err:#}"));
+        let context_source = super::StaticContextPackageSource::default();
+        let orchestrator = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        );
+
+        let first = orchestrator
+            .execute_workflow(
+                &workflow,
+                RunConfig {
+                    non_interactive: true,
+                    ..RunConfig::default()
+                },
+            )
+            .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+        assert_eq!(
+            first.status,
+            multi_agent_center_domain::RunStatus::PartiallyCompleted
+        );
+        assert_eq!(first.steps_succeeded, 1);
+        assert_eq!(first.steps_deferred, 1);
+        assert_eq!(first.human_approvals, 1);
+        assert_eq!(first.human_rejections, 0);
+        assert_eq!(first.human_deferrals, 1);
+
+        let statuses: BTreeMap<String, StepStatus> = trace_store
+            .get_step_records(first.run_id)
+            .unwrap_or_else(|_| unreachable!())
+            .into_iter()
+            .map(|step| (step.step_key, step.status))
+            .collect();
+        assert_eq!(statuses["step_a"], StepStatus::Deferred);
+        assert!(!statuses.contains_key("step_b"));
+        assert_eq!(statuses["step_c"], StepStatus::Succeeded);
+        assert!(orchestrator
+            .execute_workflow(
+                &workflow,
+                RunConfig {
+                    resume_run: Some(first.run_id),
+                    ..RunConfig::default()
+                },
+            )
+            .is_err());
+
+        let decisions = trace_store
+            .get_step_gate_decisions(first.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(decisions.len(), 1);
+        assert_eq!(decisions[0].step_key, "step_c");
+        assert_eq!(
+            decisions[0].decision.reason_codes,
+            vec!["approved.non_interactive_policy"]
+        );
+
+        let deferred = trace_store
+            .list_deferred_gates(Some(first.run_id))
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(deferred.len(), 1);
+        assert_eq!(deferred[0].step_key, "step_a");
+        assert_eq!(deferred[0].gate_name, "review");
+        assert!(deferred[0].required);
+        assert_eq!(deferred[0].resolution, None);
+
+        let resolution = multi_agent_center_domain::DeferredGateResolution {
+            approved: true,
+            decided_by: "reviewer".to_string(),
+            auth_method: multi_agent_center_domain::DeciderAuthMethod::Unverified,
+            on_behalf_of: None,
+            notes: Some("looks fine".to_string()),
+            decided_at: time::OffsetDateTime::now_utc(),
+        };
+        let resolved = trace_store
+            .resolve_deferred_gate(first.run_id, "step_a", "review", &resolution)
+            .unwrap_or_else(|err| panic!("resolve failed: {err:#}"));
+        assert_eq!(
+            resolved.resolution.map(|r| r.decided_by),
+            Some("reviewer".to_string())
+        );
+        assert!(trace_store
+            .resolve_deferred_gate(first.run_id, "step_a", "review", &resolution)
+            .is_err());
+        assert!(trace_store
+            .resolve_deferred_gate(first.run_id, "step_c", "ack", &resolution)
+            .is_err());
+
+        let rerun = orchestrator
+            .execute_workflow(
+                &workflow,
+                RunConfig {
+                    non_interactive: true,
+                    rerun_of: Some(StepRerun {
+                        source_run_id: first.run_id,
+                        step_key: "step_a".to_string(),
+                    }),
+                    ..RunConfig::default()
+                },
+            )
+            .unwrap_or_else(|err| panic!("rerun failed: {err:#}"));
+        assert_eq!(
+            rerun.status,
+            multi_agent_center_domain::RunStatus::Succeeded
+        );
+        assert_eq!(rerun.steps_succeeded, 3);
+        assert_eq!(rerun.human_deferrals, 0);
+        let rerun_decision = trace_store
+            .get_step_gate_decisions(rerun.run_id)
             .unwrap_or_else(|_| unreachable!())
+            .into_iter()
+            .find(|record| record.step_key == "step_a")
             .unwrap_or_else(|| unreachable!());
-        assert!(run_record.manifest_hash.is_some());
-        assert_eq!(run_record.manifest_signature_status, "unsigned");
+        assert_eq!(
+            rerun_decision.decision.decision,
+            multi_agent_center_domain::GateDecision::Approved
+        );
+        assert_eq!(rerun_decision.decision.decided_by, "reviewer");
+        assert_eq!(
+            rerun_decision.decision.reason_codes,
+            vec!["approved.deferred_resolution"]
+        );
 
-        let steps = trace_store.get_step_records(summary.run_id);
-        assert!(steps.is_ok());
-        let steps = steps.unwrap_or_else(|_| unreachable!());
-        assert_eq!(steps.len(), 2);
-        assert_eq!(steps[0].status, StepStatus::Failed);
-        assert_eq!(steps[1].status, StepStatus::Skipped);
+        let resumed = orchestrator
+            .execute_workflow(
+                &workflow,
+                RunConfig {
+                    non_interactive: true,
+                    resume_run: Some(first.run_id),
+                    ..RunConfig::default()
+                },
+            )
+            .unwrap_or_else(|err| panic!("resume failed: {err:#}"));
+        assert_eq!(resumed.run_id, first.run_id);
+        assert_eq!(
+            resumed.status,
+            multi_agent_center_domain::RunStatus::Succeeded
+        );
+        assert_eq!(resumed.steps_succeeded, 3);
+        assert_eq!(resumed.steps_deferred, 0);
+        let resumed_statuses: BTreeMap<String, StepStatus> = trace_store
+            .get_step_records(first.run_id)
+            .unwrap_or_else(|_| unreachable!())
+            .into_iter()
+            .map(|step| (step.step_key, step.status))
+            .collect();
+        assert_eq!(resumed_statuses.len(), 3);
+        assert!(resumed_statuses
+            .values()
+            .all(|status| *status == StepStatus::Succeeded));
+        assert!(orchestrator
+            .replay_audit(first.run_id)
+            .is_ok_and(|report| report.chain_valid));
+        assert!(orchestrator
+            .execute_workflow(
+                &workflow,
+                RunConfig {
+                    resume_run: Some(first.run_id),
+                    ..RunConfig::default()
+                },
+            )
+            .is_err());
     }
 
-    #[test]
-    fn step_supports_multiple_context_packages() {
-        let trace_db = temp_db_path("multi-packages");
-        let trace_store = SqliteTraceStore::open(&trace_db);
-        assert!(trace_store.is_ok());
-        let trace_store = trace_store.unwrap_or_else(|_| unreachable!());
-        assert!(trace_store.migrate().is_ok());
-
-        let workflow_yaml = r#"
+    fn golden_workflow_yaml(step_b_gates: &str) -> String {
+        format!(
+            r#"
 workflow_name: wf
 workflow_version: v1
 normalization_version: 0
@@ -2004,61 +12408,241 @@ agents:
 steps:
   - step_key: step_a
     agent_name: planner
-    task:
-      context_queries:
-        - { text: "q1", actor: "a", action: "x", resource: "r1" }
-        - { text: "q2", actor: "a", action: "x", resource: "r2" }
+    task: {{ text: "a" }}
     depends_on: []
-    gate_points: []
-gates: []
-defaults:
-  non_interactive: true
-"#;
+    gate_points: [review]
+  - step_key: step_b
+    agent_name: planner
+    task: {{ text: "b" }}
+    depends_on: [step_a]
+    gate_points: {step_b_gates}
+gates:
+  - gate_name: review
+    gate_kind: human
+    required: true
+"#
+        )
+    }
+
+    fn run_golden_workflow(
+        trace_store: &SqliteTraceStore,
+        workflow_yaml: &str,
+        human_gate: &dyn HumanGateDecider,
+        responses: Option<&dyn super::RecordedResponseSource>,
+    ) -> multi_agent_center_domain::RunId {
         let workflow = normalize_workflow_yaml(workflow_yaml);
         assert!(workflow.is_ok());
         let workflow = workflow.unwrap_or_else(|_| unreachable!());
 
-        let mut records_by_step = BTreeMap::new();
-        records_by_step.insert("step_a".to_string(), vec![fixture_memory_record()]);
-        let context_source = InMemoryMemoryKernelContextSource { records_by_step };
+        let context_source = super::StaticContextPackageSource::default();
+        let mut orchestrator = Orchestrator::new(
+            trace_store,
+            &context_source,
+            &AllowAllTrustGateSource,
+            human_gate,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        );
+        if let Some(responses) = responses {
+            orchestrator = orchestrator.with_recorded_responses(responses);
+        }
+        match orchestrator.execute_workflow(&workflow, RunConfig::default()) {
+            Ok(summary) => summary.run_id,
+            Err(err) => panic!("workflow execution failed: {err:#}"),
+        }
+    }
 
-        let trust_source = AllowAllTrustGateSource;
-        let human_gate = DefaultHumanGateDecider;
-        let write_applier = NoopProposedWriteApplier;
-        let summary = Orchestrator::new(
+    #[test]
+    fn golden_check_replays_recorded_responses_and_gate_decisions() {
+        let trace_db = temp_db_path("golden-match");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+        let workflow_yaml = golden_workflow_yaml("[]");
+
+        let source_run_id =
+            run_golden_workflow(&trace_store, &workflow_yaml, &DefaultHumanGateDecider, None);
+        let golden = super::record_golden_run(&trace_store, source_run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(golden.steps.len(), 2);
+        assert!(golden
+            .steps
+            .iter()
+            .all(|step| step.status == StepStatus::Succeeded
+                && step.output_hash.is_some()
+                && step.provider_responses.len() == 1));
+        assert_eq!(golden.steps[0].gate_decisions.len(), 1);
+
+        // Mock outputs depend on the run id, so a live re-execution diverges.
+        let live_run_id =
+            run_golden_workflow(&trace_store, &workflow_yaml, &DefaultHumanGateDecider, None);
+        let live =
+            super::record_golden_run(&trace_store, live_run_id).unwrap_or_else(|_| unreachable!());
+        assert!(!multi_agent_center_domain::diff_golden_runs(&golden, &live).is_empty());
+
+        let responses = super::GoldenResponseSource::new(&golden);
+        let check_run_id = run_golden_workflow(
+            &trace_store,
+            &workflow_yaml,
+            &super::GoldenHumanGateDecider::new(&golden),
+            Some(&responses),
+        );
+        let actual =
+            super::record_golden_run(&trace_store, check_run_id).unwrap_or_else(|_| unreachable!());
+        assert_eq!(
+            multi_agent_center_domain::diff_golden_runs(&golden, &actual),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn golden_check_reports_changed_step_outcomes() {
+        let trace_db = temp_db_path("golden-diff");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let source_run_id = run_golden_workflow(
+            &trace_store,
+            &golden_workflow_yaml("[]"),
+            &DefaultHumanGateDecider,
+            None,
+        );
+        let golden = super::record_golden_run(&trace_store, source_run_id)
+            .unwrap_or_else(|_| unreachable!());
+
+        let responses = super::GoldenResponseSource::new(&golden);
+        let check_run_id = run_golden_workflow(
+            &trace_store,
+            &golden_workflow_yaml("[review]"),
+            &super::GoldenHumanGateDecider::new(&golden),
+            Some(&responses),
+        );
+        let actual =
+            super::record_golden_run(&trace_store, check_run_id).unwrap_or_else(|_| unreachable!());
+        let differences = multi_agent_center_domain::diff_golden_runs(&golden, &actual);
+        let fields: Vec<(&str, &str)> = differences
+            .iter()
+            .map(|diff| (diff.step_key.as_str(), diff.field.as_str()))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("step_b", "status"),
+                ("step_b", "output_hash"),
+                ("step_b", "gate_decisions"),
+            ]
+        );
+        assert_eq!(differences[0].expected, json!("succeeded"));
+        assert_eq!(differences[0].actual, json!("rejected"));
+        assert_eq!(
+            differences[2].actual[0]["reason_codes"],
+            json!(["rejected.golden_missing"])
+        );
+    }
+
+    #[test]
+    fn replay_execute_answers_provider_calls_from_the_trace_and_flags_divergences() {
+        let trace_db = temp_db_path("replay-execute");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+        let source_run_id = run_golden_workflow(
+            &trace_store,
+            &golden_workflow_yaml("[]"),
+            &DefaultHumanGateDecider,
+            None,
+        );
+        let context_source = super::StaticContextPackageSource::default();
+        let orchestrator = Orchestrator::new(
             &trace_store,
             &context_source,
-            &trust_source,
-            &human_gate,
-            &write_applier,
-        )
-        .execute_workflow(
-            &workflow,
-            RunConfig {
-                non_interactive: true,
-                ..RunConfig::default()
-            },
+            &AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
         );
-        let summary = match summary {
-            Ok(value) => value,
-            Err(err) => panic!("workflow execution failed: {err:#}"),
-        };
+
+        // Mock outputs depend on the run id, so only the recorded responses reproduce them.
+        let report = orchestrator
+            .replay_execute(source_run_id)
+            .unwrap_or_else(|err| panic!("replay failed: {err:#}"));
+        assert_eq!(report.divergences, Vec::new());
+        assert!(report.reproduced);
+        assert_eq!(report.provider_calls_replayed, 2);
         assert_eq!(
-            summary.status,
+            report.status,
             multi_agent_center_domain::RunStatus::Succeeded
         );
+        let replay_run = trace_store
+            .get_run(report.replay_run_id)
+            .unwrap_or_else(|_| unreachable!())
+            .unwrap_or_else(|| unreachable!());
+        assert_eq!(replay_run.replay_of_run_id, Some(source_run_id));
 
-        let packages = trace_store.get_step_context_packages(summary.run_id);
-        assert!(packages.is_ok());
-        let packages = packages.unwrap_or_else(|_| unreachable!());
-        assert_eq!(packages.len(), 2);
-        assert_eq!(packages[0].envelope.package_slot, 0);
-        assert_eq!(packages[1].envelope.package_slot, 1);
+        // A recorded call the replay never makes is flagged.
+        let conn = rusqlite::Connection::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        let copied = conn.execute(
+            "INSERT INTO provider_calls
+             SELECT ?2, run_id, step_id, provider_name, adapter_version, model_id,
+                    json_set(request_json, '$.task_payload.text', 'edited'), request_hash,
+                    response_json, response_hash, latency_ms, input_tokens, output_tokens,
+                    started_at, ended_at, status, error_text, persona_json
+             FROM provider_calls
+             WHERE run_id = ?1 AND step_id = (
+               SELECT step_id FROM steps WHERE run_id = ?1 AND step_key = 'step_b'
+             )",
+            rusqlite::params![source_run_id.to_string(), ulid::Ulid::new().to_string()],
+        );
+        assert_eq!(copied.ok(), Some(1));
+        let report = orchestrator
+            .replay_execute(source_run_id)
+            .unwrap_or_else(|err| panic!("replay failed: {err:#}"));
+        assert!(!report.reproduced);
+        let fields: Vec<(&str, &str)> = report
+            .divergences
+            .iter()
+            .map(|diff| (diff.step_key.as_str(), diff.field.as_str()))
+            .collect();
+        assert_eq!(fields, vec![("step_b", "provider_calls")]);
+        assert_eq!(report.divergences[0].expected, json!(2));
+        assert_eq!(report.divergences[0].actual, json!(1));
     }
 
     #[test]
-    fn step_context_query_recall_mode_uses_recall_resolver_rules() {
-        let trace_db = temp_db_path("recall-mode");
+    fn upgrade_impact_cross_references_recent_runs_of_old_workflow() {
+        let trace_db = temp_db_path("upgrade-impact");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+        let old_yaml = golden_workflow_yaml("[]");
+        let new_yaml = golden_workflow_yaml("[review]");
+        for _ in 0..2 {
+            run_golden_workflow(&trace_store, &old_yaml, &DefaultHumanGateDecider, None);
+        }
+        run_golden_workflow(&trace_store, &new_yaml, &DefaultHumanGateDecider, None);
+
+        let old = normalize_workflow_yaml(&old_yaml).unwrap_or_else(|_| unreachable!());
+        let new = normalize_workflow_yaml(&new_yaml).unwrap_or_else(|_| unreachable!());
+        let diff = multi_agent_center_workflow::diff_workflows(&old, &new);
+        let impact = super::workflow_upgrade_impact(&trace_store, diff, 5)
+            .unwrap_or_else(|_| unreachable!());
+
+        assert_eq!(impact.runs_considered.len(), 2);
+        assert_eq!(impact.steps.len(), 1);
+        let step = &impact.steps[0];
+        assert_eq!(step.step_key, "step_b");
+        assert_eq!(step.reasons, vec!["step.gate_points".to_string()]);
+        assert_eq!(step.estimate, "behavior_change_expected");
+        assert_eq!(step.runs_observed, 2);
+        assert_eq!(step.statuses.get("succeeded"), Some(&2));
+        assert!(step.gate_decisions.is_empty());
+
+        let limited = super::workflow_upgrade_impact(&trace_store, impact.diff, 1)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(limited.runs_considered.len(), 1);
+        assert_eq!(limited.steps[0].runs_observed, 1);
+    }
+
+    #[test]
+    fn dag_with_parallel_ready_steps_executes_successfully() {
+        let trace_db = temp_db_path("parallel-ready");
         let trace_store = SqliteTraceStore::open(&trace_db);
         assert!(trace_store.is_ok());
         let trace_store = trace_store.unwrap_or_else(|_| unreachable!());
@@ -2077,11 +12661,19 @@ agents:
 steps:
   - step_key: step_a
     agent_name: planner
-    task:
-      context_queries:
-        - { mode: "recall", text: "usb project notes", record_types: ["decision", "outcome"] }
+    task: { text: "a" }
+    depends_on: []
+    gate_points: []
+  - step_key: step_b
+    agent_name: planner
+    task: { text: "b" }
     depends_on: []
     gate_points: []
+  - step_key: step_c
+    agent_name: planner
+    task: { text: "c" }
+    depends_on: [step_a, step_b]
+    gate_points: []
 gates: []
 defaults:
   non_interactive: true
@@ -2090,17 +12682,7 @@ defaults:
         assert!(workflow.is_ok());
         let workflow = workflow.unwrap_or_else(|_| unreachable!());
 
-        let mut records_by_step = BTreeMap::new();
-        records_by_step.insert(
-            "step_a".to_string(),
-            vec![
-                fixture_memory_record(),
-                fixture_summary_record(RecordType::Decision, "usb project notes decision"),
-                fixture_summary_record(RecordType::Outcome, "usb project notes outcome"),
-            ],
-        );
-        let context_source = InMemoryMemoryKernelContextSource { records_by_step };
-
+        let context_source = super::StaticContextPackageSource::default();
         let trust_source = AllowAllTrustGateSource;
         let human_gate = DefaultHumanGateDecider;
         let write_applier = NoopProposedWriteApplier;
@@ -2110,6 +12692,7 @@ defaults:
             &trust_source,
             &human_gate,
             &write_applier,
+            ProviderRegistry::default(),
         )
         .execute_workflow(
             &workflow,
@@ -2118,35 +12701,23 @@ defaults:
                 ..RunConfig::default()
             },
         );
-        let summary = match summary {
-            Ok(value) => value,
-            Err(err) => panic!("workflow execution failed: {err:#}"),
-        };
+        assert!(summary.is_ok());
+        let summary = summary.unwrap_or_else(|_| unreachable!());
         assert_eq!(
             summary.status,
             multi_agent_center_domain::RunStatus::Succeeded
         );
-
-        let packages = trace_store.get_step_context_packages(summary.run_id);
-        assert!(packages.is_ok());
-        let packages = packages.unwrap_or_else(|_| unreachable!());
-        assert_eq!(packages.len(), 1);
-        let selected = &packages[0].envelope.context_package.selected_items;
-        assert!(selected
-            .iter()
-            .all(|item| item.record_type == RecordType::Decision
-                || item.record_type == RecordType::Outcome));
+        assert_eq!(summary.steps_total, 3);
+        assert_eq!(summary.steps_succeeded, 3);
     }
 
     #[test]
-    fn step_context_query_recall_missing_record_types_defaults_scope() {
-        let trace_db = temp_db_path("recall-default-missing");
-        let trace_store = SqliteTraceStore::open(&trace_db);
-        assert!(trace_store.is_ok());
-        let trace_store = trace_store.unwrap_or_else(|_| unreachable!());
+    fn run_manifest_records_a_hashed_environment_fingerprint() {
+        let trace_store = SqliteTraceStore::open(&temp_db_path("environment-fingerprint"))
+            .unwrap_or_else(|_| unreachable!());
         assert!(trace_store.migrate().is_ok());
-
-        let workflow_yaml = r#"
+        let workflow = normalize_workflow_yaml(
+            r#"
 workflow_name: wf
 workflow_version: v1
 normalization_version: 0
@@ -2159,36 +12730,20 @@ agents:
 steps:
   - step_key: step_a
     agent_name: planner
-    task:
-      context_queries:
-        - { mode: "recall", text: "repo policy decision" }
+    task: { text: "a" }
     depends_on: []
     gate_points: []
-gates: []
-defaults:
-  non_interactive: true
-"#;
-        let workflow = normalize_workflow_yaml(workflow_yaml);
-        assert!(workflow.is_ok());
-        let workflow = workflow.unwrap_or_else(|_| unreachable!());
-
-        let mut records_by_step = BTreeMap::new();
-        records_by_step.insert(
-            "step_a".to_string(),
-            vec![
-                fixture_memory_record(),
-                fixture_summary_record(RecordType::Decision, "repo policy decision"),
-                fixture_summary_record(RecordType::Outcome, "repo policy outcome"),
-            ],
-        );
-        let context_source = InMemoryMemoryKernelContextSource { records_by_step };
-
+"#,
+        )
+        .unwrap_or_else(|err| panic!("workflow did not normalize: {err:#}"));
+        let context_source = super::StaticContextPackageSource::default();
         let summary = Orchestrator::new(
             &trace_store,
             &context_source,
             &AllowAllTrustGateSource,
             &DefaultHumanGateDecider,
             &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
         )
         .execute_workflow(
             &workflow,
@@ -2196,37 +12751,45 @@ defaults:
                 non_interactive: true,
                 ..RunConfig::default()
             },
-        );
-        let summary = match summary {
-            Ok(value) => value,
-            Err(err) => panic!("workflow execution failed: {err:#}"),
-        };
-        assert_eq!(
-            summary.status,
-            multi_agent_center_domain::RunStatus::Succeeded
-        );
-
-        let packages = trace_store.get_step_context_packages(summary.run_id);
-        assert!(packages.is_ok());
-        let packages = packages.unwrap_or_else(|_| unreachable!());
-        assert_eq!(packages.len(), 1);
-        let selected = &packages[0].envelope.context_package.selected_items;
-        assert!(!selected.is_empty());
-        let default_types = default_recall_record_types();
-        assert!(selected
-            .iter()
-            .all(|item| default_types.contains(&item.record_type)));
+        )
+        .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+        let manifest = trace_store
+            .get_run_manifest(summary.run_id)
+            .unwrap_or_else(|_| unreachable!())
+            .unwrap_or_else(|| unreachable!());
+        let manifest_hash = trace_store
+            .get_run(summary.run_id)
+            .unwrap_or_else(|_| unreachable!())
+            .and_then(|run| run.manifest_hash)
+            .unwrap_or_else(|| unreachable!());
+        assert!(multi_agent_center_domain::verify_run_manifest(&manifest, &manifest_hash).is_ok());
+
+        let environment: multi_agent_center_domain::EnvironmentFingerprint =
+            serde_json::from_value(manifest["environment"].clone())
+                .unwrap_or_else(|err| panic!("manifest environment did not parse: {err:#}"));
+        assert!(environment.hash_is_consistent());
+        assert_eq!(environment.features, vec!["non_interactive"]);
+        assert_eq!(environment.sqlite_version, rusqlite::version());
+        assert!(environment
+            .crate_versions
+            .contains_key("memory-kernel-outcome-store-sqlite"));
+        let mut tampered = manifest.clone();
+        tampered["environment"]["sqlite_version"] = json!("0.0.0");
+        let rehashed = super::hash_json(&tampered).unwrap_or_else(|_| unreachable!());
+        let err = multi_agent_center_domain::verify_run_manifest(&tampered, &rehashed)
+            .err()
+            .unwrap_or_else(|| unreachable!());
+        assert!(err.to_string().contains("environment hash"));
     }
 
     #[test]
-    fn step_context_query_recall_empty_record_types_defaults_scope() {
-        let trace_db = temp_db_path("recall-default-empty");
-        let trace_store = SqliteTraceStore::open(&trace_db);
-        assert!(trace_store.is_ok());
-        let trace_store = trace_store.unwrap_or_else(|_| unreachable!());
+    fn relaxed_trace_artifacts_are_durable_once_the_run_finishes() {
+        let path = temp_db_path("relaxed-trace-artifacts");
+        let trace_store = multi_agent_center_trace_sqlite::BufferedSqliteTraceStore::open(&path)
+            .unwrap_or_else(|_| unreachable!());
         assert!(trace_store.migrate().is_ok());
-
-        let workflow_yaml = r#"
+        let workflow = normalize_workflow_yaml(
+            r#"
 workflow_name: wf
 workflow_version: v1
 normalization_version: 0
@@ -2239,74 +12802,94 @@ agents:
 steps:
   - step_key: step_a
     agent_name: planner
-    task:
-      context_queries:
-        - { mode: "recall", text: "repo policy outcome", record_types: [] }
+    task: { text: "a" }
     depends_on: []
     gate_points: []
-gates: []
-defaults:
-  non_interactive: true
-"#;
-        let workflow = normalize_workflow_yaml(workflow_yaml);
-        assert!(workflow.is_ok());
-        let workflow = workflow.unwrap_or_else(|_| unreachable!());
-
-        let mut records_by_step = BTreeMap::new();
-        records_by_step.insert(
-            "step_a".to_string(),
-            vec![
-                fixture_memory_record(),
-                fixture_summary_record(RecordType::Decision, "repo policy decision"),
-                fixture_summary_record(RecordType::Outcome, "repo policy outcome"),
-            ],
-        );
-        let context_source = InMemoryMemoryKernelContextSource { records_by_step };
-
+  - step_key: step_b
+    agent_name: planner
+    task: { text: "b" }
+    depends_on: [step_a]
+    gate_points: []
+"#,
+        )
+        .unwrap_or_else(|err| panic!("workflow did not normalize: {err:#}"));
+        let context_source = super::StaticContextPackageSource::default();
         let summary = Orchestrator::new(
             &trace_store,
             &context_source,
             &AllowAllTrustGateSource,
             &DefaultHumanGateDecider,
             &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
         )
         .execute_workflow(
             &workflow,
             RunConfig {
                 non_interactive: true,
+                relaxed_trace_artifacts: BTreeSet::from([
+                    TraceArtifactClass::Events,
+                    TraceArtifactClass::ProviderCalls,
+                ]),
                 ..RunConfig::default()
             },
+        )
+        .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+        assert_eq!(summary.steps_succeeded, 2);
+
+        // A separate connection sees the whole run while the buffered store is still open.
+        let reader = SqliteTraceStore::open(&path).unwrap_or_else(|_| unreachable!());
+        let events = reader
+            .list_events_for_run(summary.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert!(events
+            .iter()
+            .any(|row| row.event.event_type == TraceEventType::RunFinished));
+        let seqs: Vec<i64> = events.iter().map(|row| row.event_seq).collect();
+        assert_eq!(
+            seqs,
+            (1..=i64::try_from(seqs.len()).unwrap_or(0)).collect::<Vec<_>>()
         );
-        let summary = match summary {
-            Ok(value) => value,
-            Err(err) => panic!("workflow execution failed: {err:#}"),
-        };
         assert_eq!(
-            summary.status,
-            multi_agent_center_domain::RunStatus::Succeeded
+            reader
+                .get_step_provider_calls(summary.run_id)
+                .map(|calls| calls.len())
+                .ok(),
+            Some(2)
         );
+        let audit = Orchestrator::new(
+            &reader,
+            &context_source,
+            &AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        )
+        .replay_audit(summary.run_id)
+        .unwrap_or_else(|err| panic!("replay audit failed: {err:#}"));
+        assert!(audit.chain_valid);
 
-        let packages = trace_store.get_step_context_packages(summary.run_id);
-        assert!(packages.is_ok());
-        let packages = packages.unwrap_or_else(|_| unreachable!());
-        assert_eq!(packages.len(), 1);
-        let selected = &packages[0].envelope.context_package.selected_items;
-        assert!(!selected.is_empty());
-        let default_types = default_recall_record_types();
-        assert!(selected
-            .iter()
-            .all(|item| default_types.contains(&item.record_type)));
+        let manifest = reader
+            .get_run_manifest(summary.run_id)
+            .unwrap_or_else(|_| unreachable!())
+            .unwrap_or_else(|| unreachable!());
+        let environment: multi_agent_center_domain::EnvironmentFingerprint =
+            serde_json::from_value(manifest["environment"].clone())
+                .unwrap_or_else(|err| panic!("manifest environment did not parse: {err:#}"));
+        assert_eq!(
+            environment.features,
+            vec!["non_interactive", "relaxed_trace_durability"]
+        );
     }
 
     #[test]
-    fn step_context_query_recall_invalid_record_type_fails() {
-        let trace_db = temp_db_path("recall-invalid-type");
-        let trace_store = SqliteTraceStore::open(&trace_db);
-        assert!(trace_store.is_ok());
-        let trace_store = trace_store.unwrap_or_else(|_| unreachable!());
+    fn memoized_run_reuses_outputs_of_identical_steps_without_provider_calls() {
+        let trace_store = SqliteTraceStore::open(&temp_db_path("memoized-steps"))
+            .unwrap_or_else(|_| unreachable!());
         assert!(trace_store.migrate().is_ok());
 
-        let workflow_yaml = r#"
+        let workflow = |task_b: &str| {
+            normalize_workflow_yaml(&format!(
+                r#"
 workflow_name: wf
 workflow_version: v1
 normalization_version: 0
@@ -2319,60 +12902,88 @@ agents:
 steps:
   - step_key: step_a
     agent_name: planner
-    task:
-      context_queries:
-        - { mode: "recall", text: "repo policy decision", record_types: ["constraint", "invalid_type"] }
+    task: {{ text: "a" }}
     depends_on: []
     gate_points: []
-gates: []
+  - step_key: step_b
+    agent_name: planner
+    task: {{ text: "{task_b}" }}
+    depends_on: [step_a]
+    gate_points: []
 defaults:
   non_interactive: true
-"#;
-        let workflow = normalize_workflow_yaml(workflow_yaml);
-        assert!(workflow.is_ok());
-        let workflow = workflow.unwrap_or_else(|_| unreachable!());
-
-        let mut records_by_step = BTreeMap::new();
-        records_by_step.insert("step_a".to_string(), vec![fixture_memory_record()]);
-        let context_source = InMemoryMemoryKernelContextSource { records_by_step };
-
-        let run = Orchestrator::new(
+"#
+            ))
+            .unwrap_or_else(|err| panic!("workflow did not normalize: {err:#}"))
+        };
+        let context_source = super::StaticContextPackageSource::default();
+        let orchestrator = Orchestrator::new(
             &trace_store,
             &context_source,
             &AllowAllTrustGateSource,
             &DefaultHumanGateDecider,
             &NoopProposedWriteApplier,
-        )
-        .execute_workflow(
-            &workflow,
-            RunConfig {
-                non_interactive: true,
-                ..RunConfig::default()
-            },
+            ProviderRegistry::default(),
         );
-        assert!(run.is_err());
-    }
+        let as_of = time::OffsetDateTime::now_utc();
+        let run = |workflow: &super::NormalizedWorkflowEnvelope, memoize_steps: bool| {
+            orchestrator
+                .execute_workflow(
+                    workflow,
+                    RunConfig {
+                        as_of: Some(as_of),
+                        non_interactive: true,
+                        memoize_steps,
+                        ..RunConfig::default()
+                    },
+                )
+                .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"))
+        };
 
-    #[test]
-    fn parse_recall_record_types_invalid_value_errors() {
-        let raw = json!(["decision", "invalid_type"]);
-        let parsed = super::parse_recall_record_types(Some(&raw));
-        assert!(parsed.is_err());
-        let err = parsed.err().unwrap_or_else(|| unreachable!());
-        let message = err.to_string();
-        assert!(message.contains("unsupported recall record type"));
-        assert!(message.contains("invalid_type"));
+        let first = run(&workflow("b"), false);
+        let memoized = run(&workflow("b"), true);
+        assert_eq!((first.provider_calls, memoized.provider_calls), (2, 0));
+        assert_eq!(memoized.steps_succeeded, 2);
+        let steps = trace_store
+            .get_step_records(memoized.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert!(steps
+            .iter()
+            .all(|step| step.memoized_from_run == Some(first.run_id)));
+        let golden = |run_id| {
+            super::record_golden_run(&trace_store, run_id)
+                .unwrap_or_else(|_| unreachable!())
+                .steps
+                .into_iter()
+                .map(|step| step.output_hash)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(golden(first.run_id), golden(memoized.run_id));
+        assert_eq!(
+            warning_codes(&trace_store, memoized.run_id),
+            vec!["step_memoized", "step_memoized"]
+        );
+        assert_eq!(
+            memoized.warnings,
+            std::collections::BTreeMap::from([("step_memoized".to_string(), 2)])
+        );
+        assert!(first.warnings.is_empty());
+
+        // A changed workflow hash never reuses outputs of the earlier workflow.
+        let changed = run(&workflow("b2"), true);
+        assert_eq!(changed.provider_calls, 2);
     }
 
     #[test]
-    fn step_context_query_default_mode_is_policy() {
-        let trace_db = temp_db_path("default-policy-mode");
-        let trace_store = SqliteTraceStore::open(&trace_db);
-        assert!(trace_store.is_ok());
-        let trace_store = trace_store.unwrap_or_else(|_| unreachable!());
+    #[allow(clippy::too_many_lines)]
+    fn rerun_step_reexecutes_target_and_downstream_steps_only() {
+        let trace_store =
+            SqliteTraceStore::open(&temp_db_path("rerun-step")).unwrap_or_else(|_| unreachable!());
         assert!(trace_store.migrate().is_ok());
 
-        let workflow_yaml = r#"
+        let workflow = |task_b: &str| {
+            normalize_workflow_yaml(&format!(
+                r#"
 workflow_name: wf
 workflow_version: v1
 normalization_version: 0
@@ -2385,113 +12996,110 @@ agents:
 steps:
   - step_key: step_a
     agent_name: planner
-    task:
-      context_queries:
-        - { text: "Can dev read repo?", actor: "dev", action: "read", resource: "repo" }
+    task: {{ text: "a" }}
     depends_on: []
     gate_points: []
-gates: []
+  - step_key: step_b
+    agent_name: planner
+    task: {{ text: "{task_b}" }}
+    depends_on: [step_a]
+    gate_points: []
+  - step_key: step_c
+    agent_name: planner
+    task: {{ text: "c" }}
+    depends_on: [step_b]
+    gate_points: []
+  - step_key: step_d
+    agent_name: planner
+    task: {{ text: "d" }}
+    depends_on: [step_a]
+    gate_points: []
 defaults:
   non_interactive: true
-"#;
-        let workflow = normalize_workflow_yaml(workflow_yaml);
-        assert!(workflow.is_ok());
-        let workflow = workflow.unwrap_or_else(|_| unreachable!());
-
-        let mut records_by_step = BTreeMap::new();
-        records_by_step.insert("step_a".to_string(), vec![fixture_memory_record()]);
-        let context_source = InMemoryMemoryKernelContextSource { records_by_step };
-
-        let summary = Orchestrator::new(
+"#
+            ))
+            .unwrap_or_else(|err| panic!("workflow did not normalize: {err:#}"))
+        };
+        let context_source = super::StaticContextPackageSource::default();
+        let orchestrator = Orchestrator::new(
             &trace_store,
             &context_source,
             &AllowAllTrustGateSource,
             &DefaultHumanGateDecider,
             &NoopProposedWriteApplier,
-        )
-        .execute_workflow(
-            &workflow,
-            RunConfig {
-                non_interactive: true,
-                ..RunConfig::default()
-            },
+            ProviderRegistry::default(),
         );
-        let summary = match summary {
-            Ok(value) => value,
-            Err(err) => panic!("workflow execution failed: {err:#}"),
+        let as_of = time::OffsetDateTime::now_utc();
+        let run = |workflow: &super::NormalizedWorkflowEnvelope, rerun_of: Option<StepRerun>| {
+            orchestrator.execute_workflow(
+                workflow,
+                RunConfig {
+                    as_of: Some(as_of),
+                    non_interactive: true,
+                    rerun_of,
+                    ..RunConfig::default()
+                },
+            )
+        };
+        let rerun_of = |source_run_id, step_key: &str| {
+            Some(StepRerun {
+                source_run_id,
+                step_key: step_key.to_string(),
+            })
         };
+        let memoized_from = |run_id| {
+            trace_store
+                .get_step_records(run_id)
+                .unwrap_or_else(|_| unreachable!())
+                .into_iter()
+                .map(|step| (step.step_key, step.memoized_from_run))
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        let first = run(&workflow("b"), None).unwrap_or_else(|err| panic!("run failed: {err:#}"));
+        let rerun = run(&workflow("b fixed"), rerun_of(first.run_id, "step_b"))
+            .unwrap_or_else(|err| panic!("rerun failed: {err:#}"));
+        assert_eq!((rerun.steps_succeeded, rerun.provider_calls), (4, 2));
         assert_eq!(
-            summary.status,
-            multi_agent_center_domain::RunStatus::Succeeded
+            memoized_from(rerun.run_id),
+            BTreeMap::from([
+                ("step_a".to_string(), Some(first.run_id)),
+                ("step_b".to_string(), None),
+                ("step_c".to_string(), None),
+                ("step_d".to_string(), Some(first.run_id)),
+            ])
+        );
+        let rerun_record = trace_store
+            .get_run(rerun.run_id)
+            .unwrap_or_else(|_| unreachable!())
+            .unwrap_or_else(|| unreachable!());
+        assert_eq!(rerun_record.rerun_of, rerun_of(first.run_id, "step_b"));
+        assert_eq!(rerun_record.replay_of_run_id, None);
+        assert_eq!(
+            warning_codes(&trace_store, rerun.run_id),
+            vec!["step_rerun", "step_memoized", "step_memoized"]
         );
 
-        let packages = trace_store.get_step_context_packages(summary.run_id);
-        assert!(packages.is_ok());
-        let packages = packages.unwrap_or_else(|_| unreachable!());
-        assert_eq!(packages.len(), 1);
-        assert_eq!(packages[0].envelope.context_package.query.actor, "dev");
-        assert_eq!(packages[0].envelope.context_package.query.action, "read");
-        assert_eq!(packages[0].envelope.context_package.query.resource, "repo");
+        // Steps memoized in the source run reuse the run they were memoized from.
+        let second = run(&workflow("b fixed"), rerun_of(rerun.run_id, "step_c"))
+            .unwrap_or_else(|err| panic!("rerun failed: {err:#}"));
+        assert_eq!(second.provider_calls, 1);
+        let reused = memoized_from(second.run_id);
+        assert_eq!(reused["step_a"], Some(first.run_id));
+        assert_eq!(reused["step_b"], Some(rerun.run_id));
+        assert_eq!(reused["step_c"], None);
+
+        assert!(run(&workflow("b"), rerun_of(first.run_id, "step_x")).is_err());
+        assert!(run(&workflow("b"), rerun_of(RunId::new(), "step_b")).is_err());
     }
 
     #[test]
-    #[allow(clippy::too_many_lines)]
-    fn api_memory_kernel_context_source_builds_step_packages() {
-        let trace_db = temp_db_path("api-context-source");
-        let memory_db = temp_db_path("api-memory-db");
-
-        let api = MemoryKernelApi::new(memory_db.clone());
-        assert!(api.migrate(false).is_ok());
-        assert!(api
-            .add_constraint(AddConstraintRequest {
-                actor: "dev".to_string(),
-                action: "read".to_string(),
-                resource: "repo".to_string(),
-                effect: ConstraintEffect::Allow,
-                note: None,
-                memory_id: None,
-                version: 1,
-                writer: "test".to_string(),
-                justification: "seed constraint".to_string(),
-                source_uri: "file:///constraint.md".to_string(),
-                source_hash: None,
-                evidence: Vec::new(),
-                confidence: Some(0.9),
-                truth_status: TruthStatus::Observed,
-                authority: Authority::Authoritative,
-                created_at: None,
-                effective_at: None,
-                supersedes: Vec::new(),
-                contradicts: Vec::new(),
-            })
-            .is_ok());
-        assert!(api
-            .add_summary(AddSummaryRequest {
-                record_type: RecordType::Decision,
-                summary: "repo policy decision".to_string(),
-                memory_id: None,
-                version: 1,
-                writer: "test".to_string(),
-                justification: "seed decision".to_string(),
-                source_uri: "file:///decision.md".to_string(),
-                source_hash: None,
-                evidence: Vec::new(),
-                confidence: Some(0.8),
-                truth_status: TruthStatus::Observed,
-                authority: Authority::Derived,
-                created_at: None,
-                effective_at: None,
-                supersedes: Vec::new(),
-                contradicts: Vec::new(),
-            })
-            .is_ok());
-
-        let trace_store = SqliteTraceStore::open(&trace_db);
-        assert!(trace_store.is_ok());
-        let trace_store = trace_store.unwrap_or_else(|_| unreachable!());
+    fn correlated_submission_reuses_non_failed_run() {
+        let trace_db = temp_db_path("correlated-reuse");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
         assert!(trace_store.migrate().is_ok());
-
-        let workflow_yaml = r#"
+        let workflow = normalize_workflow_yaml(
+            r#"
 workflow_name: wf
 workflow_version: v1
 normalization_version: 0
@@ -2504,33 +13112,89 @@ agents:
 steps:
   - step_key: step_a
     agent_name: planner
-    task:
-      context_queries:
-        - { mode: "policy", text: "Can dev read repo?", actor: "dev", action: "read", resource: "repo" }
-        - { mode: "recall", text: "repo policy decision", record_types: ["decision"] }
+    task: { text: "a" }
     depends_on: []
     gate_points: []
 gates: []
 defaults:
   non_interactive: true
-"#;
-        let workflow = normalize_workflow_yaml(workflow_yaml);
-        assert!(workflow.is_ok());
-        let workflow = workflow.unwrap_or_else(|_| unreachable!());
+"#,
+        )
+        .unwrap_or_else(|_| unreachable!());
+
+        let context_source = super::StaticContextPackageSource::default();
+        let orchestrator = Orchestrator::new(
+            &trace_store,
+            &context_source,
+            &AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
+        );
+        let submit = |reuse: bool| match orchestrator.execute_workflow(
+            &workflow,
+            RunConfig {
+                non_interactive: true,
+                external_correlation_id: Some("sched-42".to_string()),
+                reuse_correlated_run: reuse,
+                ..RunConfig::default()
+            },
+        ) {
+            Ok(summary) => summary,
+            Err(err) => panic!("workflow execution failed: {err:#}"),
+        };
 
-        let context_source = ApiMemoryKernelContextSource::new(&memory_db);
-        let trust_source = AllowAllTrustGateSource;
-        let human_gate = DefaultHumanGateDecider;
-        let write_applier = NoopProposedWriteApplier;
+        let first = submit(true);
+        assert!(!first.reused_existing_run);
+        let second = submit(true);
+        assert!(second.reused_existing_run);
+        assert_eq!(second.run_id, first.run_id);
+        assert_eq!(second.status, first.status);
+        assert_eq!(
+            (second.steps_total, second.steps_succeeded),
+            (first.steps_total, first.steps_succeeded)
+        );
+        assert_eq!(second.usage, first.usage);
+
+        let third = submit(false);
+        assert!(!third.reused_existing_run);
+        assert_ne!(third.run_id, first.run_id);
+        let runs = trace_store
+            .find_runs_by_external_correlation_id("sched-42")
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(runs.len(), 2);
+    }
+
+    #[test]
+    fn single_step_execution_gates_context_and_traces_an_adhoc_run() {
+        let trace_db = temp_db_path("single-step");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let agent: AgentDefinition = serde_json::from_value(json!({
+            "agent_name": "planner",
+            "role": "planning",
+            "provider": {"provider_name": "mock", "model_id": "x"},
+        }))
+        .unwrap_or_else(|_| unreachable!());
+        let mut by_step = BTreeMap::new();
+        by_step.insert(
+            super::ADHOC_STEP_KEY.to_string(),
+            vec![fixture_context_package(super::ADHOC_STEP_KEY)],
+        );
+        let context_source = super::StaticContextPackageSource::with_step_packages(by_step);
         let summary = Orchestrator::new(
             &trace_store,
             &context_source,
-            &trust_source,
-            &human_gate,
-            &write_applier,
+            &SelectiveTrustGate,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
         )
-        .execute_workflow(
-            &workflow,
+        .execute_single_step(
+            &agent,
+            json!({"text": "summarize"}),
+            StepConstraints::default(),
             RunConfig {
                 non_interactive: true,
                 ..RunConfig::default()
@@ -2538,127 +13202,479 @@ defaults:
         );
         let summary = match summary {
             Ok(value) => value,
-            Err(err) => panic!("workflow execution failed: {err:#}"),
+            Err(err) => panic!("single step execution failed: {err:#}"),
         };
         assert_eq!(
             summary.status,
             multi_agent_center_domain::RunStatus::Succeeded
         );
+        assert_eq!((summary.steps_total, summary.steps_succeeded), (1, 1));
+        assert_eq!(summary.trust_excluded, 1);
+        assert_eq!(summary.provider_calls, 1);
+
+        let run = trace_store
+            .get_run(summary.run_id)
+            .unwrap_or_else(|_| unreachable!())
+            .unwrap_or_else(|| unreachable!());
+        assert_eq!(run.workflow_name, "adhoc.planner");
+        let steps = trace_store
+            .get_step_records(summary.run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].step_key, super::ADHOC_STEP_KEY);
+        assert!(trace_store
+            .get_workflow_snapshot(&run.workflow_hash)
+            .is_ok_and(|snapshot| snapshot.is_some()));
+    }
+
+    #[test]
+    fn context_preview_reports_permission_and_trust_decisions_per_item() {
+        let step_yaml = |max_items: u32| {
+            format!(
+                r"
+agent:
+  agent_name: reviewer
+  role: review
+  provider:
+    provider_name: mock
+    model_id: x
+  permissions:
+    allowed_record_types: [constraint]
+    allowed_tools: []
+    max_context_items: {max_items}
+    can_propose_memory_writes: false
+    fail_on_permission_prune: false
+step:
+  step_key: review
+  agent_name: reviewer
+  task:
+    text: check constraints
+"
+            )
+        };
+        let mut by_step = BTreeMap::new();
+        by_step.insert(
+            "review".to_string(),
+            vec![fixture_context_package("review")],
+        );
+        let context_source = super::StaticContextPackageSource::with_step_packages(by_step);
+        let as_of = time::OffsetDateTime::now_utc();
+
+        let decisions = |max_items: u32| {
+            let definition =
+                multi_agent_center_workflow::parse_standalone_step_yaml(&step_yaml(max_items))
+                    .unwrap_or_else(|err| panic!("invalid step yaml: {err:#}"));
+            let preview = super::preview_step_context(
+                &context_source,
+                &SelectiveTrustGate,
+                &definition,
+                as_of,
+            )
+            .unwrap_or_else(|err| panic!("preview failed: {err:#}"));
+            assert_eq!(preview.step_key, "review");
+            assert_eq!(preview.injected_context_packages.len(), 1);
+            assert_eq!(
+                preview.injected_context_packages[0]
+                    .context_package
+                    .selected_items
+                    .len(),
+                1
+            );
+            preview
+                .item_decisions
+                .into_iter()
+                .map(|item| (item.version, item.decision, item.reasons))
+                .collect::<Vec<_>>()
+        };
 
-        let packages = trace_store.get_step_context_packages(summary.run_id);
-        assert!(packages.is_ok());
-        let packages = packages.unwrap_or_else(|_| unreachable!());
-        assert_eq!(packages.len(), 2);
         assert_eq!(
-            packages[0].envelope.context_package.query.actor, "dev",
-            "first query should run policy mode ask() semantics"
+            decisions(10),
+            vec![
+                (1, "injected".to_string(), vec!["fixture".to_string()]),
+                (
+                    2,
+                    "excluded_by_trust".to_string(),
+                    vec!["fixture".to_string()]
+                ),
+            ]
+        );
+        assert_eq!(
+            decisions(1),
+            vec![
+                (1, "injected".to_string(), vec!["fixture".to_string()]),
+                (
+                    2,
+                    "pruned_by_permissions".to_string(),
+                    vec!["max_context_items_exceeded".to_string()]
+                ),
+            ]
         );
-        assert_eq!(packages[0].envelope.context_package.query.action, "read");
-        assert_eq!(packages[0].envelope.context_package.query.resource, "repo");
-        assert!(packages[0]
-            .envelope
-            .context_package
-            .selected_items
-            .iter()
-            .any(|item| item.record_type == RecordType::Constraint));
-        assert!(packages[1]
-            .envelope
-            .context_package
-            .selected_items
-            .iter()
-            .all(|item| item.record_type == RecordType::Decision));
     }
 
     #[test]
-    fn trust_gate_decisions_are_persisted_per_memory_ref() {
-        let trace_db = temp_db_path("trust-gates");
-        let trace_store = SqliteTraceStore::open(&trace_db);
-        assert!(trace_store.is_ok());
-        let trace_store = trace_store.unwrap_or_else(|_| unreachable!());
+    #[allow(clippy::too_many_lines)]
+    fn enforced_permission_prune_fails_the_step_and_is_recorded_in_the_manifest() {
+        let trace_store = SqliteTraceStore::open(&temp_db_path("enforce-prune"))
+            .unwrap_or_else(|_| unreachable!());
         assert!(trace_store.migrate().is_ok());
-
-        let workflow_yaml = r#"
+        let workflow = |defaults: &str| {
+            normalize_workflow_yaml(&format!(
+                r"
 workflow_name: wf
 workflow_version: v1
 normalization_version: 0
 agents:
-  - agent_name: planner
-    role: planning
+  - agent_name: reviewer
+    role: review
     provider:
       provider_name: mock
       model_id: x
+    permissions:
+      allowed_record_types: [constraint]
+      max_context_items: 1
+      fail_on_permission_prune: true
 steps:
-  - step_key: step_a
-    agent_name: planner
-    task: { text: "a" }
+  - step_key: review
+    agent_name: reviewer
+    task: {{ text: check }}
     depends_on: []
     gate_points: []
-gates: []
 defaults:
   non_interactive: true
-"#;
-        let workflow = normalize_workflow_yaml(workflow_yaml);
-        assert!(workflow.is_ok());
-        let workflow = workflow.unwrap_or_else(|_| unreachable!());
-
+{defaults}
+"
+            ))
+            .unwrap_or_else(|err| panic!("workflow did not normalize: {err:#}"))
+        };
         let mut by_step = BTreeMap::new();
         by_step.insert(
-            "step_a".to_string(),
-            vec![fixture_context_package("step_a")],
+            "review".to_string(),
+            vec![fixture_context_package("review")],
         );
         let context_source = super::StaticContextPackageSource::with_step_packages(by_step);
-        let trust_source = SelectiveTrustGate;
-        let human_gate = ApproveHumanGate;
-        let write_applier = NoopProposedWriteApplier;
-        let summary = Orchestrator::new(
+        let orchestrator = Orchestrator::new(
             &trace_store,
             &context_source,
-            &trust_source,
-            &human_gate,
-            &write_applier,
-        )
-        .execute_workflow(
-            &workflow,
-            RunConfig {
-                non_interactive: true,
-                ..RunConfig::default()
-            },
+            &AllowAllTrustGateSource,
+            &DefaultHumanGateDecider,
+            &NoopProposedWriteApplier,
+            ProviderRegistry::default(),
         );
-        let summary = match summary {
-            Ok(value) => value,
-            Err(err) => panic!("workflow execution failed: {err:#}"),
+        let run = |defaults: &str, enforce_permission_prune: bool| {
+            let summary = orchestrator
+                .execute_workflow(
+                    &workflow(defaults),
+                    RunConfig {
+                        non_interactive: true,
+                        enforce_permission_prune,
+                        ..RunConfig::default()
+                    },
+                )
+                .unwrap_or_else(|err| panic!("workflow execution failed: {err:#}"));
+            let manifest = trace_store
+                .get_run_manifest(summary.run_id)
+                .unwrap_or_else(|_| unreachable!())
+                .unwrap_or_else(|| unreachable!());
+            let step = trace_store
+                .get_step_records(summary.run_id)
+                .unwrap_or_else(|_| unreachable!())
+                .remove(0);
+            (summary, manifest["permission_prune"].clone(), step)
         };
 
-        let conn = rusqlite::Connection::open(&trace_db).unwrap_or_else(|_| unreachable!());
-        let trust_rows: i64 = conn
-            .query_row(
-                "SELECT COUNT(*) FROM step_gate_decisions WHERE run_id = ?1 AND gate_kind = ?2",
-                rusqlite::params![summary.run_id.to_string(), "trust"],
-                |row| row.get(0),
-            )
+        // Without the engine policy the flag is still only a warning.
+        let (summary, policy, step) = run("", false);
+        assert_eq!(summary.steps_succeeded, 1);
+        assert_eq!(policy, "continue");
+        assert_eq!(step.status, StepStatus::Succeeded);
+        assert!(warning_codes(&trace_store, summary.run_id)
+            .contains(&"fail_on_permission_prune_ignored".to_string()));
+
+        for (defaults, enforce) in [("", true), ("  enforce_permission_prune: true", false)] {
+            let (summary, policy, step) = run(defaults, enforce);
+            assert_eq!(summary.status, multi_agent_center_domain::RunStatus::Failed);
+            assert_eq!(
+                (summary.steps_failed_or_rejected, summary.provider_calls),
+                (1, 0)
+            );
+            assert_eq!(policy, "fail");
+            assert_eq!(step.status, StepStatus::Failed);
+            assert_eq!(
+                step.error_json
+                    .as_ref()
+                    .and_then(|error| error["code"].as_str()),
+                Some("permission_pruned")
+            );
+            assert!(!warning_codes(&trace_store, summary.run_id)
+                .contains(&"fail_on_permission_prune_ignored".to_string()));
+            let decisions = trace_store
+                .get_step_gate_decisions(summary.run_id)
+                .unwrap_or_else(|_| unreachable!());
+            let prune = &decisions
+                .iter()
+                .find(|row| row.decision.gate_name == "context_permission")
+                .unwrap_or_else(|| unreachable!())
+                .decision;
+            assert_eq!(
+                prune.decision,
+                multi_agent_center_domain::GateDecision::Rejected
+            );
+            assert!(prune
+                .reason_codes
+                .contains(&"fail_on_permission_prune".to_string()));
+        }
+    }
+
+    #[test]
+    fn trust_gate_selection_validates_flags_and_outcome_database() {
+        use memory_kernel_outcome_store_sqlite::SqliteOutcomeStore;
+        use std::path::Path;
+
+        let selection = |source: Option<&str>, db: Option<&Path>| {
+            super::TrustGateSelection::from_flags(source, db, "safe")
+        };
+        assert!(matches!(
+            selection(None, None),
+            Ok(super::TrustGateSelection::AllowAll)
+        ));
+        assert!(selection(Some("outcome-memory"), None).is_err());
+        assert!(selection(Some("allow-all"), Some(Path::new("x.sqlite"))).is_err());
+        assert!(selection(Some("nope"), None).is_err());
+        assert!(super::TrustGateSelection::from_flags(None, None, "reckless").is_err());
+
+        let missing = temp_db_path("trust-select-missing");
+        let selected = selection(None, Some(&missing)).unwrap_or_else(|_| unreachable!());
+        assert_eq!(selected.as_str(), "outcome-memory");
+        assert!(selected.open(None).is_err());
+        assert!(!missing.exists());
+
+        let unmigrated = temp_db_path("trust-select-unmigrated");
+        rusqlite::Connection::open(&unmigrated)
+            .and_then(|conn| conn.execute_batch("CREATE TABLE other(id INTEGER);"))
             .unwrap_or_else(|_| unreachable!());
-        let rejected_rows: i64 = conn
-            .query_row(
-                "SELECT COUNT(*) FROM step_gate_decisions
-                 WHERE run_id = ?1 AND gate_kind = ?2 AND decision = ?3",
-                rusqlite::params![summary.run_id.to_string(), "trust", "rejected"],
-                |row| row.get(0),
+        let selected =
+            selection(Some("outcome-memory"), Some(&unmigrated)).unwrap_or_else(|_| unreachable!());
+        let err = selected.open(None).err().unwrap_or_else(|| unreachable!());
+        assert!(err.to_string().contains("not migrated"));
+
+        let migrated = temp_db_path("trust-select-migrated");
+        let mut kernel =
+            super::MemoryKernelSqliteStore::open(&migrated).unwrap_or_else(|_| unreachable!());
+        assert!(kernel.migrate().is_ok());
+        let store = SqliteOutcomeStore::open(&migrated).unwrap_or_else(|_| unreachable!());
+        assert!(store.migrate().is_ok());
+        let selected = selection(None, Some(&migrated)).unwrap_or_else(|_| unreachable!());
+        assert_eq!(selected.outcome_db(), Some(migrated.as_path()));
+        assert!(selected.open(None).is_ok());
+    }
+
+    #[test]
+    fn trust_gate_benchmark_measures_evaluation_and_filtering_per_step() {
+        let config = match super::TrustGateBenchmarkConfig::from_flags(40, 25, 3, "safe") {
+            Ok(config) => config,
+            Err(err) => panic!("benchmark config: {err}"),
+        };
+        let report = match super::benchmark_trust_gate(&config) {
+            Ok(report) => report,
+            Err(err) => panic!("trust gate benchmark failed: {err}"),
+        };
+
+        assert_eq!(report.trust_rows, 40);
+        assert_eq!(report.candidates, 25);
+        assert_eq!(report.iterations, 3);
+        assert_eq!(report.mode, "safe");
+        assert_eq!(report.included, 20);
+        assert_eq!(report.excluded, 5);
+        assert!(report.evaluate_p50_ms <= report.evaluate_max_ms);
+        assert!(report.package_bytes_after < report.package_bytes_before);
+        assert!(report.attachment_bytes > 0);
+
+        assert!(super::TrustGateBenchmarkConfig::from_flags(40, 25, 3, "reckless").is_err());
+        let empty = super::TrustGateBenchmarkConfig {
+            candidates: 0,
+            ..config
+        };
+        assert!(super::benchmark_trust_gate(&empty).is_err());
+    }
+
+    #[test]
+    fn pinned_ruleset_governs_outcome_trust_gate_for_pin_context() {
+        use super::{
+            now_utc, ContextRef, OutcomeEventInput, OutcomeEventType, OutcomeRuleset,
+            RetrievalMode, TrustGateSource,
+        };
+        use memory_kernel_outcome_store_sqlite::SqliteOutcomeStore;
+        use multi_agent_center_domain::{RunId, StepId};
+
+        let outcome_db = temp_db_path("trust-pin");
+        let item = fixture_context_package("step_a")
+            .context_package
+            .selected_items[0]
+            .clone();
+        let conn = rusqlite::Connection::open(&outcome_db).unwrap_or_else(|_| unreachable!());
+        assert!(
+            memory_kernel_outcome_store_sqlite::seed_minimal_memory_record(
+                &conn,
+                item.memory_id,
+                item.version
             )
-            .unwrap_or_else(|_| unreachable!());
+            .is_ok()
+        );
+        let mut store = SqliteOutcomeStore::open(&outcome_db).unwrap_or_else(|_| unreachable!());
+        assert!(store.migrate().is_ok());
+        assert!(store
+            .append_event(&OutcomeEventInput {
+                event_id: None,
+                ruleset_version: 1,
+                memory_id: item.memory_id,
+                version: item.version,
+                event_type: OutcomeEventType::Success,
+                occurred_at: now_utc(),
+                writer: "fixture".to_string(),
+                justification: "fixture".to_string(),
+                context_id: None,
+                edited: false,
+                escalated: false,
+                severity: None,
+                manual_confidence: None,
+                override_cap: false,
+                payload_json: json!({}),
+                cosigner: None,
+                signature: None,
+                source: None,
+                effective_at: None,
+            })
+            .is_ok());
+        assert!(store.replay(None).is_ok());
+        let mut strict = OutcomeRuleset::v1();
+        strict.ruleset_version = 2;
+        strict.safe_min_confidence = 0.99;
+        assert!(store.upsert_ruleset(&strict).is_ok());
+        assert!(store
+            .set_ruleset_pin("wf_regulated*", 2, "compliance", None)
+            .is_ok());
 
-        assert_eq!(trust_rows, 2);
-        assert_eq!(rejected_rows, 1);
+        let refs = [ContextRef {
+            memory_id: item.memory_id,
+            version: item.version,
+            memory_version_id: item.memory_version_id,
+        }];
+        let evaluate = |source: &super::OutcomeMemoryTrustGateSource| {
+            source
+                .evaluate(RunId::new(), StepId::new(), "step_a", now_utc(), &refs)
+                .unwrap_or_else(|_| unreachable!())
+        };
+        let source = super::OutcomeMemoryTrustGateSource::new(&outcome_db, RetrievalMode::Safe);
+
+        let unpinned = evaluate(&source);
+        assert_eq!(unpinned[0].ruleset_version, Some(1));
+        assert_eq!(unpinned[0].source, "outcome_memory.live");
+
+        let pinned = evaluate(&source.clone().with_pin_context("wf_regulated"));
+        assert_eq!(pinned[0].ruleset_version, Some(2));
+        assert!(!pinned[0].include);
+        assert_eq!(pinned[0].source, "outcome_memory.live+pin:wf_regulated*");
+        assert!(pinned[0]
+            .reason_codes
+            .contains(&"ruleset.pinned.v2".to_string()));
+
+        let other = evaluate(&source.with_pin_context("wf_other"));
+        assert_eq!(other[0].ruleset_version, Some(1));
     }
 
     #[test]
-    fn dag_with_parallel_ready_steps_executes_successfully() {
-        let trace_db = temp_db_path("parallel-ready");
-        let trace_store = SqliteTraceStore::open(&trace_db);
-        assert!(trace_store.is_ok());
-        let trace_store = trace_store.unwrap_or_else(|_| unreachable!());
-        assert!(trace_store.migrate().is_ok());
+    fn supersession_gating_excludes_refs_to_versions_with_a_trusted_successor() {
+        use super::{
+            now_utc, ContextRef, OutcomeEventInput, OutcomeEventType, RetrievalMode,
+            TrustGateSource,
+        };
+        use memory_kernel_outcome_store_sqlite::SqliteOutcomeStore;
+        use multi_agent_center_domain::{RunId, StepId};
 
-        let workflow_yaml = r#"
+        let outcome_db = temp_db_path("trust-supersession");
+        let item = fixture_context_package("step_a")
+            .context_package
+            .selected_items[0]
+            .clone();
+        let conn = rusqlite::Connection::open(&outcome_db).unwrap_or_else(|_| unreachable!());
+        let versions = [item.version, item.version + 1];
+        for version in versions {
+            assert!(
+                memory_kernel_outcome_store_sqlite::seed_minimal_memory_record(
+                    &conn,
+                    item.memory_id,
+                    version
+                )
+                .is_ok()
+            );
+        }
+        let mut store = SqliteOutcomeStore::open(&outcome_db).unwrap_or_else(|_| unreachable!());
+        assert!(store.migrate().is_ok());
+        // The successor is set above the Safe threshold; a single success would leave it too
+        // weakly trusted to supersede anything.
+        for version in versions {
+            let successor = version > item.version;
+            assert!(store
+                .append_event(&OutcomeEventInput {
+                    event_id: None,
+                    ruleset_version: 1,
+                    memory_id: item.memory_id,
+                    version,
+                    event_type: if successor {
+                        OutcomeEventType::ManualSetConfidence
+                    } else {
+                        OutcomeEventType::Success
+                    },
+                    occurred_at: now_utc(),
+                    writer: "fixture".to_string(),
+                    justification: "fixture".to_string(),
+                    context_id: None,
+                    edited: false,
+                    escalated: false,
+                    severity: None,
+                    manual_confidence: successor.then_some(0.9),
+                    override_cap: false,
+                    payload_json: json!({}),
+                    cosigner: None,
+                    signature: None,
+                    source: None,
+                    effective_at: None,
+                })
+                .is_ok());
+        }
+        assert!(store.replay(None).is_ok());
+
+        let refs = [ContextRef {
+            memory_id: item.memory_id,
+            version: item.version,
+            memory_version_id: item.memory_version_id,
+        }];
+        let source =
+            super::OutcomeMemoryTrustGateSource::new(&outcome_db, RetrievalMode::Exploration);
+        let evaluate = |source: &super::OutcomeMemoryTrustGateSource| {
+            source
+                .evaluate(RunId::new(), StepId::new(), "step_a", now_utc(), &refs)
+                .unwrap_or_else(|_| unreachable!())
+        };
+
+        assert!(evaluate(&source)[0].include);
+        let superseded = evaluate(&source.with_supersession_gating());
+        assert!(!superseded[0].include);
+        assert_eq!(
+            superseded[0].reason_codes,
+            vec![memory_kernel_core::REASON_EXCLUDED_SUPERSEDED_BY_NEWER_VERSION.to_string()]
+        );
+    }
+
+    #[test]
+    fn reason_code_registry_rejects_unregistered_trust_gate_codes() {
+        use memory_kernel_core::{ReasonCodeRegistry, ReasonCodeSpec, ReasonSeverity};
+
+        let trace_db = temp_db_path("reason-codes");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+        let workflow = normalize_workflow_yaml(
+            r#"
 workflow_name: wf
 workflow_version: v1
 normalization_version: 0
@@ -2672,51 +13688,107 @@ steps:
   - step_key: step_a
     agent_name: planner
     task: { text: "a" }
-    depends_on: []
-    gate_points: []
-  - step_key: step_b
-    agent_name: planner
-    task: { text: "b" }
-    depends_on: []
-    gate_points: []
-  - step_key: step_c
-    agent_name: planner
-    task: { text: "c" }
-    depends_on: [step_a, step_b]
-    gate_points: []
-gates: []
 defaults:
   non_interactive: true
-"#;
-        let workflow = normalize_workflow_yaml(workflow_yaml);
-        assert!(workflow.is_ok());
-        let workflow = workflow.unwrap_or_else(|_| unreachable!());
-
-        let context_source = super::StaticContextPackageSource::default();
-        let trust_source = AllowAllTrustGateSource;
-        let human_gate = DefaultHumanGateDecider;
-        let write_applier = NoopProposedWriteApplier;
-        let summary = Orchestrator::new(
-            &trace_store,
-            &context_source,
-            &trust_source,
-            &human_gate,
-            &write_applier,
+"#,
         )
-        .execute_workflow(
-            &workflow,
-            RunConfig {
-                non_interactive: true,
-                ..RunConfig::default()
-            },
-        );
-        assert!(summary.is_ok());
-        let summary = summary.unwrap_or_else(|_| unreachable!());
-        assert_eq!(
-            summary.status,
-            multi_agent_center_domain::RunStatus::Succeeded
+        .unwrap_or_else(|_| unreachable!());
+        let mut by_step = BTreeMap::new();
+        by_step.insert(
+            "step_a".to_string(),
+            vec![fixture_context_package("step_a")],
         );
-        assert_eq!(summary.steps_total, 3);
-        assert_eq!(summary.steps_succeeded, 3);
+        let context_source = super::StaticContextPackageSource::with_step_packages(by_step);
+        let run = |registry: &ReasonCodeRegistry| {
+            Orchestrator::new(
+                &trace_store,
+                &context_source,
+                &SelectiveTrustGate,
+                &ApproveHumanGate,
+                &NoopProposedWriteApplier,
+                ProviderRegistry::default(),
+            )
+            .with_reason_code_registry(registry)
+            .execute_workflow(
+                &workflow,
+                RunConfig {
+                    non_interactive: true,
+                    ..RunConfig::default()
+                },
+            )
+        };
+
+        let mut registry = ReasonCodeRegistry::default();
+        let rejected = run(&registry);
+        assert!(rejected
+            .is_err_and(|err| format!("{err:#}").contains("unregistered reason code(s): fixture")));
+
+        assert!(registry
+            .register(ReasonCodeSpec::custom(
+                "fixture",
+                "test",
+                ReasonSeverity::Info,
+                "Emitted by the selective test gate."
+            ))
+            .is_ok());
+        assert!(run(&registry).is_ok());
+    }
+
+    #[test]
+    fn required_trust_gating_refuses_allow_all_source() {
+        let trace_db = temp_db_path("require-trust");
+        let trace_store = SqliteTraceStore::open(&trace_db).unwrap_or_else(|_| unreachable!());
+        assert!(trace_store.migrate().is_ok());
+
+        let workflow_yaml = |required: bool| {
+            format!(
+                r"
+workflow_name: wf_trust
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: a
+    role: r
+    provider:
+      provider_name: mock
+      model_id: x
+steps:
+  - step_key: s1
+    agent_name: a
+defaults:
+  non_interactive: true
+  require_trust_gating: {required}
+"
+            )
+        };
+        let context_source = super::StaticContextPackageSource::default();
+        let run = |trust_source: &dyn TrustGateSource, required_by_workflow, required_by_config| {
+            let workflow = normalize_workflow_yaml(&workflow_yaml(required_by_workflow))
+                .unwrap_or_else(|_| unreachable!());
+            Orchestrator::new(
+                &trace_store,
+                &context_source,
+                trust_source,
+                &DefaultHumanGateDecider,
+                &NoopProposedWriteApplier,
+                ProviderRegistry::default(),
+            )
+            .execute_workflow(
+                &workflow,
+                RunConfig {
+                    non_interactive: true,
+                    require_trust_gating: required_by_config,
+                    ..RunConfig::default()
+                },
+            )
+        };
+
+        let refused = run(&AllowAllTrustGateSource, true, false);
+        assert!(refused.is_err_and(|err| err.to_string().contains("requires trust gating")));
+        assert!(run(&AllowAllTrustGateSource, false, true).is_err());
+        assert!(trace_store.list_runs().is_ok_and(|runs| runs.is_empty()));
+
+        assert!(run(&AllowAllTrustGateSource, false, false).is_ok());
+        assert!(run(&SelectiveTrustGate, true, true).is_ok());
     }
 }
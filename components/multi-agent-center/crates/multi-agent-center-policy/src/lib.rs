@@ -12,6 +12,8 @@ pub struct PrunedReference {
     pub memory_id: String,
     pub version: u32,
     pub record_type: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
     pub reason: String,
 }
 
@@ -46,11 +48,25 @@ pub fn apply_context_permissions(
                     memory_id: item.memory_id.to_string(),
                     version: item.version,
                     record_type: item.record_type.as_str().to_string(),
+                    tags: item.tags.clone(),
                     reason: "record_type_not_allowed".to_string(),
                 });
                 continue;
             }
 
+            if !permissions.permits_tags(&item.tags) {
+                pruned.push(PrunedReference {
+                    package_slot: package.package_slot,
+                    memory_version_id: item.memory_version_id.to_string(),
+                    memory_id: item.memory_id.to_string(),
+                    version: item.version,
+                    record_type: item.record_type.as_str().to_string(),
+                    tags: item.tags.clone(),
+                    reason: memory_kernel_core::REASON_RECORD_TAG_NOT_ALLOWED.to_string(),
+                });
+                continue;
+            }
+
             if let Some(limit) = permissions.max_context_items {
                 if total_selected >= limit {
                     pruned.push(PrunedReference {
@@ -59,7 +75,8 @@ pub fn apply_context_permissions(
                         memory_id: item.memory_id.to_string(),
                         version: item.version,
                         record_type: item.record_type.as_str().to_string(),
-                        reason: "max_context_items_exceeded".to_string(),
+                        tags: item.tags.clone(),
+                        reason: memory_kernel_core::REASON_MAX_CONTEXT_ITEMS_EXCEEDED.to_string(),
                     });
                     continue;
                 }
@@ -79,7 +96,20 @@ pub fn apply_context_permissions(
                     memory_id: item.memory_id.to_string(),
                     version: item.version,
                     record_type: item.record_type.as_str().to_string(),
-                    reason: "excluded_record_type_not_allowed".to_string(),
+                    tags: item.tags.clone(),
+                    reason: memory_kernel_core::REASON_EXCLUDED_RECORD_TYPE_NOT_ALLOWED.to_string(),
+                });
+                continue;
+            }
+            if !permissions.permits_tags(&item.tags) {
+                pruned.push(PrunedReference {
+                    package_slot: package.package_slot,
+                    memory_version_id: item.memory_version_id.to_string(),
+                    memory_id: item.memory_id.to_string(),
+                    version: item.version,
+                    record_type: item.record_type.as_str().to_string(),
+                    tags: item.tags.clone(),
+                    reason: memory_kernel_core::REASON_EXCLUDED_RECORD_TAG_NOT_ALLOWED.to_string(),
                 });
                 continue;
             }
@@ -134,6 +164,7 @@ mod tests {
                 truth_status: TruthStatus::Asserted,
                 confidence: Some(0.8),
                 authority: Authority::Authoritative,
+                tags: vec!["deploy".to_string()],
                 why: Why {
                     included: true,
                     reasons: vec!["fixture".to_string()],
@@ -149,6 +180,7 @@ mod tests {
                 truth_status: TruthStatus::Observed,
                 confidence: Some(0.6),
                 authority: Authority::Derived,
+                tags: vec!["billing".to_string()],
                 why: Why {
                     included: true,
                     reasons: vec!["fixture".to_string()],
@@ -164,6 +196,7 @@ mod tests {
                 truth_status: TruthStatus::Observed,
                 confidence: Some(0.55),
                 authority: Authority::Derived,
+                tags: vec!["deploy".to_string(), "incident".to_string()],
                 why: Why {
                     included: true,
                     reasons: vec!["fixture".to_string()],
@@ -213,6 +246,8 @@ mod tests {
             max_context_items: Some(1),
             can_propose_memory_writes: false,
             fail_on_permission_prune: false,
+            allowed_record_tags: Vec::new(),
+            denied_record_tags: Vec::new(),
         };
 
         let result = apply_context_permissions(&packages, &permissions);
@@ -244,6 +279,8 @@ mod tests {
             max_context_items: None,
             can_propose_memory_writes: false,
             fail_on_permission_prune: false,
+            allowed_record_tags: Vec::new(),
+            denied_record_tags: Vec::new(),
         };
 
         let result = apply_context_permissions(&packages, &permissions);
@@ -254,4 +291,34 @@ mod tests {
         assert!(result.pruned_references.is_empty());
         assert!(!result.packages[0].package_hash.is_empty());
     }
+
+    #[test]
+    fn prunes_items_outside_allowed_or_inside_denied_tags() {
+        let packages = vec![fixture_package()];
+        let permissions = EffectivePermissions {
+            allowed_record_types: Vec::new(),
+            allowed_tools: Vec::new(),
+            max_context_items: None,
+            can_propose_memory_writes: false,
+            fail_on_permission_prune: false,
+            allowed_record_tags: vec!["deploy".to_string()],
+            denied_record_tags: vec!["incident".to_string()],
+        };
+
+        let result = apply_context_permissions(&packages, &permissions);
+        assert!(result.is_ok());
+        let result = result.unwrap_or_else(|_| unreachable!());
+
+        let selected = &result.packages[0].context_package.selected_items;
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].record_type, RecordType::Constraint);
+        assert_eq!(result.pruned_references.len(), 2);
+        for pruned in &result.pruned_references {
+            assert_eq!(
+                pruned.reason,
+                memory_kernel_core::REASON_RECORD_TAG_NOT_ALLOWED
+            );
+            assert!(!pruned.tags.is_empty());
+        }
+    }
 }
@@ -1,12 +1,16 @@
 #![forbid(unsafe_code)]
 
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
 use multi_agent_center_domain::{
-    hash_json, now_utc, ProposedMemoryWrite, ProviderCallRecord, StepOutputEnvelope, StepRequest,
+    compute_step_memo_key, hash_json, now_utc, AppliedPersona, ProposedMemoryWrite,
+    ProviderCallRecord, RecordedProviderResponse, StepOutputEnvelope, StepRequest,
 };
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use sha2::{Digest, Sha256};
 use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use ulid::Ulid;
 
@@ -17,6 +21,107 @@ pub trait ProviderAdapter {
     fn invoke(&self, request: &StepRequest) -> Result<ProviderInvocation>;
 }
 
+/// Provider adapters by [`ProviderAdapter::provider_name`], which agent provider bindings select
+/// by `provider_name`. The default registry holds the built-in `mock`, `http_json`, and `openai`
+/// adapters; embedders add their own, or replace a built-in, with [`ProviderRegistry::register`].
+#[derive(Clone)]
+pub struct ProviderRegistry {
+    adapters: BTreeMap<&'static str, Arc<dyn ProviderAdapter + Send + Sync>>,
+}
+
+impl Default for ProviderRegistry {
+    fn default() -> Self {
+        Self::empty()
+            .with_adapter(MockProvider::new())
+            .with_adapter(HttpJsonProvider::new())
+            .with_adapter(OpenAiProvider::new())
+    }
+}
+
+impl std::fmt::Debug for ProviderRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProviderRegistry")
+            .field("providers", &self.provider_names())
+            .finish()
+    }
+}
+
+impl ProviderRegistry {
+    /// A registry without any adapter, not even the built-ins.
+    #[must_use]
+    pub fn empty() -> Self {
+        Self {
+            adapters: BTreeMap::new(),
+        }
+    }
+
+    /// Register `adapter` under its provider name, replacing any adapter already registered
+    /// under that name.
+    pub fn register(&mut self, adapter: impl ProviderAdapter + Send + Sync + 'static) {
+        self.adapters
+            .insert(adapter.provider_name(), Arc::new(adapter));
+    }
+
+    /// [`ProviderRegistry::register`] in builder form.
+    #[must_use]
+    pub fn with_adapter(mut self, adapter: impl ProviderAdapter + Send + Sync + 'static) -> Self {
+        self.register(adapter);
+        self
+    }
+
+    /// Registered provider names, in order.
+    #[must_use]
+    pub fn provider_names(&self) -> Vec<&'static str> {
+        self.adapters.keys().copied().collect()
+    }
+
+    /// The adapter registered as `provider_name`.
+    ///
+    /// # Errors
+    /// Returns an error naming the registered providers when none is registered as
+    /// `provider_name`.
+    pub fn adapter(&self, provider_name: &str) -> Result<Arc<dyn ProviderAdapter + Send + Sync>> {
+        self.adapters.get(provider_name).cloned().ok_or_else(|| {
+            let supported = self
+                .provider_names()
+                .iter()
+                .map(|name| format!("'{name}'"))
+                .collect::<Vec<_>>();
+            anyhow!(
+                "unsupported provider adapter '{provider_name}'; supported providers are {}",
+                if supported.is_empty() {
+                    "none".to_string()
+                } else {
+                    supported.join(", ")
+                }
+            )
+        })
+    }
+
+    /// Invoke the adapter `request.agent.provider` selects.
+    ///
+    /// # Errors
+    /// Returns an error when no adapter is registered under the binding's provider name, or
+    /// the adapter fails.
+    pub fn invoke(&self, request: &StepRequest) -> Result<ProviderInvocation> {
+        self.adapter(&request.agent.provider.provider_name)?
+            .invoke(request)
+    }
+}
+
+/// Transport-level provider failure (connection refused, DNS, timeout). Unlike configuration or
+/// adapter errors it may succeed against another binding or on a later attempt.
+#[derive(Debug)]
+pub struct ProviderTransportError(pub String);
+
+impl std::fmt::Display for ProviderTransportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "http transport failure: {}", self.0)
+    }
+}
+
+impl std::error::Error for ProviderTransportError {}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ProviderInvocation {
     pub provider_call: ProviderCallRecord,
@@ -24,15 +129,117 @@ pub struct ProviderInvocation {
     pub proposed_memory_writes: Vec<ProposedMemoryWrite>,
 }
 
+/// Scripted responses for [`MockProvider`], usually loaded from YAML:
+///
+/// ```yaml
+/// responses:
+///   - step_key: plan
+///     message: "plan ready"
+///     payload: { steps: 3 }
+///     latency_ms: 120
+///     input_tokens: 40
+///     output_tokens: 12
+///   - step_key: review
+///     failure: { kind: transport, message: "connection reset" }
+///   - step_key: review
+///     message: "approved"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MockScript {
+    #[serde(default)]
+    pub responses: Vec<MockScriptEntry>,
+}
+
+/// One scripted response. `request_hash` matches the run-independent request key
+/// (`compute_step_memo_key`) and takes precedence over `step_key`. Several entries for the same
+/// key answer successive calls in order, the last one repeating.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MockScriptEntry {
+    #[serde(default)]
+    pub step_key: Option<String>,
+    #[serde(default)]
+    pub request_hash: Option<String>,
+    /// Output message; defaults to `mock:<step_key>`.
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub payload: Option<Value>,
+    /// Latency recorded on the provider call; no time is actually spent.
+    #[serde(default)]
+    pub latency_ms: Option<u64>,
+    #[serde(default)]
+    pub input_tokens: Option<u32>,
+    #[serde(default)]
+    pub output_tokens: Option<u32>,
+    #[serde(default)]
+    pub failure: Option<MockFailure>,
+}
+
+/// A scripted failure, e.g. `failure: { kind: transport, message: "connection reset" }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MockFailure {
+    pub kind: MockFailureKind,
+    pub message: String,
+}
+
+/// `transport` failures are retryable like an unreachable endpoint; `error` is a non-retryable
+/// adapter error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MockFailureKind {
+    Transport,
+    Error,
+}
+
+impl MockScript {
+    /// Parse a script from YAML (JSON is valid YAML).
+    ///
+    /// # Errors
+    /// Returns an error when the YAML does not describe a script or an entry has neither
+    /// `step_key` nor `request_hash`.
+    pub fn from_yaml_str(raw: &str) -> Result<Self> {
+        let script: Self =
+            serde_yaml::from_str(raw).map_err(|err| anyhow!("invalid mock script: {err}"))?;
+        if let Some(position) = script
+            .responses
+            .iter()
+            .position(|entry| entry.step_key.is_none() && entry.request_hash.is_none())
+        {
+            return Err(anyhow!(
+                "mock script response {position} needs a step_key or request_hash"
+            ));
+        }
+        Ok(script)
+    }
+
+    /// Load a script from a YAML file.
+    ///
+    /// # Errors
+    /// Returns an error when the file cannot be read or parsed.
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read mock script {}", path.display()))?;
+        Self::from_yaml_str(&raw)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct MockProvider {
     adapter_version: String,
+    script: Option<Arc<MockScript>>,
+    /// Scripted calls answered so far, by step key.
+    scripted_calls: Arc<Mutex<BTreeMap<String, usize>>>,
 }
 
 impl Default for MockProvider {
     fn default() -> Self {
         Self {
             adapter_version: "mock.v1".to_string(),
+            script: None,
+            scripted_calls: Arc::default(),
         }
     }
 }
@@ -43,6 +250,116 @@ impl MockProvider {
         Self::default()
     }
 
+    /// Answer requests matched by `script` with their scripted responses; other requests keep
+    /// the deterministic default output.
+    #[must_use]
+    pub fn with_script(script: MockScript) -> Self {
+        Self {
+            script: Some(Arc::new(script)),
+            ..Self::default()
+        }
+    }
+
+    /// The scripted entry for the next call of `request`, if the script covers it.
+    fn scripted_entry(&self, request: &StepRequest) -> Result<Option<MockScriptEntry>> {
+        let Some(script) = &self.script else {
+            return Ok(None);
+        };
+        let memo_key = if script
+            .responses
+            .iter()
+            .any(|entry| entry.request_hash.is_some())
+        {
+            Some(compute_step_memo_key(request)?)
+        } else {
+            None
+        };
+        let by_hash: Vec<&MockScriptEntry> = script
+            .responses
+            .iter()
+            .filter(|entry| entry.request_hash.is_some() && entry.request_hash == memo_key)
+            .collect();
+        let candidates = if by_hash.is_empty() {
+            script
+                .responses
+                .iter()
+                .filter(|entry| {
+                    entry.request_hash.is_none()
+                        && entry.step_key.as_deref() == Some(request.step_key.as_str())
+                })
+                .collect()
+        } else {
+            by_hash
+        };
+        if candidates.is_empty() {
+            return Ok(None);
+        }
+        let mut calls = self
+            .scripted_calls
+            .lock()
+            .map_err(|_| anyhow!("mock script call counter poisoned"))?;
+        let answered = calls.entry(request.step_key.clone()).or_default();
+        let entry = candidates[(*answered).min(candidates.len() - 1)].clone();
+        *answered += 1;
+        Ok(Some(entry))
+    }
+
+    fn invoke_scripted(
+        &self,
+        request: &StepRequest,
+        entry: MockScriptEntry,
+    ) -> Result<ProviderInvocation> {
+        if let Some(failure) = entry.failure {
+            return Err(match failure.kind {
+                MockFailureKind::Transport => ProviderTransportError(failure.message).into(),
+                MockFailureKind::Error => anyhow!(failure.message),
+            });
+        }
+
+        let started_at = now_utc();
+        let request_json = build_request_json(request, self.provider_name(), &self.adapter_version);
+        let request_hash = hash_json(&request_json)?;
+        let message = entry
+            .message
+            .unwrap_or_else(|| format!("mock:{}", request.step_key));
+        let response_json = json!({
+            "message": message,
+            "payload": entry.payload.unwrap_or_else(|| json!({})),
+        });
+        let response_hash = hash_json(&response_json)?;
+        let latency_ms = entry.latency_ms.unwrap_or(0);
+        let ended_at = started_at
+            + Duration::from_millis(latency_ms)
+                .try_into()
+                .unwrap_or(time::Duration::ZERO);
+
+        Ok(ProviderInvocation {
+            provider_call: ProviderCallRecord {
+                provider_call_id: Ulid::new(),
+                provider_name: self.provider_name().to_string(),
+                adapter_version: self.adapter_version.clone(),
+                model_id: request.agent.provider.model_id.clone(),
+                request_json,
+                request_hash,
+                response_json: response_json.clone(),
+                response_hash,
+                latency_ms: Some(latency_ms),
+                input_tokens: entry.input_tokens,
+                output_tokens: entry.output_tokens,
+                started_at,
+                ended_at,
+                status: "succeeded".to_string(),
+                error_text: None,
+                persona_json: request.persona.as_ref().map(AppliedPersona::trace_json),
+            },
+            output: StepOutputEnvelope {
+                message,
+                payload: response_json,
+            },
+            proposed_memory_writes: Vec::new(),
+        })
+    }
+
     fn deterministic_token(&self, request: &StepRequest) -> String {
         let mut hasher = Sha256::new();
         hasher.update(request.input_hash.as_bytes());
@@ -58,6 +375,9 @@ impl ProviderAdapter for MockProvider {
     }
 
     fn invoke(&self, request: &StepRequest) -> Result<ProviderInvocation> {
+        if let Some(entry) = self.scripted_entry(request)? {
+            return self.invoke_scripted(request, entry);
+        }
         let started_at = now_utc();
         let token = self.deterministic_token(request);
         let request_json = build_request_json(request, self.provider_name(), &self.adapter_version);
@@ -106,6 +426,7 @@ impl ProviderAdapter for MockProvider {
             ended_at,
             status: "succeeded".to_string(),
             error_text: None,
+            persona_json: request.persona.as_ref().map(AppliedPersona::trace_json),
         };
 
         let output = StepOutputEnvelope {
@@ -190,7 +511,7 @@ impl ProviderAdapter for HttpJsonProvider {
                 )
             }
             Err(ureq::Error::Transport(err)) => {
-                return Err(anyhow::anyhow!("http transport failure: {err}"));
+                return Err(ProviderTransportError(err.to_string()).into());
             }
         };
 
@@ -200,14 +521,7 @@ impl ProviderAdapter for HttpJsonProvider {
         });
         let response_hash = hash_json(&response_json)?;
         let ended_at = now_utc();
-        let latency_ms = {
-            let millis = (ended_at - started_at).whole_milliseconds();
-            if millis <= 0 {
-                Some(0)
-            } else {
-                u64::try_from(millis).ok()
-            }
-        };
+        let latency_ms = elapsed_ms(started_at, ended_at);
 
         let provider_call = ProviderCallRecord {
             provider_call_id: Ulid::new(),
@@ -225,6 +539,7 @@ impl ProviderAdapter for HttpJsonProvider {
             ended_at,
             status: status.clone(),
             error_text,
+            persona_json: request.persona.as_ref().map(AppliedPersona::trace_json),
         };
 
         let output_message = if status == "succeeded" {
@@ -244,13 +559,371 @@ impl ProviderAdapter for HttpJsonProvider {
     }
 }
 
+/// `OpenAI` chat-completions adapter (`provider_name: openai`). The agent's role, instructions,
+/// and persona become the system message and the task, prompt, and injected context the user
+/// message; the model must answer with a JSON object `{message, payload,
+/// proposed_memory_writes}`, the last only read for agents allowed to propose memory writes.
+/// Token usage is recorded on the provider call.
+#[derive(Debug, Clone)]
+pub struct OpenAiProvider {
+    adapter_version: String,
+}
+
+impl Default for OpenAiProvider {
+    fn default() -> Self {
+        Self {
+            adapter_version: "openai.v1".to_string(),
+        }
+    }
+}
+
+impl OpenAiProvider {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ProviderAdapter for OpenAiProvider {
+    fn provider_name(&self) -> &'static str {
+        "openai"
+    }
+
+    fn invoke(&self, request: &StepRequest) -> Result<ProviderInvocation> {
+        let config = OpenAiProviderConfig::from_provider_params(&request.agent.provider.params)?;
+        let started_at = now_utc();
+        let request_json = build_request_json(request, self.provider_name(), &self.adapter_version);
+        let request_hash = hash_json(&request_json)?;
+        let outbound_json = openai_chat_request(request, &config.sampling)?;
+
+        let agent = ureq::AgentBuilder::new()
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .build();
+        let req = agent
+            .post(&format!(
+                "{}/chat/completions",
+                config.base_url.trim_end_matches('/')
+            ))
+            .set("content-type", "application/json")
+            .set("authorization", &format!("Bearer {}", config.api_key));
+
+        let (status_code, body_json) = match req.send_json(&outbound_json) {
+            Ok(response) => {
+                let code = response.status();
+                (code, response.into_json::<Value>()?)
+            }
+            Err(ureq::Error::Status(code, response)) => {
+                (code, response.into_json::<Value>().unwrap_or(Value::Null))
+            }
+            Err(ureq::Error::Transport(err)) => {
+                return Err(ProviderTransportError(err.to_string()).into());
+            }
+        };
+
+        let response_json = json!({
+            "status_code": status_code,
+            "body": body_json,
+        });
+        let response_hash = hash_json(&response_json)?;
+        let ended_at = now_utc();
+        let parsed = parse_openai_response(request, &response_json);
+
+        Ok(ProviderInvocation {
+            provider_call: ProviderCallRecord {
+                provider_call_id: Ulid::new(),
+                provider_name: self.provider_name().to_string(),
+                adapter_version: self.adapter_version.clone(),
+                model_id: request.agent.provider.model_id.clone(),
+                request_json: outbound_json,
+                request_hash,
+                response_json,
+                response_hash,
+                latency_ms: elapsed_ms(started_at, ended_at),
+                input_tokens: parsed.input_tokens,
+                output_tokens: parsed.output_tokens,
+                started_at,
+                ended_at,
+                status: if parsed.error_text.is_none() {
+                    "succeeded"
+                } else {
+                    "failed"
+                }
+                .to_string(),
+                error_text: parsed.error_text,
+                persona_json: request.persona.as_ref().map(AppliedPersona::trace_json),
+            },
+            output: parsed.output,
+            proposed_memory_writes: parsed.proposed_memory_writes,
+        })
+    }
+}
+
+/// A chat-completions response (`{status_code, body}` as recorded) read as a step output.
+struct OpenAiResponse {
+    output: StepOutputEnvelope,
+    proposed_memory_writes: Vec<ProposedMemoryWrite>,
+    input_tokens: Option<u32>,
+    output_tokens: Option<u32>,
+    /// Set when the call failed or the model's answer is not the requested JSON object.
+    error_text: Option<String>,
+}
+
+fn openai_chat_request(request: &StepRequest, sampling: &OpenAiSampling) -> Result<Value> {
+    let agent = &request.agent;
+    let mut system = vec![format!(
+        "You are {}, acting as {}.",
+        agent.agent_name, agent.role
+    )];
+    if let Some(applied) = &request.persona {
+        system.push(applied.persona.system_prompt.clone());
+        system.extend(applied.persona.style_constraints.iter().cloned());
+    }
+    system.extend(agent.default_instructions.iter().cloned());
+    system.push(
+        "Answer with one JSON object: `message`, a short string summarizing your answer, and \
+         `payload`, the structured result."
+            .to_string(),
+    );
+    if request.effective_permissions.can_propose_memory_writes {
+        system.push(
+            "To propose memories worth recording, add `proposed_memory_writes`: an array of \
+             objects with a `payload` and a string `justification`."
+                .to_string(),
+        );
+    }
+
+    let mut user = json!({
+        "step_key": request.step_key,
+        "task": request.task_payload,
+        "context": request
+            .injected_context_packages
+            .iter()
+            .map(|pkg| json!({"source": pkg.source, "package": pkg.context_package}))
+            .collect::<Vec<_>>(),
+    });
+    if let Some(prompt) = &request.prompt {
+        user["prompt"] = json!(prompt.body);
+    }
+    if let Some(summary) = &request.trust_gate_summary {
+        user["trust_gate_summary"] = summary.clone();
+    }
+
+    let mut body = json!({
+        "model": agent.provider.model_id,
+        "messages": [
+            {"role": "system", "content": system.join("\n\n")},
+            {"role": "user", "content": serde_json::to_string(&user)?},
+        ],
+        "response_format": {"type": "json_object"},
+    });
+    if let Some(temperature) = sampling.temperature {
+        body["temperature"] = json!(temperature);
+    }
+    if let Some(max_tokens) = sampling.max_tokens {
+        body["max_tokens"] = json!(max_tokens);
+    }
+    Ok(body)
+}
+
+fn parse_openai_response(request: &StepRequest, response_json: &Value) -> OpenAiResponse {
+    let body = &response_json["body"];
+    let tokens = |field: &str| {
+        body["usage"][field]
+            .as_u64()
+            .and_then(|count| u32::try_from(count).ok())
+    };
+    let input_tokens = tokens("prompt_tokens");
+    let output_tokens = tokens("completion_tokens");
+    let failed = |error_text: String| OpenAiResponse {
+        output: StepOutputEnvelope {
+            message: format!("openai:{}:failed", request.step_key),
+            payload: response_json.clone(),
+        },
+        proposed_memory_writes: Vec::new(),
+        input_tokens,
+        output_tokens,
+        error_text: Some(error_text),
+    };
+
+    let status_code = response_json["status_code"].as_u64().unwrap_or_default();
+    if !(200..300).contains(&status_code) {
+        return failed(match body["error"]["message"].as_str() {
+            Some(detail) => format!("http status {status_code}: {detail}"),
+            None => format!("http status {status_code}"),
+        });
+    }
+    let Some(content) = body["choices"][0]["message"]["content"].as_str() else {
+        return failed("openai response has no choices[0].message.content".to_string());
+    };
+    let Ok(answer @ Value::Object(_)) = serde_json::from_str::<Value>(content) else {
+        return failed("openai answer is not a JSON object".to_string());
+    };
+    let message = answer["message"].as_str().unwrap_or_default().to_string();
+    let proposed_memory_writes = if request.effective_permissions.can_propose_memory_writes {
+        match parse_proposed_memory_writes(&answer["proposed_memory_writes"]) {
+            Ok(writes) => writes,
+            Err(err) => return failed(err),
+        }
+    } else {
+        Vec::new()
+    };
+
+    OpenAiResponse {
+        output: StepOutputEnvelope {
+            message: message.clone(),
+            payload: json!({
+                "message": message,
+                "payload": answer.get("payload").cloned().unwrap_or(Value::Null),
+            }),
+        },
+        proposed_memory_writes,
+        input_tokens,
+        output_tokens,
+        error_text: None,
+    }
+}
+
+fn parse_proposed_memory_writes(
+    raw: &Value,
+) -> std::result::Result<Vec<ProposedMemoryWrite>, String> {
+    let proposals = match raw {
+        Value::Null => return Ok(Vec::new()),
+        Value::Array(proposals) => proposals,
+        _ => return Err("openai answer proposed_memory_writes must be an array".to_string()),
+    };
+    proposals
+        .iter()
+        .enumerate()
+        .map(|(proposal_index, proposal)| {
+            let justification = proposal["justification"].as_str().ok_or_else(|| {
+                format!(
+                    "openai proposed_memory_writes[{proposal_index}] needs a string justification"
+                )
+            })?;
+            Ok(ProposedMemoryWrite {
+                proposal_index,
+                payload: proposal.get("payload").cloned().unwrap_or(Value::Null),
+                justification: justification.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Rebuild the invocation a provider produced for `recorded` without calling it. Attempts that
+/// failed before the adapter produced a response (adapter version `unavailable`) are replayed as
+/// errors, transport failures as [`ProviderTransportError`] so they stay retryable. The replayed
+/// call records the request body the adapter sends, so it hashes like the recorded one.
+///
+/// # Errors
+/// Returns the recorded failure, or an error if the request cannot be hashed.
+pub fn replay_recorded_response(
+    request: &StepRequest,
+    recorded: &RecordedProviderResponse,
+) -> Result<ProviderInvocation> {
+    if recorded.adapter_version == "unavailable" {
+        let error_text = recorded.error_text.clone().unwrap_or_default();
+        return Err(match error_text.strip_prefix("http transport failure: ") {
+            Some(detail) => ProviderTransportError(detail.to_string()).into(),
+            None => anyhow::anyhow!(error_text),
+        });
+    }
+
+    let now = now_utc();
+    let request_json =
+        build_request_json(request, &recorded.provider_name, &recorded.adapter_version);
+    let request_hash = hash_json(&request_json)?;
+    let request_json = match recorded.provider_name.as_str() {
+        "http_json" => json!({
+            "model_id": request.agent.provider.model_id,
+            "request": request_json,
+        }),
+        "openai" => openai_chat_request(
+            request,
+            &OpenAiSampling::from_provider_params(&request.agent.provider.params)?,
+        )?,
+        _ => request_json,
+    };
+    if recorded.provider_name == "openai" {
+        let parsed = parse_openai_response(request, &recorded.response_json);
+        return Ok(ProviderInvocation {
+            provider_call: ProviderCallRecord {
+                provider_call_id: Ulid::new(),
+                provider_name: recorded.provider_name.clone(),
+                adapter_version: recorded.adapter_version.clone(),
+                model_id: recorded.model_id.clone(),
+                request_hash,
+                request_json,
+                response_json: recorded.response_json.clone(),
+                response_hash: hash_json(&recorded.response_json)?,
+                latency_ms: Some(0),
+                input_tokens: parsed.input_tokens,
+                output_tokens: parsed.output_tokens,
+                started_at: now,
+                ended_at: now,
+                status: recorded.status.clone(),
+                error_text: recorded.error_text.clone(),
+                persona_json: request.persona.as_ref().map(AppliedPersona::trace_json),
+            },
+            output: parsed.output,
+            proposed_memory_writes: parsed.proposed_memory_writes,
+        });
+    }
+    let message = match recorded.provider_name.as_str() {
+        "http_json" if recorded.status == "succeeded" => {
+            format!("http_json:{}:ok", request.step_key)
+        }
+        "http_json" => format!("http_json:{}:failed", request.step_key),
+        _ => recorded
+            .response_json
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+    };
+
+    Ok(ProviderInvocation {
+        provider_call: ProviderCallRecord {
+            provider_call_id: Ulid::new(),
+            provider_name: recorded.provider_name.clone(),
+            adapter_version: recorded.adapter_version.clone(),
+            model_id: recorded.model_id.clone(),
+            request_hash,
+            request_json,
+            response_json: recorded.response_json.clone(),
+            response_hash: hash_json(&recorded.response_json)?,
+            latency_ms: Some(0),
+            input_tokens: None,
+            output_tokens: None,
+            started_at: now,
+            ended_at: now,
+            status: recorded.status.clone(),
+            error_text: recorded.error_text.clone(),
+            persona_json: request.persona.as_ref().map(AppliedPersona::trace_json),
+        },
+        output: StepOutputEnvelope {
+            message,
+            payload: recorded.response_json.clone(),
+        },
+        proposed_memory_writes: Vec::new(),
+    })
+}
+
+fn elapsed_ms(started_at: time::OffsetDateTime, ended_at: time::OffsetDateTime) -> Option<u64> {
+    let millis = (ended_at - started_at).whole_milliseconds();
+    if millis <= 0 {
+        Some(0)
+    } else {
+        u64::try_from(millis).ok()
+    }
+}
+
 fn build_request_json(request: &StepRequest, provider_name: &str, adapter_version: &str) -> Value {
     let selected_count: usize = request
         .injected_context_packages
         .iter()
         .map(|pkg| pkg.context_package.selected_items.len())
         .sum();
-    json!({
+    let mut value = json!({
         "provider_name": provider_name,
         "adapter_version": adapter_version,
         "run_id": request.run_id.to_string(),
@@ -262,7 +935,32 @@ fn build_request_json(request: &StepRequest, provider_name: &str, adapter_versio
         "context_selected_count": selected_count,
         "trust_gate_count": request.trust_gate_attachments.len(),
         "input_hash": request.input_hash,
-    })
+    });
+    if let Some(object) = value.as_object_mut() {
+        if let Some(summary) = &request.trust_gate_summary {
+            object.insert("trust_gate_summary".to_string(), summary.clone());
+        }
+        if let Some(applied) = &request.persona {
+            object.insert(
+                "system_prompt".to_string(),
+                json!(applied.persona.system_prompt),
+            );
+            object.insert(
+                "style_constraints".to_string(),
+                json!(applied.persona.style_constraints),
+            );
+            object.insert("persona".to_string(), applied.trace_json());
+        }
+        if let Some(prompt) = &request.prompt {
+            object.insert("prompt_ref".to_string(), json!(prompt.prompt_ref));
+            object.insert("prompt".to_string(), json!(prompt.body));
+            object.insert(
+                "prompt_template_hash".to_string(),
+                json!(prompt.template_hash),
+            );
+        }
+    }
+    value
 }
 
 #[derive(Debug, Clone)]
@@ -329,15 +1027,86 @@ impl HttpProviderConfig {
     }
 }
 
+#[derive(Debug, Clone)]
+struct OpenAiProviderConfig {
+    base_url: String,
+    api_key: String,
+    timeout_ms: u64,
+    sampling: OpenAiSampling,
+}
+
+/// The `OpenAI` provider params that shape the chat request body, which a replay rebuilds
+/// without the API key.
+#[derive(Debug, Clone)]
+struct OpenAiSampling {
+    temperature: Option<f64>,
+    max_tokens: Option<u64>,
+}
+
+impl OpenAiProviderConfig {
+    fn from_provider_params(params: &Value) -> Result<Self> {
+        let base_url = params
+            .get("base_url")
+            .and_then(Value::as_str)
+            .unwrap_or("https://api.openai.com/v1")
+            .to_string();
+        let api_key_env = params
+            .get("api_key_env")
+            .and_then(Value::as_str)
+            .unwrap_or("OPENAI_API_KEY");
+        let api_key = std::env::var(api_key_env).map_err(|_| {
+            anyhow::anyhow!("missing env var '{api_key_env}' required by the openai provider")
+        })?;
+        let timeout_ms = params
+            .get("timeout_ms")
+            .and_then(Value::as_u64)
+            .unwrap_or(60_000);
+
+        Ok(Self {
+            base_url,
+            api_key,
+            timeout_ms,
+            sampling: OpenAiSampling::from_provider_params(params)?,
+        })
+    }
+}
+
+impl OpenAiSampling {
+    fn from_provider_params(params: &Value) -> Result<Self> {
+        let temperature = match params.get("temperature") {
+            None => None,
+            Some(value) => Some(value.as_f64().ok_or_else(|| {
+                anyhow::anyhow!("openai provider params.temperature must be a number")
+            })?),
+        };
+        let max_tokens = match params.get("max_tokens") {
+            None => None,
+            Some(value) => Some(value.as_u64().ok_or_else(|| {
+                anyhow::anyhow!("openai provider params.max_tokens must be a positive integer")
+            })?),
+        };
+
+        Ok(Self {
+            temperature,
+            max_tokens,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{HttpJsonProvider, MockProvider, ProviderAdapter};
+    use super::{
+        parse_openai_response, replay_recorded_response, HttpJsonProvider, MockProvider,
+        MockScript, OpenAiProvider, ProviderAdapter, ProviderInvocation, ProviderRegistry,
+        ProviderTransportError,
+    };
     use memory_kernel_core::RecordType;
     use multi_agent_center_domain::{
-        AgentDefinition, AgentPermissions, EffectivePermissions, ProviderBinding, RunId,
-        StepConstraints, StepId, StepRequest,
+        compute_step_memo_key, AgentDefinition, AgentPermissions, EffectivePermissions,
+        ProviderBinding, RecordedProviderResponse, RunId, StepConstraints, StepId, StepRequest,
     };
     use serde_json::json;
+    use std::io::{BufRead, BufReader, Read, Write};
 
     fn fixture_request(provider_name: &str, params: serde_json::Value) -> StepRequest {
         let agent = AgentDefinition {
@@ -354,9 +1123,14 @@ mod tests {
                 max_context_items: Some(10),
                 can_propose_memory_writes: false,
                 fail_on_permission_prune: false,
+                allowed_record_tags: Vec::new(),
+                denied_record_tags: Vec::new(),
             },
             default_instructions: vec!["do work".to_string()],
             metadata: std::collections::BTreeMap::default(),
+            persona: None,
+            fallbacks: Vec::new(),
+            load_balancing: None,
         };
         StepRequest {
             run_id: RunId::new(),
@@ -373,12 +1147,59 @@ mod tests {
                 max_context_items: Some(10),
                 can_propose_memory_writes: false,
                 fail_on_permission_prune: false,
+                allowed_record_tags: Vec::new(),
+                denied_record_tags: Vec::new(),
             },
             constraints: StepConstraints::default(),
+            trust_gate_summary: None,
+            persona: None,
+            prompt: None,
             input_hash: "fixture-input-hash".to_string(),
         }
     }
 
+    #[test]
+    fn provider_registry_routes_to_registered_adapters() {
+        struct EchoProvider;
+
+        impl ProviderAdapter for EchoProvider {
+            fn provider_name(&self) -> &'static str {
+                "echo"
+            }
+
+            fn invoke(&self, request: &StepRequest) -> anyhow::Result<ProviderInvocation> {
+                let mut invocation = MockProvider::new().invoke(request)?;
+                invocation.output.message = "echo".to_string();
+                Ok(invocation)
+            }
+        }
+
+        let mut registry = ProviderRegistry::default();
+        assert_eq!(
+            registry.provider_names(),
+            vec!["http_json", "mock", "openai"]
+        );
+        let request = fixture_request("echo", json!({}));
+        let message = match registry.invoke(&request) {
+            Ok(_) => panic!("expected an unregistered provider to fail"),
+            Err(err) => err.to_string(),
+        };
+        assert_eq!(
+            message,
+            "unsupported provider adapter 'echo'; supported providers are 'http_json', 'mock', \
+             'openai'"
+        );
+
+        registry.register(EchoProvider);
+        let invocation = registry
+            .invoke(&request)
+            .unwrap_or_else(|err| panic!("echo provider failed: {err}"));
+        assert_eq!(invocation.output.message, "echo");
+        assert!(ProviderRegistry::empty()
+            .invoke(&fixture_request("mock", json!({})))
+            .is_err());
+    }
+
     #[test]
     fn mock_provider_output_is_stable_for_same_input() {
         let request = fixture_request("mock", json!({}));
@@ -403,6 +1224,258 @@ mod tests {
         );
     }
 
+    #[test]
+    fn replayed_mock_response_reproduces_the_recorded_output() {
+        let request = fixture_request("mock", json!({}));
+        let live = MockProvider::new()
+            .invoke(&request)
+            .unwrap_or_else(|_| unreachable!());
+
+        let recorded = RecordedProviderResponse::from(&live.provider_call);
+        let replayed = replay_recorded_response(&request, &recorded);
+        assert!(replayed.is_ok());
+        let replayed = replayed.unwrap_or_else(|_| unreachable!());
+
+        assert_eq!(replayed.output, live.output);
+        assert_eq!(
+            replayed.provider_call.response_hash,
+            live.provider_call.response_hash
+        );
+    }
+
+    #[test]
+    fn replayed_transport_failure_stays_a_transport_error() {
+        let request = fixture_request("http_json", json!({}));
+        let recorded = RecordedProviderResponse {
+            provider_name: "http_json".to_string(),
+            adapter_version: "unavailable".to_string(),
+            model_id: "model-x".to_string(),
+            status: "failed".to_string(),
+            response_json: json!({"error": "http transport failure: connection refused"}),
+            error_text: Some("http transport failure: connection refused".to_string()),
+        };
+
+        let replayed = replay_recorded_response(&request, &recorded);
+        let err = replayed.err().unwrap_or_else(|| unreachable!());
+        assert!(err.downcast_ref::<ProviderTransportError>().is_some());
+        assert_eq!(
+            err.to_string(),
+            "http transport failure: connection refused"
+        );
+    }
+
+    #[test]
+    fn scripted_mock_provider_plays_responses_in_order() {
+        let request = fixture_request("mock", json!({}));
+        let script = MockScript::from_yaml_str(
+            r#"
+responses:
+  - step_key: step_a
+    failure: { kind: transport, message: "connection reset" }
+  - step_key: step_a
+    message: "scripted answer"
+    payload: { verdict: "ok" }
+    latency_ms: 250
+    input_tokens: 40
+    output_tokens: 12
+"#,
+        )
+        .unwrap_or_else(|_| unreachable!());
+        let provider = MockProvider::with_script(script);
+
+        let err = provider
+            .invoke(&request)
+            .err()
+            .unwrap_or_else(|| unreachable!());
+        assert!(err.downcast_ref::<ProviderTransportError>().is_some());
+        assert_eq!(err.to_string(), "http transport failure: connection reset");
+
+        for _ in 0..2 {
+            let invocation = provider.invoke(&request).unwrap_or_else(|_| unreachable!());
+            assert_eq!(invocation.output.message, "scripted answer");
+            assert_eq!(
+                invocation.output.payload,
+                json!({"message": "scripted answer", "payload": {"verdict": "ok"}})
+            );
+            let call = &invocation.provider_call;
+            assert_eq!(call.latency_ms, Some(250));
+            assert_eq!(call.input_tokens, Some(40));
+            assert_eq!(call.output_tokens, Some(12));
+            assert_eq!((call.ended_at - call.started_at).whole_milliseconds(), 250);
+        }
+
+        let mut other = fixture_request("mock", json!({}));
+        other.step_key = "step_b".to_string();
+        let unscripted = provider.invoke(&other).unwrap_or_else(|_| unreachable!());
+        assert!(unscripted.output.message.starts_with("mock:step_b:"));
+    }
+
+    #[test]
+    fn scripted_mock_provider_matches_request_hash_before_step_key() {
+        let request = fixture_request("mock", json!({}));
+        let memo_key = compute_step_memo_key(&request).unwrap_or_else(|_| unreachable!());
+        let script = MockScript::from_yaml_str(&format!(
+            "responses:\n  - step_key: step_a\n    message: by-step\n  - request_hash: {memo_key}\n    failure: {{ kind: error, message: \"bad request\" }}\n"
+        ))
+        .unwrap_or_else(|_| unreachable!());
+
+        let err = MockProvider::with_script(script)
+            .invoke(&request)
+            .err()
+            .unwrap_or_else(|| unreachable!());
+        assert!(err.downcast_ref::<ProviderTransportError>().is_none());
+        assert_eq!(err.to_string(), "bad request");
+    }
+
+    #[test]
+    fn mock_script_entries_need_a_match_key() {
+        let err = MockScript::from_yaml_str("responses:\n  - message: orphan\n")
+            .err()
+            .unwrap_or_else(|| unreachable!());
+        assert!(err.to_string().contains("needs a step_key or request_hash"));
+    }
+
+    /// Serve one HTTP request on a local port with a 200 JSON `body`, returning the port and a
+    /// handle yielding the raw request head and body.
+    fn serve_once(body: serde_json::Value) -> (u16, std::thread::JoinHandle<(String, String)>) {
+        let listener =
+            std::net::TcpListener::bind("127.0.0.1:0").unwrap_or_else(|_| unreachable!());
+        let port = listener
+            .local_addr()
+            .unwrap_or_else(|_| unreachable!())
+            .port();
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap_or_else(|_| unreachable!());
+            let mut reader = BufReader::new(stream.try_clone().unwrap_or_else(|_| unreachable!()));
+            let mut head = String::new();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                assert!(reader.read_line(&mut line).is_ok());
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap_or_default();
+                    }
+                }
+                head.push_str(&line);
+            }
+            let mut request_body = vec![0; content_length];
+            assert!(reader.read_exact(&mut request_body).is_ok());
+            let body = body.to_string();
+            assert!(write!(
+                stream,
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .is_ok());
+            (head, String::from_utf8(request_body).unwrap_or_default())
+        });
+        (port, handle)
+    }
+
+    #[test]
+    fn openai_provider_parses_structured_output_and_token_usage() {
+        let answer = json!({
+            "message": "plan ready",
+            "payload": {"steps": 3},
+            "proposed_memory_writes": [
+                {"payload": {"summary": "prefers tabs"}, "justification": "stated twice"}
+            ],
+        });
+        let (port, server) = serve_once(json!({
+            "id": "chatcmpl-1",
+            "choices": [{"index": 0, "message": {"role": "assistant", "content": answer.to_string()}}],
+            "usage": {"prompt_tokens": 21, "completion_tokens": 7, "total_tokens": 28},
+        }));
+        std::env::set_var("MAC_OPENAI_TEST_KEY", "test-key");
+        let mut request = fixture_request(
+            "openai",
+            json!({
+                "base_url": format!("http://127.0.0.1:{port}/v1/"),
+                "api_key_env": "MAC_OPENAI_TEST_KEY",
+                "temperature": 0.2,
+            }),
+        );
+        request.effective_permissions.can_propose_memory_writes = true;
+
+        let invocation = OpenAiProvider::new()
+            .invoke(&request)
+            .unwrap_or_else(|err| panic!("openai invocation failed: {err:#}"));
+        let (head, sent) = server.join().unwrap_or_else(|_| unreachable!());
+        assert!(head.starts_with("POST /v1/chat/completions "));
+        assert!(head
+            .to_ascii_lowercase()
+            .contains("authorization: bearer test-key"));
+        let sent: serde_json::Value = serde_json::from_str(&sent).unwrap_or_default();
+        assert_eq!(sent["model"], json!("model-x"));
+        assert_eq!(sent["temperature"], json!(0.2));
+        assert_eq!(sent["response_format"], json!({"type": "json_object"}));
+        assert!(sent["messages"][0]["content"]
+            .as_str()
+            .is_some_and(|system| system.contains("proposed_memory_writes")));
+
+        assert_eq!(invocation.output.message, "plan ready");
+        assert_eq!(
+            invocation.output.payload,
+            json!({"message": "plan ready", "payload": {"steps": 3}})
+        );
+        assert_eq!(invocation.proposed_memory_writes.len(), 1);
+        assert_eq!(
+            invocation.proposed_memory_writes[0].justification,
+            "stated twice"
+        );
+        let call = &invocation.provider_call;
+        assert_eq!(call.status, "succeeded");
+        assert_eq!((call.input_tokens, call.output_tokens), (Some(21), Some(7)));
+
+        let replayed = replay_recorded_response(&request, &RecordedProviderResponse::from(call))
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(replayed.output, invocation.output);
+        assert_eq!(
+            replayed.proposed_memory_writes,
+            invocation.proposed_memory_writes
+        );
+        assert_eq!(replayed.provider_call.input_tokens, Some(21));
+
+        // Without write permission proposals are ignored.
+        request.effective_permissions.can_propose_memory_writes = false;
+        let parsed = parse_openai_response(&request, &call.response_json);
+        assert!(parsed.error_text.is_none());
+        assert!(parsed.proposed_memory_writes.is_empty());
+    }
+
+    #[test]
+    fn openai_responses_that_are_not_the_requested_json_fail_the_call() {
+        let request = fixture_request("openai", json!({}));
+        let rate_limited = parse_openai_response(
+            &request,
+            &json!({"status_code": 429, "body": {"error": {"message": "slow down"}}}),
+        );
+        assert_eq!(
+            rate_limited.error_text.as_deref(),
+            Some("http status 429: slow down")
+        );
+        let prose = parse_openai_response(
+            &request,
+            &json!({"status_code": 200, "body": {"choices": [{"message": {"content": "plain text"}}]}}),
+        );
+        assert_eq!(
+            prose.error_text.as_deref(),
+            Some("openai answer is not a JSON object")
+        );
+        assert_eq!(prose.output.message, "openai:step_a:failed");
+
+        std::env::remove_var("MAC_OPENAI_MISSING_KEY");
+        let missing_key = OpenAiProvider::new().invoke(&fixture_request(
+            "openai",
+            json!({"api_key_env": "MAC_OPENAI_MISSING_KEY"}),
+        ));
+        assert!(missing_key.is_err_and(|err| err.to_string().contains("MAC_OPENAI_MISSING_KEY")));
+    }
+
     #[test]
     fn http_provider_requires_url() {
         let request = fixture_request("http_json", json!({}));
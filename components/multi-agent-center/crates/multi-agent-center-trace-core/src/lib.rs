@@ -1,10 +1,16 @@
 #![forbid(unsafe_code)]
 
+use std::collections::{BTreeMap, BTreeSet};
+
 use anyhow::Result;
 use multi_agent_center_domain::{
-    ContextPackageEnvelope, EventRow, GateDecisionRecord, ProposedMemoryWrite, ProviderCallRecord,
-    RunId, RunRecord, RunStatus, StepContextPackageRecord, StepId, StepRecord, StepStatus,
-    TraceEvent, WorkflowSnapshotRecord,
+    ApprovalDelegation, ContextPackageEnvelope, DateTimeUtc, DecisionWindow, DeferredGateRecord,
+    DeferredGateResolution, EventQuery, EventRow, GateDecisionRecord, MemoizedStepRecord,
+    PromptTemplateRecord, ProposedMemoryWrite, ProviderCallRecord, ReasonCodeStats, RunId,
+    RunProgress, RunRecord, RunStatus, RunUsage, StepCheckpoint, StepContextPackageRecord,
+    StepDurationStats, StepGateDecisionRecord, StepId, StepProposedWriteRecord,
+    StepProviderCallRecord, StepRecord, StepStatus, TraceArtifactClass, TraceEvent, WarningStats,
+    WorkflowSnapshotRecord,
 };
 
 pub trait TraceStore {
@@ -24,18 +30,35 @@ pub trait TraceStore {
     #[allow(clippy::missing_errors_doc)]
     fn insert_run(&self, run: &RunRecord) -> Result<()>;
 
+    /// Insert `run` unless a run with the same `external_correlation_id` that neither failed nor
+    /// was cancelled exists, checking and inserting in one transaction so concurrent submitters
+    /// claim the correlation id once. Returns the existing run when the claim is lost; a run
+    /// without a correlation id is always inserted.
+    #[allow(clippy::missing_errors_doc)]
+    fn insert_run_claiming_correlation(&self, run: &RunRecord) -> Result<Option<RunRecord>>;
+
+    /// Record the terminal status of a run. Implementations refresh the persisted step duration
+    /// statistics for the run's workflow hash when the run succeeded.
     #[allow(clippy::missing_errors_doc)]
     fn update_run_finished(&self, run_id: RunId, status: RunStatus) -> Result<()>;
 
+    /// Store the run manifest and its hash. `manifest` is kept verbatim so the hash can be
+    /// checked, and its `injected_memory` rollup read, without the rest of the trace.
     #[allow(clippy::missing_errors_doc)]
     fn update_run_manifest(
         &self,
         run_id: RunId,
+        manifest: &serde_json::Value,
         manifest_hash: &str,
         manifest_signature: Option<&str>,
         manifest_signature_status: &str,
     ) -> Result<()>;
 
+    /// The manifest stored by [`TraceStore::update_run_manifest`], or `None` for unknown runs
+    /// and runs recorded before manifests were stored.
+    #[allow(clippy::missing_errors_doc)]
+    fn get_run_manifest(&self, run_id: RunId) -> Result<Option<serde_json::Value>>;
+
     #[allow(clippy::missing_errors_doc)]
     fn insert_step(&self, step: &StepRecord) -> Result<()>;
 
@@ -48,14 +71,26 @@ pub trait TraceStore {
         error_json: Option<&serde_json::Value>,
     ) -> Result<()>;
 
+    #[allow(clippy::missing_errors_doc)]
+    fn update_step_retry(&self, step_id: StepId, retry_json: &serde_json::Value) -> Result<()>;
+
+    #[allow(clippy::missing_errors_doc)]
+    fn update_step_provider_selection(
+        &self,
+        step_id: StepId,
+        provider_selection_json: &serde_json::Value,
+    ) -> Result<()>;
+
     #[allow(clippy::missing_errors_doc)]
     fn append_event(&self, event: &TraceEvent) -> Result<i64>;
 
+    /// Record a context package injected into `attempt` (1-based) of a step.
     #[allow(clippy::missing_errors_doc)]
     fn append_context_package(
         &self,
         run_id: RunId,
         step_id: StepId,
+        attempt: u32,
         envelope: &ContextPackageEnvelope,
     ) -> Result<()>;
 
@@ -88,9 +123,51 @@ pub trait TraceStore {
     #[allow(clippy::missing_errors_doc)]
     fn list_runs(&self) -> Result<Vec<RunRecord>>;
 
+    /// Runs carrying every one of `labels`, most recent first; an empty filter lists all runs.
+    #[allow(clippy::missing_errors_doc)]
+    fn list_runs_with_labels(&self, labels: &BTreeMap<String, String>) -> Result<Vec<RunRecord>>;
+
+    /// Runs submitted with `external_correlation_id`, most recent first.
+    #[allow(clippy::missing_errors_doc)]
+    fn find_runs_by_external_correlation_id(
+        &self,
+        external_correlation_id: &str,
+    ) -> Result<Vec<RunRecord>>;
+
+    /// Nested runs started by `workflow_call` steps of `parent_run_id`, oldest first.
+    #[allow(clippy::missing_errors_doc)]
+    fn list_child_runs(&self, parent_run_id: RunId) -> Result<Vec<RunRecord>>;
+
     #[allow(clippy::missing_errors_doc)]
     fn list_events_for_run(&self, run_id: RunId) -> Result<Vec<EventRow>>;
 
+    /// Events across runs matching `query`, in `event_seq` order.
+    #[allow(clippy::missing_errors_doc)]
+    fn query_events(&self, query: &EventQuery) -> Result<Vec<EventRow>>;
+
+    #[allow(clippy::missing_errors_doc)]
+    fn append_step_checkpoint(&self, checkpoint: &StepCheckpoint) -> Result<()>;
+
+    /// Checkpoints recorded for `step_id`, ordered by `checkpoint_seq`.
+    #[allow(clippy::missing_errors_doc)]
+    fn get_step_checkpoints(&self, step_id: StepId) -> Result<Vec<StepCheckpoint>>;
+
+    /// Register a prompt template version. Re-registering an identical version is a no-op;
+    /// a different body for an existing version is an error.
+    #[allow(clippy::missing_errors_doc)]
+    fn put_prompt_template(&self, template: &PromptTemplateRecord) -> Result<()>;
+
+    #[allow(clippy::missing_errors_doc)]
+    fn get_prompt_template(
+        &self,
+        template_name: &str,
+        template_version: &str,
+    ) -> Result<Option<PromptTemplateRecord>>;
+
+    /// All registered prompt templates, ordered by name and version.
+    #[allow(clippy::missing_errors_doc)]
+    fn list_prompt_templates(&self) -> Result<Vec<PromptTemplateRecord>>;
+
     #[allow(clippy::missing_errors_doc)]
     fn get_run(&self, run_id: RunId) -> Result<Option<RunRecord>>;
 
@@ -102,4 +179,120 @@ pub trait TraceStore {
 
     #[allow(clippy::missing_errors_doc)]
     fn get_step_context_packages(&self, run_id: RunId) -> Result<Vec<StepContextPackageRecord>>;
+
+    /// Provider calls of a run, ordered by step index and then call order.
+    #[allow(clippy::missing_errors_doc)]
+    fn get_step_provider_calls(&self, run_id: RunId) -> Result<Vec<StepProviderCallRecord>>;
+
+    /// Most recent succeeded step of a run of `workflow_hash` recorded with `memo_key`, with its
+    /// succeeded provider call. Steps with several provider calls are never returned.
+    #[allow(clippy::missing_errors_doc)]
+    fn find_memoized_step(
+        &self,
+        workflow_hash: &str,
+        memo_key: &str,
+    ) -> Result<Option<MemoizedStepRecord>>;
+
+    /// Gate decisions of a run, ordered by step index and then decision order.
+    #[allow(clippy::missing_errors_doc)]
+    fn get_step_gate_decisions(&self, run_id: RunId) -> Result<Vec<StepGateDecisionRecord>>;
+
+    /// Proposed memory writes of a run with their dispositions, ordered by step index and then
+    /// recording order.
+    #[allow(clippy::missing_errors_doc)]
+    fn get_step_proposed_writes(&self, run_id: RunId) -> Result<Vec<StepProposedWriteRecord>>;
+
+    /// Persisted step duration statistics, optionally limited to one workflow hash.
+    #[allow(clippy::missing_errors_doc)]
+    fn list_step_duration_stats(
+        &self,
+        workflow_hash: Option<&str>,
+    ) -> Result<Vec<StepDurationStats>>;
+
+    /// Per-step elapsed time, remaining steps, and an ETA for `run_id`; `None` if the run is unknown.
+    #[allow(clippy::missing_errors_doc)]
+    fn get_run_progress(&self, run_id: RunId) -> Result<Option<RunProgress>>;
+
+    /// Aggregate the usage of a finished run from its trace rows and persist it as the run's
+    /// `run_usage` record, replacing any earlier record.
+    #[allow(clippy::missing_errors_doc)]
+    fn record_run_usage(&self, run_id: RunId) -> Result<RunUsage>;
+
+    #[allow(clippy::missing_errors_doc)]
+    fn get_run_usage(&self, run_id: RunId) -> Result<Option<RunUsage>>;
+
+    /// Recorded run usage, optionally limited to one workflow name, oldest run first.
+    #[allow(clippy::missing_errors_doc)]
+    fn list_run_usage(&self, workflow_name: Option<&str>) -> Result<Vec<RunUsage>>;
+
+    /// Which reason codes drove rejected and pruned gate decisions in `window`, broken down by
+    /// workflow and gate kind.
+    #[allow(clippy::missing_errors_doc)]
+    fn reason_code_stats(&self, window: DecisionWindow) -> Result<ReasonCodeStats>;
+
+    /// `warning` events by workflow and warning code within `window` (on `occurred_at`),
+    /// optionally for one workflow.
+    #[allow(clippy::missing_errors_doc)]
+    fn warning_stats(
+        &self,
+        window: DecisionWindow,
+        workflow_name: Option<&str>,
+    ) -> Result<WarningStats>;
+
+    /// Queue a human gate a non-interactive run deferred.
+    #[allow(clippy::missing_errors_doc)]
+    fn enqueue_deferred_gate(&self, gate: &DeferredGateRecord) -> Result<()>;
+
+    /// Deferred human gates, optionally of one run, in the order they were queued.
+    #[allow(clippy::missing_errors_doc)]
+    fn list_deferred_gates(&self, run_id: Option<RunId>) -> Result<Vec<DeferredGateRecord>>;
+
+    /// Record a person's decision on a pending deferred gate. Fails when the gate was never
+    /// deferred, is already resolved, or the decider is not allowed to decide it
+    /// (`DeferredGateRecord::authorize` against the stored delegations).
+    #[allow(clippy::missing_errors_doc)]
+    fn resolve_deferred_gate(
+        &self,
+        run_id: RunId,
+        step_key: &str,
+        gate_name: &str,
+        resolution: &DeferredGateResolution,
+    ) -> Result<DeferredGateRecord>;
+
+    #[allow(clippy::missing_errors_doc)]
+    fn add_approval_delegation(&self, delegation: &ApprovalDelegation) -> Result<()>;
+
+    /// Every approval delegation, revoked and expired ones included, oldest first.
+    #[allow(clippy::missing_errors_doc)]
+    fn list_approval_delegations(&self) -> Result<Vec<ApprovalDelegation>>;
+
+    /// Revoke a delegation as of `revoked_at`; returns whether an unrevoked delegation with that
+    /// id existed.
+    #[allow(clippy::missing_errors_doc)]
+    fn revoke_approval_delegation(
+        &self,
+        delegation_id: &str,
+        revoked_at: DateTimeUtc,
+    ) -> Result<bool>;
+
+    /// Advance the load balancing turn counter of `pool` by `turns` and return the counter's
+    /// value before the advance, in one transaction so concurrent runs claim disjoint turns.
+    /// Unknown pools start at 0.
+    #[allow(clippy::missing_errors_doc)]
+    fn claim_load_balancing_turns(&self, pool: &str, turns: u64) -> Result<u64>;
+
+    /// Select the artifact classes whose writes may stay queued until the next flush; every other
+    /// write is committed before it returns. Stores that write synchronously ignore this.
+    #[allow(clippy::missing_errors_doc)]
+    fn set_relaxed_artifacts(&self, _relaxed: &BTreeSet<TraceArtifactClass>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Durability barrier: return once every write accepted so far is committed, reporting any
+    /// write that failed after it was accepted. Stores that write synchronously have nothing to
+    /// flush.
+    #[allow(clippy::missing_errors_doc)]
+    fn flush(&self) -> Result<()> {
+        Ok(())
+    }
 }
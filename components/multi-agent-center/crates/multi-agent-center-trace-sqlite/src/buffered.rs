@@ -0,0 +1,578 @@
+//! Write-ahead buffering for the `SQLite` trace store.
+//!
+//! Trace writes are queued in memory and committed by a background writer thread in batched
+//! transactions. Writes of relaxed artifact classes ([`TraceStore::set_relaxed_artifacts`]) return
+//! once queued, so a step does not wait on one `SQLite` commit per low-value row; every other
+//! write waits for the queue to drain. Reads and [`TraceStore::flush`] drain the queue first, so
+//! callers always observe their own writes.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+
+use anyhow::{anyhow, Context, Result};
+use multi_agent_center_domain::{
+    ApprovalDelegation, ContextPackageEnvelope, DateTimeUtc, DecisionWindow, DeferredGateRecord,
+    DeferredGateResolution, EventQuery, EventRow, GateDecisionRecord, MemoizedStepRecord,
+    PromptTemplateRecord, ProposedMemoryWrite, ProviderCallRecord, ReasonCodeStats, RunId,
+    RunProgress, RunRecord, RunStatus, RunUsage, StepCheckpoint, StepContextPackageRecord,
+    StepDurationStats, StepGateDecisionRecord, StepId, StepProposedWriteRecord,
+    StepProviderCallRecord, StepRecord, StepStatus, TraceArtifactClass, TraceEvent, WarningStats,
+    WorkflowSnapshotRecord,
+};
+use multi_agent_center_trace_core::TraceStore;
+
+use crate::SqliteTraceStore;
+
+/// Upper bound on queued writes committed in one transaction.
+const MAX_BATCH: usize = 256;
+
+type TraceWrite = Box<dyn FnOnce(&SqliteTraceStore) -> Result<()> + Send>;
+
+enum QueuedOp {
+    Write(TraceWrite),
+    Flush(Sender<()>),
+}
+
+/// [`SqliteTraceStore`] wrapper that queues trace writes and commits them from a background
+/// thread in batched transactions. Until relaxed artifact classes are selected, every write is
+/// committed before it returns.
+///
+/// `append_event` assigns `event_seq` up front from the store's autoincrement sequence, so the
+/// buffered store must be the only writer of its database while it is open. The first write
+/// that fails poisons the store: queued writes behind it are dropped and every later write or
+/// flush returns that error. Dropping the store flushes the queue and joins the writer thread.
+pub struct BufferedSqliteTraceStore {
+    store: Arc<Mutex<SqliteTraceStore>>,
+    failure: Arc<Mutex<Option<String>>>,
+    queue: Option<Sender<QueuedOp>>,
+    writer: Option<JoinHandle<()>>,
+    last_event_seq: Mutex<Option<i64>>,
+    relaxed: Mutex<BTreeSet<TraceArtifactClass>>,
+}
+
+impl BufferedSqliteTraceStore {
+    /// Wrap `store` and start its background writer thread.
+    ///
+    /// # Errors
+    /// Returns an error if `store` is coordinated with other writers
+    /// ([`SqliteTraceStore::open_coordinated`]), or if the writer thread cannot be spawned.
+    pub fn new(store: SqliteTraceStore) -> Result<Self> {
+        if store.is_coordinated() {
+            return Err(anyhow!(
+                "buffered trace writes need exclusive access to the trace database; \
+                 open it without writer coordination"
+            ));
+        }
+        let store = Arc::new(Mutex::new(store));
+        let failure = Arc::new(Mutex::new(None));
+        let (queue, receiver) = mpsc::channel();
+        let writer = {
+            let store = Arc::clone(&store);
+            let failure = Arc::clone(&failure);
+            std::thread::Builder::new()
+                .name("trace-writer".to_string())
+                .spawn(move || run_writer(&store, &failure, &receiver))
+                .context("failed to spawn trace writer thread")?
+        };
+        Ok(Self {
+            store,
+            failure,
+            queue: Some(queue),
+            writer: Some(writer),
+            last_event_seq: Mutex::new(None),
+            relaxed: Mutex::new(BTreeSet::new()),
+        })
+    }
+
+    /// Open a `SQLite` trace database and wrap it in a buffered writer.
+    ///
+    /// # Errors
+    /// Returns an error if opening the database or spawning the writer thread fails.
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::new(SqliteTraceStore::open(path)?)
+    }
+
+    fn check_failure(&self) -> Result<()> {
+        match lock(&self.failure)?.as_ref() {
+            Some(message) => Err(anyhow!("buffered trace write failed: {message}")),
+            None => Ok(()),
+        }
+    }
+
+    fn enqueue(
+        &self,
+        write: impl FnOnce(&SqliteTraceStore) -> Result<()> + Send + 'static,
+    ) -> Result<()> {
+        self.check_failure()?;
+        self.queue
+            .as_ref()
+            .ok_or_else(|| anyhow!("trace writer is shut down"))?
+            .send(QueuedOp::Write(Box::new(write)))
+            .map_err(|_| anyhow!("trace writer thread exited"))
+    }
+
+    /// Queue `write`; unless `class` is relaxed, wait until it is committed.
+    fn write(
+        &self,
+        class: Option<TraceArtifactClass>,
+        write: impl FnOnce(&SqliteTraceStore) -> Result<()> + Send + 'static,
+    ) -> Result<()> {
+        self.enqueue(write)?;
+        let relaxed = match class {
+            Some(class) => lock(&self.relaxed)?.contains(&class),
+            None => false,
+        };
+        if relaxed {
+            Ok(())
+        } else {
+            self.flush()
+        }
+    }
+
+    /// Flush the queue, then run `read` against the underlying store.
+    fn read<T>(&self, read: impl FnOnce(&SqliteTraceStore) -> Result<T>) -> Result<T> {
+        self.flush()?;
+        read(&*lock(&self.store)?)
+    }
+}
+
+impl Drop for BufferedSqliteTraceStore {
+    fn drop(&mut self) {
+        drop(self.queue.take());
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> Result<MutexGuard<'_, T>> {
+    mutex
+        .lock()
+        .map_err(|_| anyhow!("buffered trace store lock poisoned"))
+}
+
+fn run_writer(
+    store: &Mutex<SqliteTraceStore>,
+    failure: &Mutex<Option<String>>,
+    receiver: &Receiver<QueuedOp>,
+) {
+    while let Ok(first) = receiver.recv() {
+        let mut batch = vec![first];
+        while batch.len() < MAX_BATCH && !matches!(batch.last(), Some(QueuedOp::Flush(_))) {
+            match receiver.try_recv() {
+                Ok(op) => batch.push(op),
+                Err(_) => break,
+            }
+        }
+
+        let mut writes = Vec::new();
+        let mut acks = Vec::new();
+        for op in batch {
+            match op {
+                QueuedOp::Write(write) => writes.push(write),
+                QueuedOp::Flush(ack) => acks.push(ack),
+            }
+        }
+
+        if !writes.is_empty() {
+            if let (Ok(store), Ok(mut failure)) = (store.lock(), failure.lock()) {
+                if failure.is_none() {
+                    if let Err(err) = commit_batch(&store, writes) {
+                        *failure = Some(format!("{err:#}"));
+                    }
+                }
+            }
+        }
+
+        for ack in acks {
+            let _ = ack.send(());
+        }
+    }
+}
+
+/// Apply `writes` in one transaction, stopping at the first failure. Writes that succeeded
+/// before the failure are still committed, matching what an unbuffered store would hold.
+fn commit_batch(store: &SqliteTraceStore, writes: Vec<TraceWrite>) -> Result<()> {
+    store
+        .conn
+        .execute_batch("BEGIN IMMEDIATE")
+        .context("failed to begin buffered trace transaction")?;
+    let mut outcome = Ok(());
+    for write in writes {
+        if let Err(err) = write(store) {
+            outcome = Err(err);
+            break;
+        }
+    }
+    if let Err(err) = store.conn.execute_batch("COMMIT") {
+        let _ = store.conn.execute_batch("ROLLBACK");
+        return Err(err).context("failed to commit buffered trace transaction");
+    }
+    outcome
+}
+
+impl TraceStore for BufferedSqliteTraceStore {
+    fn migrate(&self) -> Result<()> {
+        self.read(SqliteTraceStore::migrate)
+    }
+
+    fn upsert_workflow_snapshot(
+        &self,
+        workflow_hash: &str,
+        normalization_version: u32,
+        source_format: &str,
+        source_yaml_hash: &str,
+        normalized_json: &serde_json::Value,
+    ) -> Result<()> {
+        let workflow_hash = workflow_hash.to_string();
+        let source_format = source_format.to_string();
+        let source_yaml_hash = source_yaml_hash.to_string();
+        let normalized_json = normalized_json.clone();
+        self.write(None, move |store| {
+            store.upsert_workflow_snapshot(
+                &workflow_hash,
+                normalization_version,
+                &source_format,
+                &source_yaml_hash,
+                &normalized_json,
+            )
+        })
+    }
+
+    fn insert_run(&self, run: &RunRecord) -> Result<()> {
+        let run = run.clone();
+        self.write(None, move |store| store.insert_run(&run))
+    }
+
+    fn insert_run_claiming_correlation(&self, run: &RunRecord) -> Result<Option<RunRecord>> {
+        // The claim's answer decides whether the caller runs at all, so it cannot be queued.
+        self.read(|store| store.insert_run_claiming_correlation(run))
+    }
+
+    fn update_run_finished(&self, run_id: RunId, status: RunStatus) -> Result<()> {
+        self.write(None, move |store| store.update_run_finished(run_id, status))
+    }
+
+    fn update_run_manifest(
+        &self,
+        run_id: RunId,
+        manifest: &serde_json::Value,
+        manifest_hash: &str,
+        manifest_signature: Option<&str>,
+        manifest_signature_status: &str,
+    ) -> Result<()> {
+        let manifest = manifest.clone();
+        let manifest_hash = manifest_hash.to_string();
+        let manifest_signature = manifest_signature.map(str::to_string);
+        let manifest_signature_status = manifest_signature_status.to_string();
+        self.write(None, move |store| {
+            store.update_run_manifest(
+                run_id,
+                &manifest,
+                &manifest_hash,
+                manifest_signature.as_deref(),
+                &manifest_signature_status,
+            )
+        })
+    }
+
+    fn get_run_manifest(&self, run_id: RunId) -> Result<Option<serde_json::Value>> {
+        self.read(|store| store.get_run_manifest(run_id))
+    }
+
+    fn insert_step(&self, step: &StepRecord) -> Result<()> {
+        let step = step.clone();
+        self.write(None, move |store| store.insert_step(&step))
+    }
+
+    fn update_step_status(
+        &self,
+        step_id: StepId,
+        status: StepStatus,
+        output_hash: Option<&str>,
+        error_json: Option<&serde_json::Value>,
+    ) -> Result<()> {
+        let output_hash = output_hash.map(str::to_string);
+        let error_json = error_json.cloned();
+        self.write(None, move |store| {
+            store.update_step_status(step_id, status, output_hash.as_deref(), error_json.as_ref())
+        })
+    }
+
+    fn update_step_retry(&self, step_id: StepId, retry_json: &serde_json::Value) -> Result<()> {
+        let retry_json = retry_json.clone();
+        self.write(None, move |store| {
+            store.update_step_retry(step_id, &retry_json)
+        })
+    }
+
+    fn update_step_provider_selection(
+        &self,
+        step_id: StepId,
+        provider_selection_json: &serde_json::Value,
+    ) -> Result<()> {
+        let provider_selection_json = provider_selection_json.clone();
+        self.write(None, move |store| {
+            store.update_step_provider_selection(step_id, &provider_selection_json)
+        })
+    }
+
+    fn append_event(&self, event: &TraceEvent) -> Result<i64> {
+        let mut last_event_seq = lock(&self.last_event_seq)?;
+        let event_seq = match *last_event_seq {
+            Some(seq) => seq + 1,
+            None => self.read(SqliteTraceStore::last_event_seq)? + 1,
+        };
+        let event = event.clone();
+        self.write(Some(TraceArtifactClass::Events), move |store| {
+            store.insert_event(&event, Some(event_seq)).map(|_| ())
+        })?;
+        *last_event_seq = Some(event_seq);
+        Ok(event_seq)
+    }
+
+    fn append_context_package(
+        &self,
+        run_id: RunId,
+        step_id: StepId,
+        attempt: u32,
+        envelope: &ContextPackageEnvelope,
+    ) -> Result<()> {
+        let envelope = envelope.clone();
+        self.write(Some(TraceArtifactClass::ContextPackages), move |store| {
+            store.append_context_package(run_id, step_id, attempt, &envelope)
+        })
+    }
+
+    fn append_gate_decision(
+        &self,
+        run_id: RunId,
+        step_id: StepId,
+        decision: &GateDecisionRecord,
+    ) -> Result<()> {
+        let decision = decision.clone();
+        self.write(None, move |store| {
+            store.append_gate_decision(run_id, step_id, &decision)
+        })
+    }
+
+    fn append_provider_call(
+        &self,
+        run_id: RunId,
+        step_id: StepId,
+        call: &ProviderCallRecord,
+    ) -> Result<()> {
+        let call = call.clone();
+        self.write(Some(TraceArtifactClass::ProviderCalls), move |store| {
+            store.append_provider_call(run_id, step_id, &call)
+        })
+    }
+
+    fn append_proposed_memory_write(
+        &self,
+        run_id: RunId,
+        step_id: StepId,
+        write: &ProposedMemoryWrite,
+        disposition: &str,
+        disposition_reason: Option<&str>,
+    ) -> Result<()> {
+        let write = write.clone();
+        let disposition = disposition.to_string();
+        let disposition_reason = disposition_reason.map(str::to_string);
+        self.write(None, move |store| {
+            store.append_proposed_memory_write(
+                run_id,
+                step_id,
+                &write,
+                &disposition,
+                disposition_reason.as_deref(),
+            )
+        })
+    }
+
+    fn list_runs(&self) -> Result<Vec<RunRecord>> {
+        self.read(SqliteTraceStore::list_runs)
+    }
+
+    fn list_runs_with_labels(&self, labels: &BTreeMap<String, String>) -> Result<Vec<RunRecord>> {
+        self.read(|store| store.list_runs_with_labels(labels))
+    }
+
+    fn find_runs_by_external_correlation_id(
+        &self,
+        external_correlation_id: &str,
+    ) -> Result<Vec<RunRecord>> {
+        self.read(|store| store.find_runs_by_external_correlation_id(external_correlation_id))
+    }
+
+    fn list_child_runs(&self, parent_run_id: RunId) -> Result<Vec<RunRecord>> {
+        self.read(|store| store.list_child_runs(parent_run_id))
+    }
+
+    fn list_events_for_run(&self, run_id: RunId) -> Result<Vec<EventRow>> {
+        self.read(|store| store.list_events_for_run(run_id))
+    }
+
+    fn query_events(&self, query: &EventQuery) -> Result<Vec<EventRow>> {
+        self.read(|store| store.query_events(query))
+    }
+
+    fn append_step_checkpoint(&self, checkpoint: &StepCheckpoint) -> Result<()> {
+        let checkpoint = checkpoint.clone();
+        self.write(Some(TraceArtifactClass::StepCheckpoints), move |store| {
+            store.append_step_checkpoint(&checkpoint)
+        })
+    }
+
+    fn get_step_checkpoints(&self, step_id: StepId) -> Result<Vec<StepCheckpoint>> {
+        self.read(|store| store.get_step_checkpoints(step_id))
+    }
+
+    fn put_prompt_template(&self, template: &PromptTemplateRecord) -> Result<()> {
+        // Synchronous so immutability violations surface to the caller, not the writer thread.
+        self.read(|store| store.put_prompt_template(template))
+    }
+
+    fn get_prompt_template(
+        &self,
+        template_name: &str,
+        template_version: &str,
+    ) -> Result<Option<PromptTemplateRecord>> {
+        self.read(|store| store.get_prompt_template(template_name, template_version))
+    }
+
+    fn list_prompt_templates(&self) -> Result<Vec<PromptTemplateRecord>> {
+        self.read(SqliteTraceStore::list_prompt_templates)
+    }
+
+    fn get_run(&self, run_id: RunId) -> Result<Option<RunRecord>> {
+        self.read(|store| store.get_run(run_id))
+    }
+
+    fn get_step_records(&self, run_id: RunId) -> Result<Vec<StepRecord>> {
+        self.read(|store| store.get_step_records(run_id))
+    }
+
+    fn get_workflow_snapshot(&self, workflow_hash: &str) -> Result<Option<WorkflowSnapshotRecord>> {
+        self.read(|store| store.get_workflow_snapshot(workflow_hash))
+    }
+
+    fn get_step_context_packages(&self, run_id: RunId) -> Result<Vec<StepContextPackageRecord>> {
+        self.read(|store| store.get_step_context_packages(run_id))
+    }
+
+    fn get_step_provider_calls(&self, run_id: RunId) -> Result<Vec<StepProviderCallRecord>> {
+        self.read(|store| store.get_step_provider_calls(run_id))
+    }
+
+    fn find_memoized_step(
+        &self,
+        workflow_hash: &str,
+        memo_key: &str,
+    ) -> Result<Option<MemoizedStepRecord>> {
+        self.read(|store| store.find_memoized_step(workflow_hash, memo_key))
+    }
+
+    fn get_step_gate_decisions(&self, run_id: RunId) -> Result<Vec<StepGateDecisionRecord>> {
+        self.read(|store| store.get_step_gate_decisions(run_id))
+    }
+
+    fn get_step_proposed_writes(&self, run_id: RunId) -> Result<Vec<StepProposedWriteRecord>> {
+        self.read(|store| store.get_step_proposed_writes(run_id))
+    }
+
+    fn list_step_duration_stats(
+        &self,
+        workflow_hash: Option<&str>,
+    ) -> Result<Vec<StepDurationStats>> {
+        self.read(|store| store.list_step_duration_stats(workflow_hash))
+    }
+
+    fn get_run_progress(&self, run_id: RunId) -> Result<Option<RunProgress>> {
+        self.read(|store| store.get_run_progress(run_id))
+    }
+
+    fn record_run_usage(&self, run_id: RunId) -> Result<RunUsage> {
+        self.read(|store| store.record_run_usage(run_id))
+    }
+
+    fn get_run_usage(&self, run_id: RunId) -> Result<Option<RunUsage>> {
+        self.read(|store| store.get_run_usage(run_id))
+    }
+
+    fn list_run_usage(&self, workflow_name: Option<&str>) -> Result<Vec<RunUsage>> {
+        self.read(|store| store.list_run_usage(workflow_name))
+    }
+
+    fn reason_code_stats(&self, window: DecisionWindow) -> Result<ReasonCodeStats> {
+        self.read(|store| store.reason_code_stats(window))
+    }
+
+    fn warning_stats(
+        &self,
+        window: DecisionWindow,
+        workflow_name: Option<&str>,
+    ) -> Result<WarningStats> {
+        self.read(|store| store.warning_stats(window, workflow_name))
+    }
+
+    fn enqueue_deferred_gate(&self, gate: &DeferredGateRecord) -> Result<()> {
+        let gate = gate.clone();
+        self.write(None, move |store| store.enqueue_deferred_gate(&gate))
+    }
+
+    fn list_deferred_gates(&self, run_id: Option<RunId>) -> Result<Vec<DeferredGateRecord>> {
+        self.read(|store| store.list_deferred_gates(run_id))
+    }
+
+    fn resolve_deferred_gate(
+        &self,
+        run_id: RunId,
+        step_key: &str,
+        gate_name: &str,
+        resolution: &DeferredGateResolution,
+    ) -> Result<DeferredGateRecord> {
+        self.read(|store| store.resolve_deferred_gate(run_id, step_key, gate_name, resolution))
+    }
+
+    fn add_approval_delegation(&self, delegation: &ApprovalDelegation) -> Result<()> {
+        self.read(|store| store.add_approval_delegation(delegation))
+    }
+
+    fn list_approval_delegations(&self) -> Result<Vec<ApprovalDelegation>> {
+        self.read(SqliteTraceStore::list_approval_delegations)
+    }
+
+    fn revoke_approval_delegation(
+        &self,
+        delegation_id: &str,
+        revoked_at: DateTimeUtc,
+    ) -> Result<bool> {
+        self.read(|store| store.revoke_approval_delegation(delegation_id, revoked_at))
+    }
+
+    fn claim_load_balancing_turns(&self, pool: &str, turns: u64) -> Result<u64> {
+        self.read(|store| store.claim_load_balancing_turns(pool, turns))
+    }
+
+    fn set_relaxed_artifacts(&self, relaxed: &BTreeSet<TraceArtifactClass>) -> Result<()> {
+        lock(&self.relaxed)?.clone_from(relaxed);
+        Ok(())
+    }
+
+    fn flush(&self) -> Result<()> {
+        self.check_failure()?;
+        let (ack, done) = mpsc::channel();
+        self.queue
+            .as_ref()
+            .ok_or_else(|| anyhow!("trace writer is shut down"))?
+            .send(QueuedOp::Flush(ack))
+            .map_err(|_| anyhow!("trace writer thread exited"))?;
+        done.recv()
+            .map_err(|_| anyhow!("trace writer thread exited before flushing"))?;
+        self.check_failure()
+    }
+}
@@ -1,21 +1,146 @@
 #![forbid(unsafe_code)]
 
-use std::path::Path;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use anyhow::{anyhow, Context, Result};
-use memory_kernel_core::ContextPackage;
+use fs2::FileExt;
+use memory_kernel_core::{ContextPackage, MemoryId, MemoryVersionId};
 use multi_agent_center_domain::{
-    now_utc, ContextPackageEnvelope, EventRow, GateDecision, GateDecisionRecord, GateKind,
-    ProposedMemoryWrite, RunId, RunRecord, RunStatus, StepContextPackageRecord, StepId, StepRecord,
-    StepStatus, TraceEvent, TraceEventType, WorkflowSnapshotRecord,
+    now_utc, ApprovalDelegation, ContextPackageEnvelope, DeciderAuthMethod, DecisionWindow,
+    DeferredGateRecord, DeferredGateResolution, EventQuery, EventRow, GateDecision,
+    GateDecisionRecord, GateKind, MemoizedStepRecord, ParentStep, PromptTemplateRecord,
+    ProposedMemoryWrite, ProviderCallRecord, ReasonCodeStat, ReasonCodeStats, RunId, RunProgress,
+    RunRecord, RunStatus, RunUsage, StepCheckpoint, StepContextPackageRecord, StepDurationStats,
+    StepGateDecisionRecord, StepId, StepProgress, StepProposedWriteRecord, StepProviderCallRecord,
+    StepRecord, StepRerun, StepStatus, TraceEvent, TraceEventType, WarningStat, WarningStats,
+    WorkflowSnapshotRecord, UNSPECIFIED_WARNING_CODE,
 };
 use multi_agent_center_trace_core::TraceStore;
 use rusqlite::{params, Connection, OptionalExtension};
 use time::OffsetDateTime;
 use ulid::Ulid;
 
-const TRACE_SCHEMA_VERSION: i64 = 3;
+mod buffered;
+
+pub use buffered::BufferedSqliteTraceStore;
+
+const TRACE_SCHEMA_VERSION: i64 = 5;
+
+/// `status` constraints of trace databases created before runs could be partially completed or
+/// cancelled and steps deferred, timed out or cancelled (oldest first), with their current
+/// replacements.
+const LEGACY_STATUS_CHECKS: [(&str, &[&str], &str); 2] = [
+    (
+        "runs",
+        &[
+            "CHECK (status IN ('pending','running','succeeded','failed','rejected'))",
+            "CHECK (status IN ('pending','running','succeeded','failed','rejected','partially_completed'))",
+        ],
+        "CHECK (status IN ('pending','running','succeeded','failed','rejected','partially_completed','cancelled'))",
+    ),
+    (
+        "steps",
+        &[
+            "CHECK (status IN ('pending','running','succeeded','failed','rejected','skipped'))",
+            "CHECK (status IN ('pending','running','succeeded','failed','rejected','skipped','deferred'))",
+        ],
+        "CHECK (status IN ('pending','running','succeeded','failed','rejected','skipped','deferred','timed_out','cancelled'))",
+    ),
+];
+
+/// Key of `step_context_packages` in trace databases created before a step's retries recorded
+/// their own packages, and its replacement keyed by attempt.
+const LEGACY_CONTEXT_PACKAGE_KEY: (&str, &str) = (
+    "UNIQUE(step_id, package_slot)",
+    "attempt INTEGER NOT NULL DEFAULT 1,
+  UNIQUE(step_id, attempt, package_slot)",
+);
+
+const CONTEXT_PACKAGE_LEGACY_COLUMNS: &str = "id, run_id, step_id, package_slot, \
+     context_package_id, generated_at, query_json, determinism_json, answer_json, \
+     ordering_trace_json, package_json, package_hash";
+
+const STEP_DURATION_STATS_SQL: &str = r"
+CREATE TABLE IF NOT EXISTS step_duration_stats (
+  workflow_hash TEXT NOT NULL,
+  step_key TEXT NOT NULL,
+  sample_count INTEGER NOT NULL,
+  mean_ms INTEGER NOT NULL,
+  min_ms INTEGER NOT NULL,
+  max_ms INTEGER NOT NULL,
+  stddev_ms INTEGER NOT NULL,
+  last_ms INTEGER NOT NULL,
+  updated_at TEXT NOT NULL,
+  PRIMARY KEY (workflow_hash, step_key)
+);
+";
+
+const RUN_USAGE_SQL: &str = r"
+CREATE TABLE IF NOT EXISTS run_usage (
+  run_id TEXT PRIMARY KEY,
+  workflow_name TEXT NOT NULL,
+  workflow_version TEXT NOT NULL,
+  workflow_hash TEXT NOT NULL,
+  external_correlation_id TEXT,
+  status TEXT NOT NULL,
+  provider_calls INTEGER NOT NULL,
+  input_tokens INTEGER NOT NULL,
+  output_tokens INTEGER NOT NULL,
+  wall_time_ms INTEGER NOT NULL,
+  provider_latency_ms INTEGER NOT NULL,
+  trace_rows_written INTEGER NOT NULL,
+  context_bytes_injected INTEGER NOT NULL,
+  recorded_at TEXT NOT NULL,
+  FOREIGN KEY (run_id) REFERENCES runs(run_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_run_usage_workflow ON run_usage(workflow_name, workflow_hash);
+";
+
+const DEFERRED_GATES_SQL: &str = r"
+CREATE TABLE IF NOT EXISTS deferred_gates (
+  run_id TEXT NOT NULL,
+  step_id TEXT NOT NULL,
+  step_key TEXT NOT NULL,
+  gate_name TEXT NOT NULL,
+  required INTEGER NOT NULL,
+  deferred_at TEXT NOT NULL,
+  approved INTEGER,
+  decided_by TEXT,
+  notes TEXT,
+  decided_at TEXT,
+  approvers_json TEXT NOT NULL DEFAULT '[]',
+  auth_method TEXT,
+  on_behalf_of TEXT,
+  webhook_json TEXT,
+  PRIMARY KEY (run_id, step_key, gate_name),
+  FOREIGN KEY (run_id) REFERENCES runs(run_id),
+  FOREIGN KEY (step_id) REFERENCES steps(step_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_deferred_gates_pending ON deferred_gates(decided_at);
+
+CREATE TABLE IF NOT EXISTS approval_delegations (
+  delegation_id TEXT PRIMARY KEY,
+  delegator TEXT NOT NULL,
+  delegate TEXT NOT NULL,
+  gate_name TEXT,
+  created_at TEXT NOT NULL,
+  expires_at TEXT,
+  revoked_at TEXT
+);
+";
+
+const LOAD_BALANCING_TURNS_SQL: &str = r"
+CREATE TABLE IF NOT EXISTS load_balancing_turns (
+  pool TEXT PRIMARY KEY,
+  next_turn INTEGER NOT NULL,
+  updated_at TEXT NOT NULL
+);
+";
 
 const SCHEMA_V2: &str = r"
 CREATE TABLE IF NOT EXISTS schema_migrations (
@@ -41,7 +166,7 @@ CREATE TABLE IF NOT EXISTS runs (
   as_of_was_default INTEGER NOT NULL CHECK (as_of_was_default IN (0,1)),
   started_at TEXT NOT NULL,
   ended_at TEXT,
-  status TEXT NOT NULL CHECK (status IN ('pending','running','succeeded','failed','rejected')),
+  status TEXT NOT NULL CHECK (status IN ('pending','running','succeeded','failed','rejected','partially_completed','cancelled')),
   replay_of_run_id TEXT,
   external_correlation_id TEXT,
   engine_version TEXT NOT NULL,
@@ -49,6 +174,11 @@ CREATE TABLE IF NOT EXISTS runs (
   manifest_hash TEXT,
   manifest_signature TEXT,
   manifest_signature_status TEXT NOT NULL DEFAULT 'unsigned',
+  rerun_of_run_id TEXT,
+  rerun_step_key TEXT,
+  parent_run_id TEXT,
+  parent_step_id TEXT,
+  parent_step_key TEXT,
   FOREIGN KEY (workflow_hash) REFERENCES workflow_snapshots(workflow_hash)
 );
 
@@ -58,7 +188,7 @@ CREATE TABLE IF NOT EXISTS steps (
   step_index INTEGER NOT NULL,
   step_key TEXT NOT NULL,
   agent_name TEXT NOT NULL,
-  status TEXT NOT NULL CHECK (status IN ('pending','running','succeeded','failed','rejected','skipped')),
+  status TEXT NOT NULL CHECK (status IN ('pending','running','succeeded','failed','rejected','skipped','deferred','timed_out','cancelled')),
   started_at TEXT,
   ended_at TEXT,
   task_payload_json TEXT NOT NULL,
@@ -67,6 +197,12 @@ CREATE TABLE IF NOT EXISTS steps (
   input_hash TEXT NOT NULL,
   output_hash TEXT,
   error_json TEXT,
+  retry_json TEXT,
+  prompt_template_hash TEXT,
+  provider_selection_json TEXT,
+  taint_json TEXT,
+  memoized_from_run TEXT,
+  memo_key TEXT,
   UNIQUE(run_id, step_index),
   UNIQUE(run_id, step_key),
   FOREIGN KEY (run_id) REFERENCES runs(run_id)
@@ -103,7 +239,8 @@ CREATE TABLE IF NOT EXISTS step_context_packages (
   ordering_trace_json TEXT NOT NULL,
   package_json TEXT NOT NULL,
   package_hash TEXT NOT NULL,
-  UNIQUE(step_id, package_slot),
+  attempt INTEGER NOT NULL DEFAULT 1,
+  UNIQUE(step_id, attempt, package_slot),
   FOREIGN KEY (run_id) REFERENCES runs(run_id),
   FOREIGN KEY (step_id) REFERENCES steps(step_id)
 );
@@ -190,6 +327,7 @@ CREATE TABLE IF NOT EXISTS provider_calls (
   ended_at TEXT,
   status TEXT NOT NULL,
   error_text TEXT,
+  persona_json TEXT,
   FOREIGN KEY (run_id) REFERENCES runs(run_id),
   FOREIGN KEY (step_id) REFERENCES steps(step_id)
 );
@@ -209,7 +347,18 @@ CREATE TABLE IF NOT EXISTS proposed_memory_writes (
 
 CREATE INDEX IF NOT EXISTS idx_trace_events_run_seq ON trace_events(run_id, event_seq);
 CREATE INDEX IF NOT EXISTS idx_trace_events_step_seq ON trace_events(step_id, event_seq);
+CREATE INDEX IF NOT EXISTS idx_trace_events_type_seq ON trace_events(event_type, event_seq);
 CREATE INDEX IF NOT EXISTS idx_steps_run_index ON steps(run_id, step_index);
+CREATE TABLE IF NOT EXISTS run_labels (
+  run_id TEXT NOT NULL,
+  label_key TEXT NOT NULL,
+  label_value TEXT NOT NULL,
+  PRIMARY KEY (run_id, label_key),
+  FOREIGN KEY (run_id) REFERENCES runs(run_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_run_labels_key_value ON run_labels(label_key, label_value);
+CREATE INDEX IF NOT EXISTS idx_runs_external_correlation ON runs(external_correlation_id);
 CREATE INDEX IF NOT EXISTS idx_selected_memory ON step_context_selected(memory_id, version);
 CREATE INDEX IF NOT EXISTS idx_selected_memory_version ON step_context_selected(memory_version_id);
 CREATE INDEX IF NOT EXISTS idx_excluded_memory ON step_context_excluded(memory_id, version);
@@ -284,6 +433,51 @@ BEGIN
   SELECT RAISE(FAIL, 'provider_calls is append-only');
 END;
 
+CREATE TABLE IF NOT EXISTS step_checkpoints (
+  step_id TEXT NOT NULL,
+  checkpoint_seq INTEGER NOT NULL,
+  run_id TEXT NOT NULL,
+  provider_call_id TEXT,
+  state_json TEXT NOT NULL,
+  prev_checkpoint_hash TEXT,
+  state_hash TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  PRIMARY KEY (step_id, checkpoint_seq),
+  FOREIGN KEY (run_id) REFERENCES runs(run_id),
+  FOREIGN KEY (step_id) REFERENCES steps(step_id)
+);
+
+CREATE TABLE IF NOT EXISTS prompt_templates (
+  template_name TEXT NOT NULL,
+  template_version TEXT NOT NULL,
+  body TEXT NOT NULL,
+  template_hash TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  PRIMARY KEY (template_name, template_version)
+);
+
+CREATE TRIGGER IF NOT EXISTS trg_prompt_templates_no_update
+BEFORE UPDATE ON prompt_templates
+BEGIN
+  SELECT RAISE(FAIL, 'prompt_templates is append-only');
+END;
+CREATE TRIGGER IF NOT EXISTS trg_prompt_templates_no_delete
+BEFORE DELETE ON prompt_templates
+BEGIN
+  SELECT RAISE(FAIL, 'prompt_templates is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_step_checkpoints_no_update
+BEFORE UPDATE ON step_checkpoints
+BEGIN
+  SELECT RAISE(FAIL, 'step_checkpoints is append-only');
+END;
+CREATE TRIGGER IF NOT EXISTS trg_step_checkpoints_no_delete
+BEFORE DELETE ON step_checkpoints
+BEGIN
+  SELECT RAISE(FAIL, 'step_checkpoints is append-only');
+END;
+
 CREATE TRIGGER IF NOT EXISTS trg_proposed_memory_writes_no_update
 BEFORE UPDATE ON proposed_memory_writes
 BEGIN
@@ -298,6 +492,9 @@ END;
 
 pub struct SqliteTraceStore {
     conn: Connection,
+    /// Advisory lock file shared by every process writing this database (see
+    /// [`SqliteTraceStore::open_coordinated`]).
+    writer_lock: Option<File>,
 }
 
 impl SqliteTraceStore {
@@ -316,218 +513,229 @@ impl SqliteTraceStore {
         )
         .context("failed to configure sqlite pragmas")?;
 
-        Ok(Self { conn })
+        Ok(Self {
+            conn,
+            writer_lock: None,
+        })
     }
-}
 
-impl TraceStore for SqliteTraceStore {
-    fn migrate(&self) -> Result<()> {
-        self.conn
-            .execute_batch(SCHEMA_V2)
-            .context("failed to apply trace schema")?;
-
-        ensure_column(&self.conn, "runs", "manifest_hash", "TEXT")?;
-        ensure_column(&self.conn, "runs", "manifest_signature", "TEXT")?;
-        ensure_column(
-            &self.conn,
-            "runs",
-            "manifest_signature_status",
-            "TEXT NOT NULL DEFAULT 'unsigned'",
-        )?;
-        ensure_column(&self.conn, "step_gate_decisions", "memory_id", "TEXT")?;
-        ensure_column(&self.conn, "step_gate_decisions", "version", "INTEGER")?;
-        ensure_column(
-            &self.conn,
-            "step_gate_decisions",
-            "memory_version_id",
-            "TEXT",
-        )?;
-        ensure_column(
-            &self.conn,
-            "step_gate_decisions",
-            "source_ruleset_version",
-            "INTEGER",
-        )?;
-        ensure_column(&self.conn, "step_gate_decisions", "evidence_json", "TEXT")?;
+    /// Open a trace database that several orchestrator instances or processes write at once.
+    /// Every write runs in its own `BEGIN IMMEDIATE` transaction while holding an exclusive
+    /// advisory lock on `<db>.writer-lock`, so concurrent writers of different runs queue on the
+    /// lock instead of failing with `SQLITE_BUSY` once the busy timeout expires. Reads do not
+    /// take the lock.
+    ///
+    /// # Errors
+    /// Returns an error if opening the database or its lock file fails.
+    pub fn open_coordinated(path: &Path) -> Result<Self> {
+        let mut store = Self::open(path)?;
+        let lock_path = writer_lock_path(path);
+        let lock = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .with_context(|| format!("failed to open trace writer lock {}", lock_path.display()))?;
+        store.writer_lock = Some(lock);
+        Ok(store)
+    }
 
-        let now = rfc3339(now_utc())?;
-        self.conn
-            .execute(
-                "INSERT OR IGNORE INTO schema_migrations(version, applied_at) VALUES (?1, ?2)",
-                params![TRACE_SCHEMA_VERSION, now],
-            )
-            .context("failed to record trace migration")?;
+    /// Whether writes are coordinated through the advisory writer lock.
+    #[must_use]
+    pub fn is_coordinated(&self) -> bool {
+        self.writer_lock.is_some()
+    }
 
-        Ok(())
+    /// Run `write` under the writer lock in one immediate transaction when coordinated. Nested
+    /// calls, and writes inside a caller's transaction, run as part of the enclosing one.
+    fn coordinated<T>(&self, write: impl FnOnce() -> Result<T>) -> Result<T> {
+        let Some(lock) = &self.writer_lock else {
+            return write();
+        };
+        if !self.conn.is_autocommit() {
+            return write();
+        }
+
+        lock.lock_exclusive()
+            .context("failed to acquire trace writer lock")?;
+        let result = self.immediate(write);
+        let _ = FileExt::unlock(lock);
+        result
     }
 
-    fn upsert_workflow_snapshot(
-        &self,
-        workflow_hash: &str,
-        normalization_version: u32,
-        source_format: &str,
-        source_yaml_hash: &str,
-        normalized_json: &serde_json::Value,
-    ) -> Result<()> {
-        self.conn
-            .execute(
-                "INSERT INTO workflow_snapshots(
-                    workflow_hash, normalization_version, source_format,
-                    source_yaml_hash, normalized_json, created_at
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
-                ON CONFLICT(workflow_hash) DO UPDATE SET
-                    normalization_version = excluded.normalization_version,
-                    source_format = excluded.source_format,
-                    source_yaml_hash = excluded.source_yaml_hash,
-                    normalized_json = excluded.normalized_json",
-                params![
-                    workflow_hash,
-                    i64::from(normalization_version),
-                    source_format,
-                    source_yaml_hash,
-                    serde_json::to_string(normalized_json)?,
-                    rfc3339(now_utc())?,
-                ],
-            )
-            .context("failed to upsert workflow snapshot")?;
-        Ok(())
+    /// Run `write` in one immediate transaction whether or not the store is coordinated, for
+    /// writes that must read and write atomically against other connections.
+    fn atomic<T>(&self, write: impl FnOnce() -> Result<T>) -> Result<T> {
+        if self.writer_lock.is_some() || !self.conn.is_autocommit() {
+            self.coordinated(write)
+        } else {
+            self.immediate(write)
+        }
     }
 
-    fn insert_run(&self, run: &RunRecord) -> Result<()> {
-        self.conn
-            .execute(
-                "INSERT INTO runs(
-                    run_id, workflow_name, workflow_version, workflow_hash,
-                    as_of, as_of_was_default, started_at, ended_at, status,
-                    replay_of_run_id, external_correlation_id, engine_version, cli_args_json,
-                    manifest_hash, manifest_signature, manifest_signature_status
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
-                params![
-                    run.run_id.to_string(),
-                    run.workflow_name,
-                    run.workflow_version,
-                    run.workflow_hash,
-                    rfc3339(run.as_of)?,
-                    bool_to_sql(run.as_of_was_default),
-                    rfc3339(run.started_at)?,
-                    run.ended_at.map(rfc3339).transpose()?,
-                    run_status_to_str(&run.status),
-                    run.replay_of_run_id.map(|id| id.to_string()),
-                    run.external_correlation_id,
-                    run.engine_version,
-                    serde_json::to_string(&run.cli_args_json)?,
-                    run.manifest_hash,
-                    run.manifest_signature,
-                    run.manifest_signature_status,
-                ],
-            )
-            .context("failed to insert run")?;
-        Ok(())
+    fn immediate<T>(&self, write: impl FnOnce() -> Result<T>) -> Result<T> {
+        let result = self
+            .conn
+            .execute_batch("BEGIN IMMEDIATE")
+            .context("failed to begin trace write transaction")
+            .and_then(|()| match write() {
+                Ok(value) => self
+                    .conn
+                    .execute_batch("COMMIT")
+                    .context("failed to commit trace write transaction")
+                    .map(|()| value),
+                Err(err) => {
+                    let _ = self.conn.execute_batch("ROLLBACK");
+                    Err(err)
+                }
+            });
+        if result.is_err() && !self.conn.is_autocommit() {
+            let _ = self.conn.execute_batch("ROLLBACK");
+        }
+        result
     }
 
-    fn update_run_finished(&self, run_id: RunId, status: RunStatus) -> Result<()> {
-        self.conn
-            .execute(
-                "UPDATE runs SET status = ?2, ended_at = ?3 WHERE run_id = ?1",
-                params![
-                    run_id.to_string(),
-                    run_status_to_str(&status),
-                    rfc3339(now_utc())?
-                ],
-            )
-            .context("failed to update run status")?;
-        Ok(())
+    /// Rebuild tables whose `status` constraint predates the current statuses, keeping every
+    /// row and column. The parent `runs` table can only be swapped with foreign keys off, which
+    /// `SQLite` ignores inside a transaction, so this runs before the migration transaction.
+    fn widen_legacy_status_checks(&self) -> Result<()> {
+        let mut legacy = Vec::new();
+        for (table, legacy_checks, check) in LEGACY_STATUS_CHECKS {
+            let sql: Option<String> = self
+                .conn
+                .query_row(
+                    "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?1",
+                    params![table],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let Some(sql) = sql else {
+                continue;
+            };
+            if let Some(legacy_check) = legacy_checks.iter().find(|legacy| sql.contains(**legacy)) {
+                let columns = sql
+                    .find('(')
+                    .map(|start| &sql[start..])
+                    .ok_or_else(|| anyhow!("unexpected schema for table {table}"))?;
+                legacy.push(format!(
+                    "CREATE TABLE {table}__rebuilt {};
+                     INSERT INTO {table}__rebuilt SELECT * FROM {table};
+                     DROP TABLE {table};
+                     ALTER TABLE {table}__rebuilt RENAME TO {table};",
+                    columns.replace(*legacy_check, check)
+                ));
+            }
+        }
+        if legacy.is_empty() {
+            return Ok(());
+        }
+        self.rebuild_tables(
+            &legacy.concat(),
+            "failed to widen legacy status constraints",
+        )
     }
 
-    fn update_run_manifest(
-        &self,
-        run_id: RunId,
-        manifest_hash: &str,
-        manifest_signature: Option<&str>,
-        manifest_signature_status: &str,
-    ) -> Result<()> {
-        self.conn
-            .execute(
-                "UPDATE runs SET
-                    manifest_hash = ?2,
-                    manifest_signature = ?3,
-                    manifest_signature_status = ?4
-                 WHERE run_id = ?1",
-                params![
-                    run_id.to_string(),
-                    manifest_hash,
-                    manifest_signature,
-                    manifest_signature_status,
-                ],
+    /// Rebuild a `step_context_packages` table keyed by `(step_id, package_slot)` so each
+    /// attempt of a step records its own packages; existing rows become attempt 1.
+    fn key_context_packages_by_attempt(&self) -> Result<()> {
+        let sql: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'step_context_packages'",
+                [],
+                |row| row.get(0),
             )
-            .context("failed to update run manifest")?;
-        Ok(())
+            .optional()?;
+        let (legacy_key, key) = LEGACY_CONTEXT_PACKAGE_KEY;
+        let Some(sql) = sql.filter(|sql| sql.contains(legacy_key)) else {
+            return Ok(());
+        };
+        let columns = sql
+            .find('(')
+            .map(|start| &sql[start..])
+            .ok_or_else(|| anyhow!("unexpected schema for table step_context_packages"))?;
+        self.rebuild_tables(
+            &format!(
+                "CREATE TABLE step_context_packages__rebuilt {};
+                 INSERT INTO step_context_packages__rebuilt ({CONTEXT_PACKAGE_LEGACY_COLUMNS})
+                   SELECT {CONTEXT_PACKAGE_LEGACY_COLUMNS} FROM step_context_packages;
+                 DROP TABLE step_context_packages;
+                 ALTER TABLE step_context_packages__rebuilt RENAME TO step_context_packages;",
+                columns.replace(legacy_key, key)
+            ),
+            "failed to key context packages by attempt",
+        )
     }
 
-    fn insert_step(&self, step: &StepRecord) -> Result<()> {
-        self.conn
-            .execute(
-                "INSERT INTO steps(
-                    step_id, run_id, step_index, step_key, agent_name,
-                    status, started_at, ended_at, task_payload_json,
-                    constraints_json, permissions_json, input_hash, output_hash, error_json
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
-                params![
-                    step.step_id.to_string(),
-                    step.run_id.to_string(),
-                    i64::try_from(step.step_index)
-                        .map_err(|_| anyhow!("step_index too large for sqlite"))?,
-                    step.step_key,
-                    step.agent_name,
-                    step_status_to_str(&step.status),
-                    step.started_at.map(rfc3339).transpose()?,
-                    step.ended_at.map(rfc3339).transpose()?,
-                    serde_json::to_string(&step.task_payload_json)?,
-                    serde_json::to_string(&step.constraints_json)?,
-                    serde_json::to_string(&step.permissions_json)?,
-                    step.input_hash,
-                    step.output_hash,
-                    step.error_json
-                        .as_ref()
-                        .map(serde_json::to_string)
-                        .transpose()?,
-                ],
-            )
-            .context("failed to insert step")?;
-        Ok(())
+    /// Run table rebuild `statements` in one transaction with foreign keys off, under the
+    /// writer lock.
+    fn rebuild_tables(&self, statements: &str, failure: &'static str) -> Result<()> {
+        if let Some(lock) = &self.writer_lock {
+            lock.lock_exclusive()
+                .context("failed to acquire trace writer lock")?;
+        }
+        self.conn.execute_batch("PRAGMA foreign_keys = OFF;")?;
+        let result = self
+            .conn
+            .execute_batch(&format!("BEGIN IMMEDIATE; {statements} COMMIT;"))
+            .context(failure);
+        if result.is_err() && !self.conn.is_autocommit() {
+            let _ = self.conn.execute_batch("ROLLBACK");
+        }
+        let restored = self.conn.execute_batch("PRAGMA foreign_keys = ON;");
+        if let Some(lock) = &self.writer_lock {
+            let _ = FileExt::unlock(lock);
+        }
+        result?;
+        restored.context("failed to re-enable foreign keys")
     }
 
-    fn update_step_status(
-        &self,
-        step_id: StepId,
-        status: StepStatus,
-        output_hash: Option<&str>,
-        error_json: Option<&serde_json::Value>,
-    ) -> Result<()> {
-        self.conn
-            .execute(
-                "UPDATE steps SET status = ?2, ended_at = ?3, output_hash = ?4, error_json = ?5 WHERE step_id = ?1",
-                params![
-                    step_id.to_string(),
-                    step_status_to_str(&status),
-                    rfc3339(now_utc())?,
-                    output_hash,
-                    error_json.map(serde_json::to_string).transpose()?,
-                ],
-            )
-            .context("failed to update step status")?;
-        Ok(())
+    /// Run a lightweight integrity ping: `SQLite` `quick_check` plus a probe of the core
+    /// trace tables. Returns the list of detected issues; an empty list means healthy.
+    ///
+    /// # Errors
+    /// Returns an error if the database cannot be queried at all.
+    pub fn integrity_ping(&self) -> Result<Vec<String>> {
+        let mut issues = Vec::new();
+
+        let quick_check: String = self
+            .conn
+            .query_row("PRAGMA quick_check", [], |row| row.get(0))
+            .context("failed to run sqlite quick_check")?;
+        if quick_check != "ok" {
+            issues.push(format!("quick_check reported: {quick_check}"));
+        }
+
+        for table in ["runs", "steps", "trace_events"] {
+            let exists = self
+                .conn
+                .query_row(
+                    "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1 LIMIT 1",
+                    params![table],
+                    |_| Ok(()),
+                )
+                .optional()
+                .context("failed to query sqlite_master")?
+                .is_some();
+            if !exists {
+                issues.push(format!("missing trace table: {table}"));
+            }
+        }
+
+        Ok(issues)
     }
 
-    fn append_event(&self, event: &TraceEvent) -> Result<i64> {
+    /// Insert `event` as `event_seq` when given, otherwise under the next autoincrement value.
+    fn insert_event(&self, event: &TraceEvent, event_seq: Option<i64>) -> Result<i64> {
         self.conn
             .execute(
                 "INSERT INTO trace_events(
-                    event_id, run_id, step_id, event_type,
+                    event_seq, event_id, run_id, step_id, event_type,
                     occurred_at, recorded_at, actor_type, actor_id,
                     payload_json, payload_hash, prev_event_hash, event_hash
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
                 params![
+                    event_seq,
                     event.event_id.to_string(),
                     event.run_id.to_string(),
                     event.step_id.map(|id| id.to_string()),
@@ -547,143 +755,637 @@ impl TraceStore for SqliteTraceStore {
         Ok(self.conn.last_insert_rowid())
     }
 
-    fn append_context_package(
-        &self,
-        run_id: RunId,
-        step_id: StepId,
-        envelope: &ContextPackageEnvelope,
-    ) -> Result<()> {
+    /// Highest `event_seq` handed out so far, including sequence values of deleted rows.
+    fn last_event_seq(&self) -> Result<i64> {
         self.conn
-            .execute(
-                "INSERT INTO step_context_packages(
-                    run_id, step_id, package_slot, context_package_id, generated_at,
-                    query_json, determinism_json, answer_json, ordering_trace_json,
-                    package_json, package_hash
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
-                params![
-                    run_id.to_string(),
-                    step_id.to_string(),
-                    i64::try_from(envelope.package_slot)
-                        .map_err(|_| anyhow!("package_slot too large"))?,
-                    envelope.context_package.context_package_id,
-                    rfc3339(envelope.context_package.generated_at)?,
-                    serde_json::to_string(&envelope.context_package.query)?,
-                    serde_json::to_string(&envelope.context_package.determinism)?,
-                    serde_json::to_string(&envelope.context_package.answer)?,
-                    serde_json::to_string(&envelope.context_package.ordering_trace)?,
-                    serde_json::to_string(&envelope.context_package)?,
-                    envelope.package_hash,
-                ],
+            .query_row(
+                "SELECT MAX(
+                    COALESCE((SELECT seq FROM sqlite_sequence WHERE name = 'trace_events'), 0),
+                    COALESCE((SELECT MAX(event_seq) FROM trace_events), 0)
+                )",
+                [],
+                |row| row.get(0),
             )
-            .context("failed to insert step_context_packages row")?;
+            .context("failed to read trace event sequence")
+    }
+
+    fn run_with_labels(&self, row: &rusqlite::Row<'_>) -> Result<RunRecord> {
+        let mut run = run_from_row(row)?;
+        let mut stmt = self.conn.prepare_cached(
+            "SELECT label_key, label_value FROM run_labels WHERE run_id = ?1 ORDER BY label_key",
+        )?;
+        run.labels = stmt
+            .query_map(params![run.run_id.to_string()], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(run)
+    }
 
-        let package_row_id = self.conn.last_insert_rowid();
+    /// Recompute `step_duration_stats` for `workflow_hash` from every succeeded step of every
+    /// succeeded run, so repeated refreshes stay idempotent.
+    fn refresh_step_duration_stats(&self, workflow_hash: &str) -> Result<()> {
+        let mut stmt = self.conn.prepare(
+            "SELECT s.step_key, s.started_at, s.ended_at
+             FROM steps s
+             JOIN runs r ON r.run_id = s.run_id
+             WHERE r.workflow_hash = ?1
+               AND r.status = 'succeeded'
+               AND s.status = 'succeeded'
+               AND s.started_at IS NOT NULL
+               AND s.ended_at IS NOT NULL
+             ORDER BY r.ended_at ASC, r.run_id ASC",
+        )?;
+        let mut rows = stmt.query(params![workflow_hash])?;
+        let mut samples: BTreeMap<String, Vec<u64>> = BTreeMap::new();
+        while let Some(row) = rows.next()? {
+            let started_at = parse_rfc3339(&row.get::<_, String>(1)?)?;
+            let ended_at = parse_rfc3339(&row.get::<_, String>(2)?)?;
+            samples
+                .entry(row.get(0)?)
+                .or_default()
+                .push(duration_ms(started_at, ended_at));
+        }
 
-        for item in &envelope.context_package.selected_items {
+        let updated_at = rfc3339(now_utc())?;
+        for (step_key, durations) in samples {
+            let stats = summarize_durations(&durations);
             self.conn
                 .execute(
-                    "INSERT INTO step_context_selected(
-                        step_context_package_id, rank, memory_version_id, memory_id,
-                        version, record_type, truth_status, confidence, authority,
-                        why_reasons_json, rule_scores_json, injected,
-                        permission_decision, permission_reason
-                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, 1, 'allowed', NULL)",
+                    "INSERT INTO step_duration_stats(
+                        workflow_hash, step_key, sample_count, mean_ms, min_ms, max_ms,
+                        stddev_ms, last_ms, updated_at
+                     ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                     ON CONFLICT(workflow_hash, step_key) DO UPDATE SET
+                        sample_count = excluded.sample_count,
+                        mean_ms = excluded.mean_ms,
+                        min_ms = excluded.min_ms,
+                        max_ms = excluded.max_ms,
+                        stddev_ms = excluded.stddev_ms,
+                        last_ms = excluded.last_ms,
+                        updated_at = excluded.updated_at",
                     params![
-                        package_row_id,
-                        i64::try_from(item.rank).map_err(|_| anyhow!("rank overflow"))?,
-                        item.memory_version_id.to_string(),
-                        item.memory_id.to_string(),
-                        i64::from(item.version),
-                        item.record_type.as_str(),
-                        item.truth_status.as_str(),
-                        item.confidence,
-                        item.authority.as_str(),
-                        serde_json::to_string(&item.why.reasons)?,
-                        item.why
-                            .rule_scores
-                            .as_ref()
-                            .map(serde_json::to_string)
-                            .transpose()?,
+                        workflow_hash,
+                        step_key,
+                        u64_to_sql(stats.0)?,
+                        u64_to_sql(stats.1)?,
+                        u64_to_sql(stats.2)?,
+                        u64_to_sql(stats.3)?,
+                        u64_to_sql(stats.4)?,
+                        u64_to_sql(stats.5)?,
+                        updated_at,
                     ],
                 )
-                .context("failed to insert step_context_selected row")?;
+                .context("failed to upsert step duration stats")?;
         }
+        Ok(())
+    }
+}
+
+impl TraceStore for SqliteTraceStore {
+    fn migrate(&self) -> Result<()> {
+        self.widen_legacy_status_checks()?;
+        self.key_context_packages_by_attempt()?;
+        self.coordinated(|| {
+            self.conn
+                .execute_batch(SCHEMA_V2)
+                .context("failed to apply trace schema")?;
+
+            ensure_column(&self.conn, "runs", "manifest_hash", "TEXT")?;
+            ensure_column(&self.conn, "runs", "manifest_signature", "TEXT")?;
+            ensure_column(&self.conn, "runs", "manifest_json", "TEXT")?;
+            ensure_column(&self.conn, "runs", "rerun_of_run_id", "TEXT")?;
+            ensure_column(&self.conn, "runs", "rerun_step_key", "TEXT")?;
+            ensure_column(&self.conn, "runs", "parent_run_id", "TEXT")?;
+            ensure_column(&self.conn, "runs", "parent_step_id", "TEXT")?;
+            ensure_column(&self.conn, "runs", "parent_step_key", "TEXT")?;
+            self.conn
+                .execute_batch(
+                    "CREATE INDEX IF NOT EXISTS idx_runs_parent_run
+                       ON runs(parent_run_id) WHERE parent_run_id IS NOT NULL;",
+                )
+                .context("failed to index nested runs")?;
+            ensure_column(
+                &self.conn,
+                "runs",
+                "manifest_signature_status",
+                "TEXT NOT NULL DEFAULT 'unsigned'",
+            )?;
+            ensure_column(&self.conn, "step_gate_decisions", "memory_id", "TEXT")?;
+            ensure_column(&self.conn, "step_gate_decisions", "version", "INTEGER")?;
+            ensure_column(
+                &self.conn,
+                "step_gate_decisions",
+                "memory_version_id",
+                "TEXT",
+            )?;
+            ensure_column(
+                &self.conn,
+                "step_gate_decisions",
+                "source_ruleset_version",
+                "INTEGER",
+            )?;
+            ensure_column(&self.conn, "step_gate_decisions", "evidence_json", "TEXT")?;
+            ensure_column(&self.conn, "steps", "retry_json", "TEXT")?;
+            ensure_column(&self.conn, "steps", "prompt_template_hash", "TEXT")?;
+            ensure_column(&self.conn, "steps", "provider_selection_json", "TEXT")?;
+            ensure_column(&self.conn, "steps", "taint_json", "TEXT")?;
+            ensure_column(&self.conn, "steps", "memoized_from_run", "TEXT")?;
+            ensure_column(&self.conn, "steps", "memo_key", "TEXT")?;
+            self.conn
+                .execute_batch("CREATE INDEX IF NOT EXISTS idx_steps_memo_key ON steps(memo_key);")
+                .context("failed to create steps memo_key index")?;
+            ensure_column(&self.conn, "provider_calls", "persona_json", "TEXT")?;
+
+            let backfill_duration_stats = !table_exists(&self.conn, "step_duration_stats")?;
+            self.conn
+                .execute_batch(STEP_DURATION_STATS_SQL)
+                .context("failed to apply step duration stats schema")?;
+            if backfill_duration_stats {
+                let mut stmt = self.conn.prepare(
+                    "SELECT DISTINCT workflow_hash FROM runs WHERE status = 'succeeded'",
+                )?;
+                let hashes = stmt
+                    .query_map([], |row| row.get::<_, String>(0))?
+                    .collect::<rusqlite::Result<Vec<_>>>()?;
+                for workflow_hash in hashes {
+                    self.refresh_step_duration_stats(&workflow_hash)?;
+                }
+            }
+
+            self.conn
+                .execute_batch(RUN_USAGE_SQL)
+                .context("failed to apply run usage schema")?;
+            self.conn
+                .execute_batch(DEFERRED_GATES_SQL)
+                .context("failed to apply deferred gates schema")?;
+            ensure_column(
+                &self.conn,
+                "deferred_gates",
+                "approvers_json",
+                "TEXT NOT NULL DEFAULT '[]'",
+            )?;
+            ensure_column(&self.conn, "deferred_gates", "auth_method", "TEXT")?;
+            ensure_column(&self.conn, "deferred_gates", "on_behalf_of", "TEXT")?;
+            ensure_column(&self.conn, "deferred_gates", "webhook_json", "TEXT")?;
+            self.conn
+                .execute_batch(LOAD_BALANCING_TURNS_SQL)
+                .context("failed to apply load balancing turns schema")?;
 
-        for item in &envelope.context_package.excluded_items {
+            let now = rfc3339(now_utc())?;
             self.conn
                 .execute(
-                    "INSERT INTO step_context_excluded(
-                        step_context_package_id, rank, memory_version_id, memory_id,
-                        version, record_type, truth_status, confidence, authority, why_reasons_json
-                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-                    params![
-                        package_row_id,
-                        i64::try_from(item.rank).map_err(|_| anyhow!("rank overflow"))?,
-                        item.memory_version_id.to_string(),
-                        item.memory_id.to_string(),
-                        i64::from(item.version),
-                        item.record_type.as_str(),
-                        item.truth_status.as_str(),
-                        item.confidence,
-                        item.authority.as_str(),
-                        serde_json::to_string(&item.why.reasons)?,
-                    ],
+                    "INSERT OR IGNORE INTO schema_migrations(version, applied_at) VALUES (?1, ?2)",
+                    params![TRACE_SCHEMA_VERSION, now],
                 )
-                .context("failed to insert step_context_excluded row")?;
-        }
+                .context("failed to record trace migration")?;
 
-        Ok(())
+            Ok(())
+        })
     }
 
-    fn append_gate_decision(
+    fn upsert_workflow_snapshot(
         &self,
-        run_id: RunId,
-        step_id: StepId,
-        decision: &GateDecisionRecord,
+        workflow_hash: &str,
+        normalization_version: u32,
+        source_format: &str,
+        source_yaml_hash: &str,
+        normalized_json: &serde_json::Value,
     ) -> Result<()> {
-        if matches!(decision.gate_kind, GateKind::Trust)
-            && decision.subject_type == "memory_ref"
-            && (decision.memory_id.is_none()
-                || decision.version.is_none()
-                || decision.memory_version_id.is_none())
-        {
-            return Err(anyhow!(
-                "trust gate decision for memory_ref requires memory_id, version, and memory_version_id"
-            ));
-        }
+        self.coordinated(|| {
+            self.conn
+                .execute(
+                    "INSERT INTO workflow_snapshots(
+                        workflow_hash, normalization_version, source_format,
+                        source_yaml_hash, normalized_json, created_at
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                    ON CONFLICT(workflow_hash) DO UPDATE SET
+                        normalization_version = excluded.normalization_version,
+                        source_format = excluded.source_format,
+                        source_yaml_hash = excluded.source_yaml_hash,
+                        normalized_json = excluded.normalized_json",
+                    params![
+                        workflow_hash,
+                        i64::from(normalization_version),
+                        source_format,
+                        source_yaml_hash,
+                        serde_json::to_string(normalized_json)?,
+                        rfc3339(now_utc())?,
+                    ],
+                )
+                .context("failed to upsert workflow snapshot")?;
+            Ok(())
+        })
+    }
 
-        self.conn
-            .execute(
-                "INSERT INTO step_gate_decisions(
-                    run_id, step_id, gate_kind, gate_name, subject_type,
-                    memory_id, version, memory_version_id,
-                    decision, reason_codes_json, notes, decided_by, decided_at,
-                    source_ruleset_version, evidence_json
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
-                params![
-                    run_id.to_string(),
-                    step_id.to_string(),
-                    gate_kind_to_str(&decision.gate_kind),
-                    decision.gate_name,
-                    decision.subject_type,
-                    decision.memory_id.map(|value| value.to_string()),
-                    decision.version.map(i64::from),
-                    decision.memory_version_id.map(|value| value.to_string()),
-                    gate_decision_to_str(&decision.decision),
-                    serde_json::to_string(&decision.reason_codes)?,
-                    decision.notes,
-                    decision.decided_by,
-                    rfc3339(decision.decided_at)?,
-                    decision.source_ruleset_version.map(i64::from),
-                    decision
-                        .evidence_json
-                        .as_ref()
-                        .map(serde_json::to_string)
-                        .transpose()?,
-                ],
-            )
-            .context("failed to insert step_gate_decisions row")?;
-        Ok(())
+    fn insert_run(&self, run: &RunRecord) -> Result<()> {
+        self.coordinated(|| {
+            self.conn
+                .execute(
+                    "INSERT INTO runs(
+                        run_id, workflow_name, workflow_version, workflow_hash,
+                        as_of, as_of_was_default, started_at, ended_at, status,
+                        replay_of_run_id, external_correlation_id, engine_version, cli_args_json,
+                        manifest_hash, manifest_signature, manifest_signature_status,
+                        rerun_of_run_id, rerun_step_key,
+                        parent_run_id, parent_step_id, parent_step_key
+                    ) VALUES (
+                        ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18,
+                        ?19, ?20, ?21
+                    )",
+                    params![
+                        run.run_id.to_string(),
+                        run.workflow_name,
+                        run.workflow_version,
+                        run.workflow_hash,
+                        rfc3339(run.as_of)?,
+                        bool_to_sql(run.as_of_was_default),
+                        rfc3339(run.started_at)?,
+                        run.ended_at.map(rfc3339).transpose()?,
+                        run_status_to_str(&run.status),
+                        run.replay_of_run_id.map(|id| id.to_string()),
+                        run.external_correlation_id,
+                        run.engine_version,
+                        serde_json::to_string(&run.cli_args_json)?,
+                        run.manifest_hash,
+                        run.manifest_signature,
+                        run.manifest_signature_status,
+                        run.rerun_of
+                            .as_ref()
+                            .map(|rerun| rerun.source_run_id.to_string()),
+                        run.rerun_of.as_ref().map(|rerun| rerun.step_key.as_str()),
+                        run.parent_step
+                            .as_ref()
+                            .map(|parent| parent.run_id.to_string()),
+                        run.parent_step
+                            .as_ref()
+                            .map(|parent| parent.step_id.to_string()),
+                        run.parent_step
+                            .as_ref()
+                            .map(|parent| parent.step_key.as_str()),
+                    ],
+                )
+                .context("failed to insert run")?;
+            for (key, value) in &run.labels {
+                self.conn
+                    .execute(
+                        "INSERT INTO run_labels(run_id, label_key, label_value) VALUES (?1, ?2, ?3)",
+                        params![run.run_id.to_string(), key, value],
+                    )
+                    .context("failed to insert run label")?;
+            }
+            Ok(())
+        })
+    }
+
+    fn insert_run_claiming_correlation(&self, run: &RunRecord) -> Result<Option<RunRecord>> {
+        let Some(correlation_id) = run.external_correlation_id.as_deref() else {
+            self.insert_run(run)?;
+            return Ok(None);
+        };
+        self.atomic(|| {
+            let existing = self
+                .find_runs_by_external_correlation_id(correlation_id)?
+                .into_iter()
+                .find(|existing| {
+                    !matches!(existing.status, RunStatus::Failed | RunStatus::Cancelled)
+                });
+            if existing.is_some() {
+                return Ok(existing);
+            }
+            self.insert_run(run)?;
+            Ok(None)
+        })
+    }
+
+    fn update_run_finished(&self, run_id: RunId, status: RunStatus) -> Result<()> {
+        self.coordinated(|| {
+            self.conn
+                .execute(
+                    "UPDATE runs SET status = ?2, ended_at = ?3 WHERE run_id = ?1",
+                    params![
+                        run_id.to_string(),
+                        run_status_to_str(&status),
+                        rfc3339(now_utc())?
+                    ],
+                )
+                .context("failed to update run status")?;
+
+            if status == RunStatus::Succeeded {
+                let workflow_hash: Option<String> = self
+                    .conn
+                    .query_row(
+                        "SELECT workflow_hash FROM runs WHERE run_id = ?1",
+                        params![run_id.to_string()],
+                        |row| row.get(0),
+                    )
+                    .optional()?;
+                if let Some(workflow_hash) = workflow_hash {
+                    self.refresh_step_duration_stats(&workflow_hash)?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn update_run_manifest(
+        &self,
+        run_id: RunId,
+        manifest: &serde_json::Value,
+        manifest_hash: &str,
+        manifest_signature: Option<&str>,
+        manifest_signature_status: &str,
+    ) -> Result<()> {
+        self.coordinated(|| {
+            self.conn
+                .execute(
+                    "UPDATE runs SET
+                        manifest_hash = ?2,
+                        manifest_signature = ?3,
+                        manifest_signature_status = ?4,
+                        manifest_json = ?5
+                     WHERE run_id = ?1",
+                    params![
+                        run_id.to_string(),
+                        manifest_hash,
+                        manifest_signature,
+                        manifest_signature_status,
+                        serde_json::to_string(manifest)?,
+                    ],
+                )
+                .context("failed to update run manifest")?;
+            Ok(())
+        })
+    }
+
+    fn get_run_manifest(&self, run_id: RunId) -> Result<Option<serde_json::Value>> {
+        let raw: Option<Option<String>> = self
+            .conn
+            .query_row(
+                "SELECT manifest_json FROM runs WHERE run_id = ?1",
+                params![run_id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        raw.flatten()
+            .map(|raw| serde_json::from_str(&raw).context("failed to decode run manifest"))
+            .transpose()
+    }
+
+    fn insert_step(&self, step: &StepRecord) -> Result<()> {
+        self.coordinated(|| {
+            self.conn
+                .execute(
+                    "INSERT INTO steps(
+                        step_id, run_id, step_index, step_key, agent_name,
+                        status, started_at, ended_at, task_payload_json,
+                        constraints_json, permissions_json, input_hash, output_hash, error_json,
+                        retry_json, prompt_template_hash, provider_selection_json, taint_json,
+                        memoized_from_run, memo_key
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20)",
+                    params![
+                        step.step_id.to_string(),
+                        step.run_id.to_string(),
+                        i64::try_from(step.step_index)
+                            .map_err(|_| anyhow!("step_index too large for sqlite"))?,
+                        step.step_key,
+                        step.agent_name,
+                        step_status_to_str(&step.status),
+                        step.started_at.map(rfc3339).transpose()?,
+                        step.ended_at.map(rfc3339).transpose()?,
+                        serde_json::to_string(&step.task_payload_json)?,
+                        serde_json::to_string(&step.constraints_json)?,
+                        serde_json::to_string(&step.permissions_json)?,
+                        step.input_hash,
+                        step.output_hash,
+                        step.error_json
+                            .as_ref()
+                            .map(serde_json::to_string)
+                            .transpose()?,
+                        step.retry_json
+                            .as_ref()
+                            .map(serde_json::to_string)
+                            .transpose()?,
+                        step.prompt_template_hash,
+                        step.provider_selection_json
+                            .as_ref()
+                            .map(serde_json::to_string)
+                            .transpose()?,
+                        step.taint_json
+                            .as_ref()
+                            .map(serde_json::to_string)
+                            .transpose()?,
+                        step.memoized_from_run.map(|run_id| run_id.to_string()),
+                        step.memo_key,
+                    ],
+                )
+                .context("failed to insert step")?;
+            Ok(())
+        })
+    }
+
+    fn update_step_status(
+        &self,
+        step_id: StepId,
+        status: StepStatus,
+        output_hash: Option<&str>,
+        error_json: Option<&serde_json::Value>,
+    ) -> Result<()> {
+        self.coordinated(|| {
+            self.conn
+                .execute(
+                    "UPDATE steps SET status = ?2, ended_at = ?3, output_hash = ?4, error_json = ?5 WHERE step_id = ?1",
+                    params![
+                        step_id.to_string(),
+                        step_status_to_str(&status),
+                        rfc3339(now_utc())?,
+                        output_hash,
+                        error_json.map(serde_json::to_string).transpose()?,
+                    ],
+                )
+                .context("failed to update step status")?;
+            Ok(())
+        })
+    }
+
+    fn update_step_retry(&self, step_id: StepId, retry_json: &serde_json::Value) -> Result<()> {
+        self.coordinated(|| {
+            self.conn
+                .execute(
+                    "UPDATE steps SET retry_json = ?2 WHERE step_id = ?1",
+                    params![step_id.to_string(), serde_json::to_string(retry_json)?],
+                )
+                .context("failed to update step retry")?;
+            Ok(())
+        })
+    }
+
+    fn update_step_provider_selection(
+        &self,
+        step_id: StepId,
+        provider_selection_json: &serde_json::Value,
+    ) -> Result<()> {
+        self.coordinated(|| {
+            self.conn
+                .execute(
+                    "UPDATE steps SET provider_selection_json = ?2 WHERE step_id = ?1",
+                    params![
+                        step_id.to_string(),
+                        serde_json::to_string(provider_selection_json)?
+                    ],
+                )
+                .context("failed to update step provider selection")?;
+            Ok(())
+        })
+    }
+
+    fn append_event(&self, event: &TraceEvent) -> Result<i64> {
+        self.coordinated(|| self.insert_event(event, None))
+    }
+
+    fn append_context_package(
+        &self,
+        run_id: RunId,
+        step_id: StepId,
+        attempt: u32,
+        envelope: &ContextPackageEnvelope,
+    ) -> Result<()> {
+        self.coordinated(|| {
+            self.conn
+                .execute(
+                    "INSERT INTO step_context_packages(
+                        run_id, step_id, attempt, package_slot, context_package_id,
+                        generated_at, query_json, determinism_json, answer_json,
+                        ordering_trace_json, package_json, package_hash
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                    params![
+                        run_id.to_string(),
+                        step_id.to_string(),
+                        i64::from(attempt),
+                        i64::try_from(envelope.package_slot)
+                            .map_err(|_| anyhow!("package_slot too large"))?,
+                        envelope.context_package.context_package_id,
+                        rfc3339(envelope.context_package.generated_at)?,
+                        serde_json::to_string(&envelope.context_package.query)?,
+                        serde_json::to_string(&envelope.context_package.determinism)?,
+                        serde_json::to_string(&envelope.context_package.answer)?,
+                        serde_json::to_string(&envelope.context_package.ordering_trace)?,
+                        serde_json::to_string(&envelope.context_package)?,
+                        envelope.package_hash,
+                    ],
+                )
+                .context("failed to insert step_context_packages row")?;
+
+            let package_row_id = self.conn.last_insert_rowid();
+
+            for item in &envelope.context_package.selected_items {
+                self.conn
+                    .execute(
+                        "INSERT INTO step_context_selected(
+                            step_context_package_id, rank, memory_version_id, memory_id,
+                            version, record_type, truth_status, confidence, authority,
+                            why_reasons_json, rule_scores_json, injected,
+                            permission_decision, permission_reason
+                        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, 1, 'allowed', NULL)",
+                        params![
+                            package_row_id,
+                            i64::try_from(item.rank).map_err(|_| anyhow!("rank overflow"))?,
+                            item.memory_version_id.to_string(),
+                            item.memory_id.to_string(),
+                            i64::from(item.version),
+                            item.record_type.as_str(),
+                            item.truth_status.as_str(),
+                            item.confidence,
+                            item.authority.as_str(),
+                            serde_json::to_string(&item.why.reasons)?,
+                            item.why
+                                .rule_scores
+                                .as_ref()
+                                .map(serde_json::to_string)
+                                .transpose()?,
+                        ],
+                    )
+                    .context("failed to insert step_context_selected row")?;
+            }
+
+            for item in &envelope.context_package.excluded_items {
+                self.conn
+                    .execute(
+                        "INSERT INTO step_context_excluded(
+                            step_context_package_id, rank, memory_version_id, memory_id,
+                            version, record_type, truth_status, confidence, authority, why_reasons_json
+                        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                        params![
+                            package_row_id,
+                            i64::try_from(item.rank).map_err(|_| anyhow!("rank overflow"))?,
+                            item.memory_version_id.to_string(),
+                            item.memory_id.to_string(),
+                            i64::from(item.version),
+                            item.record_type.as_str(),
+                            item.truth_status.as_str(),
+                            item.confidence,
+                            item.authority.as_str(),
+                            serde_json::to_string(&item.why.reasons)?,
+                        ],
+                    )
+                    .context("failed to insert step_context_excluded row")?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn append_gate_decision(
+        &self,
+        run_id: RunId,
+        step_id: StepId,
+        decision: &GateDecisionRecord,
+    ) -> Result<()> {
+        self.coordinated(|| {
+            if matches!(decision.gate_kind, GateKind::Trust)
+                && decision.subject_type == "memory_ref"
+                && (decision.memory_id.is_none()
+                    || decision.version.is_none()
+                    || decision.memory_version_id.is_none())
+            {
+                return Err(anyhow!(
+                    "trust gate decision for memory_ref requires memory_id, version, and memory_version_id"
+                ));
+            }
+
+            self.conn
+                .execute(
+                    "INSERT INTO step_gate_decisions(
+                        run_id, step_id, gate_kind, gate_name, subject_type,
+                        memory_id, version, memory_version_id,
+                        decision, reason_codes_json, notes, decided_by, decided_at,
+                        source_ruleset_version, evidence_json
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+                    params![
+                        run_id.to_string(),
+                        step_id.to_string(),
+                        gate_kind_to_str(&decision.gate_kind),
+                        decision.gate_name,
+                        decision.subject_type,
+                        decision.memory_id.map(|value| value.to_string()),
+                        decision.version.map(i64::from),
+                        decision.memory_version_id.map(|value| value.to_string()),
+                        gate_decision_to_str(&decision.decision),
+                        serde_json::to_string(&decision.reason_codes)?,
+                        decision.notes,
+                        decision.decided_by,
+                        rfc3339(decision.decided_at)?,
+                        decision.source_ruleset_version.map(i64::from),
+                        decision
+                            .evidence_json
+                            .as_ref()
+                            .map(serde_json::to_string)
+                            .transpose()?,
+                    ],
+                )
+                .context("failed to insert step_gate_decisions row")?;
+            Ok(())
+        })
     }
 
     fn append_provider_call(
@@ -692,40 +1394,182 @@ impl TraceStore for SqliteTraceStore {
         step_id: StepId,
         call: &multi_agent_center_domain::ProviderCallRecord,
     ) -> Result<()> {
+        self.coordinated(|| {
+            self.conn
+                .execute(
+                    "INSERT INTO provider_calls(
+                        provider_call_id, run_id, step_id, provider_name,
+                        adapter_version, model_id, request_json, request_hash,
+                        response_json, response_hash, latency_ms,
+                        input_tokens, output_tokens, started_at, ended_at,
+                        status, error_text, persona_json
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
+                    params![
+                        call.provider_call_id.to_string(),
+                        run_id.to_string(),
+                        step_id.to_string(),
+                        call.provider_name,
+                        call.adapter_version,
+                        call.model_id,
+                        serde_json::to_string(&call.request_json)?,
+                        call.request_hash,
+                        serde_json::to_string(&call.response_json)?,
+                        call.response_hash,
+                        call.latency_ms
+                            .map(i64::try_from)
+                            .transpose()
+                            .map_err(|_| anyhow!("latency overflow"))?,
+                        call.input_tokens.map(i64::from),
+                        call.output_tokens.map(i64::from),
+                        rfc3339(call.started_at)?,
+                        rfc3339(call.ended_at)?,
+                        call.status,
+                        call.error_text,
+                        call.persona_json
+                            .as_ref()
+                            .map(serde_json::to_string)
+                            .transpose()?,
+                    ],
+                )
+                .context("failed to insert provider_call row")?;
+            Ok(())
+        })
+    }
+
+    fn append_step_checkpoint(&self, checkpoint: &StepCheckpoint) -> Result<()> {
+        self.coordinated(|| {
+            self.conn
+                .execute(
+                    "INSERT INTO step_checkpoints(
+                        step_id, checkpoint_seq, run_id, provider_call_id, state_json,
+                        prev_checkpoint_hash, state_hash, created_at
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![
+                        checkpoint.step_id.to_string(),
+                        i64::from(checkpoint.checkpoint_seq),
+                        checkpoint.run_id.to_string(),
+                        checkpoint.provider_call_id.map(|id| id.to_string()),
+                        serde_json::to_string(&checkpoint.state_json)?,
+                        checkpoint.prev_checkpoint_hash,
+                        checkpoint.state_hash,
+                        rfc3339(checkpoint.created_at)?,
+                    ],
+                )
+                .context("failed to insert step_checkpoints row")?;
+            Ok(())
+        })
+    }
+
+    fn put_prompt_template(&self, template: &PromptTemplateRecord) -> Result<()> {
+        self.coordinated(|| {
+            if let Some(existing) =
+                self.get_prompt_template(&template.template_name, &template.template_version)?
+            {
+                if existing.template_hash == template.template_hash {
+                    return Ok(());
+                }
+                return Err(anyhow!(
+                    "prompt template {} is already registered with a different body",
+                    template.prompt_ref()
+                ));
+            }
+            self.conn
+                .execute(
+                    "INSERT INTO prompt_templates(
+                        template_name, template_version, body, template_hash, created_at
+                    ) VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        template.template_name,
+                        template.template_version,
+                        template.body,
+                        template.template_hash,
+                        rfc3339(template.created_at)?,
+                    ],
+                )
+                .context("failed to insert prompt_templates row")?;
+            Ok(())
+        })
+    }
+
+    fn get_prompt_template(
+        &self,
+        template_name: &str,
+        template_version: &str,
+    ) -> Result<Option<PromptTemplateRecord>> {
         self.conn
-            .execute(
-                "INSERT INTO provider_calls(
-                    provider_call_id, run_id, step_id, provider_name,
-                    adapter_version, model_id, request_json, request_hash,
-                    response_json, response_hash, latency_ms,
-                    input_tokens, output_tokens, started_at, ended_at,
-                    status, error_text
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
-                params![
-                    call.provider_call_id.to_string(),
-                    run_id.to_string(),
-                    step_id.to_string(),
-                    call.provider_name,
-                    call.adapter_version,
-                    call.model_id,
-                    serde_json::to_string(&call.request_json)?,
-                    call.request_hash,
-                    serde_json::to_string(&call.response_json)?,
-                    call.response_hash,
-                    call.latency_ms
-                        .map(i64::try_from)
-                        .transpose()
-                        .map_err(|_| anyhow!("latency overflow"))?,
-                    call.input_tokens.map(i64::from),
-                    call.output_tokens.map(i64::from),
-                    rfc3339(call.started_at)?,
-                    rfc3339(call.ended_at)?,
-                    call.status,
-                    call.error_text,
-                ],
+            .query_row(
+                "SELECT template_name, template_version, body, template_hash, created_at
+                 FROM prompt_templates
+                 WHERE template_name = ?1 AND template_version = ?2",
+                params![template_name, template_version],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, String>(3)?,
+                        row.get::<_, String>(4)?,
+                    ))
+                },
             )
-            .context("failed to insert provider_call row")?;
-        Ok(())
+            .optional()?
+            .map(prompt_template_from_row)
+            .transpose()
+    }
+
+    fn list_prompt_templates(&self) -> Result<Vec<PromptTemplateRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT template_name, template_version, body, template_hash, created_at
+             FROM prompt_templates
+             ORDER BY template_name ASC, template_version ASC",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        rows.into_iter().map(prompt_template_from_row).collect()
+    }
+
+    fn get_step_checkpoints(&self, step_id: StepId) -> Result<Vec<StepCheckpoint>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT
+                step_id, checkpoint_seq, run_id, provider_call_id, state_json,
+                prev_checkpoint_hash, state_hash, created_at
+             FROM step_checkpoints
+             WHERE step_id = ?1
+             ORDER BY checkpoint_seq ASC",
+        )?;
+        let mut rows = stmt.query(params![step_id.to_string()])?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next()? {
+            let checkpoint_seq: i64 = row.get(1)?;
+            let state_json: String = row.get(4)?;
+            out.push(StepCheckpoint {
+                step_id: parse_step_id(&row.get::<_, String>(0)?)?,
+                checkpoint_seq: u32::try_from(checkpoint_seq)
+                    .map_err(|_| anyhow!("invalid checkpoint_seq: {checkpoint_seq}"))?,
+                run_id: parse_run_id(&row.get::<_, String>(2)?)?,
+                provider_call_id: row
+                    .get::<_, Option<String>>(3)?
+                    .map(|value| {
+                        Ulid::from_str(&value)
+                            .map_err(|err| anyhow!("invalid provider_call_id ULID: {err}"))
+                    })
+                    .transpose()?,
+                state_json: serde_json::from_str(&state_json).context("invalid state_json")?,
+                prev_checkpoint_hash: row.get(5)?,
+                state_hash: row.get(6)?,
+                created_at: parse_rfc3339(&row.get::<_, String>(7)?)?,
+            });
+        }
+        Ok(out)
     }
 
     fn append_proposed_memory_write(
@@ -736,196 +1580,188 @@ impl TraceStore for SqliteTraceStore {
         disposition: &str,
         disposition_reason: Option<&str>,
     ) -> Result<()> {
-        self.conn
-            .execute(
-                "INSERT INTO proposed_memory_writes(
-                    run_id, step_id, proposal_index, proposal_json,
-                    proposal_hash, disposition, disposition_reason
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-                params![
-                    run_id.to_string(),
-                    step_id.to_string(),
-                    i64::try_from(write.proposal_index)
-                        .map_err(|_| anyhow!("proposal_index overflow"))?,
-                    serde_json::to_string(&write.payload)?,
-                    multi_agent_center_domain::hash_json(&write.payload)?,
-                    disposition,
-                    disposition_reason,
-                ],
-            )
-            .context("failed to insert proposed_memory_writes row")?;
-        Ok(())
+        self.coordinated(|| {
+            self.conn
+                .execute(
+                    "INSERT INTO proposed_memory_writes(
+                        run_id, step_id, proposal_index, proposal_json,
+                        proposal_hash, disposition, disposition_reason
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![
+                        run_id.to_string(),
+                        step_id.to_string(),
+                        i64::try_from(write.proposal_index)
+                            .map_err(|_| anyhow!("proposal_index overflow"))?,
+                        serde_json::to_string(&write.payload)?,
+                        multi_agent_center_domain::hash_json(&write.payload)?,
+                        disposition,
+                        disposition_reason,
+                    ],
+                )
+                .context("failed to insert proposed_memory_writes row")?;
+            Ok(())
+        })
     }
 
     fn list_runs(&self) -> Result<Vec<RunRecord>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT
-                run_id, workflow_name, workflow_version, workflow_hash,
-                as_of, as_of_was_default, started_at, ended_at,
-                status, replay_of_run_id, external_correlation_id,
-                engine_version, cli_args_json,
-                manifest_hash, manifest_signature, manifest_signature_status
-             FROM runs
-             ORDER BY started_at DESC, run_id ASC",
-        )?;
-
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {RUN_COLUMNS} FROM runs ORDER BY started_at DESC, run_id ASC"
+        ))?;
         let mut rows = stmt.query([])?;
         let mut out = Vec::new();
+        while let Some(row) = rows.next()? {
+            out.push(self.run_with_labels(row)?);
+        }
+        Ok(out)
+    }
+
+    fn list_runs_with_labels(&self, labels: &BTreeMap<String, String>) -> Result<Vec<RunRecord>> {
+        let filters = (1..=labels.len())
+            .map(|index| {
+                format!(
+                    " AND EXISTS (SELECT 1 FROM run_labels l WHERE l.run_id = runs.run_id
+                       AND l.label_key = ?{} AND l.label_value = ?{})",
+                    2 * index - 1,
+                    2 * index
+                )
+            })
+            .collect::<Vec<_>>()
+            .concat();
+        let values: Vec<&str> = labels
+            .iter()
+            .flat_map(|(key, value)| [key.as_str(), value.as_str()])
+            .collect();
+        let sql = format!(
+            "SELECT {RUN_COLUMNS} FROM runs WHERE 1 = 1{filters}
+             ORDER BY started_at DESC, run_id ASC"
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let mut rows = stmt.query(rusqlite::params_from_iter(values))?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next()? {
+            out.push(self.run_with_labels(row)?);
+        }
+        Ok(out)
+    }
 
+    fn find_runs_by_external_correlation_id(
+        &self,
+        external_correlation_id: &str,
+    ) -> Result<Vec<RunRecord>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {RUN_COLUMNS} FROM runs
+             WHERE external_correlation_id = ?1
+             ORDER BY started_at DESC, run_id ASC"
+        ))?;
+        let mut rows = stmt.query(params![external_correlation_id])?;
+        let mut out = Vec::new();
         while let Some(row) = rows.next()? {
-            let run_id_str: String = row.get(0)?;
-            let replay_of_run_id_str: Option<String> = row.get(9)?;
-            let cli_args_json: String = row.get(12)?;
-            out.push(RunRecord {
-                run_id: parse_run_id(&run_id_str)?,
-                workflow_name: row.get(1)?,
-                workflow_version: row.get(2)?,
-                workflow_hash: row.get(3)?,
-                as_of: parse_rfc3339(&row.get::<_, String>(4)?)?,
-                as_of_was_default: sql_to_bool(row.get::<_, i64>(5)?),
-                started_at: parse_rfc3339(&row.get::<_, String>(6)?)?,
-                ended_at: row
-                    .get::<_, Option<String>>(7)?
-                    .map(|v| parse_rfc3339(&v))
-                    .transpose()?,
-                status: parse_run_status(&row.get::<_, String>(8)?)?,
-                replay_of_run_id: replay_of_run_id_str
-                    .map(|value| parse_run_id(&value))
-                    .transpose()?,
-                external_correlation_id: row.get(10)?,
-                engine_version: row.get(11)?,
-                cli_args_json: serde_json::from_str(&cli_args_json)
-                    .context("invalid cli_args_json")?,
-                manifest_hash: row.get(13)?,
-                manifest_signature: row.get(14)?,
-                manifest_signature_status: row.get(15)?,
-            });
+            out.push(self.run_with_labels(row)?);
         }
+        Ok(out)
+    }
 
+    fn list_child_runs(&self, parent_run_id: RunId) -> Result<Vec<RunRecord>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {RUN_COLUMNS} FROM runs
+             WHERE parent_run_id = ?1
+             ORDER BY started_at ASC, run_id ASC"
+        ))?;
+        let mut rows = stmt.query(params![parent_run_id.to_string()])?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next()? {
+            out.push(self.run_with_labels(row)?);
+        }
         Ok(out)
     }
 
     fn list_events_for_run(&self, run_id: RunId) -> Result<Vec<EventRow>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT
-                event_seq, event_id, run_id, step_id, event_type,
-                occurred_at, recorded_at, actor_type, actor_id,
-                payload_json, payload_hash, prev_event_hash, event_hash
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {EVENT_COLUMNS}
              FROM trace_events
              WHERE run_id = ?1
-             ORDER BY event_seq ASC",
-        )?;
+             ORDER BY event_seq ASC"
+        ))?;
 
         let mut rows = stmt.query(params![run_id.to_string()])?;
         let mut out = Vec::new();
 
         while let Some(row) = rows.next()? {
-            let event_id_raw: String = row.get(1)?;
-            let run_id_raw: String = row.get(2)?;
-            let step_id_raw: Option<String> = row.get(3)?;
-            let payload_raw: String = row.get(9)?;
-            out.push(EventRow {
-                event_seq: row.get(0)?,
-                event: TraceEvent {
-                    event_id: Ulid::from_str(&event_id_raw)
-                        .map_err(|err| anyhow!("invalid event_id ULID: {err}"))?,
-                    run_id: parse_run_id(&run_id_raw)?,
-                    step_id: step_id_raw.map(|value| parse_step_id(&value)).transpose()?,
-                    event_type: parse_event_type(&row.get::<_, String>(4)?)?,
-                    occurred_at: parse_rfc3339(&row.get::<_, String>(5)?)?,
-                    recorded_at: parse_rfc3339(&row.get::<_, String>(6)?)?,
-                    actor_type: row.get(7)?,
-                    actor_id: row.get(8)?,
-                    payload_json: serde_json::from_str(&payload_raw)
-                        .context("invalid payload_json")?,
-                    payload_hash: row.get(10)?,
-                    prev_event_hash: row.get(11)?,
-                    event_hash: row.get(12)?,
-                },
-            });
+            out.push(event_row_from_row(row)?);
         }
 
         Ok(out)
     }
 
-    fn get_run(&self, run_id: RunId) -> Result<Option<RunRecord>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT
-                run_id, workflow_name, workflow_version, workflow_hash,
-                as_of, as_of_was_default, started_at, ended_at,
-                status, replay_of_run_id, external_correlation_id,
-                engine_version, cli_args_json,
-                manifest_hash, manifest_signature, manifest_signature_status
-             FROM runs WHERE run_id = ?1",
-        )?;
-
-        stmt.query_row(params![run_id.to_string()], |row| {
-            let run_id_str: String = row.get(0)?;
-            let replay_of_run_id_str: Option<String> = row.get(9)?;
-            let cli_args_json: String = row.get(12)?;
-            Ok((
-                run_id_str,
-                row.get::<_, String>(1)?,
-                row.get::<_, String>(2)?,
-                row.get::<_, String>(3)?,
-                row.get::<_, String>(4)?,
-                row.get::<_, i64>(5)?,
-                row.get::<_, String>(6)?,
-                row.get::<_, Option<String>>(7)?,
-                row.get::<_, String>(8)?,
-                replay_of_run_id_str,
-                row.get::<_, Option<String>>(10)?,
-                row.get::<_, String>(11)?,
-                cli_args_json,
-                row.get::<_, Option<String>>(13)?,
-                row.get::<_, Option<String>>(14)?,
-                row.get::<_, String>(15)?,
-            ))
-        })
-        .optional()?
-        .map(
-            |(
-                run_id_raw,
-                workflow_name,
-                workflow_version,
-                workflow_hash,
-                as_of,
-                as_of_was_default,
-                started_at,
-                ended_at,
-                status,
-                replay_of_run_id,
-                external_correlation_id,
-                engine_version,
-                cli_args_json,
-                manifest_hash,
-                manifest_signature,
-                manifest_signature_status,
-            )| {
-                Ok(RunRecord {
-                    run_id: parse_run_id(&run_id_raw)?,
-                    workflow_name,
-                    workflow_version,
-                    workflow_hash,
-                    as_of: parse_rfc3339(&as_of)?,
-                    as_of_was_default: sql_to_bool(as_of_was_default),
-                    started_at: parse_rfc3339(&started_at)?,
-                    ended_at: ended_at.map(|value| parse_rfc3339(&value)).transpose()?,
-                    status: parse_run_status(&status)?,
-                    replay_of_run_id: replay_of_run_id
-                        .map(|value| parse_run_id(&value))
-                        .transpose()?,
-                    external_correlation_id,
-                    engine_version,
-                    cli_args_json: serde_json::from_str(&cli_args_json)
-                        .context("invalid cli_args_json")?,
-                    manifest_hash,
-                    manifest_signature,
-                    manifest_signature_status,
-                })
-            },
-        )
-        .transpose()
+    fn query_events(&self, query: &EventQuery) -> Result<Vec<EventRow>> {
+        let mut filters: Vec<String> = Vec::new();
+        let mut values: Vec<String> = Vec::new();
+        let mut bind = |column: &str, value: String| {
+            values.push(value);
+            format!(" AND {column} = ?{}", values.len())
+        };
+        if let Some(run_id) = query.run_id {
+            filters.push(bind("run_id", run_id.to_string()));
+        }
+        if let Some(actor_type) = &query.actor_type {
+            filters.push(bind("actor_type", actor_type.clone()));
+        }
+        if let Some(actor_id) = &query.actor_id {
+            filters.push(bind("actor_id", actor_id.clone()));
+        }
+        if !query.event_types.is_empty() {
+            let placeholders = query
+                .event_types
+                .iter()
+                .map(|event_type| {
+                    values.push(event_type_to_str(event_type).to_string());
+                    format!("?{}", values.len())
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            filters.push(format!(" AND event_type IN ({placeholders})"));
+        }
+
+        let filters = filters.concat();
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {EVENT_COLUMNS}
+             FROM trace_events
+             WHERE 1 = 1{filters}
+             ORDER BY event_seq ASC"
+        ))?;
+        let mut rows = stmt.query(rusqlite::params_from_iter(values))?;
+        let limit = query.limit.unwrap_or(usize::MAX);
+        let mut out = Vec::new();
+        while out.len() < limit {
+            let Some(row) = rows.next()? else {
+                break;
+            };
+            let event_row = event_row_from_row(row)?;
+            let occurred_at = event_row.event.occurred_at;
+            if query.since.is_some_and(|since| occurred_at < since)
+                || query.until.is_some_and(|until| occurred_at >= until)
+            {
+                continue;
+            }
+            if query
+                .payload
+                .iter()
+                .all(|filter| filter.matches(&event_row.event.payload_json))
+            {
+                out.push(event_row);
+            }
+        }
+        Ok(out)
+    }
+
+    fn get_run(&self, run_id: RunId) -> Result<Option<RunRecord>> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!("SELECT {RUN_COLUMNS} FROM runs WHERE run_id = ?1"))?;
+        let mut rows = stmt.query(params![run_id.to_string()])?;
+        rows.next()?
+            .map(|row| self.run_with_labels(row))
+            .transpose()
     }
 
     fn get_step_records(&self, run_id: RunId) -> Result<Vec<StepRecord>> {
@@ -934,7 +1770,8 @@ impl TraceStore for SqliteTraceStore {
                 step_id, run_id, step_index, step_key, agent_name,
                 status, started_at, ended_at, task_payload_json,
                 constraints_json, permissions_json, input_hash,
-                output_hash, error_json
+                output_hash, error_json, retry_json, prompt_template_hash,
+                provider_selection_json, taint_json, memoized_from_run, memo_key
              FROM steps
              WHERE run_id = ?1
              ORDER BY step_index ASC",
@@ -949,6 +1786,7 @@ impl TraceStore for SqliteTraceStore {
             let constraints_json: String = row.get(9)?;
             let permissions_json: String = row.get(10)?;
             let error_json: Option<String> = row.get(13)?;
+            let retry_json: Option<String> = row.get(14)?;
             let step_index: i64 = row.get(2)?;
             out.push(StepRecord {
                 step_id: parse_step_id(&step_id_str)?,
@@ -977,98 +1815,1061 @@ impl TraceStore for SqliteTraceStore {
                 error_json: error_json
                     .map(|value| serde_json::from_str(&value).context("invalid error_json"))
                     .transpose()?,
+                retry_json: retry_json
+                    .map(|value| serde_json::from_str(&value).context("invalid retry_json"))
+                    .transpose()?,
+                prompt_template_hash: row.get(15)?,
+                provider_selection_json: row
+                    .get::<_, Option<String>>(16)?
+                    .map(|value| {
+                        serde_json::from_str(&value).context("invalid provider_selection_json")
+                    })
+                    .transpose()?,
+                taint_json: row
+                    .get::<_, Option<String>>(17)?
+                    .map(|value| serde_json::from_str(&value).context("invalid taint_json"))
+                    .transpose()?,
+                memoized_from_run: row
+                    .get::<_, Option<String>>(18)?
+                    .map(|value| parse_run_id(&value))
+                    .transpose()?,
+                memo_key: row.get(19)?,
+            });
+        }
+
+        Ok(out)
+    }
+
+    fn get_workflow_snapshot(&self, workflow_hash: &str) -> Result<Option<WorkflowSnapshotRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT
+                workflow_hash, normalization_version, source_format,
+                source_yaml_hash, normalized_json
+             FROM workflow_snapshots
+             WHERE workflow_hash = ?1",
+        )?;
+
+        stmt.query_row(params![workflow_hash], |row| {
+            let normalized_json: String = row.get(4)?;
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                normalized_json,
+            ))
+        })
+        .optional()?
+        .map(
+            |(
+                workflow_hash_value,
+                normalization_version_raw,
+                source_format,
+                source_yaml_hash,
+                normalized_json,
+            )| {
+                let normalization_version =
+                    u32::try_from(normalization_version_raw).map_err(|_| {
+                        anyhow!("invalid normalization_version: {normalization_version_raw}")
+                    })?;
+                Ok(WorkflowSnapshotRecord {
+                    workflow_hash: workflow_hash_value,
+                    normalization_version,
+                    source_format,
+                    source_yaml_hash,
+                    normalized_json: serde_json::from_str(&normalized_json)
+                        .context("invalid workflow_snapshots.normalized_json")?,
+                })
+            },
+        )
+        .transpose()
+    }
+
+    fn get_step_context_packages(&self, run_id: RunId) -> Result<Vec<StepContextPackageRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT
+                s.step_key,
+                scp.attempt,
+                scp.package_slot,
+                scp.package_json,
+                scp.package_hash
+             FROM step_context_packages scp
+             INNER JOIN steps s ON s.step_id = scp.step_id
+             WHERE scp.run_id = ?1
+             ORDER BY s.step_index ASC, scp.attempt ASC, scp.package_slot ASC",
+        )?;
+
+        let mut rows = stmt.query(params![run_id.to_string()])?;
+        let mut out = Vec::new();
+
+        while let Some(row) = rows.next()? {
+            let step_key: String = row.get(0)?;
+            let attempt_raw: i64 = row.get(1)?;
+            let package_slot_raw: i64 = row.get(2)?;
+            let package_json: String = row.get(3)?;
+            let package_hash: String = row.get(4)?;
+            let context_package: ContextPackage =
+                serde_json::from_str(&package_json).context("invalid step_context package_json")?;
+            let attempt = u32::try_from(attempt_raw).map_err(|_| anyhow!("invalid attempt"))?;
+            let package_slot =
+                usize::try_from(package_slot_raw).map_err(|_| anyhow!("invalid package_slot"))?;
+
+            out.push(StepContextPackageRecord {
+                step_key,
+                attempt,
+                envelope: ContextPackageEnvelope {
+                    package_slot,
+                    source: "trace.snapshot".to_string(),
+                    context_package,
+                    package_hash,
+                },
+            });
+        }
+
+        Ok(out)
+    }
+
+    fn get_step_provider_calls(&self, run_id: RunId) -> Result<Vec<StepProviderCallRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT
+                s.step_key, pc.provider_call_id, pc.provider_name, pc.adapter_version,
+                pc.model_id, pc.request_json, pc.request_hash, pc.response_json,
+                pc.response_hash, pc.latency_ms, pc.input_tokens, pc.output_tokens,
+                pc.started_at, pc.ended_at, pc.status, pc.error_text, pc.persona_json
+             FROM provider_calls pc
+             INNER JOIN steps s ON s.step_id = pc.step_id
+             WHERE pc.run_id = ?1
+             ORDER BY s.step_index ASC, pc.rowid ASC",
+        )?;
+
+        let mut rows = stmt.query(params![run_id.to_string()])?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next()? {
+            out.push(StepProviderCallRecord {
+                step_key: row.get(0)?,
+                provider_call: provider_call_from_row(row, 1)?,
+            });
+        }
+
+        Ok(out)
+    }
+
+    fn find_memoized_step(
+        &self,
+        workflow_hash: &str,
+        memo_key: &str,
+    ) -> Result<Option<MemoizedStepRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT
+                s.run_id, s.step_id, pc.provider_call_id, pc.provider_name, pc.adapter_version,
+                pc.model_id, pc.request_json, pc.request_hash, pc.response_json,
+                pc.response_hash, pc.latency_ms, pc.input_tokens, pc.output_tokens,
+                pc.started_at, pc.ended_at, pc.status, pc.error_text, pc.persona_json
+             FROM steps s
+             INNER JOIN runs r ON r.run_id = s.run_id
+             INNER JOIN provider_calls pc ON pc.step_id = s.step_id
+             WHERE r.workflow_hash = ?1
+               AND s.memo_key = ?2
+               AND s.status = 'succeeded'
+               AND pc.status = 'succeeded'
+               AND (SELECT COUNT(*) FROM provider_calls other WHERE other.step_id = s.step_id) = 1
+             ORDER BY r.started_at DESC, s.run_id DESC
+             LIMIT 1",
+        )?;
+        let mut rows = stmt.query(params![workflow_hash, memo_key])?;
+        let Some(row) = rows.next()? else {
+            return Ok(None);
+        };
+        Ok(Some(MemoizedStepRecord {
+            run_id: parse_run_id(&row.get::<_, String>(0)?)?,
+            step_id: parse_step_id(&row.get::<_, String>(1)?)?,
+            provider_call: provider_call_from_row(row, 2)?,
+        }))
+    }
+
+    fn get_step_gate_decisions(&self, run_id: RunId) -> Result<Vec<StepGateDecisionRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT
+                s.step_key, g.gate_kind, g.gate_name, g.subject_type, g.memory_id,
+                g.version, g.memory_version_id, g.decision, g.reason_codes_json,
+                g.notes, g.decided_by, g.decided_at, g.source_ruleset_version,
+                g.evidence_json
+             FROM step_gate_decisions g
+             INNER JOIN steps s ON s.step_id = g.step_id
+             WHERE g.run_id = ?1
+             ORDER BY s.step_index ASC, g.id ASC",
+        )?;
+
+        let mut rows = stmt.query(params![run_id.to_string()])?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next()? {
+            let reason_codes_json: String = row.get(8)?;
+            let decided_at: String = row.get(11)?;
+            out.push(StepGateDecisionRecord {
+                step_key: row.get(0)?,
+                decision: GateDecisionRecord {
+                    gate_kind: parse_gate_kind(&row.get::<_, String>(1)?)?,
+                    gate_name: row.get(2)?,
+                    subject_type: row.get(3)?,
+                    memory_id: row
+                        .get::<_, Option<String>>(4)?
+                        .map(|value| {
+                            Ulid::from_str(&value)
+                                .map(MemoryId)
+                                .map_err(|err| anyhow!("invalid memory_id ULID: {err}"))
+                        })
+                        .transpose()?,
+                    version: row
+                        .get::<_, Option<i64>>(5)?
+                        .map(|value| u32::try_from(value).map_err(|_| anyhow!("invalid version")))
+                        .transpose()?,
+                    memory_version_id: row
+                        .get::<_, Option<String>>(6)?
+                        .map(|value| {
+                            Ulid::from_str(&value)
+                                .map(MemoryVersionId)
+                                .map_err(|err| anyhow!("invalid memory_version_id ULID: {err}"))
+                        })
+                        .transpose()?,
+                    decision: parse_gate_decision(&row.get::<_, String>(7)?)?,
+                    reason_codes: serde_json::from_str(&reason_codes_json)
+                        .context("invalid reason_codes_json")?,
+                    notes: row.get(9)?,
+                    decided_by: row.get(10)?,
+                    decided_at: parse_rfc3339(&decided_at)?,
+                    source_ruleset_version: row
+                        .get::<_, Option<i64>>(12)?
+                        .map(|value| {
+                            u32::try_from(value)
+                                .map_err(|_| anyhow!("invalid source_ruleset_version"))
+                        })
+                        .transpose()?,
+                    evidence_json: row
+                        .get::<_, Option<String>>(13)?
+                        .map(|value| serde_json::from_str(&value).context("invalid evidence_json"))
+                        .transpose()?,
+                },
+            });
+        }
+
+        Ok(out)
+    }
+
+    fn get_step_proposed_writes(&self, run_id: RunId) -> Result<Vec<StepProposedWriteRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT s.step_key, w.proposal_index, w.proposal_json, w.disposition,
+                    w.disposition_reason
+             FROM proposed_memory_writes w
+             INNER JOIN steps s ON s.step_id = w.step_id
+             WHERE w.run_id = ?1
+             ORDER BY s.step_index ASC, w.id ASC",
+        )?;
+        let mut rows = stmt.query(params![run_id.to_string()])?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next()? {
+            let proposal_json: String = row.get(2)?;
+            out.push(StepProposedWriteRecord {
+                step_key: row.get(0)?,
+                proposal_index: usize::try_from(row.get::<_, i64>(1)?)
+                    .map_err(|_| anyhow!("invalid proposal_index"))?,
+                payload: serde_json::from_str(&proposal_json)
+                    .context("invalid proposed write proposal_json")?,
+                disposition: row.get(3)?,
+                disposition_reason: row.get(4)?,
+            });
+        }
+        Ok(out)
+    }
+
+    fn get_run_progress(&self, run_id: RunId) -> Result<Option<RunProgress>> {
+        let Some(run) = self.get_run(run_id)? else {
+            return Ok(None);
+        };
+        let steps = self.get_step_records(run_id)?;
+        let planned = self
+            .get_workflow_snapshot(&run.workflow_hash)?
+            .map(|snapshot| planned_step_keys(&snapshot.normalized_json))
+            .unwrap_or_default();
+        let stats = self.list_step_duration_stats(Some(&run.workflow_hash))?;
+        let historical_runs = stats
+            .iter()
+            .map(|entry| usize::try_from(entry.sample_count).unwrap_or(usize::MAX))
+            .max()
+            .unwrap_or(0);
+        let means = stats
+            .into_iter()
+            .map(|entry| (entry.step_key, entry.mean_ms))
+            .collect();
+        Ok(Some(build_run_progress(
+            &run,
+            &steps,
+            &planned,
+            &means,
+            historical_runs,
+            now_utc(),
+        )))
+    }
+
+    fn list_step_duration_stats(
+        &self,
+        workflow_hash: Option<&str>,
+    ) -> Result<Vec<StepDurationStats>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT
+                workflow_hash, step_key, sample_count, mean_ms, min_ms, max_ms,
+                stddev_ms, last_ms, updated_at
+             FROM step_duration_stats
+             WHERE ?1 IS NULL OR workflow_hash = ?1
+             ORDER BY workflow_hash ASC, step_key ASC",
+        )?;
+        let mut rows = stmt.query(params![workflow_hash])?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next()? {
+            out.push(StepDurationStats {
+                workflow_hash: row.get(0)?,
+                step_key: row.get(1)?,
+                sample_count: sql_to_u64(row.get(2)?)?,
+                mean_ms: sql_to_u64(row.get(3)?)?,
+                min_ms: sql_to_u64(row.get(4)?)?,
+                max_ms: sql_to_u64(row.get(5)?)?,
+                stddev_ms: sql_to_u64(row.get(6)?)?,
+                last_ms: sql_to_u64(row.get(7)?)?,
+                updated_at: parse_rfc3339(&row.get::<_, String>(8)?)?,
+            });
+        }
+        Ok(out)
+    }
+
+    fn record_run_usage(&self, run_id: RunId) -> Result<RunUsage> {
+        self.coordinated(|| {
+            let run = self
+                .get_run(run_id)?
+                .ok_or_else(|| anyhow!("run not found: {}", run_id.0))?;
+            let id = run_id.to_string();
+            let (provider_calls, input_tokens, output_tokens, provider_latency_ms): (
+                i64,
+                i64,
+                i64,
+                i64,
+            ) = self.conn.query_row(
+                "SELECT COUNT(*), COALESCE(SUM(input_tokens), 0), COALESCE(SUM(output_tokens), 0),
+                        COALESCE(SUM(latency_ms), 0)
+                 FROM provider_calls WHERE run_id = ?1",
+                params![id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )?;
+            let context_bytes_injected: i64 = self.conn.query_row(
+                "SELECT COALESCE(SUM(LENGTH(CAST(package_json AS BLOB))), 0)
+                 FROM step_context_packages WHERE run_id = ?1",
+                params![id],
+                |row| row.get(0),
+            )?;
+            let trace_rows_written: i64 = self.conn.query_row(
+                "SELECT 1
+                    + (SELECT COUNT(*) FROM steps WHERE run_id = ?1)
+                    + (SELECT COUNT(*) FROM trace_events WHERE run_id = ?1)
+                    + (SELECT COUNT(*) FROM step_context_packages WHERE run_id = ?1)
+                    + (SELECT COUNT(*) FROM step_context_selected sel
+                       JOIN step_context_packages pkg ON pkg.id = sel.step_context_package_id
+                       WHERE pkg.run_id = ?1)
+                    + (SELECT COUNT(*) FROM step_context_excluded exc
+                       JOIN step_context_packages pkg ON pkg.id = exc.step_context_package_id
+                       WHERE pkg.run_id = ?1)
+                    + (SELECT COUNT(*) FROM step_gate_decisions WHERE run_id = ?1)
+                    + (SELECT COUNT(*) FROM provider_calls WHERE run_id = ?1)
+                    + (SELECT COUNT(*) FROM proposed_memory_writes WHERE run_id = ?1)
+                    + (SELECT COUNT(*) FROM step_checkpoints WHERE run_id = ?1)",
+                params![id],
+                |row| row.get(0),
+            )?;
+
+            let usage = RunUsage {
+                run_id,
+                workflow_name: run.workflow_name,
+                workflow_version: run.workflow_version,
+                workflow_hash: run.workflow_hash,
+                external_correlation_id: run.external_correlation_id,
+                status: run.status,
+                provider_calls: sql_to_u64(provider_calls)?,
+                input_tokens: sql_to_u64(input_tokens)?,
+                output_tokens: sql_to_u64(output_tokens)?,
+                wall_time_ms: duration_ms(run.started_at, run.ended_at.unwrap_or_else(now_utc)),
+                provider_latency_ms: sql_to_u64(provider_latency_ms)?,
+                trace_rows_written: sql_to_u64(trace_rows_written)?,
+                context_bytes_injected: sql_to_u64(context_bytes_injected)?,
+                recorded_at: now_utc(),
+            };
+            self.conn
+                .execute(
+                    "INSERT OR REPLACE INTO run_usage(
+                        run_id, workflow_name, workflow_version, workflow_hash,
+                        external_correlation_id, status, provider_calls, input_tokens,
+                        output_tokens, wall_time_ms, provider_latency_ms, trace_rows_written,
+                        context_bytes_injected, recorded_at
+                     ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+                    params![
+                        id,
+                        usage.workflow_name,
+                        usage.workflow_version,
+                        usage.workflow_hash,
+                        usage.external_correlation_id,
+                        run_status_to_str(&usage.status),
+                        provider_calls,
+                        input_tokens,
+                        output_tokens,
+                        u64_to_sql(usage.wall_time_ms)?,
+                        provider_latency_ms,
+                        trace_rows_written,
+                        context_bytes_injected,
+                        rfc3339(usage.recorded_at)?,
+                    ],
+                )
+                .context("failed to record run usage")?;
+            Ok(usage)
+        })
+    }
+
+    fn get_run_usage(&self, run_id: RunId) -> Result<Option<RunUsage>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {RUN_USAGE_COLUMNS} FROM run_usage WHERE run_id = ?1"
+        ))?;
+        let mut rows = stmt.query(params![run_id.to_string()])?;
+        rows.next()?.map(run_usage_from_row).transpose()
+    }
+
+    fn list_run_usage(&self, workflow_name: Option<&str>) -> Result<Vec<RunUsage>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {RUN_USAGE_COLUMNS} FROM run_usage
+             WHERE ?1 IS NULL OR workflow_name = ?1
+             ORDER BY run_id ASC"
+        ))?;
+        let mut rows = stmt.query(params![workflow_name])?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next()? {
+            out.push(run_usage_from_row(row)?);
+        }
+        Ok(out)
+    }
+
+    fn reason_code_stats(&self, window: DecisionWindow) -> Result<ReasonCodeStats> {
+        let mut stmt = self.conn.prepare(
+            "SELECT r.workflow_name, g.gate_kind, g.decision, g.reason_codes_json, g.decided_at
+             FROM step_gate_decisions g
+             INNER JOIN runs r ON r.run_id = g.run_id
+             ORDER BY g.id ASC",
+        )?;
+        let mut rows = stmt.query([])?;
+        let mut decisions_evaluated = 0_u64;
+        let mut decisions_blocking = 0_u64;
+        let mut grouped: BTreeMap<(String, String, String, String), ReasonCodeStat> =
+            BTreeMap::new();
+        while let Some(row) = rows.next()? {
+            let decided_at = parse_rfc3339(&row.get::<_, String>(4)?)?;
+            if !window.contains(decided_at) {
+                continue;
+            }
+            decisions_evaluated += 1;
+            let decision = parse_gate_decision(&row.get::<_, String>(2)?)?;
+            if decision == GateDecision::Approved {
+                continue;
+            }
+            decisions_blocking += 1;
+            let workflow_name: String = row.get(0)?;
+            let gate_kind_raw: String = row.get(1)?;
+            let gate_kind = parse_gate_kind(&gate_kind_raw)?;
+            let reason_codes: Vec<String> = serde_json::from_str(&row.get::<_, String>(3)?)
+                .context("invalid reason_codes_json")?;
+            for reason_code in reason_codes {
+                let key = (
+                    workflow_name.clone(),
+                    gate_kind_raw.clone(),
+                    gate_decision_to_str(&decision).to_string(),
+                    reason_code.clone(),
+                );
+                let entry = grouped.entry(key).or_insert_with(|| ReasonCodeStat {
+                    workflow_name: workflow_name.clone(),
+                    gate_kind: gate_kind.clone(),
+                    decision: decision.clone(),
+                    reason_code,
+                    count: 0,
+                    first_decided_at: decided_at,
+                    last_decided_at: decided_at,
+                });
+                entry.count += 1;
+                entry.first_decided_at = entry.first_decided_at.min(decided_at);
+                entry.last_decided_at = entry.last_decided_at.max(decided_at);
+            }
+        }
+        let mut stats: Vec<ReasonCodeStat> = grouped.into_values().collect();
+        stats.sort_by_key(|entry| std::cmp::Reverse(entry.count));
+        Ok(ReasonCodeStats {
+            window,
+            decisions_evaluated,
+            decisions_blocking,
+            stats,
+        })
+    }
+
+    fn warning_stats(
+        &self,
+        window: DecisionWindow,
+        workflow_name: Option<&str>,
+    ) -> Result<WarningStats> {
+        let mut stmt = self.conn.prepare(
+            "SELECT r.workflow_name, json_extract(e.payload_json, '$.warning_code'),
+                    e.run_id, e.occurred_at
+             FROM trace_events e
+             INNER JOIN runs r ON r.run_id = e.run_id
+             WHERE e.event_type = ?1 AND (?2 IS NULL OR r.workflow_name = ?2)
+             ORDER BY e.event_seq ASC",
+        )?;
+        let mut rows = stmt.query(params![
+            event_type_to_str(&TraceEventType::Warning),
+            workflow_name
+        ])?;
+        let mut warnings_total = 0_u64;
+        let mut grouped: BTreeMap<(String, String), (WarningStat, BTreeSet<String>)> =
+            BTreeMap::new();
+        while let Some(row) = rows.next()? {
+            let occurred_at = parse_rfc3339(&row.get::<_, String>(3)?)?;
+            if !window.contains(occurred_at) {
+                continue;
+            }
+            warnings_total += 1;
+            let workflow_name: String = row.get(0)?;
+            let warning_code = match row.get_ref(1)? {
+                rusqlite::types::ValueRef::Text(text) => String::from_utf8_lossy(text).into_owned(),
+                _ => UNSPECIFIED_WARNING_CODE.to_string(),
+            };
+            let run_id: String = row.get(2)?;
+            let (entry, runs) = grouped
+                .entry((workflow_name.clone(), warning_code.clone()))
+                .or_insert_with(|| {
+                    (
+                        WarningStat {
+                            workflow_name,
+                            warning_code,
+                            count: 0,
+                            runs: 0,
+                            first_occurred_at: occurred_at,
+                            last_occurred_at: occurred_at,
+                        },
+                        BTreeSet::new(),
+                    )
+                });
+            entry.count += 1;
+            entry.first_occurred_at = entry.first_occurred_at.min(occurred_at);
+            entry.last_occurred_at = entry.last_occurred_at.max(occurred_at);
+            runs.insert(run_id);
+        }
+        let mut stats: Vec<WarningStat> = grouped
+            .into_values()
+            .map(|(mut entry, runs)| {
+                entry.runs = runs.len() as u64;
+                entry
+            })
+            .collect();
+        stats.sort_by_key(|entry| std::cmp::Reverse(entry.count));
+        Ok(WarningStats {
+            window,
+            warnings_total,
+            stats,
+        })
+    }
+
+    fn enqueue_deferred_gate(&self, gate: &DeferredGateRecord) -> Result<()> {
+        self.coordinated(|| {
+            self.conn
+                .execute(
+                    "INSERT INTO deferred_gates(
+                        run_id, step_id, step_key, gate_name, required, deferred_at,
+                        approvers_json, webhook_json
+                     ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![
+                        gate.run_id.to_string(),
+                        gate.step_id.to_string(),
+                        gate.step_key,
+                        gate.gate_name,
+                        bool_to_sql(gate.required),
+                        rfc3339(gate.deferred_at)?,
+                        serde_json::to_string(&gate.approvers)?,
+                        gate.webhook
+                            .as_ref()
+                            .map(serde_json::to_string)
+                            .transpose()?,
+                    ],
+                )
+                .context("failed to enqueue deferred gate")?;
+            Ok(())
+        })
+    }
+
+    fn list_deferred_gates(&self, run_id: Option<RunId>) -> Result<Vec<DeferredGateRecord>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {DEFERRED_GATE_COLUMNS} FROM deferred_gates
+             WHERE ?1 IS NULL OR run_id = ?1
+             ORDER BY deferred_at ASC, rowid ASC"
+        ))?;
+        let mut rows = stmt.query(params![run_id.map(|run_id| run_id.to_string())])?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next()? {
+            out.push(deferred_gate_from_row(row)?);
+        }
+        Ok(out)
+    }
+
+    fn resolve_deferred_gate(
+        &self,
+        run_id: RunId,
+        step_key: &str,
+        gate_name: &str,
+        resolution: &DeferredGateResolution,
+    ) -> Result<DeferredGateRecord> {
+        self.coordinated(|| {
+            let gate = self
+                .conn
+                .query_row(
+                    &format!(
+                        "SELECT {DEFERRED_GATE_COLUMNS} FROM deferred_gates
+                         WHERE run_id = ?1 AND step_key = ?2 AND gate_name = ?3"
+                    ),
+                    params![run_id.to_string(), step_key, gate_name],
+                    |row| Ok(deferred_gate_from_row(row)),
+                )
+                .optional()?
+                .transpose()?
+                .ok_or_else(|| {
+                    anyhow!("gate {gate_name} of step {step_key} in run {run_id} was not deferred")
+                })?;
+            if gate.resolution.is_some() {
+                return Err(anyhow!(
+                    "deferred gate {gate_name} of step {step_key} in run {run_id} is already resolved"
+                ));
+            }
+            gate.authorize(resolution, &self.list_approval_delegations()?)?;
+
+            self.conn
+                .execute(
+                    "UPDATE deferred_gates
+                     SET approved = ?4, decided_by = ?5, notes = ?6, decided_at = ?7,
+                         auth_method = ?8, on_behalf_of = ?9
+                     WHERE run_id = ?1 AND step_key = ?2 AND gate_name = ?3",
+                    params![
+                        run_id.to_string(),
+                        step_key,
+                        gate_name,
+                        bool_to_sql(resolution.approved),
+                        resolution.decided_by,
+                        resolution.notes,
+                        rfc3339(resolution.decided_at)?,
+                        resolution.auth_method.as_str(),
+                        resolution.on_behalf_of,
+                    ],
+                )
+                .context("failed to resolve deferred gate")?;
+            Ok(DeferredGateRecord {
+                resolution: Some(resolution.clone()),
+                ..gate
+            })
+        })
+    }
+
+    fn add_approval_delegation(&self, delegation: &ApprovalDelegation) -> Result<()> {
+        self.coordinated(|| {
+            self.conn
+                .execute(
+                    "INSERT INTO approval_delegations(
+                        delegation_id, delegator, delegate, gate_name, created_at, expires_at,
+                        revoked_at
+                     ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![
+                        delegation.delegation_id,
+                        delegation.delegator,
+                        delegation.delegate,
+                        delegation.gate_name,
+                        rfc3339(delegation.created_at)?,
+                        delegation.expires_at.map(rfc3339).transpose()?,
+                        delegation.revoked_at.map(rfc3339).transpose()?,
+                    ],
+                )
+                .context("failed to add approval delegation")?;
+            Ok(())
+        })
+    }
+
+    fn list_approval_delegations(&self) -> Result<Vec<ApprovalDelegation>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT delegation_id, delegator, delegate, gate_name, created_at, expires_at,
+                    revoked_at
+             FROM approval_delegations
+             ORDER BY created_at ASC, delegation_id ASC",
+        )?;
+        let mut rows = stmt.query([])?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next()? {
+            out.push(ApprovalDelegation {
+                delegation_id: row.get(0)?,
+                delegator: row.get(1)?,
+                delegate: row.get(2)?,
+                gate_name: row.get(3)?,
+                created_at: parse_rfc3339(&row.get::<_, String>(4)?)?,
+                expires_at: row
+                    .get::<_, Option<String>>(5)?
+                    .as_deref()
+                    .map(parse_rfc3339)
+                    .transpose()?,
+                revoked_at: row
+                    .get::<_, Option<String>>(6)?
+                    .as_deref()
+                    .map(parse_rfc3339)
+                    .transpose()?,
             });
         }
-
         Ok(out)
     }
 
-    fn get_workflow_snapshot(&self, workflow_hash: &str) -> Result<Option<WorkflowSnapshotRecord>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT
-                workflow_hash, normalization_version, source_format,
-                source_yaml_hash, normalized_json
-             FROM workflow_snapshots
-             WHERE workflow_hash = ?1",
-        )?;
+    fn revoke_approval_delegation(
+        &self,
+        delegation_id: &str,
+        revoked_at: OffsetDateTime,
+    ) -> Result<bool> {
+        self.coordinated(|| {
+            let updated = self
+                .conn
+                .execute(
+                    "UPDATE approval_delegations SET revoked_at = ?2
+                     WHERE delegation_id = ?1 AND revoked_at IS NULL",
+                    params![delegation_id, rfc3339(revoked_at)?],
+                )
+                .context("failed to revoke approval delegation")?;
+            Ok(updated > 0)
+        })
+    }
 
-        stmt.query_row(params![workflow_hash], |row| {
-            let normalized_json: String = row.get(4)?;
-            Ok((
-                row.get::<_, String>(0)?,
-                row.get::<_, i64>(1)?,
-                row.get::<_, String>(2)?,
-                row.get::<_, String>(3)?,
-                normalized_json,
-            ))
+    fn claim_load_balancing_turns(&self, pool: &str, turns: u64) -> Result<u64> {
+        let turns = i64::try_from(turns).context("load balancing turns out of range")?;
+        self.atomic(|| {
+            let first: i64 = self
+                .conn
+                .query_row(
+                    "SELECT next_turn FROM load_balancing_turns WHERE pool = ?1",
+                    params![pool],
+                    |row| row.get(0),
+                )
+                .optional()?
+                .unwrap_or(0);
+            self.conn
+                .execute(
+                    "INSERT INTO load_balancing_turns(pool, next_turn, updated_at)
+                     VALUES (?1, ?2, ?3)
+                     ON CONFLICT(pool) DO UPDATE SET
+                       next_turn = excluded.next_turn,
+                       updated_at = excluded.updated_at",
+                    params![pool, first.saturating_add(turns), rfc3339(now_utc())?],
+                )
+                .context("failed to claim load balancing turns")?;
+            Ok(u64::try_from(first).unwrap_or(0))
         })
-        .optional()?
-        .map(
-            |(
-                workflow_hash_value,
-                normalization_version_raw,
-                source_format,
-                source_yaml_hash,
-                normalized_json,
-            )| {
-                let normalization_version =
-                    u32::try_from(normalization_version_raw).map_err(|_| {
-                        anyhow!("invalid normalization_version: {normalization_version_raw}")
-                    })?;
-                Ok(WorkflowSnapshotRecord {
-                    workflow_hash: workflow_hash_value,
-                    normalization_version,
-                    source_format,
-                    source_yaml_hash,
-                    normalized_json: serde_json::from_str(&normalized_json)
-                        .context("invalid workflow_snapshots.normalized_json")?,
-                })
-            },
-        )
-        .transpose()
     }
+}
 
-    fn get_step_context_packages(&self, run_id: RunId) -> Result<Vec<StepContextPackageRecord>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT
-                s.step_key,
-                scp.package_slot,
-                scp.package_json,
-                scp.package_hash
-             FROM step_context_packages scp
-             INNER JOIN steps s ON s.step_id = scp.step_id
-             WHERE scp.run_id = ?1
-             ORDER BY s.step_index ASC, scp.package_slot ASC",
-        )?;
+const DEFERRED_GATE_COLUMNS: &str = "run_id, step_id, step_key, gate_name, required,
+    deferred_at, approved, decided_by, notes, decided_at, approvers_json, auth_method,
+    on_behalf_of, webhook_json";
 
-        let mut rows = stmt.query(params![run_id.to_string()])?;
-        let mut out = Vec::new();
+fn deferred_gate_from_row(row: &rusqlite::Row<'_>) -> Result<DeferredGateRecord> {
+    let resolution = match (
+        row.get::<_, Option<i64>>(6)?,
+        row.get::<_, Option<String>>(7)?,
+        row.get::<_, Option<String>>(9)?,
+    ) {
+        (Some(approved), Some(decided_by), Some(decided_at)) => Some(DeferredGateResolution {
+            approved: sql_to_bool(approved),
+            decided_by,
+            auth_method: row
+                .get::<_, Option<String>>(11)?
+                .as_deref()
+                .map(DeciderAuthMethod::parse)
+                .transpose()?
+                .unwrap_or_default(),
+            on_behalf_of: row.get(12)?,
+            notes: row.get(8)?,
+            decided_at: parse_rfc3339(&decided_at)?,
+        }),
+        _ => None,
+    };
+    Ok(DeferredGateRecord {
+        run_id: parse_run_id(&row.get::<_, String>(0)?)?,
+        step_id: parse_step_id(&row.get::<_, String>(1)?)?,
+        step_key: row.get(2)?,
+        gate_name: row.get(3)?,
+        required: sql_to_bool(row.get(4)?),
+        deferred_at: parse_rfc3339(&row.get::<_, String>(5)?)?,
+        approvers: serde_json::from_str(&row.get::<_, String>(10)?)
+            .context("invalid deferred gate approvers_json")?,
+        webhook: row
+            .get::<_, Option<String>>(13)?
+            .as_deref()
+            .map(serde_json::from_str)
+            .transpose()
+            .context("invalid deferred gate webhook_json")?,
+        resolution,
+    })
+}
 
-        while let Some(row) = rows.next()? {
-            let step_key: String = row.get(0)?;
-            let package_slot_raw: i64 = row.get(1)?;
-            let package_json: String = row.get(2)?;
-            let package_hash: String = row.get(3)?;
-            let context_package: ContextPackage =
-                serde_json::from_str(&package_json).context("invalid step_context package_json")?;
-            let package_slot =
-                usize::try_from(package_slot_raw).map_err(|_| anyhow!("invalid package_slot"))?;
+const RUN_COLUMNS: &str = "run_id, workflow_name, workflow_version, workflow_hash,
+    as_of, as_of_was_default, started_at, ended_at,
+    status, replay_of_run_id, external_correlation_id,
+    engine_version, cli_args_json,
+    manifest_hash, manifest_signature, manifest_signature_status,
+    rerun_of_run_id, rerun_step_key,
+    parent_run_id, parent_step_id, parent_step_key";
 
-            out.push(StepContextPackageRecord {
-                step_key,
-                envelope: ContextPackageEnvelope {
-                    package_slot,
-                    source: "trace.snapshot".to_string(),
-                    context_package,
-                    package_hash,
-                },
-            });
+fn run_from_row(row: &rusqlite::Row<'_>) -> Result<RunRecord> {
+    let replay_of_run_id: Option<String> = row.get(9)?;
+    let cli_args_json: String = row.get(12)?;
+    let rerun_of = match (
+        row.get::<_, Option<String>>(16)?,
+        row.get::<_, Option<String>>(17)?,
+    ) {
+        (Some(source_run_id), Some(step_key)) => Some(StepRerun {
+            source_run_id: parse_run_id(&source_run_id)?,
+            step_key,
+        }),
+        _ => None,
+    };
+    let parent_step = match (
+        row.get::<_, Option<String>>(18)?,
+        row.get::<_, Option<String>>(19)?,
+        row.get::<_, Option<String>>(20)?,
+    ) {
+        (Some(run_id), Some(step_id), Some(step_key)) => Some(ParentStep {
+            run_id: parse_run_id(&run_id)?,
+            step_id: parse_step_id(&step_id)?,
+            step_key,
+        }),
+        _ => None,
+    };
+    Ok(RunRecord {
+        run_id: parse_run_id(&row.get::<_, String>(0)?)?,
+        workflow_name: row.get(1)?,
+        workflow_version: row.get(2)?,
+        workflow_hash: row.get(3)?,
+        as_of: parse_rfc3339(&row.get::<_, String>(4)?)?,
+        as_of_was_default: sql_to_bool(row.get::<_, i64>(5)?),
+        started_at: parse_rfc3339(&row.get::<_, String>(6)?)?,
+        ended_at: row
+            .get::<_, Option<String>>(7)?
+            .map(|v| parse_rfc3339(&v))
+            .transpose()?,
+        status: parse_run_status(&row.get::<_, String>(8)?)?,
+        replay_of_run_id: replay_of_run_id
+            .map(|value| parse_run_id(&value))
+            .transpose()?,
+        external_correlation_id: row.get(10)?,
+        engine_version: row.get(11)?,
+        cli_args_json: serde_json::from_str(&cli_args_json).context("invalid cli_args_json")?,
+        manifest_hash: row.get(13)?,
+        manifest_signature: row.get(14)?,
+        manifest_signature_status: row.get(15)?,
+        labels: BTreeMap::new(),
+        rerun_of,
+        parent_step,
+    })
+}
+
+const RUN_USAGE_COLUMNS: &str = "run_id, workflow_name, workflow_version, workflow_hash,
+    external_correlation_id, status, provider_calls, input_tokens, output_tokens, wall_time_ms,
+    provider_latency_ms, trace_rows_written, context_bytes_injected, recorded_at";
+
+fn run_usage_from_row(row: &rusqlite::Row<'_>) -> Result<RunUsage> {
+    Ok(RunUsage {
+        run_id: parse_run_id(&row.get::<_, String>(0)?)?,
+        workflow_name: row.get(1)?,
+        workflow_version: row.get(2)?,
+        workflow_hash: row.get(3)?,
+        external_correlation_id: row.get(4)?,
+        status: parse_run_status(&row.get::<_, String>(5)?)?,
+        provider_calls: sql_to_u64(row.get(6)?)?,
+        input_tokens: sql_to_u64(row.get(7)?)?,
+        output_tokens: sql_to_u64(row.get(8)?)?,
+        wall_time_ms: sql_to_u64(row.get(9)?)?,
+        provider_latency_ms: sql_to_u64(row.get(10)?)?,
+        trace_rows_written: sql_to_u64(row.get(11)?)?,
+        context_bytes_injected: sql_to_u64(row.get(12)?)?,
+        recorded_at: parse_rfc3339(&row.get::<_, String>(13)?)?,
+    })
+}
+
+fn planned_step_keys(normalized_json: &serde_json::Value) -> Vec<String> {
+    normalized_json
+        .get("steps")
+        .and_then(serde_json::Value::as_array)
+        .map(|steps| {
+            steps
+                .iter()
+                .filter_map(|step| step.get("step_key").and_then(serde_json::Value::as_str))
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn table_exists(conn: &Connection, table: &str) -> Result<bool> {
+    let found: Option<i64> = conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            params![table],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(found.is_some())
+}
+
+fn u64_to_sql(value: u64) -> Result<i64> {
+    i64::try_from(value).map_err(|_| anyhow!("value out of range for sqlite integer: {value}"))
+}
+
+fn sql_to_u64(value: i64) -> Result<u64> {
+    u64::try_from(value).map_err(|_| anyhow!("negative value where unsigned expected: {value}"))
+}
+
+/// `(count, mean, min, max, population stddev, last)` in milliseconds; `durations` must be
+/// non-empty and ordered oldest first.
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn summarize_durations(durations: &[u64]) -> (u64, u64, u64, u64, u64, u64) {
+    let count = durations.len() as u64;
+    let total: u128 = durations.iter().map(|value| u128::from(*value)).sum();
+    let mean = u64::try_from(total / u128::from(count.max(1))).unwrap_or(u64::MAX);
+    let min = durations.iter().copied().min().unwrap_or(0);
+    let max = durations.iter().copied().max().unwrap_or(0);
+    let mean_f = total as f64 / count.max(1) as f64;
+    let variance = durations
+        .iter()
+        .map(|value| {
+            let delta = *value as f64 - mean_f;
+            delta * delta
+        })
+        .sum::<f64>()
+        / count.max(1) as f64;
+    let stddev = variance.sqrt().round() as u64;
+    let last = durations.last().copied().unwrap_or(0);
+    (count, mean, min, max, stddev, last)
+}
+
+fn duration_ms(start: OffsetDateTime, end: OffsetDateTime) -> u64 {
+    u64::try_from((end - start).whole_milliseconds()).unwrap_or(0)
+}
+
+fn build_run_progress(
+    run: &RunRecord,
+    steps: &[StepRecord],
+    planned: &[String],
+    means: &BTreeMap<String, u64>,
+    historical_runs: usize,
+    now: OffsetDateTime,
+) -> RunProgress {
+    let mut keys = planned.to_vec();
+    for step in steps {
+        if !keys.contains(&step.step_key) {
+            keys.push(step.step_key.clone());
         }
+    }
 
-        Ok(out)
+    let step_progress = keys
+        .into_iter()
+        .map(|step_key| {
+            let record = steps.iter().find(|step| step.step_key == step_key);
+            let status = record.map_or(StepStatus::Pending, |step| step.status.clone());
+            let started_at = record.and_then(|step| step.started_at);
+            let ended_at = record.and_then(|step| step.ended_at);
+            let elapsed_ms = started_at.map(|start| duration_ms(start, ended_at.unwrap_or(now)));
+            let historical_mean_ms = means.get(&step_key).copied();
+            StepProgress {
+                step_key,
+                status,
+                started_at,
+                ended_at,
+                elapsed_ms,
+                historical_mean_ms,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let is_finished = |status: &StepStatus| {
+        matches!(
+            status,
+            StepStatus::Succeeded | StepStatus::Failed | StepStatus::Rejected | StepStatus::Skipped
+        )
+    };
+    let finished_steps = step_progress
+        .iter()
+        .filter(|step| is_finished(&step.status))
+        .count();
+    let running_steps = step_progress
+        .iter()
+        .filter(|step| step.status == StepStatus::Running)
+        .count();
+    let pending_steps = step_progress.len() - finished_steps - running_steps;
+
+    let eta_ms = if matches!(run.status, RunStatus::Pending | RunStatus::Running) {
+        step_progress
+            .iter()
+            .filter(|step| !is_finished(&step.status))
+            .try_fold(0_u64, |total, step| {
+                let mean = step.historical_mean_ms?;
+                let remaining = mean.saturating_sub(step.elapsed_ms.unwrap_or(0));
+                Some(total.saturating_add(remaining))
+            })
+    } else {
+        Some(0)
+    };
+
+    RunProgress {
+        run_id: run.run_id,
+        workflow_hash: run.workflow_hash.clone(),
+        status: run.status.clone(),
+        started_at: run.started_at,
+        elapsed_ms: duration_ms(run.started_at, run.ended_at.unwrap_or(now)),
+        total_steps: step_progress.len(),
+        finished_steps,
+        running_steps,
+        pending_steps,
+        historical_runs,
+        eta_ms,
+        steps: step_progress,
+        generated_at: now,
     }
 }
 
+fn prompt_template_from_row(
+    (template_name, template_version, body, template_hash, created_at): (
+        String,
+        String,
+        String,
+        String,
+        String,
+    ),
+) -> Result<PromptTemplateRecord> {
+    Ok(PromptTemplateRecord {
+        template_name,
+        template_version,
+        body,
+        template_hash,
+        created_at: parse_rfc3339(&created_at)?,
+    })
+}
+
+fn writer_lock_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".writer-lock");
+    PathBuf::from(name)
+}
+
 fn ensure_column(conn: &Connection, table: &str, column: &str, sql_type: &str) -> Result<()> {
     if table_has_column(conn, table, column)? {
         return Ok(());
@@ -1104,6 +2905,8 @@ fn parse_run_status(value: &str) -> Result<RunStatus> {
         "succeeded" => Ok(RunStatus::Succeeded),
         "failed" => Ok(RunStatus::Failed),
         "rejected" => Ok(RunStatus::Rejected),
+        "partially_completed" => Ok(RunStatus::PartiallyCompleted),
+        "cancelled" => Ok(RunStatus::Cancelled),
         _ => Err(anyhow!("unknown run status: {value}")),
     }
 }
@@ -1115,9 +2918,60 @@ fn run_status_to_str(status: &RunStatus) -> &'static str {
         RunStatus::Succeeded => "succeeded",
         RunStatus::Failed => "failed",
         RunStatus::Rejected => "rejected",
+        RunStatus::PartiallyCompleted => "partially_completed",
+        RunStatus::Cancelled => "cancelled",
     }
 }
 
+/// Provider call columns (`provider_call_id` through `persona_json`) starting at `offset`.
+fn provider_call_from_row(row: &rusqlite::Row<'_>, offset: usize) -> Result<ProviderCallRecord> {
+    let provider_call_id: String = row.get(offset)?;
+    let request_json: String = row.get(offset + 4)?;
+    let response_json: Option<String> = row.get(offset + 6)?;
+    let started_at: String = row.get(offset + 11)?;
+    Ok(ProviderCallRecord {
+        provider_call_id: Ulid::from_str(&provider_call_id)
+            .map_err(|err| anyhow!("invalid provider_call_id ULID: {err}"))?,
+        provider_name: row.get(offset + 1)?,
+        adapter_version: row.get(offset + 2)?,
+        model_id: row.get(offset + 3)?,
+        request_json: serde_json::from_str(&request_json)
+            .context("invalid provider request_json")?,
+        request_hash: row.get(offset + 5)?,
+        response_json: response_json
+            .map(|value| serde_json::from_str(&value).context("invalid provider response_json"))
+            .transpose()?
+            .unwrap_or(serde_json::Value::Null),
+        response_hash: row
+            .get::<_, Option<String>>(offset + 7)?
+            .unwrap_or_default(),
+        latency_ms: row
+            .get::<_, Option<i64>>(offset + 8)?
+            .map(sql_to_u64)
+            .transpose()?,
+        input_tokens: row
+            .get::<_, Option<i64>>(offset + 9)?
+            .map(|value| u32::try_from(value).map_err(|_| anyhow!("invalid input_tokens")))
+            .transpose()?,
+        output_tokens: row
+            .get::<_, Option<i64>>(offset + 10)?
+            .map(|value| u32::try_from(value).map_err(|_| anyhow!("invalid output_tokens")))
+            .transpose()?,
+        started_at: parse_rfc3339(&started_at)?,
+        ended_at: parse_rfc3339(
+            row.get::<_, Option<String>>(offset + 12)?
+                .as_deref()
+                .unwrap_or(&started_at),
+        )?,
+        status: row.get(offset + 13)?,
+        error_text: row.get(offset + 14)?,
+        persona_json: row
+            .get::<_, Option<String>>(offset + 15)?
+            .map(|value| serde_json::from_str(&value).context("invalid persona_json"))
+            .transpose()?,
+    })
+}
+
 fn parse_step_status(value: &str) -> Result<StepStatus> {
     match value {
         "pending" => Ok(StepStatus::Pending),
@@ -1126,6 +2980,9 @@ fn parse_step_status(value: &str) -> Result<StepStatus> {
         "failed" => Ok(StepStatus::Failed),
         "rejected" => Ok(StepStatus::Rejected),
         "skipped" => Ok(StepStatus::Skipped),
+        "deferred" => Ok(StepStatus::Deferred),
+        "timed_out" => Ok(StepStatus::TimedOut),
+        "cancelled" => Ok(StepStatus::Cancelled),
         _ => Err(anyhow!("unknown step status: {value}")),
     }
 }
@@ -1138,6 +2995,9 @@ fn step_status_to_str(status: &StepStatus) -> &'static str {
         StepStatus::Failed => "failed",
         StepStatus::Rejected => "rejected",
         StepStatus::Skipped => "skipped",
+        StepStatus::Deferred => "deferred",
+        StepStatus::TimedOut => "timed_out",
+        StepStatus::Cancelled => "cancelled",
     }
 }
 
@@ -1145,6 +3005,15 @@ fn parse_event_type(value: &str) -> Result<TraceEventType> {
     match value {
         "run_started" => Ok(TraceEventType::RunStarted),
         "run_finished" => Ok(TraceEventType::RunFinished),
+        "run_resumed" => Ok(TraceEventType::RunResumed),
+        "condition_evaluated" => Ok(TraceEventType::ConditionEvaluated),
+        "foreach_expanded" => Ok(TraceEventType::ForeachExpanded),
+        "iteration_started" => Ok(TraceEventType::IterationStarted),
+        "iteration_evaluated" => Ok(TraceEventType::IterationEvaluated),
+        "workflow_call_started" => Ok(TraceEventType::WorkflowCallStarted),
+        "workflow_call_finished" => Ok(TraceEventType::WorkflowCallFinished),
+        "vote_expanded" => Ok(TraceEventType::VoteExpanded),
+        "vote_decided" => Ok(TraceEventType::VoteDecided),
         "workflow_normalized" => Ok(TraceEventType::WorkflowNormalized),
         "step_ready" => Ok(TraceEventType::StepReady),
         "step_started" => Ok(TraceEventType::StepStarted),
@@ -1156,6 +3025,7 @@ fn parse_event_type(value: &str) -> Result<TraceEventType> {
         "proposed_memory_write" => Ok(TraceEventType::ProposedMemoryWrite),
         "replay_started" => Ok(TraceEventType::ReplayStarted),
         "replay_finished" => Ok(TraceEventType::ReplayFinished),
+        "step_scored" => Ok(TraceEventType::StepScored),
         "warning" => Ok(TraceEventType::Warning),
         "error" => Ok(TraceEventType::Error),
         _ => Err(anyhow!("unknown event_type: {value}")),
@@ -1166,6 +3036,15 @@ fn event_type_to_str(value: &TraceEventType) -> &'static str {
     match value {
         TraceEventType::RunStarted => "run_started",
         TraceEventType::RunFinished => "run_finished",
+        TraceEventType::RunResumed => "run_resumed",
+        TraceEventType::ConditionEvaluated => "condition_evaluated",
+        TraceEventType::ForeachExpanded => "foreach_expanded",
+        TraceEventType::IterationStarted => "iteration_started",
+        TraceEventType::IterationEvaluated => "iteration_evaluated",
+        TraceEventType::WorkflowCallStarted => "workflow_call_started",
+        TraceEventType::WorkflowCallFinished => "workflow_call_finished",
+        TraceEventType::VoteExpanded => "vote_expanded",
+        TraceEventType::VoteDecided => "vote_decided",
         TraceEventType::WorkflowNormalized => "workflow_normalized",
         TraceEventType::StepReady => "step_ready",
         TraceEventType::StepStarted => "step_started",
@@ -1177,6 +3056,7 @@ fn event_type_to_str(value: &TraceEventType) -> &'static str {
         TraceEventType::ProposedMemoryWrite => "proposed_memory_write",
         TraceEventType::ReplayStarted => "replay_started",
         TraceEventType::ReplayFinished => "replay_finished",
+        TraceEventType::StepScored => "step_scored",
         TraceEventType::Warning => "warning",
         TraceEventType::Error => "error",
     }
@@ -1190,6 +3070,24 @@ fn gate_kind_to_str(value: &GateKind) -> &'static str {
     }
 }
 
+fn parse_gate_kind(value: &str) -> Result<GateKind> {
+    match value {
+        "human" => Ok(GateKind::Human),
+        "trust" => Ok(GateKind::Trust),
+        "policy" => Ok(GateKind::Policy),
+        other => Err(anyhow!("invalid gate_kind: {other}")),
+    }
+}
+
+fn parse_gate_decision(value: &str) -> Result<GateDecision> {
+    match value {
+        "approved" => Ok(GateDecision::Approved),
+        "rejected" => Ok(GateDecision::Rejected),
+        "pruned" => Ok(GateDecision::Pruned),
+        other => Err(anyhow!("invalid gate decision: {other}")),
+    }
+}
+
 fn gate_decision_to_str(value: &GateDecision) -> &'static str {
     match value {
         GateDecision::Approved => "approved",
@@ -1216,6 +3114,35 @@ fn sql_to_bool(value: i64) -> bool {
     value != 0
 }
 
+const EVENT_COLUMNS: &str = "event_seq, event_id, run_id, step_id, event_type,
+    occurred_at, recorded_at, actor_type, actor_id,
+    payload_json, payload_hash, prev_event_hash, event_hash";
+
+fn event_row_from_row(row: &rusqlite::Row<'_>) -> Result<EventRow> {
+    let event_id_raw: String = row.get(1)?;
+    let run_id_raw: String = row.get(2)?;
+    let step_id_raw: Option<String> = row.get(3)?;
+    let payload_raw: String = row.get(9)?;
+    Ok(EventRow {
+        event_seq: row.get(0)?,
+        event: TraceEvent {
+            event_id: Ulid::from_str(&event_id_raw)
+                .map_err(|err| anyhow!("invalid event_id ULID: {err}"))?,
+            run_id: parse_run_id(&run_id_raw)?,
+            step_id: step_id_raw.map(|value| parse_step_id(&value)).transpose()?,
+            event_type: parse_event_type(&row.get::<_, String>(4)?)?,
+            occurred_at: parse_rfc3339(&row.get::<_, String>(5)?)?,
+            recorded_at: parse_rfc3339(&row.get::<_, String>(6)?)?,
+            actor_type: row.get(7)?,
+            actor_id: row.get(8)?,
+            payload_json: serde_json::from_str(&payload_raw).context("invalid payload_json")?,
+            payload_hash: row.get(10)?,
+            prev_event_hash: row.get(11)?,
+            event_hash: row.get(12)?,
+        },
+    })
+}
+
 fn rfc3339(value: OffsetDateTime) -> Result<String> {
     value
         .format(&time::format_description::well_known::Rfc3339)
@@ -1229,14 +3156,18 @@ fn parse_rfc3339(value: &str) -> Result<OffsetDateTime> {
 
 #[cfg(test)]
 mod tests {
-    use super::SqliteTraceStore;
+    use std::collections::BTreeSet;
+
+    use super::{BufferedSqliteTraceStore, SqliteTraceStore};
     use memory_kernel_core::{
         Answer, AnswerResult, Authority, ContextItem, ContextPackage, DeterminismMetadata,
         MemoryId, MemoryVersionId, QueryRequest, RecordType, TruthStatus, Why,
     };
     use multi_agent_center_domain::{
-        ContextPackageEnvelope, GateDecision, GateDecisionRecord, GateKind, RunId, RunRecord,
-        RunStatus, StepId, StepRecord, StepStatus, TraceEvent, TraceEventType,
+        ApprovalDelegation, ContextPackageEnvelope, DeciderAuthMethod, DeferredGateRecord,
+        DeferredGateResolution, GateDecision, GateDecisionRecord, GateKind, GateWebhook,
+        PromptTemplateRecord, RunId, RunRecord, RunStatus, StepCheckpoint, StepId, StepRecord,
+        StepStatus, TraceArtifactClass, TraceEvent, TraceEventType, UNSPECIFIED_WARNING_CODE,
     };
     use multi_agent_center_trace_core::TraceStore;
     use rusqlite::{params, Connection};
@@ -1270,6 +3201,9 @@ mod tests {
             manifest_hash: None,
             manifest_signature: None,
             manifest_signature_status: "unsigned".to_string(),
+            labels: std::collections::BTreeMap::new(),
+            rerun_of: None,
+            parent_step: None,
         }
     }
 
@@ -1289,6 +3223,12 @@ mod tests {
             input_hash: "input-hash".to_string(),
             output_hash: None,
             error_json: None,
+            retry_json: None,
+            prompt_template_hash: None,
+            provider_selection_json: None,
+            taint_json: None,
+            memoized_from_run: None,
+            memo_key: None,
         }
     }
 
@@ -1303,6 +3243,7 @@ mod tests {
             truth_status: TruthStatus::Asserted,
             confidence: Some(0.9),
             authority: Authority::Authoritative,
+            tags: Vec::new(),
             why: Why {
                 included: true,
                 reasons: vec!["fixture".to_string()],
@@ -1357,6 +3298,7 @@ mod tests {
         let mut rows = stmt.query([]).unwrap_or_else(|_| unreachable!());
         let mut found_manifest_hash = false;
         let mut found_manifest_signature_status = false;
+        let mut found_manifest_json = false;
         while let Some(row) = rows.next().unwrap_or_else(|_| unreachable!()) {
             let col_name: String = row.get(1).unwrap_or_else(|_| unreachable!());
             if col_name == "manifest_hash" {
@@ -1365,9 +3307,237 @@ mod tests {
             if col_name == "manifest_signature_status" {
                 found_manifest_signature_status = true;
             }
+            if col_name == "manifest_json" {
+                found_manifest_json = true;
+            }
         }
         assert!(found_manifest_hash);
         assert!(found_manifest_signature_status);
+        assert!(found_manifest_json);
+    }
+
+    #[test]
+    fn deferred_gate_resolution_requires_verified_approver_or_active_delegation() {
+        let path = temp_db_path("gate-approvers");
+        let store = SqliteTraceStore::open(&path).unwrap_or_else(|_| unreachable!());
+        assert!(store.migrate().is_ok());
+        assert!(store
+            .upsert_workflow_snapshot("hash", 1, "yaml", "yaml-hash", &json!({"x":1}))
+            .is_ok());
+        let run_id = RunId::new();
+        let step_id = StepId::new();
+        assert!(store.insert_run(&fixture_run(run_id)).is_ok());
+        assert!(store.insert_step(&fixture_step(run_id, step_id)).is_ok());
+        let now = time::OffsetDateTime::now_utc();
+        for gate_name in ["review", "signoff"] {
+            assert!(store
+                .enqueue_deferred_gate(&DeferredGateRecord {
+                    run_id,
+                    step_id,
+                    step_key: "step".to_string(),
+                    gate_name: gate_name.to_string(),
+                    required: true,
+                    deferred_at: now,
+                    approvers: vec!["alice@example.com".to_string()],
+                    webhook: (gate_name == "signoff").then(|| GateWebhook {
+                        url: "https://hooks.example.com/gates".to_string(),
+                        secret_env: "SIGNOFF_GATE_SECRET".to_string(),
+                    }),
+                    resolution: None,
+                })
+                .is_ok());
+        }
+        let resolution =
+            |decided_by: &str, auth_method, on_behalf_of: Option<&str>| DeferredGateResolution {
+                approved: true,
+                decided_by: decided_by.to_string(),
+                auth_method,
+                on_behalf_of: on_behalf_of.map(str::to_string),
+                notes: None,
+                decided_at: time::OffsetDateTime::now_utc(),
+            };
+
+        let unverified = resolution("alice@example.com", DeciderAuthMethod::Unverified, None);
+        let err = store.resolve_deferred_gate(run_id, "step", "review", &unverified);
+        assert!(err.is_err_and(|err| err.to_string().contains("verified approvers")));
+        let outsider = resolution("mallory@example.com", DeciderAuthMethod::Sso, None);
+        let err = store.resolve_deferred_gate(run_id, "step", "review", &outsider);
+        assert!(err.is_err_and(|err| err.to_string().contains("is not an approver")));
+        let proxy = resolution("bob", DeciderAuthMethod::Token, Some("alice@example.com"));
+        let err = store.resolve_deferred_gate(run_id, "step", "review", &proxy);
+        assert!(err.is_err_and(|err| err.to_string().contains("no active delegation")));
+
+        let delegation = ApprovalDelegation {
+            delegation_id: "delegation-1".to_string(),
+            delegator: "Alice@example.com".to_string(),
+            delegate: "bob".to_string(),
+            gate_name: Some("review".to_string()),
+            created_at: now,
+            expires_at: None,
+            revoked_at: None,
+        };
+        assert!(store.add_approval_delegation(&delegation).is_ok());
+        let resolved = store
+            .resolve_deferred_gate(run_id, "step", "review", &proxy)
+            .unwrap_or_else(|err| panic!("delegated resolution failed: {err:#}"));
+        assert_eq!(resolved.resolution.as_ref(), Some(&proxy));
+        let pending = store
+            .list_deferred_gates(Some(run_id))
+            .unwrap_or_else(|_| unreachable!());
+        assert!(pending
+            .iter()
+            .any(|gate| gate.gate_name == "review" && gate.resolution.as_ref() == Some(&proxy)));
+        assert!(pending.iter().any(|gate| gate.gate_name == "signoff"
+            && gate
+                .webhook
+                .as_ref()
+                .is_some_and(|webhook| webhook.secret_env == "SIGNOFF_GATE_SECRET")));
+
+        // The delegation is scoped to `review`, and a revoked delegation covers nothing.
+        let err = store.resolve_deferred_gate(run_id, "step", "signoff", &proxy);
+        assert!(err.is_err_and(|err| err.to_string().contains("no active delegation")));
+        assert!(matches!(
+            store.revoke_approval_delegation("delegation-1", now),
+            Ok(true)
+        ));
+        assert!(matches!(
+            store.revoke_approval_delegation("delegation-1", now),
+            Ok(false)
+        ));
+        let delegations = store
+            .list_approval_delegations()
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(delegations.len(), 1);
+        assert_eq!(delegations[0].revoked_at, Some(now));
+
+        let approver = resolution("ALICE@example.com", DeciderAuthMethod::Sso, None);
+        assert!(store
+            .resolve_deferred_gate(run_id, "step", "signoff", &approver)
+            .is_ok());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn migrate_widens_legacy_status_constraints_and_keeps_rows() {
+        let path = temp_db_path("legacy-status");
+        let store = SqliteTraceStore::open(&path).unwrap_or_else(|_| unreachable!());
+        let mut legacy_schema = super::SCHEMA_V2.to_string();
+        for (_, legacy_checks, check) in super::LEGACY_STATUS_CHECKS {
+            legacy_schema = legacy_schema.replace(check, legacy_checks[0]);
+        }
+        assert!(store.conn.execute_batch(&legacy_schema).is_ok());
+        assert!(store
+            .conn
+            .execute(
+                "INSERT INTO workflow_snapshots VALUES ('hash', 1, 'yaml', 'yaml-hash', '{}', 'now')",
+                [],
+            )
+            .is_ok());
+        let run_id = RunId::new();
+        let insert_run = |status: &str| {
+            store.conn.execute(
+                "INSERT INTO runs (run_id, workflow_name, workflow_version, workflow_hash, as_of,
+                   as_of_was_default, started_at, status, engine_version, cli_args_json)
+                 VALUES (?1, 'wf', 'v1', 'hash', '2026-01-01T00:00:00Z', 1,
+                   '2026-01-01T00:00:00Z', ?2, 'test', '{}')",
+                params![run_id.to_string(), status],
+            )
+        };
+        assert!(insert_run("partially_completed").is_err());
+        assert!(insert_run("succeeded").is_ok());
+
+        assert!(store.migrate().is_ok());
+        assert!(store.migrate().is_ok());
+
+        let run = store.get_run(run_id).unwrap_or_else(|_| unreachable!());
+        assert_eq!(run.map(|run| run.status), Some(RunStatus::Succeeded));
+        assert!(store
+            .conn
+            .execute(
+                "UPDATE runs SET status = 'partially_completed' WHERE run_id = ?1",
+                params![run_id.to_string()],
+            )
+            .is_ok());
+        let mut step = fixture_step(run_id, StepId::new());
+        step.status = StepStatus::Deferred;
+        assert!(store.insert_step(&step).is_ok());
+        let steps = store
+            .get_step_records(run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(steps[0].status, StepStatus::Deferred);
+        assert!(store.integrity_ping().is_ok_and(|issues| issues.is_empty()));
+    }
+
+    #[test]
+    fn migrate_keys_legacy_context_packages_by_attempt_and_keeps_rows() {
+        let path = temp_db_path("legacy-context-packages");
+        let store = SqliteTraceStore::open(&path).unwrap_or_else(|_| unreachable!());
+        let (legacy_key, key) = super::LEGACY_CONTEXT_PACKAGE_KEY;
+        let legacy_schema = super::SCHEMA_V2.replace(key, legacy_key);
+        assert!(store.conn.execute_batch(&legacy_schema).is_ok());
+        assert!(store
+            .upsert_workflow_snapshot("hash", 1, "yaml", "yaml-hash", &json!({"x":1}))
+            .is_ok());
+        let run_id = RunId::new();
+        let step_id = StepId::new();
+        assert!(store.insert_run(&fixture_run(run_id)).is_ok());
+        assert!(store.insert_step(&fixture_step(run_id, step_id)).is_ok());
+        let package = fixture_package();
+        assert!(store
+            .conn
+            .execute(
+                "INSERT INTO step_context_packages(
+                   run_id, step_id, package_slot, context_package_id, generated_at, query_json,
+                   determinism_json, answer_json, ordering_trace_json, package_json, package_hash
+                 ) VALUES (?1, ?2, 0, 'pkg', 'now', '{}', '{}', '{}', '[]', ?3, ?4)",
+                params![
+                    run_id.to_string(),
+                    step_id.to_string(),
+                    serde_json::to_string(&package.context_package)
+                        .unwrap_or_else(|_| unreachable!()),
+                    package.package_hash,
+                ],
+            )
+            .is_ok());
+
+        assert!(store.migrate().is_ok());
+        assert!(store.migrate().is_ok());
+
+        assert!(store
+            .append_context_package(run_id, step_id, 2, &package)
+            .is_ok());
+        let records = store
+            .get_step_context_packages(run_id)
+            .unwrap_or_else(|_| unreachable!());
+        let attempts: Vec<(u32, String)> = records
+            .iter()
+            .map(|record| (record.attempt, record.envelope.package_hash.clone()))
+            .collect();
+        assert_eq!(
+            attempts,
+            vec![
+                (1, package.package_hash.clone()),
+                (2, package.package_hash.clone())
+            ]
+        );
+        assert!(store.integrity_ping().is_ok_and(|issues| issues.is_empty()));
+    }
+
+    #[test]
+    fn integrity_ping_flags_unmigrated_store() {
+        let path = temp_db_path("integrity-ping");
+        let store = SqliteTraceStore::open(&path);
+        assert!(store.is_ok());
+        let store = store.unwrap_or_else(|_| unreachable!());
+
+        let issues = store.integrity_ping().unwrap_or_else(|_| unreachable!());
+        assert!(issues
+            .iter()
+            .any(|issue| issue == "missing trace table: runs"));
+
+        assert!(store.migrate().is_ok());
+        let issues = store.integrity_ping().unwrap_or_else(|_| unreachable!());
+        assert!(issues.is_empty(), "unexpected issues: {issues:?}");
     }
 
     #[test]
@@ -1403,10 +3573,111 @@ mod tests {
         };
         assert!(store.append_event(&event).is_ok());
 
-        let mutated = store.conn.execute(
-            "UPDATE trace_events SET actor_id = 'mutated' WHERE event_seq = 1",
-            [],
+        let mutated = store.conn.execute(
+            "UPDATE trace_events SET actor_id = 'mutated' WHERE event_seq = 1",
+            [],
+        );
+        assert!(mutated.is_err());
+    }
+
+    #[test]
+    fn prompt_template_versions_are_immutable() {
+        let path = temp_db_path("prompt-templates");
+        let store = SqliteTraceStore::open(&path).unwrap_or_else(|_| unreachable!());
+        assert!(store.migrate().is_ok());
+
+        let v1 = PromptTemplateRecord::new("summarize", "v1", "Summarize {{input}}.")
+            .unwrap_or_else(|_| unreachable!());
+        let v2 = PromptTemplateRecord::new("summarize", "v2", "Summarize {{input}} briefly.")
+            .unwrap_or_else(|_| unreachable!());
+        assert!(store.put_prompt_template(&v2).is_ok());
+        assert!(store.put_prompt_template(&v1).is_ok());
+        assert!(store.put_prompt_template(&v1).is_ok());
+
+        let conflicting = PromptTemplateRecord::new("summarize", "v1", "Different body.")
+            .unwrap_or_else(|_| unreachable!());
+        assert!(store
+            .put_prompt_template(&conflicting)
+            .is_err_and(|err| err.to_string().contains("different body")));
+
+        let stored = store
+            .get_prompt_template("summarize", "v1")
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(
+            stored.as_ref().map(|t| t.template_hash.as_str()),
+            Some(v1.template_hash.as_str())
+        );
+        let listed = store
+            .list_prompt_templates()
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(
+            listed
+                .iter()
+                .map(PromptTemplateRecord::prompt_ref)
+                .collect::<Vec<_>>(),
+            vec!["summarize.v1".to_string(), "summarize.v2".to_string()]
+        );
+
+        let mutated = store
+            .conn
+            .execute("UPDATE prompt_templates SET body = 'mutated'", []);
+        assert!(mutated.is_err());
+    }
+
+    #[test]
+    fn step_checkpoints_round_trip_in_order_and_are_append_only() {
+        let path = temp_db_path("checkpoints");
+        let store = SqliteTraceStore::open(&path);
+        assert!(store.is_ok());
+        let store = store.unwrap_or_else(|_| unreachable!());
+        assert!(store.migrate().is_ok());
+        assert!(store
+            .upsert_workflow_snapshot("hash", 1, "yaml", "yaml-hash", &json!({"x":1}))
+            .is_ok());
+
+        let run_id = RunId::new();
+        let step_id = StepId::new();
+        assert!(store.insert_run(&fixture_run(run_id)).is_ok());
+        assert!(store.insert_step(&fixture_step(run_id, step_id)).is_ok());
+
+        let mut prev_checkpoint_hash = None;
+        for seq in [1_u32, 0] {
+            let state_json = json!({ "turn_index": seq });
+            let state_hash = multi_agent_center_domain::compute_checkpoint_hash(
+                prev_checkpoint_hash.as_deref(),
+                &state_json,
+            )
+            .unwrap_or_else(|_| unreachable!());
+            let checkpoint = StepCheckpoint {
+                run_id,
+                step_id,
+                checkpoint_seq: seq,
+                provider_call_id: Some(Ulid::new()),
+                state_json,
+                prev_checkpoint_hash: prev_checkpoint_hash.clone(),
+                state_hash: state_hash.clone(),
+                created_at: time::OffsetDateTime::now_utc(),
+            };
+            assert!(store.append_step_checkpoint(&checkpoint).is_ok());
+            assert!(store.append_step_checkpoint(&checkpoint).is_err());
+            prev_checkpoint_hash = Some(state_hash);
+        }
+
+        let checkpoints = store
+            .get_step_checkpoints(step_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(
+            checkpoints
+                .iter()
+                .map(|checkpoint| checkpoint.checkpoint_seq)
+                .collect::<Vec<_>>(),
+            vec![0, 1]
         );
+        assert_eq!(checkpoints[0].state_json, json!({ "turn_index": 0 }));
+
+        let mutated = store
+            .conn
+            .execute("UPDATE step_checkpoints SET state_hash = 'mutated'", []);
         assert!(mutated.is_err());
     }
 
@@ -1428,7 +3699,7 @@ mod tests {
 
         let package = fixture_package();
         assert!(store
-            .append_context_package(run_id, step_id, &package)
+            .append_context_package(run_id, step_id, 1, &package)
             .is_ok());
         assert!(store
             .append_gate_decision(
@@ -1459,7 +3730,23 @@ mod tests {
         let records = records.unwrap_or_else(|_| unreachable!());
         assert_eq!(records.len(), 1);
         assert_eq!(records[0].step_key, "step");
+        assert_eq!(records[0].attempt, 1);
         assert_eq!(records[0].envelope.package_slot, 0);
+
+        assert!(store
+            .append_context_package(run_id, step_id, 1, &package)
+            .is_err());
+        assert!(store
+            .append_context_package(run_id, step_id, 2, &package)
+            .is_ok());
+        let records = store.get_step_context_packages(run_id);
+        assert!(records.is_ok());
+        let attempts: Vec<u32> = records
+            .unwrap_or_else(|_| unreachable!())
+            .iter()
+            .map(|record| record.attempt)
+            .collect();
+        assert_eq!(attempts, vec![1, 2]);
     }
 
     #[test]
@@ -1578,4 +3865,955 @@ mod tests {
         );
         assert!(insert_invalid.is_err());
     }
+
+    #[test]
+    fn run_progress_reports_remaining_steps_and_eta_from_history() {
+        let path = temp_db_path("run-progress");
+        let store = SqliteTraceStore::open(&path).unwrap_or_else(|_| unreachable!());
+        assert!(store.migrate().is_ok());
+        let workflow = json!({"steps": [{"step_key": "a"}, {"step_key": "b"}, {"step_key": "c"}]});
+        assert!(store
+            .upsert_workflow_snapshot("hash", 1, "yaml", "yaml-hash", &workflow)
+            .is_ok());
+
+        let now = time::OffsetDateTime::now_utc();
+        let seconds = time::Duration::seconds;
+        let step =
+            |run_id: RunId, index: usize, key: &str, status: StepStatus, start, end| StepRecord {
+                step_index: index,
+                step_key: key.to_string(),
+                status,
+                started_at: Some(start),
+                ended_at: end,
+                ..fixture_step(run_id, StepId::new())
+            };
+
+        let prior_run_id = RunId::new();
+        assert!(store.insert_run(&fixture_run(prior_run_id)).is_ok());
+        let base = now - seconds(60);
+        for (index, (key, secs)) in [("a", 2), ("b", 3), ("c", 1)].into_iter().enumerate() {
+            let record = step(
+                prior_run_id,
+                index,
+                key,
+                StepStatus::Succeeded,
+                base,
+                Some(base + seconds(secs)),
+            );
+            assert!(store.insert_step(&record).is_ok());
+        }
+
+        assert!(store
+            .update_run_finished(prior_run_id, RunStatus::Succeeded)
+            .is_ok());
+
+        let run_id = RunId::new();
+        assert!(store.insert_run(&fixture_run(run_id)).is_ok());
+        let done = step(
+            run_id,
+            0,
+            "a",
+            StepStatus::Succeeded,
+            now - seconds(3),
+            Some(now - seconds(1)),
+        );
+        let running = step(run_id, 1, "b", StepStatus::Running, now - seconds(1), None);
+        assert!(store.insert_step(&done).is_ok());
+        assert!(store.insert_step(&running).is_ok());
+
+        let progress = store
+            .get_run_progress(run_id)
+            .unwrap_or_else(|_| unreachable!());
+        let Some(progress) = progress else {
+            panic!("run progress should exist")
+        };
+        assert_eq!(progress.total_steps, 3);
+        assert_eq!(
+            (
+                progress.finished_steps,
+                progress.running_steps,
+                progress.pending_steps
+            ),
+            (1, 1, 1)
+        );
+        assert_eq!(progress.historical_runs, 1);
+        assert_eq!(progress.steps[1].historical_mean_ms, Some(3_000));
+        assert_eq!(progress.steps[2].status, StepStatus::Pending);
+        let eta = progress.eta_ms.unwrap_or_else(|| unreachable!());
+        assert!((2_500..=3_000).contains(&eta), "unexpected eta: {eta}");
+
+        assert!(matches!(store.get_run_progress(RunId::new()), Ok(None)));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn load_balancing_turns_are_claimed_per_pool_in_sequence() {
+        let store = SqliteTraceStore::open(&temp_db_path("load-balancing-turns"))
+            .unwrap_or_else(|_| unreachable!());
+        assert!(store.migrate().is_ok());
+        let claims: Vec<u64> = [
+            ("planner", 3),
+            ("planner", 2),
+            ("reviewer", 1),
+            ("planner", 1),
+        ]
+        .into_iter()
+        .map(|(pool, turns)| {
+            store
+                .claim_load_balancing_turns(pool, turns)
+                .unwrap_or_else(|_| unreachable!())
+        })
+        .collect();
+        assert_eq!(claims, vec![0, 3, 0, 5]);
+    }
+
+    #[test]
+    fn step_duration_stats_refresh_idempotently_after_succeeded_runs() {
+        let path = temp_db_path("duration-stats");
+        let store = SqliteTraceStore::open(&path).unwrap_or_else(|_| unreachable!());
+        assert!(store.migrate().is_ok());
+        assert!(store
+            .upsert_workflow_snapshot("hash", 1, "yaml", "yaml-hash", &json!({"x":1}))
+            .is_ok());
+
+        let base = time::OffsetDateTime::now_utc() - time::Duration::minutes(5);
+        for (offset, millis, status) in [
+            (0, 1_000, RunStatus::Succeeded),
+            (1, 3_000, RunStatus::Succeeded),
+            (2, 50_000, RunStatus::Failed),
+        ] {
+            let run_id = RunId::new();
+            assert!(store.insert_run(&fixture_run(run_id)).is_ok());
+            let started_at = base + time::Duration::seconds(offset * 60);
+            let step = StepRecord {
+                status: StepStatus::Succeeded,
+                started_at: Some(started_at),
+                ended_at: Some(started_at + time::Duration::milliseconds(millis)),
+                ..fixture_step(run_id, StepId::new())
+            };
+            assert!(store.insert_step(&step).is_ok());
+            assert!(store.update_run_finished(run_id, status.clone()).is_ok());
+            assert!(store.update_run_finished(run_id, status).is_ok());
+        }
+
+        let stats = store
+            .list_step_duration_stats(Some("hash"))
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].step_key, "step");
+        assert_eq!(stats[0].sample_count, 2);
+        assert_eq!(
+            (stats[0].mean_ms, stats[0].min_ms, stats[0].max_ms),
+            (2_000, 1_000, 3_000)
+        );
+        assert_eq!(stats[0].stddev_ms, 1_000);
+        assert_eq!(stats[0].last_ms, 3_000);
+        assert!(store
+            .list_step_duration_stats(Some("other"))
+            .unwrap_or_else(|_| unreachable!())
+            .is_empty());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn run_usage_aggregates_tokens_latency_and_rows_per_run() {
+        let path = temp_db_path("run-usage");
+        let store = SqliteTraceStore::open(&path).unwrap_or_else(|_| unreachable!());
+        assert!(store.migrate().is_ok());
+        assert!(store
+            .upsert_workflow_snapshot("hash", 1, "yaml", "yaml-hash", &json!({"steps": []}))
+            .is_ok());
+
+        let run_id = RunId::new();
+        let step_id = StepId::new();
+        let started_at = time::OffsetDateTime::now_utc() - time::Duration::seconds(2);
+        let run = RunRecord {
+            started_at,
+            external_correlation_id: Some("team-a".to_string()),
+            ..fixture_run(run_id)
+        };
+        assert!(store.insert_run(&run).is_ok());
+        assert!(store.insert_step(&fixture_step(run_id, step_id)).is_ok());
+        let package = fixture_package();
+        assert!(store
+            .append_context_package(run_id, step_id, 1, &package)
+            .is_ok());
+        for (latency_ms, tokens) in [(Some(40), Some((10, 5))), (Some(60), None)] {
+            let call = multi_agent_center_domain::ProviderCallRecord {
+                provider_call_id: Ulid::new(),
+                provider_name: "mock".to_string(),
+                adapter_version: "v1".to_string(),
+                model_id: "x".to_string(),
+                request_json: json!({}),
+                request_hash: "request".to_string(),
+                response_json: json!({}),
+                response_hash: "response".to_string(),
+                latency_ms,
+                input_tokens: tokens.map(|(input, _)| input),
+                output_tokens: tokens.map(|(_, output)| output),
+                started_at,
+                ended_at: started_at,
+                status: "ok".to_string(),
+                error_text: None,
+                persona_json: None,
+            };
+            assert!(store.append_provider_call(run_id, step_id, &call).is_ok());
+        }
+        assert!(store
+            .update_run_finished(run_id, RunStatus::Succeeded)
+            .is_ok());
+
+        let usage = store.record_run_usage(run_id);
+        assert!(usage.is_ok());
+        let usage = usage.unwrap_or_else(|_| unreachable!());
+        assert_eq!(usage.status, RunStatus::Succeeded);
+        assert_eq!(usage.external_correlation_id.as_deref(), Some("team-a"));
+        assert_eq!(usage.provider_calls, 2);
+        assert_eq!((usage.input_tokens, usage.output_tokens), (10, 5));
+        assert_eq!(usage.provider_latency_ms, 100);
+        assert!(usage.wall_time_ms >= 2000);
+        let package_json =
+            serde_json::to_string(&package.context_package).unwrap_or_else(|_| unreachable!());
+        assert_eq!(usage.context_bytes_injected, package_json.len() as u64);
+        // run + step + package + its selected item + two provider calls
+        assert_eq!(usage.trace_rows_written, 6);
+
+        assert!(matches!(store.get_run_usage(run_id), Ok(Some(ref stored)) if *stored == usage));
+        assert!(matches!(store.get_run_usage(RunId::new()), Ok(None)));
+        assert_eq!(
+            store.list_run_usage(Some("wf")).map(|all| all.len()).ok(),
+            Some(1)
+        );
+        assert_eq!(
+            store
+                .list_run_usage(Some("other"))
+                .map(|all| all.len())
+                .ok(),
+            Some(0)
+        );
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn run_labels_round_trip_and_filter_run_listing() {
+        let path = temp_db_path("run-labels");
+        let store = SqliteTraceStore::open(&path).unwrap_or_else(|_| unreachable!());
+        assert!(store.migrate().is_ok());
+        assert!(store
+            .upsert_workflow_snapshot("hash", 1, "yaml", "yaml-hash", &json!({"steps": []}))
+            .is_ok());
+
+        let labeled = |pairs: &[(&str, &str)]| {
+            let run = RunRecord {
+                labels: pairs
+                    .iter()
+                    .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+                    .collect(),
+                ..fixture_run(RunId::new())
+            };
+            assert!(store.insert_run(&run).is_ok());
+            run
+        };
+        let payments_prod = labeled(&[("team", "payments"), ("env", "prod")]);
+        let payments_dev = labeled(&[("team", "payments"), ("env", "dev")]);
+        let _search = labeled(&[("team", "search")]);
+        let _unlabeled = labeled(&[]);
+
+        let filter = |pairs: &[(&str, &str)]| -> Vec<RunId> {
+            let labels = pairs
+                .iter()
+                .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+                .collect();
+            let mut ids: Vec<RunId> = store
+                .list_runs_with_labels(&labels)
+                .unwrap_or_else(|_| unreachable!())
+                .into_iter()
+                .map(|run| run.run_id)
+                .collect();
+            ids.sort_by_key(|id| id.0);
+            ids
+        };
+        let mut payments = vec![payments_prod.run_id, payments_dev.run_id];
+        payments.sort_by_key(|id| id.0);
+        assert_eq!(filter(&[("team", "payments")]), payments);
+        assert_eq!(
+            filter(&[("team", "payments"), ("env", "prod")]),
+            vec![payments_prod.run_id]
+        );
+        assert!(filter(&[("team", "billing")]).is_empty());
+        assert_eq!(filter(&[]).len(), 4);
+
+        let stored = store.get_run(payments_prod.run_id);
+        assert!(matches!(stored, Ok(Some(ref run)) if run.labels == payments_prod.labels));
+        let listed = store.list_runs().unwrap_or_else(|_| unreachable!());
+        assert!(listed
+            .iter()
+            .any(|run| run.run_id == payments_dev.run_id && run.labels == payments_dev.labels));
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn reason_code_stats_group_blocking_decisions_by_workflow_and_gate_kind() {
+        use multi_agent_center_domain::DecisionWindow;
+
+        let path = temp_db_path("reason-code-stats");
+        let store = SqliteTraceStore::open(&path).unwrap_or_else(|_| unreachable!());
+        assert!(store.migrate().is_ok());
+        assert!(store
+            .upsert_workflow_snapshot("hash", 1, "yaml", "yaml-hash", &json!({"x":1}))
+            .is_ok());
+
+        let base = time::OffsetDateTime::now_utc();
+        let mut steps = Vec::new();
+        for workflow_name in ["wf", "wf_other"] {
+            let run_id = RunId::new();
+            let step_id = StepId::new();
+            let mut run = fixture_run(run_id);
+            run.workflow_name = workflow_name.to_string();
+            assert!(store.insert_run(&run).is_ok());
+            assert!(store.insert_step(&fixture_step(run_id, step_id)).is_ok());
+            steps.push((run_id, step_id));
+        }
+        let record = |gate_kind: GateKind, decision: GateDecision, code: &str, hours: i64| {
+            GateDecisionRecord {
+                gate_kind,
+                gate_name: "gate".to_string(),
+                subject_type: "memory_ref".to_string(),
+                memory_id: Some(MemoryId::new()),
+                version: Some(1),
+                memory_version_id: Some(MemoryVersionId::new()),
+                decision,
+                reason_codes: vec![code.to_string()],
+                notes: None,
+                decided_by: "test".to_string(),
+                decided_at: base + time::Duration::hours(hours),
+                source_ruleset_version: Some(1),
+                evidence_json: None,
+            }
+        };
+        let (wf_run, wf_step) = steps[0];
+        let (other_run, other_step) = steps[1];
+        for (run_id, step_id, decision) in [
+            (
+                wf_run,
+                wf_step,
+                record(
+                    GateKind::Trust,
+                    GateDecision::Rejected,
+                    "excluded.retired",
+                    0,
+                ),
+            ),
+            (
+                wf_run,
+                wf_step,
+                record(
+                    GateKind::Trust,
+                    GateDecision::Rejected,
+                    "excluded.retired",
+                    1,
+                ),
+            ),
+            (
+                wf_run,
+                wf_step,
+                record(
+                    GateKind::Trust,
+                    GateDecision::Approved,
+                    "included.safe.validated_threshold",
+                    1,
+                ),
+            ),
+            (
+                wf_run,
+                wf_step,
+                record(
+                    GateKind::Policy,
+                    GateDecision::Pruned,
+                    "max_context_items_exceeded",
+                    2,
+                ),
+            ),
+            (
+                other_run,
+                other_step,
+                record(
+                    GateKind::Trust,
+                    GateDecision::Rejected,
+                    "excluded.retired",
+                    2,
+                ),
+            ),
+            (
+                wf_run,
+                wf_step,
+                record(
+                    GateKind::Trust,
+                    GateDecision::Rejected,
+                    "excluded.retired",
+                    -48,
+                ),
+            ),
+        ] {
+            assert!(store
+                .append_gate_decision(run_id, step_id, &decision)
+                .is_ok());
+        }
+
+        let stats = store
+            .reason_code_stats(DecisionWindow {
+                since: Some(base - time::Duration::hours(1)),
+                until: Some(base + time::Duration::hours(3)),
+            })
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(stats.decisions_evaluated, 5);
+        assert_eq!(stats.decisions_blocking, 4);
+        let rows: Vec<(&str, GateKind, GateDecision, &str, u64)> = stats
+            .stats
+            .iter()
+            .map(|stat| {
+                (
+                    stat.workflow_name.as_str(),
+                    stat.gate_kind.clone(),
+                    stat.decision.clone(),
+                    stat.reason_code.as_str(),
+                    stat.count,
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                (
+                    "wf",
+                    GateKind::Trust,
+                    GateDecision::Rejected,
+                    "excluded.retired",
+                    2
+                ),
+                (
+                    "wf",
+                    GateKind::Policy,
+                    GateDecision::Pruned,
+                    "max_context_items_exceeded",
+                    1
+                ),
+                (
+                    "wf_other",
+                    GateKind::Trust,
+                    GateDecision::Rejected,
+                    "excluded.retired",
+                    1
+                ),
+            ]
+        );
+        assert_eq!(stats.stats[0].first_decided_at, base);
+        assert_eq!(
+            stats.stats[0].last_decided_at,
+            base + time::Duration::hours(1)
+        );
+
+        let all_time = store
+            .reason_code_stats(DecisionWindow::default())
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(all_time.decisions_evaluated, 6);
+        assert_eq!(all_time.stats[0].count, 3);
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    fn fixture_event(run_id: RunId, step_id: StepId, event_hash: &str) -> TraceEvent {
+        let now = time::OffsetDateTime::now_utc();
+        TraceEvent {
+            event_id: Ulid::new(),
+            run_id,
+            step_id: Some(step_id),
+            event_type: TraceEventType::StepStarted,
+            occurred_at: now,
+            recorded_at: now,
+            actor_type: "system".to_string(),
+            actor_id: "test".to_string(),
+            payload_json: json!({}),
+            payload_hash: "payload".to_string(),
+            prev_event_hash: None,
+            event_hash: event_hash.to_string(),
+        }
+    }
+
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn query_events_filters_across_runs_by_type_actor_time_and_payload() {
+        use multi_agent_center_domain::{EventQuery, PayloadFilter};
+
+        let path = temp_db_path("query-events");
+        let store = SqliteTraceStore::open(&path).unwrap_or_else(|_| unreachable!());
+        assert!(store.migrate().is_ok());
+        assert!(store
+            .upsert_workflow_snapshot("hash", 1, "yaml", "yaml-hash", &json!({"steps": []}))
+            .is_ok());
+        let base = time::OffsetDateTime::now_utc() - time::Duration::hours(1);
+        let runs = [RunId::new(), RunId::new()];
+        let mut seq = 0;
+        for (run_index, run_id) in runs.iter().enumerate() {
+            let step_id = StepId::new();
+            assert!(store.insert_run(&fixture_run(*run_id)).is_ok());
+            assert!(store.insert_step(&fixture_step(*run_id, step_id)).is_ok());
+            let mut warning = |code: &str, actor: &str, minutes: i64| {
+                seq += 1;
+                TraceEvent {
+                    event_type: TraceEventType::Warning,
+                    occurred_at: base + time::Duration::minutes(minutes),
+                    actor_id: actor.to_string(),
+                    payload_json: json!({"warning_code": code, "details": {"items": [{"dropped": 2}]}}),
+                    ..fixture_event(*run_id, step_id, &format!("e{seq}"))
+                }
+            };
+            let offset = i64::try_from(run_index).unwrap_or_else(|_| unreachable!()) * 10;
+            for event in [
+                warning("context_pruned", "orchestrator", offset),
+                warning("budget_exceeded", "orchestrator", offset + 1),
+                warning("context_pruned", "planner", offset + 2),
+                fixture_event(*run_id, step_id, &format!("started-{run_index}")),
+            ] {
+                assert!(store.append_event(&event).is_ok());
+            }
+        }
+
+        let filter = |raw: &str| PayloadFilter::parse(raw).unwrap_or_else(|_| unreachable!());
+        let query = |query: EventQuery| -> Vec<(RunId, String)> {
+            store
+                .query_events(&query)
+                .unwrap_or_else(|_| unreachable!())
+                .into_iter()
+                .map(|row| (row.event.run_id, row.event.actor_id))
+                .collect()
+        };
+
+        let pruned = EventQuery {
+            payload: vec![filter(r#"payload.warning_code == "context_pruned""#)],
+            ..EventQuery::default()
+        };
+        assert_eq!(query(pruned.clone()).len(), 4);
+        assert_eq!(
+            query(EventQuery {
+                actor_id: Some("planner".to_string()),
+                ..pruned.clone()
+            }),
+            vec![
+                (runs[0], "planner".to_string()),
+                (runs[1], "planner".to_string())
+            ]
+        );
+        assert_eq!(
+            query(EventQuery {
+                since: Some(base + time::Duration::minutes(5)),
+                limit: Some(1),
+                ..pruned.clone()
+            }),
+            vec![(runs[1], "orchestrator".to_string())]
+        );
+        assert_eq!(
+            query(EventQuery {
+                run_id: Some(runs[0]),
+                until: Some(base + time::Duration::minutes(2)),
+                ..pruned
+            }),
+            vec![(runs[0], "orchestrator".to_string())]
+        );
+
+        let not_pruned = EventQuery {
+            event_types: vec![TraceEventType::Warning],
+            payload: vec![
+                filter(r#"payload.warning_code != "context_pruned""#),
+                filter("payload.details.items[0].dropped == 2.0"),
+            ],
+            ..EventQuery::default()
+        };
+        assert_eq!(query(not_pruned).len(), 2);
+        assert_eq!(
+            query(EventQuery {
+                event_types: vec![TraceEventType::StepStarted, TraceEventType::RunFinished],
+                ..EventQuery::default()
+            })
+            .len(),
+            2
+        );
+        assert!(query(EventQuery {
+            payload: vec![filter("payload.missing != 1")],
+            ..EventQuery::default()
+        })
+        .is_empty());
+
+        for invalid in [
+            "warning_code == 1",
+            "payload == 1",
+            "payload.code = 1",
+            "payload.items[x] == 1",
+            r#"payload.code == {"a": 1}"#,
+            "payload.code == context_pruned",
+        ] {
+            assert!(PayloadFilter::parse(invalid).is_err(), "{invalid}");
+        }
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn warning_stats_group_warning_codes_by_workflow_and_count_distinct_runs() {
+        use multi_agent_center_domain::DecisionWindow;
+
+        let path = temp_db_path("warning-stats");
+        let store = SqliteTraceStore::open(&path).unwrap_or_else(|_| unreachable!());
+        assert!(store.migrate().is_ok());
+        assert!(store
+            .upsert_workflow_snapshot("hash", 1, "yaml", "yaml-hash", &json!({"steps": []}))
+            .is_ok());
+        let base = time::OffsetDateTime::now_utc() - time::Duration::hours(1);
+        let mut seq = 0;
+        for (workflow_name, codes) in [
+            (
+                "wf-a",
+                vec![Some("context_pruned"), Some("context_pruned"), None],
+            ),
+            ("wf-a", vec![Some("context_pruned")]),
+            ("wf-b", vec![Some("budget_exceeded")]),
+        ] {
+            let run_id = RunId::new();
+            let step_id = StepId::new();
+            let run = RunRecord {
+                workflow_name: workflow_name.to_string(),
+                ..fixture_run(run_id)
+            };
+            assert!(store.insert_run(&run).is_ok());
+            assert!(store.insert_step(&fixture_step(run_id, step_id)).is_ok());
+            for code in codes {
+                seq += 1;
+                let payload_json = match code {
+                    Some(code) => json!({"warning_code": code}),
+                    None => json!({"message": "no code"}),
+                };
+                let event = TraceEvent {
+                    event_type: TraceEventType::Warning,
+                    occurred_at: base + time::Duration::minutes(seq),
+                    payload_json,
+                    ..fixture_event(run_id, step_id, &format!("w{seq}"))
+                };
+                assert!(store.append_event(&event).is_ok());
+            }
+            assert!(store
+                .append_event(&fixture_event(run_id, step_id, &format!("started-{seq}")))
+                .is_ok());
+        }
+
+        let stats = store
+            .warning_stats(DecisionWindow::default(), None)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(stats.warnings_total, 5);
+        let summary: Vec<(&str, &str, u64, u64)> = stats
+            .stats
+            .iter()
+            .map(|stat| {
+                (
+                    stat.workflow_name.as_str(),
+                    stat.warning_code.as_str(),
+                    stat.count,
+                    stat.runs,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("wf-a", "context_pruned", 3, 2),
+                ("wf-a", UNSPECIFIED_WARNING_CODE, 1, 1),
+                ("wf-b", "budget_exceeded", 1, 1),
+            ]
+        );
+        assert_eq!(
+            stats.stats[0].first_occurred_at,
+            base + time::Duration::minutes(1)
+        );
+        assert_eq!(
+            stats.stats[0].last_occurred_at,
+            base + time::Duration::minutes(4)
+        );
+
+        let windowed = store
+            .warning_stats(
+                DecisionWindow {
+                    since: Some(base + time::Duration::minutes(3)),
+                    until: None,
+                },
+                Some("wf-a"),
+            )
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(windowed.warnings_total, 2);
+        assert_eq!(
+            windowed
+                .stats
+                .iter()
+                .map(|stat| (stat.warning_code.as_str(), stat.count, stat.runs))
+                .collect::<Vec<_>>(),
+            vec![("context_pruned", 1, 1), (UNSPECIFIED_WARNING_CODE, 1, 1)]
+        );
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn buffered_store_reads_its_own_writes_and_continues_the_event_sequence() {
+        let path = temp_db_path("buffered");
+        let run_id = RunId::new();
+        let step_id = StepId::new();
+        {
+            let store = SqliteTraceStore::open(&path).unwrap_or_else(|_| unreachable!());
+            assert!(store.migrate().is_ok());
+            assert!(store
+                .upsert_workflow_snapshot("hash", 1, "yaml", "yaml-hash", &json!({"x":1}))
+                .is_ok());
+            assert!(store.insert_run(&fixture_run(run_id)).is_ok());
+            assert!(store.insert_step(&fixture_step(run_id, step_id)).is_ok());
+            assert_eq!(
+                store
+                    .append_event(&fixture_event(run_id, step_id, "e1"))
+                    .unwrap_or_else(|_| unreachable!()),
+                1
+            );
+        }
+
+        let buffered = BufferedSqliteTraceStore::open(&path).unwrap_or_else(|_| unreachable!());
+        let second_run = RunId::new();
+        let second_step = StepId::new();
+        assert!(buffered.insert_run(&fixture_run(second_run)).is_ok());
+        assert!(buffered
+            .insert_step(&fixture_step(second_run, second_step))
+            .is_ok());
+        let seqs: Vec<i64> = ["e2", "e3", "e4"]
+            .iter()
+            .map(|hash| {
+                buffered
+                    .append_event(&fixture_event(second_run, second_step, hash))
+                    .unwrap_or_else(|_| unreachable!())
+            })
+            .collect();
+        assert_eq!(seqs, vec![2, 3, 4]);
+
+        let run = buffered
+            .get_run(second_run)
+            .unwrap_or_else(|_| unreachable!());
+        assert!(run.is_some());
+        assert!(buffered
+            .update_run_finished(second_run, RunStatus::Succeeded)
+            .is_ok());
+        assert!(buffered.flush().is_ok());
+
+        let store = SqliteTraceStore::open(&path).unwrap_or_else(|_| unreachable!());
+        let events = store
+            .list_events_for_run(second_run)
+            .unwrap_or_else(|_| unreachable!());
+        let persisted: Vec<(i64, &str)> = events
+            .iter()
+            .map(|row| (row.event_seq, row.event.event_hash.as_str()))
+            .collect();
+        assert_eq!(persisted, vec![(2, "e2"), (3, "e3"), (4, "e4")]);
+        let finished = store.get_run(second_run).unwrap_or_else(|_| unreachable!());
+        assert_eq!(finished.map(|run| run.status), Some(RunStatus::Succeeded));
+
+        drop(buffered);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn buffered_store_commits_synchronous_writes_and_reports_relaxed_failures_on_flush() {
+        // Without relaxed classes a constraint failure surfaces from the write itself.
+        let strict_path = temp_db_path("buffered-strict");
+        let strict =
+            BufferedSqliteTraceStore::open(&strict_path).unwrap_or_else(|_| unreachable!());
+        assert!(strict.migrate().is_ok());
+        assert!(strict
+            .upsert_workflow_snapshot("hash", 1, "yaml", "yaml-hash", &json!({"x":1}))
+            .is_ok());
+        let run_id = RunId::new();
+        let step_id = StepId::new();
+        assert!(strict.insert_run(&fixture_run(run_id)).is_ok());
+        assert!(strict.insert_run(&fixture_run(run_id)).is_err());
+        drop(strict);
+        let _ = std::fs::remove_file(strict_path);
+
+        let path = temp_db_path("buffered-relaxed");
+        let buffered = BufferedSqliteTraceStore::open(&path).unwrap_or_else(|_| unreachable!());
+        assert!(buffered.migrate().is_ok());
+        assert!(buffered
+            .upsert_workflow_snapshot("hash", 1, "yaml", "yaml-hash", &json!({"x":1}))
+            .is_ok());
+        assert!(buffered
+            .set_relaxed_artifacts(&BTreeSet::from([TraceArtifactClass::Events]))
+            .is_ok());
+        assert!(buffered.insert_run(&fixture_run(run_id)).is_ok());
+        assert!(buffered.insert_step(&fixture_step(run_id, step_id)).is_ok());
+
+        // Synchronous rows are visible to another connection without a flush.
+        let reader = SqliteTraceStore::open(&path).unwrap_or_else(|_| unreachable!());
+        assert!(reader
+            .get_run(run_id)
+            .unwrap_or_else(|_| unreachable!())
+            .is_some());
+
+        // A relaxed duplicate event is accepted into the queue; its failure surfaces on flush.
+        let event = fixture_event(run_id, step_id, "e1");
+        assert!(buffered.append_event(&event).is_ok());
+        assert!(buffered.append_event(&event).is_ok());
+        assert!(buffered.flush().is_err());
+        assert!(buffered.insert_run(&fixture_run(RunId::new())).is_err());
+        assert!(buffered.list_runs().is_err());
+
+        drop(buffered);
+        let events = reader
+            .list_events_for_run(run_id)
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(events.len(), 1);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn concurrent_correlation_claims_insert_one_live_run() {
+        let path = temp_db_path("correlation-claim");
+        {
+            let store = SqliteTraceStore::open(&path).unwrap_or_else(|_| unreachable!());
+            assert!(store.migrate().is_ok());
+            assert!(store
+                .upsert_workflow_snapshot("hash", 1, "yaml", "yaml-hash", &json!({"x":1}))
+                .is_ok());
+        }
+        let claim = |path: &std::path::Path| {
+            let store = SqliteTraceStore::open(path).unwrap_or_else(|_| unreachable!());
+            let mut run = fixture_run(RunId::new());
+            run.external_correlation_id = Some("sched-7".to_string());
+            let claimed = store
+                .insert_run_claiming_correlation(&run)
+                .unwrap_or_else(|err| panic!("claim failed: {err:#}"));
+            (run.run_id, claimed.map(|existing| existing.run_id))
+        };
+
+        let results: Vec<(RunId, Option<RunId>)> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..4).map(|_| scope.spawn(|| claim(&path))).collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap_or_else(|_| unreachable!()))
+                .collect()
+        });
+        let winners: Vec<RunId> = results
+            .iter()
+            .filter(|(_, existing)| existing.is_none())
+            .map(|(run_id, _)| *run_id)
+            .collect();
+        assert_eq!(winners.len(), 1);
+        assert!(results.iter().all(
+            |(run_id, existing)| existing.map_or(*run_id == winners[0], |id| id == winners[0])
+        ));
+
+        // Once the claimed run fails, the next submitter claims the id again.
+        let store = SqliteTraceStore::open(&path).unwrap_or_else(|_| unreachable!());
+        assert!(store
+            .update_run_finished(winners[0], RunStatus::Failed)
+            .is_ok());
+        let (retry_id, existing) = claim(&path);
+        assert_eq!(existing, None);
+        let runs = store
+            .find_runs_by_external_correlation_id("sched-7")
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].run_id, retry_id);
+        let _ = std::fs::remove_file(path);
+    }
+
+    const WRITER_CHILD_DB: &str = "MAC_TRACE_WRITER_CHILD_DB";
+    const RUNS_PER_WRITER: usize = 20;
+
+    /// Write `RUNS_PER_WRITER` complete runs through a coordinated store with `SQLite`'s busy
+    /// timeout disabled, so any write that is not serialized by the writer lock fails at once.
+    fn write_coordinated_runs(path: &std::path::Path) -> anyhow::Result<()> {
+        let store = SqliteTraceStore::open_coordinated(path)?;
+        store.conn.execute_batch("PRAGMA busy_timeout = 0")?;
+        for _ in 0..RUNS_PER_WRITER {
+            let run_id = RunId::new();
+            let step_id = StepId::new();
+            store.insert_run(&fixture_run(run_id))?;
+            store.insert_step(&fixture_step(run_id, step_id))?;
+            for index in 0..3 {
+                store.append_event(&fixture_event(run_id, step_id, &format!("e{index}")))?;
+            }
+            store.update_step_status(step_id, StepStatus::Succeeded, Some("out"), None)?;
+            store.update_run_finished(run_id, RunStatus::Succeeded)?;
+        }
+        Ok(())
+    }
+
+    /// Child half of `coordinated_writers_in_separate_processes_share_one_trace_db`; does
+    /// nothing unless that test spawns it.
+    #[test]
+    fn coordinated_writer_child_process() {
+        let Ok(path) = std::env::var(WRITER_CHILD_DB) else {
+            return;
+        };
+        if let Err(err) = write_coordinated_runs(std::path::Path::new(&path)) {
+            panic!("child writer failed: {err:#}");
+        }
+    }
+
+    #[test]
+    fn coordinated_writers_in_separate_processes_share_one_trace_db() {
+        let path = temp_db_path("coordinated-writers");
+        {
+            let store =
+                SqliteTraceStore::open_coordinated(&path).unwrap_or_else(|_| unreachable!());
+            assert!(store.is_coordinated());
+            assert!(store.migrate().is_ok());
+            assert!(store
+                .upsert_workflow_snapshot("hash", 1, "yaml", "yaml-hash", &json!({"x":1}))
+                .is_ok());
+        }
+        assert!(super::writer_lock_path(&path).exists());
+
+        let exe = std::env::current_exe().unwrap_or_else(|_| unreachable!());
+        let children: Vec<std::process::Child> = (0..3)
+            .map(|_| {
+                std::process::Command::new(&exe)
+                    .args(["tests::coordinated_writer_child_process", "--exact"])
+                    .env(WRITER_CHILD_DB, &path)
+                    .stdout(std::process::Stdio::null())
+                    .spawn()
+                    .unwrap_or_else(|err| panic!("failed to spawn writer process: {err}"))
+            })
+            .collect();
+        let parent = write_coordinated_runs(&path);
+        for mut child in children {
+            let status = child
+                .wait()
+                .unwrap_or_else(|err| panic!("failed to wait for writer process: {err}"));
+            assert!(status.success(), "writer process failed: {status}");
+        }
+        assert!(parent.is_ok(), "parent writer failed: {parent:?}");
+
+        let store = SqliteTraceStore::open(&path).unwrap_or_else(|_| unreachable!());
+        let runs = store.list_runs().unwrap_or_else(|_| unreachable!());
+        assert_eq!(runs.len(), 4 * RUNS_PER_WRITER);
+        assert!(runs.iter().all(|run| run.status == RunStatus::Succeeded));
+        let events: i64 = store
+            .conn
+            .query_row("SELECT COUNT(*) FROM trace_events", [], |row| row.get(0))
+            .unwrap_or_else(|_| unreachable!());
+        assert_eq!(events, i64::try_from(4 * RUNS_PER_WRITER * 3).unwrap_or(0));
+
+        // A coordinated store is shared with other writers, so it cannot be buffered.
+        let coordinated =
+            SqliteTraceStore::open_coordinated(&path).unwrap_or_else(|_| unreachable!());
+        assert!(BufferedSqliteTraceStore::new(coordinated).is_err());
+
+        let _ = std::fs::remove_file(super::writer_lock_path(&path));
+        let _ = std::fs::remove_file(path);
+    }
 }
@@ -2,23 +2,99 @@
 
 use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use multi_agent_center_domain::{
-    ensure_non_empty, hash_bytes, hash_json, AgentDefinition, NormalizedWorkflow,
-    NormalizedWorkflowEnvelope, WorkflowStepDefinition,
+    ensure_non_empty, hash_bytes, hash_json, parse_prompt_ref, AffectedStep, AgentDefinition,
+    GateKind, GatePointDefinition, NormalizedWorkflow, NormalizedWorkflowEnvelope,
+    PersonaDefinition, ProviderLoadBalancing, StandaloneStepDefinition, StepCondition,
+    StepConvergence, StepForeach, StepIteration, StepVote, StepWorkflowCall, TaintPolicy,
+    VoteAggregator, WorkflowChange, WorkflowChangeCategory, WorkflowDefaults, WorkflowDiff,
+    WorkflowStepDefinition,
 };
+use serde_json::{json, Value};
 
 const NORMALIZATION_VERSION: u32 = 1;
+/// Upper bound on `iterate.max_iterations`, so a step that never converges still ends.
+pub const MAX_STEP_ITERATIONS: u32 = 50;
+/// Largest weight a load-balanced provider endpoint may declare.
+pub const MAX_ENDPOINT_WEIGHT: u32 = 1000;
+/// How deep `workflow_call` steps may nest workflows inside one another.
+pub const MAX_WORKFLOW_CALL_DEPTH: usize = 8;
 
-/// Load workflow YAML from disk and normalize it into canonical internal form.
+/// Load workflow YAML from disk and normalize it into canonical internal form. The workflows
+/// named by `workflow_call` paths are loaded relative to the file that names them and embedded
+/// in the calling step.
 ///
 /// # Errors
-/// Returns an error when the file cannot be read, parsed, validated, or normalized.
+/// Returns an error when the file or a called workflow cannot be read, parsed, validated, or
+/// normalized, or when workflow calls form a cycle.
 pub fn load_workflow_from_path(path: &Path) -> Result<NormalizedWorkflowEnvelope> {
     let content = fs::read_to_string(path)?;
-    normalize_workflow_yaml(&content)
+    let source_yaml_hash = hash_bytes(content.as_bytes());
+    let mut workflow: NormalizedWorkflow = serde_yaml::from_str(&content)
+        .map_err(|err| anyhow!("invalid workflow YAML structure: {err}"))?;
+    let canonical = fs::canonicalize(path)
+        .with_context(|| format!("failed to resolve workflow path {}", path.display()))?;
+    resolve_workflow_calls(&mut workflow, &canonical, &mut vec![canonical.clone()])?;
+    finish_normalization(workflow, "yaml", source_yaml_hash)
+}
+
+/// Load the workflow of every unresolved `workflow_call` in `workflow`, which was read from
+/// `file`; `loading` holds the files of the workflows currently being resolved.
+fn resolve_workflow_calls(
+    workflow: &mut NormalizedWorkflow,
+    file: &Path,
+    loading: &mut Vec<PathBuf>,
+) -> Result<()> {
+    for step in &mut workflow.steps {
+        let Some(call) = step.workflow_call.as_mut() else {
+            continue;
+        };
+        if call.workflow.is_some() || call.path.is_empty() {
+            continue;
+        }
+        let callee_path = file.parent().unwrap_or(Path::new(".")).join(&call.path);
+        let callee_path = fs::canonicalize(&callee_path).with_context(|| {
+            format!(
+                "step {} calls workflow {}, which cannot be found",
+                step.step_key,
+                callee_path.display()
+            )
+        })?;
+        if loading.contains(&callee_path) {
+            return Err(anyhow!(
+                "step {} calls workflow {}, which is already being loaded; workflow calls may \
+                 not form a cycle",
+                step.step_key,
+                callee_path.display()
+            ));
+        }
+        if loading.len() > MAX_WORKFLOW_CALL_DEPTH {
+            return Err(anyhow!(
+                "step {} calls workflow {} more than {MAX_WORKFLOW_CALL_DEPTH} workflow calls deep",
+                step.step_key,
+                callee_path.display()
+            ));
+        }
+        let content = fs::read_to_string(&callee_path)
+            .with_context(|| format!("failed to read called workflow {}", callee_path.display()))?;
+        let mut callee: NormalizedWorkflow = serde_yaml::from_str(&content).map_err(|err| {
+            anyhow!(
+                "invalid workflow YAML structure in {}: {err}",
+                callee_path.display()
+            )
+        })?;
+        loading.push(callee_path.clone());
+        resolve_workflow_calls(&mut callee, &callee_path, loading)?;
+        loading.pop();
+        validate_workflow(&callee)
+            .map_err(|err| anyhow!("step {} called workflow: {err}", step.step_key))?;
+        normalize_workflow(&mut callee);
+        call.workflow = Some(Box::new(callee));
+    }
+    Ok(())
 }
 
 /// Normalize workflow YAML into deterministic canonical JSON + hash.
@@ -27,9 +103,62 @@ pub fn load_workflow_from_path(path: &Path) -> Result<NormalizedWorkflowEnvelope
 /// Returns an error when YAML parsing, validation, or serialization fails.
 pub fn normalize_workflow_yaml(yaml: &str) -> Result<NormalizedWorkflowEnvelope> {
     let source_yaml_hash = hash_bytes(yaml.as_bytes());
-    let mut workflow: NormalizedWorkflow = serde_yaml::from_str(yaml)
+    let workflow: NormalizedWorkflow = serde_yaml::from_str(yaml)
         .map_err(|err| anyhow!("invalid workflow YAML structure: {err}"))?;
+    finish_normalization(workflow, "yaml", source_yaml_hash)
+}
+
+/// Normalize a workflow built in code; the source hash covers its JSON form as given.
+///
+/// # Errors
+/// Returns an error when validation or serialization fails.
+pub fn normalize_workflow_definition(
+    workflow: NormalizedWorkflow,
+) -> Result<NormalizedWorkflowEnvelope> {
+    let source_hash = hash_json(&serde_json::to_value(&workflow)?)?;
+    finish_normalization(workflow, "definition", source_hash)
+}
+
+/// Load a standalone step YAML (`agent` plus `step`) from disk and validate it.
+///
+/// # Errors
+/// Returns an error when the file cannot be read, parsed, or validated.
+pub fn load_standalone_step_from_path(path: &Path) -> Result<StandaloneStepDefinition> {
+    let content = fs::read_to_string(path)?;
+    parse_standalone_step_yaml(&content)
+}
+
+/// Parse and validate a standalone step YAML with the same rules as a one-step workflow.
+///
+/// # Errors
+/// Returns an error when YAML parsing or validation fails.
+pub fn parse_standalone_step_yaml(yaml: &str) -> Result<StandaloneStepDefinition> {
+    let definition: StandaloneStepDefinition =
+        serde_yaml::from_str(yaml).map_err(|err| anyhow!("invalid step YAML structure: {err}"))?;
+    if !definition.step.depends_on.is_empty() {
+        return Err(anyhow!(
+            "standalone step {} cannot declare depends_on",
+            definition.step.step_key
+        ));
+    }
+    validate_workflow(&NormalizedWorkflow {
+        workflow_name: "standalone".to_string(),
+        workflow_version: "standalone".to_string(),
+        normalization_version: NORMALIZATION_VERSION,
+        agents: vec![definition.agent.clone()],
+        steps: vec![definition.step.clone()],
+        gates: Vec::new(),
+        defaults: WorkflowDefaults::default(),
+        personas: Vec::new(),
+    })?;
+    Ok(definition)
+}
 
+fn finish_normalization(
+    mut workflow: NormalizedWorkflow,
+    source_format: &str,
+    source_yaml_hash: String,
+) -> Result<NormalizedWorkflowEnvelope> {
     validate_workflow(&workflow)?;
     normalize_workflow(&mut workflow);
     validate_workflow(&workflow)?;
@@ -38,7 +167,7 @@ pub fn normalize_workflow_yaml(yaml: &str) -> Result<NormalizedWorkflowEnvelope>
     let normalized_hash = hash_json(&normalized_json)?;
 
     Ok(NormalizedWorkflowEnvelope {
-        source_format: "yaml".to_string(),
+        source_format: source_format.to_string(),
         source_yaml_hash,
         normalized_hash,
         normalized_workflow: workflow,
@@ -46,6 +175,387 @@ pub fn normalize_workflow_yaml(yaml: &str) -> Result<NormalizedWorkflowEnvelope>
     })
 }
 
+/// Diff two normalized workflows: added/removed steps, dependency, permission, provider, gate,
+/// and persona changes, plus the steps whose behaviour may change as a result (directly or through
+/// an affected dependency).
+#[must_use]
+pub fn diff_workflows(
+    old: &NormalizedWorkflowEnvelope,
+    new: &NormalizedWorkflowEnvelope,
+) -> WorkflowDiff {
+    let old_wf = &old.normalized_workflow;
+    let new_wf = &new.normalized_workflow;
+    let mut changes = Vec::new();
+
+    let old_steps: BTreeMap<&str, &WorkflowStepDefinition> = old_wf
+        .steps
+        .iter()
+        .map(|step| (step.step_key.as_str(), step))
+        .collect();
+    let new_steps: BTreeMap<&str, &WorkflowStepDefinition> = new_wf
+        .steps
+        .iter()
+        .map(|step| (step.step_key.as_str(), step))
+        .collect();
+    for step in &new_wf.steps {
+        let Some(previous) = old_steps.get(step.step_key.as_str()) else {
+            changes.push(change(
+                WorkflowChangeCategory::StepAdded,
+                &step.step_key,
+                None,
+                Value::Null,
+                json!(step),
+            ));
+            continue;
+        };
+        if previous.depends_on != step.depends_on {
+            changes.push(change(
+                WorkflowChangeCategory::DependenciesChanged,
+                &step.step_key,
+                None,
+                json!(previous.depends_on),
+                json!(step.depends_on),
+            ));
+        }
+        for (field, old_value, new_value) in step_fields(previous, step) {
+            if old_value != new_value {
+                changes.push(change(
+                    WorkflowChangeCategory::StepChanged,
+                    &step.step_key,
+                    Some(field),
+                    old_value,
+                    new_value,
+                ));
+            }
+        }
+    }
+    for step in &old_wf.steps {
+        if !new_steps.contains_key(step.step_key.as_str()) {
+            changes.push(change(
+                WorkflowChangeCategory::StepRemoved,
+                &step.step_key,
+                None,
+                json!(step),
+                Value::Null,
+            ));
+        }
+    }
+
+    diff_agents(old_wf, new_wf, &mut changes);
+    diff_named(
+        &old_wf.gates,
+        &new_wf.gates,
+        |gate| gate.gate_name.as_str(),
+        [
+            WorkflowChangeCategory::GateAdded,
+            WorkflowChangeCategory::GateRemoved,
+            WorkflowChangeCategory::GateChanged,
+        ],
+        &mut changes,
+    );
+    diff_named(
+        &old_wf.personas,
+        &new_wf.personas,
+        |persona| persona.persona_name.as_str(),
+        [WorkflowChangeCategory::PersonaChanged; 3],
+        &mut changes,
+    );
+
+    let affected_steps = affected_steps(old_wf, new_wf, &changes);
+    WorkflowDiff {
+        old_workflow_hash: old.normalized_hash.clone(),
+        new_workflow_hash: new.normalized_hash.clone(),
+        changes,
+        affected_steps,
+    }
+}
+
+fn change(
+    category: WorkflowChangeCategory,
+    subject: &str,
+    field: Option<&str>,
+    old: Value,
+    new: Value,
+) -> WorkflowChange {
+    WorkflowChange {
+        category,
+        subject: subject.to_string(),
+        field: field.map(str::to_string),
+        old,
+        new,
+    }
+}
+
+fn step_fields(
+    old: &WorkflowStepDefinition,
+    new: &WorkflowStepDefinition,
+) -> [(&'static str, Value, Value); 14] {
+    [
+        ("agent_name", json!(old.agent_name), json!(new.agent_name)),
+        ("task", old.task.clone(), new.task.clone()),
+        ("condition", json!(old.condition), json!(new.condition)),
+        ("when", json!(old.when), json!(new.when)),
+        ("foreach", json!(old.foreach), json!(new.foreach)),
+        ("iterate", json!(old.iterate), json!(new.iterate)),
+        (
+            "workflow_call",
+            json!(old.workflow_call),
+            json!(new.workflow_call),
+        ),
+        ("vote", json!(old.vote), json!(new.vote)),
+        (
+            "gate_points",
+            json!(old.gate_points),
+            json!(new.gate_points),
+        ),
+        (
+            "constraints",
+            json!(old.constraints),
+            json!(new.constraints),
+        ),
+        ("persona", json!(old.persona), json!(new.persona)),
+        ("prompt_ref", json!(old.prompt_ref), json!(new.prompt_ref)),
+        ("scoring", json!(old.scoring), json!(new.scoring)),
+        (
+            "memory_dependencies",
+            json!(old.memory_dependencies),
+            json!(new.memory_dependencies),
+        ),
+    ]
+}
+
+fn diff_agents(
+    old: &NormalizedWorkflow,
+    new: &NormalizedWorkflow,
+    changes: &mut Vec<WorkflowChange>,
+) {
+    let old_agents: BTreeMap<&str, &AgentDefinition> = old
+        .agents
+        .iter()
+        .map(|agent| (agent.agent_name.as_str(), agent))
+        .collect();
+    for agent in &new.agents {
+        let Some(previous) = old_agents.get(agent.agent_name.as_str()) else {
+            changes.push(change(
+                WorkflowChangeCategory::AgentAdded,
+                &agent.agent_name,
+                None,
+                Value::Null,
+                json!(agent),
+            ));
+            continue;
+        };
+        let fields = [
+            (
+                WorkflowChangeCategory::ProviderChanged,
+                "provider",
+                json!(previous.provider),
+                json!(agent.provider),
+            ),
+            (
+                WorkflowChangeCategory::ProviderChanged,
+                "fallbacks",
+                json!(previous.fallbacks),
+                json!(agent.fallbacks),
+            ),
+            (
+                WorkflowChangeCategory::ProviderChanged,
+                "load_balancing",
+                json!(previous.load_balancing),
+                json!(agent.load_balancing),
+            ),
+            (
+                WorkflowChangeCategory::PermissionsChanged,
+                "permissions",
+                json!(previous.permissions),
+                json!(agent.permissions),
+            ),
+            (
+                WorkflowChangeCategory::AgentChanged,
+                "role",
+                json!(previous.role),
+                json!(agent.role),
+            ),
+            (
+                WorkflowChangeCategory::AgentChanged,
+                "default_instructions",
+                json!(previous.default_instructions),
+                json!(agent.default_instructions),
+            ),
+            (
+                WorkflowChangeCategory::AgentChanged,
+                "metadata",
+                json!(previous.metadata),
+                json!(agent.metadata),
+            ),
+            (
+                WorkflowChangeCategory::AgentChanged,
+                "persona",
+                json!(previous.persona),
+                json!(agent.persona),
+            ),
+        ];
+        for (category, field, old_value, new_value) in fields {
+            if old_value != new_value {
+                changes.push(change(
+                    category,
+                    &agent.agent_name,
+                    Some(field),
+                    old_value,
+                    new_value,
+                ));
+            }
+        }
+    }
+    for agent in &old.agents {
+        if !new
+            .agents
+            .iter()
+            .any(|candidate| candidate.agent_name == agent.agent_name)
+        {
+            changes.push(change(
+                WorkflowChangeCategory::AgentRemoved,
+                &agent.agent_name,
+                None,
+                json!(agent),
+                Value::Null,
+            ));
+        }
+    }
+}
+
+/// Diff two name-keyed definition lists; `categories` are `[added, removed, changed]`.
+fn diff_named<T: serde::Serialize + PartialEq>(
+    old: &[T],
+    new: &[T],
+    name: impl Fn(&T) -> &str,
+    categories: [WorkflowChangeCategory; 3],
+    changes: &mut Vec<WorkflowChange>,
+) {
+    let [added, removed, modified] = categories;
+    for item in new {
+        match old.iter().find(|previous| name(previous) == name(item)) {
+            None => changes.push(change(added, name(item), None, Value::Null, json!(item))),
+            Some(previous) if previous != item => {
+                changes.push(change(
+                    modified,
+                    name(item),
+                    None,
+                    json!(previous),
+                    json!(item),
+                ));
+            }
+            Some(_) => {}
+        }
+    }
+    for item in old {
+        if !new.iter().any(|candidate| name(candidate) == name(item)) {
+            changes.push(change(removed, name(item), None, json!(item), Value::Null));
+        }
+    }
+}
+
+fn affected_steps(
+    old: &NormalizedWorkflow,
+    new: &NormalizedWorkflow,
+    changes: &[WorkflowChange],
+) -> Vec<AffectedStep> {
+    let mut reasons: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for step in &new.steps {
+        let agent = new
+            .agents
+            .iter()
+            .find(|agent| agent.agent_name == step.agent_name);
+        let persona = step
+            .persona
+            .as_deref()
+            .or_else(|| agent.and_then(|agent| agent.persona.as_deref()));
+        let step_reasons = reasons.entry(step.step_key.as_str()).or_default();
+        for item in changes {
+            let reason = match item.category {
+                WorkflowChangeCategory::StepAdded if item.subject == step.step_key => {
+                    Some("added".to_string())
+                }
+                WorkflowChangeCategory::DependenciesChanged if item.subject == step.step_key => {
+                    Some("dependencies".to_string())
+                }
+                WorkflowChangeCategory::StepChanged if item.subject == step.step_key => {
+                    item.field.as_ref().map(|field| format!("step.{field}"))
+                }
+                WorkflowChangeCategory::ProviderChanged
+                | WorkflowChangeCategory::PermissionsChanged
+                | WorkflowChangeCategory::AgentChanged
+                    if item.subject == step.agent_name =>
+                {
+                    item.field.as_ref().map(|field| format!("agent.{field}"))
+                }
+                WorkflowChangeCategory::GateAdded
+                | WorkflowChangeCategory::GateRemoved
+                | WorkflowChangeCategory::GateChanged
+                    if step.gate_points.contains(&item.subject) =>
+                {
+                    Some(format!("gate.{}", item.subject))
+                }
+                WorkflowChangeCategory::PersonaChanged
+                    if persona == Some(item.subject.as_str()) =>
+                {
+                    Some(format!("persona.{}", item.subject))
+                }
+                _ => None,
+            };
+            if let Some(reason) = reason {
+                if !step_reasons.contains(&reason) {
+                    step_reasons.push(reason);
+                }
+            }
+        }
+    }
+
+    // Propagate to dependents until no step gains a new upstream reason.
+    loop {
+        let mut updated = false;
+        for step in &new.steps {
+            for dep in &step.depends_on {
+                let dep_affected = reasons.get(dep.as_str()).is_some_and(|r| !r.is_empty());
+                let reason = format!("upstream.{dep}");
+                if let Some(step_reasons) = reasons.get_mut(step.step_key.as_str()) {
+                    if dep_affected && !step_reasons.contains(&reason) {
+                        step_reasons.push(reason);
+                        updated = true;
+                    }
+                }
+            }
+        }
+        if !updated {
+            break;
+        }
+    }
+
+    let mut out: Vec<AffectedStep> = new
+        .steps
+        .iter()
+        .filter_map(|step| {
+            reasons
+                .remove(step.step_key.as_str())
+                .filter(|step_reasons| !step_reasons.is_empty())
+                .map(|step_reasons| AffectedStep {
+                    step_key: step.step_key.clone(),
+                    reasons: step_reasons,
+                })
+        })
+        .collect();
+    out.extend(
+        old.steps
+            .iter()
+            .filter(|step| !new.steps.iter().any(|kept| kept.step_key == step.step_key))
+            .map(|step| AffectedStep {
+                step_key: step.step_key.clone(),
+                reasons: vec!["removed".to_string()],
+            }),
+    );
+    out
+}
+
 fn validate_workflow(workflow: &NormalizedWorkflow) -> Result<()> {
     ensure_non_empty("workflow_name", &workflow.workflow_name)?;
     ensure_non_empty("workflow_version", &workflow.workflow_version)?;
@@ -64,18 +574,45 @@ fn validate_workflow(workflow: &NormalizedWorkflow) -> Result<()> {
         .map(|agent| agent.agent_name.as_str())
         .collect();
 
+    let mut persona_names = BTreeSet::new();
+    for persona in &workflow.personas {
+        validate_persona(persona)?;
+        if !persona_names.insert(persona.persona_name.as_str()) {
+            return Err(anyhow!("duplicate persona_name: {}", persona.persona_name));
+        }
+    }
+    for agent in &workflow.agents {
+        if let Some(persona) = &agent.persona {
+            if !persona_names.contains(persona.as_str()) {
+                return Err(anyhow!(
+                    "agent {} references unknown persona {persona}",
+                    agent.agent_name
+                ));
+            }
+        }
+    }
+
     let mut step_keys = BTreeSet::new();
     for step in &workflow.steps {
         validate_step(step)?;
         if !step_keys.insert(step.step_key.clone()) {
             return Err(anyhow!("duplicate step_key: {}", step.step_key));
         }
-        if !agent_name_set.contains(step.agent_name.as_str()) {
-            return Err(anyhow!(
-                "step {} references unknown agent {}",
-                step.step_key,
-                step.agent_name
-            ));
+        for agent_name in step_agents(step) {
+            if !agent_name_set.contains(agent_name) {
+                return Err(anyhow!(
+                    "step {} references unknown agent {agent_name}",
+                    step.step_key
+                ));
+            }
+        }
+        if let Some(persona) = &step.persona {
+            if !persona_names.contains(persona.as_str()) {
+                return Err(anyhow!(
+                    "step {} references unknown persona {persona}",
+                    step.step_key
+                ));
+            }
         }
     }
 
@@ -96,6 +633,10 @@ fn validate_workflow(workflow: &NormalizedWorkflow) -> Result<()> {
         }
     }
 
+    for gate in &workflow.gates {
+        validate_gate(gate)?;
+    }
+
     let gate_name_set: BTreeSet<&str> = workflow
         .gates
         .iter()
@@ -113,6 +654,10 @@ fn validate_workflow(workflow: &NormalizedWorkflow) -> Result<()> {
         }
     }
 
+    if let Some(taint) = &workflow.defaults.taint {
+        validate_taint_policy(workflow, taint)?;
+    }
+
     detect_cycle(workflow)?;
 
     Ok(())
@@ -123,12 +668,370 @@ fn validate_agent(agent: &AgentDefinition) -> Result<()> {
     ensure_non_empty("role", &agent.role)?;
     ensure_non_empty("provider_name", &agent.provider.provider_name)?;
     ensure_non_empty("model_id", &agent.provider.model_id)?;
+    for fallback in &agent.fallbacks {
+        ensure_non_empty("fallbacks.provider_name", &fallback.provider_name)?;
+        ensure_non_empty("fallbacks.model_id", &fallback.model_id)?;
+    }
+    if let Some(balancing) = &agent.load_balancing {
+        validate_load_balancing(agent, balancing)?;
+    }
+    agent
+        .permissions
+        .validate()
+        .map_err(|err| anyhow!("agent {}: {err}", agent.agent_name))?;
+    Ok(())
+}
+
+fn validate_load_balancing(
+    agent: &AgentDefinition,
+    balancing: &ProviderLoadBalancing,
+) -> Result<()> {
+    if balancing.endpoints.is_empty() {
+        return Err(anyhow!(
+            "agent {} declares load_balancing without endpoints",
+            agent.agent_name
+        ));
+    }
+    let weights = std::iter::once(("provider_weight", balancing.provider_weight)).chain(
+        balancing
+            .endpoints
+            .iter()
+            .map(|endpoint| ("endpoints.weight", endpoint.weight)),
+    );
+    for (field, weight) in weights {
+        if !(1..=MAX_ENDPOINT_WEIGHT).contains(&weight) {
+            return Err(anyhow!(
+                "agent {} has load_balancing.{field} {weight}; it must be between 1 and \
+                 {MAX_ENDPOINT_WEIGHT}",
+                agent.agent_name
+            ));
+        }
+    }
+    for endpoint in &balancing.endpoints {
+        ensure_non_empty("endpoints.provider_name", &endpoint.provider_name)?;
+        ensure_non_empty("endpoints.model_id", &endpoint.model_id)?;
+    }
+    Ok(())
+}
+
+fn validate_persona(persona: &PersonaDefinition) -> Result<()> {
+    ensure_non_empty("persona_name", &persona.persona_name)?;
+    ensure_non_empty("persona_version", &persona.persona_version)?;
+    ensure_non_empty("system_prompt", &persona.system_prompt)?;
     Ok(())
 }
 
+/// Agents `step` invokes: its agent, or the voting agents and judge of a `vote` step.
+fn step_agents(step: &WorkflowStepDefinition) -> Vec<&str> {
+    match (&step.workflow_call, &step.vote) {
+        (Some(_), _) => Vec::new(),
+        (None, Some(vote)) => vote
+            .agents
+            .iter()
+            .chain(&vote.judge_agent)
+            .map(String::as_str)
+            .collect(),
+        (None, None) => vec![step.agent_name.as_str()],
+    }
+}
+
 fn validate_step(step: &WorkflowStepDefinition) -> Result<()> {
     ensure_non_empty("step_key", &step.step_key)?;
-    ensure_non_empty("agent_name", &step.agent_name)?;
+    match (&step.workflow_call, &step.vote) {
+        (Some(call), _) => validate_workflow_call(step, call)?,
+        (None, Some(vote)) => validate_vote(step, vote)?,
+        (None, None) => ensure_non_empty("agent_name", &step.agent_name)?,
+    }
+    if step.step_key.contains(['[', ']', '#', '@']) {
+        return Err(anyhow!(
+            "step key {} may not contain `[`, `]`, `#`, or `@`, which foreach child steps, \
+             iteration steps, and vote candidate steps use",
+            step.step_key
+        ));
+    }
+    if let Some(prompt_ref) = &step.prompt_ref {
+        parse_prompt_ref(prompt_ref)?;
+    }
+    if let Some(pattern) = step.scoring.as_ref().and_then(|spec| spec.regex.as_deref()) {
+        regex::Regex::new(pattern)
+            .map_err(|err| anyhow!("step {} has invalid scoring.regex: {err}", step.step_key))?;
+    }
+    for dependency in &step.memory_dependencies {
+        dependency
+            .validate()
+            .map_err(|err| anyhow!("step {}: {err}", step.step_key))?;
+    }
+    if let Some(when) = &step.when {
+        let condition =
+            StepCondition::parse(when).map_err(|err| anyhow!("step {}: {err}", step.step_key))?;
+        if !step.depends_on.contains(&condition.step_key) {
+            return Err(anyhow!(
+                "step {} when condition references step {}, which is not in its depends_on",
+                step.step_key,
+                condition.step_key
+            ));
+        }
+    }
+    if let Some(foreach) = &step.foreach {
+        validate_foreach(step, foreach)?;
+    }
+    if let Some(iterate) = &step.iterate {
+        validate_iterate(step, iterate)?;
+    }
+    if step.constraints.timeout_seconds == Some(0) {
+        return Err(anyhow!(
+            "step {} has constraints.timeout_seconds 0; it must be positive",
+            step.step_key
+        ));
+    }
+    Ok(())
+}
+
+fn validate_foreach(step: &WorkflowStepDefinition, foreach: &str) -> Result<()> {
+    let source =
+        StepForeach::parse(foreach).map_err(|err| anyhow!("step {}: {err}", step.step_key))?;
+    if !step.depends_on.contains(&source.step_key) {
+        return Err(anyhow!(
+            "step {} foreach source references step {}, which is not in its depends_on",
+            step.step_key,
+            source.step_key
+        ));
+    }
+    let reserved = match &step.task {
+        Value::Object(fields) => fields.contains_key("item") || fields.contains_key("item_index"),
+        _ => false,
+    };
+    if reserved {
+        return Err(anyhow!(
+            "step {} runs foreach, so its task may not define `item` or `item_index`",
+            step.step_key
+        ));
+    }
+    Ok(())
+}
+
+fn validate_iterate(step: &WorkflowStepDefinition, iterate: &StepIteration) -> Result<()> {
+    if !(1..=MAX_STEP_ITERATIONS).contains(&iterate.max_iterations) {
+        return Err(anyhow!(
+            "step {} has iterate.max_iterations {}; it must be between 1 and \
+             {MAX_STEP_ITERATIONS}",
+            step.step_key,
+            iterate.max_iterations
+        ));
+    }
+    StepConvergence::parse(&iterate.until)
+        .map_err(|err| anyhow!("step {}: {err}", step.step_key))?;
+    if step.foreach.is_some() {
+        return Err(anyhow!(
+            "step {} may declare foreach or iterate, not both",
+            step.step_key
+        ));
+    }
+    let reserved = match &step.task {
+        Value::Object(fields) => {
+            fields.contains_key("iteration") || fields.contains_key("previous_output")
+        }
+        _ => false,
+    };
+    if reserved {
+        return Err(anyhow!(
+            "step {} iterates, so its task may not define `iteration` or `previous_output`",
+            step.step_key
+        ));
+    }
+    Ok(())
+}
+
+fn validate_vote(step: &WorkflowStepDefinition, vote: &StepVote) -> Result<()> {
+    if vote.agents.len() < 2 {
+        return Err(anyhow!(
+            "step {} votes, so vote.agents must name at least two agents",
+            step.step_key
+        ));
+    }
+    let mut agents = BTreeSet::new();
+    for agent in &vote.agents {
+        ensure_non_empty("vote.agents", agent)?;
+        if !agents.insert(agent.as_str()) {
+            return Err(anyhow!(
+                "step {} vote.agents lists agent {agent} twice",
+                step.step_key
+            ));
+        }
+    }
+    match (vote.aggregator, &vote.judge_agent) {
+        (VoteAggregator::Judge, None) => {
+            return Err(anyhow!(
+                "step {} votes with the judge aggregator, so vote.judge_agent is required",
+                step.step_key
+            ));
+        }
+        (VoteAggregator::Majority | VoteAggregator::HumanGate, Some(_)) => {
+            return Err(anyhow!(
+                "step {} declares vote.judge_agent but does not use the judge aggregator",
+                step.step_key
+            ));
+        }
+        _ => {}
+    }
+    if vote.ballot.is_some() {
+        if vote.aggregator != VoteAggregator::Majority {
+            return Err(anyhow!(
+                "step {} declares vote.ballot but does not use the majority aggregator",
+                step.step_key
+            ));
+        }
+        vote.ballot(&Value::Null)
+            .map_err(|err| anyhow!("step {}: {err}", step.step_key))?;
+    }
+    let reserved = match &step.task {
+        Value::Object(fields) => fields.contains_key("candidates"),
+        _ => false,
+    };
+    if reserved && vote.aggregator == VoteAggregator::Judge {
+        return Err(anyhow!(
+            "step {} is judged, so its task may not define `candidates`",
+            step.step_key
+        ));
+    }
+    if !step.agent_name.is_empty() || step.foreach.is_some() || step.iterate.is_some() {
+        return Err(anyhow!(
+            "step {} votes, so it may not declare agent_name, foreach, or iterate",
+            step.step_key
+        ));
+    }
+    Ok(())
+}
+
+fn validate_workflow_call(step: &WorkflowStepDefinition, call: &StepWorkflowCall) -> Result<()> {
+    let Some(callee) = call.workflow.as_deref() else {
+        return Err(if call.path.is_empty() {
+            anyhow!(
+                "step {} workflow_call must name a path or a workflow",
+                step.step_key
+            )
+        } else {
+            anyhow!(
+                "step {} calls workflow {}, which was not resolved; load the calling workflow \
+                 from its file",
+                step.step_key,
+                call.path
+            )
+        });
+    };
+    let empty_task = match &step.task {
+        Value::Null => true,
+        Value::Object(fields) => fields.is_empty(),
+        _ => false,
+    };
+    if !step.agent_name.is_empty()
+        || !empty_task
+        || step.persona.is_some()
+        || step.prompt_ref.is_some()
+        || step.scoring.is_some()
+        || !step.memory_dependencies.is_empty()
+        || !step.gate_points.is_empty()
+        || step.foreach.is_some()
+        || step.iterate.is_some()
+        || step.vote.is_some()
+    {
+        return Err(anyhow!(
+            "step {} calls a workflow, so it may not declare agent_name, task, persona, \
+             prompt_ref, scoring, memory_dependencies, gate_points, foreach, iterate, or vote",
+            step.step_key
+        ));
+    }
+    if workflow_call_depth(callee) >= MAX_WORKFLOW_CALL_DEPTH {
+        return Err(anyhow!(
+            "step {} nests workflow calls more than {MAX_WORKFLOW_CALL_DEPTH} deep",
+            step.step_key
+        ));
+    }
+    validate_workflow(callee)
+        .map_err(|err| anyhow!("step {} called workflow: {err}", step.step_key))
+}
+
+/// How many levels of `workflow_call` steps `workflow` nests below itself.
+fn workflow_call_depth(workflow: &NormalizedWorkflow) -> usize {
+    workflow
+        .steps
+        .iter()
+        .filter_map(|step| step.workflow_call.as_ref()?.workflow.as_deref())
+        .map(|callee| workflow_call_depth(callee) + 1)
+        .max()
+        .unwrap_or(0)
+}
+
+fn validate_gate(gate: &GatePointDefinition) -> Result<()> {
+    if !gate.auto_approve.is_empty() && gate.gate_kind != GateKind::Human {
+        return Err(anyhow!(
+            "gate {} declares auto_approve rules but is not a human gate",
+            gate.gate_name
+        ));
+    }
+    if gate.on_non_interactive.is_some() && gate.gate_kind != GateKind::Human {
+        return Err(anyhow!(
+            "gate {} declares on_non_interactive but is not a human gate",
+            gate.gate_name
+        ));
+    }
+    if !gate.approvers.is_empty() && gate.gate_kind != GateKind::Human {
+        return Err(anyhow!(
+            "gate {} declares approvers but is not a human gate",
+            gate.gate_name
+        ));
+    }
+    for approver in &gate.approvers {
+        ensure_non_empty("approvers", approver)?;
+    }
+    if let Some(webhook) = &gate.webhook {
+        if gate.gate_kind != GateKind::Human {
+            return Err(anyhow!(
+                "gate {} declares a webhook but is not a human gate",
+                gate.gate_name
+            ));
+        }
+        if !(webhook.url.starts_with("http://") || webhook.url.starts_with("https://")) {
+            return Err(anyhow!(
+                "gate {} webhook url must be http:// or https://",
+                gate.gate_name
+            ));
+        }
+        ensure_non_empty("webhook.secret_env", &webhook.secret_env)?;
+    }
+    for rule in &gate.auto_approve {
+        ensure_non_empty("auto_approve.rule_name", &rule.rule_name)?;
+        for status in &rule.trust_statuses {
+            if !matches!(status.as_str(), "active" | "validated" | "retired") {
+                return Err(anyhow!(
+                    "gate {} rule {} has unsupported trust status `{status}`; expected one of active|validated|retired",
+                    gate.gate_name,
+                    rule.rule_name
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn validate_taint_policy(workflow: &NormalizedWorkflow, taint: &TaintPolicy) -> Result<()> {
+    taint.validate()?;
+    let elevated = workflow
+        .gates
+        .iter()
+        .find(|gate| gate.gate_name == taint.elevated_gate)
+        .ok_or_else(|| {
+            anyhow!(
+                "defaults.taint.elevated_gate references unknown gate {}",
+                taint.elevated_gate
+            )
+        })?;
+    if elevated.gate_kind != GateKind::Human {
+        return Err(anyhow!(
+            "defaults.taint.elevated_gate {} must be a human gate",
+            taint.elevated_gate
+        ));
+    }
     Ok(())
 }
 
@@ -144,24 +1047,43 @@ fn normalize_workflow(workflow: &mut NormalizedWorkflow) {
             .allowed_record_types
             .sort_by(|lhs, rhs| lhs.as_str().cmp(rhs.as_str()));
         agent.permissions.allowed_tools.sort();
+        agent.permissions.allowed_record_tags.sort();
+        agent.permissions.denied_record_tags.sort();
         agent.default_instructions.sort();
         let ordered_metadata: BTreeMap<String, String> =
             agent.metadata.clone().into_iter().collect();
         agent.metadata = ordered_metadata;
     }
 
+    workflow
+        .personas
+        .sort_by(|lhs, rhs| lhs.persona_name.cmp(&rhs.persona_name));
+
     workflow
         .gates
         .sort_by(|lhs, rhs| lhs.gate_name.cmp(&rhs.gate_name));
     for gate in &mut workflow.gates {
         gate.gate_name = gate.gate_name.trim().to_string();
     }
+    if let Some(taint) = workflow.defaults.taint.as_mut() {
+        taint.sensitive_sources.sort();
+        taint.sensitive_sources.dedup();
+        taint.sensitive_tags.sort();
+        taint.sensitive_tags.dedup();
+    }
 
     for step in &mut workflow.steps {
         step.depends_on.sort();
         step.depends_on.dedup();
         step.gate_points.sort();
         step.gate_points.dedup();
+        if let Some(callee) = step
+            .workflow_call
+            .as_mut()
+            .and_then(|call| call.workflow.as_deref_mut())
+        {
+            normalize_workflow(callee);
+        }
     }
 }
 
@@ -209,7 +1131,12 @@ fn detect_cycle(workflow: &NormalizedWorkflow) -> Result<()> {
 
 #[cfg(test)]
 mod tests {
-    use super::normalize_workflow_yaml;
+    use super::{diff_workflows, load_workflow_from_path, normalize_workflow_yaml};
+    use multi_agent_center_domain::{
+        MissingDependencyAction, NormalizedWorkflowEnvelope, StepIteration, StepVote,
+        VoteAggregator, WorkflowChangeCategory,
+    };
+    use std::fs;
 
     #[test]
     fn normalize_hash_is_stable() {
@@ -248,4 +1175,708 @@ defaults:
             _ => unreachable!(),
         }
     }
+
+    #[test]
+    fn auto_approve_rules_require_human_gates_and_known_trust_statuses() {
+        let workflow = |gate_kind: &str, status: &str| {
+            format!(
+                r"
+workflow_name: test
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: b
+    role: r
+    provider:
+      provider_name: mock
+      model_id: m
+steps:
+  - step_key: s1
+    agent_name: b
+    depends_on: []
+    gate_points: [review]
+gates:
+  - gate_name: review
+    gate_kind: {gate_kind}
+    auto_approve:
+      - {{ rule_name: low_risk, trust_statuses: [{status}] }}
+"
+            )
+        };
+
+        assert!(normalize_workflow_yaml(&workflow("human", "validated")).is_ok());
+        let policy_gate = normalize_workflow_yaml(&workflow("policy", "validated"));
+        assert!(policy_gate.is_err_and(|err| err.to_string().contains("not a human gate")));
+        let unknown_status = normalize_workflow_yaml(&workflow("human", "trusted"));
+        assert!(unknown_status.is_err_and(|err| err
+            .to_string()
+            .contains("unsupported trust status `trusted`")));
+    }
+
+    #[test]
+    fn personas_are_hashed_into_workflow_and_references_validated() {
+        let workflow = |system_prompt: &str, agent_persona: &str| {
+            format!(
+                r"
+workflow_name: test
+workflow_version: v1
+normalization_version: 0
+personas:
+  - persona_name: terse
+    persona_version: v1
+    system_prompt: {system_prompt}
+    style_constraints: [no_preamble]
+agents:
+  - agent_name: b
+    role: r
+    provider:
+      provider_name: mock
+      model_id: m
+    persona: {agent_persona}
+steps:
+  - step_key: s1
+    agent_name: b
+    depends_on: []
+    gate_points: []
+"
+            )
+        };
+
+        let first = normalize_workflow_yaml(&workflow("Be brief.", "terse"));
+        let second = normalize_workflow_yaml(&workflow("Be thorough.", "terse"));
+        match (first, second) {
+            (Ok(first), Ok(second)) => {
+                assert_eq!(first.normalized_workflow.personas.len(), 1);
+                assert_ne!(first.normalized_hash, second.normalized_hash);
+            }
+            _ => unreachable!(),
+        }
+
+        let unknown = normalize_workflow_yaml(&workflow("Be brief.", "verbose"));
+        assert!(unknown.is_err_and(|err| err.to_string().contains("unknown persona verbose")));
+    }
+
+    #[test]
+    fn memory_dependency_patterns_are_validated() {
+        let workflow = |pattern: &str| {
+            format!(
+                r#"
+workflow_name: test
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: b
+    role: r
+    provider:
+      provider_name: mock
+      model_id: m
+steps:
+  - step_key: s1
+    agent_name: b
+    depends_on: []
+    gate_points: []
+    memory_dependencies:
+      - memory_id: "{pattern}"
+        on_missing: warn
+"#
+            )
+        };
+
+        let exact = normalize_workflow_yaml(&workflow("01JABCDEFGHJKMNPQRSTVWXYZ0"));
+        assert!(exact.is_ok_and(|envelope| {
+            envelope.normalized_workflow.steps[0].memory_dependencies[0].on_missing
+                == MissingDependencyAction::Warn
+        }));
+        assert!(normalize_workflow_yaml(&workflow("01JABC*")).is_ok());
+        for invalid in ["", "*", "01J*ABC", "not-a-ulid"] {
+            let result = normalize_workflow_yaml(&workflow(invalid));
+            assert!(
+                result.is_err_and(|err| err.to_string().contains("step s1")),
+                "pattern {invalid:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn when_conditions_must_parse_and_reference_a_dependency() {
+        let workflow = |depends_on: &str, when: &str| {
+            format!(
+                r"
+workflow_name: test
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: b
+    role: r
+    provider:
+      provider_name: mock
+      model_id: m
+steps:
+  - step_key: triage
+    agent_name: b
+    depends_on: []
+    gate_points: []
+  - step_key: billing
+    agent_name: b
+    depends_on: [{depends_on}]
+    gate_points: []
+    when: '{when}'
+"
+            )
+        };
+
+        let valid = normalize_workflow_yaml(&workflow(
+            "triage",
+            r#"steps.triage.payload.route == "billing""#,
+        ));
+        assert!(valid.is_ok_and(|envelope| {
+            envelope.normalized_workflow.steps[1].when.as_deref()
+                == Some(r#"steps.triage.payload.route == "billing""#)
+        }));
+        let not_a_dependency =
+            normalize_workflow_yaml(&workflow("", "steps.triage.payload.route != null"));
+        assert!(
+            not_a_dependency.is_err_and(|err| err.to_string().contains("not in its depends_on"))
+        );
+        for invalid in [
+            "steps.triage == true",
+            "payload.route == 1",
+            "steps.triage.payload.route > 1",
+            "steps.triage.payload.route == [1]",
+        ] {
+            let result = normalize_workflow_yaml(&workflow("triage", invalid));
+            assert!(
+                result.is_err_and(|err| err.to_string().contains("step billing")),
+                "condition {invalid:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn foreach_sources_must_parse_and_reference_a_dependency() {
+        let workflow = |depends_on: &str, foreach: &str, task: &str| {
+            format!(
+                r"
+workflow_name: test
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: b
+    role: r
+    provider:
+      provider_name: mock
+      model_id: m
+steps:
+  - step_key: plan
+    agent_name: b
+    depends_on: []
+    gate_points: []
+  - step_key: review
+    agent_name: b
+    depends_on: [{depends_on}]
+    gate_points: []
+    task: {task}
+    foreach: '{foreach}'
+"
+            )
+        };
+
+        let valid = normalize_workflow_yaml(&workflow(
+            "plan",
+            "steps.plan.payload.tickets",
+            "{kind: review}",
+        ));
+        assert!(valid.is_ok_and(|envelope| {
+            envelope.normalized_workflow.steps[1].foreach.as_deref()
+                == Some("steps.plan.payload.tickets")
+        }));
+        let not_a_dependency =
+            normalize_workflow_yaml(&workflow("", "steps.plan.payload.tickets", "{}"));
+        assert!(
+            not_a_dependency.is_err_and(|err| err.to_string().contains("not in its depends_on"))
+        );
+        let reserved =
+            normalize_workflow_yaml(&workflow("plan", "steps.plan.payload.tickets", "{item: 1}"));
+        assert!(reserved.is_err_and(|err| err.to_string().contains("`item`")));
+        for invalid in [
+            "steps.plan",
+            "payload.tickets",
+            "steps.plan.payload.tickets == 1",
+        ] {
+            let result = normalize_workflow_yaml(&workflow("plan", invalid, "{}"));
+            assert!(
+                result.is_err_and(|err| err.to_string().contains("step review")),
+                "foreach source {invalid:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn iterate_blocks_must_bound_iterations_and_parse_their_until_expression() {
+        let workflow = |iterate: &str, task: &str| {
+            format!(
+                r"
+workflow_name: test
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: b
+    role: r
+    provider:
+      provider_name: mock
+      model_id: m
+steps:
+  - step_key: draft
+    agent_name: b
+    depends_on: []
+    gate_points: []
+    task: {task}
+    iterate: {iterate}
+"
+            )
+        };
+
+        let valid = normalize_workflow_yaml(&workflow(
+            "{max_iterations: 3, until: 'output.payload.approved == true'}",
+            "{kind: draft}",
+        ));
+        assert!(valid.is_ok_and(|envelope| {
+            envelope.normalized_workflow.steps[0].iterate
+                == Some(StepIteration {
+                    max_iterations: 3,
+                    until: "output.payload.approved == true".to_string(),
+                })
+        }));
+        let reserved = normalize_workflow_yaml(&workflow(
+            "{max_iterations: 3, until: 'output.payload.approved == true'}",
+            "{iteration: 1}",
+        ));
+        assert!(reserved.is_err_and(|err| err.to_string().contains("`iteration`")));
+        for invalid in [
+            "{max_iterations: 0, until: 'output.payload.approved == true'}",
+            "{max_iterations: 51, until: 'output.payload.approved == true'}",
+            "{max_iterations: 3, until: 'output == true'}",
+            "{max_iterations: 3, until: 'steps.draft.payload.approved == true'}",
+            "{max_iterations: 3, until: 'output.payload.approved'}",
+        ] {
+            let result = normalize_workflow_yaml(&workflow(invalid, "{}"));
+            assert!(
+                result.is_err_and(|err| err.to_string().contains("step draft")),
+                "iterate block {invalid:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn vote_blocks_must_name_known_agents_and_a_judge_only_when_judged() {
+        let workflow = |step: &str| {
+            format!(
+                r"
+workflow_name: test
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: a
+    role: r
+    provider: {{provider_name: mock, model_id: m}}
+  - agent_name: b
+    role: r
+    provider: {{provider_name: mock, model_id: m}}
+steps:
+  - step_key: pick
+    task: {{kind: classify}}
+    {step}
+"
+            )
+        };
+
+        let valid = normalize_workflow_yaml(&workflow(
+            "vote: {agents: [a, b], aggregator: judge, judge_agent: b}",
+        ));
+        assert!(valid.is_ok_and(|envelope| {
+            envelope.normalized_workflow.steps[0].vote
+                == Some(StepVote {
+                    agents: vec!["a".to_string(), "b".to_string()],
+                    aggregator: VoteAggregator::Judge,
+                    judge_agent: Some("b".to_string()),
+                    ballot: None,
+                })
+        }));
+        assert!(normalize_workflow_yaml(&workflow(
+            "vote: {agents: [a, b], ballot: output.payload.label}"
+        ))
+        .is_ok());
+        let unknown = normalize_workflow_yaml(&workflow("vote: {agents: [a, c]}"));
+        assert!(unknown.is_err_and(|err| err.to_string().contains("unknown agent c")));
+        for invalid in [
+            "vote: {agents: [a]}",
+            "vote: {agents: [a, a]}",
+            "vote: {agents: [a, b], aggregator: judge}",
+            "vote: {agents: [a, b], judge_agent: b}",
+            "vote: {agents: [a, b], aggregator: human_gate, ballot: output.payload}",
+            "vote: {agents: [a, b], ballot: payload.label}",
+            "agent_name: a\n    vote: {agents: [a, b]}",
+        ] {
+            let result = normalize_workflow_yaml(&workflow(invalid));
+            assert!(
+                result.is_err_and(|err| err.to_string().contains("step pick")),
+                "vote block {invalid:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn load_balancing_requires_endpoints_with_bounded_weights() {
+        let workflow = |balancing: &str| {
+            format!(
+                r"
+workflow_name: test
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: a
+    role: r
+    provider: {{provider_name: mock, model_id: m}}
+    load_balancing: {balancing}
+steps:
+  - step_key: s
+    agent_name: a
+    task: {{}}
+"
+            )
+        };
+
+        let valid = normalize_workflow_yaml(&workflow(
+            "{strategy: weighted, endpoints: [{provider_name: mock, model_id: m2, weight: 3}]}",
+        ));
+        assert!(valid.is_ok_and(|envelope| {
+            envelope.normalized_workflow.agents[0]
+                .load_balancing
+                .as_ref()
+                .is_some_and(|balancing| {
+                    balancing.provider_weight == 1 && balancing.endpoints[0].weight == 3
+                })
+        }));
+        for invalid in [
+            "{endpoints: []}",
+            "{endpoints: [{provider_name: mock, model_id: m2, weight: 0}]}",
+            "{provider_weight: 1001, endpoints: [{provider_name: mock, model_id: m2}]}",
+            "{endpoints: [{provider_name: mock, model_id: ''}]}",
+        ] {
+            assert!(
+                normalize_workflow_yaml(&workflow(invalid)).is_err(),
+                "load_balancing {invalid:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn workflow_calls_load_relative_to_the_calling_file_and_reject_cycles() {
+        let workflow = |name: &str, call: &str| {
+            format!(
+                r"
+workflow_name: {name}
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: b
+    role: r
+    provider:
+      provider_name: mock
+      model_id: m
+steps:
+  - step_key: work
+    agent_name: b
+    task: {{kind: work}}
+  - step_key: nested
+    depends_on: [work]
+    {call}
+"
+            )
+        };
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos());
+        let dir =
+            std::env::temp_dir().join(format!("mac-workflow-calls-{}-{nanos}", std::process::id()));
+        assert!(fs::create_dir_all(dir.join("children")).is_ok());
+        let write =
+            |file: &str, content: String| assert!(fs::write(dir.join(file), content).is_ok());
+        write(
+            "children/leaf.yaml",
+            workflow("leaf", "").replace("  - step_key: nested\n    depends_on: [work]\n", ""),
+        );
+        write(
+            "parent.yaml",
+            workflow("parent", "workflow_call: {path: children/leaf.yaml}"),
+        );
+        write(
+            "loop.yaml",
+            workflow("loop", "workflow_call: {path: loop.yaml}"),
+        );
+        write(
+            "agent.yaml",
+            workflow(
+                "agent",
+                "agent_name: b\n    workflow_call: {path: children/leaf.yaml}",
+            ),
+        );
+
+        let parent = load_workflow_from_path(&dir.join("parent.yaml"));
+        assert!(parent.is_ok_and(|envelope| {
+            envelope.normalized_workflow.steps[1]
+                .workflow_call
+                .as_ref()
+                .and_then(|call| call.workflow.as_deref())
+                .is_some_and(|callee| callee.workflow_name == "leaf")
+        }));
+        let looped = load_workflow_from_path(&dir.join("loop.yaml"));
+        assert!(looped.is_err_and(|err| err.to_string().contains("cycle")));
+        let agent = load_workflow_from_path(&dir.join("agent.yaml"));
+        assert!(agent.is_err_and(|err| err.to_string().contains("step nested calls a workflow")));
+        let unresolved = normalize_workflow_yaml(&workflow(
+            "inline",
+            "workflow_call: {path: children/leaf.yaml}",
+        ));
+        assert!(unresolved.is_err_and(|err| err.to_string().contains("was not resolved")));
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn tag_dependencies_and_permission_tags_are_validated() {
+        let workflow = |denied_tag: &str, dependency: &str| {
+            format!(
+                r#"
+workflow_name: test
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: b
+    role: r
+    provider:
+      provider_name: mock
+      model_id: m
+    permissions:
+      denied_record_tags: ["{denied_tag}"]
+steps:
+  - step_key: s1
+    agent_name: b
+    depends_on: []
+    gate_points: []
+    memory_dependencies:
+      - {{ {dependency} }}
+"#
+            )
+        };
+
+        let valid = normalize_workflow_yaml(&workflow("billing", "tag: deploy"));
+        assert!(valid.is_ok_and(|envelope| {
+            envelope.normalized_workflow.steps[0].memory_dependencies[0]
+                .tag
+                .as_deref()
+                == Some("deploy")
+        }));
+        assert!(normalize_workflow_yaml(&workflow("Billing", "tag: deploy"))
+            .is_err_and(|err| err.to_string().contains("agent b")));
+        for invalid in [
+            "tag: Deploy",
+            "tag: deploy, memory_id: \"01JABCDEFGHJKMNPQRSTVWXYZ0\"",
+        ] {
+            let result = normalize_workflow_yaml(&workflow("billing", invalid));
+            assert!(
+                result.is_err_and(|err| err.to_string().contains("step s1")),
+                "dependency {invalid:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn taint_policy_requires_a_human_elevated_gate() {
+        let workflow = |gate_kind: &str, taint: &str| {
+            format!(
+                r"
+workflow_name: test
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: b
+    role: r
+    provider:
+      provider_name: mock
+      model_id: m
+steps:
+  - step_key: s1
+    agent_name: b
+    depends_on: []
+    gate_points: []
+gates:
+  - gate_name: security_review
+    gate_kind: {gate_kind}
+defaults:
+  taint: {{ {taint} }}
+"
+            )
+        };
+
+        let valid = normalize_workflow_yaml(&workflow(
+            "human",
+            "sensitive_tags: [pii, pii], elevated_gate: security_review",
+        ));
+        assert!(valid.is_ok_and(|envelope| {
+            envelope
+                .normalized_workflow
+                .defaults
+                .taint
+                .is_some_and(|taint| taint.sensitive_tags == vec!["pii".to_string()])
+        }));
+        let policy_gate = normalize_workflow_yaml(&workflow(
+            "policy",
+            "sensitive_sources: [hr], elevated_gate: security_review",
+        ));
+        assert!(policy_gate.is_err_and(|err| err.to_string().contains("must be a human gate")));
+        for (taint, message) in [
+            (
+                "sensitive_tags: [pii], elevated_gate: review",
+                "unknown gate review",
+            ),
+            (
+                "elevated_gate: security_review",
+                "must list sensitive_sources",
+            ),
+            (
+                "sensitive_tags: [PII], elevated_gate: security_review",
+                "defaults.taint",
+            ),
+        ] {
+            let result = normalize_workflow_yaml(&workflow("human", taint));
+            assert!(
+                result.is_err_and(|err| err.to_string().contains(message)),
+                "taint {taint:?} should be rejected"
+            );
+        }
+    }
+
+    fn upgrade_fixture() -> (NormalizedWorkflowEnvelope, NormalizedWorkflowEnvelope) {
+        let old = normalize_workflow_yaml(
+            r"
+workflow_name: test
+workflow_version: v1
+normalization_version: 0
+agents:
+  - agent_name: a
+    role: r
+    provider:
+      provider_name: mock
+      model_id: m
+steps:
+  - step_key: s1
+    agent_name: a
+    task: { text: one }
+  - step_key: s2
+    agent_name: a
+    depends_on: [s1]
+  - step_key: s3
+    agent_name: a
+gates:
+  - gate_name: review
+    gate_kind: human
+",
+        )
+        .unwrap_or_else(|_| unreachable!());
+        let new = normalize_workflow_yaml(
+            r"
+workflow_name: test
+workflow_version: v2
+normalization_version: 0
+agents:
+  - agent_name: a
+    role: r
+    provider:
+      provider_name: mock
+      model_id: m2
+    permissions:
+      max_context_items: 3
+steps:
+  - step_key: s1
+    agent_name: a
+    task: { text: one }
+    gate_points: [review]
+  - step_key: s2
+    agent_name: a
+    depends_on: [s1]
+  - step_key: s4
+    agent_name: a
+    depends_on: [s2]
+gates:
+  - gate_name: review
+    gate_kind: human
+    required: true
+",
+        )
+        .unwrap_or_else(|_| unreachable!());
+        (old, new)
+    }
+
+    #[test]
+    fn diff_reports_structural_changes_and_downstream_affected_steps() {
+        let (old, new) = upgrade_fixture();
+        let diff = diff_workflows(&old, &new);
+        assert_eq!(diff.old_workflow_hash, old.normalized_hash);
+        let categories: Vec<(WorkflowChangeCategory, &str)> = diff
+            .changes
+            .iter()
+            .map(|change| (change.category, change.subject.as_str()))
+            .collect();
+        assert_eq!(
+            categories,
+            vec![
+                (WorkflowChangeCategory::StepChanged, "s1"),
+                (WorkflowChangeCategory::StepAdded, "s4"),
+                (WorkflowChangeCategory::StepRemoved, "s3"),
+                (WorkflowChangeCategory::ProviderChanged, "a"),
+                (WorkflowChangeCategory::PermissionsChanged, "a"),
+                (WorkflowChangeCategory::GateChanged, "review"),
+            ]
+        );
+        assert_eq!(diff.changes[0].field.as_deref(), Some("gate_points"));
+
+        let affected: Vec<(&str, Vec<&str>)> = diff
+            .affected_steps
+            .iter()
+            .map(|step| {
+                (
+                    step.step_key.as_str(),
+                    step.reasons.iter().map(String::as_str).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            affected,
+            vec![
+                (
+                    "s1",
+                    vec![
+                        "step.gate_points",
+                        "agent.provider",
+                        "agent.permissions",
+                        "gate.review"
+                    ]
+                ),
+                (
+                    "s2",
+                    vec!["agent.provider", "agent.permissions", "upstream.s1"]
+                ),
+                (
+                    "s4",
+                    vec![
+                        "added",
+                        "agent.provider",
+                        "agent.permissions",
+                        "upstream.s2"
+                    ]
+                ),
+                ("s3", vec!["removed"]),
+            ]
+        );
+    }
 }
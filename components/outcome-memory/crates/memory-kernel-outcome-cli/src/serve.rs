@@ -0,0 +1,552 @@
+//! `mk serve`: the v1 embed surface (log, gate preview, trust get, projector status/check,
+//! events list) as a small HTTP JSON API for hosts that cannot link the crate.
+//!
+//! Every response is a `outcome_http.v1` envelope: `{"contract_version", "data"}` on success
+//! and `{"contract_version", "error": {"code", "message"}}` otherwise. `data` carries the same
+//! payload the matching CLI command prints as JSON.
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use axum::extract::rejection::{JsonRejection, QueryRejection};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use memory_kernel_outcome_core::{parse_rfc3339_utc, MemoryKey, OutcomeError, RetrievalMode};
+use memory_kernel_outcome_store_sqlite::{SqliteOutcomeStore, SqlitePragmas, StaleSnapshotPolicy};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    build_gate_preview_json_payload, deliver_queued_notifications, log_line_input, parse_memory_id,
+    LogBatchLine,
+};
+
+pub const HTTP_CONTRACT_VERSION: &str = "outcome_http.v1";
+
+#[derive(Debug, Clone)]
+pub(crate) struct ServeState {
+    db_path: PathBuf,
+    pragmas: SqlitePragmas,
+}
+
+impl ServeState {
+    pub(crate) fn new(db_path: PathBuf, pragmas: SqlitePragmas) -> Self {
+        Self { db_path, pragmas }
+    }
+
+    /// Run `operation` against a freshly opened store on the blocking pool; the schema is
+    /// migrated once at startup.
+    async fn with_store<T, F>(&self, operation: F) -> Result<T, ServeError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut SqliteOutcomeStore) -> Result<T, ServeError> + Send + 'static,
+    {
+        let state = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut store = SqliteOutcomeStore::open_with_pragmas(&state.db_path, &state.pragmas)
+                .map_err(ServeError::from)?;
+            operation(&mut store)
+        })
+        .await
+        .map_err(|err| ServeError::from(anyhow!("outcome request task failed: {err}")))?
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Envelope<T: Serialize> {
+    contract_version: &'static str,
+    data: T,
+}
+
+fn envelope<T: Serialize>(data: T) -> Json<Envelope<T>> {
+    Json(Envelope {
+        contract_version: HTTP_CONTRACT_VERSION,
+        data,
+    })
+}
+
+#[derive(Debug)]
+struct ServeError {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+impl ServeError {
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            code: "invalid_request",
+            message: message.into(),
+        }
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            code: "not_found",
+            message: message.into(),
+        }
+    }
+}
+
+impl From<anyhow::Error> for ServeError {
+    fn from(err: anyhow::Error) -> Self {
+        let validation = err.chain().any(|cause| {
+            matches!(
+                cause.downcast_ref::<OutcomeError>(),
+                Some(OutcomeError::Validation(_))
+            )
+        });
+        Self {
+            status: if validation {
+                StatusCode::UNPROCESSABLE_ENTITY
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            },
+            code: if validation {
+                "validation_error"
+            } else {
+                "internal_error"
+            },
+            message: format!("{err:#}"),
+        }
+    }
+}
+
+impl From<JsonRejection> for ServeError {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::bad_request(rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for ServeError {
+    fn from(rejection: QueryRejection) -> Self {
+        Self::bad_request(rejection.body_text())
+    }
+}
+
+impl IntoResponse for ServeError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({
+            "contract_version": HTTP_CONTRACT_VERSION,
+            "error": {
+                "code": self.code,
+                "message": self.message,
+            },
+        });
+        (self.status, Json(body)).into_response()
+    }
+}
+
+pub(crate) fn app(state: ServeState) -> Router {
+    Router::new()
+        .route("/v1/log", post(log))
+        .route("/v1/gate/preview", post(gate_preview))
+        .route("/v1/trust/:memory_id", get(trust_versions))
+        .route("/v1/trust/:memory_id/:version", get(trust_get))
+        .route("/v1/projector/status", get(projector_status))
+        .route("/v1/projector/check", get(projector_check))
+        .route("/v1/events", get(events_list))
+        .with_state(state)
+}
+
+/// Serve the API on `listen` until the process is stopped.
+pub(crate) fn serve(listen: SocketAddr, state: ServeState) -> anyhow::Result<()> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(async move {
+        let listener = tokio::net::TcpListener::bind(listen).await?;
+        eprintln!("outcome API listening on http://{}", listener.local_addr()?);
+        axum::serve(listener, app(state)).await?;
+        Ok(())
+    })
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LogQuery {
+    #[serde(default)]
+    project: bool,
+}
+
+async fn log(
+    State(state): State<ServeState>,
+    query: Result<Query<LogQuery>, QueryRejection>,
+    payload: Result<Json<LogBatchLine>, JsonRejection>,
+) -> Result<Response, ServeError> {
+    let Query(query) = query?;
+    let Json(line) = payload?;
+    let input =
+        log_line_input(line, None).map_err(|err| ServeError::bad_request(format!("{err:#}")))?;
+    let event = state
+        .with_store(move |store| {
+            let event = if query.project {
+                store.append_event_and_project(&input)?
+            } else {
+                store.append_event(&input)?
+            };
+            deliver_queued_notifications(store)?;
+            Ok(event)
+        })
+        .await?;
+    Ok((StatusCode::CREATED, envelope(event)).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GatePreviewRequest {
+    mode: RetrievalMode,
+    as_of: String,
+    #[serde(default)]
+    context_id: Option<String>,
+    candidates: Vec<MemoryKey>,
+    #[serde(default)]
+    on_stale: Option<StaleSnapshotPolicy>,
+    #[serde(default)]
+    exclude_superseded: bool,
+}
+
+async fn gate_preview(
+    State(state): State<ServeState>,
+    payload: Result<Json<GatePreviewRequest>, JsonRejection>,
+) -> Result<Response, ServeError> {
+    let Json(request) = payload?;
+    if request.candidates.is_empty() {
+        return Err(ServeError::bad_request(
+            "at least one candidate is required",
+        ));
+    }
+    let as_of = parse_rfc3339_utc(&request.as_of)
+        .map_err(|err| ServeError::bad_request(format!("invalid as_of value: {err}")))?;
+    let payload = state
+        .with_store(move |store| {
+            let context_id = request.context_id.as_deref();
+            let mut decisions = match request.on_stale {
+                None => store.gate_preview(request.mode, as_of, context_id, &request.candidates)?,
+                Some(on_stale) => store.gate_preview_consistent(
+                    request.mode,
+                    as_of,
+                    context_id,
+                    &request.candidates,
+                    on_stale,
+                )?,
+            };
+            if request.exclude_superseded {
+                store.exclude_superseded(request.mode, as_of, context_id, &mut decisions)?;
+            }
+            Ok(build_gate_preview_json_payload(
+                request.mode,
+                as_of,
+                context_id,
+                &request.candidates,
+                &decisions,
+            )?)
+        })
+        .await?;
+    Ok(envelope(payload).into_response())
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TrustQuery {
+    #[serde(default)]
+    as_of: Option<String>,
+}
+
+async fn trust_get(
+    State(state): State<ServeState>,
+    Path((memory_id, version)): Path<(String, u32)>,
+    query: Result<Query<TrustQuery>, QueryRejection>,
+) -> Result<Response, ServeError> {
+    let Query(query) = query?;
+    let parsed_id =
+        parse_memory_id(&memory_id).map_err(|err| ServeError::bad_request(format!("{err:#}")))?;
+    let as_of = match query.as_of {
+        Some(raw) => Some(
+            parse_rfc3339_utc(&raw)
+                .map_err(|err| ServeError::bad_request(format!("invalid as_of value: {err}")))?,
+        ),
+        None => None,
+    };
+    let trust = state
+        .with_store(move |store| {
+            store
+                .get_memory_trust(parsed_id, version, as_of)?
+                .ok_or_else(|| {
+                    ServeError::not_found(format!(
+                        "trust snapshot not found for {memory_id}:{version}"
+                    ))
+                })
+        })
+        .await?;
+    Ok(envelope(trust).into_response())
+}
+
+/// Every version of a memory with its trust, `404` when the memory has no version at all.
+async fn trust_versions(
+    State(state): State<ServeState>,
+    Path(memory_id): Path<String>,
+    query: Result<Query<TrustQuery>, QueryRejection>,
+) -> Result<Response, ServeError> {
+    let Query(query) = query?;
+    let parsed_id =
+        parse_memory_id(&memory_id).map_err(|err| ServeError::bad_request(format!("{err:#}")))?;
+    let as_of = match query.as_of {
+        Some(raw) => Some(
+            parse_rfc3339_utc(&raw)
+                .map_err(|err| ServeError::bad_request(format!("invalid as_of value: {err}")))?,
+        ),
+        None => None,
+    };
+    let history = state
+        .with_store(move |store| {
+            let history = store.memory_trust_versions(parsed_id, as_of)?;
+            if history.versions.is_empty() {
+                return Err(ServeError::not_found(format!(
+                    "no versions found for memory {memory_id}"
+                )));
+            }
+            Ok(history)
+        })
+        .await?;
+    Ok(envelope(history).into_response())
+}
+
+async fn projector_status(State(state): State<ServeState>) -> Result<Response, ServeError> {
+    let status = state
+        .with_store(|store| Ok(store.projector_status()?))
+        .await?;
+    Ok(envelope(status).into_response())
+}
+
+/// Unlike `projector check`, an unhealthy projector is not an error: the report is returned
+/// with `healthy: false`.
+async fn projector_check(State(state): State<ServeState>) -> Result<Response, ServeError> {
+    let check = state
+        .with_store(|store| Ok(store.projector_check()?))
+        .await?;
+    Ok(envelope(check).into_response())
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EventsQuery {
+    #[serde(default)]
+    memory_id: Option<String>,
+    #[serde(default)]
+    version: Option<u32>,
+    #[serde(default)]
+    run_id: Option<String>,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+async fn events_list(
+    State(state): State<ServeState>,
+    query: Result<Query<EventsQuery>, QueryRejection>,
+) -> Result<Response, ServeError> {
+    let Query(query) = query?;
+    let events = match (query.run_id, query.memory_id, query.version) {
+        (Some(run_id), None, None) => {
+            state
+                .with_store(move |store| Ok(store.list_events_for_run(&run_id, query.limit)?))
+                .await?
+        }
+        (None, Some(memory_id), Some(version)) => {
+            let memory_id = parse_memory_id(&memory_id)
+                .map_err(|err| ServeError::bad_request(format!("{err:#}")))?;
+            state
+                .with_store(move |store| {
+                    Ok(store.list_events_for_key(memory_id, version, query.limit)?)
+                })
+                .await?
+        }
+        _ => {
+            return Err(ServeError::bad_request(
+                "events list requires run_id or memory_id and version",
+            ))
+        }
+    };
+    Ok(envelope(events).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::too_many_lines)]
+
+    use super::*;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    const MEMORY_ID: &str = "01J0SQQP7M70P6Y3R4T8D8G8M2";
+
+    async fn send(
+        router: &Router,
+        method: &str,
+        uri: &str,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(body.map_or_else(Body::empty, |body| Body::from(body.to_string())))
+            .unwrap_or_else(|err| panic!("failed to build request: {err}"));
+        let response = match router.clone().oneshot(request).await {
+            Ok(response) => response,
+            Err(err) => panic!("router request failed: {err}"),
+        };
+        let status = response.status();
+        let bytes = match to_bytes(response.into_body(), 1024 * 1024).await {
+            Ok(bytes) => bytes,
+            Err(err) => panic!("failed to read response body: {err}"),
+        };
+        match serde_json::from_slice(&bytes) {
+            Ok(value) => (status, value),
+            Err(err) => panic!("response body is not JSON: {err}"),
+        }
+    }
+
+    fn error_code(value: &Value) -> Option<&str> {
+        value.pointer("/error/code").and_then(Value::as_str)
+    }
+
+    #[tokio::test]
+    async fn serve_routes_cover_the_v1_embed_surface() {
+        let db_path =
+            std::env::temp_dir().join(format!("outcome-serve-{}.sqlite3", ulid::Ulid::new()));
+        let memory_id = match ulid::Ulid::from_string(MEMORY_ID) {
+            Ok(parsed) => memory_kernel_core::MemoryId(parsed),
+            Err(err) => panic!("invalid fixture ULID: {err}"),
+        };
+        let seeded = rusqlite::Connection::open(&db_path)
+            .map_err(anyhow::Error::from)
+            .and_then(|conn| {
+                memory_kernel_outcome_store_sqlite::seed_minimal_memory_record(&conn, memory_id, 1)
+            })
+            .and_then(|()| SqliteOutcomeStore::open(&db_path)?.migrate());
+        if let Err(err) = seeded {
+            panic!("failed to prepare store: {err}");
+        }
+        let router = app(ServeState::new(db_path.clone(), SqlitePragmas::default()));
+
+        let (status, logged) = send(
+            &router,
+            "POST",
+            "/v1/log?project=true",
+            Some(json!({
+                "memory_id": MEMORY_ID,
+                "version": 1,
+                "event": "success",
+                "writer": "http-host",
+                "justification": "served over http",
+                "occurred_at": "2026-02-07T12:00:00Z",
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED, "{logged}");
+        assert_eq!(logged["contract_version"], HTTP_CONTRACT_VERSION);
+        assert_eq!(logged["data"]["event_seq"], 1);
+        assert_eq!(logged["data"]["writer"], "http-host");
+
+        let (status, trust) = send(&router, "GET", &format!("/v1/trust/{MEMORY_ID}/1"), None).await;
+        assert_eq!(status, StatusCode::OK, "{trust}");
+        assert_eq!(trust["data"]["memory_id"], MEMORY_ID);
+        assert_eq!(trust["data"]["version"], 1);
+
+        let (status, versions) = send(
+            &router,
+            "GET",
+            &format!("/v1/trust/{MEMORY_ID}?as_of=2026-02-08T00:00:00Z"),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{versions}");
+        assert_eq!(versions["data"]["contract_version"], "trust_versions.v1");
+        assert_eq!(versions["data"]["current_version"], 1);
+        assert_eq!(versions["data"]["versions"][0]["trust"]["version"], 1);
+        let (status, unknown) =
+            send(&router, "GET", "/v1/trust/01J0SQQP7M70P6Y3R4T8D8G8M3", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(error_code(&unknown), Some("not_found"));
+
+        let (status, preview) = send(
+            &router,
+            "POST",
+            "/v1/gate/preview",
+            Some(json!({
+                "mode": "safe",
+                "as_of": "2026-02-07T13:00:00Z",
+                "candidates": [{"memory_id": MEMORY_ID, "version": 1}],
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{preview}");
+        assert_eq!(preview["data"]["contract_version"], "gate_preview.v1");
+        assert_eq!(
+            preview["data"]["decisions"].as_array().map(Vec::len),
+            Some(1)
+        );
+
+        let (status, projector) = send(&router, "GET", "/v1/projector/status", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(projector["data"]["contract_version"], "projector_status.v1");
+        let (status, check) = send(&router, "GET", "/v1/projector/check", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(check["data"]["contract_version"], "projector_check.v1");
+
+        let (status, events) = send(
+            &router,
+            "GET",
+            &format!("/v1/events?memory_id={MEMORY_ID}&version=1"),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(events["data"].as_array().map(Vec::len), Some(1));
+
+        let (status, missing) =
+            send(&router, "GET", &format!("/v1/trust/{MEMORY_ID}/2"), None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(error_code(&missing), Some("not_found"));
+
+        let (status, unscoped) = send(&router, "GET", "/v1/events", None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error_code(&unscoped), Some("invalid_request"));
+
+        let (status, manual) = send(
+            &router,
+            "POST",
+            "/v1/log",
+            Some(json!({
+                "memory_id": MEMORY_ID,
+                "version": 1,
+                "event": "manual_promote",
+                "writer": "http-host",
+                "justification": "not loggable",
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error_code(&manual), Some("invalid_request"));
+
+        let (status, malformed) = send(
+            &router,
+            "POST",
+            "/v1/gate/preview",
+            Some(json!({"mode": "safe"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(malformed["contract_version"], HTTP_CONTRACT_VERSION);
+
+        let _ = std::fs::remove_file(&db_path);
+    }
+}
@@ -197,6 +197,90 @@ fn benchmark_command_emits_contract_json_and_report_artifact() {
     let _ = std::fs::remove_file(&report_path);
 }
 
+#[test]
+fn benchmark_command_emits_csv_and_criterion_json_formats() {
+    let db_path = std::env::temp_dir().join(format!(
+        "outcome-contract-bench-format-{}.sqlite3",
+        Ulid::new()
+    ));
+    let bench_args = |format: &'static str| {
+        [
+            "outcome",
+            "benchmark",
+            "run",
+            "--volume",
+            "10",
+            "--repetitions",
+            "1",
+            "--gate-trust-rows",
+            "20",
+            "--gate-candidates",
+            "5",
+            "--gate-previews",
+            "2",
+            "--format",
+            format,
+        ]
+    };
+
+    let output = mk_output(&db_path, &bench_args("csv"));
+    assert!(
+        output.status.success(),
+        "csv benchmark failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let csv = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines.first().copied(),
+        Some("scenario,operation,size,candidates,repetitions,p50_ms,p95_ms,p99_ms,max_ms")
+    );
+    assert_eq!(lines.len(), 5);
+    assert!(lines[1].starts_with("volume,append,10,,1,"));
+    assert!(lines[4].starts_with("gating,gate_preview,20,5,2,"));
+    assert!(lines.iter().all(|line| line.split(',').count() == 9));
+
+    let output = mk_output(&db_path, &bench_args("criterion-json"));
+    assert!(
+        output.status.success(),
+        "criterion-json benchmark failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let messages: Vec<Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| match serde_json::from_str(line) {
+            Ok(value) => value,
+            Err(err) => panic!("criterion message is not json ({err}): {line}"),
+        })
+        .collect();
+    let ids: Vec<&str> = messages
+        .iter()
+        .filter_map(|message| message["id"].as_str())
+        .collect();
+    assert_eq!(
+        ids,
+        vec![
+            "outcome/append/10",
+            "outcome/replay/10",
+            "outcome/gate_preview/10",
+            "outcome/gate_trust_rows/20x5",
+        ]
+    );
+    for message in &messages {
+        assert_eq!(message["reason"], "benchmark-complete");
+        assert_eq!(message["typical"]["unit"], "ns");
+        assert!(message["typical"]["estimate"].is_number());
+    }
+
+    let conflicting = mk_output(
+        &db_path,
+        &["outcome", "benchmark", "run", "--json", "--format", "csv"],
+    );
+    assert!(!conflicting.status.success());
+
+    let _ = std::fs::remove_file(&db_path);
+}
+
 #[test]
 fn benchmark_command_exits_non_zero_on_threshold_violation() {
     let db_path = std::env::temp_dir().join(format!(
@@ -271,3 +355,301 @@ fn projector_json_contract_contains_versioned_payloads() {
 
     let _ = std::fs::remove_file(&db_path);
 }
+
+#[test]
+fn log_batch_appends_jsonl_events_atomically() {
+    let db_path = std::env::temp_dir().join(format!(
+        "outcome-contract-log-batch-{}.sqlite3",
+        Ulid::new()
+    ));
+    let batch_path = std::env::temp_dir().join(format!("outcome-log-batch-{}.jsonl", Ulid::new()));
+    let memory_id = fixture_memory_id();
+
+    let setup_conn = match Connection::open(&db_path) {
+        Ok(value) => value,
+        Err(err) => panic!("failed to open setup db: {err}"),
+    };
+    if let Err(err) = seed_minimal_memory_record(&setup_conn, memory_id, 1) {
+        panic!("failed to seed memory row: {err}");
+    }
+    let event_count = || -> i64 {
+        match setup_conn.query_row("SELECT COUNT(*) FROM outcome_events", [], |row| row.get(0)) {
+            Ok(value) => value,
+            Err(err) => panic!("failed to count events: {err}"),
+        }
+    };
+
+    let line = |event: &str, extra: &str| {
+        format!(
+            r#"{{"memory_id":"{memory_id}","version":1,"event":"{event}","writer":"backfill","justification":"import"{extra}}}"#
+        )
+    };
+    let write_batch = |lines: &[String]| {
+        if let Err(err) = std::fs::write(&batch_path, lines.join("\n")) {
+            panic!("failed to write batch file: {err}");
+        }
+    };
+    let batch_arg = batch_path.to_str().unwrap_or("");
+
+    write_batch(&[
+        "# backfill from 2026-01".to_string(),
+        line("success", r#","occurred_at":"2026-01-05T10:00:00Z""#),
+        String::new(),
+        line("failure", r#","severity":"high","context_id":"ctx-1""#),
+        line(
+            "ignored",
+            r#","payload":{"ticket":"T-1"},"source":{"run_id":"run-7","step_id":"step-1"}"#,
+        ),
+    ]);
+    let output = mk_output(&db_path, &["outcome", "log-batch", "--file", batch_arg]);
+    assert!(
+        output.status.success(),
+        "log-batch failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let payload = stdout_json(&output);
+    assert_eq!(payload["appended"], Value::Number(3_u64.into()));
+    assert_eq!(
+        payload["last_event_seq"].as_i64(),
+        payload["first_event_seq"].as_i64().map(|seq| seq + 2)
+    );
+    assert_eq!(event_count(), 3);
+
+    let output = mk_output(
+        &db_path,
+        &["outcome", "events", "list", "--run-id", "run-7"],
+    );
+    assert!(
+        output.status.success(),
+        "events list --run-id failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let run_events = stdout_json(&output);
+    assert_eq!(run_events.as_array().map(Vec::len), Some(1));
+    assert_eq!(run_events[0]["source"]["step_id"], "step-1");
+    assert_eq!(run_events[0]["payload_json"]["ticket"], "T-1");
+
+    write_batch(&[line("success", ""), line("manual_promote", "")]);
+    let output = mk_output(&db_path, &["outcome", "log-batch", "--file", batch_arg]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains(":2: invalid event"), "stderr={stderr}");
+    assert_eq!(event_count(), 3);
+
+    write_batch(&[
+        line("success", ""),
+        line("success", r#","ruleset_version":99"#),
+    ]);
+    let output = mk_output(&db_path, &["outcome", "log-batch", "--file", batch_arg]);
+    assert!(!output.status.success());
+    assert_eq!(event_count(), 3);
+
+    let _ = std::fs::remove_file(&db_path);
+    let _ = std::fs::remove_file(&batch_path);
+}
+
+#[test]
+fn gate_explain_json_contract_breaks_down_the_decision() {
+    let db_path = std::env::temp_dir().join(format!(
+        "outcome-contract-gate-explain-{}.sqlite3",
+        Ulid::new()
+    ));
+    let memory_id = fixture_memory_id();
+
+    let setup_conn = match Connection::open(&db_path) {
+        Ok(value) => value,
+        Err(err) => panic!("failed to open setup db: {err}"),
+    };
+    if let Err(err) = seed_minimal_memory_record(&setup_conn, memory_id, 1) {
+        panic!("failed to seed memory row: {err}");
+    }
+
+    for _ in 0..3 {
+        let output = mk_output(
+            &db_path,
+            &[
+                "outcome",
+                "log",
+                "--memory-id",
+                &memory_id.to_string(),
+                "--version",
+                "1",
+                "--event",
+                "success",
+                "--writer",
+                "tester",
+                "--justification",
+                "explain fixture",
+                "--occurred-at",
+                "2026-02-07T12:00:00Z",
+                "--project",
+            ],
+        );
+        assert!(
+            output.status.success(),
+            "log failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let output = mk_output(
+        &db_path,
+        &[
+            "outcome",
+            "gate",
+            "explain",
+            "--memory-id",
+            &memory_id.to_string(),
+            "--version",
+            "1",
+            "--mode",
+            "safe",
+            "--as-of",
+            "2026-02-17T12:00:00Z",
+            "--json",
+        ],
+    );
+    assert!(
+        output.status.success(),
+        "gate explain failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let payload = stdout_json(&output);
+    assert_eq!(payload["contract_version"], "gate_explanation.v1");
+    assert_eq!(payload["decision"]["include"], true);
+    assert_eq!(payload["explanation"]["decay"]["applied"], true);
+    assert!(payload["explanation"]["decay"]["elapsed_days"].as_f64() > Some(9.9));
+    assert!(payload["explanation"]["parameters_used"]["safe_min_confidence"].is_number());
+    let checks = payload["explanation"]["checks"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    assert!(!checks.is_empty());
+    assert!(checks.iter().all(|check| check["passed"] == true));
+
+    let _ = std::fs::remove_file(&db_path);
+}
+
+#[test]
+#[allow(clippy::too_many_lines)]
+fn events_export_writes_jsonl_csv_and_parquet() {
+    let db_path = std::env::temp_dir().join(format!(
+        "outcome-contract-events-export-{}.sqlite3",
+        Ulid::new()
+    ));
+    let jsonl_path =
+        std::env::temp_dir().join(format!("outcome-events-export-{}.jsonl", Ulid::new()));
+    let csv_path = std::env::temp_dir().join(format!("outcome-events-export-{}.csv", Ulid::new()));
+    let parquet_path =
+        std::env::temp_dir().join(format!("outcome-events-export-{}.parquet", Ulid::new()));
+    let memory_id = fixture_memory_id();
+
+    let setup_conn = match Connection::open(&db_path) {
+        Ok(value) => value,
+        Err(err) => panic!("failed to open setup db: {err}"),
+    };
+    if let Err(err) = seed_minimal_memory_record(&setup_conn, memory_id, 1) {
+        panic!("failed to seed memory row: {err}");
+    }
+
+    for event in ["success", "failure"] {
+        let output = mk_output(
+            &db_path,
+            &[
+                "outcome",
+                "log",
+                "--memory-id",
+                &memory_id.to_string(),
+                "--version",
+                "1",
+                "--event",
+                event,
+                "--writer",
+                "tester",
+                "--justification",
+                "export, fixture",
+            ],
+        );
+        assert!(
+            output.status.success(),
+            "log failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let export = |format: &str, since: &str, out: &Path| {
+        mk_output(
+            &db_path,
+            &[
+                "outcome",
+                "events",
+                "export",
+                "--format",
+                format,
+                "--since-seq",
+                since,
+                "--out",
+                out.to_str().unwrap_or(""),
+            ],
+        )
+    };
+
+    let output = export("jsonl", "0", &jsonl_path);
+    assert!(
+        output.status.success(),
+        "jsonl export failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let payload = stdout_json(&output);
+    assert_eq!(payload["report"]["format"], "jsonl");
+    assert_eq!(payload["report"]["events_written"], 2);
+    let first_seq = match std::fs::read_to_string(&jsonl_path) {
+        Ok(contents) => {
+            let lines = contents.lines().collect::<Vec<_>>();
+            assert_eq!(lines.len(), 2);
+            let first: Value = match serde_json::from_str(lines[0]) {
+                Ok(value) => value,
+                Err(err) => panic!("invalid jsonl line: {err}"),
+            };
+            assert_eq!(first["event_type"], "success");
+            first["event_seq"].to_string()
+        }
+        Err(err) => panic!("failed to read jsonl export: {err}"),
+    };
+
+    let output = export("csv", &first_seq, &csv_path);
+    assert!(
+        output.status.success(),
+        "csv export failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(stdout_json(&output)["report"]["events_written"], 1);
+    match std::fs::read_to_string(&csv_path) {
+        Ok(contents) => {
+            let rows = contents.split_terminator("\r\n").collect::<Vec<_>>();
+            assert_eq!(rows.len(), 2, "csv: {contents}");
+            assert!(rows[0].starts_with("event_seq,event_id,"));
+            assert!(rows[1].contains(",failure,"));
+            assert!(rows[1].contains(",\"export, fixture\","));
+        }
+        Err(err) => panic!("failed to read csv export: {err}"),
+    }
+
+    let output = export("parquet", "0", &parquet_path);
+    assert!(
+        output.status.success(),
+        "parquet export failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let payload = stdout_json(&output);
+    assert_eq!(payload["report"]["format"], "parquet");
+    assert_eq!(payload["report"]["events_written"], 2);
+    match std::fs::read(&parquet_path) {
+        Ok(contents) => {
+            assert!(contents.starts_with(b"PAR1"));
+            assert!(contents.ends_with(b"PAR1"));
+        }
+        Err(err) => panic!("failed to read parquet export: {err}"),
+    }
+}
@@ -106,7 +106,8 @@ fn snapshot_projector_status_json_v1() {
   "keys_with_events_no_trust_row": 0,
   "trust_rows_without_events": 0,
   "max_stale_seq_gap": 0,
-  "updated_at": "<timestamp>"
+  "updated_at": "<timestamp>",
+  "pending_events": 0
 }"#;
 
     assert_eq!(snapshot, expected);
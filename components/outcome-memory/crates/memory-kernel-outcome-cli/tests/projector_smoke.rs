@@ -171,3 +171,239 @@ fn projector_check_smoke_after_replay() {
 
     let _ = std::fs::remove_file(&db_path);
 }
+
+#[test]
+fn projector_watch_projects_new_events_and_reports_lag_per_tick() {
+    let db_path =
+        std::env::temp_dir().join(format!("outcome-projector-watch-{}.sqlite3", Ulid::new()));
+    let memory_id = fixture_memory_id();
+
+    let connection = match Connection::open(&db_path) {
+        Ok(value) => value,
+        Err(err) => panic!("failed to open setup sqlite db: {err}"),
+    };
+    if let Err(err) = seed_minimal_memory_record(&connection, memory_id, 1) {
+        panic!("failed to seed memory row: {err}");
+    }
+
+    for event in ["success", "failure"] {
+        let output = mk_output(
+            &db_path,
+            &[
+                "outcome",
+                "log",
+                "--memory-id",
+                &memory_id.to_string(),
+                "--version",
+                "1",
+                "--event",
+                event,
+                "--writer",
+                "tester",
+                "--justification",
+                "watch",
+            ],
+        );
+        assert!(
+            output.status.success(),
+            "log command failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    let watch = mk_output(
+        &db_path,
+        &[
+            "outcome",
+            "projector",
+            "watch",
+            "--interval",
+            "10ms",
+            "--max-ticks",
+            "2",
+            "--json",
+        ],
+    );
+    assert!(
+        watch.status.success(),
+        "watch command failed: {}",
+        String::from_utf8_lossy(&watch.stderr)
+    );
+    let ticks = String::from_utf8_lossy(&watch.stdout)
+        .lines()
+        .map(|line| match serde_json::from_str::<Value>(line) {
+            Ok(value) => value,
+            Err(err) => panic!("watch line is not JSON: {err}: {line}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(ticks.len(), 2);
+    assert_eq!(ticks[0]["contract_version"], "projector_watch_tick.v1");
+    assert_eq!(ticks[0]["lag_events_before"], 2);
+    assert_eq!(ticks[0]["projected_keys"], 1);
+    assert_eq!(ticks[0]["lag_events"], 0);
+    assert_eq!(ticks[1]["lag_events_before"], 0);
+    assert_eq!(ticks[1]["projected_keys"], 0);
+
+    let check = mk_output(&db_path, &["outcome", "projector", "check", "--json"]);
+    assert!(
+        check.status.success(),
+        "projector check should pass after watch: {}",
+        String::from_utf8_lossy(&check.stderr)
+    );
+
+    let invalid = mk_output(
+        &db_path,
+        &["outcome", "projector", "watch", "--interval", "soon"],
+    );
+    assert!(!invalid.status.success());
+
+    let _ = std::fs::remove_file(&db_path);
+}
+
+#[test]
+fn log_with_project_keeps_projector_healthy_without_replay() {
+    let db_path = std::env::temp_dir().join(format!("outcome-log-project-{}.sqlite3", Ulid::new()));
+    let memory_id = fixture_memory_id();
+
+    let connection = match Connection::open(&db_path) {
+        Ok(value) => value,
+        Err(err) => panic!("failed to open setup sqlite db: {err}"),
+    };
+    if let Err(err) = seed_minimal_memory_record(&connection, memory_id, 1) {
+        panic!("failed to seed memory row: {err}");
+    }
+
+    let output = mk_output(
+        &db_path,
+        &[
+            "outcome",
+            "log",
+            "--memory-id",
+            &memory_id.to_string(),
+            "--version",
+            "1",
+            "--event",
+            "failure",
+            "--writer",
+            "tester",
+            "--justification",
+            "read-your-writes",
+            "--project",
+        ],
+    );
+    assert!(
+        output.status.success(),
+        "log --project failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let event = stdout_json(&output);
+
+    let check = mk_output(&db_path, &["outcome", "projector", "check", "--json"]);
+    assert!(
+        check.status.success(),
+        "projector check should pass without replay: {}",
+        String::from_utf8_lossy(&check.stderr)
+    );
+    let trust = mk_output(
+        &db_path,
+        &[
+            "outcome",
+            "trust",
+            "show",
+            "--memory-id",
+            &memory_id.to_string(),
+            "--version",
+            "1",
+        ],
+    );
+    assert!(
+        trust.status.success(),
+        "trust show failed: {}",
+        String::from_utf8_lossy(&trust.stderr)
+    );
+    assert_eq!(stdout_json(&trust)["last_event_seq"], event["event_seq"]);
+
+    let _ = std::fs::remove_file(&db_path);
+}
+
+#[test]
+fn gate_preview_on_stale_refuses_then_projects_unreplayed_events() {
+    let db_path = std::env::temp_dir().join(format!("outcome-gate-stale-{}.sqlite3", Ulid::new()));
+    let memory_id = fixture_memory_id();
+    let candidate = format!("{memory_id}:1");
+
+    let connection = match Connection::open(&db_path) {
+        Ok(value) => value,
+        Err(err) => panic!("failed to open setup sqlite db: {err}"),
+    };
+    if let Err(err) = seed_minimal_memory_record(&connection, memory_id, 1) {
+        panic!("failed to seed memory row: {err}");
+    }
+
+    let log = mk_output(
+        &db_path,
+        &[
+            "outcome",
+            "log",
+            "--memory-id",
+            &memory_id.to_string(),
+            "--version",
+            "1",
+            "--event",
+            "success",
+            "--writer",
+            "tester",
+            "--justification",
+            "not yet replayed",
+        ],
+    );
+    assert!(
+        log.status.success(),
+        "log failed: {}",
+        String::from_utf8_lossy(&log.stderr)
+    );
+
+    let preview = |on_stale: &str| {
+        mk_output(
+            &db_path,
+            &[
+                "outcome",
+                "gate",
+                "preview",
+                "--mode",
+                "exploration",
+                "--as-of",
+                "2026-02-07T12:00:00Z",
+                "--candidate",
+                &candidate,
+                "--on-stale",
+                on_stale,
+                "--json",
+            ],
+        )
+    };
+
+    let refused = preview("refuse");
+    assert!(!refused.status.success());
+    let stderr = String::from_utf8_lossy(&refused.stderr);
+    assert!(
+        stderr.contains("stale trust snapshot for 1 candidate(s)"),
+        "unexpected stderr: {stderr}"
+    );
+
+    let projected = preview("project");
+    assert!(
+        projected.status.success(),
+        "gate preview --on-stale project failed: {}",
+        String::from_utf8_lossy(&projected.stderr)
+    );
+    let payload = stdout_json(&projected);
+    let reasons = payload.to_string();
+    assert!(
+        !reasons.contains("excluded.no_trust_snapshot"),
+        "expected a projected snapshot, got {reasons}"
+    );
+    assert!(preview("refuse").status.success());
+
+    let _ = std::fs::remove_file(&db_path);
+}
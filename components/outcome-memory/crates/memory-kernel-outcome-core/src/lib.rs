@@ -3,7 +3,7 @@ use std::fmt::{Display, Formatter};
 
 use memory_kernel_core::MemoryId;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
 use time::{Duration, OffsetDateTime, UtcOffset};
 use ulid::Ulid;
 
@@ -93,6 +93,65 @@ impl Severity {
     }
 }
 
+/// Curve [`apply_as_of_decay`] uses to pull confidence back toward baseline between scorings,
+/// with `lambda` the ruleset's `read_decay_lambda_per_day`.
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum DecayModel {
+    /// `exp(-lambda * days)`.
+    #[default]
+    Exponential,
+    /// `max(0, 1 - lambda * days)`.
+    Linear,
+    /// Exponential decay applied once per completed `read_decay_step_days` window, so confidence
+    /// holds steady within a window.
+    Step,
+    /// No read-time decay.
+    None,
+}
+
+impl DecayModel {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Exponential => "exponential",
+            Self::Linear => "linear",
+            Self::Step => "step",
+            Self::None => "none",
+        }
+    }
+
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "exponential" => Some(Self::Exponential),
+            "linear" => Some(Self::Linear),
+            "step" => Some(Self::Step),
+            "none" => Some(Self::None),
+            _ => None,
+        }
+    }
+
+    /// Factor the distance from baseline is scaled by after `elapsed_days`.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn factor(self, lambda_per_day: f32, step_days: f32, elapsed_days: f64) -> f32 {
+        let lambda = f64::from(lambda_per_day);
+        let factor = match self {
+            Self::Exponential => (-lambda * elapsed_days).exp(),
+            Self::Linear => (1.0 - lambda * elapsed_days).max(0.0),
+            Self::Step => {
+                let step_days = f64::from(step_days);
+                (-lambda * step_days * (elapsed_days / step_days).floor()).exp()
+            }
+            Self::None => 1.0,
+        };
+        factor as f32
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum TrustStatus {
@@ -152,6 +211,21 @@ impl Display for MemoryKey {
     }
 }
 
+/// Where an automated writer such as the orchestrator produced an event. Stored in its own
+/// column rather than in `payload_json`, and indexed by `run_id`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct OutcomeEventSource {
+    pub run_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step_id: Option<String>,
+    /// The gate or trust context the event was scored under, e.g. `<run_id>:<step_key>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gate_context: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub engine_version: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct OutcomeEvent {
     pub event_seq: i64,
@@ -171,6 +245,81 @@ pub struct OutcomeEvent {
     pub manual_confidence: Option<f32>,
     pub override_cap: bool,
     pub payload_json: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cosigner: Option<String>,
+    /// Hex-encoded detached Ed25519 signature by the writer over the event's signing material.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<OutcomeEventSource>,
+    /// When a scheduled event starts to count; `None` means it counts from when it is recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_at: Option<OffsetDateTime>,
+    /// `event_hash` of the preceding event in the log, `None` for the first hashed event.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_event_hash: Option<String>,
+    /// Hash over [`OutcomeEvent::chain_material`]; `None` on rows written before chaining.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_hash: Option<String>,
+}
+
+impl OutcomeEvent {
+    /// Canonical bytes covered by `event_hash`: the signing material plus the store-assigned
+    /// `recorded_at`, the signature, and `prev_event_hash`, which links each event to its
+    /// predecessor.
+    ///
+    /// # Errors
+    /// Returns [`OutcomeError::Validation`] when a field cannot be serialized.
+    pub fn chain_material(&self) -> Result<Vec<u8>, OutcomeError> {
+        let mut material = OutcomeEventInput::from(self).material_value()?;
+        if let Value::Object(map) = &mut material {
+            map.insert(
+                "recorded_at".to_string(),
+                Value::String(format_rfc3339(self.recorded_at)?),
+            );
+            map.insert("signature".to_string(), serde_json::json!(self.signature));
+            map.insert(
+                "prev_event_hash".to_string(),
+                serde_json::json!(self.prev_event_hash),
+            );
+        }
+        serde_json::to_vec(&material).map_err(|err| {
+            OutcomeError::Validation(format!("failed to serialize chain material: {err}"))
+        })
+    }
+
+    /// Whether the event counts toward projection at `as_of`. Unscheduled events always do.
+    #[must_use]
+    pub fn is_effective_at(&self, as_of: OffsetDateTime) -> bool {
+        self.effective_at
+            .map_or(true, |effective_at| effective_at <= as_of)
+    }
+}
+
+impl From<&OutcomeEvent> for OutcomeEventInput {
+    fn from(event: &OutcomeEvent) -> Self {
+        Self {
+            event_id: Some(event.event_id),
+            ruleset_version: event.ruleset_version,
+            memory_id: event.memory_id,
+            version: event.version,
+            event_type: event.event_type,
+            occurred_at: event.occurred_at,
+            writer: event.writer.clone(),
+            justification: event.justification.clone(),
+            context_id: event.context_id.clone(),
+            edited: event.edited,
+            escalated: event.escalated,
+            severity: event.severity,
+            manual_confidence: event.manual_confidence,
+            override_cap: event.override_cap,
+            payload_json: event.payload_json.clone(),
+            cosigner: event.cosigner.clone(),
+            signature: event.signature.clone(),
+            source: event.source.clone(),
+            effective_at: event.effective_at,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -190,9 +339,68 @@ pub struct OutcomeEventInput {
     pub manual_confidence: Option<f32>,
     pub override_cap: bool,
     pub payload_json: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cosigner: Option<String>,
+    /// Hex-encoded detached Ed25519 signature by the writer over the event's signing material.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<OutcomeEventSource>,
+    /// Schedules the event: it is recorded now but only counts toward projection once
+    /// `effective_at` has passed. MUST NOT precede `occurred_at`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effective_at: Option<OffsetDateTime>,
 }
 
 impl OutcomeEventInput {
+    /// Canonical bytes a writer signs: every caller-supplied field except the signature itself,
+    /// serialized in a fixed order. `recorded_at` and `event_seq` are assigned by the store and
+    /// are not covered.
+    ///
+    /// # Errors
+    /// Returns [`OutcomeError::Validation`] when `event_id` is missing or a field cannot be
+    /// serialized.
+    pub fn signing_material(&self) -> Result<Vec<u8>, OutcomeError> {
+        serde_json::to_vec(&self.material_value()?).map_err(|err| {
+            OutcomeError::Validation(format!("failed to serialize signing material: {err}"))
+        })
+    }
+
+    fn material_value(&self) -> Result<Value, OutcomeError> {
+        let event_id = self.event_id.ok_or_else(|| {
+            OutcomeError::Validation("signed events MUST carry event_id".to_string())
+        })?;
+        let mut material = serde_json::json!({
+            "event_id": event_id.to_string(),
+            "ruleset_version": self.ruleset_version,
+            "memory_id": self.memory_id.to_string(),
+            "version": self.version,
+            "event_type": self.event_type.as_str(),
+            "occurred_at": format_rfc3339(self.occurred_at)?,
+            "writer": self.writer,
+            "justification": self.justification,
+            "context_id": self.context_id,
+            "edited": self.edited,
+            "escalated": self.escalated,
+            "severity": self.severity.map(Severity::as_str),
+            "manual_confidence": self.manual_confidence,
+            "override_cap": self.override_cap,
+            "payload_json": self.payload_json,
+            "cosigner": self.cosigner,
+        });
+        // Only attributed events cover `source`, so signatures and hashes of earlier events hold.
+        if let (Some(source), Value::Object(map)) = (&self.source, &mut material) {
+            map.insert("source".to_string(), serde_json::json!(source));
+        }
+        if let (Some(effective_at), Value::Object(map)) = (self.effective_at, &mut material) {
+            map.insert(
+                "effective_at".to_string(),
+                Value::String(format_rfc3339(effective_at)?),
+            );
+        }
+        Ok(material)
+    }
+
     /// Validates a write event payload before append.
     ///
     /// # Errors
@@ -221,12 +429,59 @@ impl OutcomeEventInput {
             ));
         }
 
+        if let Some(cosigner) = &self.cosigner {
+            if cosigner.trim().is_empty() {
+                return Err(OutcomeError::Validation(
+                    "cosigner MUST NOT be empty when provided".to_string(),
+                ));
+            }
+            if cosigner.trim() == self.writer.trim() {
+                return Err(OutcomeError::Validation(
+                    "cosigner MUST differ from writer".to_string(),
+                ));
+            }
+        }
+
+        if let Some(source) = &self.source {
+            if source.run_id.trim().is_empty() {
+                return Err(OutcomeError::Validation(
+                    "source.run_id MUST NOT be empty when source is provided".to_string(),
+                ));
+            }
+        }
+
+        if let Some(signature) = &self.signature {
+            if self.event_id.is_none() {
+                return Err(OutcomeError::Validation(
+                    "signed events MUST carry event_id".to_string(),
+                ));
+            }
+            if signature.is_empty() || !signature.chars().all(|ch| ch.is_ascii_hexdigit()) {
+                return Err(OutcomeError::Validation(
+                    "signature MUST be hex-encoded".to_string(),
+                ));
+            }
+        }
+
         if self.occurred_at.offset() != UtcOffset::UTC {
             return Err(OutcomeError::Validation(
                 "occurred_at MUST be UTC (offset Z)".to_string(),
             ));
         }
 
+        if let Some(effective_at) = self.effective_at {
+            if effective_at.offset() != UtcOffset::UTC {
+                return Err(OutcomeError::Validation(
+                    "effective_at MUST be UTC (offset Z)".to_string(),
+                ));
+            }
+            if effective_at < self.occurred_at {
+                return Err(OutcomeError::Validation(
+                    "effective_at MUST NOT precede occurred_at".to_string(),
+                ));
+            }
+        }
+
         if self.escalated && self.severity.is_none() {
             return Err(OutcomeError::Validation(
                 "severity is required when escalated=true".to_string(),
@@ -273,6 +528,14 @@ pub struct OutcomeRuleset {
     pub severity_low_multiplier: f32,
     pub severity_med_multiplier: f32,
     pub severity_high_multiplier: f32,
+    /// Scales `failure_weight` for failures recorded with `severity: low`, whether or not they
+    /// were escalated. Rulesets stored before severity weighting weigh every failure at 1.0.
+    #[serde(default = "default_failure_severity_weight")]
+    pub failure_severity_low_weight: f32,
+    #[serde(default = "default_failure_severity_weight")]
+    pub failure_severity_med_weight: f32,
+    #[serde(default = "default_failure_severity_weight")]
+    pub failure_severity_high_weight: f32,
     pub inheritance_factor: f32,
     pub inheritance_cap: f32,
     pub base_confidence: f32,
@@ -286,6 +549,37 @@ pub struct OutcomeRuleset {
     pub exploration_probe_max_confidence: f32,
     pub exploration_probe_budget: f32,
     pub read_decay_lambda_per_day: f32,
+    /// Read-time decay curve; rulesets stored before decay models existed decay exponentially.
+    #[serde(default)]
+    pub decay_model: DecayModel,
+    /// Window length in days for [`DecayModel::Step`].
+    #[serde(default = "default_read_decay_step_days")]
+    pub read_decay_step_days: f32,
+    /// Days after `manual_retire` during which the retirement is soft: the memory stays out of
+    /// Safe mode but is still admitted, flagged, in Exploration mode. `0.0` (the default) retires
+    /// immediately.
+    #[serde(default)]
+    pub retire_grace_days: f32,
+    /// Successes that reinstate a soft-retired memory when they occur within its grace period.
+    #[serde(default = "default_retire_reinstate_successes")]
+    pub retire_reinstate_successes: u8,
+    /// Confidence that read-time decay alone cannot take an authoritative memory below. Outcome
+    /// events still can; a memory already below the floor stays where they left it. `0.0` (the
+    /// default) sets no floor.
+    #[serde(default)]
+    pub authoritative_confidence_floor: f32,
+}
+
+fn default_read_decay_step_days() -> f32 {
+    30.0
+}
+
+fn default_failure_severity_weight() -> f32 {
+    1.0
+}
+
+fn default_retire_reinstate_successes() -> u8 {
+    2
 }
 
 impl OutcomeRuleset {
@@ -302,6 +596,9 @@ impl OutcomeRuleset {
             severity_low_multiplier: 1.0,
             severity_med_multiplier: 1.2,
             severity_high_multiplier: 1.5,
+            failure_severity_low_weight: default_failure_severity_weight(),
+            failure_severity_med_weight: default_failure_severity_weight(),
+            failure_severity_high_weight: default_failure_severity_weight(),
             inheritance_factor: 0.70,
             inheritance_cap: 0.80,
             base_confidence: 0.50,
@@ -315,9 +612,25 @@ impl OutcomeRuleset {
             exploration_probe_max_confidence: 0.30,
             exploration_probe_budget: 0.20,
             read_decay_lambda_per_day: 0.01,
+            decay_model: DecayModel::Exponential,
+            read_decay_step_days: default_read_decay_step_days(),
+            retire_grace_days: 0.0,
+            retire_reinstate_successes: default_retire_reinstate_successes(),
+            authoritative_confidence_floor: 0.0,
         }
     }
 
+    /// When a retirement at `retired_at` stops being soft, or `None` without a grace period.
+    #[must_use]
+    pub fn retire_grace_until(&self, retired_at: OffsetDateTime) -> Option<OffsetDateTime> {
+        (self.retire_grace_days > 0.0).then(|| {
+            retired_at
+                + Duration::seconds_f64(
+                    f64::from(self.retire_grace_days) * Duration::DAY.as_seconds_f64(),
+                )
+        })
+    }
+
     /// Validates ruleset numeric bounds and window invariants.
     ///
     /// # Errors
@@ -352,6 +665,10 @@ impl OutcomeRuleset {
             ),
             ("exploration_probe_budget", self.exploration_probe_budget),
             ("read_decay_lambda_per_day", self.read_decay_lambda_per_day),
+            (
+                "authoritative_confidence_floor",
+                self.authoritative_confidence_floor,
+            ),
         ] {
             if !(0.0..=1.0).contains(&value) {
                 return Err(OutcomeError::Configuration(format!(
@@ -360,6 +677,45 @@ impl OutcomeRuleset {
             }
         }
 
+        for (name, value) in [
+            (
+                "failure_severity_low_weight",
+                self.failure_severity_low_weight,
+            ),
+            (
+                "failure_severity_med_weight",
+                self.failure_severity_med_weight,
+            ),
+            (
+                "failure_severity_high_weight",
+                self.failure_severity_high_weight,
+            ),
+        ] {
+            if !(value.is_finite() && value >= 0.0) {
+                return Err(OutcomeError::Configuration(format!(
+                    "{name} MUST be >= 0.0"
+                )));
+            }
+        }
+
+        if !(self.read_decay_step_days.is_finite() && self.read_decay_step_days > 0.0) {
+            return Err(OutcomeError::Configuration(
+                "read_decay_step_days MUST be > 0.0".to_string(),
+            ));
+        }
+
+        if !(self.retire_grace_days.is_finite() && self.retire_grace_days >= 0.0) {
+            return Err(OutcomeError::Configuration(
+                "retire_grace_days MUST be >= 0.0".to_string(),
+            ));
+        }
+
+        if self.retire_reinstate_successes == 0 {
+            return Err(OutcomeError::Configuration(
+                "retire_reinstate_successes MUST be >= 1".to_string(),
+            ));
+        }
+
         if self.validated_window_size == 0 {
             return Err(OutcomeError::Configuration(
                 "validated_window_size MUST be >= 1".to_string(),
@@ -394,6 +750,18 @@ impl OutcomeRuleset {
         }
     }
 
+    /// Weight applied to `failure_weight` for a failure of `severity`; failures without a
+    /// severity weigh 1.0.
+    #[must_use]
+    pub fn failure_severity_weight(&self, severity: Option<Severity>) -> f32 {
+        match severity {
+            None => 1.0,
+            Some(Severity::Low) => self.failure_severity_low_weight,
+            Some(Severity::Med) => self.failure_severity_med_weight,
+            Some(Severity::High) => self.failure_severity_high_weight,
+        }
+    }
+
     /// Decodes and validates a ruleset from JSON.
     ///
     /// # Errors
@@ -406,6 +774,161 @@ impl OutcomeRuleset {
         ruleset.validate()?;
         Ok(ruleset)
     }
+
+    /// Tunable parameters as `(name, category, value)` in declaration order. Categories are
+    /// `threshold` (confidence floors, caps, and windows), `weight` (event weights, multipliers,
+    /// and update factors), and `decay`.
+    #[must_use]
+    #[allow(clippy::too_many_lines)]
+    pub fn parameters(&self) -> Vec<(&'static str, &'static str, Value)> {
+        vec![
+            ("alpha", "weight", json!(self.alpha)),
+            ("per_event_decay", "decay", json!(self.per_event_decay)),
+            ("success_weight", "weight", json!(self.success_weight)),
+            (
+                "edited_success_weight",
+                "weight",
+                json!(self.edited_success_weight),
+            ),
+            ("failure_weight", "weight", json!(self.failure_weight)),
+            ("ignored_weight", "weight", json!(self.ignored_weight)),
+            (
+                "severity_low_multiplier",
+                "weight",
+                json!(self.severity_low_multiplier),
+            ),
+            (
+                "severity_med_multiplier",
+                "weight",
+                json!(self.severity_med_multiplier),
+            ),
+            (
+                "severity_high_multiplier",
+                "weight",
+                json!(self.severity_high_multiplier),
+            ),
+            (
+                "failure_severity_low_weight",
+                "weight",
+                json!(self.failure_severity_low_weight),
+            ),
+            (
+                "failure_severity_med_weight",
+                "weight",
+                json!(self.failure_severity_med_weight),
+            ),
+            (
+                "failure_severity_high_weight",
+                "weight",
+                json!(self.failure_severity_high_weight),
+            ),
+            (
+                "inheritance_factor",
+                "weight",
+                json!(self.inheritance_factor),
+            ),
+            ("inheritance_cap", "threshold", json!(self.inheritance_cap)),
+            ("base_confidence", "weight", json!(self.base_confidence)),
+            (
+                "contradiction_degrade",
+                "weight",
+                json!(self.contradiction_degrade),
+            ),
+            (
+                "contradiction_cap",
+                "threshold",
+                json!(self.contradiction_cap),
+            ),
+            (
+                "validated_wins_required",
+                "threshold",
+                json!(self.validated_wins_required),
+            ),
+            (
+                "validated_window_size",
+                "threshold",
+                json!(self.validated_window_size),
+            ),
+            (
+                "safe_min_confidence",
+                "threshold",
+                json!(self.safe_min_confidence),
+            ),
+            (
+                "exploration_min_confidence",
+                "threshold",
+                json!(self.exploration_min_confidence),
+            ),
+            (
+                "exploration_probe_min_confidence",
+                "threshold",
+                json!(self.exploration_probe_min_confidence),
+            ),
+            (
+                "exploration_probe_max_confidence",
+                "threshold",
+                json!(self.exploration_probe_max_confidence),
+            ),
+            (
+                "exploration_probe_budget",
+                "threshold",
+                json!(self.exploration_probe_budget),
+            ),
+            (
+                "read_decay_lambda_per_day",
+                "decay",
+                json!(self.read_decay_lambda_per_day),
+            ),
+            ("decay_model", "decay", json!(self.decay_model)),
+            (
+                "read_decay_step_days",
+                "decay",
+                json!(self.read_decay_step_days),
+            ),
+            (
+                "retire_grace_days",
+                "threshold",
+                json!(self.retire_grace_days),
+            ),
+            (
+                "retire_reinstate_successes",
+                "threshold",
+                json!(self.retire_reinstate_successes),
+            ),
+            (
+                "authoritative_confidence_floor",
+                "decay",
+                json!(self.authoritative_confidence_floor),
+            ),
+        ]
+    }
+}
+
+/// One parameter that differs between two rulesets.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RulesetParameterChange {
+    pub parameter: String,
+    pub category: String,
+    pub from: Value,
+    pub to: Value,
+}
+
+/// Parameters whose values differ between `from` and `to`, in declaration order.
+#[must_use]
+pub fn diff_rulesets(from: &OutcomeRuleset, to: &OutcomeRuleset) -> Vec<RulesetParameterChange> {
+    from.parameters()
+        .into_iter()
+        .zip(to.parameters())
+        .filter(|((_, _, old), (_, _, new))| old != new)
+        .map(
+            |((parameter, category, old), (_, _, new))| RulesetParameterChange {
+                parameter: parameter.to_string(),
+                category: category.to_string(),
+                from: old,
+                to: new,
+            },
+        )
+        .collect()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -424,6 +947,24 @@ pub struct MemoryTrust {
     pub last_event_seq: i64,
     pub last_scored_at: Option<OffsetDateTime>,
     pub updated_at: OffsetDateTime,
+    /// End of the grace period of a soft retirement; only set while `trust_status` is retired.
+    /// [`apply_as_of_decay`] clears it once the period is over, after which the retirement is
+    /// final.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retire_grace_until: Option<OffsetDateTime>,
+    /// The memory record is flagged authoritative in the memory kernel. Not part of the stored
+    /// snapshot: gate sources join it from `memory_records` before [`apply_as_of_decay`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub authoritative: bool,
+}
+
+impl MemoryTrust {
+    /// Whether the memory is retired but still within its grace period at `as_of`.
+    #[must_use]
+    pub fn in_retire_grace(&self, as_of: OffsetDateTime) -> bool {
+        self.trust_status == TrustStatus::Retired
+            && self.retire_grace_until.is_some_and(|until| as_of < until)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -437,6 +978,88 @@ pub struct GateDecision {
     pub reason_codes: Vec<String>,
 }
 
+/// Read-time decay from the stored snapshot to `as_of`, as [`apply_as_of_decay`] applies it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DecayExplanation {
+    pub applied: bool,
+    /// Why no decay applied: `retired`, `never_scored`, or `not_elapsed`.
+    pub skipped_reason: Option<String>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_scored_at: Option<OffsetDateTime>,
+    pub elapsed_days: f64,
+    #[serde(default)]
+    pub decay_model: DecayModel,
+    pub lambda_per_day: f32,
+    /// [`DecayModel::factor`] for `elapsed_days`; the distance from baseline is scaled by this.
+    pub decay_factor: f32,
+    pub baseline_confidence: f32,
+    pub confidence_raw_before: f32,
+    pub confidence_raw_after: f32,
+    /// Floor decay was held at because the memory is authoritative, when one applied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authority_floor: Option<f32>,
+}
+
+/// How the contradiction cap turned decayed confidence into `confidence_effective`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CapExplanation {
+    pub contradiction_cap_active: bool,
+    pub manual_override_active: bool,
+    pub cap_value: f32,
+    pub confidence_before_cap: f32,
+    pub confidence_effective: f32,
+    /// The cap is active and not overridden, which also blocks validated inclusion.
+    pub capped: bool,
+}
+
+/// One comparison made while gating. `rule` names the inclusion path it belongs to:
+/// `not_retired`, `safe_validated`, `exploration_active`, `exploration_probe`, or
+/// `retire_grace`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GateCheck {
+    pub rule: String,
+    pub check: String,
+    pub actual: Value,
+    pub op: String,
+    pub required: Value,
+    pub passed: bool,
+}
+
+/// Structured account of a [`gate_memory`] decision: the decay and cap math behind
+/// `confidence_effective`, every threshold comparison per inclusion path, and the ruleset
+/// parameters those steps read.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GateExplanation {
+    pub ruleset_version: u32,
+    pub parameters_used: BTreeMap<String, Value>,
+    pub decay: DecayExplanation,
+    pub cap: CapExplanation,
+    pub checks: Vec<GateCheck>,
+    /// Deterministic probe bucket, reported when the probe path was evaluated.
+    pub probe_bucket: Option<f32>,
+}
+
+/// Projects the events of `events` already effective at `as_of` into a trust snapshot; scheduled
+/// events still in the future are left out. Returns `None` when no event is effective yet.
+///
+/// # Errors
+/// Same as [`project_memory_trust`].
+pub fn project_memory_trust_as_of(
+    events: &[OutcomeEvent],
+    rulesets: &BTreeMap<u32, OutcomeRuleset>,
+    as_of: OffsetDateTime,
+) -> Result<Option<MemoryTrust>, OutcomeError> {
+    if events.iter().all(|event| event.is_effective_at(as_of)) {
+        return project_memory_trust(events, rulesets);
+    }
+    let effective = events
+        .iter()
+        .filter(|event| event.is_effective_at(as_of))
+        .cloned()
+        .collect::<Vec<_>>();
+    project_memory_trust(&effective, rulesets)
+}
+
 /// Projects append-only outcome events into a trust snapshot.
 ///
 /// # Errors
@@ -473,6 +1096,8 @@ pub fn project_memory_trust(
     let mut cap_value = 1.0;
     let mut manual_override_active = false;
     let mut last_scored_at = None;
+    let mut retire_grace_until = None;
+    let mut reinstating_successes = 0_u8;
 
     for event in events {
         if event.memory_id != key.memory_id || event.version != key.version {
@@ -505,6 +1130,7 @@ pub fn project_memory_trust(
                 manual_override_active = false;
                 wins_window.clear();
                 trust_status = TrustStatus::Active;
+                retire_grace_until = None;
             }
             OutcomeEventType::Success => {
                 let base_weight = if event.edited {
@@ -519,12 +1145,19 @@ pub fn project_memory_trust(
                     OutcomeWindowEntry::Success,
                     ruleset.validated_window_size,
                 );
+                if retire_grace_until.is_some_and(|until| event.occurred_at < until) {
+                    reinstating_successes = reinstating_successes.saturating_add(1);
+                    if reinstating_successes >= ruleset.retire_reinstate_successes {
+                        trust_status = TrustStatus::Active;
+                        retire_grace_until = None;
+                    }
+                }
             }
             OutcomeEventType::Failure => {
                 apply_scored_event(
                     &mut confidence_raw,
                     baseline,
-                    ruleset.failure_weight,
+                    ruleset.failure_weight * ruleset.failure_severity_weight(event.severity),
                     &ruleset,
                     event,
                 );
@@ -534,6 +1167,7 @@ pub fn project_memory_trust(
                     OutcomeWindowEntry::Failure,
                     ruleset.validated_window_size,
                 );
+                reinstating_successes = 0;
             }
             OutcomeEventType::Ignored => {
                 apply_scored_event(
@@ -569,9 +1203,12 @@ pub fn project_memory_trust(
             OutcomeEventType::ManualPromote => {
                 trust_status = TrustStatus::Active;
                 wins_window.clear();
+                retire_grace_until = None;
             }
             OutcomeEventType::ManualRetire => {
                 trust_status = TrustStatus::Retired;
+                retire_grace_until = ruleset.retire_grace_until(event.occurred_at);
+                reinstating_successes = 0;
             }
             OutcomeEventType::AuthoritativeContradiction => {
                 contradiction_cap_active = true;
@@ -634,43 +1271,34 @@ pub fn project_memory_trust(
         last_event_seq: last.event_seq,
         last_scored_at,
         updated_at: last.recorded_at,
+        retire_grace_until,
+        authoritative: false,
     }))
 }
 
 #[must_use]
-#[allow(clippy::cast_possible_truncation)]
 pub fn apply_as_of_decay(
     trust: &MemoryTrust,
     ruleset: &OutcomeRuleset,
     as_of: OffsetDateTime,
 ) -> MemoryTrust {
-    if trust.trust_status == TrustStatus::Retired {
-        return trust.clone();
+    let mut decayed = trust.clone();
+    if !trust.in_retire_grace(as_of) {
+        decayed.retire_grace_until = None;
     }
-
-    let Some(last_scored_at) = trust.last_scored_at else {
-        return trust.clone();
+    let Ok((_, decay_term)) = read_decay_term(trust, ruleset, as_of) else {
+        return decayed;
     };
 
-    if as_of <= last_scored_at {
-        return trust.clone();
-    }
-
-    let elapsed = as_of - last_scored_at;
-    if elapsed <= Duration::ZERO {
-        return trust.clone();
-    }
-
-    let elapsed_days = elapsed.as_seconds_f64() / Duration::DAY.as_seconds_f64();
-    let decay_term = (-f64::from(ruleset.read_decay_lambda_per_day) * elapsed_days).exp() as f32;
-
-    let mut decayed = trust.clone();
     decayed.confidence_raw = clamp(
         decayed.baseline_confidence
             + (decayed.confidence_raw - decayed.baseline_confidence) * decay_term,
         0.0,
         1.0,
     );
+    if let Some(floor) = authority_floor(trust, ruleset) {
+        decayed.confidence_raw = decayed.confidence_raw.max(floor);
+    }
 
     decayed.confidence_effective = decayed.confidence_raw;
     if decayed.contradiction_cap_active && !decayed.manual_override_active {
@@ -680,6 +1308,46 @@ pub fn apply_as_of_decay(
     decayed
 }
 
+/// The confidence decay may not take `trust` below: the ruleset floor for an authoritative
+/// memory, lowered to the snapshot's own confidence when outcomes already took it under.
+fn authority_floor(trust: &MemoryTrust, ruleset: &OutcomeRuleset) -> Option<f32> {
+    (trust.authoritative && ruleset.authoritative_confidence_floor > 0.0).then(|| {
+        ruleset
+            .authoritative_confidence_floor
+            .min(trust.confidence_raw)
+    })
+}
+
+/// Elapsed days since the snapshot was scored and the resulting read-decay factor, or why
+/// [`apply_as_of_decay`] leaves the snapshot untouched.
+fn read_decay_term(
+    trust: &MemoryTrust,
+    ruleset: &OutcomeRuleset,
+    as_of: OffsetDateTime,
+) -> Result<(f64, f32), &'static str> {
+    if trust.trust_status == TrustStatus::Retired {
+        return Err("retired");
+    }
+    let Some(last_scored_at) = trust.last_scored_at else {
+        return Err("never_scored");
+    };
+    let elapsed = as_of - last_scored_at;
+    if elapsed <= Duration::ZERO {
+        return Err("not_elapsed");
+    }
+
+    let elapsed_days = elapsed.as_seconds_f64() / Duration::DAY.as_seconds_f64();
+    let decay_term = ruleset.decay_model.factor(
+        ruleset.read_decay_lambda_per_day,
+        ruleset.read_decay_step_days,
+        elapsed_days,
+    );
+    Ok((elapsed_days, decay_term))
+}
+
+/// Gate `trust`, a snapshot already brought to the gating time by [`apply_as_of_decay`]. A
+/// retired snapshot that still carries `retire_grace_until` is soft-retired: excluded in Safe
+/// mode and included, flagged, in Exploration mode.
 #[must_use]
 pub fn gate_memory(
     trust: &MemoryTrust,
@@ -691,7 +1359,19 @@ pub fn gate_memory(
     let mut reason_codes = Vec::new();
 
     if trust.trust_status == TrustStatus::Retired {
-        reason_codes.push("excluded.retired".to_string());
+        reason_codes.push(
+            match (trust.retire_grace_until, mode) {
+                (None, _) => memory_kernel_core::REASON_EXCLUDED_RETIRED,
+                (Some(_), RetrievalMode::Safe) => {
+                    memory_kernel_core::REASON_EXCLUDED_SAFE_RETIRE_GRACE
+                }
+                (Some(_), RetrievalMode::Exploration) => {
+                    include = true;
+                    memory_kernel_core::REASON_INCLUDED_EXPLORATION_RETIRE_GRACE
+                }
+            }
+            .to_string(),
+        );
         return GateDecision {
             memory_id: trust.memory_id,
             version: trust.version,
@@ -712,9 +1392,11 @@ pub fn gate_memory(
                 && trust.confidence_effective >= ruleset.safe_min_confidence
             {
                 include = true;
-                reason_codes.push("included.safe.validated_threshold".to_string());
+                reason_codes
+                    .push(memory_kernel_core::REASON_INCLUDED_SAFE_VALIDATED_THRESHOLD.to_string());
             } else {
-                reason_codes.push("excluded.safe.threshold_or_status".to_string());
+                reason_codes
+                    .push(memory_kernel_core::REASON_EXCLUDED_SAFE_THRESHOLD_OR_STATUS.to_string());
             }
         }
         RetrievalMode::Exploration => {
@@ -723,12 +1405,16 @@ pub fn gate_memory(
                 && trust.confidence_effective >= ruleset.safe_min_confidence
             {
                 include = true;
-                reason_codes.push("included.exploration.safe_equivalent".to_string());
+                reason_codes.push(
+                    memory_kernel_core::REASON_INCLUDED_EXPLORATION_SAFE_EQUIVALENT.to_string(),
+                );
             } else if trust.trust_status == TrustStatus::Active
                 && trust.confidence_effective >= ruleset.exploration_min_confidence
             {
                 include = true;
-                reason_codes.push("included.exploration.active_threshold".to_string());
+                reason_codes.push(
+                    memory_kernel_core::REASON_INCLUDED_EXPLORATION_ACTIVE_THRESHOLD.to_string(),
+                );
             } else if trust.trust_status == TrustStatus::Active
                 && trust.confidence_effective >= ruleset.exploration_probe_min_confidence
                 && trust.confidence_effective < ruleset.exploration_probe_max_confidence
@@ -741,12 +1427,18 @@ pub fn gate_memory(
                 ));
                 if bucket <= ruleset.exploration_probe_budget {
                     include = true;
-                    reason_codes.push("included.exploration.probe_bucket".to_string());
+                    reason_codes.push(
+                        memory_kernel_core::REASON_INCLUDED_EXPLORATION_PROBE_BUCKET.to_string(),
+                    );
                 } else {
-                    reason_codes.push("excluded.exploration.probe_bucket".to_string());
+                    reason_codes.push(
+                        memory_kernel_core::REASON_EXCLUDED_EXPLORATION_PROBE_BUCKET.to_string(),
+                    );
                 }
             } else {
-                reason_codes.push("excluded.exploration.threshold_or_status".to_string());
+                reason_codes.push(
+                    memory_kernel_core::REASON_EXCLUDED_EXPLORATION_THRESHOLD_OR_STATUS.to_string(),
+                );
             }
         }
     }
@@ -762,6 +1454,280 @@ pub fn gate_memory(
     }
 }
 
+/// Supersession-aware gating: exclude every included decision for which `latest_trusted`, the
+/// newest version of each memory that passes the gate and is not retired, names a newer version
+/// of the same memory. The decision's inclusion reasons are replaced by
+/// `excluded.superseded_by_newer_version`; other reasons, such as ruleset pins, are kept.
+pub fn exclude_superseded_versions(
+    decisions: &mut [GateDecision],
+    latest_trusted: &BTreeMap<MemoryId, u32>,
+) {
+    for decision in decisions {
+        let superseded = latest_trusted
+            .get(&decision.memory_id)
+            .is_some_and(|latest| *latest > decision.version);
+        if decision.include && superseded {
+            decision.include = false;
+            decision
+                .reason_codes
+                .retain(|code| !code.starts_with("included."));
+            decision.reason_codes.insert(
+                0,
+                memory_kernel_core::REASON_EXCLUDED_SUPERSEDED_BY_NEWER_VERSION.to_string(),
+            );
+        }
+    }
+}
+
+/// Whether `decision`, gated under `ruleset`, may supersede older versions under
+/// [`exclude_superseded_versions`]: it must be included on its own merit, so validated, or
+/// active, uncapped and at least `safe_min_confidence`. Exploration probe and retire-grace
+/// inclusions never supersede.
+#[must_use]
+pub fn is_trusted_for_supersession(decision: &GateDecision, ruleset: &OutcomeRuleset) -> bool {
+    if !decision.include
+        || decision.reason_codes.iter().any(|code| {
+            code == memory_kernel_core::REASON_INCLUDED_EXPLORATION_PROBE_BUCKET
+                || code == memory_kernel_core::REASON_INCLUDED_EXPLORATION_RETIRE_GRACE
+        })
+    {
+        return false;
+    }
+    match decision.trust_status {
+        TrustStatus::Validated => true,
+        TrustStatus::Active => {
+            !decision.capped && decision.confidence_effective >= ruleset.safe_min_confidence
+        }
+        TrustStatus::Retired => false,
+    }
+}
+
+/// Explain how [`gate_memory`] decides `trust` as of `as_of`, returning the explanation with
+/// the decision itself so the two cannot disagree.
+#[must_use]
+pub fn explain_gate_memory(
+    trust: &MemoryTrust,
+    mode: RetrievalMode,
+    context_id: Option<&str>,
+    ruleset: &OutcomeRuleset,
+    as_of: OffsetDateTime,
+) -> (GateExplanation, GateDecision) {
+    let decayed = apply_as_of_decay(trust, ruleset, as_of);
+    let decision = gate_memory(&decayed, mode, context_id, ruleset);
+    let read_decay = read_decay_term(trust, ruleset, as_of);
+    let decay = DecayExplanation {
+        applied: read_decay.is_ok(),
+        skipped_reason: read_decay.err().map(str::to_string),
+        last_scored_at: trust.last_scored_at,
+        elapsed_days: read_decay.map_or(0.0, |(days, _)| days),
+        decay_model: ruleset.decay_model,
+        lambda_per_day: ruleset.read_decay_lambda_per_day,
+        decay_factor: read_decay.map_or(1.0, |(_, term)| term),
+        baseline_confidence: trust.baseline_confidence,
+        confidence_raw_before: trust.confidence_raw,
+        confidence_raw_after: decayed.confidence_raw,
+        authority_floor: read_decay
+            .ok()
+            .and_then(|_| authority_floor(trust, ruleset)),
+    };
+    let cap = CapExplanation {
+        contradiction_cap_active: decayed.contradiction_cap_active,
+        manual_override_active: decayed.manual_override_active,
+        cap_value: decayed.cap_value,
+        confidence_before_cap: decayed.confidence_raw,
+        confidence_effective: decayed.confidence_effective,
+        capped: decision.capped,
+    };
+
+    let mut explainer = GateExplainer {
+        checks: Vec::new(),
+        parameters_used: BTreeMap::from([(
+            "read_decay_lambda_per_day".to_string(),
+            json!(ruleset.read_decay_lambda_per_day),
+        )]),
+    };
+    let mut probe_bucket = None;
+    let status = decayed.trust_status;
+    if explainer.check(
+        "not_retired",
+        "trust_status",
+        json!(status),
+        "!=",
+        json!(TrustStatus::Retired),
+        status != TrustStatus::Retired,
+    ) {
+        let validated = explainer.safe_validated(&decayed, decision.capped, ruleset);
+        if mode == RetrievalMode::Exploration && !validated {
+            probe_bucket = explainer.exploration(&decayed, context_id, ruleset);
+        }
+    } else if let Some(until) = trust.retire_grace_until {
+        explainer.parameters_used.insert(
+            "retire_grace_days".to_string(),
+            json!(ruleset.retire_grace_days),
+        );
+        let in_grace = explainer.check(
+            "retire_grace",
+            "as_of",
+            json!(format_rfc3339(as_of).ok()),
+            "<",
+            json!(format_rfc3339(until).ok()),
+            decayed.retire_grace_until.is_some(),
+        );
+        if in_grace {
+            explainer.check(
+                "retire_grace",
+                "mode",
+                json!(mode),
+                "==",
+                json!(RetrievalMode::Exploration),
+                mode == RetrievalMode::Exploration,
+            );
+        }
+    }
+
+    (
+        GateExplanation {
+            ruleset_version: ruleset.ruleset_version,
+            parameters_used: explainer.parameters_used,
+            decay,
+            cap,
+            checks: explainer.checks,
+            probe_bucket,
+        },
+        decision,
+    )
+}
+
+struct GateExplainer {
+    checks: Vec<GateCheck>,
+    parameters_used: BTreeMap<String, Value>,
+}
+
+impl GateExplainer {
+    fn check(
+        &mut self,
+        rule: &str,
+        check: &str,
+        actual: Value,
+        op: &str,
+        required: Value,
+        passed: bool,
+    ) -> bool {
+        self.checks.push(GateCheck {
+            rule: rule.to_string(),
+            check: check.to_string(),
+            actual,
+            op: op.to_string(),
+            required,
+            passed,
+        });
+        passed
+    }
+
+    fn threshold(
+        &mut self,
+        rule: &str,
+        actual: f32,
+        op: &str,
+        parameter: &str,
+        required: f32,
+    ) -> bool {
+        let passed = match op {
+            ">=" => actual >= required,
+            "<" => actual < required,
+            _ => actual <= required,
+        };
+        self.parameters_used
+            .insert(parameter.to_string(), json!(required));
+        self.check(rule, parameter, json!(actual), op, json!(required), passed)
+    }
+
+    fn safe_validated(
+        &mut self,
+        trust: &MemoryTrust,
+        capped: bool,
+        ruleset: &OutcomeRuleset,
+    ) -> bool {
+        let rule = "safe_validated";
+        self.check(
+            rule,
+            "trust_status",
+            json!(trust.trust_status),
+            "==",
+            json!(TrustStatus::Validated),
+            trust.trust_status == TrustStatus::Validated,
+        ) & self.check(rule, "capped", json!(capped), "==", json!(false), !capped)
+            & self.threshold(
+                rule,
+                trust.confidence_effective,
+                ">=",
+                "safe_min_confidence",
+                ruleset.safe_min_confidence,
+            )
+    }
+
+    /// The exploration active and probe paths, returning the probe bucket when it was drawn.
+    fn exploration(
+        &mut self,
+        trust: &MemoryTrust,
+        context_id: Option<&str>,
+        ruleset: &OutcomeRuleset,
+    ) -> Option<f32> {
+        let confidence = trust.confidence_effective;
+        let active = trust.trust_status == TrustStatus::Active;
+        let rule = "exploration_active";
+        let active_included = self.check(
+            rule,
+            "trust_status",
+            json!(trust.trust_status),
+            "==",
+            json!(TrustStatus::Active),
+            active,
+        ) & self.threshold(
+            rule,
+            confidence,
+            ">=",
+            "exploration_min_confidence",
+            ruleset.exploration_min_confidence,
+        );
+        if !active || active_included {
+            return None;
+        }
+
+        let rule = "exploration_probe";
+        let in_band = self.threshold(
+            rule,
+            confidence,
+            ">=",
+            "exploration_probe_min_confidence",
+            ruleset.exploration_probe_min_confidence,
+        ) & self.threshold(
+            rule,
+            confidence,
+            "<",
+            "exploration_probe_max_confidence",
+            ruleset.exploration_probe_max_confidence,
+        );
+        if !in_band {
+            return None;
+        }
+        let bucket = deterministic_bucket(&format!(
+            "{}:{}:{}",
+            trust.memory_id,
+            trust.version,
+            context_id.unwrap_or_default()
+        ));
+        self.threshold(
+            rule,
+            bucket,
+            "<=",
+            "exploration_probe_budget",
+            ruleset.exploration_probe_budget,
+        );
+        Some(bucket)
+    }
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 enum OutcomeWindowEntry {
     Success,
@@ -925,6 +1891,12 @@ mod tests {
             manual_confidence: None,
             override_cap: false,
             payload_json: Value::Object(Map::default()),
+            cosigner: None,
+            signature: None,
+            source: None,
+            effective_at: None,
+            prev_event_hash: None,
+            event_hash: None,
         }
     }
 
@@ -945,6 +1917,43 @@ mod tests {
         assert!(trust.confidence_raw < 0.6);
     }
 
+    #[test]
+    fn failure_severity_weights_scale_failure_impact() {
+        let mut ruleset = OutcomeRuleset::v1();
+        ruleset.failure_severity_low_weight = 0.5;
+        ruleset.failure_severity_high_weight = 2.0;
+        let mut rulesets = BTreeMap::new();
+        rulesets.insert(1, ruleset);
+
+        let confidence_after = |severity: Option<Severity>| {
+            let mut failure = fixture_event(1, OutcomeEventType::Failure);
+            failure.severity = severity;
+            must_some(must_ok(project_memory_trust(&[failure], &rulesets))).confidence_raw
+        };
+
+        let unrated = confidence_after(None);
+        let low = confidence_after(Some(Severity::Low));
+        let med = confidence_after(Some(Severity::Med));
+        let high = confidence_after(Some(Severity::High));
+        assert!(high < med);
+        assert!(med < low);
+        assert!((med - unrated).abs() < f32::EPSILON);
+
+        let neutral = must_some(must_ok(project_memory_trust(
+            &[{
+                let mut failure = fixture_event(1, OutcomeEventType::Failure);
+                failure.severity = Some(Severity::High);
+                failure
+            }],
+            &ruleset_map(),
+        )));
+        assert!((neutral.confidence_raw - unrated).abs() < f32::EPSILON);
+
+        let mut invalid = OutcomeRuleset::v1();
+        invalid.failure_severity_med_weight = -1.0;
+        assert!(invalid.validate().is_err());
+    }
+
     #[test]
     fn contradiction_applies_cap_without_override() {
         let success = fixture_event(1, OutcomeEventType::Success);
@@ -982,6 +1991,120 @@ mod tests {
         assert_eq!(trust.trust_status, TrustStatus::Retired);
     }
 
+    #[test]
+    fn soft_retirement_has_a_grace_period_and_reinstates_on_successes() {
+        let mut ruleset = OutcomeRuleset::v1();
+        ruleset.retire_grace_days = 7.0;
+        let rulesets = BTreeMap::from([(1, ruleset.clone())]);
+        let event_at = |seq: i64, event_type: OutcomeEventType, occurred_at: &str| {
+            let mut event = fixture_event(seq, event_type);
+            event.occurred_at = must_utc(occurred_at);
+            event
+        };
+        let retired = vec![
+            event_at(1, OutcomeEventType::Success, "2026-02-01T12:00:00Z"),
+            event_at(2, OutcomeEventType::ManualRetire, "2026-02-07T12:00:00Z"),
+        ];
+
+        let trust = must_some(must_ok(project_memory_trust(&retired, &rulesets)));
+        assert_eq!(trust.trust_status, TrustStatus::Retired);
+        assert_eq!(
+            trust.retire_grace_until,
+            Some(must_utc("2026-02-14T12:00:00Z"))
+        );
+        let gate = |mode: RetrievalMode, as_of: &str| {
+            gate_memory(
+                &apply_as_of_decay(&trust, &ruleset, must_utc(as_of)),
+                mode,
+                None,
+                &ruleset,
+            )
+        };
+        let safe = gate(RetrievalMode::Safe, "2026-02-10T00:00:00Z");
+        assert!(!safe.include);
+        assert_eq!(safe.reason_codes, vec!["excluded.safe.retire_grace"]);
+        let exploration = gate(RetrievalMode::Exploration, "2026-02-10T00:00:00Z");
+        assert!(exploration.include);
+        assert_eq!(
+            exploration.reason_codes,
+            vec!["included.exploration.retire_grace"]
+        );
+        let expired = gate(RetrievalMode::Exploration, "2026-02-20T00:00:00Z");
+        assert!(!expired.include);
+        assert_eq!(expired.reason_codes, vec!["excluded.retired"]);
+        let (explanation, decision) = explain_gate_memory(
+            &trust,
+            RetrievalMode::Exploration,
+            None,
+            &ruleset,
+            must_utc("2026-02-10T00:00:00Z"),
+        );
+        assert_eq!(decision, exploration);
+        assert!(explanation
+            .checks
+            .iter()
+            .filter(|check| check.rule == "retire_grace")
+            .all(|check| check.passed));
+
+        let project = |extra: &[(OutcomeEventType, &str)]| {
+            let mut events = retired.clone();
+            for (offset, (event_type, occurred_at)) in (3_i64..).zip(extra) {
+                events.push(event_at(offset, *event_type, occurred_at));
+            }
+            must_some(must_ok(project_memory_trust(&events, &rulesets)))
+        };
+        let reinstated = project(&[
+            (OutcomeEventType::Success, "2026-02-09T00:00:00Z"),
+            (OutcomeEventType::Success, "2026-02-10T00:00:00Z"),
+        ]);
+        // Reinstatement recomputes the status, and the three wins in the window validate it.
+        assert_eq!(reinstated.trust_status, TrustStatus::Validated);
+        assert_eq!(reinstated.retire_grace_until, None);
+        let interrupted = project(&[
+            (OutcomeEventType::Success, "2026-02-09T00:00:00Z"),
+            (OutcomeEventType::Failure, "2026-02-10T00:00:00Z"),
+            (OutcomeEventType::Success, "2026-02-11T00:00:00Z"),
+        ]);
+        assert_eq!(interrupted.trust_status, TrustStatus::Retired);
+        let late = project(&[
+            (OutcomeEventType::Success, "2026-02-15T00:00:00Z"),
+            (OutcomeEventType::Success, "2026-02-16T00:00:00Z"),
+        ]);
+        assert_eq!(late.trust_status, TrustStatus::Retired);
+
+        let hard = must_some(must_ok(project_memory_trust(&retired, &ruleset_map())));
+        assert_eq!(hard.retire_grace_until, None);
+        ruleset.retire_reinstate_successes = 0;
+        assert!(ruleset.validate().is_err());
+    }
+
+    #[test]
+    fn scheduled_events_only_count_once_effective() {
+        let mut retire = fixture_event(2, OutcomeEventType::ManualRetire);
+        retire.effective_at = Some(must_utc("2026-03-31T23:59:59Z"));
+        let events = vec![fixture_event(1, OutcomeEventType::Success), retire.clone()];
+
+        let before = must_some(must_ok(project_memory_trust_as_of(
+            &events,
+            &ruleset_map(),
+            must_utc("2026-03-01T00:00:00Z"),
+        )));
+        assert_eq!(before.trust_status, TrustStatus::Active);
+        assert_eq!(before.last_event_seq, 1);
+
+        let after = must_some(must_ok(project_memory_trust_as_of(
+            &events,
+            &ruleset_map(),
+            must_utc("2026-04-01T00:00:00Z"),
+        )));
+        assert_eq!(after.trust_status, TrustStatus::Retired);
+        assert_eq!(after.last_event_seq, 2);
+
+        let mut input = OutcomeEventInput::from(&retire);
+        input.effective_at = Some(must_utc("2026-02-01T00:00:00Z"));
+        assert!(input.validate().is_err());
+    }
+
     #[test]
     fn manual_promote_requires_reearning_validation() {
         let events = vec![
@@ -1051,6 +2174,8 @@ mod tests {
             last_event_seq: 10,
             last_scored_at: Some(must_utc("2026-02-07T12:00:00Z")),
             updated_at: must_utc("2026-02-07T12:00:00Z"),
+            retire_grace_until: None,
+            authoritative: false,
         };
 
         let decision = gate_memory(
@@ -1062,6 +2187,51 @@ mod tests {
         assert!(!decision.include);
     }
 
+    #[test]
+    fn gate_reason_codes_are_registered() {
+        let registry = memory_kernel_core::ReasonCodeRegistry::default();
+        let ruleset = OutcomeRuleset::v1();
+        let mut emitted = std::collections::BTreeSet::new();
+        for trust_status in [
+            TrustStatus::Active,
+            TrustStatus::Validated,
+            TrustStatus::Retired,
+        ] {
+            for confidence in [0.05_f32, 0.3, 0.5, 0.7, 0.95] {
+                let trust = MemoryTrust {
+                    memory_id: fixture_memory_id(),
+                    version: 1,
+                    confidence_raw: confidence,
+                    confidence_effective: confidence,
+                    baseline_confidence: 0.5,
+                    trust_status,
+                    contradiction_cap_active: false,
+                    cap_value: 1.0,
+                    manual_override_active: false,
+                    wins_last5: 0,
+                    failures_last5: 0,
+                    last_event_seq: 1,
+                    last_scored_at: None,
+                    updated_at: must_utc("2026-02-07T12:00:00Z"),
+                    retire_grace_until: None,
+                    authoritative: false,
+                };
+                for mode in [RetrievalMode::Safe, RetrievalMode::Exploration] {
+                    for context in 0..16 {
+                        let context_id = format!("ctx-{context}");
+                        emitted.extend(
+                            gate_memory(&trust, mode, Some(&context_id), &ruleset).reason_codes,
+                        );
+                    }
+                }
+            }
+        }
+        assert!(emitted.len() > 4);
+        assert!(registry
+            .validate(emitted.iter().map(String::as_str))
+            .is_ok());
+    }
+
     #[test]
     fn exploration_probe_bucket_is_deterministic() {
         let trust = MemoryTrust {
@@ -1079,6 +2249,8 @@ mod tests {
             last_event_seq: 10,
             last_scored_at: Some(must_utc("2026-02-07T12:00:00Z")),
             updated_at: must_utc("2026-02-07T12:00:00Z"),
+            retire_grace_until: None,
+            authoritative: false,
         };
 
         let ruleset = OutcomeRuleset::v1();
@@ -1104,6 +2276,8 @@ mod tests {
             last_event_seq: 2,
             last_scored_at: Some(must_utc("2026-02-01T00:00:00Z")),
             updated_at: must_utc("2026-02-01T00:00:00Z"),
+            retire_grace_until: None,
+            authoritative: false,
         };
 
         let decayed = apply_as_of_decay(
@@ -1115,4 +2289,299 @@ mod tests {
         assert!(decayed.confidence_raw < trust.confidence_raw);
         assert!(decayed.confidence_raw > trust.baseline_confidence);
     }
+
+    #[test]
+    fn decay_model_selects_the_read_decay_curve() {
+        let trust = MemoryTrust {
+            memory_id: fixture_memory_id(),
+            version: 1,
+            confidence_raw: 0.9,
+            confidence_effective: 0.9,
+            baseline_confidence: 0.5,
+            trust_status: TrustStatus::Active,
+            contradiction_cap_active: false,
+            cap_value: 1.0,
+            manual_override_active: false,
+            wins_last5: 3,
+            failures_last5: 0,
+            last_event_seq: 2,
+            last_scored_at: Some(must_utc("2026-02-01T00:00:00Z")),
+            updated_at: must_utc("2026-02-01T00:00:00Z"),
+            retire_grace_until: None,
+            authoritative: false,
+        };
+        let decayed = |model: &str, as_of: &str| {
+            let mut raw =
+                serde_json::to_value(OutcomeRuleset::v1()).unwrap_or_else(|_| unreachable!());
+            raw["decay_model"] = json!(model);
+            raw["read_decay_lambda_per_day"] = json!(0.05);
+            raw["read_decay_step_days"] = json!(7.0);
+            let ruleset = must_ok(OutcomeRuleset::from_json(&raw));
+            apply_as_of_decay(&trust, &ruleset, must_utc(as_of)).confidence_raw
+        };
+        let close = |actual: f32, expected: f32| (actual - expected).abs() < 1e-5;
+
+        assert!(close(
+            decayed("exponential", "2026-02-07T00:00:00Z"),
+            0.5 + 0.4 * (-0.3_f32).exp()
+        ));
+        assert!(close(decayed("linear", "2026-02-07T00:00:00Z"), 0.78));
+        assert!(close(decayed("linear", "2026-03-31T00:00:00Z"), 0.5));
+        assert!(close(decayed("step", "2026-02-07T00:00:00Z"), 0.9));
+        assert!(close(
+            decayed("step", "2026-02-16T00:00:00Z"),
+            0.5 + 0.4 * (-0.7_f32).exp()
+        ));
+        assert!(close(decayed("none", "2026-12-01T00:00:00Z"), 0.9));
+
+        let mut legacy =
+            serde_json::to_value(OutcomeRuleset::v1()).unwrap_or_else(|_| unreachable!());
+        if let Some(fields) = legacy.as_object_mut() {
+            fields.remove("decay_model");
+            fields.remove("read_decay_step_days");
+        }
+        assert_eq!(
+            must_ok(OutcomeRuleset::from_json(&legacy)),
+            OutcomeRuleset::v1()
+        );
+        legacy["decay_model"] = json!("cubic");
+        assert!(OutcomeRuleset::from_json(&legacy).is_err());
+        legacy["decay_model"] = json!("step");
+        legacy["read_decay_step_days"] = json!(0.0);
+        assert!(OutcomeRuleset::from_json(&legacy).is_err());
+    }
+
+    #[test]
+    fn supersession_excludes_versions_older_than_the_latest_trusted_one() {
+        let decision = |version: u32, include: bool, reason: &str| GateDecision {
+            memory_id: fixture_memory_id(),
+            version,
+            include,
+            confidence_effective: 0.7,
+            trust_status: TrustStatus::Validated,
+            capped: false,
+            reason_codes: vec![reason.to_string(), "ruleset.pinned.v2".to_string()],
+        };
+        let mut decisions = vec![
+            decision(1, true, "included.safe.validated"),
+            decision(2, false, "excluded.safe.threshold_or_status"),
+            decision(3, true, "included.safe.validated"),
+        ];
+        let ruleset = OutcomeRuleset::v1();
+        assert!(is_trusted_for_supersession(&decisions[2], &ruleset));
+        let mut retired = decisions[2].clone();
+        retired.trust_status = TrustStatus::Retired;
+        assert!(!is_trusted_for_supersession(&retired, &ruleset));
+        let mut active = decisions[2].clone();
+        active.trust_status = TrustStatus::Active;
+        active.reason_codes = vec!["included.exploration.active_threshold".to_string()];
+        assert!(is_trusted_for_supersession(&active, &ruleset));
+        active.confidence_effective = 0.45;
+        assert!(!is_trusted_for_supersession(&active, &ruleset));
+        let mut probe = active.clone();
+        probe.confidence_effective = 0.7;
+        probe.reason_codes = vec!["included.exploration.probe_bucket".to_string()];
+        assert!(!is_trusted_for_supersession(&probe, &ruleset));
+
+        exclude_superseded_versions(&mut decisions, &BTreeMap::from([(fixture_memory_id(), 3)]));
+        assert_eq!(
+            decisions
+                .iter()
+                .map(|decision| (decision.include, decision.reason_codes[0].as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (false, "excluded.superseded_by_newer_version"),
+                (false, "excluded.safe.threshold_or_status"),
+                (true, "included.safe.validated"),
+            ]
+        );
+        assert_eq!(decisions[0].reason_codes[1], "ruleset.pinned.v2");
+    }
+
+    #[test]
+    fn authoritative_memories_do_not_decay_below_the_ruleset_floor() {
+        let mut ruleset = OutcomeRuleset::v1();
+        ruleset.read_decay_lambda_per_day = 0.5;
+        ruleset.authoritative_confidence_floor = 0.8;
+        let mut trust = MemoryTrust {
+            memory_id: fixture_memory_id(),
+            version: 1,
+            confidence_raw: 0.95,
+            confidence_effective: 0.95,
+            baseline_confidence: 0.5,
+            trust_status: TrustStatus::Validated,
+            contradiction_cap_active: false,
+            cap_value: 1.0,
+            manual_override_active: false,
+            wins_last5: 3,
+            failures_last5: 0,
+            last_event_seq: 3,
+            last_scored_at: Some(must_utc("2026-02-01T00:00:00Z")),
+            updated_at: must_utc("2026-02-01T00:00:00Z"),
+            retire_grace_until: None,
+            authoritative: false,
+        };
+        let as_of = must_utc("2026-03-01T00:00:00Z");
+
+        let unflagged = apply_as_of_decay(&trust, &ruleset, as_of);
+        assert!(unflagged.confidence_raw < 0.55);
+
+        trust.authoritative = true;
+        let floored = apply_as_of_decay(&trust, &ruleset, as_of);
+        assert!((floored.confidence_raw - 0.8).abs() < f32::EPSILON);
+        assert!((floored.confidence_effective - 0.8).abs() < f32::EPSILON);
+        let decision = gate_memory(&floored, RetrievalMode::Safe, None, &ruleset);
+        assert!(decision.include);
+        let (explanation, explained) =
+            explain_gate_memory(&trust, RetrievalMode::Safe, None, &ruleset, as_of);
+        assert_eq!(explanation.decay.authority_floor, Some(0.8));
+        assert_eq!(explained, decision);
+
+        // Failures took the memory under the floor; decay cannot hold it above where they left
+        // it, and only moves it back toward baseline.
+        trust.confidence_raw = 0.3;
+        trust.confidence_effective = 0.3;
+        let failed = apply_as_of_decay(&trust, &ruleset, as_of);
+        assert!(failed.confidence_raw > 0.3 && failed.confidence_raw < 0.8);
+
+        ruleset.authoritative_confidence_floor = 1.5;
+        assert!(ruleset.validate().is_err());
+    }
+
+    #[test]
+    fn explain_gate_memory_matches_gate_decision_and_records_checks() {
+        let ruleset = OutcomeRuleset::v1();
+        let as_of = must_utc("2026-03-09T12:00:00Z");
+        let validated = must_some(must_ok(project_memory_trust(
+            &[
+                fixture_event(1, OutcomeEventType::Success),
+                fixture_event(2, OutcomeEventType::Success),
+                fixture_event(3, OutcomeEventType::Success),
+            ],
+            &ruleset_map(),
+        )));
+
+        let (explanation, decision) =
+            explain_gate_memory(&validated, RetrievalMode::Safe, None, &ruleset, as_of);
+        let expected = gate_memory(
+            &apply_as_of_decay(&validated, &ruleset, as_of),
+            RetrievalMode::Safe,
+            None,
+            &ruleset,
+        );
+        assert_eq!(decision, expected);
+        assert!(explanation.decay.applied);
+        assert!(explanation.decay.elapsed_days > 29.0);
+        assert!(explanation.decay.decay_factor < 1.0);
+        assert!(
+            (explanation.cap.confidence_effective - decision.confidence_effective).abs() < 1e-6
+        );
+        let rules: Vec<(&str, bool)> = explanation
+            .checks
+            .iter()
+            .map(|check| (check.rule.as_str(), check.passed))
+            .collect();
+        assert_eq!(rules[0], ("not_retired", true));
+        assert!(rules[1..].iter().all(|(rule, _)| *rule == "safe_validated"));
+        assert_eq!(
+            explanation.checks.iter().all(|check| check.passed),
+            decision.include
+        );
+        assert!(explanation
+            .parameters_used
+            .contains_key("safe_min_confidence"));
+        assert_eq!(explanation.probe_bucket, None);
+
+        let capped = must_some(must_ok(project_memory_trust(
+            &[
+                fixture_event(1, OutcomeEventType::Success),
+                fixture_event(2, OutcomeEventType::AuthoritativeContradiction),
+            ],
+            &ruleset_map(),
+        )));
+        let (explanation, decision) = explain_gate_memory(
+            &capped,
+            RetrievalMode::Exploration,
+            Some("ctx-1"),
+            &ruleset,
+            as_of,
+        );
+        assert!(explanation.cap.capped);
+        assert!(decision.capped);
+        assert!(explanation
+            .checks
+            .iter()
+            .any(|check| check.rule == "exploration_active"));
+
+        let retired = must_some(must_ok(project_memory_trust(
+            &[
+                fixture_event(1, OutcomeEventType::Success),
+                fixture_event(2, OutcomeEventType::ManualRetire),
+            ],
+            &ruleset_map(),
+        )));
+        let (explanation, decision) =
+            explain_gate_memory(&retired, RetrievalMode::Safe, None, &ruleset, as_of);
+        assert!(!decision.include);
+        assert!(!explanation.decay.applied);
+        assert_eq!(explanation.decay.skipped_reason.as_deref(), Some("retired"));
+        assert_eq!(explanation.checks.len(), 1);
+        assert!(!explanation.checks[0].passed);
+    }
+
+    #[test]
+    fn event_source_is_covered_by_signing_material_only_when_present() {
+        let event = fixture_event(1, OutcomeEventType::Success);
+        let unattributed = must_ok(OutcomeEventInput::from(&event).signing_material());
+        assert!(!String::from_utf8_lossy(&unattributed).contains("\"source\""));
+
+        let mut attributed = event.clone();
+        attributed.source = Some(OutcomeEventSource {
+            run_id: "run-1".to_string(),
+            step_id: None,
+            gate_context: None,
+            engine_version: Some("v0".to_string()),
+        });
+        let material = must_ok(OutcomeEventInput::from(&attributed).signing_material());
+        assert_ne!(material, unattributed);
+        assert_ne!(
+            must_ok(attributed.chain_material()),
+            must_ok(event.chain_material())
+        );
+    }
+
+    #[test]
+    fn ruleset_parameters_cover_every_field_and_diff_reports_changes() {
+        let v1 = OutcomeRuleset::v1();
+        let value = must_ok(serde_json::to_value(&v1));
+        let mut fields: Vec<&str> = must_some(value.as_object())
+            .keys()
+            .map(String::as_str)
+            .filter(|key| *key != "ruleset_version")
+            .collect();
+        let mut names: Vec<&str> = v1.parameters().iter().map(|(name, _, _)| *name).collect();
+        fields.sort_unstable();
+        names.sort_unstable();
+        assert_eq!(names, fields);
+
+        assert!(diff_rulesets(&v1, &v1).is_empty());
+        let mut v2 = v1.clone();
+        v2.ruleset_version = 2;
+        v2.safe_min_confidence = 0.65;
+        v2.failure_weight = -1.5;
+        let changes = diff_rulesets(&v1, &v2);
+        let summary: Vec<(&str, &str)> = changes
+            .iter()
+            .map(|change| (change.parameter.as_str(), change.category.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("failure_weight", "weight"),
+                ("safe_min_confidence", "threshold")
+            ]
+        );
+        assert_eq!(changes[1].from, json!(0.60_f32));
+        assert_eq!(changes[1].to, json!(0.65_f32));
+    }
 }
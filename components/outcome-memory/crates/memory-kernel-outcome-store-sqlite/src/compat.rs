@@ -0,0 +1,245 @@
+//! Compatibility of the host `MemoryKernel` schema with the outcome store (`mk doctor compat`).
+
+use anyhow::{anyhow, Context, Result};
+use rusqlite::Connection;
+
+use crate::{table_exists, SqliteOutcomeStore};
+
+const MEMORY_RECORDS: &str = "memory_records";
+
+/// `memory_records` columns the outcome store relies on, with the type affinity each must have.
+/// `memory_id` and `version` key outcome rows; the context path filters on `record_type` and
+/// `effective_at`.
+const REQUIRED_COLUMNS: [(&str, ColumnAffinity); 4] = [
+    ("memory_id", ColumnAffinity::Text),
+    ("version", ColumnAffinity::Integer),
+    ("record_type", ColumnAffinity::Text),
+    ("effective_at", ColumnAffinity::Text),
+];
+
+/// `SQLite` type affinity of a declared column type.
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnAffinity {
+    Text,
+    Numeric,
+    Integer,
+    Real,
+    Blob,
+}
+
+impl ColumnAffinity {
+    /// Affinity `SQLite` derives from a declared type (section 3.1 of the datatype docs).
+    #[must_use]
+    pub fn of_declared_type(declared: &str) -> Self {
+        let declared = declared.to_ascii_uppercase();
+        if declared.contains("INT") {
+            Self::Integer
+        } else if ["CHAR", "CLOB", "TEXT"]
+            .iter()
+            .any(|marker| declared.contains(marker))
+        {
+            Self::Text
+        } else if declared.is_empty() || declared.contains("BLOB") {
+            Self::Blob
+        } else if ["REAL", "FLOA", "DOUB"]
+            .iter()
+            .any(|marker| declared.contains(marker))
+        {
+            Self::Real
+        } else {
+            Self::Numeric
+        }
+    }
+
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Text => "TEXT",
+            Self::Numeric => "NUMERIC",
+            Self::Integer => "INTEGER",
+            Self::Real => "REAL",
+            Self::Blob => "BLOB",
+        }
+    }
+}
+
+/// One compatibility check. Failed checks carry a remediation hint.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct CompatibilityCheck {
+    pub check: String,
+    pub passed: bool,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+}
+
+impl CompatibilityCheck {
+    fn pass(check: String, message: String) -> Self {
+        Self {
+            check,
+            passed: true,
+            message,
+            remediation: None,
+        }
+    }
+
+    fn fail(check: String, message: String, remediation: &str) -> Self {
+        Self {
+            check,
+            passed: false,
+            message,
+            remediation: Some(remediation.to_string()),
+        }
+    }
+}
+
+/// Result of checking the host `MemoryKernel` schema. `compatible` holds when every check passed;
+/// the column and index checks are skipped when `memory_records` is missing.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct CompatibilityReport {
+    pub compatible: bool,
+    pub checks: Vec<CompatibilityCheck>,
+}
+
+impl SqliteOutcomeStore {
+    /// Check `memory_records` for the columns, column types, and identity key the outcome store
+    /// and the context path depend on.
+    pub fn memory_kernel_compatibility_report(&self) -> Result<CompatibilityReport> {
+        compatibility_report(&self.conn)
+    }
+}
+
+/// Fail with the first failed compatibility check.
+pub(crate) fn ensure_memory_kernel_compatibility(conn: &Connection) -> Result<()> {
+    let report = compatibility_report(conn)?;
+    match report.checks.into_iter().find(|check| !check.passed) {
+        Some(failed) => Err(anyhow!(
+            "MemoryKernel compatibility check failed: {}",
+            failed.message
+        )),
+        None => Ok(()),
+    }
+}
+
+fn compatibility_report(conn: &Connection) -> Result<CompatibilityReport> {
+    let mut checks = Vec::new();
+    if table_exists(conn, MEMORY_RECORDS)? {
+        checks.push(CompatibilityCheck::pass(
+            format!("table:{MEMORY_RECORDS}"),
+            format!("found table {MEMORY_RECORDS}"),
+        ));
+    } else {
+        checks.push(CompatibilityCheck::fail(
+            format!("table:{MEMORY_RECORDS}"),
+            format!("expected table {MEMORY_RECORDS}"),
+            "run `mk db migrate` on this database, or point --db at the MemoryKernel database \
+             the outcome store shares",
+        ));
+        return Ok(CompatibilityReport {
+            compatible: false,
+            checks,
+        });
+    }
+
+    let columns = declared_columns(conn, MEMORY_RECORDS)?;
+    for (column, expected) in REQUIRED_COLUMNS {
+        let check = format!("column:{MEMORY_RECORDS}.{column}");
+        let declared = columns
+            .iter()
+            .find(|(name, _)| name == column)
+            .map(|(_, declared)| declared);
+        checks.push(match declared {
+            None => CompatibilityCheck::fail(
+                check,
+                format!("missing column {MEMORY_RECORDS}.{column}"),
+                "run `mk db migrate` to bring memory_records up to the current MemoryKernel \
+                 schema; a hand-made table must declare memory_id, version, record_type, and \
+                 effective_at",
+            ),
+            Some(declared) if ColumnAffinity::of_declared_type(declared) != expected => {
+                CompatibilityCheck::fail(
+                    check,
+                    format!(
+                        "column {MEMORY_RECORDS}.{column} is declared `{declared}` ({} affinity); \
+                         expected {} affinity",
+                        ColumnAffinity::of_declared_type(declared).as_str(),
+                        expected.as_str()
+                    ),
+                    "recreate memory_records from the MemoryKernel schema (export with `mk db \
+                     export`, migrate a fresh database, and import); SQLite cannot change a \
+                     column's type in place",
+                )
+            }
+            Some(declared) => CompatibilityCheck::pass(
+                check,
+                format!("column {MEMORY_RECORDS}.{column} is declared `{declared}`"),
+            ),
+        });
+    }
+
+    let identity = ["memory_id", "version"];
+    let check = format!("unique:{MEMORY_RECORDS}(memory_id,version)");
+    checks.push(if has_unique_index_on(conn, MEMORY_RECORDS, &identity)? {
+        CompatibilityCheck::pass(
+            check,
+            format!("{MEMORY_RECORDS} has UNIQUE(memory_id, version)"),
+        )
+    } else {
+        CompatibilityCheck::fail(
+            check,
+            format!("expected UNIQUE(memory_id, version) on {MEMORY_RECORDS}"),
+            "run `mk db migrate`, or add the key with `CREATE UNIQUE INDEX \
+             memory_records_identity ON memory_records(memory_id, version)`",
+        )
+    });
+
+    Ok(CompatibilityReport {
+        compatible: checks.iter().all(|check| check.passed),
+        checks,
+    })
+}
+
+/// Name and declared type of each column of `table_name`.
+fn declared_columns(conn: &Connection, table_name: &str) -> Result<Vec<(String, String)>> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA table_info({table_name})"))
+        .with_context(|| format!("failed to inspect table_info for {table_name}"))?;
+    let rows = stmt.query_map([], |row| Ok((row.get(1)?, row.get(2)?)))?;
+    rows.collect::<rusqlite::Result<_>>()
+        .with_context(|| format!("failed to read columns for {table_name}"))
+}
+
+fn has_unique_index_on(conn: &Connection, table_name: &str, columns: &[&str]) -> Result<bool> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA index_list({table_name})"))
+        .with_context(|| format!("failed to inspect index_list for {table_name}"))?;
+    let mut rows = stmt.query([])?;
+
+    while let Some(row) = rows.next()? {
+        let index_name: String = row.get(1)?;
+        let is_unique: i64 = row.get(2)?;
+        if is_unique != 1 {
+            continue;
+        }
+
+        if index_columns(conn, &index_name)? == columns {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn index_columns(conn: &Connection, index_name: &str) -> Result<Vec<String>> {
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA index_info({index_name})"))
+        .with_context(|| format!("failed to inspect index_info for {index_name}"))?;
+    let mut rows = stmt.query([])?;
+
+    let mut columns = Vec::new();
+    while let Some(row) = rows.next()? {
+        columns.push(row.get::<_, String>(2)?);
+    }
+
+    Ok(columns)
+}
@@ -0,0 +1,221 @@
+//! Parquet encoding for [`EventExportFormat::Parquet`](crate::EventExportFormat::Parquet)
+//! exports (`mk outcome events export --format parquet`).
+
+use std::io::Write;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use memory_kernel_outcome_core::OutcomeEvent;
+use parquet::basic::{Repetition, Type as PhysicalType};
+use parquet::column::writer::ColumnWriter;
+use parquet::data_type::ByteArray;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use serde_json::Value;
+
+/// Events buffered per row group. The writer holds at most this many encoded events before
+/// flushing them to `out`, which bounds export memory independently of log size.
+pub(crate) const PARQUET_ROW_GROUP_EVENTS: usize = 4096;
+
+/// Schema of Parquet exports: the [`EVENT_EXPORT_CSV_COLUMNS`](crate::EVENT_EXPORT_CSV_COLUMNS)
+/// in the same order, typed. Timestamps stay RFC 3339 text as in `events list`; `payload_json`
+/// and `source` are compact JSON text.
+const EVENT_EXPORT_PARQUET_SCHEMA: &str = "
+message outcome_event {
+    REQUIRED INT64 event_seq;
+    REQUIRED BYTE_ARRAY event_id (UTF8);
+    REQUIRED INT64 ruleset_version;
+    REQUIRED BYTE_ARRAY memory_id (UTF8);
+    REQUIRED INT64 version;
+    REQUIRED BYTE_ARRAY event_type (UTF8);
+    REQUIRED BYTE_ARRAY occurred_at (UTF8);
+    REQUIRED BYTE_ARRAY recorded_at (UTF8);
+    REQUIRED BYTE_ARRAY writer (UTF8);
+    REQUIRED BYTE_ARRAY justification (UTF8);
+    OPTIONAL BYTE_ARRAY context_id (UTF8);
+    REQUIRED BOOLEAN edited;
+    REQUIRED BOOLEAN escalated;
+    OPTIONAL BYTE_ARRAY severity (UTF8);
+    OPTIONAL FLOAT manual_confidence;
+    REQUIRED BOOLEAN override_cap;
+    REQUIRED BYTE_ARRAY payload_json (UTF8);
+    OPTIONAL BYTE_ARRAY cosigner (UTF8);
+    OPTIONAL BYTE_ARRAY signature (UTF8);
+    OPTIONAL BYTE_ARRAY source (UTF8);
+    OPTIONAL BYTE_ARRAY prev_event_hash (UTF8);
+    OPTIONAL BYTE_ARRAY event_hash (UTF8);
+}
+";
+
+/// Streams events into a Parquet file on `out`, one row group per
+/// [`PARQUET_ROW_GROUP_EVENTS`] events. [`ParquetEventWriter::finish`] writes the final row
+/// group and the footer; a writer dropped without it leaves `out` truncated.
+pub(crate) struct ParquetEventWriter<W: Write + Send> {
+    writer: SerializedFileWriter<W>,
+    columns: Vec<ParquetColumn>,
+    buffered: usize,
+}
+
+impl<W: Write + Send> ParquetEventWriter<W> {
+    pub(crate) fn new(out: W) -> Result<Self> {
+        let schema = Arc::new(parse_message_type(EVENT_EXPORT_PARQUET_SCHEMA)?);
+        let columns = schema
+            .get_fields()
+            .iter()
+            .map(|field| ParquetColumn::new(field))
+            .collect::<Result<Vec<_>>>()?;
+        let properties = Arc::new(WriterProperties::builder().build());
+        Ok(Self {
+            writer: SerializedFileWriter::new(out, schema, properties)?,
+            columns,
+            buffered: 0,
+        })
+    }
+
+    pub(crate) fn push(&mut self, event: &OutcomeEvent) -> Result<()> {
+        let Value::Object(fields) = serde_json::to_value(event)? else {
+            return Err(anyhow!("outcome event did not serialize to a JSON object"));
+        };
+        for column in &mut self.columns {
+            column.push(fields.get(&column.name))?;
+        }
+        self.buffered += 1;
+        if self.buffered >= PARQUET_ROW_GROUP_EVENTS {
+            self.flush_row_group()?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn finish(mut self) -> Result<()> {
+        self.flush_row_group()?;
+        self.writer.close()?;
+        Ok(())
+    }
+
+    fn flush_row_group(&mut self) -> Result<()> {
+        if self.buffered == 0 {
+            return Ok(());
+        }
+        let mut row_group = self.writer.next_row_group()?;
+        for column in &mut self.columns {
+            let mut writer = row_group
+                .next_column()?
+                .ok_or_else(|| anyhow!("parquet row group ended before column {}", column.name))?;
+            column.write(writer.untyped())?;
+            writer.close()?;
+        }
+        row_group.close()?;
+        self.buffered = 0;
+        Ok(())
+    }
+}
+
+/// Values of one column for the buffered row group. `levels` holds definition levels (1 for a
+/// value, 0 for null) and stays empty for required columns.
+struct ParquetColumn {
+    name: String,
+    optional: bool,
+    levels: Vec<i16>,
+    values: ParquetValues,
+}
+
+enum ParquetValues {
+    Int64(Vec<i64>),
+    Boolean(Vec<bool>),
+    Float(Vec<f32>),
+    Text(Vec<ByteArray>),
+}
+
+impl ParquetColumn {
+    fn new(field: &parquet::schema::types::Type) -> Result<Self> {
+        let values = match field.get_physical_type() {
+            PhysicalType::INT64 => ParquetValues::Int64(Vec::new()),
+            PhysicalType::BOOLEAN => ParquetValues::Boolean(Vec::new()),
+            PhysicalType::FLOAT => ParquetValues::Float(Vec::new()),
+            PhysicalType::BYTE_ARRAY => ParquetValues::Text(Vec::new()),
+            other => {
+                return Err(anyhow!(
+                    "unsupported parquet export type {other} for column {}",
+                    field.name()
+                ))
+            }
+        };
+        Ok(Self {
+            name: field.name().to_string(),
+            optional: field.get_basic_info().repetition() == Repetition::OPTIONAL,
+            levels: Vec::new(),
+            values,
+        })
+    }
+
+    fn push(&mut self, value: Option<&Value>) -> Result<()> {
+        let Some(value) = value.filter(|value| !value.is_null()) else {
+            if !self.optional {
+                return Err(anyhow!(
+                    "outcome event has no value for column {}",
+                    self.name
+                ));
+            }
+            self.levels.push(0);
+            return Ok(());
+        };
+        match (&mut self.values, value) {
+            (ParquetValues::Int64(values), Value::Number(number)) => {
+                values.push(number.as_i64().ok_or_else(|| {
+                    anyhow!("column {} value {number} is not an integer", self.name)
+                })?);
+            }
+            (ParquetValues::Boolean(values), Value::Bool(flag)) => values.push(*flag),
+            (ParquetValues::Float(values), Value::Number(number)) => {
+                let number = number.as_f64().ok_or_else(|| {
+                    anyhow!("column {} value {number} is not a number", self.name)
+                })?;
+                // Float columns hold `f32` fields widened by serde_json; narrowing is exact.
+                #[allow(clippy::cast_possible_truncation)]
+                values.push(number as f32);
+            }
+            (ParquetValues::Text(values), Value::String(text)) => {
+                values.push(ByteArray::from(text.as_str()));
+            }
+            (ParquetValues::Text(values), other) => {
+                values.push(ByteArray::from(other.to_string().as_str()));
+            }
+            (_, other) => return Err(anyhow!("column {} cannot hold value {other}", self.name)),
+        }
+        if self.optional {
+            self.levels.push(1);
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, writer: &mut ColumnWriter<'_>) -> Result<()> {
+        let levels = self.optional.then_some(self.levels.as_slice());
+        match (writer, &mut self.values) {
+            (ColumnWriter::Int64ColumnWriter(writer), ParquetValues::Int64(values)) => {
+                writer.write_batch(values, levels, None)?;
+                values.clear();
+            }
+            (ColumnWriter::BoolColumnWriter(writer), ParquetValues::Boolean(values)) => {
+                writer.write_batch(values, levels, None)?;
+                values.clear();
+            }
+            (ColumnWriter::FloatColumnWriter(writer), ParquetValues::Float(values)) => {
+                writer.write_batch(values, levels, None)?;
+                values.clear();
+            }
+            (ColumnWriter::ByteArrayColumnWriter(writer), ParquetValues::Text(values)) => {
+                writer.write_batch(values, levels, None)?;
+                values.clear();
+            }
+            _ => {
+                return Err(anyhow!(
+                    "parquet column writer does not match column {}",
+                    self.name
+                ))
+            }
+        }
+        self.levels.clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,1411 @@
+//! `PostgreSQL` backend for [`OutcomeStore`], selected by a `postgres://` `--db` location.
+//!
+//! It keeps the event log, trust snapshots, rulesets, and projector cursor in Postgres and
+//! projects and gates with the same `memory-kernel-outcome-core` rules as the `SQLite` store.
+//! The host `memory_records` table lives in the memory kernel's `SQLite` database, so events
+//! are not checked against it, no memory is authoritative, and the writer registry, override
+//! policy, ruleset pins, notify hooks, pending events, and sync remain `SQLite`-only.
+//! Connections are made without TLS.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Context, Result};
+use memory_kernel_core::MemoryId;
+use memory_kernel_outcome_core::{
+    apply_as_of_decay, format_rfc3339, gate_memory, now_utc, parse_rfc3339_utc,
+    project_memory_trust_as_of, GateDecision, MemoryKey, MemoryTrust, OutcomeEvent,
+    OutcomeEventInput, OutcomeEventType, OutcomeRuleset, RetrievalMode, Severity, TrustStatus,
+};
+use postgres::{Client, GenericClient, NoTls, Row};
+use serde_json::Value;
+use ulid::Ulid;
+
+use crate::{
+    build_projector_digest, compute_outcome_event_hash, effective_at_to_sql, event_from_input,
+    no_trust_snapshot_decision, parse_memory_key, projector_check_from_status, record_transition,
+    redact_url_password, source_to_sql, stale_snapshot_error, OutcomeStore, ProjectorCheck,
+    ProjectorDigest, ProjectorStaleKey, ProjectorStatus, ReplayReport, StaleSnapshotPolicy,
+    OUTCOME_MIGRATION_VERSION, PROJECTOR_NAME,
+};
+
+const SCHEMA_OUTCOME_POSTGRES: &str = r"
+CREATE TABLE IF NOT EXISTS schema_migrations (
+  version BIGINT PRIMARY KEY,
+  applied_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS outcome_rulesets (
+  ruleset_version BIGINT PRIMARY KEY,
+  ruleset_json TEXT NOT NULL,
+  created_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS outcome_events (
+  event_seq BIGSERIAL PRIMARY KEY,
+  event_id TEXT NOT NULL UNIQUE,
+  ruleset_version BIGINT NOT NULL REFERENCES outcome_rulesets(ruleset_version),
+  memory_id TEXT NOT NULL,
+  version BIGINT NOT NULL CHECK (version >= 1),
+  event_type TEXT NOT NULL CHECK (
+    event_type IN (
+      'inherited',
+      'success',
+      'failure',
+      'ignored',
+      'unknown',
+      'manual_set_confidence',
+      'manual_promote',
+      'manual_retire',
+      'authoritative_contradiction'
+    )
+  ),
+  occurred_at TEXT NOT NULL,
+  recorded_at TEXT NOT NULL,
+  writer TEXT NOT NULL,
+  justification TEXT NOT NULL,
+  context_id TEXT,
+  edited BOOLEAN NOT NULL DEFAULT FALSE,
+  escalated BOOLEAN NOT NULL DEFAULT FALSE,
+  severity TEXT CHECK (severity IN ('low','med','high') OR severity IS NULL),
+  manual_confidence REAL CHECK (manual_confidence BETWEEN 0.0 AND 1.0 OR manual_confidence IS NULL),
+  override_cap BOOLEAN NOT NULL DEFAULT FALSE,
+  payload_json TEXT NOT NULL DEFAULT '{}',
+  cosigner TEXT,
+  signature TEXT,
+  prev_event_hash TEXT,
+  event_hash TEXT,
+  source_run_id TEXT,
+  source_json TEXT,
+  effective_at TEXT
+);
+
+CREATE OR REPLACE FUNCTION outcome_events_append_only() RETURNS trigger
+LANGUAGE plpgsql AS $$
+BEGIN
+  RAISE EXCEPTION 'outcome_events is append-only';
+END;
+$$;
+
+CREATE OR REPLACE TRIGGER trg_outcome_events_append_only
+BEFORE UPDATE OR DELETE ON outcome_events
+FOR EACH ROW EXECUTE FUNCTION outcome_events_append_only();
+
+CREATE INDEX IF NOT EXISTS idx_outcome_events_memory_version_seq
+  ON outcome_events(memory_id, version, event_seq);
+CREATE INDEX IF NOT EXISTS idx_outcome_events_type_seq
+  ON outcome_events(event_type, event_seq);
+CREATE INDEX IF NOT EXISTS idx_outcome_events_source_run_seq
+  ON outcome_events(source_run_id, event_seq)
+  WHERE source_run_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_outcome_events_effective_at
+  ON outcome_events(effective_at)
+  WHERE effective_at IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS memory_trust (
+  memory_id TEXT NOT NULL,
+  version BIGINT NOT NULL CHECK (version >= 1),
+  confidence_raw REAL NOT NULL CHECK (confidence_raw BETWEEN 0.0 AND 1.0),
+  confidence_effective REAL NOT NULL CHECK (confidence_effective BETWEEN 0.0 AND 1.0),
+  baseline_confidence REAL NOT NULL CHECK (baseline_confidence BETWEEN 0.0 AND 1.0),
+  trust_status TEXT NOT NULL CHECK (trust_status IN ('active', 'validated', 'retired')),
+  contradiction_cap_active BOOLEAN NOT NULL,
+  cap_value REAL NOT NULL CHECK (cap_value BETWEEN 0.0 AND 1.0),
+  manual_override_active BOOLEAN NOT NULL,
+  wins_last5 SMALLINT NOT NULL CHECK (wins_last5 BETWEEN 0 AND 5),
+  failures_last5 SMALLINT NOT NULL CHECK (failures_last5 BETWEEN 0 AND 5),
+  last_event_seq BIGINT NOT NULL,
+  last_ruleset_version BIGINT NOT NULL,
+  last_scored_at TEXT,
+  updated_at TEXT NOT NULL,
+  retire_grace_until TEXT,
+  projected_at TEXT,
+  PRIMARY KEY (memory_id, version)
+);
+
+CREATE INDEX IF NOT EXISTS idx_memory_trust_gate
+  ON memory_trust(trust_status, confidence_effective DESC, contradiction_cap_active);
+
+CREATE TABLE IF NOT EXISTS outcome_projection_state (
+  projector_name TEXT PRIMARY KEY,
+  ruleset_version BIGINT NOT NULL,
+  last_event_seq BIGINT NOT NULL,
+  updated_at TEXT NOT NULL
+);
+";
+
+const EVENT_COLUMNS: &str = "event_seq, event_id, ruleset_version, memory_id, version, event_type,
+    occurred_at, recorded_at, writer, justification, context_id,
+    edited, escalated, severity, manual_confidence, override_cap, payload_json,
+    cosigner, signature, prev_event_hash, event_hash, source_json, effective_at";
+
+const TRUST_COLUMNS: &str = "memory_id, version, confidence_raw, confidence_effective,
+    baseline_confidence, trust_status, contradiction_cap_active, cap_value,
+    manual_override_active, wins_last5, failures_last5, last_event_seq, last_ruleset_version,
+    last_scored_at, updated_at, retire_grace_until";
+
+/// Outcome store backed by a `PostgreSQL` database. Appends lock `outcome_events` against other
+/// writers, so `event_seq` order is commit order and the hash chain stays linear when several
+/// processes share one database.
+pub struct PostgresOutcomeStore {
+    client: RefCell<Client>,
+}
+
+impl PostgresOutcomeStore {
+    /// Connect to the database at `url` (`postgres://` or `postgresql://`).
+    pub fn connect(url: &str) -> Result<Self> {
+        let client = Client::connect(url, NoTls)
+            .with_context(|| format!("failed to connect to {}", redact_url_password(url)))?;
+        Ok(Self {
+            client: RefCell::new(client),
+        })
+    }
+
+    /// Create the outcome tables when missing and register ruleset v1 and the projector cursor.
+    /// Concurrent migrations serialize on an advisory lock.
+    pub fn migrate(&self) -> Result<()> {
+        let mut client = self.client.borrow_mut();
+        let mut tx = client
+            .transaction()
+            .context("failed to start migration transaction")?;
+        tx.batch_execute("SELECT pg_advisory_xact_lock(hashtext('memory_kernel_outcome_migrate'))")
+            .context("failed to take the outcome migration lock")?;
+        tx.batch_execute(SCHEMA_OUTCOME_POSTGRES)
+            .context("failed to apply outcome schema")?;
+
+        let now = format_rfc3339(now_utc()).map_err(|err| anyhow!(err.to_string()))?;
+        tx.execute(
+            "INSERT INTO schema_migrations(version, applied_at) VALUES ($1, $2)
+             ON CONFLICT (version) DO NOTHING",
+            &[&OUTCOME_MIGRATION_VERSION, &now],
+        )
+        .context("failed to register outcome schema migration")?;
+        upsert_ruleset(&mut tx, &OutcomeRuleset::v1())?;
+        tx.execute(
+            "INSERT INTO outcome_projection_state(projector_name, ruleset_version, last_event_seq, updated_at)
+             VALUES ($1, 1, 0, $2)
+             ON CONFLICT (projector_name) DO NOTHING",
+            &[&PROJECTOR_NAME, &now],
+        )
+        .context("failed to initialize projection state")?;
+        tx.commit()
+            .context("failed to commit outcome schema migration")?;
+        Ok(())
+    }
+
+    pub fn upsert_ruleset(&self, ruleset: &OutcomeRuleset) -> Result<()> {
+        upsert_ruleset(&mut *self.client.borrow_mut(), ruleset)
+    }
+
+    pub fn get_rulesets(&self) -> Result<BTreeMap<u32, OutcomeRuleset>> {
+        load_rulesets(&mut *self.client.borrow_mut())
+    }
+
+    /// Append several events atomically: every input is validated up front and all rows are
+    /// written in one transaction, so a rejected input leaves the log untouched.
+    pub fn append_events(&mut self, inputs: &[OutcomeEventInput]) -> Result<Vec<OutcomeEvent>> {
+        self.append_events_projecting(inputs, false)
+    }
+
+    /// [`PostgresOutcomeStore::append_events`] that updates the trust snapshot of every touched
+    /// key in the same transaction, advancing the projector cursor only when it was caught up.
+    pub fn append_events_and_project(
+        &mut self,
+        inputs: &[OutcomeEventInput],
+    ) -> Result<Vec<OutcomeEvent>> {
+        self.append_events_projecting(inputs, true)
+    }
+
+    fn append_events_projecting(
+        &mut self,
+        inputs: &[OutcomeEventInput],
+        project: bool,
+    ) -> Result<Vec<OutcomeEvent>> {
+        let rulesets = self.get_rulesets()?;
+        for input in inputs {
+            input
+                .validate()
+                .map_err(|err| anyhow!("event validation failed: {err}"))?;
+            if !rulesets.contains_key(&input.ruleset_version) {
+                return Err(anyhow!(
+                    "missing ruleset_version {} in outcome_rulesets",
+                    input.ruleset_version
+                ));
+            }
+        }
+
+        let recorded_at = now_utc();
+        let mut client = self.client.borrow_mut();
+        let mut tx = client
+            .transaction()
+            .context("failed to start event transaction")?;
+        // Exclusive, so no other writer can append between reading the chain head and linking to it.
+        tx.batch_execute("LOCK TABLE outcome_events IN EXCLUSIVE MODE")
+            .context("failed to lock outcome_events for append")?;
+        let mut prev_event_hash = chain_head_hash(&mut tx)?;
+
+        let mut events = Vec::with_capacity(inputs.len());
+        for input in inputs {
+            let event_id = input.event_id.unwrap_or_else(Ulid::new);
+            let event = insert_outcome_event(
+                &mut tx,
+                input,
+                event_id,
+                recorded_at,
+                prev_event_hash.take(),
+            )?;
+            prev_event_hash.clone_from(&event.event_hash);
+            events.push(event);
+        }
+
+        if project {
+            project_appended_events(&mut tx, &events, &rulesets)?;
+        }
+        tx.commit().context("failed to commit event transaction")?;
+        Ok(events)
+    }
+
+    pub fn list_events_for_key(
+        &self,
+        memory_id: MemoryId,
+        version: u32,
+        limit: Option<usize>,
+    ) -> Result<Vec<OutcomeEvent>> {
+        query_events_for_key(&mut *self.client.borrow_mut(), memory_id, version, limit)
+    }
+
+    /// Re-project every key with events at or after `from_event_seq` (all keys when unset) and
+    /// move the projector cursor to the log head. Appends wait until the replay commits.
+    pub fn replay(&mut self, from_event_seq: Option<i64>) -> Result<ReplayReport> {
+        let rulesets = self.get_rulesets()?;
+        let mut client = self.client.borrow_mut();
+        let mut tx = client
+            .transaction()
+            .context("failed to start replay transaction")?;
+        tx.batch_execute("LOCK TABLE outcome_events IN SHARE MODE")
+            .context("failed to lock outcome_events for replay")?;
+
+        let rows = tx
+            .query(
+                "SELECT DISTINCT memory_id, version
+                 FROM outcome_events
+                 WHERE event_seq >= $1
+                 ORDER BY memory_id ASC, version ASC",
+                &[&from_event_seq.unwrap_or(0)],
+            )
+            .context("failed to query keys to replay")?;
+        let mut projected_keys = 0_usize;
+        let mut processed_events = 0_usize;
+        for row in rows {
+            let key = key_from_row(&row)?;
+            let events = query_events_for_key(&mut tx, key.memory_id, key.version, None)?;
+            processed_events += events.len();
+
+            let as_of = now_utc();
+            if let Some(trust) = project_memory_trust_as_of(&events, &rulesets, as_of)
+                .map_err(|err| anyhow!("failed projecting {key}: {err}"))?
+            {
+                upsert_memory_trust(
+                    &mut tx,
+                    &trust,
+                    events.last().map_or(1, |item| item.ruleset_version),
+                    as_of,
+                )?;
+                projected_keys += 1;
+            }
+        }
+
+        let last_event_seq = latest_event_seq(&mut tx)?;
+        save_projection_cursor(&mut tx, last_event_seq)?;
+        tx.commit().context("failed to commit replay transaction")?;
+
+        Ok(ReplayReport {
+            projected_keys,
+            processed_events,
+            last_event_seq,
+        })
+    }
+
+    pub fn get_memory_trust(
+        &self,
+        memory_id: MemoryId,
+        version: u32,
+        as_of: Option<time::OffsetDateTime>,
+    ) -> Result<Option<MemoryTrust>> {
+        let mut client = self.client.borrow_mut();
+        let row = client
+            .query_opt(
+                &format!(
+                    "SELECT {TRUST_COLUMNS} FROM memory_trust WHERE memory_id = $1 AND version = $2"
+                ),
+                &[&memory_id.to_string(), &i64::from(version)],
+            )
+            .context("failed to read trust snapshot")?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let (trust, last_ruleset_version) = trust_from_row(&row)?;
+
+        let Some(as_of_value) = as_of else {
+            return Ok(Some(trust));
+        };
+        let rulesets = load_rulesets(&mut *client)?;
+        let Some(ruleset) = rulesets.get(&last_ruleset_version) else {
+            return Err(anyhow!(
+                "missing ruleset {last_ruleset_version} for trust decay",
+            ));
+        };
+
+        Ok(Some(apply_as_of_decay(&trust, ruleset, as_of_value)))
+    }
+
+    pub fn gate_preview(
+        &self,
+        mode: RetrievalMode,
+        as_of: time::OffsetDateTime,
+        context_id: Option<&str>,
+        candidates: &[MemoryKey],
+    ) -> Result<Vec<GateDecision>> {
+        gate_candidates(
+            &mut *self.client.borrow_mut(),
+            mode,
+            as_of,
+            context_id,
+            candidates,
+        )
+    }
+
+    /// [`PostgresOutcomeStore::gate_preview`] that never gates on a trust snapshot older than the
+    /// candidate's events. [`StaleSnapshotPolicy::Refuse`] checks and gates in one repeatable-read
+    /// snapshot; [`StaleSnapshotPolicy::Project`] holds appends off while it re-projects the stale
+    /// keys and gates. Projecting does not move the projector cursor.
+    pub fn gate_preview_consistent(
+        &mut self,
+        mode: RetrievalMode,
+        as_of: time::OffsetDateTime,
+        context_id: Option<&str>,
+        candidates: &[MemoryKey],
+        on_stale: StaleSnapshotPolicy,
+    ) -> Result<Vec<GateDecision>> {
+        let mut client = self.client.borrow_mut();
+        let mut tx = client
+            .transaction()
+            .context("failed to start consistent gate transaction")?;
+        tx.batch_execute(match on_stale {
+            StaleSnapshotPolicy::Refuse => "SET TRANSACTION ISOLATION LEVEL REPEATABLE READ",
+            StaleSnapshotPolicy::Project => "LOCK TABLE outcome_events IN SHARE MODE",
+        })
+        .context("failed to isolate consistent gate transaction")?;
+
+        let stale = stale_candidate_keys(&mut tx, candidates)?;
+        if !stale.is_empty() {
+            match on_stale {
+                StaleSnapshotPolicy::Refuse => return Err(stale_snapshot_error(&stale)),
+                StaleSnapshotPolicy::Project => {
+                    let rulesets = load_rulesets(&mut tx)?;
+                    for key in &stale {
+                        project_key(&mut tx, key.memory_id, key.version, &rulesets)?;
+                    }
+                }
+            }
+        }
+        let decisions = gate_candidates(&mut tx, mode, as_of, context_id, candidates)?;
+        tx.commit()
+            .context("failed to commit consistent gate transaction")?;
+        Ok(decisions)
+    }
+
+    pub fn projector_status(&self) -> Result<ProjectorStatus> {
+        let mut client = self.client.borrow_mut();
+        let state = client
+            .query_opt(
+                "SELECT ruleset_version, last_event_seq, updated_at
+                 FROM outcome_projection_state
+                 WHERE projector_name = $1",
+                &[&PROJECTOR_NAME],
+            )
+            .context("failed to read projection state")?;
+        let (ruleset_version, projected_event_seq, updated_at) = match state {
+            Some(row) => (
+                to_u32(row.try_get(0)?, "ruleset_version")?,
+                row.try_get::<_, i64>(1)?,
+                Some(row.try_get::<_, String>(2)?),
+            ),
+            None => (1_u32, 0_i64, None),
+        };
+
+        let latest_event_seq = latest_event_seq(&mut *client)?;
+        let lag_events = (latest_event_seq - projected_event_seq).max(0);
+        let tracked_keys = count(
+            &mut *client,
+            "SELECT COUNT(*) FROM (SELECT DISTINCT memory_id, version FROM outcome_events) keys",
+        )?;
+        let trust_rows = count(&mut *client, "SELECT COUNT(*) FROM memory_trust")?;
+        let trust_rows_without_events = count(
+            &mut *client,
+            "SELECT COUNT(*) FROM memory_trust trust
+             WHERE NOT EXISTS (
+                 SELECT 1 FROM outcome_events events
+                 WHERE events.memory_id = trust.memory_id AND events.version = trust.version
+             )",
+        )?;
+        let stale_keys = query_stale_keys(&mut *client, None)?;
+        let keys_with_events_no_trust_row = stale_keys
+            .iter()
+            .filter(|item| item.projected_event_seq.is_none())
+            .count();
+        let max_stale_seq_gap = stale_keys
+            .iter()
+            .map(|item| item.max_event_seq - item.projected_event_seq.unwrap_or(0))
+            .max()
+            .unwrap_or(0);
+
+        Ok(ProjectorStatus {
+            contract_version: "projector_status.v1".to_string(),
+            projector_name: PROJECTOR_NAME.to_string(),
+            ruleset_version,
+            projected_event_seq,
+            latest_event_seq,
+            lag_events,
+            lag_delta_events: lag_events,
+            tracked_keys,
+            trust_rows,
+            stale_trust_rows: stale_keys.len(),
+            keys_with_events_no_trust_row,
+            trust_rows_without_events,
+            max_stale_seq_gap,
+            updated_at,
+            pending_events: 0,
+            oldest_pending_queued_at: None,
+        })
+    }
+
+    pub fn projector_check(&self) -> Result<ProjectorCheck> {
+        let status = self.projector_status()?;
+        let stale_key_sample = self.projector_stale_keys(Some(25))?;
+        Ok(projector_check_from_status(status, stale_key_sample))
+    }
+
+    pub fn projector_stale_keys(&self, limit: Option<usize>) -> Result<Vec<ProjectorStaleKey>> {
+        query_stale_keys(&mut *self.client.borrow_mut(), limit)
+    }
+
+    /// Same report as [`crate::SqliteOutcomeStore::projector_digest`].
+    pub fn projector_digest(
+        &self,
+        since: time::OffsetDateTime,
+        revalidate_after_days: u32,
+    ) -> Result<ProjectorDigest> {
+        let generated_at = now_utc();
+        let check = self.projector_check()?;
+        let mut client = self.client.borrow_mut();
+        let retired_at =
+            latest_transitions(&mut *client, OutcomeEventType::ManualRetire, generated_at)?;
+        let capped_at = latest_transitions(
+            &mut *client,
+            OutcomeEventType::AuthoritativeContradiction,
+            generated_at,
+        )?;
+        let trust_rows = client
+            .query(
+                &format!(
+                    "SELECT {TRUST_COLUMNS} FROM memory_trust ORDER BY memory_id ASC, version ASC"
+                ),
+                &[],
+            )
+            .context("failed to list trust snapshots")?
+            .iter()
+            .map(trust_from_row)
+            .collect::<Result<Vec<_>>>()?;
+        drop(client);
+
+        build_projector_digest(
+            check,
+            trust_rows,
+            &retired_at,
+            &capped_at,
+            since,
+            revalidate_after_days,
+            generated_at,
+        )
+    }
+
+    #[cfg(test)]
+    fn client(&self) -> std::cell::RefMut<'_, Client> {
+        self.client.borrow_mut()
+    }
+}
+
+impl OutcomeStore for PostgresOutcomeStore {
+    fn migrate(&self) -> Result<()> {
+        Self::migrate(self)
+    }
+
+    fn get_rulesets(&self) -> Result<BTreeMap<u32, OutcomeRuleset>> {
+        Self::get_rulesets(self)
+    }
+
+    fn append_events(&mut self, inputs: &[OutcomeEventInput]) -> Result<Vec<OutcomeEvent>> {
+        Self::append_events(self, inputs)
+    }
+
+    fn append_events_and_project(
+        &mut self,
+        inputs: &[OutcomeEventInput],
+    ) -> Result<Vec<OutcomeEvent>> {
+        Self::append_events_and_project(self, inputs)
+    }
+
+    fn list_events_for_key(
+        &self,
+        memory_id: MemoryId,
+        version: u32,
+        limit: Option<usize>,
+    ) -> Result<Vec<OutcomeEvent>> {
+        Self::list_events_for_key(self, memory_id, version, limit)
+    }
+
+    fn replay(&mut self, from_event_seq: Option<i64>) -> Result<ReplayReport> {
+        Self::replay(self, from_event_seq)
+    }
+
+    fn get_memory_trust(
+        &self,
+        memory_id: MemoryId,
+        version: u32,
+        as_of: Option<time::OffsetDateTime>,
+    ) -> Result<Option<MemoryTrust>> {
+        Self::get_memory_trust(self, memory_id, version, as_of)
+    }
+
+    fn gate_preview(
+        &self,
+        mode: RetrievalMode,
+        as_of: time::OffsetDateTime,
+        context_id: Option<&str>,
+        candidates: &[MemoryKey],
+    ) -> Result<Vec<GateDecision>> {
+        Self::gate_preview(self, mode, as_of, context_id, candidates)
+    }
+
+    fn gate_preview_consistent(
+        &mut self,
+        mode: RetrievalMode,
+        as_of: time::OffsetDateTime,
+        context_id: Option<&str>,
+        candidates: &[MemoryKey],
+        on_stale: StaleSnapshotPolicy,
+    ) -> Result<Vec<GateDecision>> {
+        Self::gate_preview_consistent(self, mode, as_of, context_id, candidates, on_stale)
+    }
+
+    fn projector_status(&self) -> Result<ProjectorStatus> {
+        Self::projector_status(self)
+    }
+
+    fn projector_check(&self) -> Result<ProjectorCheck> {
+        Self::projector_check(self)
+    }
+
+    fn projector_stale_keys(&self, limit: Option<usize>) -> Result<Vec<ProjectorStaleKey>> {
+        Self::projector_stale_keys(self, limit)
+    }
+
+    fn projector_digest(
+        &self,
+        since: time::OffsetDateTime,
+        revalidate_after_days: u32,
+    ) -> Result<ProjectorDigest> {
+        Self::projector_digest(self, since, revalidate_after_days)
+    }
+}
+
+fn upsert_ruleset(client: &mut impl GenericClient, ruleset: &OutcomeRuleset) -> Result<()> {
+    ruleset
+        .validate()
+        .map_err(|err| anyhow!("invalid ruleset configuration: {err}"))?;
+
+    let payload = serde_json::to_string(ruleset).context("failed to serialize ruleset")?;
+    let now = format_rfc3339(now_utc()).map_err(|err| anyhow!(err.to_string()))?;
+    client
+        .execute(
+            "INSERT INTO outcome_rulesets(ruleset_version, ruleset_json, created_at)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (ruleset_version) DO UPDATE SET
+               ruleset_json = EXCLUDED.ruleset_json,
+               created_at = EXCLUDED.created_at",
+            &[&i64::from(ruleset.ruleset_version), &payload, &now],
+        )
+        .context("failed to upsert ruleset")?;
+    Ok(())
+}
+
+fn load_rulesets(client: &mut impl GenericClient) -> Result<BTreeMap<u32, OutcomeRuleset>> {
+    let rows = client
+        .query(
+            "SELECT ruleset_version, ruleset_json FROM outcome_rulesets ORDER BY ruleset_version ASC",
+            &[],
+        )
+        .context("failed to read rulesets")?;
+    let mut map = BTreeMap::new();
+    for row in rows {
+        let version = to_u32(row.try_get(0)?, "ruleset_version")?;
+        let json: String = row.try_get(1)?;
+        let value: Value = serde_json::from_str(&json).context("invalid stored ruleset JSON")?;
+        let ruleset = OutcomeRuleset::from_json(&value)
+            .map_err(|err| anyhow!("failed to parse ruleset {version}: {err}"))?;
+        map.insert(version, ruleset);
+    }
+    Ok(map)
+}
+
+fn chain_head_hash(client: &mut impl GenericClient) -> Result<Option<String>> {
+    Ok(client
+        .query_opt(
+            "SELECT event_hash FROM outcome_events ORDER BY event_seq DESC LIMIT 1",
+            &[],
+        )
+        .context("failed to read outcome event chain head")?
+        .map(|row| row.try_get::<_, Option<String>>(0))
+        .transpose()?
+        .flatten())
+}
+
+fn latest_event_seq(client: &mut impl GenericClient) -> Result<i64> {
+    let row = client
+        .query_one("SELECT MAX(event_seq) FROM outcome_events", &[])
+        .context("failed to query latest event_seq")?;
+    Ok(row.try_get::<_, Option<i64>>(0)?.unwrap_or(0))
+}
+
+fn count(client: &mut impl GenericClient, query: &str) -> Result<usize> {
+    let value: i64 = client
+        .query_one(query, &[])
+        .with_context(|| format!("failed to run count query: {query}"))?
+        .try_get(0)?;
+    usize::try_from(value).with_context(|| format!("invalid count: {value}"))
+}
+
+/// Insert `input` at the head of the event chain, linked to `prev_event_hash`.
+fn insert_outcome_event(
+    client: &mut impl GenericClient,
+    input: &OutcomeEventInput,
+    event_id: Ulid,
+    recorded_at: time::OffsetDateTime,
+    prev_event_hash: Option<String>,
+) -> Result<OutcomeEvent> {
+    let mut event = event_from_input(input, event_id, 0, recorded_at);
+    event.prev_event_hash = prev_event_hash;
+    event.event_hash = Some(compute_outcome_event_hash(&event)?);
+
+    let row = client
+        .query_one(
+            "INSERT INTO outcome_events(
+                event_id, ruleset_version, memory_id, version, event_type,
+                occurred_at, recorded_at, writer, justification,
+                context_id, edited, escalated, severity,
+                manual_confidence, override_cap, payload_json, cosigner, signature,
+                prev_event_hash, event_hash, source_run_id, source_json, effective_at
+             ) VALUES (
+                $1, $2, $3, $4, $5,
+                $6, $7, $8, $9,
+                $10, $11, $12, $13,
+                $14, $15, $16, $17, $18,
+                $19, $20, $21, $22, $23
+             )
+             RETURNING event_seq",
+            &[
+                &event_id.to_string(),
+                &i64::from(input.ruleset_version),
+                &input.memory_id.to_string(),
+                &i64::from(input.version),
+                &input.event_type.as_str(),
+                &format_rfc3339(input.occurred_at).map_err(|err| anyhow!(err.to_string()))?,
+                &format_rfc3339(recorded_at).map_err(|err| anyhow!(err.to_string()))?,
+                &input.writer,
+                &input.justification,
+                &input.context_id,
+                &input.edited,
+                &input.escalated,
+                &input.severity.map(Severity::as_str),
+                &input.manual_confidence,
+                &input.override_cap,
+                &serde_json::to_string(&input.payload_json)
+                    .context("failed to serialize payload_json")?,
+                &input.cosigner,
+                &input.signature,
+                &event.prev_event_hash,
+                &event.event_hash,
+                &input.source.as_ref().map(|source| source.run_id.as_str()),
+                &source_to_sql(input.source.as_ref())?,
+                &effective_at_to_sql(input.effective_at)?,
+            ],
+        )
+        .with_context(|| {
+            format!(
+                "failed to append outcome event for {}:{}",
+                input.memory_id, input.version
+            )
+        })?;
+
+    event.event_seq = row.try_get(0)?;
+    Ok(event)
+}
+
+fn query_events_for_key(
+    client: &mut impl GenericClient,
+    memory_id: MemoryId,
+    version: u32,
+    limit: Option<usize>,
+) -> Result<Vec<OutcomeEvent>> {
+    let limit = limit.map(|raw| i64::try_from(raw).unwrap_or(i64::MAX));
+    client
+        .query(
+            &format!(
+                "SELECT {EVENT_COLUMNS}
+                 FROM outcome_events
+                 WHERE memory_id = $1 AND version = $2
+                 ORDER BY event_seq ASC
+                 LIMIT $3"
+            ),
+            &[&memory_id.to_string(), &i64::from(version), &limit],
+        )
+        .with_context(|| format!("failed to list events for {memory_id}:{version}"))?
+        .iter()
+        .map(event_from_row)
+        .collect()
+}
+
+/// Re-project the keys touched by `events`, just appended in this transaction, and advance the
+/// projector cursor past them when it was caught up with the log beforehand.
+fn project_appended_events(
+    client: &mut impl GenericClient,
+    events: &[OutcomeEvent],
+    rulesets: &BTreeMap<u32, OutcomeRuleset>,
+) -> Result<()> {
+    let Some(first_seq) = events.first().map(|event| event.event_seq) else {
+        return Ok(());
+    };
+    let keys = events
+        .iter()
+        .map(|event| (event.memory_id, event.version))
+        .collect::<std::collections::BTreeSet<_>>();
+    for (memory_id, version) in keys {
+        project_key(client, memory_id, version, rulesets)?;
+    }
+
+    let previous_head: Option<i64> = client
+        .query_one(
+            "SELECT MAX(event_seq) FROM outcome_events WHERE event_seq < $1",
+            &[&first_seq],
+        )
+        .context("failed to query previous event_seq")?
+        .try_get(0)?;
+    let projected: Option<i64> = client
+        .query_opt(
+            "SELECT last_event_seq FROM outcome_projection_state WHERE projector_name = $1",
+            &[&PROJECTOR_NAME],
+        )
+        .context("failed to read projection state")?
+        .map(|row| row.try_get(0))
+        .transpose()?;
+    if projected.unwrap_or(0) < previous_head.unwrap_or(0) {
+        return Ok(());
+    }
+    save_projection_cursor(
+        client,
+        events.last().map_or(first_seq, |event| event.event_seq),
+    )
+}
+
+/// Rewrite the trust snapshot of one key from its full event history.
+fn project_key(
+    client: &mut impl GenericClient,
+    memory_id: MemoryId,
+    version: u32,
+    rulesets: &BTreeMap<u32, OutcomeRuleset>,
+) -> Result<()> {
+    let key_events = query_events_for_key(client, memory_id, version, None)?;
+    let as_of = now_utc();
+    if let Some(trust) = project_memory_trust_as_of(&key_events, rulesets, as_of)
+        .map_err(|err| anyhow!("failed projecting {memory_id}:{version}: {err}"))?
+    {
+        let ruleset_version = key_events.last().map_or(1, |item| item.ruleset_version);
+        upsert_memory_trust(client, &trust, ruleset_version, as_of)?;
+    }
+    Ok(())
+}
+
+fn upsert_memory_trust(
+    client: &mut impl GenericClient,
+    trust: &MemoryTrust,
+    ruleset_version: u32,
+    projected_at: time::OffsetDateTime,
+) -> Result<()> {
+    client
+        .execute(
+            "INSERT INTO memory_trust(
+                memory_id, version, confidence_raw, confidence_effective, baseline_confidence,
+                trust_status, contradiction_cap_active, cap_value, manual_override_active,
+                wins_last5, failures_last5, last_event_seq, last_ruleset_version, last_scored_at,
+                updated_at, retire_grace_until, projected_at
+             ) VALUES (
+                $1, $2, $3, $4, $5,
+                $6, $7, $8, $9,
+                $10, $11, $12, $13, $14,
+                $15, $16, $17
+             )
+             ON CONFLICT (memory_id, version) DO UPDATE SET
+                confidence_raw = EXCLUDED.confidence_raw,
+                confidence_effective = EXCLUDED.confidence_effective,
+                baseline_confidence = EXCLUDED.baseline_confidence,
+                trust_status = EXCLUDED.trust_status,
+                contradiction_cap_active = EXCLUDED.contradiction_cap_active,
+                cap_value = EXCLUDED.cap_value,
+                manual_override_active = EXCLUDED.manual_override_active,
+                wins_last5 = EXCLUDED.wins_last5,
+                failures_last5 = EXCLUDED.failures_last5,
+                last_event_seq = EXCLUDED.last_event_seq,
+                last_ruleset_version = EXCLUDED.last_ruleset_version,
+                last_scored_at = EXCLUDED.last_scored_at,
+                updated_at = EXCLUDED.updated_at,
+                retire_grace_until = EXCLUDED.retire_grace_until,
+                projected_at = EXCLUDED.projected_at",
+            &[
+                &trust.memory_id.to_string(),
+                &i64::from(trust.version),
+                &trust.confidence_raw,
+                &trust.confidence_effective,
+                &trust.baseline_confidence,
+                &trust.trust_status.as_str(),
+                &trust.contradiction_cap_active,
+                &trust.cap_value,
+                &trust.manual_override_active,
+                &i16::from(trust.wins_last5),
+                &i16::from(trust.failures_last5),
+                &trust.last_event_seq,
+                &i64::from(ruleset_version),
+                &trust
+                    .last_scored_at
+                    .map(format_rfc3339)
+                    .transpose()
+                    .map_err(|err| anyhow!(err.to_string()))?,
+                &format_rfc3339(trust.updated_at).map_err(|err| anyhow!(err.to_string()))?,
+                &trust
+                    .retire_grace_until
+                    .map(format_rfc3339)
+                    .transpose()
+                    .map_err(|err| anyhow!(err.to_string()))?,
+                &format_rfc3339(projected_at).map_err(|err| anyhow!(err.to_string()))?,
+            ],
+        )
+        .context("failed to upsert memory_trust snapshot")?;
+    Ok(())
+}
+
+fn save_projection_cursor(client: &mut impl GenericClient, last_event_seq: i64) -> Result<()> {
+    let now = format_rfc3339(now_utc()).map_err(|err| anyhow!(err.to_string()))?;
+    client
+        .execute(
+            "INSERT INTO outcome_projection_state(projector_name, ruleset_version, last_event_seq, updated_at)
+             VALUES ($1, 1, $2, $3)
+             ON CONFLICT (projector_name) DO UPDATE SET
+               ruleset_version = EXCLUDED.ruleset_version,
+               last_event_seq = EXCLUDED.last_event_seq,
+               updated_at = EXCLUDED.updated_at",
+            &[&PROJECTOR_NAME, &last_event_seq, &now],
+        )
+        .context("failed to update projection state")?;
+    Ok(())
+}
+
+/// Gate `candidates` on their stored trust snapshots, decayed to `as_of`.
+fn gate_candidates(
+    client: &mut impl GenericClient,
+    mode: RetrievalMode,
+    as_of: time::OffsetDateTime,
+    context_id: Option<&str>,
+    candidates: &[MemoryKey],
+) -> Result<Vec<GateDecision>> {
+    let rulesets = load_rulesets(client)?;
+    let memory_ids = candidates
+        .iter()
+        .map(|key| key.memory_id.to_string())
+        .collect::<std::collections::BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    let mut snapshots = BTreeMap::new();
+    for row in client
+        .query(
+            &format!("SELECT {TRUST_COLUMNS} FROM memory_trust WHERE memory_id = ANY($1)"),
+            &[&memory_ids],
+        )
+        .context("failed to read candidate trust snapshots")?
+    {
+        let (trust, ruleset_version) = trust_from_row(&row)?;
+        let key = MemoryKey {
+            memory_id: trust.memory_id,
+            version: trust.version,
+        };
+        snapshots.insert(key, (trust, ruleset_version));
+    }
+
+    let mut decisions = Vec::with_capacity(candidates.len());
+    for candidate in candidates {
+        let Some((trust, ruleset_version)) = snapshots.get(candidate) else {
+            decisions.push(no_trust_snapshot_decision(candidate));
+            continue;
+        };
+        let ruleset = rulesets
+            .get(ruleset_version)
+            .ok_or_else(|| anyhow!("missing ruleset {ruleset_version}"))?;
+        let trust_with_decay = apply_as_of_decay(trust, ruleset, as_of);
+        decisions.push(gate_memory(&trust_with_decay, mode, context_id, ruleset));
+    }
+    Ok(decisions)
+}
+
+/// The `candidates` whose latest effective event is newer than their trust snapshot, or that
+/// hold a scheduled event which took effect after the snapshot was projected.
+fn stale_candidate_keys(
+    client: &mut impl GenericClient,
+    candidates: &[MemoryKey],
+) -> Result<Vec<ProjectorStaleKey>> {
+    let now = format_rfc3339(now_utc()).map_err(|err| anyhow!(err.to_string()))?;
+    let mut stale = Vec::new();
+    for candidate in candidates {
+        let row = client
+            .query_one(
+                "SELECT
+                    (SELECT MAX(event_seq) FROM outcome_events
+                     WHERE memory_id = $1 AND version = $2
+                       AND (effective_at IS NULL
+                            OR effective_at::timestamptz <= $3::TEXT::timestamptz)),
+                    (SELECT last_event_seq FROM memory_trust
+                     WHERE memory_id = $1 AND version = $2),
+                    EXISTS (SELECT 1 FROM outcome_events events
+                     JOIN memory_trust trust
+                       ON trust.memory_id = events.memory_id AND trust.version = events.version
+                     WHERE events.memory_id = $1 AND events.version = $2
+                       AND events.effective_at IS NOT NULL
+                       AND events.effective_at::timestamptz <= $3::TEXT::timestamptz
+                       AND events.effective_at::timestamptz > trust.projected_at::timestamptz)",
+                &[
+                    &candidate.memory_id.to_string(),
+                    &i64::from(candidate.version),
+                    &now,
+                ],
+            )
+            .context("failed to read candidate projection state")?;
+        let max_event_seq: Option<i64> = row.try_get(0)?;
+        let projected_event_seq: Option<i64> = row.try_get(1)?;
+        let scheduled_due: bool = row.try_get(2)?;
+        let Some(max_event_seq) = max_event_seq else {
+            continue;
+        };
+        if scheduled_due || projected_event_seq.map_or(true, |seq| seq < max_event_seq) {
+            stale.push(ProjectorStaleKey {
+                memory_id: candidate.memory_id,
+                version: candidate.version,
+                max_event_seq,
+                projected_event_seq,
+            });
+        }
+    }
+    Ok(stale)
+}
+
+fn query_stale_keys(
+    client: &mut impl GenericClient,
+    limit: Option<usize>,
+) -> Result<Vec<ProjectorStaleKey>> {
+    let now = format_rfc3339(now_utc()).map_err(|err| anyhow!(err.to_string()))?;
+    let limit = limit.map(|raw| i64::try_from(raw).unwrap_or(i64::MAX));
+    let rows = client
+        .query(
+            "SELECT events.memory_id, events.version, events.max_event_seq, trust.last_event_seq
+             FROM (
+                SELECT memory_id, version, MAX(event_seq) AS max_event_seq
+                FROM outcome_events
+                WHERE effective_at IS NULL OR effective_at::timestamptz <= $1::TEXT::timestamptz
+                GROUP BY memory_id, version
+             ) events
+             LEFT JOIN memory_trust trust
+               ON trust.memory_id = events.memory_id
+              AND trust.version = events.version
+             WHERE trust.last_event_seq IS NULL OR trust.last_event_seq < events.max_event_seq
+                OR EXISTS (SELECT 1 FROM outcome_events due
+                   WHERE due.memory_id = events.memory_id AND due.version = events.version
+                     AND due.effective_at IS NOT NULL
+                     AND due.effective_at::timestamptz <= $1::TEXT::timestamptz
+                     AND due.effective_at::timestamptz > trust.projected_at::timestamptz)
+             ORDER BY events.memory_id ASC, events.version ASC
+             LIMIT $2",
+            &[&now, &limit],
+        )
+        .context("failed to query stale projector keys")?;
+    rows.iter()
+        .map(|row| {
+            let key = key_from_row(row)?;
+            Ok(ProjectorStaleKey {
+                memory_id: key.memory_id,
+                version: key.version,
+                max_event_seq: row.try_get(2)?,
+                projected_event_seq: row.try_get(3)?,
+            })
+        })
+        .collect()
+}
+
+/// When each key's latest `event_type` event effective at `as_of` took effect.
+fn latest_transitions(
+    client: &mut impl GenericClient,
+    event_type: OutcomeEventType,
+    as_of: time::OffsetDateTime,
+) -> Result<BTreeMap<MemoryKey, time::OffsetDateTime>> {
+    let as_of = format_rfc3339(as_of).map_err(|err| anyhow!(err.to_string()))?;
+    let rows = client
+        .query(
+            "SELECT memory_id, version, recorded_at, effective_at
+             FROM outcome_events
+             WHERE event_type = $1
+               AND (effective_at IS NULL OR effective_at::timestamptz <= $2::TEXT::timestamptz)",
+            &[&event_type.as_str(), &as_of],
+        )
+        .context("failed to query transition events")?;
+
+    let mut transitions = BTreeMap::new();
+    for row in rows {
+        let effective_at: Option<String> = row.try_get(3)?;
+        record_transition(
+            &mut transitions,
+            row.try_get(0)?,
+            row.try_get(1)?,
+            row.try_get(2)?,
+            effective_at.as_deref(),
+        )?;
+    }
+    Ok(transitions)
+}
+
+fn key_from_row(row: &Row) -> Result<MemoryKey> {
+    let memory_id: &str = row.try_get(0)?;
+    let version: i64 = row.try_get(1)?;
+    parse_memory_key(&format!("{memory_id}:{version}"))
+}
+
+fn to_u32(value: i64, column: &str) -> Result<u32> {
+    u32::try_from(value).with_context(|| format!("invalid {column}: {value}"))
+}
+
+fn parse_utc(raw: &str, column: &str) -> Result<time::OffsetDateTime> {
+    parse_rfc3339_utc(raw).map_err(|err| anyhow!("invalid {column} `{raw}`: {err}"))
+}
+
+fn event_from_row(row: &Row) -> Result<OutcomeEvent> {
+    let event_id_raw: String = row.try_get(1)?;
+    let memory_id_raw: String = row.try_get(3)?;
+    let event_type_raw: String = row.try_get(5)?;
+    let severity_raw: Option<String> = row.try_get(13)?;
+    let payload_json: String = row.try_get(16)?;
+    let source_json: Option<String> = row.try_get(21)?;
+    let effective_at: Option<String> = row.try_get(22)?;
+
+    Ok(OutcomeEvent {
+        event_seq: row.try_get(0)?,
+        event_id: Ulid::from_string(&event_id_raw)
+            .map_err(|_| anyhow!("invalid event_id ULID: {event_id_raw}"))?,
+        ruleset_version: to_u32(row.try_get(2)?, "ruleset_version")?,
+        memory_id: MemoryId(
+            Ulid::from_string(&memory_id_raw)
+                .map_err(|_| anyhow!("invalid ULID: {memory_id_raw}"))?,
+        ),
+        version: to_u32(row.try_get(4)?, "version")?,
+        event_type: OutcomeEventType::parse(&event_type_raw)
+            .ok_or_else(|| anyhow!("invalid event_type: {event_type_raw}"))?,
+        occurred_at: parse_utc(row.try_get(6)?, "occurred_at")?,
+        recorded_at: parse_utc(row.try_get(7)?, "recorded_at")?,
+        writer: row.try_get(8)?,
+        justification: row.try_get(9)?,
+        context_id: row.try_get(10)?,
+        edited: row.try_get(11)?,
+        escalated: row.try_get(12)?,
+        severity: severity_raw
+            .as_deref()
+            .map(|raw| Severity::parse(raw).ok_or_else(|| anyhow!("invalid severity: {raw}")))
+            .transpose()?,
+        manual_confidence: row.try_get(14)?,
+        override_cap: row.try_get(15)?,
+        payload_json: serde_json::from_str(&payload_json).context("invalid payload_json")?,
+        cosigner: row.try_get(17)?,
+        signature: row.try_get(18)?,
+        source: source_json
+            .as_deref()
+            .map(serde_json::from_str)
+            .transpose()
+            .context("invalid source_json")?,
+        effective_at: effective_at
+            .as_deref()
+            .map(|raw| parse_utc(raw, "effective_at"))
+            .transpose()?,
+        prev_event_hash: row.try_get(19)?,
+        event_hash: row.try_get(20)?,
+    })
+}
+
+fn trust_from_row(row: &Row) -> Result<(MemoryTrust, u32)> {
+    let memory_id_raw: String = row.try_get(0)?;
+    let trust_status_raw: String = row.try_get(5)?;
+    let last_scored_at: Option<String> = row.try_get(13)?;
+    let retire_grace_until: Option<String> = row.try_get(15)?;
+    let wins_last5: i16 = row.try_get(9)?;
+    let failures_last5: i16 = row.try_get(10)?;
+
+    Ok((
+        MemoryTrust {
+            memory_id: MemoryId(
+                Ulid::from_string(&memory_id_raw)
+                    .map_err(|_| anyhow!("invalid ULID: {memory_id_raw}"))?,
+            ),
+            version: to_u32(row.try_get(1)?, "version")?,
+            confidence_raw: row.try_get(2)?,
+            confidence_effective: row.try_get(3)?,
+            baseline_confidence: row.try_get(4)?,
+            trust_status: TrustStatus::parse(&trust_status_raw)
+                .ok_or_else(|| anyhow!("invalid trust_status: {trust_status_raw}"))?,
+            contradiction_cap_active: row.try_get(6)?,
+            cap_value: row.try_get(7)?,
+            manual_override_active: row.try_get(8)?,
+            wins_last5: u8::try_from(wins_last5)
+                .with_context(|| format!("invalid wins_last5: {wins_last5}"))?,
+            failures_last5: u8::try_from(failures_last5)
+                .with_context(|| format!("invalid failures_last5: {failures_last5}"))?,
+            last_event_seq: row.try_get(11)?,
+            last_scored_at: last_scored_at
+                .as_deref()
+                .map(|raw| parse_utc(raw, "last_scored_at"))
+                .transpose()?,
+            updated_at: parse_utc(row.try_get(14)?, "updated_at")?,
+            retire_grace_until: retire_grace_until
+                .as_deref()
+                .map(|raw| parse_utc(raw, "retire_grace_until"))
+                .transpose()?,
+            authoritative: false,
+        },
+        to_u32(row.try_get(12)?, "last_ruleset_version")?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Connection URL of a scratch database; the tests below return early when it is unset.
+    const TEST_DATABASE_URL_ENV: &str = "MEMORY_KERNEL_TEST_POSTGRES_URL";
+
+    fn must<T>(result: Result<T>) -> T {
+        match result {
+            Ok(value) => value,
+            Err(err) => panic!("test failure: {err:#}"),
+        }
+    }
+
+    /// Connect to the test database with `search_path` set to `schema`.
+    fn connect_in_schema(url: &str, schema: &str) -> PostgresOutcomeStore {
+        let store = must(PostgresOutcomeStore::connect(url));
+        must(
+            store
+                .client()
+                .batch_execute(&format!(
+                    "CREATE SCHEMA IF NOT EXISTS {schema}; SET search_path TO {schema}"
+                ))
+                .map_err(Into::into),
+        );
+        store
+    }
+
+    /// A migrated store in a fresh schema, with the URL and schema to reach it again.
+    fn test_store() -> Option<(PostgresOutcomeStore, String, String)> {
+        let url = std::env::var(TEST_DATABASE_URL_ENV).ok()?;
+        let schema = format!(
+            "outcome_test_{}",
+            Ulid::new().to_string().to_ascii_lowercase()
+        );
+        let store = connect_in_schema(&url, &schema);
+        must(store.migrate());
+        Some((store, url, schema))
+    }
+
+    fn drop_schema(store: &PostgresOutcomeStore, schema: &str) {
+        must(
+            store
+                .client()
+                .batch_execute(&format!("DROP SCHEMA {schema} CASCADE"))
+                .map_err(Into::into),
+        );
+    }
+
+    fn fixture_input(memory_id: MemoryId, event_type: OutcomeEventType) -> OutcomeEventInput {
+        OutcomeEventInput {
+            event_id: None,
+            ruleset_version: 1,
+            memory_id,
+            version: 1,
+            event_type,
+            occurred_at: must(
+                parse_rfc3339_utc("2026-02-07T12:00:00Z").map_err(|err| anyhow!(err.to_string())),
+            ),
+            writer: "tester".to_string(),
+            justification: "fixture".to_string(),
+            context_id: Some("ctx-1".to_string()),
+            edited: false,
+            escalated: false,
+            severity: None,
+            manual_confidence: None,
+            override_cap: false,
+            payload_json: serde_json::json!({"source": "postgres"}),
+            cosigner: None,
+            signature: None,
+            source: None,
+            effective_at: None,
+        }
+    }
+
+    #[test]
+    fn postgres_store_appends_projects_and_gates_like_sqlite() {
+        let Some((mut store, _, schema)) = test_store() else {
+            return;
+        };
+        let memory_id = MemoryId(Ulid::new());
+        let key = MemoryKey {
+            memory_id,
+            version: 1,
+        };
+
+        let appended = must(store.append_events_and_project(&[
+            fixture_input(memory_id, OutcomeEventType::Success),
+            fixture_input(memory_id, OutcomeEventType::Success),
+        ]));
+        assert_eq!(appended[1].prev_event_hash, appended[0].event_hash);
+        let listed = must(store.list_events_for_key(memory_id, 1, None));
+        assert_eq!(listed, appended);
+        assert_eq!(
+            must(store.list_events_for_key(memory_id, 1, Some(1))).len(),
+            1
+        );
+
+        let trust = must(store.get_memory_trust(memory_id, 1, None));
+        assert_eq!(
+            trust.map(|trust| trust.last_event_seq),
+            Some(appended[1].event_seq)
+        );
+        let decisions =
+            must(store.gate_preview(RetrievalMode::Exploration, now_utc(), None, &[key]));
+        assert_eq!(decisions.len(), 1);
+        assert!(decisions[0].include);
+        assert!(must(store.projector_check()).healthy);
+
+        must(store.append_events(&[fixture_input(memory_id, OutcomeEventType::Failure)]));
+        let status = must(store.projector_status());
+        assert_eq!(status.lag_events, 1);
+        assert_eq!(status.stale_trust_rows, 1);
+        assert!(!must(store.projector_check()).healthy);
+        let refused = store.gate_preview_consistent(
+            RetrievalMode::Safe,
+            now_utc(),
+            None,
+            &[key],
+            StaleSnapshotPolicy::Refuse,
+        );
+        assert!(refused.is_err_and(|err| err.to_string().contains("stale trust snapshot")));
+        must(store.gate_preview_consistent(
+            RetrievalMode::Safe,
+            now_utc(),
+            None,
+            &[key],
+            StaleSnapshotPolicy::Project,
+        ));
+        assert!(must(store.projector_stale_keys(None)).is_empty());
+
+        let report = must(store.replay(None));
+        assert_eq!(report.processed_events, 3);
+        assert_eq!(report.last_event_seq, listed[1].event_seq + 1);
+        assert_eq!(must(store.projector_status()).lag_events, 0);
+        let digest = must(store.projector_digest(now_utc() - time::Duration::days(1), 30));
+        assert!(digest.healthy);
+
+        drop_schema(&store, &schema);
+    }
+
+    #[test]
+    fn postgres_store_keeps_the_log_append_only_and_checks_rulesets() {
+        let Some((mut store, _, schema)) = test_store() else {
+            return;
+        };
+        let memory_id = MemoryId(Ulid::new());
+        must(store.append_events(&[fixture_input(memory_id, OutcomeEventType::Success)]));
+
+        let update = store
+            .client()
+            .execute("UPDATE outcome_events SET writer = 'mallory'", &[]);
+        assert!(update.is_err_and(|err| format!("{err:?}").contains("append-only")));
+
+        let mut unknown = fixture_input(memory_id, OutcomeEventType::Success);
+        unknown.ruleset_version = 9;
+        let err =
+            store.append_events(&[fixture_input(memory_id, OutcomeEventType::Success), unknown]);
+        assert!(err.is_err_and(|err| err.to_string().contains("missing ruleset_version 9")));
+        assert_eq!(must(store.list_events_for_key(memory_id, 1, None)).len(), 1);
+        must(store.migrate());
+
+        drop_schema(&store, &schema);
+    }
+
+    #[test]
+    fn postgres_appends_from_concurrent_connections_form_one_chain() {
+        let Some((store, url, schema)) = test_store() else {
+            return;
+        };
+        let writers =
+            (0..3)
+                .map(|_| {
+                    let (url, schema) = (url.clone(), schema.clone());
+                    std::thread::spawn(move || {
+                        let mut writer = connect_in_schema(&url, &schema);
+                        let memory_id = MemoryId(Ulid::new());
+                        for _ in 0..10 {
+                            must(writer.append_events(&[fixture_input(
+                                memory_id,
+                                OutcomeEventType::Success,
+                            )]));
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+        for writer in writers {
+            assert!(writer.join().is_ok());
+        }
+
+        let rows = must(
+            store
+                .client()
+                .query(
+                    &format!("SELECT {EVENT_COLUMNS} FROM outcome_events ORDER BY event_seq"),
+                    &[],
+                )
+                .map_err(Into::into),
+        );
+        let events = must(rows.iter().map(event_from_row).collect::<Result<Vec<_>>>());
+        assert_eq!(events.len(), 30);
+        assert_eq!(events[0].prev_event_hash, None);
+        for pair in events.windows(2) {
+            assert_eq!(pair[1].prev_event_hash, pair[0].event_hash);
+            assert_eq!(
+                pair[1].event_hash.as_deref(),
+                Some(must(compute_outcome_event_hash(&pair[1])).as_str())
+            );
+        }
+
+        drop_schema(&store, &schema);
+    }
+}
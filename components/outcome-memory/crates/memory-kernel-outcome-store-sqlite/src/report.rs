@@ -0,0 +1,216 @@
+//! Aggregated outcome store health report (`mk outcome report`).
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Context, Result};
+use memory_kernel_outcome_core::{format_rfc3339, now_utc, OutcomeEventType, TrustStatus};
+use rusqlite::params;
+
+use crate::{SqliteOutcomeStore, PROJECTOR_NAME};
+
+/// Width of the time buckets event volume is grouped into. Buckets start at UTC boundaries;
+/// weeks start on Monday.
+#[derive(Debug, Clone, Copy, Default, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportBucket {
+    Hour,
+    #[default]
+    Day,
+    Week,
+}
+
+impl ReportBucket {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Hour => "hour",
+            Self::Day => "day",
+            Self::Week => "week",
+        }
+    }
+
+    /// `SQLite` expression mapping `occurred_at` to the start of its bucket.
+    fn start_expr(self) -> &'static str {
+        match self {
+            Self::Hour => "strftime('%Y-%m-%dT%H:00:00Z', occurred_at)",
+            Self::Day => "strftime('%Y-%m-%dT00:00:00Z', occurred_at)",
+            Self::Week => "strftime('%Y-%m-%dT00:00:00Z', occurred_at, 'weekday 0', '-6 days')",
+        }
+    }
+}
+
+/// Events that occurred in one time bucket, by event type. Types without events are omitted.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct EventVolumeBucket {
+    pub bucket_start: String,
+    pub total: u64,
+    pub by_type: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct ContradictionCounts {
+    /// `authoritative_contradiction` events that occurred in the report window.
+    pub events: u64,
+    /// Distinct memory keys with at least one contradiction in the report window.
+    pub keys: u64,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct ProjectorLag {
+    pub projected_event_seq: i64,
+    pub latest_event_seq: i64,
+    pub lag_events: i64,
+    pub updated_at: Option<String>,
+}
+
+/// Store health summary. Event volume and contradiction counts cover events that occurred at or
+/// after `since` (all events when absent); trust figures describe the current snapshots.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+pub struct OutcomeReport {
+    pub contract_version: String,
+    pub generated_at: String,
+    pub since: Option<String>,
+    pub bucket: ReportBucket,
+    pub total_events: u64,
+    pub event_volume: Vec<EventVolumeBucket>,
+    /// Snapshot count per trust status; every status is present.
+    pub trust_status: BTreeMap<String, u64>,
+    pub capped_memories: u64,
+    pub contradictions: ContradictionCounts,
+    pub projector: ProjectorLag,
+}
+
+impl SqliteOutcomeStore {
+    /// Aggregate event volume, trust status distribution, capped memories, contradiction counts,
+    /// and projector lag into one report.
+    pub fn outcome_report(
+        &self,
+        bucket: ReportBucket,
+        since: Option<time::OffsetDateTime>,
+    ) -> Result<OutcomeReport> {
+        let since = since
+            .map(format_rfc3339)
+            .transpose()
+            .map_err(|err| anyhow!(err.to_string()))?;
+
+        let event_volume = self.event_volume(bucket, since.as_deref())?;
+        let total_events = event_volume.iter().map(|bucket| bucket.total).sum();
+
+        let mut trust_status: BTreeMap<String, u64> = [
+            TrustStatus::Active,
+            TrustStatus::Validated,
+            TrustStatus::Retired,
+        ]
+        .into_iter()
+        .map(|status| (status.as_str().to_string(), 0))
+        .collect();
+        let mut stmt = self.conn.prepare(
+            "SELECT trust_status, COUNT(*) FROM memory_trust GROUP BY trust_status ORDER BY 1",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?;
+        for row in rows {
+            let (status, count) = row.context("failed to read trust status distribution")?;
+            trust_status.insert(status, count_u64(count)?);
+        }
+
+        let capped_memories = count_u64(
+            self.conn
+                .query_row(
+                    "SELECT COUNT(*) FROM memory_trust WHERE contradiction_cap_active = 1",
+                    [],
+                    |row| row.get(0),
+                )
+                .context("failed to count capped memories")?,
+        )?;
+
+        let (contradiction_events, contradiction_keys): (i64, i64) = self
+            .conn
+            .query_row(
+                "SELECT COUNT(*), COUNT(DISTINCT memory_id || ':' || version)
+                 FROM outcome_events
+                 WHERE event_type = ?1
+                   AND (?2 IS NULL OR julianday(occurred_at) >= julianday(?2))",
+                params![OutcomeEventType::AuthoritativeContradiction.as_str(), since],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .context("failed to count contradictions")?;
+
+        let (projected_event_seq, updated_at) = match self.projection_state(PROJECTOR_NAME)? {
+            Some((_, seq, updated_at)) => (seq, Some(updated_at)),
+            None => (0, None),
+        };
+        let latest_event_seq = self.latest_event_seq()?.unwrap_or(0);
+
+        Ok(OutcomeReport {
+            contract_version: "outcome_report.v1".to_string(),
+            generated_at: format_rfc3339(now_utc()).map_err(|err| anyhow!(err.to_string()))?,
+            since,
+            bucket,
+            total_events,
+            event_volume,
+            trust_status,
+            capped_memories,
+            contradictions: ContradictionCounts {
+                events: count_u64(contradiction_events)?,
+                keys: count_u64(contradiction_keys)?,
+            },
+            projector: ProjectorLag {
+                projected_event_seq,
+                latest_event_seq,
+                lag_events: (latest_event_seq - projected_event_seq).max(0),
+                updated_at,
+            },
+        })
+    }
+
+    fn event_volume(
+        &self,
+        bucket: ReportBucket,
+        since: Option<&str>,
+    ) -> Result<Vec<EventVolumeBucket>> {
+        let sql = format!(
+            "SELECT {start} AS bucket_start, event_type, COUNT(*)
+             FROM outcome_events
+             WHERE ?1 IS NULL OR julianday(occurred_at) >= julianday(?1)
+             GROUP BY bucket_start, event_type
+             ORDER BY bucket_start, event_type",
+            start = bucket.start_expr()
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params![since], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })?;
+
+        let mut buckets: Vec<EventVolumeBucket> = Vec::new();
+        for row in rows {
+            let (bucket_start, event_type, count) =
+                row.context("failed to read event volume row")?;
+            let count = count_u64(count)?;
+            if buckets
+                .last()
+                .map_or(true, |last| last.bucket_start != bucket_start)
+            {
+                buckets.push(EventVolumeBucket {
+                    bucket_start,
+                    total: 0,
+                    by_type: BTreeMap::new(),
+                });
+            }
+            if let Some(current) = buckets.last_mut() {
+                current.total += count;
+                current.by_type.insert(event_type, count);
+            }
+        }
+        Ok(buckets)
+    }
+}
+
+fn count_u64(count: i64) -> Result<u64> {
+    u64::try_from(count).with_context(|| format!("invalid aggregate count: {count}"))
+}
@@ -1,13 +1,15 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{anyhow, Result};
 use memory_kernel_core::{
-    build_context_package, build_recall_context_package, default_recall_record_types, Authority,
-    ConstraintEffect, ConstraintPayload, ConstraintScope, ContextPackage, DecisionPayload,
-    EventPayload, LinkType, MemoryId, MemoryPayload, MemoryRecord, MemoryVersionId,
-    PreferencePayload, QueryRequest, RecordType, TruthStatus,
+    build_context_package, build_recall_context_package_with_tags, default_recall_record_types,
+    normalize_memory_tags, Authority, ConstraintEffect, ConstraintPayload, ConstraintScope,
+    ContextPackage, DecisionPayload, EventPayload, LinkType, MemoryId, MemoryPayload, MemoryRecord,
+    MemoryVersionId, PreferencePayload, QueryRequest, RecordType, TruthStatus,
+};
+use memory_kernel_store_sqlite::{
+    AdminAuditEntry, ApiToken, SchemaStatus, SqlitePragmas, SqliteStore,
 };
-use memory_kernel_store_sqlite::{SchemaStatus, SqliteStore};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use time::OffsetDateTime;
@@ -48,6 +50,8 @@ pub struct AddConstraintRequest {
     pub effective_at: Option<OffsetDateTime>,
     pub supersedes: Vec<MemoryVersionId>,
     pub contradicts: Vec<MemoryVersionId>,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -70,6 +74,8 @@ pub struct AddSummaryRequest {
     pub effective_at: Option<OffsetDateTime>,
     pub supersedes: Vec<MemoryVersionId>,
     pub contradicts: Vec<MemoryVersionId>,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -104,6 +110,9 @@ pub struct AskRequest {
 pub struct RecallRequest {
     pub text: String,
     pub record_types: Vec<RecordType>,
+    /// Restrict recall to records carrying at least one of these tags.
+    #[serde(default)]
+    pub record_tags: Vec<String>,
     #[serde(with = "time::serde::rfc3339::option")]
     pub as_of: Option<OffsetDateTime>,
 }
@@ -111,16 +120,69 @@ pub struct RecallRequest {
 #[derive(Debug, Clone)]
 pub struct MemoryKernelApi {
     db_path: PathBuf,
+    pragmas: SqlitePragmas,
+    admin_audit: bool,
 }
 
 impl MemoryKernelApi {
     #[must_use]
     pub fn new(db_path: PathBuf) -> Self {
-        Self { db_path }
+        Self { db_path, pragmas: SqlitePragmas::default(), admin_audit: true }
+    }
+
+    /// Skip the `admin_audit` entries mutating calls append, for embedders that audit every
+    /// call themselves (such as `memory-kernel-service --require-auth`).
+    #[must_use]
+    pub fn without_admin_audit(mut self) -> Self {
+        self.admin_audit = false;
+        self
+    }
+
+    /// Open the database with `pragmas` on every call instead of the store defaults.
+    #[must_use]
+    pub fn with_sqlite_pragmas(mut self, pragmas: SqlitePragmas) -> Self {
+        self.pragmas = pragmas;
+        self
+    }
+
+    /// Path of the backing `SQLite` database.
+    #[must_use]
+    pub fn db_path(&self) -> &Path {
+        &self.db_path
     }
 
     fn open_store(&self) -> Result<SqliteStore> {
-        SqliteStore::open(&self.db_path)
+        SqliteStore::open_with_pragmas(&self.db_path, &self.pragmas)
+    }
+
+    /// Run a mutating operation and append its outcome to `admin_audit`. The entry is written
+    /// after `op` has committed, so failing to record it only warns on stderr: returning an
+    /// error for a mutation that was applied would invite the caller to retry it.
+    fn audited<A, T, F>(&self, command: &str, writer: Option<&str>, args: &A, op: F) -> Result<T>
+    where
+        A: Serialize,
+        F: FnOnce(&mut SqliteStore) -> Result<T>,
+    {
+        let mut store = self.open_store()?;
+        let result = op(&mut store);
+        if self.admin_audit {
+            let recorded = serde_json::to_string(args)
+                .map_err(anyhow::Error::from)
+                .and_then(|args_json| {
+                    AdminAuditEntry::new(
+                        "api",
+                        command,
+                        &[args_json],
+                        writer,
+                        result.as_ref().err().map(|err| format!("{err:#}")),
+                    )
+                })
+                .and_then(|entry| store.record_admin_audit(&entry));
+            if let Err(err) = recorded {
+                eprintln!("warning: failed to record admin audit for `{command}`: {err:#}");
+            }
+        }
+        result
     }
 
     /// Inspect schema status without mutating data.
@@ -137,7 +199,7 @@ impl MemoryKernelApi {
     /// # Errors
     /// Returns an error when migration planning or execution fails.
     pub fn migrate(&self, dry_run: bool) -> Result<MigrateResult> {
-        let mut store = self.open_store()?;
+        let store = self.open_store()?;
         let before = store.schema_status()?;
         if dry_run {
             return Ok(MigrateResult {
@@ -151,17 +213,21 @@ impl MemoryKernelApi {
             });
         }
 
+        drop(store);
+
         let planned_versions = before.pending_versions;
-        store.migrate()?;
-        let after = store.schema_status()?;
-        Ok(MigrateResult {
-            dry_run: false,
-            current_version: before.current_version,
-            target_version: before.target_version,
-            would_apply_versions: planned_versions,
-            inferred_from_legacy: before.inferred_from_legacy,
-            after_version: Some(after.current_version),
-            up_to_date: Some(after.pending_versions.is_empty()),
+        self.audited("migrate", None, &serde_json::json!({ "dry_run": false }), |store| {
+            store.migrate()?;
+            let after = store.schema_status()?;
+            Ok(MigrateResult {
+                dry_run: false,
+                current_version: before.current_version,
+                target_version: before.target_version,
+                would_apply_versions: planned_versions,
+                inferred_from_legacy: before.inferred_from_legacy,
+                after_version: Some(after.current_version),
+                up_to_date: Some(after.pending_versions.is_empty()),
+            })
         })
     }
 
@@ -170,11 +236,13 @@ impl MemoryKernelApi {
     /// # Errors
     /// Returns an error when record validation or persistence fails.
     pub fn add_constraint(&self, input: AddConstraintRequest) -> Result<MemoryRecord> {
-        let mut store = self.open_store()?;
-        store.migrate()?;
-        let record = build_constraint_record(input);
-        store.write_record(&record)?;
-        Ok(record)
+        let writer = input.writer.clone();
+        self.audited("add_constraint", Some(&writer), &input.clone(), |store| {
+            store.migrate()?;
+            let record = build_constraint_record(input);
+            store.write_record(&record)?;
+            Ok(record)
+        })
     }
 
     /// Add one summary-backed memory record (`decision`, `preference`, `event`, or `outcome`).
@@ -182,11 +250,13 @@ impl MemoryKernelApi {
     /// # Errors
     /// Returns an error when an unsupported record type is provided, or persistence fails.
     pub fn add_summary(&self, input: AddSummaryRequest) -> Result<MemoryRecord> {
-        let mut store = self.open_store()?;
-        store.migrate()?;
-        let record = build_summary_record(input)?;
-        store.write_record(&record)?;
-        Ok(record)
+        let writer = input.writer.clone();
+        self.audited("add_summary", Some(&writer), &input.clone(), |store| {
+            store.migrate()?;
+            let record = build_summary_record(input)?;
+            store.write_record(&record)?;
+            Ok(record)
+        })
     }
 
     /// Add one lineage link between memory versions.
@@ -194,22 +264,24 @@ impl MemoryKernelApi {
     /// # Errors
     /// Returns an error when link persistence fails.
     pub fn add_link(&self, input: AddLinkRequest) -> Result<AddLinkResult> {
-        let mut store = self.open_store()?;
-        store.migrate()?;
-        store.add_link(
-            input.from,
-            input.to,
-            input.relation,
-            &input.writer,
-            &input.justification,
-        )?;
-
-        Ok(AddLinkResult {
-            from_memory_version_id: input.from,
-            to_memory_version_id: input.to,
-            relation: input.relation,
-            writer: input.writer,
-            justification: input.justification,
+        let writer = input.writer.clone();
+        self.audited("add_link", Some(&writer), &input.clone(), |store| {
+            store.migrate()?;
+            store.add_link(
+                input.from,
+                input.to,
+                input.relation,
+                &input.writer,
+                &input.justification,
+            )?;
+
+            Ok(AddLinkResult {
+                from_memory_version_id: input.from,
+                to_memory_version_id: input.to,
+                relation: input.relation,
+                writer: input.writer,
+                justification: input.justification,
+            })
         })
     }
 
@@ -272,17 +344,17 @@ impl MemoryKernelApi {
             .collect::<Vec<_>>();
         record_type_names.sort_unstable();
 
-        let snapshot_id = compute_snapshot_id(
-            &records,
-            as_of,
-            &input.text,
-            &[
-                "query_mode=recall".to_string(),
-                format!("record_types={}", record_type_names.join(",")),
-            ],
-        );
+        let mut scope_parts = vec![
+            "query_mode=recall".to_string(),
+            format!("record_types={}", record_type_names.join(",")),
+        ];
+        let record_tags = normalize_memory_tags(input.record_tags);
+        if !record_tags.is_empty() {
+            scope_parts.push(format!("record_tags={}", record_tags.join(",")));
+        }
+        let snapshot_id = compute_snapshot_id(&records, as_of, &input.text, &scope_parts);
 
-        let package = build_recall_context_package(
+        let package = build_recall_context_package_with_tags(
             &records,
             QueryRequest {
                 text: input.text,
@@ -293,6 +365,7 @@ impl MemoryKernelApi {
             },
             &snapshot_id,
             &selected_record_types,
+            &record_tags,
         )?;
         store.save_context_package(&package)?;
         Ok(package)
@@ -310,6 +383,31 @@ impl MemoryKernelApi {
             .ok_or_else(|| anyhow!("context package not found: {context_package_id}"))?;
         Ok(package)
     }
+
+    /// Resolve a bearer secret to its active API token.
+    ///
+    /// # Errors
+    /// Returns an error when the token table cannot be queried.
+    pub fn authenticate_token(&self, secret: &str) -> Result<Option<ApiToken>> {
+        self.open_store()?.authenticate_api_token(secret)
+    }
+
+    /// Append an externally built entry (for example, an authenticated service call) to
+    /// `admin_audit`.
+    ///
+    /// # Errors
+    /// Returns an error when the audit row cannot be written.
+    pub fn record_admin_audit(&self, entry: &AdminAuditEntry) -> Result<()> {
+        self.open_store()?.record_admin_audit(entry)
+    }
+
+    /// List `admin_audit` entries, most recent first.
+    ///
+    /// # Errors
+    /// Returns an error when the audit table cannot be queried.
+    pub fn admin_audit_log(&self, limit: Option<usize>) -> Result<Vec<AdminAuditEntry>> {
+        self.open_store()?.list_admin_audit(limit)
+    }
 }
 
 fn build_constraint_record(input: AddConstraintRequest) -> MemoryRecord {
@@ -334,6 +432,7 @@ fn build_constraint_record(input: AddConstraintRequest) -> MemoryRecord {
         },
         supersedes: input.supersedes,
         contradicts: input.contradicts,
+        tags: normalize_memory_tags(input.tags),
         payload: MemoryPayload::Constraint(ConstraintPayload {
             scope: ConstraintScope {
                 actor: input.actor,
@@ -382,6 +481,7 @@ fn build_summary_record(input: AddSummaryRequest) -> Result<MemoryRecord> {
         },
         supersedes: input.supersedes,
         contradicts: input.contradicts,
+        tags: normalize_memory_tags(input.tags),
         payload,
     })
 }
@@ -448,6 +548,7 @@ mod tests {
             effective_at: None,
             supersedes: Vec::new(),
             contradicts: Vec::new(),
+            tags: Vec::new(),
         })?;
 
         let package = api.query_ask(AskRequest {
@@ -488,6 +589,7 @@ mod tests {
             effective_at: None,
             supersedes: Vec::new(),
             contradicts: Vec::new(),
+            tags: Vec::new(),
         })?;
 
         let _outcome = api.add_summary(AddSummaryRequest {
@@ -507,11 +609,13 @@ mod tests {
             effective_at: None,
             supersedes: Vec::new(),
             contradicts: Vec::new(),
+            tags: Vec::new(),
         })?;
 
         let package = api.query_recall(RecallRequest {
             text: "usb compliance".to_string(),
             record_types: vec![RecordType::Decision, RecordType::Outcome],
+            record_tags: Vec::new(),
             as_of: None,
         })?;
 
@@ -548,6 +652,7 @@ mod tests {
             effective_at: None,
             supersedes: Vec::new(),
             contradicts: Vec::new(),
+            tags: Vec::new(),
         })?;
 
         let _decision = api.add_summary(AddSummaryRequest {
@@ -567,11 +672,13 @@ mod tests {
             effective_at: None,
             supersedes: Vec::new(),
             contradicts: Vec::new(),
+            tags: Vec::new(),
         })?;
 
         let package = api.query_recall(RecallRequest {
             text: "usb usage".to_string(),
             record_types: Vec::new(),
+            record_tags: Vec::new(),
             as_of: None,
         })?;
 
@@ -585,4 +692,82 @@ mod tests {
         let _ = std::fs::remove_file(&db_path);
         Ok(())
     }
+
+    // Test IDs: TAPI-004
+    #[test]
+    fn api_mutations_are_recorded_in_admin_audit() -> Result<()> {
+        let db_path = unique_temp_db_path();
+        let api = MemoryKernelApi::new(db_path.clone());
+
+        let _record = api.add_summary(AddSummaryRequest {
+            record_type: RecordType::Decision,
+            summary: "Decision: audit every mutation".to_string(),
+            memory_id: None,
+            version: 1,
+            writer: "auditor".to_string(),
+            justification: "api audit fixture".to_string(),
+            source_uri: "file:///decision.md".to_string(),
+            source_hash: None,
+            evidence: Vec::new(),
+            confidence: Some(0.8),
+            truth_status: TruthStatus::Observed,
+            authority: Authority::Authoritative,
+            created_at: None,
+            effective_at: None,
+            supersedes: Vec::new(),
+            contradicts: Vec::new(),
+            tags: Vec::new(),
+        })?;
+
+        let entries = SqliteStore::open(&db_path)?.list_admin_audit(None)?;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].surface, "api");
+        assert_eq!(entries[0].writer.as_deref(), Some("auditor"));
+        assert_eq!(entries[0].outcome, "ok");
+
+        let _ = std::fs::remove_file(&db_path);
+        Ok(())
+    }
+
+    // Test IDs: TAPI-005
+    #[test]
+    fn api_mutation_succeeds_when_admin_audit_cannot_be_recorded() -> Result<()> {
+        let db_path = unique_temp_db_path();
+        let api = MemoryKernelApi::new(db_path.clone());
+        api.migrate(false)?;
+        // An incompatible table makes every audit insert fail after the mutation commits.
+        rusqlite::Connection::open(&db_path)?.execute_batch(
+            "DROP TABLE IF EXISTS admin_audit; CREATE TABLE admin_audit(unrelated TEXT);",
+        )?;
+
+        let record = api.add_constraint(AddConstraintRequest {
+            actor: "user".to_string(),
+            action: "use".to_string(),
+            resource: "usb_drive".to_string(),
+            effect: ConstraintEffect::Deny,
+            note: None,
+            memory_id: None,
+            version: 1,
+            writer: "auditor".to_string(),
+            justification: "audit failure fixture".to_string(),
+            source_uri: "file:///policy.md".to_string(),
+            source_hash: None,
+            evidence: Vec::new(),
+            confidence: Some(0.9),
+            truth_status: TruthStatus::Asserted,
+            authority: Authority::Authoritative,
+            created_at: None,
+            effective_at: None,
+            supersedes: Vec::new(),
+            contradicts: Vec::new(),
+            tags: Vec::new(),
+        })?;
+
+        let stored = SqliteStore::open(&db_path)?.list_records()?;
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].memory_version_id, record.memory_version_id);
+
+        let _ = std::fs::remove_file(&db_path);
+        Ok(())
+    }
 }
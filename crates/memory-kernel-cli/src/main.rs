@@ -6,13 +6,23 @@ use chacha20poly1305::aead::{Aead, KeyInit};
 use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
 use clap::{Args, Parser, Subcommand, ValueEnum};
 use hmac::{Hmac, Mac};
+use memory_kernel_api::MemoryKernelApi;
 use memory_kernel_core::{
-    build_context_package, build_recall_context_package, default_recall_record_types, Authority,
-    ConstraintEffect, ConstraintPayload, ConstraintScope, LinkType, MemoryId, MemoryPayload,
-    MemoryRecord, MemoryVersionId, QueryRequest, RecordType, TruthStatus,
+    build_context_package, build_recall_context_package_with_tags, default_recall_record_types,
+    normalize_memory_tags, Authority, ConstraintEffect, ConstraintPayload, ConstraintScope,
+    LinkType, MemoryId, MemoryPayload, MemoryRecord, MemoryVersionId, QueryRequest,
+    ReasonCodeRegistry, RecordType, TruthStatus,
 };
-use memory_kernel_outcome_cli::OutcomeCommand as OutcomeCliCommand;
-use memory_kernel_store_sqlite::{ExportManifest, SqliteStore};
+use memory_kernel_outcome_cli::{OutcomeCommand as OutcomeCliCommand, SqlitePragmaArgs};
+use memory_kernel_outcome_core::RetrievalMode;
+use memory_kernel_outcome_store_sqlite::{OutcomeStoreLocation, SqliteOutcomeStore};
+use memory_kernel_store_sqlite::{
+    AdminAuditEntry, ApiRole, ExportManifest, SqlitePragmas, SqliteStore,
+};
+use multi_agent_center_orchestrator::{
+    preview_step_context, ApiMemoryKernelContextSource, TrustGateSelection,
+};
+use multi_agent_center_workflow::load_standalone_step_from_path;
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -20,6 +30,8 @@ use sha2::{Digest, Sha256};
 use time::OffsetDateTime;
 use ulid::Ulid;
 
+mod mcp;
+
 const CLI_CONTRACT_VERSION: &str = "cli.v1";
 const MANIFEST_FILE: &str = "manifest.json";
 const MANIFEST_SIG_FILE: &str = "manifest.sig";
@@ -37,6 +49,9 @@ struct Cli {
     #[arg(long, default_value = "./memory_kernel.sqlite3")]
     db: PathBuf,
 
+    #[command(flatten)]
+    sqlite: SqlitePragmaArgs,
+
     #[command(subcommand)]
     command: Command,
 }
@@ -63,6 +78,83 @@ enum Command {
         #[command(subcommand)]
         command: Box<OutcomeCliCommand>,
     },
+    Auth {
+        #[command(subcommand)]
+        command: Box<AuthCommand>,
+    },
+    Explain {
+        #[command(subcommand)]
+        command: Box<ExplainCommand>,
+    },
+    Doctor {
+        #[command(subcommand)]
+        command: Box<DoctorCommand>,
+    },
+    /// Serve the outcome log, gate preview, trust, projector, and events surface over HTTP.
+    Serve(ServeArgs),
+    /// Serve trust-gated `recall`, `ask`, and `log_outcome` tools to MCP clients over stdio.
+    Mcp(McpArgs),
+}
+
+#[derive(Debug, Args)]
+struct ServeArgs {
+    #[arg(long, default_value = "127.0.0.1:7171")]
+    listen: std::net::SocketAddr,
+}
+
+#[derive(Debug, Args)]
+struct McpArgs {
+    /// Trust gate mode for calls that do not pass `mode`: `safe` or `exploration`.
+    #[arg(long, default_value = "safe")]
+    trust_mode: String,
+}
+
+#[derive(Debug, Subcommand)]
+enum ExplainCommand {
+    /// Describe a reason code from the registry, or list the whole registry when no code is given.
+    ReasonCode(ExplainReasonCodeArgs),
+}
+
+#[derive(Debug, Subcommand)]
+enum DoctorCommand {
+    /// Check that the database's `memory_records` schema has the columns, column types, and
+    /// identity key the outcome store and context path rely on; failed checks carry remediation
+    /// hints and exit non-zero.
+    Compat,
+}
+
+#[derive(Debug, Args)]
+struct ExplainReasonCodeArgs {
+    code: Option<String>,
+}
+
+#[derive(Debug, Subcommand)]
+enum AuthCommand {
+    Token {
+        #[command(subcommand)]
+        command: Box<TokenCommand>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum TokenCommand {
+    Create(TokenCreateArgs),
+    Revoke(TokenRevokeArgs),
+    List,
+}
+
+#[derive(Debug, Args)]
+struct TokenCreateArgs {
+    #[arg(long)]
+    role: RoleArg,
+    #[arg(long)]
+    label: String,
+}
+
+#[derive(Debug, Args)]
+struct TokenRevokeArgs {
+    #[arg(long)]
+    token_id: String,
 }
 
 #[derive(Debug, Subcommand)]
@@ -74,6 +166,13 @@ enum DbCommand {
     Backup(DbBackupArgs),
     Restore(DbRestoreArgs),
     IntegrityCheck,
+    AuditLog(DbAuditLogArgs),
+}
+
+#[derive(Debug, Args)]
+struct DbAuditLogArgs {
+    #[arg(long)]
+    limit: Option<usize>,
 }
 
 #[derive(Debug, Args)]
@@ -191,6 +290,9 @@ struct WriteArgs {
     supersedes: Vec<String>,
     #[arg(long = "contradicts")]
     contradicts: Vec<String>,
+    /// Label the record for tag-scoped recall and permissions; repeatable.
+    #[arg(long = "tag")]
+    tags: Vec<String>,
 }
 
 #[derive(Debug, Args)]
@@ -233,6 +335,9 @@ struct QueryRecallArgs {
     text: String,
     #[arg(long = "record-type", value_enum)]
     record_types: Vec<RecordTypeArg>,
+    /// Only recall records carrying at least one of these tags; repeatable.
+    #[arg(long = "tag")]
+    record_tags: Vec<String>,
     #[arg(long)]
     as_of: Option<String>,
 }
@@ -240,6 +345,9 @@ struct QueryRecallArgs {
 #[derive(Debug, Subcommand)]
 enum ContextCommand {
     Show(ContextShowArgs),
+    /// Resolve a step's context, apply its permissions and trust gating, and print the package
+    /// it would be given with a decision per item, without executing anything.
+    Preview(ContextPreviewArgs),
 }
 
 #[derive(Debug, Args)]
@@ -248,6 +356,22 @@ struct ContextShowArgs {
     context_package_id: String,
 }
 
+#[derive(Debug, Args)]
+struct ContextPreviewArgs {
+    #[arg(long)]
+    step_yaml: PathBuf,
+    #[arg(long)]
+    as_of: Option<String>,
+    /// Trust gate: `allow-all` or `outcome-memory`; defaults to `outcome-memory` exactly when
+    /// `--trust-db` is given.
+    #[arg(long)]
+    trust_source: Option<String>,
+    #[arg(long)]
+    trust_db: Option<PathBuf>,
+    #[arg(long, default_value = "safe")]
+    trust_mode: String,
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 enum TruthStatusArg {
     Asserted,
@@ -270,6 +394,14 @@ enum EffectArg {
     Deny,
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum RoleArg {
+    Reader,
+    Writer,
+    Operator,
+    Admin,
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 enum RelationArg {
     Supersedes,
@@ -315,26 +447,112 @@ fn emit_json(value: Value) -> Result<()> {
 }
 
 fn main() -> Result<()> {
+    let argv = std::env::args().skip(1).collect::<Vec<_>>();
     let cli = Cli::parse();
+    let db_path = cli.db.clone();
+    let pragmas = cli.sqlite.pragmas();
+    let audit_command = audit_label(&cli.command);
+    let result = run(cli, &pragmas);
+    if let Some(command) = audit_command {
+        record_cli_audit(&db_path, &pragmas, &command, &argv, &result);
+    }
+    result
+}
+
+fn run(cli: Cli, pragmas: &SqlitePragmas) -> Result<()> {
     match cli.command {
         Command::Db { command } => {
-            let mut store = SqliteStore::open(&cli.db)?;
+            let mut store = SqliteStore::open_with_pragmas(&cli.db, pragmas)?;
             run_db(*command, &mut store)
         }
         Command::Memory { command } => {
-            let mut store = SqliteStore::open(&cli.db)?;
+            let mut store = SqliteStore::open_with_pragmas(&cli.db, pragmas)?;
             run_memory(*command, &mut store)
         }
         Command::Query { command } => {
-            let mut store = SqliteStore::open(&cli.db)?;
+            let mut store = SqliteStore::open_with_pragmas(&cli.db, pragmas)?;
             run_query(*command, &mut store)
         }
-        Command::Context { command } => {
-            let mut store = SqliteStore::open(&cli.db)?;
-            run_context(*command, &mut store)
+        Command::Context { command } => run_context(*command, &cli.db, pragmas),
+        Command::Outcome { command } => memory_kernel_outcome_cli::run_outcome_with_db_pragmas(
+            &cli.db,
+            &cli.sqlite.pragmas(),
+            *command,
+        ),
+        Command::Auth { command } => {
+            let store = SqliteStore::open_with_pragmas(&cli.db, pragmas)?;
+            run_auth(*command, &store)
+        }
+        Command::Explain { command } => run_explain(*command),
+        Command::Doctor { command } => run_doctor(&command, &cli.db, &cli.sqlite),
+        Command::Serve(args) => memory_kernel_outcome_cli::serve_outcome_api(
+            &cli.db,
+            &cli.sqlite.pragmas(),
+            args.listen,
+        ),
+        Command::Mcp(args) => run_mcp(&args, &cli.db, pragmas, &cli.sqlite),
+    }
+}
+
+fn run_mcp(
+    args: &McpArgs,
+    db: &Path,
+    pragmas: &SqlitePragmas,
+    sqlite: &SqlitePragmaArgs,
+) -> Result<()> {
+    let mode = RetrievalMode::parse(&args.trust_mode).ok_or_else(|| {
+        anyhow!("invalid --trust-mode `{}`; use safe or exploration", args.trust_mode)
+    })?;
+    let api = MemoryKernelApi::new(db.to_path_buf()).with_sqlite_pragmas(*pragmas);
+    api.migrate(false)?;
+    let outcome_pragmas = sqlite.pragmas();
+    SqliteOutcomeStore::open_with_pragmas(db, &outcome_pragmas)?.migrate()?;
+    let server = mcp::McpServer::new(api, db.to_path_buf(), outcome_pragmas, mode);
+    server.serve(std::io::stdin().lock(), std::io::stdout().lock())
+}
+
+fn run_explain(command: ExplainCommand) -> Result<()> {
+    match command {
+        ExplainCommand::ReasonCode(args) => {
+            let registry = ReasonCodeRegistry::default();
+            let Some(code) = args.code else {
+                return emit_json(serde_json::json!({ "reason_codes": registry.entries() }));
+            };
+            let entry = registry.lookup(&code).ok_or_else(|| {
+                anyhow!("unknown reason code `{code}`; run `mk explain reason-code` to list them")
+            })?;
+            emit_json(serde_json::json!({
+                "code": code,
+                "registry_entry": entry.code,
+                "category": entry.category,
+                "severity": entry.severity,
+                "description": entry.description,
+                "builtin": entry.builtin,
+            }))
         }
-        Command::Outcome { command } => {
-            memory_kernel_outcome_cli::run_outcome_with_db(&cli.db, *command)
+    }
+}
+
+fn run_doctor(command: &DoctorCommand, db: &Path, sqlite: &SqlitePragmaArgs) -> Result<()> {
+    match command {
+        DoctorCommand::Compat => {
+            if !db.exists() {
+                return Err(anyhow!("database {} does not exist", db.display()));
+            }
+            let store = SqliteOutcomeStore::open_with_pragmas(db, &sqlite.pragmas())?;
+            let report = store.memory_kernel_compatibility_report()?;
+            let compatible = report.compatible;
+            emit_json(
+                serde_json::to_value(&report)
+                    .context("failed to serialize compatibility report")?,
+            )?;
+            if !compatible {
+                return Err(anyhow!(
+                    "MemoryKernel schema is not compatible with the outcome store; see \
+                     checks[].remediation"
+                ));
+            }
+            Ok(())
         }
     }
 }
@@ -348,6 +566,80 @@ fn run_db(command: DbCommand, store: &mut SqliteStore) -> Result<()> {
         DbCommand::Backup(args) => run_db_backup(&args, store),
         DbCommand::Restore(args) => run_db_restore(&args, store),
         DbCommand::IntegrityCheck => run_db_integrity_check(store),
+        DbCommand::AuditLog(args) => run_db_audit_log(&args, store),
+    }
+}
+
+/// Label for commands that mutate the database; read-only commands are not audited.
+fn audit_label(command: &Command) -> Option<String> {
+    match command {
+        Command::Db { command } => match command.as_ref() {
+            DbCommand::Migrate(args) if !args.dry_run => Some("db migrate".to_string()),
+            DbCommand::Import(_) => Some("db import".to_string()),
+            DbCommand::Restore(_) => Some("db restore".to_string()),
+            _ => None,
+        },
+        Command::Memory { command } => match command.as_ref() {
+            MemoryCommand::Add { command } => Some(
+                match command.as_ref() {
+                    AddCommand::Constraint(_) => "memory add constraint",
+                    AddCommand::Decision(_) => "memory add decision",
+                    AddCommand::Preference(_) => "memory add preference",
+                    AddCommand::Event(_) => "memory add event",
+                    AddCommand::Outcome(_) => "memory add outcome",
+                }
+                .to_string(),
+            ),
+            MemoryCommand::Link(_) => Some("memory link".to_string()),
+            MemoryCommand::List => None,
+        },
+        Command::Query { .. }
+        | Command::Context { .. }
+        | Command::Explain { .. }
+        | Command::Doctor { .. }
+        | Command::Serve(_)
+        | Command::Mcp(_) => None,
+        Command::Outcome { command } => command.audit_label().map(str::to_string),
+        Command::Auth { command } => match command.as_ref() {
+            AuthCommand::Token { command } => match command.as_ref() {
+                TokenCommand::Create(_) => Some("auth token create".to_string()),
+                TokenCommand::Revoke(_) => Some("auth token revoke".to_string()),
+                TokenCommand::List => None,
+            },
+        },
+    }
+}
+
+fn record_cli_audit(
+    db_path: &Path,
+    pragmas: &SqlitePragmas,
+    command: &str,
+    argv: &[String],
+    result: &Result<()>,
+) {
+    if result.is_err() && !db_path.exists() {
+        return;
+    }
+    // The admin audit table lives in the memory kernel's SQLite database, which a PostgreSQL
+    // outcome store does not have.
+    if matches!(OutcomeStoreLocation::parse(db_path), OutcomeStoreLocation::Postgres(_)) {
+        return;
+    }
+    let writer = argv
+        .iter()
+        .position(|arg| arg == "--writer")
+        .and_then(|index| argv.get(index + 1).cloned())
+        .or_else(|| argv.iter().find_map(|arg| arg.strip_prefix("--writer=").map(str::to_string)));
+    let recorded = AdminAuditEntry::new(
+        "cli",
+        command,
+        argv,
+        writer.as_deref(),
+        result.as_ref().err().map(|err| format!("{err:#}")),
+    )
+    .and_then(|entry| SqliteStore::open_with_pragmas(db_path, pragmas)?.record_admin_audit(&entry));
+    if let Err(err) = recorded {
+        eprintln!("warning: failed to record admin audit for `{command}`: {err:#}");
     }
 }
 
@@ -475,6 +767,31 @@ fn run_db_integrity_check(store: &SqliteStore) -> Result<()> {
     emit_json(serde_json::to_value(&report).context("failed to serialize integrity report")?)
 }
 
+fn run_db_audit_log(args: &DbAuditLogArgs, store: &SqliteStore) -> Result<()> {
+    let entries = store.list_admin_audit(args.limit)?;
+    emit_json(serde_json::json!({ "entries": entries }))
+}
+
+fn run_auth(command: AuthCommand, store: &SqliteStore) -> Result<()> {
+    let AuthCommand::Token { command } = command;
+    match *command {
+        TokenCommand::Create(args) => {
+            let issued = store.create_api_token(args.role.into_api_role(), &args.label)?;
+            emit_json(serde_json::to_value(&issued).context("failed to serialize api token")?)
+        }
+        TokenCommand::Revoke(args) => {
+            if !store.revoke_api_token(&args.token_id)? {
+                return Err(anyhow!("no active api token with id {}", args.token_id));
+            }
+            emit_json(serde_json::json!({ "token_id": args.token_id, "revoked": true }))
+        }
+        TokenCommand::List => {
+            let tokens = store.list_api_tokens()?;
+            emit_json(serde_json::json!({ "tokens": tokens }))
+        }
+    }
+}
+
 fn run_memory(command: MemoryCommand, store: &mut SqliteStore) -> Result<()> {
     store.migrate()?;
     match command {
@@ -598,17 +915,17 @@ fn run_query(command: QueryCommand, store: &mut SqliteStore) -> Result<()> {
                 .collect::<Vec<_>>();
             type_names.sort_unstable();
 
-            let snapshot_id = compute_snapshot_id(
-                &records,
-                as_of,
-                &args.text,
-                &[
-                    "query_mode=recall".to_string(),
-                    format!("record_types={}", type_names.join(",")),
-                ],
-            );
-
-            let package = build_recall_context_package(
+            let mut scope_parts = vec![
+                "query_mode=recall".to_string(),
+                format!("record_types={}", type_names.join(",")),
+            ];
+            let record_tags = normalize_memory_tags(args.record_tags);
+            if !record_tags.is_empty() {
+                scope_parts.push(format!("record_tags={}", record_tags.join(",")));
+            }
+            let snapshot_id = compute_snapshot_id(&records, as_of, &args.text, &scope_parts);
+
+            let package = build_recall_context_package_with_tags(
                 &records,
                 QueryRequest {
                     text: args.text,
@@ -619,6 +936,7 @@ fn run_query(command: QueryCommand, store: &mut SqliteStore) -> Result<()> {
                 },
                 &snapshot_id,
                 &selected_record_types,
+                &record_tags,
             )?;
 
             store.save_context_package(&package)?;
@@ -629,10 +947,11 @@ fn run_query(command: QueryCommand, store: &mut SqliteStore) -> Result<()> {
     }
 }
 
-fn run_context(command: ContextCommand, store: &mut SqliteStore) -> Result<()> {
-    store.migrate()?;
+fn run_context(command: ContextCommand, db: &Path, pragmas: &SqlitePragmas) -> Result<()> {
     match command {
         ContextCommand::Show(args) => {
+            let mut store = SqliteStore::open_with_pragmas(db, pragmas)?;
+            store.migrate()?;
             let Some(package) = store.get_context_package(&args.context_package_id)? else {
                 return Err(anyhow!("context package not found: {}", args.context_package_id));
             };
@@ -641,6 +960,20 @@ fn run_context(command: ContextCommand, store: &mut SqliteStore) -> Result<()> {
                 serde_json::to_value(&package).context("failed to serialize context package")?,
             )
         }
+        ContextCommand::Preview(args) => {
+            let definition = load_standalone_step_from_path(&args.step_yaml)?;
+            let as_of = parse_optional_rfc3339(args.as_of.as_deref())?;
+            let trust_source = TrustGateSelection::from_flags(
+                args.trust_source.as_deref(),
+                args.trust_db.as_deref(),
+                &args.trust_mode,
+            )?
+            .open(None)?;
+            let context_source = ApiMemoryKernelContextSource::new(db);
+            let preview =
+                preview_step_context(&context_source, trust_source.as_ref(), &definition, as_of)?;
+            emit_json(serde_json::to_value(&preview).context("failed to serialize preview")?)
+        }
     }
 }
 
@@ -694,6 +1027,7 @@ fn build_record(payload: MemoryPayload, write: WriteArgs) -> Result<MemoryRecord
         },
         supersedes,
         contradicts,
+        tags: normalize_memory_tags(write.tags),
         payload,
     })
 }
@@ -984,3 +1318,14 @@ impl RecordTypeArg {
         }
     }
 }
+
+impl RoleArg {
+    fn into_api_role(self) -> ApiRole {
+        match self {
+            Self::Reader => ApiRole::Reader,
+            Self::Writer => ApiRole::Writer,
+            Self::Operator => ApiRole::Operator,
+            Self::Admin => ApiRole::Admin,
+        }
+    }
+}
@@ -0,0 +1,405 @@
+//! `mk mcp`: a Model Context Protocol server on stdio, so agents can query trust-gated memory
+//! directly. `recall` and `ask` run the usual context queries and pass the selected items through
+//! the outcome trust gate; `log_outcome` reports how a memory worked out.
+//!
+//! Messages are newline-delimited JSON-RPC 2.0 on stdin/stdout; diagnostics go to stderr. Tool
+//! results carry their JSON payload as `structuredContent` and as text content; a failed tool
+//! call is a result with `isError` set, not a JSON-RPC error.
+
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context, Result};
+use memory_kernel_api::{AskRequest, MemoryKernelApi, RecallRequest};
+use memory_kernel_core::{Answer, ContextItem, ContextPackage, RecordType};
+use memory_kernel_outcome_core::{
+    parse_rfc3339_utc, GateDecision, MemoryKey, RetrievalMode, TrustStatus,
+};
+use memory_kernel_outcome_store_sqlite::{SqliteOutcomeStore, SqlitePragmas as OutcomePragmas};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use time::OffsetDateTime;
+
+/// Protocol revision offered when the client asks for one this server does not know.
+const MCP_PROTOCOL_VERSION: &str = "2025-06-18";
+const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+const GATED_CONTEXT_CONTRACT_VERSION: &str = "mcp_gated_context.v1";
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+pub(crate) struct McpServer {
+    api: MemoryKernelApi,
+    outcome_db: PathBuf,
+    outcome_pragmas: OutcomePragmas,
+    default_mode: RetrievalMode,
+}
+
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RecallArgs {
+    text: String,
+    #[serde(default)]
+    record_types: Vec<RecordType>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    as_of: Option<String>,
+    #[serde(default)]
+    mode: Option<RetrievalMode>,
+    #[serde(default)]
+    context_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AskArgs {
+    text: String,
+    actor: String,
+    action: String,
+    resource: String,
+    #[serde(default)]
+    as_of: Option<String>,
+    #[serde(default)]
+    mode: Option<RetrievalMode>,
+    #[serde(default)]
+    context_id: Option<String>,
+}
+
+/// A context package after trust gating. `answer` is the package's own answer; gating does not
+/// re-derive it, so check `gated_out` before relying on an answer backed by excluded items.
+#[derive(Debug, Serialize)]
+struct GatedContext {
+    contract_version: &'static str,
+    context_package_id: String,
+    mode: RetrievalMode,
+    #[serde(with = "time::serde::rfc3339")]
+    as_of: OffsetDateTime,
+    context_id: Option<String>,
+    answer: Answer,
+    /// Selected items the trust gate admitted, in rank order.
+    items: Vec<GatedItem>,
+    /// Selected items the trust gate held back; `gate.reason_codes` says why.
+    gated_out: Vec<GatedItem>,
+}
+
+#[derive(Debug, Serialize)]
+struct GatedItem {
+    #[serde(flatten)]
+    item: ContextItem,
+    gate: GateVerdict,
+}
+
+#[derive(Debug, Serialize)]
+struct GateVerdict {
+    include: bool,
+    trust_status: TrustStatus,
+    confidence_effective: f32,
+    capped: bool,
+    reason_codes: Vec<String>,
+}
+
+impl From<GateDecision> for GateVerdict {
+    fn from(decision: GateDecision) -> Self {
+        Self {
+            include: decision.include,
+            trust_status: decision.trust_status,
+            confidence_effective: decision.confidence_effective,
+            capped: decision.capped,
+            reason_codes: decision.reason_codes,
+        }
+    }
+}
+
+impl McpServer {
+    pub(crate) fn new(
+        api: MemoryKernelApi,
+        outcome_db: PathBuf,
+        outcome_pragmas: OutcomePragmas,
+        default_mode: RetrievalMode,
+    ) -> Self {
+        Self { api, outcome_db, outcome_pragmas, default_mode }
+    }
+
+    /// Answer messages from `input` until it closes.
+    pub(crate) fn serve(&self, input: impl BufRead, mut output: impl Write) -> Result<()> {
+        for line in input.lines() {
+            let line = line.context("failed to read MCP message")?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = self.handle_message(&line) {
+                serde_json::to_writer(&mut output, &response)?;
+                output.write_all(b"\n")?;
+                output.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The response to one JSON-RPC message; `None` for notifications.
+    fn handle_message(&self, message: &str) -> Option<Value> {
+        let request: Value = match serde_json::from_str(message) {
+            Ok(value) => value,
+            Err(err) => {
+                return Some(error_response(
+                    &Value::Null,
+                    &RpcError::new(PARSE_ERROR, format!("invalid JSON: {err}")),
+                ))
+            }
+        };
+        let id = request.get("id").cloned();
+        let Some(method) = request.get("method").and_then(Value::as_str) else {
+            return Some(error_response(
+                &id.unwrap_or(Value::Null),
+                &RpcError::new(INVALID_REQUEST, "expected a JSON-RPC request with a method"),
+            ));
+        };
+        let id = id?;
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+        Some(match self.dispatch(method, params) {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(err) => error_response(&id, &err),
+        })
+    }
+
+    fn dispatch(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => Ok(initialize_result(&params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+            "tools/call" => self.call_tool(params),
+            other => Err(RpcError::new(METHOD_NOT_FOUND, format!("unknown method `{other}`"))),
+        }
+    }
+
+    fn call_tool(&self, params: Value) -> Result<Value, RpcError> {
+        #[derive(Deserialize)]
+        struct ToolCall {
+            name: String,
+            #[serde(default)]
+            arguments: Option<Value>,
+        }
+
+        let call: ToolCall = serde_json::from_value(params).map_err(|err| {
+            RpcError::new(INVALID_PARAMS, format!("invalid tools/call params: {err}"))
+        })?;
+        let arguments = call.arguments.unwrap_or_else(|| json!({}));
+        let result = match call.name.as_str() {
+            "recall" => self.recall(arguments),
+            "ask" => self.ask(arguments),
+            "log_outcome" => self.log_outcome(arguments),
+            other => return Err(RpcError::new(INVALID_PARAMS, format!("unknown tool `{other}`"))),
+        };
+        Ok(match result {
+            Ok(value) => json!({
+                "content": [{ "type": "text", "text": value.to_string() }],
+                "structuredContent": value,
+                "isError": false,
+            }),
+            Err(err) => json!({
+                "content": [{ "type": "text", "text": format!("{err:#}") }],
+                "isError": true,
+            }),
+        })
+    }
+
+    fn recall(&self, arguments: Value) -> Result<Value> {
+        let args: RecallArgs =
+            serde_json::from_value(arguments).context("invalid recall arguments")?;
+        let package = self.api.query_recall(RecallRequest {
+            text: args.text,
+            record_types: args.record_types,
+            record_tags: args.tags,
+            as_of: parse_as_of(args.as_of.as_deref())?,
+        })?;
+        self.gate(package, args.mode, args.context_id)
+    }
+
+    fn ask(&self, arguments: Value) -> Result<Value> {
+        let args: AskArgs = serde_json::from_value(arguments).context("invalid ask arguments")?;
+        let package = self.api.query_ask(AskRequest {
+            text: args.text,
+            actor: args.actor,
+            action: args.action,
+            resource: args.resource,
+            as_of: parse_as_of(args.as_of.as_deref())?,
+        })?;
+        self.gate(package, args.mode, args.context_id)
+    }
+
+    fn log_outcome(&self, arguments: Value) -> Result<Value> {
+        let Value::Object(mut line) = arguments else {
+            return Err(anyhow!("log_outcome arguments must be an object"));
+        };
+        let project = match line.remove("project") {
+            None => false,
+            Some(Value::Bool(project)) => project,
+            Some(other) => return Err(anyhow!("`project` must be a boolean, got {other}")),
+        };
+        let mut store = self.open_outcome_store()?;
+        let event =
+            memory_kernel_outcome_cli::log_outcome_value(&mut store, Value::Object(line), project)?;
+        Ok(serde_json::to_value(event)?)
+    }
+
+    /// Gate the package's selected items at its `as_of`, splitting them into admitted and
+    /// held-back items.
+    fn gate(
+        &self,
+        package: ContextPackage,
+        mode: Option<RetrievalMode>,
+        context_id: Option<String>,
+    ) -> Result<Value> {
+        let mode = mode.unwrap_or(self.default_mode);
+        let as_of = package.query.as_of;
+        let candidates: Vec<MemoryKey> = package
+            .selected_items
+            .iter()
+            .map(|item| MemoryKey { memory_id: item.memory_id, version: item.version })
+            .collect();
+        let decisions = self.open_outcome_store()?.gate_preview(
+            mode,
+            as_of,
+            context_id.as_deref(),
+            &candidates,
+        )?;
+
+        let (mut items, mut gated_out) = (Vec::new(), Vec::new());
+        for (item, decision) in package.selected_items.into_iter().zip(decisions) {
+            let gated = GatedItem { item, gate: GateVerdict::from(decision) };
+            if gated.gate.include {
+                items.push(gated);
+            } else {
+                gated_out.push(gated);
+            }
+        }
+        Ok(serde_json::to_value(GatedContext {
+            contract_version: GATED_CONTEXT_CONTRACT_VERSION,
+            context_package_id: package.context_package_id,
+            mode,
+            as_of,
+            context_id,
+            answer: package.answer,
+            items,
+            gated_out,
+        })?)
+    }
+
+    fn open_outcome_store(&self) -> Result<SqliteOutcomeStore> {
+        SqliteOutcomeStore::open_with_pragmas(&self.outcome_db, &self.outcome_pragmas)
+    }
+}
+
+fn parse_as_of(raw: Option<&str>) -> Result<Option<OffsetDateTime>> {
+    raw.map(|raw| parse_rfc3339_utc(raw).map_err(|err| anyhow!("invalid as_of value: {err}")))
+        .transpose()
+}
+
+fn error_response(id: &Value, err: &RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": err.code, "message": err.message },
+    })
+}
+
+fn initialize_result(params: &Value) -> Value {
+    let protocol_version = params
+        .get("protocolVersion")
+        .and_then(Value::as_str)
+        .filter(|requested| SUPPORTED_PROTOCOL_VERSIONS.contains(requested))
+        .unwrap_or(MCP_PROTOCOL_VERSION);
+    json!({
+        "protocolVersion": protocol_version,
+        "capabilities": { "tools": { "listChanged": false } },
+        "serverInfo": { "name": "memory-kernel", "version": env!("CARGO_PKG_VERSION") },
+        "instructions": "Call recall or ask before acting on remembered decisions and constraints; \
+            only `items` passed the trust gate. Report how a used memory worked out with log_outcome.",
+    })
+}
+
+fn tool_definitions() -> Value {
+    let gate_properties = json!({
+        "as_of": { "type": "string", "description": "RFC3339 time to query and gate at; defaults to now." },
+        "mode": { "type": "string", "enum": ["safe", "exploration"], "description": "Trust gate retrieval mode; defaults to the server's --trust-mode." },
+        "context_id": { "type": "string", "description": "Gate context, used for ruleset pins and exploration sampling." },
+    });
+    let with_gate = |properties: Value| {
+        let mut merged = gate_properties.clone();
+        if let (Some(merged), Value::Object(properties)) = (merged.as_object_mut(), properties) {
+            merged.extend(properties);
+        }
+        merged
+    };
+    json!([
+        {
+            "name": "recall",
+            "description": "Recall memories relevant to a question. Returns the items the outcome trust gate admitted and those it held back, each with the gate's reason codes.",
+            "inputSchema": {
+                "type": "object",
+                "properties": with_gate(json!({
+                    "text": { "type": "string" },
+                    "record_types": {
+                        "type": "array",
+                        "items": { "type": "string", "enum": ["constraint", "decision", "preference", "event", "outcome"] },
+                    },
+                    "tags": { "type": "array", "items": { "type": "string" } },
+                })),
+                "required": ["text"],
+                "additionalProperties": false,
+            },
+        },
+        {
+            "name": "ask",
+            "description": "Ask whether an actor may perform an action on a resource under the recorded constraints. Returns the policy answer with its supporting items split by the outcome trust gate, each with the gate's reason codes.",
+            "inputSchema": {
+                "type": "object",
+                "properties": with_gate(json!({
+                    "text": { "type": "string" },
+                    "actor": { "type": "string" },
+                    "action": { "type": "string" },
+                    "resource": { "type": "string" },
+                })),
+                "required": ["text", "actor", "action", "resource"],
+                "additionalProperties": false,
+            },
+        },
+        {
+            "name": "log_outcome",
+            "description": "Record whether using a memory led to success, failure, was ignored, or is unknown. Set project to update its trust snapshot immediately.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "memory_id": { "type": "string" },
+                    "version": { "type": "integer", "minimum": 1 },
+                    "event": { "type": "string", "enum": ["success", "failure", "ignored", "unknown"] },
+                    "writer": { "type": "string" },
+                    "justification": { "type": "string" },
+                    "context_id": { "type": "string" },
+                    "edited": { "type": "boolean" },
+                    "escalated": { "type": "boolean" },
+                    "severity": { "type": "string", "enum": ["low", "med", "high"] },
+                    "occurred_at": { "type": "string" },
+                    "payload": { "type": "object" },
+                    "project": { "type": "boolean" },
+                },
+                "required": ["memory_id", "version", "event", "writer", "justification"],
+            },
+        },
+    ])
+}
@@ -546,6 +546,102 @@ fn memory_add_query_and_context_show_flow_is_consistent() {
     let _ = fs::remove_dir_all(&sandbox);
 }
 
+// Test IDs: TCLI-010
+#[test]
+fn context_preview_reports_per_item_decisions() {
+    let sandbox = unique_temp_dir("memorykernel-cli-context-preview");
+    let db = sandbox.join("kernel.sqlite3");
+
+    let added = run_json([
+        "--db",
+        path_str(&db),
+        "memory",
+        "add",
+        "constraint",
+        "--actor",
+        "user",
+        "--action",
+        "use",
+        "--resource",
+        "usb_drive",
+        "--effect",
+        "deny",
+        "--writer",
+        "tester",
+        "--justification",
+        "policy",
+        "--source-uri",
+        "file:///policy.md",
+        "--truth-status",
+        "asserted",
+        "--authority",
+        "authoritative",
+    ]);
+    let memory_version_id = as_str(&added, "memory_version_id").to_string();
+
+    let step_yaml = sandbox.join("step.yaml");
+    let write_step = |allowed: &str| {
+        fs::write(
+            &step_yaml,
+            format!(
+                r#"
+agent:
+  agent_name: reviewer
+  role: review
+  provider:
+    provider_name: mock
+    model_id: x
+  permissions:
+    allowed_record_types: [{allowed}]
+    allowed_tools: []
+    max_context_items: 10
+    can_propose_memory_writes: false
+    fail_on_permission_prune: false
+step:
+  step_key: review
+  agent_name: reviewer
+  task:
+    context_queries:
+      - text: "Am I allowed to use a USB drive?"
+        actor: user
+        action: use
+        resource: usb_drive
+"#
+            ),
+        )
+        .unwrap_or_else(|err| panic!("failed to write step yaml: {err}"));
+    };
+    let preview = || {
+        run_json(["--db", path_str(&db), "context", "preview", "--step-yaml", path_str(&step_yaml)])
+    };
+    let decision_for = |preview: &Value| {
+        preview
+            .get("item_decisions")
+            .and_then(Value::as_array)
+            .and_then(|items| {
+                items.iter().find(|item| {
+                    item.get("memory_version_id").and_then(Value::as_str)
+                        == Some(memory_version_id.as_str())
+                })
+            })
+            .map_or_else(
+                || panic!("missing decision for constraint: {preview}"),
+                |item| as_str(item, "decision").to_string(),
+            )
+    };
+
+    write_step("constraint");
+    let included = preview();
+    assert_eq!(as_str(&included, "step_key"), "review");
+    assert_eq!(decision_for(&included), "injected");
+
+    write_step("decision");
+    let pruned = preview();
+    assert_eq!(decision_for(&pruned), "pruned_by_permissions");
+
+    let _ = fs::remove_dir_all(&sandbox);
+}
+
 // Test IDs: TCLI-007
 #[test]
 fn query_recall_returns_persisted_mixed_record_context_package() {
@@ -630,6 +726,90 @@ fn query_recall_returns_persisted_mixed_record_context_package() {
     let _ = fs::remove_dir_all(&sandbox);
 }
 
+// Test IDs: TCLI-009
+#[test]
+fn query_recall_tag_filter_selects_only_tagged_records() {
+    let sandbox = unique_temp_dir("memorykernel-cli-recall-tags");
+    let db = sandbox.join("kernel.sqlite3");
+
+    let tagged = run_json([
+        "--db",
+        path_str(&db),
+        "memory",
+        "add",
+        "decision",
+        "--summary",
+        "Decision: USB rollout paused for billing deploy",
+        "--writer",
+        "tester",
+        "--justification",
+        "tagged fixture",
+        "--source-uri",
+        "file:///deploy.md",
+        "--truth-status",
+        "observed",
+        "--authority",
+        "authoritative",
+        "--confidence",
+        "0.8",
+        "--tag",
+        "deploy",
+        "--tag",
+        "billing",
+    ]);
+    assert_eq!(tagged.get("tags"), Some(&serde_json::json!(["billing", "deploy"])));
+
+    let _untagged = run_json([
+        "--db",
+        path_str(&db),
+        "memory",
+        "add",
+        "decision",
+        "--summary",
+        "Decision: USB rollout approved",
+        "--writer",
+        "tester",
+        "--justification",
+        "untagged fixture",
+        "--source-uri",
+        "file:///rollout.md",
+        "--truth-status",
+        "observed",
+        "--authority",
+        "authoritative",
+        "--confidence",
+        "0.9",
+    ]);
+
+    let package = run_json([
+        "--db",
+        path_str(&db),
+        "query",
+        "recall",
+        "--text",
+        "usb rollout",
+        "--tag",
+        "deploy",
+    ]);
+    let selected =
+        package.get("selected_items").and_then(Value::as_array).cloned().unwrap_or_default();
+    assert_eq!(selected.len(), 1);
+    assert_eq!(selected[0].get("memory_id"), tagged.get("memory_id"));
+    assert_eq!(selected[0].get("tags"), Some(&serde_json::json!(["billing", "deploy"])));
+
+    let rejected = run_mk([
+        "--db",
+        path_str(&db),
+        "query",
+        "recall",
+        "--text",
+        "usb rollout",
+        "--tag",
+        "Deploy",
+    ]);
+    assert!(!rejected.status.success());
+}
+
 // Test IDs: TCLI-004
 #[test]
 fn memory_link_rejects_non_ulid_version_ids() {
@@ -660,6 +840,200 @@ fn memory_link_rejects_non_ulid_version_ids() {
     let _ = fs::remove_dir_all(&sandbox);
 }
 
+// Test IDs: TCLI-011
+#[test]
+fn explain_reason_code_describes_registry_entries() {
+    let pinned = run_json(["explain", "reason-code", "ruleset.pinned.v3"]);
+    assert_eq!(as_str(&pinned, "contract_version"), "cli.v1");
+    assert_eq!(as_str(&pinned, "registry_entry"), "ruleset.pinned.v*");
+    assert_eq!(as_str(&pinned, "category"), "ruleset");
+    assert_eq!(as_str(&pinned, "severity"), "info");
+
+    let retired = run_json(["explain", "reason-code", "excluded.retired"]);
+    assert_eq!(as_str(&retired, "severity"), "exclusion");
+
+    let all = run_json(["explain", "reason-code"]);
+    let codes = all
+        .get("reason_codes")
+        .and_then(Value::as_array)
+        .unwrap_or_else(|| panic!("missing reason_codes in payload: {all}"));
+    assert!(codes.iter().any(|entry| entry["code"] == "excluded.no_trust_snapshot"));
+
+    let output = run_mk(["explain", "reason-code", "made.up.code"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("unknown reason code"), "unexpected stderr: {stderr}");
+}
+
+// Test IDs: TCLI-012
+#[test]
+fn doctor_compat_reports_schema_checks_with_remediation() {
+    let sandbox = unique_temp_dir("memorykernel-cli-doctor-compat");
+    let db = sandbox.join("kernel.sqlite3");
+
+    let _ = run_json(["--db", path_str(&db), "db", "migrate"]);
+    let report = run_json(["--db", path_str(&db), "doctor", "compat"]);
+    assert_eq!(as_str(&report, "contract_version"), "cli.v1");
+    assert_eq!(report["compatible"], Value::Bool(true));
+    let checks = report["checks"]
+        .as_array()
+        .unwrap_or_else(|| panic!("missing checks in payload: {report}"));
+    assert!(checks
+        .iter()
+        .any(|check| check["check"] == "column:memory_records.effective_at"
+            && check["passed"] == true));
+
+    let empty = sandbox.join("empty.sqlite3");
+    fs::write(&empty, b"").unwrap_or_else(|err| panic!("failed to create empty db: {err}"));
+    let output = run_mk(["--db", path_str(&empty), "doctor", "compat"]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let failed: Value = serde_json::from_str(stdout.trim())
+        .unwrap_or_else(|err| panic!("stdout is not valid JSON: {err}\nstdout:\n{stdout}"));
+    assert_eq!(failed["compatible"], Value::Bool(false));
+    assert_eq!(failed["checks"][0]["check"], "table:memory_records");
+    assert!(failed["checks"][0]["remediation"]
+        .as_str()
+        .is_some_and(|hint| hint.contains("mk db migrate")));
+
+    let missing = run_mk(["--db", path_str(&sandbox.join("missing.sqlite3")), "doctor", "compat"]);
+    assert!(!missing.status.success());
+
+    let _ = fs::remove_dir_all(&sandbox);
+}
+
+#[test]
+fn mutating_commands_are_recorded_in_admin_audit_log() {
+    let sandbox = unique_temp_dir("memorykernel-cli-admin-audit");
+    let db = sandbox.join("kernel.sqlite3");
+
+    let _ = run_json(["--db", path_str(&db), "db", "migrate"]);
+    let _ = run_json(["--db", path_str(&db), "db", "schema-version"]);
+    let _ = run_json([
+        "--db",
+        path_str(&db),
+        "memory",
+        "add",
+        "decision",
+        "--summary",
+        "Decision: audit admin actions",
+        "--writer",
+        "auditor",
+        "--justification",
+        "audit fixture",
+        "--source-uri",
+        "file:///decision.md",
+        "--truth-status",
+        "observed",
+        "--authority",
+        "authoritative",
+    ]);
+    let failed = run_mk([
+        "--db",
+        path_str(&db),
+        "memory",
+        "link",
+        "--from",
+        "not-a-ulid",
+        "--to",
+        "also-not-a-ulid",
+        "--relation",
+        "supersedes",
+        "--writer",
+        "auditor",
+        "--justification",
+        "invalid input",
+    ]);
+    assert!(!failed.status.success());
+
+    let log = run_json(["--db", path_str(&db), "db", "audit-log"]);
+    let entries = log
+        .get("entries")
+        .and_then(Value::as_array)
+        .unwrap_or_else(|| panic!("audit-log should return entries: {log}"));
+    let commands = entries.iter().map(|entry| as_str(entry, "command")).collect::<Vec<_>>();
+    assert_eq!(commands, vec!["memory link", "memory add decision", "db migrate"]);
+    assert_eq!(as_str(&entries[0], "outcome"), "error");
+    assert_eq!(as_str(&entries[0], "writer"), "auditor");
+    assert_eq!(as_str(&entries[1], "outcome"), "ok");
+    assert_eq!(as_str(&entries[1], "surface"), "cli");
+
+    let limited = run_json(["--db", path_str(&db), "db", "audit-log", "--limit", "1"]);
+    assert_eq!(limited.get("entries").and_then(Value::as_array).map(Vec::len), Some(1));
+
+    let _ = fs::remove_dir_all(&sandbox);
+}
+
+// Test IDs: TCLI-013
+#[test]
+fn auth_token_create_list_and_revoke_round_trip() {
+    let sandbox = unique_temp_dir("memorykernel-cli-auth-token");
+    let db = sandbox.join("kernel.sqlite3");
+
+    let created = run_json([
+        "--db",
+        path_str(&db),
+        "auth",
+        "token",
+        "create",
+        "--role",
+        "writer",
+        "--label",
+        "ci",
+    ]);
+    assert_eq!(as_str(&created, "role"), "writer");
+    assert!(as_str(&created, "secret").starts_with("mkt_"));
+    let token_id = as_str(&created, "token_id").to_string();
+
+    let listed = run_json(["--db", path_str(&db), "auth", "token", "list"]);
+    let tokens = listed
+        .get("tokens")
+        .and_then(Value::as_array)
+        .unwrap_or_else(|| panic!("token list should return tokens: {listed}"));
+    assert_eq!(tokens.len(), 1);
+    assert!(tokens[0].get("secret").is_none());
+
+    let revoked =
+        run_json(["--db", path_str(&db), "auth", "token", "revoke", "--token-id", &token_id]);
+    assert_eq!(revoked.get("revoked").and_then(Value::as_bool), Some(true));
+    let again = run_mk(["--db", path_str(&db), "auth", "token", "revoke", "--token-id", &token_id]);
+    assert!(!again.status.success());
+
+    let _ = fs::remove_dir_all(&sandbox);
+}
+
+#[test]
+fn sqlite_pragma_flags_apply_to_memory_and_outcome_stores() {
+    let sandbox = unique_temp_dir("memorykernel-cli-sqlite-pragmas");
+    let db = sandbox.join("kernel.sqlite3");
+    let pragma_flags = ["--sqlite-journal-mode", "delete", "--sqlite-synchronous", "normal"];
+
+    let migrated =
+        run_mk(["--db", path_str(&db)].into_iter().chain(pragma_flags).chain(["db", "migrate"]));
+    assert!(migrated.status.success(), "{}", String::from_utf8_lossy(&migrated.stderr));
+    // Header bytes 18-19 are the file format versions: 1 for rollback journals, 2 for WAL.
+    let header_version = |db: &Path| fs::read(db).ok().and_then(|bytes| bytes.get(18).copied());
+    assert_eq!(header_version(&db), Some(1));
+
+    let outcome = run_mk(["--db", path_str(&db)].into_iter().chain(pragma_flags).chain([
+        "outcome",
+        "projector",
+        "status",
+    ]));
+    assert!(outcome.status.success(), "{}", String::from_utf8_lossy(&outcome.stderr));
+    assert_eq!(header_version(&db), Some(1));
+
+    let defaulted = run_mk(["--db", path_str(&db), "outcome", "projector", "status"]);
+    assert!(defaulted.status.success());
+    assert_eq!(header_version(&db), Some(2));
+
+    let rejected =
+        run_mk(["--db", path_str(&db), "--sqlite-journal-mode", "fast", "db", "migrate"]);
+    assert!(!rejected.status.success());
+
+    let _ = fs::remove_dir_all(&sandbox);
+}
+
 // Test IDs: TDOC-001
 #[test]
 fn spec_docs_restrict_ambiguous_terms_to_mkr_027_exception_line() {
@@ -889,6 +1263,139 @@ fn host_cli_exposes_outcome_command_tree() {
     let _ = fs::remove_dir_all(&sandbox);
 }
 
+// Test IDs: TCLI-014
+#[test]
+#[allow(clippy::too_many_lines)]
+fn mcp_server_serves_trust_gated_recall_and_outcome_logging_over_stdio() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let sandbox = unique_temp_dir("memorykernel-cli-mcp");
+    let db_path = sandbox.join("memory.sqlite3");
+    let decision = run_json([
+        "--db",
+        path_str(&db_path),
+        "memory",
+        "add",
+        "decision",
+        "--summary",
+        "Deploy with blue green rollouts",
+        "--writer",
+        "tester",
+        "--justification",
+        "mcp fixture",
+        "--source-uri",
+        "file:///deploy.md",
+        "--truth-status",
+        "observed",
+        "--authority",
+        "authoritative",
+    ]);
+    let memory_id = as_str(&decision, "memory_id");
+
+    let call = |id: u64, name: &str, arguments: Value| {
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": { "name": name, "arguments": arguments },
+        })
+    };
+    let recall = serde_json::json!({ "text": "how do we deploy", "record_types": ["decision"] });
+    let messages = [
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": { "protocolVersion": "2024-11-05", "capabilities": {} },
+        }),
+        serde_json::json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+        serde_json::json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }),
+        call(3, "recall", recall.clone()),
+        call(
+            4,
+            "log_outcome",
+            serde_json::json!({
+                "memory_id": memory_id,
+                "version": 1,
+                "event": "success",
+                "writer": "agent",
+                "justification": "rollout went fine",
+                "project": true,
+            }),
+        ),
+        call(5, "recall", recall),
+        call(6, "ask", serde_json::json!({ "text": "deploy?" })),
+        serde_json::json!({ "jsonrpc": "2.0", "id": 7, "method": "resources/list" }),
+    ];
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_mk"))
+        .args(["--db", path_str(&db_path), "mcp"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap_or_else(|err| panic!("failed to spawn mk mcp: {err}"));
+    {
+        let mut stdin = child.stdin.take().unwrap_or_else(|| unreachable!());
+        for message in &messages {
+            writeln!(stdin, "{message}").unwrap_or_else(|err| panic!("write failed: {err}"));
+        }
+    }
+    let output =
+        child.wait_with_output().unwrap_or_else(|err| panic!("mk mcp did not exit: {err}"));
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let responses: Vec<Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| {
+            serde_json::from_str(line)
+                .unwrap_or_else(|err| panic!("invalid response {line}: {err}"))
+        })
+        .collect();
+    // The `notifications/initialized` notification gets no response.
+    let ids: Vec<u64> = responses.iter().filter_map(|response| response["id"].as_u64()).collect();
+    assert_eq!(ids, vec![1, 2, 3, 4, 5, 6, 7]);
+
+    assert_eq!(responses[0]["result"]["protocolVersion"], "2024-11-05");
+    let tools: Vec<&str> = responses[1]["result"]["tools"]
+        .as_array()
+        .unwrap_or_else(|| unreachable!())
+        .iter()
+        .filter_map(|tool| tool["name"].as_str())
+        .collect();
+    assert_eq!(tools, vec!["recall", "ask", "log_outcome"]);
+
+    // Without a trust snapshot the gate holds the memory back and says why.
+    let first = &responses[2]["result"];
+    assert_eq!(first["isError"], false);
+    let first = &first["structuredContent"];
+    assert_eq!(as_str(first, "contract_version"), "mcp_gated_context.v1");
+    assert_eq!(first["items"].as_array().map(Vec::len), Some(0));
+    assert_eq!(first["gated_out"][0]["memory_id"], memory_id);
+    assert_eq!(
+        first["gated_out"][0]["gate"]["reason_codes"],
+        serde_json::json!(["excluded.no_trust_snapshot"])
+    );
+
+    let logged = &responses[3]["result"];
+    assert_eq!(logged["isError"], false);
+    assert_eq!(logged["structuredContent"]["event_type"], "success");
+
+    // The projected success gives the memory a snapshot the gate evaluates.
+    let second = &responses[4]["result"]["structuredContent"];
+    let gated: Vec<&Value> = ["items", "gated_out"]
+        .iter()
+        .flat_map(|key| second[*key].as_array().into_iter().flatten())
+        .collect();
+    assert_eq!(gated.len(), 1);
+    assert_ne!(gated[0]["gate"]["reason_codes"], serde_json::json!(["excluded.no_trust_snapshot"]));
+
+    assert_eq!(responses[5]["result"]["isError"], true);
+    assert_eq!(responses[6]["error"]["code"], -32601);
+
+    let _ = fs::remove_dir_all(&sandbox);
+}
+
 #[test]
 fn integration_contract_schemas_validate_fixtures() {
     let repo = repo_root();
@@ -26,6 +26,7 @@ fn mk_constraint(index: usize) -> MemoryRecord {
         },
         supersedes: Vec::new(),
         contradicts: Vec::new(),
+        tags: Vec::new(),
         payload: MemoryPayload::Constraint(ConstraintPayload {
             scope: ConstraintScope {
                 actor: "user".to_string(),
@@ -72,6 +73,7 @@ fn mk_summary(index: usize) -> MemoryRecord {
         },
         supersedes: Vec::new(),
         contradicts: Vec::new(),
+        tags: Vec::new(),
         payload,
     }
 }
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::fmt::{Display, Formatter};
 
@@ -303,6 +304,10 @@ pub struct MemoryRecord {
     pub supersedes: Vec<MemoryVersionId>,
     #[serde(default)]
     pub contradicts: Vec<MemoryVersionId>,
+    /// Free-form labels (for example `deploy` or `billing`) used to scope recall and
+    /// permissions semantically rather than by record type alone.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
     pub payload: MemoryPayload,
 }
 
@@ -374,10 +379,48 @@ impl MemoryRecord {
             }
         }
 
+        let mut seen_tags = std::collections::BTreeSet::new();
+        for tag in &self.tags {
+            validate_memory_tag(tag)?;
+            if !seen_tags.insert(tag.as_str()) {
+                return Err(KernelError::Validation(format!(
+                    "tag `{tag}` is listed more than once"
+                )));
+            }
+        }
+
         Ok(())
     }
 }
 
+/// Check that `tag` is 1-64 characters of lowercase ASCII letters, digits, `-`, `_`, `.`,
+/// `:` or `/`, so tags compare byte-for-byte across writers.
+///
+/// # Errors
+/// Returns [`KernelError::Validation`] when the tag is empty, too long, or uses other characters.
+pub fn validate_memory_tag(tag: &str) -> Result<(), KernelError> {
+    if tag.is_empty() || tag.len() > 64 {
+        return Err(KernelError::Validation(format!("tag `{tag}` MUST be 1-64 characters long")));
+    }
+    if !tag
+        .bytes()
+        .all(|byte| byte.is_ascii_lowercase() || byte.is_ascii_digit() || b"-_.:/".contains(&byte))
+    {
+        return Err(KernelError::Validation(format!(
+            "tag `{tag}` MUST use only lowercase ASCII letters, digits, '-', '_', '.', ':' or '/'"
+        )));
+    }
+    Ok(())
+}
+
+/// Sort and de-duplicate `tags` into the order stores return them in.
+#[must_use]
+pub fn normalize_memory_tags(mut tags: Vec<String>) -> Vec<String> {
+    tags.sort_unstable();
+    tags.dedup();
+    tags
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub struct QueryRequest {
     pub text: String,
@@ -421,6 +464,8 @@ pub struct ContextItem {
     pub truth_status: TruthStatus,
     pub confidence: Option<f32>,
     pub authority: Authority,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
     pub why: Why,
 }
 
@@ -571,6 +616,7 @@ fn excluded_item(record: &MemoryRecord, reason: &str) -> ContextItem {
         truth_status: record.truth_status,
         confidence: record.confidence,
         authority: record.authority,
+        tags: record.tags.clone(),
         why: Why { included: false, reasons: vec![reason.to_string()], rule_scores: None },
     }
 }
@@ -622,6 +668,7 @@ fn selected_policy_item(index: usize, candidate: &PolicyCandidate<'_>) -> Contex
         truth_status: candidate.record.truth_status,
         confidence: candidate.record.confidence,
         authority: candidate.record.authority,
+        tags: candidate.record.tags.clone(),
         why: Why {
             included: true,
             reasons: vec![
@@ -699,9 +746,14 @@ fn record_terms(record: &MemoryRecord) -> std::collections::BTreeSet<String> {
     terms
 }
 
+struct RecallScope<'a> {
+    record_types: &'a std::collections::BTreeSet<RecordType>,
+    record_tags: &'a std::collections::BTreeSet<&'a str>,
+}
+
 fn collect_recall_candidates_and_exclusions<'a>(
     records: &'a [MemoryRecord],
-    allowed_types: &std::collections::BTreeSet<RecordType>,
+    scope: &RecallScope<'_>,
     query_terms: &[String],
     superseded_ids: &std::collections::BTreeSet<MemoryVersionId>,
 ) -> (Vec<RecallCandidate<'a>>, Vec<ContextItem>) {
@@ -710,7 +762,13 @@ fn collect_recall_candidates_and_exclusions<'a>(
 
     for record in records {
         let record_type = record.payload.record_type();
-        if !allowed_types.contains(&record_type) {
+        if !scope.record_types.contains(&record_type) {
+            continue;
+        }
+
+        if !scope.record_tags.is_empty()
+            && !record.tags.iter().any(|tag| scope.record_tags.contains(tag.as_str()))
+        {
             continue;
         }
 
@@ -755,6 +813,7 @@ fn selected_recall_item(index: usize, candidate: &RecallCandidate<'_>) -> Contex
         truth_status: candidate.record.truth_status,
         confidence: candidate.record.confidence,
         authority: candidate.record.authority,
+        tags: candidate.record.tags.clone(),
         why: Why {
             included: true,
             reasons: vec![
@@ -861,6 +920,22 @@ pub fn build_recall_context_package(
     query: QueryRequest,
     snapshot_id: &str,
     record_types: &[RecordType],
+) -> Result<ContextPackage, KernelError> {
+    build_recall_context_package_with_tags(records, query, snapshot_id, record_types, &[])
+}
+
+/// Build a recall Context Package restricted to records carrying at least one of
+/// `record_tags`; an empty tag list leaves the scope to `record_types` alone.
+///
+/// # Errors
+/// Returns [`KernelError::Query`] when snapshot metadata, query text, or a tag is invalid,
+/// or [`KernelError::Validation`] when any source record violates domain invariants.
+pub fn build_recall_context_package_with_tags(
+    records: &[MemoryRecord],
+    query: QueryRequest,
+    snapshot_id: &str,
+    record_types: &[RecordType],
+    record_tags: &[String],
 ) -> Result<ContextPackage, KernelError> {
     use std::collections::BTreeSet;
 
@@ -884,6 +959,11 @@ pub fn build_recall_context_package(
         record_types.iter().copied().collect::<BTreeSet<_>>()
     };
 
+    for tag in record_tags {
+        validate_memory_tag(tag).map_err(|err| KernelError::Query(err.to_string()))?;
+    }
+    let allowed_tags = record_tags.iter().map(String::as_str).collect::<BTreeSet<_>>();
+
     let query_terms = tokenize_query_terms(&query.text);
     if query_terms.is_empty() {
         return Err(KernelError::Query(
@@ -894,7 +974,7 @@ pub fn build_recall_context_package(
     let superseded_ids = collect_superseded_ids(records);
     let (mut candidates, mut excluded) = collect_recall_candidates_and_exclusions(
         records,
-        &allowed_types,
+        &RecallScope { record_types: &allowed_types, record_tags: &allowed_tags },
         &query_terms,
         &superseded_ids,
     );
@@ -911,6 +991,17 @@ pub fn build_recall_context_package(
     selected_types.sort_unstable();
     let selected_types = selected_types.join(", ");
 
+    let mut ordering_trace = vec![format!("filter: record_type in [{selected_types}]")];
+    if !allowed_tags.is_empty() {
+        let tags = allowed_tags.iter().copied().collect::<Vec<_>>().join(", ");
+        ordering_trace.push(format!("filter: tags intersect [{tags}]"));
+    }
+    ordering_trace.extend([
+        "filter: lexical overlap with normalized query terms".to_string(),
+        "exclude: retracted and superseded".to_string(),
+        "sort: recall precedence tuple with deterministic tie-breakers".to_string(),
+    ]);
+
     Ok(ContextPackage {
         context_package_id,
         generated_at: query.as_of,
@@ -930,12 +1021,7 @@ pub fn build_recall_context_package(
         },
         selected_items: selected,
         excluded_items: excluded,
-        ordering_trace: vec![
-            format!("filter: record_type in [{selected_types}]"),
-            "filter: lexical overlap with normalized query terms".to_string(),
-            "exclude: retracted and superseded".to_string(),
-            "sort: recall precedence tuple with deterministic tie-breakers".to_string(),
-        ],
+        ordering_trace,
     })
 }
 
@@ -1015,6 +1101,287 @@ fn constraint_effect_by_version_id(
     })
 }
 
+/// How a reason code bears on the item it is attached to.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ReasonSeverity {
+    /// The item was kept for an expected reason.
+    Info,
+    /// The item was kept, but under a condition an operator should know about.
+    Warning,
+    /// The item was withheld from the context.
+    Exclusion,
+}
+
+impl ReasonSeverity {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Exclusion => "exclusion",
+        }
+    }
+}
+
+/// One entry of the reason-code taxonomy. A `code` ending in `*` names a family of codes that
+/// share the prefix (for example `ruleset.pinned.v*`).
+#[derive(Debug, Clone, Serialize, Eq, PartialEq)]
+pub struct ReasonCodeSpec {
+    pub code: Cow<'static, str>,
+    pub category: Cow<'static, str>,
+    pub severity: ReasonSeverity,
+    pub description: Cow<'static, str>,
+    pub builtin: bool,
+}
+
+impl ReasonCodeSpec {
+    const fn builtin(
+        code: &'static str,
+        category: &'static str,
+        severity: ReasonSeverity,
+        description: &'static str,
+    ) -> Self {
+        Self {
+            code: Cow::Borrowed(code),
+            category: Cow::Borrowed(category),
+            severity,
+            description: Cow::Borrowed(description),
+            builtin: true,
+        }
+    }
+
+    /// A reason code registered at runtime, e.g. by a custom trust gate.
+    #[must_use]
+    pub fn custom(
+        code: impl Into<String>,
+        category: impl Into<String>,
+        severity: ReasonSeverity,
+        description: impl Into<String>,
+    ) -> Self {
+        Self {
+            code: Cow::Owned(code.into()),
+            category: Cow::Owned(category.into()),
+            severity,
+            description: Cow::Owned(description.into()),
+            builtin: false,
+        }
+    }
+
+    #[must_use]
+    pub fn matches(&self, code: &str) -> bool {
+        match self.code.strip_suffix('*') {
+            Some(prefix) => code.len() > prefix.len() && code.starts_with(prefix),
+            None => self.code == code,
+        }
+    }
+}
+
+pub const REASON_INCLUDED_SAFE_VALIDATED_THRESHOLD: &str = "included.safe.validated_threshold";
+pub const REASON_EXCLUDED_SAFE_THRESHOLD_OR_STATUS: &str = "excluded.safe.threshold_or_status";
+pub const REASON_INCLUDED_EXPLORATION_SAFE_EQUIVALENT: &str =
+    "included.exploration.safe_equivalent";
+pub const REASON_INCLUDED_EXPLORATION_ACTIVE_THRESHOLD: &str =
+    "included.exploration.active_threshold";
+pub const REASON_INCLUDED_EXPLORATION_PROBE_BUCKET: &str = "included.exploration.probe_bucket";
+pub const REASON_EXCLUDED_EXPLORATION_PROBE_BUCKET: &str = "excluded.exploration.probe_bucket";
+pub const REASON_EXCLUDED_EXPLORATION_THRESHOLD_OR_STATUS: &str =
+    "excluded.exploration.threshold_or_status";
+pub const REASON_EXCLUDED_RETIRED: &str = "excluded.retired";
+pub const REASON_EXCLUDED_SAFE_RETIRE_GRACE: &str = "excluded.safe.retire_grace";
+pub const REASON_INCLUDED_EXPLORATION_RETIRE_GRACE: &str = "included.exploration.retire_grace";
+pub const REASON_EXCLUDED_NO_TRUST_SNAPSHOT: &str = "excluded.no_trust_snapshot";
+pub const REASON_EXCLUDED_SUPERSEDED_BY_NEWER_VERSION: &str =
+    "excluded.superseded_by_newer_version";
+pub const REASON_INCLUDED_NO_TRUST_GATING_CONFIGURED: &str = "included.no_trust_gating_configured";
+/// Prefix of `ruleset.pinned.v<N>`, appended when a context is pinned to ruleset `N`.
+pub const REASON_RULESET_PINNED_PREFIX: &str = "ruleset.pinned.v";
+pub const REASON_MAX_CONTEXT_ITEMS_EXCEEDED: &str = "max_context_items_exceeded";
+pub const REASON_EXCLUDED_RECORD_TYPE_NOT_ALLOWED: &str = "excluded_record_type_not_allowed";
+pub const REASON_RECORD_TAG_NOT_ALLOWED: &str = "record_tag_not_allowed";
+pub const REASON_EXCLUDED_RECORD_TAG_NOT_ALLOWED: &str = "excluded_record_tag_not_allowed";
+
+/// Every reason code emitted by the trust gate, ruleset pinning, and agent permissions.
+pub const BUILTIN_REASON_CODES: &[ReasonCodeSpec] = &[
+    ReasonCodeSpec::builtin(
+        REASON_INCLUDED_SAFE_VALIDATED_THRESHOLD,
+        "trust_gate",
+        ReasonSeverity::Info,
+        "Safe mode: the memory is validated and its effective confidence meets the safe threshold.",
+    ),
+    ReasonCodeSpec::builtin(
+        REASON_EXCLUDED_SAFE_THRESHOLD_OR_STATUS,
+        "trust_gate",
+        ReasonSeverity::Exclusion,
+        "Safe mode: the memory is not validated or its effective confidence is below the safe threshold.",
+    ),
+    ReasonCodeSpec::builtin(
+        REASON_INCLUDED_EXPLORATION_SAFE_EQUIVALENT,
+        "trust_gate",
+        ReasonSeverity::Info,
+        "Exploration mode: the memory would also pass the safe-mode gate.",
+    ),
+    ReasonCodeSpec::builtin(
+        REASON_INCLUDED_EXPLORATION_ACTIVE_THRESHOLD,
+        "trust_gate",
+        ReasonSeverity::Warning,
+        "Exploration mode: the memory is active (not validated) and meets the exploration threshold.",
+    ),
+    ReasonCodeSpec::builtin(
+        REASON_INCLUDED_EXPLORATION_PROBE_BUCKET,
+        "trust_gate",
+        ReasonSeverity::Warning,
+        "Exploration mode: a below-threshold memory was admitted by the deterministic probe bucket for this context.",
+    ),
+    ReasonCodeSpec::builtin(
+        REASON_EXCLUDED_EXPLORATION_PROBE_BUCKET,
+        "trust_gate",
+        ReasonSeverity::Exclusion,
+        "Exploration mode: a probe-eligible memory fell outside the probe bucket for this context.",
+    ),
+    ReasonCodeSpec::builtin(
+        REASON_EXCLUDED_EXPLORATION_THRESHOLD_OR_STATUS,
+        "trust_gate",
+        ReasonSeverity::Exclusion,
+        "Exploration mode: the memory's status or effective confidence is below every exploration threshold.",
+    ),
+    ReasonCodeSpec::builtin(
+        REASON_EXCLUDED_RETIRED,
+        "trust_gate",
+        ReasonSeverity::Exclusion,
+        "The memory is retired and is never gated in.",
+    ),
+    ReasonCodeSpec::builtin(
+        REASON_EXCLUDED_SAFE_RETIRE_GRACE,
+        "trust_gate",
+        ReasonSeverity::Exclusion,
+        "Safe mode: the memory was retired and is within the ruleset's retire grace period.",
+    ),
+    ReasonCodeSpec::builtin(
+        REASON_INCLUDED_EXPLORATION_RETIRE_GRACE,
+        "trust_gate",
+        ReasonSeverity::Warning,
+        "Exploration mode: the memory was retired but is still admitted during the ruleset's retire grace period; enough successes in that period reinstate it.",
+    ),
+    ReasonCodeSpec::builtin(
+        REASON_EXCLUDED_NO_TRUST_SNAPSHOT,
+        "trust_gate",
+        ReasonSeverity::Exclusion,
+        "No trust snapshot exists for the memory version; log an outcome or run the projector first.",
+    ),
+    ReasonCodeSpec::builtin(
+        REASON_EXCLUDED_SUPERSEDED_BY_NEWER_VERSION,
+        "trust_gate",
+        ReasonSeverity::Exclusion,
+        "Supersession-aware gating: a newer version of the same memory passes the gate, so this older version is left out.",
+    ),
+    ReasonCodeSpec::builtin(
+        REASON_INCLUDED_NO_TRUST_GATING_CONFIGURED,
+        "trust_gate",
+        ReasonSeverity::Warning,
+        "No trust gate source is configured, so every item is included unchecked.",
+    ),
+    ReasonCodeSpec::builtin(
+        "ruleset.pinned.v*",
+        "ruleset",
+        ReasonSeverity::Info,
+        "The gating context is pinned to ruleset version N, which was used instead of the memory's latest ruleset.",
+    ),
+    ReasonCodeSpec::builtin(
+        REASON_MAX_CONTEXT_ITEMS_EXCEEDED,
+        "permissions",
+        ReasonSeverity::Exclusion,
+        "The agent's max_context_items limit was reached before this item.",
+    ),
+    ReasonCodeSpec::builtin(
+        REASON_EXCLUDED_RECORD_TYPE_NOT_ALLOWED,
+        "permissions",
+        ReasonSeverity::Exclusion,
+        "The item's record type is not in the agent's allowed_record_types.",
+    ),
+    ReasonCodeSpec::builtin(
+        REASON_RECORD_TAG_NOT_ALLOWED,
+        "permissions",
+        ReasonSeverity::Exclusion,
+        "The item carries a tag in the agent's denied_record_tags, or none of its allowed_record_tags.",
+    ),
+    ReasonCodeSpec::builtin(
+        REASON_EXCLUDED_RECORD_TAG_NOT_ALLOWED,
+        "permissions",
+        ReasonSeverity::Exclusion,
+        "An already-excluded item was withheld because its tags fall outside the agent's tag permissions.",
+    ),
+];
+
+/// The reason-code taxonomy: the built-in catalogue plus codes registered at runtime.
+#[derive(Debug, Clone)]
+pub struct ReasonCodeRegistry {
+    entries: Vec<ReasonCodeSpec>,
+}
+
+impl Default for ReasonCodeRegistry {
+    fn default() -> Self {
+        Self { entries: BUILTIN_REASON_CODES.to_vec() }
+    }
+}
+
+impl ReasonCodeRegistry {
+    /// Register a custom code or family.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::Validation`] when the code is blank, contains whitespace or an
+    /// inner `*`, or is already registered.
+    pub fn register(&mut self, spec: ReasonCodeSpec) -> Result<(), KernelError> {
+        let body = spec.code.strip_suffix('*').unwrap_or(&spec.code);
+        if body.is_empty() || body.contains('*') || body.chars().any(char::is_whitespace) {
+            return Err(KernelError::Validation(format!("invalid reason code `{}`", spec.code)));
+        }
+        if self.entries.iter().any(|entry| entry.code == spec.code) {
+            return Err(KernelError::Validation(format!(
+                "reason code `{}` is already registered",
+                spec.code
+            )));
+        }
+        self.entries.push(spec);
+        Ok(())
+    }
+
+    /// The entry describing `code`: an exact entry wins, then the longest matching family.
+    #[must_use]
+    pub fn lookup(&self, code: &str) -> Option<&ReasonCodeSpec> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.matches(code))
+            .max_by_key(|entry| (!entry.code.ends_with('*'), entry.code.len()))
+    }
+
+    #[must_use]
+    pub fn entries(&self) -> &[ReasonCodeSpec] {
+        &self.entries
+    }
+
+    /// Check that every code in `codes` is registered.
+    ///
+    /// # Errors
+    /// Returns [`KernelError::Validation`] listing the unregistered codes.
+    pub fn validate<'c>(
+        &self,
+        codes: impl IntoIterator<Item = &'c str>,
+    ) -> Result<(), KernelError> {
+        let mut unregistered: Vec<&str> =
+            codes.into_iter().filter(|code| self.lookup(code).is_none()).collect();
+        if unregistered.is_empty() {
+            return Ok(());
+        }
+        unregistered.sort_unstable();
+        unregistered.dedup();
+        Err(KernelError::Validation(format!(
+            "unregistered reason code(s): {}",
+            unregistered.join(", ")
+        )))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use proptest::prelude::*;
@@ -1092,6 +1459,7 @@ mod tests {
             },
             supersedes,
             contradicts: vec![],
+            tags: vec![],
             payload: MemoryPayload::Constraint(ConstraintPayload {
                 scope: ConstraintScope {
                     actor: scope_actor.to_string(),
@@ -1149,6 +1517,7 @@ mod tests {
             },
             supersedes,
             contradicts: vec![],
+            tags: vec![],
             payload,
         }
     }
@@ -1843,6 +2212,104 @@ mod tests {
         assert_eq!(package.answer.result, AnswerResult::Inconclusive);
     }
 
+    #[test]
+    fn recall_tags_scope_selection_and_are_carried_into_context_items() {
+        let mut deploy = mk_summary(
+            fixture_id("01K1D3A7E9J5MNNN8F5JVCJY51"),
+            RecordType::Decision,
+            Authority::Authoritative,
+            TruthStatus::Observed,
+            Some(0.8),
+            "Decision: freeze usb rollout during deploy",
+            vec![],
+        );
+        deploy.tags = vec!["billing".to_string(), "deploy".to_string()];
+        let mut untagged = mk_summary(
+            fixture_id("01K1D3A7E9J5MNNN8F5JVCJY52"),
+            RecordType::Decision,
+            Authority::Authoritative,
+            TruthStatus::Observed,
+            Some(0.9),
+            "Decision: usb rollout approved",
+            vec![],
+        );
+        let query = QueryRequest {
+            text: "usb rollout".to_string(),
+            actor: "*".to_string(),
+            action: "*".to_string(),
+            resource: "*".to_string(),
+            as_of: fixture_time(),
+        };
+
+        let package = match build_recall_context_package_with_tags(
+            &[deploy.clone(), untagged.clone()],
+            query.clone(),
+            "txn_recall_tags",
+            &[],
+            &["deploy".to_string()],
+        ) {
+            Ok(package) => package,
+            Err(err) => panic!("tagged recall context package should build: {err}"),
+        };
+        assert_eq!(package.selected_items.len(), 1);
+        assert_eq!(package.selected_items[0].memory_id, deploy.memory_id);
+        assert_eq!(package.selected_items[0].tags, deploy.tags);
+        assert!(package.ordering_trace.contains(&"filter: tags intersect [deploy]".to_string()));
+
+        untagged.tags = vec!["Deploy".to_string()];
+        assert!(untagged.validate().is_err());
+        untagged.tags = vec!["deploy".to_string(), "deploy".to_string()];
+        assert!(untagged.validate().is_err());
+        assert!(build_recall_context_package_with_tags(
+            &[deploy],
+            query,
+            "txn_recall_tags",
+            &[],
+            &["bad tag".to_string()],
+        )
+        .is_err());
+    }
+
+    // Test IDs: TRES-007
+    #[test]
+    fn reason_code_registry_resolves_families_and_rejects_unregistered_codes() {
+        let mut registry = ReasonCodeRegistry::default();
+        let retired = registry.lookup(REASON_EXCLUDED_RETIRED);
+        assert_eq!(retired.map(|entry| entry.severity), Some(ReasonSeverity::Exclusion));
+        let pinned = registry.lookup("ruleset.pinned.v12");
+        assert_eq!(pinned.map(|entry| entry.code.as_ref()), Some("ruleset.pinned.v*"));
+        assert!(registry.lookup(REASON_RULESET_PINNED_PREFIX).is_none());
+
+        assert!(registry.validate([REASON_EXCLUDED_RETIRED, "ruleset.pinned.v2"]).is_ok());
+        assert_eq!(
+            registry.validate([
+                "custom.gate.blocked",
+                REASON_EXCLUDED_RETIRED,
+                "custom.gate.blocked"
+            ]),
+            Err(KernelError::Validation(
+                "unregistered reason code(s): custom.gate.blocked".to_string()
+            ))
+        );
+
+        let custom = ReasonCodeSpec::custom(
+            "custom.gate.*",
+            "custom_gate",
+            ReasonSeverity::Exclusion,
+            "Blocked by the custom gate.",
+        );
+        assert!(registry.register(custom.clone()).is_ok());
+        assert!(registry.register(custom).is_err());
+        assert!(registry
+            .register(ReasonCodeSpec::custom("bad code", "x", ReasonSeverity::Info, "x"))
+            .is_err());
+        assert!(registry
+            .register(ReasonCodeSpec::custom("*", "x", ReasonSeverity::Info, "x"))
+            .is_err());
+        assert!(registry.validate(["custom.gate.blocked"]).is_ok());
+        assert!(registry.lookup("custom.gate.blocked").is_some_and(|entry| !entry.builtin));
+    }
+
     // Test IDs: TPERF-001
     #[test]
     fn policy_context_package_meets_baseline_budget() {
@@ -1,24 +1,40 @@
 use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::path::{Path as FsPath, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Result;
+use axum::body::Bytes;
 use axum::extract::rejection::JsonRejection;
-use axum::extract::{Path, State};
-use axum::http::StatusCode;
+use axum::extract::{MatchedPath, Path, Query, Request, State};
+use axum::http::{header, HeaderMap, Method, StatusCode};
+use axum::middleware::{self, Next};
 use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
-use axum::{Json, Router};
+use axum::{Extension, Json, Router};
 use clap::Parser;
 use memory_kernel_api::{
     AddConstraintRequest, AddLinkRequest, AddSummaryRequest, AskRequest, MemoryKernelApi,
     RecallRequest, API_CONTRACT_VERSION,
 };
+use memory_kernel_outcome_store_sqlite::{
+    SqliteOutcomeStore, SyncApplyReport, SyncBatch, SyncPullRequest, SYNC_PROTOCOL_VERSION,
+};
+use memory_kernel_store_sqlite::{AdminAuditEntry, ApiRole, ApiToken};
+use multi_agent_center_domain::{
+    verify_gate_signature, DeciderAuthMethod, DeferredGateRecord, DeferredGateResolution, RunId,
+    RunProgress, GATE_SIGNATURE_HEADER,
+};
+use multi_agent_center_trace_core::TraceStore;
+use multi_agent_center_trace_sqlite::SqliteTraceStore;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
+mod rate_limit;
+
+use rate_limit::{Admission, Exceeded, RateLimitConfig, RateLimiter};
+
 const SERVICE_CONTRACT_VERSION: &str = "service.v3";
 const OPENAPI_YAML: &str = include_str!("../../../openapi/openapi.yaml");
 
@@ -27,6 +43,17 @@ struct ServiceState {
     api: MemoryKernelApi,
     operation_timeout: Duration,
     telemetry: Arc<ServiceTelemetry>,
+    health: HealthConfig,
+    require_auth: bool,
+    limits: Arc<RateLimiter>,
+    require_signed_sync: bool,
+    sso_email_header: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct HealthConfig {
+    trace_db: Option<PathBuf>,
+    min_free_disk_bytes: u64,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -61,6 +88,42 @@ struct ServiceFailure {
     details: Option<serde_json::Value>,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+struct AuditLogQuery {
+    limit: Option<usize>,
+}
+
+/// Decision on a deferred human gate; the decider is the authenticated caller.
+#[derive(Debug, Clone, Deserialize)]
+struct ResolveGateRequest {
+    step_key: String,
+    gate: String,
+    approve: bool,
+    notes: Option<String>,
+    /// Approver the caller decides for under an approval delegation.
+    on_behalf_of: Option<String>,
+}
+
+/// Decision a gate webhook receiver posts back; it is authenticated by its signature under the
+/// gate's webhook secret rather than by a bearer token.
+#[derive(Debug, Clone, Deserialize)]
+struct GateCallbackRequest {
+    /// Must match the path, so a signed body cannot be replayed against another run.
+    run_id: String,
+    step_key: String,
+    gate: String,
+    approve: bool,
+    /// Identity of the person who decided, as the webhook receiver established it.
+    decided_by: String,
+    notes: Option<String>,
+    on_behalf_of: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct AuditLogResponse {
+    entries: Vec<AdminAuditEntry>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 struct MigrateRequest {
     dry_run: bool,
@@ -119,6 +182,23 @@ struct ReadinessResponse {
     checks: ReadinessChecks,
 }
 
+#[derive(Debug, Clone, Serialize)]
+struct SubsystemCheck {
+    name: &'static str,
+    status: &'static str,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SubsystemHealthResponse {
+    status: &'static str,
+    liveness: &'static str,
+    readiness: &'static str,
+    checks: Vec<SubsystemCheck>,
+}
+
 #[derive(Debug, Parser)]
 #[command(name = "memory-kernel-service")]
 #[command(about = "Local HTTP service for Memory Kernel")]
@@ -129,6 +209,28 @@ struct Args {
     bind: SocketAddr,
     #[arg(long, default_value_t = 2500)]
     operation_timeout_ms: u64,
+    /// Optional multi-agent-center trace database to include in `/health`.
+    #[arg(long)]
+    trace_db: Option<PathBuf>,
+    /// Minimum free disk space next to the database before `/health` reports not ready.
+    #[arg(long, default_value_t = 256)]
+    min_free_disk_mb: u64,
+    /// Require a bearer API token (see `mk auth token create`) on every non-probe endpoint.
+    #[arg(long)]
+    require_auth: bool,
+    /// Requests per caller (API token, or the shared anonymous caller) per minute; `0` disables.
+    #[arg(long, default_value_t = 0)]
+    rate_limit_per_minute: u32,
+    /// Expensive requests (query execution) per caller per UTC day; `0` disables.
+    #[arg(long, default_value_t = 0)]
+    daily_quota: u32,
+    /// Reject outcome sync pushes that are not signed by a registered writer key.
+    #[arg(long)]
+    require_signed_sync: bool,
+    /// Header in which a trusted SSO proxy passes the signed-in user's email; gate resolutions
+    /// carrying it are attributed to that email.
+    #[arg(long)]
+    sso_email_header: Option<String>,
 }
 
 impl IntoResponse for ServiceFailure {
@@ -330,8 +432,14 @@ where
     }
 }
 
-fn app(state: ServiceState) -> Router {
+fn app(mut state: ServiceState) -> Router {
+    // With auth on, `authorize` audits every call under its token, so the API's own entry for
+    // the same mutation would be a duplicate.
+    if state.require_auth {
+        state.api = state.api.clone().without_admin_audit();
+    }
     Router::new()
+        .route("/health", get(subsystem_health))
         .route("/v1/health", get(health))
         .route("/v1/ready", get(ready))
         .route("/v1/openapi", get(openapi))
@@ -343,9 +451,224 @@ fn app(state: ServiceState) -> Router {
         .route("/v1/query/ask", post(query_ask))
         .route("/v1/query/recall", post(query_recall))
         .route("/v1/context/:context_package_id", get(context_show))
+        .route("/v1/admin/audit-log", get(admin_audit_log))
+        .route("/v1/trace/runs/:run_id/progress", get(trace_run_progress))
+        .route("/v1/trace/runs/:run_id/gates/resolve", post(trace_resolve_gate))
+        .route("/v1/trace/runs/:run_id/gates/callback", post(trace_gate_callback))
+        .route("/v1/outcome/sync/pull", post(outcome_sync_pull))
+        .route("/v1/outcome/sync/push", post(outcome_sync_push))
+        .route_layer(middleware::from_fn_with_state(state.clone(), enforce_rate_limits))
+        .route_layer(middleware::from_fn_with_state(state.clone(), authorize))
         .with_state(state)
 }
 
+/// Minimum role for each endpoint; `None` marks unauthenticated probe endpoints and the gate
+/// callback, which authenticates by signature.
+fn required_role(method: &Method, route: &str) -> Option<ApiRole> {
+    match (method.as_str(), route) {
+        ("GET", "/health" | "/v1/health" | "/v1/ready")
+        | ("POST", "/v1/trace/runs/:run_id/gates/callback") => None,
+        (
+            "POST",
+            "/v1/memory/add/constraint"
+            | "/v1/memory/add/summary"
+            | "/v1/memory/link"
+            | "/v1/outcome/sync/push"
+            | "/v1/trace/runs/:run_id/gates/resolve",
+        ) => Some(ApiRole::Writer),
+        ("POST", "/v1/db/migrate") => Some(ApiRole::Operator),
+        ("GET", "/v1/admin/audit-log") => Some(ApiRole::Admin),
+        _ => Some(ApiRole::Reader),
+    }
+}
+
+fn bearer_secret(request: &Request) -> Option<String> {
+    request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|secret| secret.trim().to_string())
+}
+
+async fn authorize(
+    State(state): State<ServiceState>,
+    matched: MatchedPath,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(required) = required_role(request.method(), matched.as_str()) else {
+        return next.run(request).await;
+    };
+    if !state.require_auth {
+        return next.run(request).await;
+    }
+
+    let Some(secret) = bearer_secret(&request) else {
+        state.telemetry.record_failure("unauthorized", false);
+        return ServiceState::failure(
+            StatusCode::UNAUTHORIZED,
+            "unauthorized",
+            "missing bearer API token",
+            None,
+        )
+        .into_response();
+    };
+    let api = state.api.clone();
+    let lookup = tokio::task::spawn_blocking(move || api.authenticate_token(&secret)).await;
+    let token = match lookup {
+        Ok(Ok(Some(token))) => token,
+        Ok(Ok(None)) => {
+            state.telemetry.record_failure("unauthorized", false);
+            return ServiceState::failure(
+                StatusCode::UNAUTHORIZED,
+                "unauthorized",
+                "unknown or revoked API token",
+                None,
+            )
+            .into_response();
+        }
+        Ok(Err(err)) => {
+            state.telemetry.record_failure("internal_error", false);
+            return ServiceState::failure(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal_error",
+                format!("api token lookup failed: {err:#}"),
+                None,
+            )
+            .into_response();
+        }
+        Err(err) => {
+            state.telemetry.record_failure("internal_error", false);
+            return ServiceState::failure(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal_error",
+                format!("api token lookup join failure: {err}"),
+                None,
+            )
+            .into_response();
+        }
+    };
+
+    let command = format!("{} {}", request.method(), matched.as_str());
+    let args = vec![request.uri().to_string()];
+    let mut request = request;
+    request.extensions_mut().insert(token.clone());
+    let response = if token.role.grants(required) {
+        next.run(request).await
+    } else {
+        state.telemetry.record_failure("forbidden", false);
+        ServiceState::failure(
+            StatusCode::FORBIDDEN,
+            "forbidden",
+            format!(
+                "role `{}` cannot call {command}; requires `{}`",
+                token.role.as_str(),
+                required.as_str()
+            ),
+            None,
+        )
+        .into_response()
+    };
+
+    record_authenticated_call(&state, &token, &command, args, response.status()).await;
+    response
+}
+
+/// Endpoints that count against the daily quota because they build context packages.
+fn is_expensive(method: &Method, route: &str) -> bool {
+    method == Method::POST && matches!(route, "/v1/query/ask" | "/v1/query/recall")
+}
+
+async fn enforce_rate_limits(
+    State(state): State<ServiceState>,
+    matched: MatchedPath,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !state.limits.is_enabled() || required_role(request.method(), matched.as_str()).is_none() {
+        return next.run(request).await;
+    }
+
+    let caller = request
+        .extensions()
+        .get::<ApiToken>()
+        .map_or_else(|| "anonymous".to_string(), |token| format!("token:{}", token.token_id));
+    let expensive = is_expensive(request.method(), matched.as_str());
+    let admission = state.limits.admit(
+        &caller,
+        expensive,
+        std::time::Instant::now(),
+        time::OffsetDateTime::now_utc(),
+    );
+
+    let mut response = match admission.exceeded {
+        None => next.run(request).await,
+        Some(exceeded) => {
+            state.telemetry.record_failure("rate_limited", false);
+            let message = match exceeded {
+                Exceeded::RateLimit => "per-minute rate limit exceeded",
+                Exceeded::DailyQuota => "daily quota for expensive endpoints exceeded",
+            };
+            ServiceState::failure(
+                StatusCode::TOO_MANY_REQUESTS,
+                "rate_limited",
+                message,
+                Some(json!({
+                    "caller": caller,
+                    "retry_after_secs": admission.retry_after_secs(),
+                })),
+            )
+            .into_response()
+        }
+    };
+    apply_limit_headers(&mut response, &admission);
+    response
+}
+
+fn apply_limit_headers(response: &mut Response, admission: &Admission) {
+    let headers = response.headers_mut();
+    let mut set = |name: &'static str, value: u64| {
+        headers.insert(name, header::HeaderValue::from(value));
+    };
+    if let Some(rate) = admission.rate {
+        set("x-ratelimit-limit", u64::from(rate.limit));
+        set("x-ratelimit-remaining", u64::from(rate.remaining));
+        set("x-ratelimit-reset", rate.reset_secs);
+    }
+    if let Some(quota) = admission.quota {
+        set("x-quota-limit", u64::from(quota.limit));
+        set("x-quota-remaining", u64::from(quota.remaining));
+        set("x-quota-reset", quota.reset_secs);
+    }
+    if let Some(retry_after) = admission.retry_after_secs() {
+        headers.insert(header::RETRY_AFTER, header::HeaderValue::from(retry_after));
+    }
+}
+
+async fn record_authenticated_call(
+    state: &ServiceState,
+    token: &ApiToken,
+    command: &str,
+    args: Vec<String>,
+    status: StatusCode,
+) {
+    let api = state.api.clone();
+    let command = command.to_string();
+    let writer = format!("token:{}", token.token_id);
+    let error = (!status.is_success()).then(|| format!("http status {}", status.as_u16()));
+    let recorded = tokio::task::spawn_blocking(move || {
+        let entry = AdminAuditEntry::new("api", &command, &args, Some(&writer), error)?;
+        api.record_admin_audit(&entry)
+    })
+    .await;
+    match recorded {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => eprintln!("warning: failed to record admin audit: {err:#}"),
+        Err(err) => eprintln!("warning: admin audit join failure: {err}"),
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
@@ -353,6 +676,17 @@ async fn main() -> Result<()> {
         api: MemoryKernelApi::new(args.db),
         operation_timeout: Duration::from_millis(args.operation_timeout_ms),
         telemetry: Arc::new(ServiceTelemetry::default()),
+        health: HealthConfig {
+            trace_db: args.trace_db,
+            min_free_disk_bytes: args.min_free_disk_mb.saturating_mul(1024 * 1024),
+        },
+        require_auth: args.require_auth,
+        limits: Arc::new(RateLimiter::new(RateLimitConfig {
+            per_minute: args.rate_limit_per_minute,
+            daily_quota: args.daily_quota,
+        })),
+        require_signed_sync: args.require_signed_sync,
+        sso_email_header: args.sso_email_header,
     };
     let listener = tokio::net::TcpListener::bind(args.bind).await?;
     axum::serve(listener, app(state)).await?;
@@ -407,6 +741,217 @@ async fn ready(
     ))
 }
 
+async fn subsystem_health(
+    State(state): State<ServiceState>,
+) -> Result<Json<ServiceEnvelope<SubsystemHealthResponse>>, ServiceFailure> {
+    let config = state.health.clone();
+    let checks = state
+        .run_blocking(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "schema_unavailable",
+            "subsystem_health",
+            move |api| Ok(run_subsystem_checks(&api, &config)),
+        )
+        .await?;
+
+    let failed = checks.iter().any(|check| check.status == "fail");
+    let warned = checks.iter().any(|check| check.status == "warn");
+    let response = SubsystemHealthResponse {
+        status: if failed {
+            "unhealthy"
+        } else if warned {
+            "degraded"
+        } else {
+            "healthy"
+        },
+        liveness: "alive",
+        readiness: if failed { "not_ready" } else { "ready" },
+        checks,
+    };
+
+    if !failed {
+        return Ok(Json(envelope(response)));
+    }
+
+    state.telemetry.record_failure("schema_unavailable", false);
+    let failing = response
+        .checks
+        .iter()
+        .filter(|check| check.status == "fail")
+        .map(|check| check.name)
+        .collect::<Vec<_>>();
+    Err(ServiceState::failure(
+        StatusCode::SERVICE_UNAVAILABLE,
+        "schema_unavailable",
+        format!("subsystem health checks failed: {}", failing.join(", ")),
+        Some(json!(response)),
+    ))
+}
+
+fn run_subsystem_checks(api: &MemoryKernelApi, config: &HealthConfig) -> Vec<SubsystemCheck> {
+    let mut checks = vec![check_memory_kernel_schema(api)];
+    checks.extend(check_outcome_subsystem(api.db_path()));
+    checks.push(check_trace_store(config.trace_db.as_deref()));
+    checks.push(check_disk_headroom(api.db_path(), config.min_free_disk_bytes));
+    checks
+}
+
+fn subsystem_check(
+    name: &'static str,
+    status: &'static str,
+    message: impl Into<String>,
+    details: Option<serde_json::Value>,
+) -> SubsystemCheck {
+    SubsystemCheck { name, status, message: message.into(), details }
+}
+
+fn check_memory_kernel_schema(api: &MemoryKernelApi) -> SubsystemCheck {
+    match api.schema_status() {
+        Ok(status) => {
+            let details = Some(json!({
+                "current_version": status.current_version,
+                "target_version": status.target_version,
+                "pending_versions": status.pending_versions,
+            }));
+            if status.pending_versions.is_empty() && status.current_version == status.target_version
+            {
+                subsystem_check("memory_kernel_schema", "pass", "schema is current", details)
+            } else {
+                subsystem_check(
+                    "memory_kernel_schema",
+                    "fail",
+                    "schema has pending migrations",
+                    details,
+                )
+            }
+        }
+        Err(err) => subsystem_check("memory_kernel_schema", "fail", format!("{err:#}"), None),
+    }
+}
+
+fn check_outcome_subsystem(db_path: &FsPath) -> Vec<SubsystemCheck> {
+    let store = match SqliteOutcomeStore::open(db_path) {
+        Ok(store) => store,
+        Err(err) => {
+            let message = format!("{err:#}");
+            return vec![
+                subsystem_check("memory_kernel_compatibility", "fail", message.clone(), None),
+                subsystem_check("outcome_projector", "fail", message, None),
+            ];
+        }
+    };
+
+    let compatibility = match store.check_memory_kernel_compatibility() {
+        Ok(()) => subsystem_check(
+            "memory_kernel_compatibility",
+            "pass",
+            "memory_records exposes (memory_id, version) identity and context columns",
+            None,
+        ),
+        Err(err) => {
+            subsystem_check("memory_kernel_compatibility", "fail", format!("{err:#}"), None)
+        }
+    };
+
+    let projector = match store.is_migrated() {
+        Ok(false) => subsystem_check(
+            "outcome_projector",
+            "skip",
+            "outcome schema is not migrated in this database",
+            None,
+        ),
+        Ok(true) => match store.projector_check() {
+            Ok(check) => {
+                let status = if !check.healthy {
+                    "fail"
+                } else if check.issues.is_empty() {
+                    "pass"
+                } else {
+                    "warn"
+                };
+                let message = if check.issues.is_empty() {
+                    "projection is current".to_string()
+                } else {
+                    check
+                        .issues
+                        .iter()
+                        .map(|issue| issue.message.clone())
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                };
+                subsystem_check(
+                    "outcome_projector",
+                    status,
+                    message,
+                    Some(json!({
+                        "lag_events": check.status.lag_events,
+                        "stale_trust_rows": check.status.stale_trust_rows,
+                        "latest_event_seq": check.status.latest_event_seq,
+                        "projected_event_seq": check.status.projected_event_seq,
+                    })),
+                )
+            }
+            Err(err) => subsystem_check("outcome_projector", "fail", format!("{err:#}"), None),
+        },
+        Err(err) => subsystem_check("outcome_projector", "fail", format!("{err:#}"), None),
+    };
+
+    vec![compatibility, projector]
+}
+
+fn check_trace_store(trace_db: Option<&FsPath>) -> SubsystemCheck {
+    let Some(path) = trace_db else {
+        return subsystem_check("trace_store", "skip", "no trace database configured", None);
+    };
+    let details = Some(json!({ "path": path.display().to_string() }));
+    if !path.exists() {
+        return subsystem_check("trace_store", "fail", "trace database does not exist", details);
+    }
+
+    match SqliteTraceStore::open(path).and_then(|store| store.integrity_ping()) {
+        Ok(issues) if issues.is_empty() => {
+            subsystem_check("trace_store", "pass", "integrity ping succeeded", details)
+        }
+        Ok(issues) => subsystem_check("trace_store", "fail", issues.join("; "), details),
+        Err(err) => subsystem_check("trace_store", "fail", format!("{err:#}"), details),
+    }
+}
+
+fn check_disk_headroom(db_path: &FsPath, min_free_bytes: u64) -> SubsystemCheck {
+    let dir = match db_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => FsPath::new("."),
+    };
+
+    match fs2::available_space(dir) {
+        Ok(available) => {
+            let details = Some(json!({
+                "path": dir.display().to_string(),
+                "available_bytes": available,
+                "min_free_bytes": min_free_bytes,
+            }));
+            if available < min_free_bytes {
+                subsystem_check("disk_headroom", "fail", "free disk space below minimum", details)
+            } else if available < min_free_bytes.saturating_mul(2) {
+                subsystem_check(
+                    "disk_headroom",
+                    "warn",
+                    "free disk space within 2x of minimum",
+                    details,
+                )
+            } else {
+                subsystem_check("disk_headroom", "pass", "free disk space above minimum", details)
+            }
+        }
+        Err(err) => subsystem_check(
+            "disk_headroom",
+            "fail",
+            format!("failed to read free disk space for {}: {err}", dir.display()),
+            None,
+        ),
+    }
+}
+
 async fn openapi() -> impl IntoResponse {
     (StatusCode::OK, [("content-type", "application/yaml; charset=utf-8")], OPENAPI_YAML)
 }
@@ -536,6 +1081,366 @@ async fn context_show(
     Ok(Json(envelope(package)))
 }
 
+async fn admin_audit_log(
+    State(state): State<ServiceState>,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<Json<ServiceEnvelope<AuditLogResponse>>, ServiceFailure> {
+    let entries = state
+        .run_blocking(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "internal_error",
+            "admin_audit_log",
+            move |api| api.admin_audit_log(query.limit),
+        )
+        .await?;
+    Ok(Json(envelope(AuditLogResponse { entries })))
+}
+
+/// Largest pull batch the service returns, whatever the client asks for.
+const MAX_SYNC_BATCH: usize = 1000;
+
+fn open_outcome_store(db_path: &FsPath) -> anyhow::Result<SqliteOutcomeStore> {
+    let store = SqliteOutcomeStore::open(db_path)?;
+    if !store.is_migrated()? {
+        return Err(anyhow::anyhow!("outcome schema is not migrated in this database"));
+    }
+    store.migrate()?;
+    Ok(store)
+}
+
+async fn outcome_sync_pull(
+    State(state): State<ServiceState>,
+    payload: Result<Json<SyncPullRequest>, JsonRejection>,
+) -> Result<Json<ServiceEnvelope<SyncBatch>>, ServiceFailure> {
+    let Json(request) =
+        payload.map_err(|rejection| state.invalid_json_with_telemetry(&rejection))?;
+    let batch = state
+        .run_blocking(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "sync_failed",
+            "outcome_sync_pull",
+            move |api| {
+                if request.protocol_version != SYNC_PROTOCOL_VERSION {
+                    return Err(anyhow::anyhow!(
+                        "sync request validation failed: unsupported protocol_version `{}`",
+                        request.protocol_version
+                    ));
+                }
+                open_outcome_store(api.db_path())?.export_sync_batch(
+                    "memory-kernel-service",
+                    &request.cursors,
+                    request.limit.clamp(1, MAX_SYNC_BATCH),
+                    None,
+                )
+            },
+        )
+        .await?;
+    Ok(Json(envelope(batch)))
+}
+
+async fn outcome_sync_push(
+    State(state): State<ServiceState>,
+    payload: Result<Json<SyncBatch>, JsonRejection>,
+) -> Result<Json<ServiceEnvelope<SyncApplyReport>>, ServiceFailure> {
+    let Json(batch) = payload.map_err(|rejection| state.invalid_json_with_telemetry(&rejection))?;
+    let require_signed = state.require_signed_sync;
+    let report = state
+        .run_blocking(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "sync_rejected",
+            "outcome_sync_push",
+            move |api| open_outcome_store(api.db_path())?.apply_sync_batch(&batch, require_signed),
+        )
+        .await?;
+    if !report.conflicts.is_empty() {
+        state.telemetry.record_failure("write_conflict", false);
+        return Err(ServiceState::failure(
+            StatusCode::CONFLICT,
+            "write_conflict",
+            format!(
+                "{} pushed event(s) conflict with stored events sharing their event_id",
+                report.conflicts.len()
+            ),
+            Some(json!(report)),
+        ));
+    }
+    Ok(Json(envelope(report)))
+}
+
+async fn trace_run_progress(
+    State(state): State<ServiceState>,
+    Path(run_id): Path<String>,
+) -> Result<Json<ServiceEnvelope<RunProgress>>, ServiceFailure> {
+    let Some(trace_db) = state.health.trace_db.clone() else {
+        state.telemetry.record_failure("schema_unavailable", false);
+        return Err(ServiceState::failure(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "schema_unavailable",
+            "trace store is not configured; start the service with --trace-db",
+            None,
+        ));
+    };
+    let Ok(run_ulid) = ulid::Ulid::from_string(&run_id) else {
+        state.telemetry.record_failure("validation_error", false);
+        return Err(ServiceState::failure(
+            StatusCode::BAD_REQUEST,
+            "validation_error",
+            format!("run_id must be a ULID: {run_id}"),
+            None,
+        ));
+    };
+    let progress = state
+        .run_blocking(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "query_failed",
+            "trace_run_progress",
+            move |_| SqliteTraceStore::open(&trace_db)?.get_run_progress(RunId(run_ulid)),
+        )
+        .await?;
+    let Some(progress) = progress else {
+        state.telemetry.record_failure("run_not_found", false);
+        return Err(ServiceState::failure(
+            StatusCode::NOT_FOUND,
+            "run_not_found",
+            format!("run not found: {run_id}"),
+            None,
+        ));
+    };
+    Ok(Json(envelope(progress)))
+}
+
+/// The identity deciding a gate: the SSO email from the trusted proxy header when present,
+/// otherwise the label of the authenticated API token.
+fn gate_decider(
+    state: &ServiceState,
+    token: Option<&ApiToken>,
+    headers: &HeaderMap,
+) -> Option<(String, DeciderAuthMethod)> {
+    let sso_email = state
+        .sso_email_header
+        .as_deref()
+        .and_then(|name| headers.get(name))
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|email| !email.is_empty());
+    if let Some(email) = sso_email {
+        return Some((email.to_string(), DeciderAuthMethod::Sso));
+    }
+    token.map(|token| (token.label.clone(), DeciderAuthMethod::Token))
+}
+
+async fn trace_resolve_gate(
+    State(state): State<ServiceState>,
+    Path(run_id): Path<String>,
+    token: Option<Extension<ApiToken>>,
+    headers: HeaderMap,
+    payload: Result<Json<ResolveGateRequest>, JsonRejection>,
+) -> Result<Json<ServiceEnvelope<DeferredGateRecord>>, ServiceFailure> {
+    let Json(request) =
+        payload.map_err(|rejection| state.invalid_json_with_telemetry(&rejection))?;
+    let Some(trace_db) = state.health.trace_db.clone() else {
+        state.telemetry.record_failure("schema_unavailable", false);
+        return Err(ServiceState::failure(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "schema_unavailable",
+            "trace store is not configured; start the service with --trace-db",
+            None,
+        ));
+    };
+    let Ok(run_ulid) = ulid::Ulid::from_string(&run_id) else {
+        state.telemetry.record_failure("validation_error", false);
+        return Err(ServiceState::failure(
+            StatusCode::BAD_REQUEST,
+            "validation_error",
+            format!("run_id must be a ULID: {run_id}"),
+            None,
+        ));
+    };
+    let Some((decided_by, auth_method)) =
+        gate_decider(&state, token.as_ref().map(|Extension(token)| token), &headers)
+    else {
+        state.telemetry.record_failure("unauthorized", false);
+        return Err(ServiceState::failure(
+            StatusCode::UNAUTHORIZED,
+            "unauthorized",
+            "gate decisions need a verified decider: an API token (--require-auth) or an SSO \
+             email header (--sso-email-header)",
+            None,
+        ));
+    };
+    let resolution = DeferredGateResolution {
+        approved: request.approve,
+        decided_by,
+        auth_method,
+        on_behalf_of: request.on_behalf_of,
+        notes: request.notes,
+        decided_at: time::OffsetDateTime::now_utc(),
+    };
+    let resolved = state
+        .run_blocking(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "query_failed",
+            "trace_resolve_gate",
+            move |_| {
+                let store = SqliteTraceStore::open(&trace_db)?;
+                Ok(store.resolve_deferred_gate(
+                    RunId(run_ulid),
+                    &request.step_key,
+                    &request.gate,
+                    &resolution,
+                ))
+            },
+        )
+        .await?;
+    resolved.map(|gate| Json(envelope(gate))).map_err(|err| gate_resolution_failure(&state, &err))
+}
+
+/// Resolve the gate `request` names once `signature` checks out under its webhook secret.
+fn resolve_signed_gate_callback(
+    trace_db: &FsPath,
+    run_id: RunId,
+    request: GateCallbackRequest,
+    body: &[u8],
+    signature: &str,
+) -> Result<DeferredGateRecord> {
+    let store = SqliteTraceStore::open(trace_db)?;
+    let webhook = store
+        .list_deferred_gates(Some(run_id))?
+        .into_iter()
+        .find(|gate| gate.step_key == request.step_key && gate.gate_name == request.gate)
+        .map(|gate| gate.webhook);
+    let webhook = match webhook {
+        None => {
+            return Err(anyhow::anyhow!(
+                "gate {} of step {} was not deferred in run {run_id}",
+                request.gate,
+                request.step_key
+            ))
+        }
+        Some(None) => {
+            return Err(anyhow::anyhow!(
+                "gate {} has no webhook, so callbacks cannot be signature verified; use \
+                 /gates/resolve",
+                request.gate
+            ))
+        }
+        Some(Some(webhook)) => webhook,
+    };
+    if !verify_gate_signature(&webhook.secret()?, body, signature) {
+        return Err(anyhow::anyhow!("gate callback signature does not match the webhook secret"));
+    }
+    store.resolve_deferred_gate(
+        run_id,
+        &request.step_key,
+        &request.gate,
+        &DeferredGateResolution {
+            approved: request.approve,
+            decided_by: request.decided_by,
+            auth_method: DeciderAuthMethod::Webhook,
+            on_behalf_of: request.on_behalf_of,
+            notes: request.notes,
+            decided_at: time::OffsetDateTime::now_utc(),
+        },
+    )
+}
+
+fn gate_resolution_failure(state: &ServiceState, err: &anyhow::Error) -> ServiceFailure {
+    let message = err.to_string();
+    let (status, code) = if message.contains("was not deferred") {
+        (StatusCode::NOT_FOUND, "gate_not_found")
+    } else if message.contains("already resolved") {
+        (StatusCode::CONFLICT, "write_conflict")
+    } else if message.contains("approver")
+        || message.contains("delegation")
+        || message.contains("has no webhook")
+    {
+        (StatusCode::FORBIDDEN, "forbidden")
+    } else if message.contains("signature") {
+        (StatusCode::UNAUTHORIZED, "unauthorized")
+    } else {
+        (StatusCode::INTERNAL_SERVER_ERROR, "query_failed")
+    };
+    state.telemetry.record_failure(code, false);
+    ServiceState::failure(status, code, message, None)
+}
+
+/// Resolve a deferred gate from its webhook receiver. The raw body must carry a valid
+/// `X-Gate-Signature` under the secret of the webhook the gate was deferred with; the decider
+/// is then recorded as verified by `webhook`.
+async fn trace_gate_callback(
+    State(state): State<ServiceState>,
+    Path(run_id): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<ServiceEnvelope<DeferredGateRecord>>, ServiceFailure> {
+    let Some(trace_db) = state.health.trace_db.clone() else {
+        state.telemetry.record_failure("schema_unavailable", false);
+        return Err(ServiceState::failure(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "schema_unavailable",
+            "trace store is not configured; start the service with --trace-db",
+            None,
+        ));
+    };
+    let Ok(run_ulid) = ulid::Ulid::from_string(&run_id) else {
+        state.telemetry.record_failure("validation_error", false);
+        return Err(ServiceState::failure(
+            StatusCode::BAD_REQUEST,
+            "validation_error",
+            format!("run_id must be a ULID: {run_id}"),
+            None,
+        ));
+    };
+    let Some(signature) = headers
+        .get(GATE_SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+    else {
+        state.telemetry.record_failure("unauthorized", false);
+        return Err(ServiceState::failure(
+            StatusCode::UNAUTHORIZED,
+            "unauthorized",
+            format!("missing {GATE_SIGNATURE_HEADER} header"),
+            None,
+        ));
+    };
+    let request: GateCallbackRequest = serde_json::from_slice(&body).map_err(|err| {
+        state.telemetry.record_failure("invalid_json", false);
+        ServiceState::failure(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "invalid_json",
+            format!("invalid gate callback body: {err}"),
+            None,
+        )
+    })?;
+    if request.run_id != run_id {
+        state.telemetry.record_failure("validation_error", false);
+        return Err(ServiceState::failure(
+            StatusCode::BAD_REQUEST,
+            "validation_error",
+            format!("gate callback is for run {}, not {run_id}", request.run_id),
+            None,
+        ));
+    }
+    let resolved = state
+        .run_blocking(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "query_failed",
+            "trace_gate_callback",
+            move |_| {
+                Ok(resolve_signed_gate_callback(
+                    &trace_db,
+                    RunId(run_ulid),
+                    request,
+                    &body,
+                    &signature,
+                ))
+            },
+        )
+        .await?;
+    resolved.map(|gate| Json(envelope(gate))).map_err(|err| gate_resolution_failure(&state, &err))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -552,6 +1457,11 @@ mod tests {
             api,
             operation_timeout: Duration::from_millis(timeout_ms),
             telemetry: Arc::new(ServiceTelemetry::default()),
+            health: HealthConfig::default(),
+            require_auth: false,
+            limits: Arc::new(RateLimiter::default()),
+            require_signed_sync: false,
+            sso_email_header: None,
         }
     }
 
@@ -717,6 +1627,135 @@ mod tests {
         );
     }
 
+    // Test IDs: TSVC-017
+    #[tokio::test]
+    async fn subsystem_health_reports_ready_when_all_checks_pass() {
+        let db_path = unique_temp_db_path();
+        let trace_db_path = unique_temp_db_path();
+        let api = MemoryKernelApi::new(db_path.clone());
+        if let Err(err) = api.migrate(false) {
+            panic!("failed to migrate schema before health test: {err:#}");
+        }
+        match SqliteOutcomeStore::open(&db_path).and_then(|store| store.migrate()) {
+            Ok(()) => {}
+            Err(err) => panic!("failed to migrate outcome schema: {err:#}"),
+        }
+        match SqliteTraceStore::open(&trace_db_path).and_then(|store| store.migrate()) {
+            Ok(()) => {}
+            Err(err) => panic!("failed to migrate trace schema: {err:#}"),
+        }
+        let mut state = test_state(api, 2500);
+        state.health.trace_db = Some(trace_db_path.clone());
+        let router = app(state);
+
+        let response = match router
+            .oneshot(
+                Request::builder()
+                    .uri("/health")
+                    .method("GET")
+                    .body(axum::body::Body::empty())
+                    .unwrap_or_else(|err| panic!("failed to build health request: {err}")),
+            )
+            .await
+        {
+            Ok(response) => response,
+            Err(err) => panic!("health request failed: {err}"),
+        };
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let value = response_json(response).await;
+        let data = value.get("data").cloned().unwrap_or(serde_json::Value::Null);
+        assert_eq!(data.get("readiness").and_then(serde_json::Value::as_str), Some("ready"));
+        assert_eq!(data.get("liveness").and_then(serde_json::Value::as_str), Some("alive"));
+        let statuses = data
+            .get("checks")
+            .and_then(serde_json::Value::as_array)
+            .map(|checks| {
+                checks
+                    .iter()
+                    .map(|check| {
+                        (
+                            check.get("name").and_then(serde_json::Value::as_str).unwrap_or(""),
+                            check.get("status").and_then(serde_json::Value::as_str).unwrap_or(""),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        assert_eq!(
+            statuses,
+            vec![
+                ("memory_kernel_schema", "pass"),
+                ("memory_kernel_compatibility", "pass"),
+                ("outcome_projector", "pass"),
+                ("trace_store", "pass"),
+                ("disk_headroom", "pass"),
+            ]
+        );
+
+        let _ = std::fs::remove_file(&db_path);
+        let _ = std::fs::remove_file(&trace_db_path);
+    }
+
+    // Test IDs: TSVC-018
+    #[tokio::test]
+    async fn subsystem_health_returns_not_ready_verdict_when_a_check_fails() {
+        let db_path = unique_temp_db_path();
+        let api = MemoryKernelApi::new(db_path.clone());
+        if let Err(err) = api.migrate(false) {
+            panic!("failed to migrate schema before health test: {err:#}");
+        }
+        let mut state = test_state(api, 2500);
+        state.health.trace_db = Some(unique_temp_db_path());
+        state.health.min_free_disk_bytes = u64::MAX;
+        let router = app(state);
+
+        let response = match router
+            .oneshot(
+                Request::builder()
+                    .uri("/health")
+                    .method("GET")
+                    .body(axum::body::Body::empty())
+                    .unwrap_or_else(|err| panic!("failed to build health request: {err}")),
+            )
+            .await
+        {
+            Ok(response) => response,
+            Err(err) => panic!("health request failed: {err}"),
+        };
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let value = response_json(response).await;
+        let error = value.get("error").cloned().unwrap_or(serde_json::Value::Null);
+        assert_eq!(
+            error.get("code").and_then(serde_json::Value::as_str),
+            Some("schema_unavailable")
+        );
+        let verdict = error.get("details").cloned().unwrap_or(serde_json::Value::Null);
+        assert_eq!(verdict.get("readiness").and_then(serde_json::Value::as_str), Some("not_ready"));
+        assert_eq!(verdict.get("status").and_then(serde_json::Value::as_str), Some("unhealthy"));
+        let failing = verdict
+            .get("checks")
+            .and_then(serde_json::Value::as_array)
+            .map(|checks| {
+                checks
+                    .iter()
+                    .filter(|check| {
+                        check.get("status").and_then(serde_json::Value::as_str) == Some("fail")
+                    })
+                    .filter_map(|check| check.get("name").and_then(serde_json::Value::as_str))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        assert_eq!(failing, vec!["trace_store", "disk_headroom"]);
+        assert!(
+            value.get("api_contract_version").is_none(),
+            "error envelope must not include api_contract_version: {value}"
+        );
+
+        let _ = std::fs::remove_file(&db_path);
+    }
+
     // Test IDs: TSVC-012
     #[tokio::test]
     async fn run_blocking_returns_success_for_fast_operation() {
@@ -1353,4 +2392,682 @@ mod tests {
 
         let _ = std::fs::remove_file(&db_path);
     }
+
+    async fn send(router: &Router, method: &str, uri: &str, token: Option<&str>) -> Response {
+        let mut builder = Request::builder().uri(uri).method(method);
+        if let Some(token) = token {
+            builder = builder.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        let body = if method == "POST" {
+            builder = builder.header(header::CONTENT_TYPE, "application/json");
+            axum::body::Body::from("{}")
+        } else {
+            axum::body::Body::empty()
+        };
+        let request =
+            builder.body(body).unwrap_or_else(|err| panic!("failed to build request: {err}"));
+        match router.clone().oneshot(request).await {
+            Ok(response) => response,
+            Err(err) => panic!("router request failed: {err}"),
+        }
+    }
+
+    fn error_code(value: &serde_json::Value) -> Option<&str> {
+        value.get("error").and_then(|error| error.get("code")).and_then(serde_json::Value::as_str)
+    }
+
+    // Test IDs: TSVC-019
+    #[tokio::test]
+    async fn require_auth_enforces_roles_per_endpoint_and_audits_calls() {
+        let db_path = unique_temp_db_path();
+        let api = MemoryKernelApi::new(db_path.clone());
+        if let Err(err) = api.migrate(false) {
+            panic!("failed to migrate schema before auth test: {err:#}");
+        }
+        let store = match memory_kernel_store_sqlite::SqliteStore::open(&db_path) {
+            Ok(store) => store,
+            Err(err) => panic!("failed to open store: {err:#}"),
+        };
+        let issue = |role: ApiRole, label: &str| match store.create_api_token(role, label) {
+            Ok(issued) => issued,
+            Err(err) => panic!("failed to create api token: {err:#}"),
+        };
+        let reader = issue(ApiRole::Reader, "reader");
+        let admin = issue(ApiRole::Admin, "admin");
+        let revoked = issue(ApiRole::Admin, "revoked");
+        match store.revoke_api_token(&revoked.token.token_id) {
+            Ok(true) => {}
+            other => panic!("failed to revoke token: {other:?}"),
+        }
+
+        let mut state = test_state(api, 2500);
+        state.require_auth = true;
+        let router = app(state);
+
+        let response = send(&router, "GET", "/v1/health", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = send(&router, "POST", "/v1/db/schema-version", None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(error_code(&response_json(response).await), Some("unauthorized"));
+
+        let response = send(&router, "POST", "/v1/db/schema-version", Some(&revoked.secret)).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = send(&router, "POST", "/v1/db/schema-version", Some(&reader.secret)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = send(&router, "POST", "/v1/db/migrate", Some(&reader.secret)).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(error_code(&response_json(response).await), Some("forbidden"));
+
+        let response = send(&router, "GET", "/v1/admin/audit-log", Some(&admin.secret)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let value = response_json(response).await;
+        let entries = value
+            .get("data")
+            .and_then(|data| data.get("entries"))
+            .and_then(serde_json::Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        let reader_writer = format!("token:{}", reader.token.token_id);
+        let reader_calls = entries
+            .iter()
+            .filter(|entry| {
+                entry.get("writer").and_then(serde_json::Value::as_str)
+                    == Some(reader_writer.as_str())
+            })
+            .map(|entry| {
+                (
+                    entry.get("command").and_then(serde_json::Value::as_str).unwrap_or_default(),
+                    entry.get("outcome").and_then(serde_json::Value::as_str).unwrap_or_default(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            reader_calls,
+            vec![("POST /v1/db/migrate", "error"), ("POST /v1/db/schema-version", "ok")]
+        );
+
+        let _ = std::fs::remove_file(&db_path);
+    }
+
+    // Test IDs: TSVC-024
+    #[tokio::test]
+    async fn require_auth_audits_memory_add_once_under_calling_token() {
+        let db_path = unique_temp_db_path();
+        let api = MemoryKernelApi::new(db_path.clone());
+        if let Err(err) = api.migrate(false) {
+            panic!("failed to migrate schema before auth test: {err:#}");
+        }
+        let writer = match memory_kernel_store_sqlite::SqliteStore::open(&db_path)
+            .and_then(|store| store.create_api_token(ApiRole::Writer, "writer"))
+        {
+            Ok(issued) => issued,
+            Err(err) => panic!("failed to create api token: {err:#}"),
+        };
+        let mut state = test_state(api.clone(), 2500);
+        state.require_auth = true;
+        let router = app(state);
+
+        let add_payload = serde_json::json!({
+            "actor": "user",
+            "action": "use",
+            "resource": "usb_drive",
+            "effect": "deny",
+            "note": null,
+            "memory_id": null,
+            "version": 1,
+            "writer": "tester",
+            "justification": "service fixture",
+            "source_uri": "file:///policy.md",
+            "source_hash": null,
+            "evidence": [],
+            "confidence": 0.9,
+            "truth_status": "asserted",
+            "authority": "authoritative",
+            "created_at": null,
+            "effective_at": null,
+            "supersedes": [],
+            "contradicts": []
+        });
+        let request = Request::builder()
+            .uri("/v1/memory/add/constraint")
+            .method("POST")
+            .header(header::AUTHORIZATION, format!("Bearer {}", writer.secret))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(axum::body::Body::from(add_payload.to_string()))
+            .unwrap_or_else(|err| panic!("failed to build add request: {err}"));
+        let response = match router.clone().oneshot(request).await {
+            Ok(response) => response,
+            Err(err) => panic!("add request failed: {err}"),
+        };
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Only the migration above is audited by the API; the add is audited once, by token.
+        let entries = match api.admin_audit_log(None) {
+            Ok(entries) => entries,
+            Err(err) => panic!("failed to list admin audit: {err:#}"),
+        };
+        let commands = entries
+            .iter()
+            .map(|entry| (entry.command.as_str(), entry.writer.as_deref()))
+            .collect::<Vec<_>>();
+        let token_writer = format!("token:{}", writer.token.token_id);
+        assert_eq!(
+            commands,
+            vec![
+                ("POST /v1/memory/add/constraint", Some(token_writer.as_str())),
+                ("migrate", None),
+            ]
+        );
+
+        let _ = std::fs::remove_file(&db_path);
+    }
+
+    // Test IDs: TSVC-020
+    #[tokio::test]
+    async fn rate_limit_returns_429_with_quota_headers() {
+        let db_path = unique_temp_db_path();
+        let mut state = test_state(MemoryKernelApi::new(db_path.clone()), 2500);
+        state.limits =
+            Arc::new(RateLimiter::new(RateLimitConfig { per_minute: 1, daily_quota: 5 }));
+        let router = app(state);
+
+        let response = send(&router, "POST", "/v1/db/schema-version", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let header_value = |response: &Response, name: &str| {
+            response.headers().get(name).and_then(|value| value.to_str().ok()).map(str::to_string)
+        };
+        assert_eq!(header_value(&response, "x-ratelimit-limit").as_deref(), Some("1"));
+        assert_eq!(header_value(&response, "x-ratelimit-remaining").as_deref(), Some("0"));
+        assert_eq!(header_value(&response, "x-quota-limit"), None);
+
+        let response = send(&router, "POST", "/v1/query/ask", None).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(header_value(&response, header::RETRY_AFTER.as_str()).is_some());
+        assert_eq!(header_value(&response, "x-quota-remaining").as_deref(), Some("5"));
+        assert_eq!(error_code(&response_json(response).await), Some("rate_limited"));
+
+        let response = send(&router, "GET", "/v1/health", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(header_value(&response, "x-ratelimit-limit"), None);
+
+        let _ = std::fs::remove_file(&db_path);
+    }
+
+    // Test IDs: TSVC-021
+    #[tokio::test]
+    async fn trace_run_progress_endpoint_reports_planned_steps() {
+        let trace_db_path = unique_temp_db_path();
+        let trace_store = match SqliteTraceStore::open(&trace_db_path) {
+            Ok(store) => store,
+            Err(err) => panic!("failed to open trace store: {err:#}"),
+        };
+        let workflow = json!({"steps": [{"step_key": "plan"}, {"step_key": "review"}]});
+        let run_id = RunId::new();
+        let now = time::OffsetDateTime::now_utc();
+        let seeded = trace_store
+            .migrate()
+            .and_then(|()| {
+                trace_store.upsert_workflow_snapshot("hash", 1, "yaml", "yaml-hash", &workflow)
+            })
+            .and_then(|()| {
+                trace_store.insert_run(&multi_agent_center_domain::RunRecord {
+                    run_id,
+                    workflow_name: "wf".to_string(),
+                    workflow_version: "v1".to_string(),
+                    workflow_hash: "hash".to_string(),
+                    as_of: now,
+                    as_of_was_default: true,
+                    started_at: now,
+                    ended_at: None,
+                    status: multi_agent_center_domain::RunStatus::Running,
+                    replay_of_run_id: None,
+                    external_correlation_id: None,
+                    engine_version: "test".to_string(),
+                    cli_args_json: json!({}),
+                    manifest_hash: None,
+                    manifest_signature: None,
+                    manifest_signature_status: "unsigned".to_string(),
+                    labels: std::collections::BTreeMap::new(),
+                    rerun_of: None,
+                    parent_step: None,
+                })
+            });
+        if let Err(err) = seeded {
+            panic!("failed to seed trace store: {err:#}");
+        }
+
+        let mut state = test_state(MemoryKernelApi::new(unique_temp_db_path()), 2500);
+        state.health.trace_db = Some(trace_db_path.clone());
+        let router = app(state);
+
+        let uri = format!("/v1/trace/runs/{}/progress", run_id.0);
+        let response = send(&router, "GET", &uri, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let value = response_json(response).await;
+        let data = value.get("data").cloned().unwrap_or(serde_json::Value::Null);
+        assert_eq!(data.get("total_steps").and_then(serde_json::Value::as_u64), Some(2));
+        assert_eq!(data.get("pending_steps").and_then(serde_json::Value::as_u64), Some(2));
+        assert_eq!(data.get("eta_ms"), Some(&serde_json::Value::Null));
+
+        let uri = format!("/v1/trace/runs/{}/progress", RunId::new().0);
+        let response = send(&router, "GET", &uri, None).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(error_code(&response_json(response).await), Some("run_not_found"));
+
+        let _ = std::fs::remove_file(&trace_db_path);
+    }
+
+    /// Seed a `partially_completed` run whose `deploy` step deferred each `(gate, approver)`.
+    fn seed_deferred_gates(
+        trace_db_path: &FsPath,
+        gates: &[(&str, &str)],
+        webhook: Option<&multi_agent_center_domain::GateWebhook>,
+    ) -> RunId {
+        let trace_store = match SqliteTraceStore::open(trace_db_path) {
+            Ok(store) => store,
+            Err(err) => panic!("failed to open trace store: {err:#}"),
+        };
+        let run_id = RunId::new();
+        let step_id = multi_agent_center_domain::StepId::new();
+        let now = time::OffsetDateTime::now_utc();
+        let mut seeded = trace_store
+            .migrate()
+            .and_then(|()| {
+                trace_store.upsert_workflow_snapshot("hash", 1, "yaml", "yaml-hash", &json!({}))
+            })
+            .and_then(|()| {
+                trace_store.insert_run(&multi_agent_center_domain::RunRecord {
+                    run_id,
+                    workflow_name: "wf".to_string(),
+                    workflow_version: "v1".to_string(),
+                    workflow_hash: "hash".to_string(),
+                    as_of: now,
+                    as_of_was_default: true,
+                    started_at: now,
+                    ended_at: Some(now),
+                    status: multi_agent_center_domain::RunStatus::PartiallyCompleted,
+                    replay_of_run_id: None,
+                    external_correlation_id: None,
+                    engine_version: "test".to_string(),
+                    cli_args_json: json!({}),
+                    manifest_hash: None,
+                    manifest_signature: None,
+                    manifest_signature_status: "unsigned".to_string(),
+                    labels: std::collections::BTreeMap::new(),
+                    rerun_of: None,
+                    parent_step: None,
+                })
+            })
+            .and_then(|()| {
+                trace_store.insert_step(&multi_agent_center_domain::StepRecord {
+                    step_id,
+                    run_id,
+                    step_index: 0,
+                    step_key: "deploy".to_string(),
+                    agent_name: "agent".to_string(),
+                    status: multi_agent_center_domain::StepStatus::Deferred,
+                    started_at: Some(now),
+                    ended_at: Some(now),
+                    task_payload_json: json!({}),
+                    constraints_json: json!({}),
+                    permissions_json: json!({}),
+                    input_hash: "input-hash".to_string(),
+                    output_hash: None,
+                    error_json: None,
+                    retry_json: None,
+                    prompt_template_hash: None,
+                    provider_selection_json: None,
+                    taint_json: None,
+                    memoized_from_run: None,
+                    memo_key: None,
+                })
+            });
+        for (gate_name, approver) in gates {
+            seeded = seeded.and_then(|()| {
+                trace_store.enqueue_deferred_gate(&DeferredGateRecord {
+                    run_id,
+                    step_id,
+                    step_key: "deploy".to_string(),
+                    gate_name: (*gate_name).to_string(),
+                    required: true,
+                    deferred_at: now,
+                    approvers: vec![(*approver).to_string()],
+                    webhook: webhook.cloned(),
+                    resolution: None,
+                })
+            });
+        }
+        if let Err(err) = seeded {
+            panic!("failed to seed trace store: {err:#}");
+        }
+        run_id
+    }
+
+    #[tokio::test]
+    // Test IDs: TSVC-022
+    async fn trace_resolve_gate_records_verified_decider_and_enforces_approvers() {
+        let trace_db_path = unique_temp_db_path();
+        let run_id = seed_deferred_gates(
+            &trace_db_path,
+            &[("release", "release-lead"), ("signoff", "alice@example.com")],
+            None,
+        );
+
+        let db_path = unique_temp_db_path();
+        let api = MemoryKernelApi::new(db_path.clone());
+        if let Err(err) = api.migrate(false) {
+            panic!("failed to migrate schema: {err:#}");
+        }
+        let writer = match memory_kernel_store_sqlite::SqliteStore::open(&db_path)
+            .and_then(|store| store.create_api_token(ApiRole::Writer, "release-lead"))
+        {
+            Ok(issued) => issued,
+            Err(err) => panic!("failed to create api token: {err:#}"),
+        };
+        let mut state = test_state(api, 2500);
+        state.require_auth = true;
+        state.health.trace_db = Some(trace_db_path.clone());
+        state.sso_email_header = Some("x-sso-email".to_string());
+        let router = app(state);
+
+        let uri = format!("/v1/trace/runs/{}/gates/resolve", run_id.0);
+        let resolve = |gate: &str, sso_email: Option<&str>| {
+            let mut builder = Request::builder()
+                .uri(&uri)
+                .method("POST")
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(header::AUTHORIZATION, format!("Bearer {}", writer.secret));
+            if let Some(email) = sso_email {
+                builder = builder.header("x-sso-email", email);
+            }
+            let body = json!({"step_key": "deploy", "gate": gate, "approve": true});
+            let request = builder
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap_or_else(|err| panic!("failed to build request: {err}"));
+            let router = router.clone();
+            async move {
+                let response = match router.oneshot(request).await {
+                    Ok(response) => response,
+                    Err(err) => panic!("router request failed: {err}"),
+                };
+                (response.status(), response_json(response).await)
+            }
+        };
+        let resolution = |value: &serde_json::Value, field: &str| {
+            value
+                .get("data")
+                .and_then(|data| data.get("resolution"))
+                .and_then(|resolution| resolution.get(field))
+                .and_then(serde_json::Value::as_str)
+                .map(str::to_string)
+        };
+
+        let response = send(&router, "POST", &uri, None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let (status, value) = resolve("release", None).await;
+        assert_eq!(status, StatusCode::OK, "{value}");
+        assert_eq!(resolution(&value, "decided_by").as_deref(), Some("release-lead"));
+        assert_eq!(resolution(&value, "auth_method").as_deref(), Some("token"));
+        let (status, value) = resolve("release", None).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(error_code(&value), Some("write_conflict"));
+
+        let (status, value) = resolve("signoff", None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(error_code(&value), Some("forbidden"));
+        let (status, value) = resolve("signoff", Some("mallory@example.com")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(error_code(&value), Some("forbidden"));
+        let (status, value) = resolve("signoff", Some("alice@example.com")).await;
+        assert_eq!(status, StatusCode::OK, "{value}");
+        assert_eq!(resolution(&value, "decided_by").as_deref(), Some("alice@example.com"));
+        assert_eq!(resolution(&value, "auth_method").as_deref(), Some("sso"));
+        let (status, value) = resolve("missing", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(error_code(&value), Some("gate_not_found"));
+
+        let _ = std::fs::remove_file(&trace_db_path);
+        let _ = std::fs::remove_file(&db_path);
+    }
+
+    #[tokio::test]
+    // Test IDs: TSVC-023
+    async fn trace_gate_callback_requires_a_valid_webhook_signature() {
+        let secret_env = format!("MK_TEST_GATE_SECRET_{}", ulid::Ulid::new());
+        std::env::set_var(&secret_env, "gate-secret");
+        let webhook = multi_agent_center_domain::GateWebhook {
+            url: "https://hooks.example.com/gates".to_string(),
+            secret_env,
+        };
+        let trace_db_path = unique_temp_db_path();
+        let run_id = seed_deferred_gates(
+            &trace_db_path,
+            &[("release", "alice@example.com"), ("signoff", "alice@example.com")],
+            Some(&webhook),
+        );
+        let unhooked_run = seed_deferred_gates(&trace_db_path, &[("release", "ops")], None);
+
+        let db_path = unique_temp_db_path();
+        let api = MemoryKernelApi::new(db_path.clone());
+        if let Err(err) = api.migrate(false) {
+            panic!("failed to migrate schema: {err:#}");
+        }
+        let mut state = test_state(api, 2500);
+        state.require_auth = true;
+        state.health.trace_db = Some(trace_db_path.clone());
+        let router = app(state);
+
+        let callback = |run: RunId, body: serde_json::Value, secret: Option<&str>| {
+            let body = body.to_string();
+            let mut builder = Request::builder()
+                .uri(format!("/v1/trace/runs/{}/gates/callback", run.0))
+                .method("POST")
+                .header(header::CONTENT_TYPE, "application/json");
+            if let Some(secret) = secret {
+                let signature =
+                    multi_agent_center_domain::sign_gate_payload(secret, body.as_bytes());
+                builder = builder.header(GATE_SIGNATURE_HEADER, format!("sha256={signature}"));
+            }
+            let request = builder
+                .body(axum::body::Body::from(body))
+                .unwrap_or_else(|err| panic!("failed to build request: {err}"));
+            let router = router.clone();
+            async move {
+                let response = match router.oneshot(request).await {
+                    Ok(response) => response,
+                    Err(err) => panic!("router request failed: {err}"),
+                };
+                (response.status(), response_json(response).await)
+            }
+        };
+        let decision = |run: RunId, gate: &str, decided_by: &str| {
+            json!({
+                "run_id": run.0.to_string(),
+                "step_key": "deploy",
+                "gate": gate,
+                "approve": true,
+                "decided_by": decided_by,
+            })
+        };
+
+        let alice = decision(run_id, "release", "alice@example.com");
+        let (status, value) = callback(run_id, alice.clone(), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(error_code(&value), Some("unauthorized"));
+        let (status, value) = callback(run_id, alice.clone(), Some("guessed-secret")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(error_code(&value), Some("unauthorized"));
+        let (status, value) = callback(unhooked_run, alice.clone(), Some("gate-secret")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error_code(&value), Some("validation_error"));
+
+        let (status, value) = callback(run_id, alice.clone(), Some("gate-secret")).await;
+        assert_eq!(status, StatusCode::OK, "{value}");
+        let resolution = value.get("data").and_then(|data| data.get("resolution"));
+        assert_eq!(
+            resolution.and_then(|resolution| resolution.get("auth_method")),
+            Some(&json!("webhook"))
+        );
+        let (status, value) = callback(run_id, alice, Some("gate-secret")).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(error_code(&value), Some("write_conflict"));
+
+        let mallory = decision(run_id, "signoff", "mallory@example.com");
+        let (status, value) = callback(run_id, mallory, Some("gate-secret")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(error_code(&value), Some("forbidden"));
+        let unhooked = decision(unhooked_run, "release", "ops");
+        let (status, value) = callback(unhooked_run, unhooked, Some("gate-secret")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(error_code(&value), Some("forbidden"));
+
+        let _ = std::fs::remove_file(&trace_db_path);
+        let _ = std::fs::remove_file(&db_path);
+    }
+
+    fn sync_fixture_db(
+        memory_id: Option<memory_kernel_core::MemoryId>,
+    ) -> (PathBuf, MemoryKernelApi, memory_kernel_core::MemoryId) {
+        let db_path = unique_temp_db_path();
+        let api = MemoryKernelApi::new(db_path.clone());
+        if let Err(err) = api.migrate(false) {
+            panic!("failed to migrate schema before sync test: {err:#}");
+        }
+        let request: AddConstraintRequest = match serde_json::from_value(json!({
+            "actor": "user",
+            "action": "use",
+            "resource": "usb_drive",
+            "effect": "deny",
+            "note": null,
+            "memory_id": memory_id,
+            "version": 1,
+            "writer": "tester",
+            "justification": "sync fixture",
+            "source_uri": "file:///policy.md",
+            "source_hash": "sha256:abc123",
+            "evidence": [],
+            "confidence": 0.9,
+            "truth_status": "asserted",
+            "authority": "authoritative",
+            "created_at": null,
+            "effective_at": null,
+            "supersedes": [],
+            "contradicts": []
+        })) {
+            Ok(request) => request,
+            Err(err) => panic!("invalid constraint fixture: {err}"),
+        };
+        let record = match api.add_constraint(request) {
+            Ok(record) => record,
+            Err(err) => panic!("failed to add constraint fixture: {err:#}"),
+        };
+        if let Err(err) = SqliteOutcomeStore::open(&db_path).and_then(|store| store.migrate()) {
+            panic!("failed to migrate outcome schema: {err:#}");
+        }
+        (db_path, api, record.memory_id)
+    }
+
+    async fn post_json(
+        router: &Router,
+        uri: &str,
+        body: &serde_json::Value,
+    ) -> (StatusCode, serde_json::Value) {
+        let response = match router
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(uri)
+                    .method("POST")
+                    .header("content-type", "application/json")
+                    .body(axum::body::Body::from(body.to_string()))
+                    .unwrap_or_else(|err| panic!("failed to build request: {err}")),
+            )
+            .await
+        {
+            Ok(response) => response,
+            Err(err) => panic!("request failed: {err}"),
+        };
+        let status = response.status();
+        (status, response_json(response).await)
+    }
+
+    // Test IDs: TSVC-025
+    #[tokio::test]
+    async fn outcome_sync_push_and_pull_round_trip_events() {
+        let (db_path, api, memory_id) = sync_fixture_db(None);
+        let (edge_path, _, _) = sync_fixture_db(Some(memory_id));
+        let mut edge = match SqliteOutcomeStore::open(&edge_path) {
+            Ok(store) => store,
+            Err(err) => panic!("failed to open edge store: {err:#}"),
+        };
+        let input = memory_kernel_outcome_core::OutcomeEventInput {
+            event_id: None,
+            ruleset_version: 1,
+            memory_id,
+            version: 1,
+            event_type: memory_kernel_outcome_core::OutcomeEventType::Success,
+            occurred_at: time::OffsetDateTime::now_utc(),
+            writer: "edge-agent".to_string(),
+            justification: "offline verdict".to_string(),
+            context_id: None,
+            edited: false,
+            escalated: false,
+            severity: None,
+            manual_confidence: None,
+            override_cap: false,
+            payload_json: json!({}),
+            cosigner: None,
+            signature: None,
+            source: None,
+            effective_at: None,
+        };
+        let batch = match edge.append_event(&input).and_then(|_| {
+            edge.export_sync_batch(
+                "edge",
+                &memory_kernel_outcome_store_sqlite::SyncCursors::default(),
+                10,
+                None,
+            )
+        }) {
+            Ok(batch) => batch,
+            Err(err) => panic!("failed to build sync batch: {err:#}"),
+        };
+        let batch_json = json!(batch);
+
+        let router = app(test_state(api.clone(), 2500));
+        let (status, value) = post_json(&router, "/v1/outcome/sync/push", &batch_json).await;
+        assert_eq!(status, StatusCode::OK, "{value}");
+        assert_eq!(value["data"]["accepted"].as_array().map(Vec::len), Some(1));
+        let (status, value) = post_json(&router, "/v1/outcome/sync/push", &batch_json).await;
+        assert_eq!(status, StatusCode::OK, "{value}");
+        assert_eq!(value["data"]["duplicates"], json!(1));
+
+        let (status, value) = post_json(
+            &router,
+            "/v1/outcome/sync/pull",
+            &json!({"protocol_version": SYNC_PROTOCOL_VERSION, "cursors": {"floor": 0}, "limit": 10}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{value}");
+        assert_eq!(value["data"]["events"].as_array().map(Vec::len), Some(1));
+        assert_eq!(value["data"]["events"][0]["event_id"], batch_json["events"][0]["event_id"]);
+
+        let mut tampered = batch_json.clone();
+        tampered["batch_hash"] = json!("0".repeat(64));
+        let (status, value) = post_json(&router, "/v1/outcome/sync/push", &tampered).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{value}");
+        assert_eq!(value["error"]["code"], json!("validation_error"));
+
+        let mut strict = test_state(api, 2500);
+        strict.require_signed_sync = true;
+        let (status, _) = post_json(&app(strict), "/v1/outcome/sync/push", &batch_json).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let _ = std::fs::remove_file(&db_path);
+        let _ = std::fs::remove_file(&edge_path);
+    }
 }
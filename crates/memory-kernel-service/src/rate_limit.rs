@@ -0,0 +1,187 @@
+//! Per-caller request rate limits and daily quotas for serve mode.
+//!
+//! Counters are in-process: a fixed one-minute window per caller plus a per-UTC-day counter for
+//! expensive endpoints. Both reset when the service restarts.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use time::{Date, OffsetDateTime};
+
+const WINDOW: Duration = Duration::from_secs(60);
+const SECONDS_PER_DAY: i64 = 86_400;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct RateLimitConfig {
+    /// Requests per caller per minute across all limited endpoints; `0` disables the limit.
+    pub(crate) per_minute: u32,
+    /// Expensive requests per caller per UTC day; `0` disables the quota.
+    pub(crate) daily_quota: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct LimitStatus {
+    pub(crate) limit: u32,
+    pub(crate) remaining: u32,
+    pub(crate) reset_secs: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Exceeded {
+    RateLimit,
+    DailyQuota,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Admission {
+    pub(crate) exceeded: Option<Exceeded>,
+    pub(crate) rate: Option<LimitStatus>,
+    pub(crate) quota: Option<LimitStatus>,
+}
+
+impl Admission {
+    pub(crate) fn retry_after_secs(&self) -> Option<u64> {
+        match self.exceeded? {
+            Exceeded::RateLimit => self.rate.map(|status| status.reset_secs),
+            Exceeded::DailyQuota => self.quota.map(|status| status.reset_secs),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CallerUsage {
+    window_start: Instant,
+    window_count: u32,
+    quota_day: Date,
+    quota_used: u32,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct RateLimiter {
+    config: RateLimitConfig,
+    usage: Mutex<HashMap<String, CallerUsage>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(config: RateLimitConfig) -> Self {
+        Self { config, usage: Mutex::new(HashMap::new()) }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.config.per_minute > 0 || self.config.daily_quota > 0
+    }
+
+    /// Count one request for `caller`, or reject it without counting when a limit is exhausted.
+    pub(crate) fn admit(
+        &self,
+        caller: &str,
+        expensive: bool,
+        now: Instant,
+        wall_clock: OffsetDateTime,
+    ) -> Admission {
+        let mut usage = self.usage.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        let entry = usage.entry(caller.to_string()).or_insert_with(|| CallerUsage {
+            window_start: now,
+            window_count: 0,
+            quota_day: wall_clock.date(),
+            quota_used: 0,
+        });
+
+        if now.saturating_duration_since(entry.window_start) >= WINDOW {
+            entry.window_start = now;
+            entry.window_count = 0;
+        }
+        if entry.quota_day != wall_clock.date() {
+            entry.quota_day = wall_clock.date();
+            entry.quota_used = 0;
+        }
+
+        let rate_limited =
+            self.config.per_minute > 0 && entry.window_count >= self.config.per_minute;
+        let quota_limited =
+            expensive && self.config.daily_quota > 0 && entry.quota_used >= self.config.daily_quota;
+        let exceeded = if rate_limited {
+            Some(Exceeded::RateLimit)
+        } else if quota_limited {
+            Some(Exceeded::DailyQuota)
+        } else {
+            if self.config.per_minute > 0 {
+                entry.window_count += 1;
+            }
+            if expensive && self.config.daily_quota > 0 {
+                entry.quota_used += 1;
+            }
+            None
+        };
+
+        let rate = (self.config.per_minute > 0).then(|| LimitStatus {
+            limit: self.config.per_minute,
+            remaining: self.config.per_minute.saturating_sub(entry.window_count),
+            reset_secs: WINDOW
+                .saturating_sub(now.saturating_duration_since(entry.window_start))
+                .as_secs()
+                .max(1),
+        });
+        let quota = (expensive && self.config.daily_quota > 0).then(|| LimitStatus {
+            limit: self.config.daily_quota,
+            remaining: self.config.daily_quota.saturating_sub(entry.quota_used),
+            reset_secs: seconds_until_utc_midnight(wall_clock),
+        });
+
+        Admission { exceeded, rate, quota }
+    }
+}
+
+fn seconds_until_utc_midnight(now: OffsetDateTime) -> u64 {
+    let elapsed =
+        i64::from(now.hour()) * 3_600 + i64::from(now.minute()) * 60 + i64::from(now.second());
+    u64::try_from(SECONDS_PER_DAY - elapsed).unwrap_or(1).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2026-01-01T00:00:00Z
+    const NEW_YEAR: i64 = 1_767_225_600;
+
+    fn utc(offset_secs: i64) -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp(NEW_YEAR + offset_secs)
+            .unwrap_or_else(|_| unreachable!())
+    }
+
+    #[test]
+    fn per_minute_limit_rejects_until_window_rolls_over() {
+        let limiter = RateLimiter::new(RateLimitConfig { per_minute: 2, daily_quota: 0 });
+        let start = Instant::now();
+        let wall_clock = utc(12 * 3_600);
+
+        assert_eq!(limiter.admit("token-a", false, start, wall_clock).exceeded, None);
+        let second = limiter.admit("token-a", false, start, wall_clock);
+        assert_eq!(second.exceeded, None);
+        assert_eq!(second.rate.map(|status| status.remaining), Some(0));
+        let third = limiter.admit("token-a", false, start, wall_clock);
+        assert_eq!(third.exceeded, Some(Exceeded::RateLimit));
+        assert_eq!(third.retry_after_secs(), Some(60));
+
+        assert_eq!(limiter.admit("token-b", false, start, wall_clock).exceeded, None);
+        assert_eq!(limiter.admit("token-a", false, start + WINDOW, wall_clock).exceeded, None);
+    }
+
+    #[test]
+    fn daily_quota_counts_only_expensive_requests_and_resets_at_utc_midnight() {
+        let limiter = RateLimiter::new(RateLimitConfig { per_minute: 0, daily_quota: 1 });
+        let now = Instant::now();
+        let evening = utc(23 * 3_600);
+
+        assert_eq!(limiter.admit("token-a", true, now, evening).exceeded, None);
+        assert_eq!(limiter.admit("token-a", false, now, evening).exceeded, None);
+        let denied = limiter.admit("token-a", true, now, evening);
+        assert_eq!(denied.exceeded, Some(Exceeded::DailyQuota));
+        assert_eq!(denied.retry_after_secs(), Some(3_600));
+
+        let next_day = utc(SECONDS_PER_DAY + 1);
+        assert_eq!(limiter.admit("token-a", true, now, next_day).exceeded, None);
+    }
+}
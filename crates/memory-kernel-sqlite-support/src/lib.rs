@@ -0,0 +1,449 @@
+//! Connection setup shared by the memory and outcome sqlite stores: pragmas and the
+//! per-connection instrumentation behind their `stats()`.
+
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context, Result};
+use rusqlite::hooks::{Action, AuthAction, AuthContext, Authorization, TransactionOperation};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+/// Point-in-time copy of a store's instrumentation counters, for correlating host-level
+/// latency with database behaviour. Counters start at zero when the store is opened.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StoreStats {
+    /// Statement executions; re-running a prepared statement counts each run.
+    pub statements_executed: u64,
+    /// Rows inserted, updated, or deleted, including writes later rolled back.
+    pub rows_written: u64,
+    /// Waits on a lock held by another connection before a statement was retried.
+    pub busy_retries: u64,
+    pub transactions_committed: u64,
+    pub transactions_rolled_back: u64,
+    /// Wall time of explicit transactions, from `BEGIN` to `COMMIT` or `ROLLBACK`.
+    pub transaction_time_total_us: u64,
+    pub transaction_time_max_us: u64,
+}
+
+/// Pragmas applied when a store is opened. The defaults are WAL journaling, sqlite's own
+/// `synchronous`, `cache_size`, and `mmap_size`, and a five second busy timeout; network
+/// filesystems usually need a rollback journal instead of WAL.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct SqlitePragmas {
+    pub journal_mode: JournalMode,
+    /// `None` keeps sqlite's compiled-in default (`FULL`).
+    pub synchronous: Option<SynchronousMode>,
+    /// Page cache size: positive values are pages, negative values are KiB.
+    pub cache_size: Option<i64>,
+    /// Bytes of the database file to memory-map; `0` disables memory-mapped I/O.
+    pub mmap_size: Option<i64>,
+    /// Total time the busy handler waits for a lock before giving up with `SQLITE_BUSY`.
+    pub busy_timeout_ms: u64,
+}
+
+impl Default for SqlitePragmas {
+    fn default() -> Self {
+        Self {
+            journal_mode: JournalMode::Wal,
+            synchronous: None,
+            cache_size: None,
+            mmap_size: None,
+            busy_timeout_ms: 5_000,
+        }
+    }
+}
+
+impl SqlitePragmas {
+    /// Apply the journal, `synchronous`, cache, and mmap settings to `conn` and enable foreign
+    /// keys. `busy_timeout_ms` is left to the connection's busy handler.
+    ///
+    /// # Errors
+    /// Returns an error when sqlite rejects a pragma.
+    pub fn apply(&self, conn: &Connection) -> Result<()> {
+        let mut statements = vec![
+            format!("PRAGMA journal_mode = {}", self.journal_mode.as_str()),
+            "PRAGMA foreign_keys = ON".to_string(),
+        ];
+        if let Some(synchronous) = self.synchronous {
+            statements.push(format!("PRAGMA synchronous = {}", synchronous.as_str()));
+        }
+        if let Some(cache_size) = self.cache_size {
+            statements.push(format!("PRAGMA cache_size = {cache_size}"));
+        }
+        if let Some(mmap_size) = self.mmap_size {
+            statements.push(format!("PRAGMA mmap_size = {mmap_size}"));
+        }
+        conn.execute_batch(&format!("{};", statements.join(";\n")))
+            .context("failed to configure sqlite pragmas")
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalMode {
+    Delete,
+    Truncate,
+    Persist,
+    Memory,
+    Wal,
+    Off,
+}
+
+impl JournalMode {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Delete => "delete",
+            Self::Truncate => "truncate",
+            Self::Persist => "persist",
+            Self::Memory => "memory",
+            Self::Wal => "wal",
+            Self::Off => "off",
+        }
+    }
+}
+
+impl FromStr for JournalMode {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "delete" => Ok(Self::Delete),
+            "truncate" => Ok(Self::Truncate),
+            "persist" => Ok(Self::Persist),
+            "memory" => Ok(Self::Memory),
+            "wal" => Ok(Self::Wal),
+            "off" => Ok(Self::Off),
+            other => Err(anyhow!("unknown sqlite journal mode: {other}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SynchronousMode {
+    Off,
+    Normal,
+    Full,
+    Extra,
+}
+
+impl SynchronousMode {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Normal => "normal",
+            Self::Full => "full",
+            Self::Extra => "extra",
+        }
+    }
+}
+
+impl FromStr for SynchronousMode {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "off" => Ok(Self::Off),
+            "normal" => Ok(Self::Normal),
+            "full" => Ok(Self::Full),
+            "extra" => Ok(Self::Extra),
+            other => Err(anyhow!("unknown sqlite synchronous mode: {other}")),
+        }
+    }
+}
+
+/// Counters of one instrumented connection; see [`install_store_counters`].
+#[derive(Debug, Default)]
+pub struct StoreCounters {
+    statements_executed: AtomicU64,
+    rows_written: AtomicU64,
+    busy_retries: AtomicU64,
+    transactions_committed: AtomicU64,
+    transactions_rolled_back: AtomicU64,
+    transaction_time_total_us: AtomicU64,
+    transaction_time_max_us: AtomicU64,
+    transaction_started: Mutex<Option<Instant>>,
+    busy_timeout_ms: u64,
+}
+
+impl StoreCounters {
+    #[must_use]
+    pub fn snapshot(&self) -> StoreStats {
+        StoreStats {
+            statements_executed: self.statements_executed.load(Ordering::Relaxed),
+            rows_written: self.rows_written.load(Ordering::Relaxed),
+            busy_retries: self.busy_retries.load(Ordering::Relaxed),
+            transactions_committed: self.transactions_committed.load(Ordering::Relaxed),
+            transactions_rolled_back: self.transactions_rolled_back.load(Ordering::Relaxed),
+            transaction_time_total_us: self.transaction_time_total_us.load(Ordering::Relaxed),
+            transaction_time_max_us: self.transaction_time_max_us.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn reset(&self) {
+        for counter in [
+            &self.statements_executed,
+            &self.rows_written,
+            &self.busy_retries,
+            &self.transactions_committed,
+            &self.transactions_rolled_back,
+            &self.transaction_time_total_us,
+            &self.transaction_time_max_us,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Total time the connection's busy handler waits for a lock.
+    #[must_use]
+    pub fn busy_timeout_ms(&self) -> u64 {
+        self.busy_timeout_ms
+    }
+
+    fn transaction_boundary(&self, operation: TransactionOperation) {
+        let Ok(mut started) = self.transaction_started.lock() else {
+            return;
+        };
+        if matches!(operation, TransactionOperation::Begin) {
+            *started = Some(Instant::now());
+        } else if let Some(begun) = started.take() {
+            let elapsed_us = u64::try_from(begun.elapsed().as_micros()).unwrap_or(u64::MAX);
+            self.transaction_time_total_us.fetch_add(elapsed_us, Ordering::Relaxed);
+            self.transaction_time_max_us.fetch_max(elapsed_us, Ordering::Relaxed);
+        }
+    }
+
+    /// Busy handler body: sleep with sqlite's default back-off until `busy_timeout_ms` has
+    /// passed, counting each retry.
+    fn wait_busy(&self, attempt: i32) -> bool {
+        const DELAYS_MS: [u64; 12] = [1, 2, 5, 10, 15, 20, 25, 25, 25, 50, 50, 100];
+        let attempt = usize::try_from(attempt).unwrap_or(0);
+        let waited_ms = DELAYS_MS.iter().take(attempt).sum::<u64>()
+            + 100 * u64::try_from(attempt.saturating_sub(DELAYS_MS.len())).unwrap_or(u64::MAX);
+        if waited_ms >= self.busy_timeout_ms {
+            return false;
+        }
+        self.busy_retries.fetch_add(1, Ordering::Relaxed);
+        let delay_ms =
+            DELAYS_MS.get(attempt).copied().unwrap_or(100).min(self.busy_timeout_ms - waited_ms);
+        std::thread::sleep(Duration::from_millis(delay_ms));
+        true
+    }
+}
+
+/// Attach fresh counters to `conn` through sqlite's hooks and return them. The busy handler
+/// replaces `PRAGMA busy_timeout`, waiting up to `busy_timeout_ms` with sqlite's default
+/// back-off.
+///
+/// The authorizer, update, commit, and rollback hooks capture the counters directly. sqlite's
+/// profile and busy callbacks carry no context, so the connection claims one of
+/// [`COUNTER_SLOTS`] slots and installs that slot's callbacks; the slot frees itself when the
+/// connection and the returned counters are dropped. A connection opened while every slot is
+/// taken uses sqlite's built-in busy timeout and reports zero `statements_executed` and
+/// `busy_retries`.
+///
+/// # Errors
+/// Returns an error when the busy handler cannot be installed.
+pub fn install_store_counters(
+    conn: &mut Connection,
+    busy_timeout_ms: u64,
+) -> Result<Arc<StoreCounters>> {
+    let counters = Arc::new(StoreCounters { busy_timeout_ms, ..StoreCounters::default() });
+    let hooked = Arc::clone(&counters);
+    conn.authorizer(Some(move |ctx: AuthContext<'_>| {
+        if let (AuthAction::Transaction { operation }, None) = (&ctx.action, ctx.accessor) {
+            hooked.transaction_boundary(*operation);
+        }
+        Authorization::Allow
+    }));
+    let hooked = Arc::clone(&counters);
+    conn.update_hook(Some(move |_: Action, _: &str, _: &str, _: i64| {
+        hooked.rows_written.fetch_add(1, Ordering::Relaxed);
+    }));
+    let hooked = Arc::clone(&counters);
+    conn.commit_hook(Some(move || {
+        hooked.transactions_committed.fetch_add(1, Ordering::Relaxed);
+        false
+    }));
+    let hooked = Arc::clone(&counters);
+    conn.rollback_hook(Some(move || {
+        hooked.transactions_rolled_back.fetch_add(1, Ordering::Relaxed);
+    }));
+    if let Some(slot) = claim_counter_slot(&counters) {
+        conn.profile(Some(STATEMENT_CALLBACKS[slot]));
+        conn.busy_handler(Some(BUSY_CALLBACKS[slot]))
+            .context("failed to install sqlite busy handler")?;
+    } else {
+        conn.busy_timeout(Duration::from_millis(busy_timeout_ms))
+            .context("failed to set sqlite busy timeout")?;
+    }
+    Ok(counters)
+}
+
+/// Connections that can count statements and busy retries at the same time.
+pub const COUNTER_SLOTS: usize = 64;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY_COUNTER_SLOT: Mutex<Weak<StoreCounters>> = Mutex::new(Weak::new());
+
+static COUNTER_SLOT_OWNERS: [Mutex<Weak<StoreCounters>>; COUNTER_SLOTS] =
+    [EMPTY_COUNTER_SLOT; COUNTER_SLOTS];
+
+/// Give `counters` the first slot whose previous owner is gone. The hooks installed by
+/// [`install_store_counters`] keep the owner alive for as long as its connection is open.
+fn claim_counter_slot(counters: &Arc<StoreCounters>) -> Option<usize> {
+    COUNTER_SLOT_OWNERS.iter().position(|owner| {
+        let Ok(mut owner) = owner.lock() else {
+            return false;
+        };
+        if owner.strong_count() > 0 {
+            return false;
+        }
+        *owner = Arc::downgrade(counters);
+        true
+    })
+}
+
+fn counter_slot_owner<const SLOT: usize>() -> Option<Arc<StoreCounters>> {
+    COUNTER_SLOT_OWNERS[SLOT].lock().ok().and_then(|owner| owner.upgrade())
+}
+
+fn count_statement_execution<const SLOT: usize>(_sql: &str, _elapsed: Duration) {
+    if let Some(counters) = counter_slot_owner::<SLOT>() {
+        counters.statements_executed.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn count_busy_retry<const SLOT: usize>(attempt: i32) -> bool {
+    counter_slot_owner::<SLOT>().is_some_and(|counters| counters.wait_busy(attempt))
+}
+
+macro_rules! counter_slot_callbacks {
+    ($($slot:literal)*) => {
+        const STATEMENT_CALLBACKS: [fn(&str, Duration); COUNTER_SLOTS] =
+            [$(count_statement_execution::<$slot>),*];
+        const BUSY_CALLBACKS: [fn(i32) -> bool; COUNTER_SLOTS] = [$(count_busy_retry::<$slot>),*];
+    };
+}
+
+counter_slot_callbacks!(
+    0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+    32 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48 49 50 51 52 53 54 55 56 57 58 59 60 61 62 63
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test IDs: TDB-011
+    #[test]
+    fn pragma_modes_parse_case_insensitively_and_round_trip() -> Result<()> {
+        for mode in [
+            JournalMode::Delete,
+            JournalMode::Truncate,
+            JournalMode::Persist,
+            JournalMode::Memory,
+            JournalMode::Wal,
+            JournalMode::Off,
+        ] {
+            assert_eq!(mode.as_str().to_ascii_uppercase().parse::<JournalMode>()?, mode);
+        }
+        for mode in [
+            SynchronousMode::Off,
+            SynchronousMode::Normal,
+            SynchronousMode::Full,
+            SynchronousMode::Extra,
+        ] {
+            assert_eq!(mode.as_str().parse::<SynchronousMode>()?, mode);
+        }
+        assert!("fast".parse::<SynchronousMode>().is_err());
+        assert!("rollback".parse::<JournalMode>().is_err());
+        Ok(())
+    }
+
+    // Test IDs: TDB-012
+    #[test]
+    fn apply_sets_only_the_configured_pragmas() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        let pragma = |name: &str| -> Result<i64> {
+            Ok(conn.query_row(&format!("PRAGMA {name}"), [], |row| row.get(0))?)
+        };
+        let default_cache_size = pragma("cache_size")?;
+
+        SqlitePragmas { synchronous: Some(SynchronousMode::Off), ..SqlitePragmas::default() }
+            .apply(&conn)?;
+        assert_eq!(pragma("foreign_keys")?, 1);
+        assert_eq!(pragma("synchronous")?, 0);
+        assert_eq!(pragma("cache_size")?, default_cache_size);
+
+        SqlitePragmas { cache_size: Some(-2048), ..SqlitePragmas::default() }.apply(&conn)?;
+        assert_eq!(pragma("cache_size")?, -2048);
+        Ok(())
+    }
+
+    // Test IDs: TDB-013
+    #[test]
+    fn store_counters_stay_with_their_connection_on_a_shared_thread() -> Result<()> {
+        let db_path = std::env::temp_dir().join(format!(
+            "memorykernel-counters-{}-{:?}.sqlite3",
+            std::process::id(),
+            Instant::now()
+        ));
+        let mut writer = Connection::open(&db_path)?;
+        let mut waiter = Connection::open(&db_path)?;
+        let holder_stats = install_store_counters(&mut writer, 1_000)?;
+        let blocked_stats = install_store_counters(&mut waiter, 30)?;
+        writer.execute_batch("CREATE TABLE items(id INTEGER PRIMARY KEY)")?;
+
+        // Prepared on the writer, then a statement compiled on the waiter, then run on the
+        // writer: every run still belongs to the writer.
+        let mut insert = writer.prepare("INSERT INTO items(id) VALUES (?1)")?;
+        waiter.execute_batch("SELECT 1")?;
+        holder_stats.reset();
+        blocked_stats.reset();
+        for id in 1..=3 {
+            insert.execute([id])?;
+        }
+        drop(insert);
+        assert_eq!(holder_stats.snapshot().statements_executed, 3);
+        assert_eq!(holder_stats.snapshot().rows_written, 3);
+        assert_eq!(blocked_stats.snapshot(), StoreStats::default());
+
+        writer.execute_batch("BEGIN IMMEDIATE; INSERT INTO items(id) VALUES (4);")?;
+        assert!(waiter.execute("INSERT INTO items(id) VALUES (5)", []).is_err());
+        writer.execute_batch("COMMIT")?;
+        assert!(blocked_stats.snapshot().busy_retries > 0);
+        assert_eq!(holder_stats.snapshot().busy_retries, 0);
+        assert_eq!(holder_stats.snapshot().transactions_committed, 4);
+        assert_eq!(blocked_stats.busy_timeout_ms(), 30);
+
+        drop(writer);
+        drop(waiter);
+        let _ = std::fs::remove_file(&db_path);
+        Ok(())
+    }
+
+    // Test IDs: TDB-014
+    #[test]
+    fn counter_slots_are_reused_after_their_connection_closes() -> Result<()> {
+        let opened = (0..=COUNTER_SLOTS)
+            .map(|_| {
+                let mut conn = Connection::open_in_memory()?;
+                install_store_counters(&mut conn, 0).map(|counters| (conn, counters))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        drop(opened);
+
+        let mut conn = Connection::open_in_memory()?;
+        let counters = install_store_counters(&mut conn, 0)?;
+        conn.execute_batch("CREATE TABLE items(id INTEGER PRIMARY KEY); SELECT 1;")?;
+        assert!(counters.snapshot().statements_executed > 0);
+        Ok(())
+    }
+}
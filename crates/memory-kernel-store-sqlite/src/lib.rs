@@ -3,6 +3,7 @@ use std::fs::{self, File};
 use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::path::Path;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use anyhow::{anyhow, Context, Result};
 use memory_kernel_core::{
@@ -16,7 +17,12 @@ use sha2::{Digest, Sha256};
 use time::OffsetDateTime;
 use ulid::Ulid;
 
+use memory_kernel_sqlite_support::{install_store_counters, StoreCounters};
+pub use memory_kernel_sqlite_support::{JournalMode, SqlitePragmas, StoreStats, SynchronousMode};
+
 const LATEST_SCHEMA_VERSION: i64 = 2;
+/// Version of this crate as built, for hosts recording what produced their data.
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 const CREATE_SCHEMA_MIGRATIONS_SQL: &str = r"
 CREATE TABLE IF NOT EXISTS schema_migrations (
@@ -192,8 +198,58 @@ CREATE INDEX IF NOT EXISTS idx_memory_links_from ON memory_links(from_memory_ver
 CREATE INDEX IF NOT EXISTS idx_memory_links_to ON memory_links(to_memory_version_id);
 ";
 
+const ADMIN_AUDIT_SQL: &str = r"
+CREATE TABLE IF NOT EXISTS admin_audit (
+  audit_seq INTEGER PRIMARY KEY AUTOINCREMENT,
+  audit_id TEXT NOT NULL UNIQUE,
+  surface TEXT NOT NULL CHECK (surface IN ('cli', 'api')),
+  command TEXT NOT NULL,
+  args_hash TEXT NOT NULL,
+  writer TEXT,
+  recorded_at TEXT NOT NULL,
+  outcome TEXT NOT NULL CHECK (outcome IN ('ok', 'error')),
+  error TEXT
+);
+
+CREATE TRIGGER IF NOT EXISTS trg_admin_audit_no_update
+BEFORE UPDATE ON admin_audit
+BEGIN
+  SELECT RAISE(FAIL, 'admin_audit is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_admin_audit_no_delete
+BEFORE DELETE ON admin_audit
+BEGIN
+  SELECT RAISE(FAIL, 'admin_audit is append-only');
+END;
+";
+
+const API_TOKENS_SQL: &str = r"
+CREATE TABLE IF NOT EXISTS api_tokens (
+  token_id TEXT PRIMARY KEY,
+  token_hash TEXT NOT NULL UNIQUE,
+  role TEXT NOT NULL CHECK (role IN ('reader', 'writer', 'operator', 'admin')),
+  label TEXT NOT NULL,
+  created_at TEXT NOT NULL,
+  revoked_at TEXT
+);
+";
+
+const MEMORY_TAGS_SQL: &str = r"
+CREATE TABLE IF NOT EXISTS memory_tags (
+  memory_version_id TEXT NOT NULL REFERENCES memory_records(memory_version_id),
+  tag TEXT NOT NULL,
+  PRIMARY KEY (memory_version_id, tag)
+);
+
+CREATE INDEX IF NOT EXISTS idx_memory_tags_tag ON memory_tags(tag);
+";
+
+const API_TOKEN_PREFIX: &str = "mkt_";
+
 pub struct SqliteStore {
     conn: Connection,
+    counters: Arc<StoreCounters>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -234,6 +290,124 @@ pub struct ForeignKeyViolation {
     pub fk_index: i64,
 }
 
+/// One mutating CLI/API invocation. Kept apart from domain events so forensics can tell
+/// "who ran what" from "what happened to memories".
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AdminAuditEntry {
+    pub audit_seq: Option<i64>,
+    pub audit_id: String,
+    pub surface: String,
+    pub command: String,
+    pub args_hash: String,
+    pub writer: Option<String>,
+    pub recorded_at: String,
+    pub outcome: String,
+    pub error: Option<String>,
+}
+
+impl AdminAuditEntry {
+    /// Build an entry stamped with a fresh id and the current UTC time. `args_hash` is the
+    /// SHA-256 of `args` joined by NUL bytes, so raw arguments are never persisted; `error`
+    /// is `None` for a successful invocation.
+    ///
+    /// # Errors
+    /// Returns an error when the timestamp cannot be formatted.
+    pub fn new(
+        surface: &str,
+        command: &str,
+        args: &[String],
+        writer: Option<&str>,
+        error: Option<String>,
+    ) -> Result<Self> {
+        let mut hasher = Sha256::new();
+        for (index, arg) in args.iter().enumerate() {
+            if index > 0 {
+                hasher.update([0_u8]);
+            }
+            hasher.update(arg.as_bytes());
+        }
+
+        Ok(Self {
+            audit_seq: None,
+            audit_id: Ulid::new().to_string(),
+            surface: surface.to_string(),
+            command: command.to_string(),
+            args_hash: format!("{:x}", hasher.finalize()),
+            writer: writer.map(str::to_string),
+            recorded_at: now_rfc3339()?,
+            outcome: if error.is_none() { "ok" } else { "error" }.to_string(),
+            error,
+        })
+    }
+}
+
+/// Access level carried by an API token. Roles are ordered: each one grants everything the
+/// roles before it grant.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiRole {
+    Reader,
+    Writer,
+    Operator,
+    Admin,
+}
+
+impl ApiRole {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Reader => "reader",
+            Self::Writer => "writer",
+            Self::Operator => "operator",
+            Self::Admin => "admin",
+        }
+    }
+
+    /// Whether a token holding this role may call an endpoint that requires `required`.
+    #[must_use]
+    pub fn grants(self, required: Self) -> bool {
+        self >= required
+    }
+}
+
+impl FromStr for ApiRole {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "reader" => Ok(Self::Reader),
+            "writer" => Ok(Self::Writer),
+            "operator" => Ok(Self::Operator),
+            "admin" => Ok(Self::Admin),
+            other => Err(anyhow!("unknown api role: {other}")),
+        }
+    }
+}
+
+/// Stored metadata for an API token. The secret itself is only kept as a SHA-256 hash.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ApiToken {
+    pub token_id: String,
+    pub role: ApiRole,
+    pub label: String,
+    pub created_at: String,
+    pub revoked_at: Option<String>,
+}
+
+/// A freshly created token together with its secret, which is shown exactly once.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct IssuedApiToken {
+    #[serde(flatten)]
+    pub token: ApiToken,
+    pub secret: String,
+}
+
+fn hash_api_token_secret(secret: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(secret.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct IntegrityReport {
     pub quick_check_ok: bool,
@@ -248,17 +422,210 @@ impl SqliteStore {
     /// # Errors
     /// Returns an error when the database cannot be opened or pragmas cannot be applied.
     pub fn open(path: &Path) -> Result<Self> {
-        let conn = Connection::open(path)
+        Self::open_with_pragmas(path, &SqlitePragmas::default())
+    }
+
+    /// Open a store with deployment-specific pragmas instead of the defaults.
+    ///
+    /// # Errors
+    /// Returns an error when the database cannot be opened or pragmas cannot be applied.
+    pub fn open_with_pragmas(path: &Path, pragmas: &SqlitePragmas) -> Result<Self> {
+        let mut conn = Connection::open(path)
             .with_context(|| format!("failed to open sqlite database at {}", path.display()))?;
 
-        conn.execute_batch(
-            "PRAGMA journal_mode = WAL;
-             PRAGMA foreign_keys = ON;
-             PRAGMA busy_timeout = 5000;",
-        )
-        .context("failed to configure sqlite pragmas")?;
+        pragmas.apply(&conn)?;
+        let counters = install_store_counters(&mut conn, pragmas.busy_timeout_ms)?;
+
+        Ok(Self { conn, counters })
+    }
+
+    /// Instrumentation counters of the store's connection since it was opened or last reset.
+    #[must_use]
+    pub fn stats(&self) -> StoreStats {
+        self.counters.snapshot()
+    }
+
+    /// Zero the counters reported by [`SqliteStore::stats`].
+    pub fn reset_stats(&self) {
+        self.counters.reset();
+    }
+
+    /// Append one entry to the `admin_audit` log, creating the table on first use.
+    ///
+    /// # Errors
+    /// Returns an error when the audit table cannot be created or the row cannot be inserted.
+    pub fn record_admin_audit(&self, entry: &AdminAuditEntry) -> Result<()> {
+        self.conn.execute_batch(ADMIN_AUDIT_SQL).context("failed to ensure admin_audit table")?;
+        self.conn
+            .execute(
+                "INSERT INTO admin_audit(
+                    audit_id, surface, command, args_hash, writer, recorded_at, outcome, error
+                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    entry.audit_id,
+                    entry.surface,
+                    entry.command,
+                    entry.args_hash,
+                    entry.writer,
+                    entry.recorded_at,
+                    entry.outcome,
+                    entry.error,
+                ],
+            )
+            .context("failed to record admin audit entry")?;
+        Ok(())
+    }
+
+    /// Create an API token with `role`, returning its one-time secret.
+    ///
+    /// # Errors
+    /// Returns an error when `label` is empty, randomness is unavailable, or the row cannot be
+    /// inserted.
+    pub fn create_api_token(&self, role: ApiRole, label: &str) -> Result<IssuedApiToken> {
+        if label.trim().is_empty() {
+            return Err(anyhow!("api token label cannot be empty"));
+        }
+        self.conn.execute_batch(API_TOKENS_SQL).context("failed to ensure api_tokens table")?;
+
+        let mut bytes = [0_u8; 32];
+        getrandom::getrandom(&mut bytes)
+            .map_err(|err| anyhow!("failed to generate api token secret: {err}"))?;
+        let secret = format!("{API_TOKEN_PREFIX}{}", hex::encode(bytes));
+        let token = ApiToken {
+            token_id: Ulid::new().to_string(),
+            role,
+            label: label.to_string(),
+            created_at: now_rfc3339()?,
+            revoked_at: None,
+        };
+        self.conn
+            .execute(
+                "INSERT INTO api_tokens(token_id, token_hash, role, label, created_at, revoked_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, NULL)",
+                params![
+                    token.token_id,
+                    hash_api_token_secret(&secret),
+                    role.as_str(),
+                    token.label,
+                    token.created_at,
+                ],
+            )
+            .context("failed to insert api token")?;
+        Ok(IssuedApiToken { token, secret })
+    }
+
+    /// Revoke an API token. Returns `false` when the token does not exist or is already revoked.
+    ///
+    /// # Errors
+    /// Returns an error when the token table cannot be updated.
+    pub fn revoke_api_token(&self, token_id: &str) -> Result<bool> {
+        self.conn.execute_batch(API_TOKENS_SQL).context("failed to ensure api_tokens table")?;
+        let updated = self
+            .conn
+            .execute(
+                "UPDATE api_tokens SET revoked_at = ?2 WHERE token_id = ?1 AND revoked_at IS NULL",
+                params![token_id, now_rfc3339()?],
+            )
+            .context("failed to revoke api token")?;
+        Ok(updated > 0)
+    }
+
+    /// List all API tokens (active and revoked) in creation order.
+    ///
+    /// # Errors
+    /// Returns an error when the token table cannot be queried.
+    pub fn list_api_tokens(&self) -> Result<Vec<ApiToken>> {
+        self.conn.execute_batch(API_TOKENS_SQL).context("failed to ensure api_tokens table")?;
+        let mut stmt = self.conn.prepare(
+            "SELECT token_id, role, label, created_at, revoked_at
+             FROM api_tokens
+             ORDER BY created_at ASC, token_id ASC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })?;
+
+        let mut tokens = Vec::new();
+        for row in rows {
+            let (token_id, role, label, created_at, revoked_at) =
+                row.context("failed to decode api token row")?;
+            tokens.push(ApiToken { token_id, role: role.parse()?, label, created_at, revoked_at });
+        }
+        Ok(tokens)
+    }
+
+    /// Resolve a presented secret to its active token, or `None` when it is unknown or revoked.
+    ///
+    /// # Errors
+    /// Returns an error when the token table cannot be queried.
+    pub fn authenticate_api_token(&self, secret: &str) -> Result<Option<ApiToken>> {
+        if !secret.starts_with(API_TOKEN_PREFIX) {
+            return Ok(None);
+        }
+        self.conn.execute_batch(API_TOKENS_SQL).context("failed to ensure api_tokens table")?;
+        let row = self
+            .conn
+            .query_row(
+                "SELECT token_id, role, label, created_at
+                 FROM api_tokens
+                 WHERE token_hash = ?1 AND revoked_at IS NULL",
+                params![hash_api_token_secret(secret)],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, String>(3)?,
+                    ))
+                },
+            )
+            .optional()
+            .context("failed to look up api token")?;
+
+        row.map(|(token_id, role, label, created_at)| {
+            Ok(ApiToken { token_id, role: role.parse()?, label, created_at, revoked_at: None })
+        })
+        .transpose()
+    }
+
+    /// List `admin_audit` entries, most recent first.
+    ///
+    /// # Errors
+    /// Returns an error when the audit table cannot be queried.
+    pub fn list_admin_audit(&self, limit: Option<usize>) -> Result<Vec<AdminAuditEntry>> {
+        self.conn.execute_batch(ADMIN_AUDIT_SQL).context("failed to ensure admin_audit table")?;
+        let limit = limit.map_or(-1, |value| i64::try_from(value).unwrap_or(i64::MAX));
+        let mut stmt = self.conn.prepare(
+            "SELECT audit_seq, audit_id, surface, command, args_hash, writer, recorded_at, outcome, error
+             FROM admin_audit
+             ORDER BY audit_seq DESC
+             LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(params![limit], |row| {
+            Ok(AdminAuditEntry {
+                audit_seq: row.get(0)?,
+                audit_id: row.get(1)?,
+                surface: row.get(2)?,
+                command: row.get(3)?,
+                args_hash: row.get(4)?,
+                writer: row.get(5)?,
+                recorded_at: row.get(6)?,
+                outcome: row.get(7)?,
+                error: row.get(8)?,
+            })
+        })?;
 
-        Ok(Self { conn })
+        let mut entries = Vec::new();
+        for row in rows {
+            entries.push(row.context("failed to decode admin audit row")?);
+        }
+        Ok(entries)
     }
 
     /// Report current and target schema versions plus pending migrations.
@@ -310,6 +677,7 @@ impl SqliteStore {
             ));
         }
 
+        self.conn.execute_batch(MEMORY_TAGS_SQL).context("failed to ensure memory_tags table")?;
         Ok(())
     }
 
@@ -454,6 +822,9 @@ impl SqliteStore {
         record.validate().map_err(|err| anyhow!("record validation failed: {err}"))?;
 
         let tx = self.conn.transaction().context("failed to start transaction")?;
+        if !record.tags.is_empty() {
+            tx.execute_batch(MEMORY_TAGS_SQL).context("failed to ensure memory_tags table")?;
+        }
 
         tx.execute(
             "INSERT INTO memory_records(
@@ -488,6 +859,13 @@ impl SqliteStore {
         Self::insert_payload(&tx, record)?;
         Self::insert_links(&tx, record, LinkType::Supersedes, &record.supersedes)?;
         Self::insert_links(&tx, record, LinkType::Contradicts, &record.contradicts)?;
+        for tag in &record.tags {
+            tx.execute(
+                "INSERT INTO memory_tags(memory_version_id, tag) VALUES (?1, ?2)",
+                params![record.memory_version_id.to_string(), tag],
+            )
+            .context("failed to insert memory tag")?;
+        }
 
         tx.commit().context("failed to commit write transaction")?;
         Ok(())
@@ -509,6 +887,7 @@ impl SqliteStore {
 
         let mut rows = stmt.query([])?;
         let mut records = Vec::new();
+        let has_tags = table_exists(&self.conn, "memory_tags")?;
 
         while let Some(row) = rows.next()? {
             let memory_version_id_raw: String = row.get(0)?;
@@ -522,6 +901,7 @@ impl SqliteStore {
             let payload = self.load_payload(memory_version_id, record_type)?;
             let supersedes = self.load_links(memory_version_id, LinkType::Supersedes)?;
             let contradicts = self.load_links(memory_version_id, LinkType::Contradicts)?;
+            let tags = if has_tags { self.load_tags(memory_version_id)? } else { Vec::new() };
 
             let truth_status_raw: String = row.get(6)?;
             let authority_raw: String = row.get(7)?;
@@ -549,6 +929,7 @@ impl SqliteStore {
                 },
                 supersedes,
                 contradicts,
+                tags,
             });
         }
 
@@ -987,6 +1368,19 @@ impl SqliteStore {
         Ok(ids)
     }
 
+    fn load_tags(&self, memory_version_id: MemoryVersionId) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT tag FROM memory_tags WHERE memory_version_id = ?1 ORDER BY tag ASC")?;
+        let rows =
+            stmt.query_map(params![memory_version_id.to_string()], |row| row.get::<_, String>(0))?;
+        let mut tags = Vec::new();
+        for row in rows {
+            tags.push(row.context("failed to decode memory tag row")?);
+        }
+        Ok(tags)
+    }
+
     fn list_context_packages(&self) -> Result<Vec<ContextPackage>> {
         let mut stmt = self.conn.prepare(
             "SELECT package_json FROM context_packages ORDER BY generated_at DESC, context_package_id ASC",
@@ -1474,6 +1868,7 @@ mod tests {
             },
             supersedes: vec![],
             contradicts: vec![],
+            tags: vec![],
             payload: MemoryPayload::Constraint(ConstraintPayload {
                 scope: ConstraintScope {
                     actor: "user".to_string(),
@@ -1571,6 +1966,71 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn store_stats_count_writes_rollbacks_and_reset() -> Result<()> {
+        let mut store = SqliteStore::open(Path::new(":memory:"))?;
+        store.migrate()?;
+        store.reset_stats();
+
+        let record = mk_store_constraint_record(
+            MemoryId::new(),
+            1,
+            TruthStatus::Asserted,
+            Some(0.8),
+            ConstraintEffect::Deny,
+        );
+        store.write_record(&record)?;
+        let stats = store.stats();
+        assert!(stats.statements_executed >= 3, "{stats:?}");
+        assert!(stats.rows_written >= 2, "{stats:?}");
+        assert_eq!(stats.transactions_committed, 1);
+        assert_eq!(stats.transactions_rolled_back, 0);
+
+        assert!(store.write_record(&record).is_err());
+        let after_duplicate = store.stats();
+        assert_eq!(after_duplicate.transactions_committed, 1);
+        assert_eq!(after_duplicate.transactions_rolled_back, 1);
+        assert!(
+            after_duplicate.transaction_time_max_us <= after_duplicate.transaction_time_total_us
+        );
+
+        store.reset_stats();
+        assert_eq!(store.stats(), StoreStats::default());
+        Ok(())
+    }
+
+    #[test]
+    fn open_with_pragmas_applies_deployment_settings() -> Result<()> {
+        let db_path =
+            std::env::temp_dir().join(format!("memorykernel-pragmas-{}.sqlite3", Ulid::new()));
+        let pragmas = SqlitePragmas {
+            journal_mode: "truncate".parse()?,
+            synchronous: Some("EXTRA".parse()?),
+            cache_size: Some(500),
+            mmap_size: Some(0),
+            busy_timeout_ms: 250,
+        };
+        let mut store = SqliteStore::open_with_pragmas(&db_path, &pragmas)?;
+        store.migrate()?;
+
+        let journal_mode: String =
+            store.conn.query_row("PRAGMA journal_mode", [], |row| row.get(0))?;
+        assert_eq!(journal_mode, "truncate");
+        let pragma = |name: &str| -> Result<i64> {
+            Ok(store.conn.query_row(&format!("PRAGMA {name}"), [], |row| row.get(0))?)
+        };
+        assert_eq!(pragma("synchronous")?, 3);
+        assert_eq!(pragma("cache_size")?, 500);
+        assert_eq!(pragma("mmap_size")?, 0);
+        assert_eq!(pragma("foreign_keys")?, 1);
+        assert_eq!(store.counters.busy_timeout_ms(), 250);
+        assert!("fast".parse::<SynchronousMode>().is_err());
+
+        drop(store);
+        let _ = fs::remove_file(&db_path);
+        Ok(())
+    }
+
     // Test IDs: TID-002
     #[test]
     fn duplicate_memory_id_version_is_rejected() -> Result<()> {
@@ -1624,6 +2084,7 @@ mod tests {
             },
             supersedes: vec![],
             contradicts: vec![],
+            tags: vec![],
             payload: MemoryPayload::Constraint(ConstraintPayload {
                 scope: ConstraintScope {
                     actor: "user".to_string(),
@@ -1657,6 +2118,42 @@ mod tests {
         Ok(())
     }
 
+    // Test IDs: TDB-010
+    #[test]
+    fn record_tags_round_trip_in_sorted_order() -> Result<()> {
+        let mut store = SqliteStore::open(Path::new(":memory:"))?;
+        store.migrate()?;
+
+        let mut tagged = mk_store_constraint_record(
+            MemoryId::new(),
+            1,
+            TruthStatus::Asserted,
+            Some(0.8),
+            ConstraintEffect::Deny,
+        );
+        tagged.tags = vec!["deploy".to_string(), "billing".to_string()];
+        let untagged = mk_store_constraint_record(
+            MemoryId::new(),
+            1,
+            TruthStatus::Asserted,
+            Some(0.8),
+            ConstraintEffect::Allow,
+        );
+        store.write_record(&tagged)?;
+        store.write_record(&untagged)?;
+
+        let records = store.list_records()?;
+        let tags_for = |id: MemoryVersionId| {
+            records.iter().find(|record| record.memory_version_id == id).map(|r| r.tags.clone())
+        };
+        assert_eq!(
+            tags_for(tagged.memory_version_id),
+            Some(vec!["billing".to_string(), "deploy".to_string()])
+        );
+        assert_eq!(tags_for(untagged.memory_version_id), Some(Vec::new()));
+        Ok(())
+    }
+
     // Test IDs: TID-003
     #[test]
     fn supersedes_links_round_trip_by_memory_version_id() -> Result<()> {
@@ -1682,6 +2179,7 @@ mod tests {
             },
             supersedes: vec![],
             contradicts: vec![],
+            tags: vec![],
             payload: MemoryPayload::Constraint(ConstraintPayload {
                 scope: ConstraintScope {
                     actor: "user".to_string(),
@@ -1711,6 +2209,7 @@ mod tests {
             },
             supersedes: vec![old.memory_version_id],
             contradicts: vec![],
+            tags: vec![],
             payload: MemoryPayload::Constraint(ConstraintPayload {
                 scope: ConstraintScope {
                     actor: "user".to_string(),
@@ -1851,6 +2350,7 @@ mod tests {
             },
             supersedes: vec![],
             contradicts: vec![],
+            tags: vec![],
             payload: MemoryPayload::Constraint(ConstraintPayload {
                 scope: ConstraintScope {
                     actor: "user".to_string(),
@@ -1963,6 +2463,7 @@ mod tests {
             },
             supersedes: vec![],
             contradicts: vec![],
+            tags: vec![],
             payload: MemoryPayload::Constraint(ConstraintPayload {
                 scope: ConstraintScope {
                     actor: "user".to_string(),
@@ -2047,6 +2548,7 @@ mod tests {
                         },
                         supersedes: vec![],
                         contradicts: vec![],
+                        tags: vec![],
                         payload: MemoryPayload::Constraint(ConstraintPayload {
                             scope: ConstraintScope {
                                 actor: "user".to_string(),
@@ -2103,4 +2605,64 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn admin_audit_records_entries_and_rejects_mutation() -> Result<()> {
+        let store = SqliteStore::open(Path::new(":memory:"))?;
+        assert!(store.list_admin_audit(None)?.is_empty());
+
+        let args = vec!["memory".to_string(), "link".to_string()];
+        store.record_admin_audit(&AdminAuditEntry::new(
+            "cli",
+            "memory link",
+            &args,
+            Some("tester"),
+            None,
+        )?)?;
+        store.record_admin_audit(&AdminAuditEntry::new(
+            "api",
+            "db migrate",
+            &[],
+            None,
+            Some("boom".to_string()),
+        )?)?;
+
+        let entries = store.list_admin_audit(None)?;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].command, "db migrate");
+        assert_eq!(entries[0].outcome, "error");
+        assert_eq!(entries[1].writer.as_deref(), Some("tester"));
+        assert_eq!(entries[1].outcome, "ok");
+        assert_eq!(store.list_admin_audit(Some(1))?.len(), 1);
+
+        assert!(store.conn.execute("UPDATE admin_audit SET outcome = 'ok'", []).is_err());
+        assert!(store.conn.execute("DELETE FROM admin_audit", []).is_err());
+        Ok(())
+    }
+
+    // Test IDs: TSEC-003
+    #[test]
+    fn api_tokens_authenticate_until_revoked() -> Result<()> {
+        let store = SqliteStore::open(Path::new(":memory:"))?;
+        assert!(store.create_api_token(ApiRole::Reader, " ").is_err());
+
+        let issued = store.create_api_token(ApiRole::Writer, "ci")?;
+        assert!(issued.secret.starts_with(API_TOKEN_PREFIX));
+        let Some(token) = store.authenticate_api_token(&issued.secret)? else {
+            return Err(anyhow!("fresh token should authenticate"));
+        };
+        assert_eq!(token.role, ApiRole::Writer);
+        assert!(token.role.grants(ApiRole::Reader));
+        assert!(!token.role.grants(ApiRole::Operator));
+        assert!(store.authenticate_api_token("mkt_not-a-real-token")?.is_none());
+
+        assert!(store.revoke_api_token(&issued.token.token_id)?);
+        assert!(!store.revoke_api_token(&issued.token.token_id)?);
+        assert!(store.authenticate_api_token(&issued.secret)?.is_none());
+
+        let tokens = store.list_api_tokens()?;
+        assert_eq!(tokens.len(), 1);
+        assert!(tokens[0].revoked_at.is_some());
+        Ok(())
+    }
 }
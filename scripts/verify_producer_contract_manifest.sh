@@ -128,6 +128,11 @@ expected_codes = [
     "query_failed",
     "context_lookup_failed",
     "internal_error",
+    "unauthorized",
+    "forbidden",
+    "rate_limited",
+    "run_not_found",
+    "gate_not_found",
 ]
 if data["error_code_enum"] != expected_codes:
     raise SystemExit("error_code_enum does not match expected service.v3 canonical ordering")
@@ -173,7 +178,12 @@ for code in \
   migration_failed \
   query_failed \
   context_lookup_failed \
-  internal_error; do
+  internal_error \
+  unauthorized \
+  forbidden \
+  rate_limited \
+  run_not_found \
+  gate_not_found; do
   require_grep "\\- ${code}" "$openapi"
   require_grep "\"${code}\"" "$manifest"
 done
@@ -104,7 +104,12 @@ for code in \
   migration_failed \
   query_failed \
   context_lookup_failed \
-  internal_error; do
+  internal_error \
+  unauthorized \
+  forbidden \
+  rate_limited \
+  run_not_found \
+  gate_not_found; do
   require_grep "\\- ${code}" "$openapi"
 done
 